@@ -16,9 +16,11 @@ use std::{error::Error, fs::File};
 
 use arg::{parse_args, Args};
 use flucoma_rs::{
+    analyzation::MultiChannel,
+    buffer::interleave,
     data::Matrix,
-    fourier::{ComplexSpectrum, Istft, Stft, WindowType},
-    transformation::{AudioTransport, NMFFilter, NMFMorph},
+    fourier::{ComplexSpectrum, Istft, OverlapAdd, Stft, WindowType},
+    transformation::{BufAudioTransport, BufAudioTransportConfig, NMFFilter, NMFMorph},
 };
 
 // -------------------------------------------------------------------------------------------------
@@ -97,14 +99,6 @@ fn main() -> Result<(), Box<dyn Error>> {
     let len1 = samples1[0].len();
     let len2 = samples2[0].len();
 
-    // Prepend WINDOW_SIZE silence so OLA has full overlap before real audio starts
-    for v in &mut samples1 {
-        v.splice(0..0, vec![0.0; WINDOW_SIZE]);
-    }
-    for v in &mut samples2 {
-        v.splice(0..0, vec![0.0; WINDOW_SIZE]);
-    }
-
     println!(
         "  File 1: {} frames ({:.2}s)",
         len1,
@@ -116,45 +110,54 @@ fn main() -> Result<(), Box<dyn Error>> {
         len2 as f64 / sample_rate as f64
     );
 
-    let output_frames = samples1[0].len().min(samples2[0].len());
-    let total_hops = (output_frames + WINDOW_SIZE).div_ceil(HOP_SIZE);
-
-    // Use FFT_SIZE for tail room: morph mode ISTFT outputs FFT_SIZE samples per frame.
-    let acc_len = total_hops * HOP_SIZE + FFT_SIZE;
-    let mut audio_acc = vec![vec![0.0f64; acc_len]; channel_count];
-    let mut norm_acc = vec![vec![0.0f64; acc_len]; channel_count];
-
     if mode == "transport" {
-        run_transport(
-            &samples1,
-            &samples2,
-            channel_count,
-            total_hops,
-            &mut audio_acc,
-            &mut norm_acc,
-        )?;
-    } else {
-        run_nmf_morph(
-            &samples1,
-            &samples2,
-            channel_count,
-            total_hops,
-            &mut audio_acc,
-            &mut norm_acc,
+        let transport = BufAudioTransport::new(BufAudioTransportConfig {
+            window_size: WINDOW_SIZE,
+            hop_size: HOP_SIZE,
+            fft_size: FFT_SIZE,
+        })?;
+        let channels = samples1
+            .iter()
+            .zip(&samples2)
+            .map(|(source, target)| transport.process(source, target, &[0.0, 1.0]))
+            .collect::<Result<Vec<_>, _>>()?;
+        let interleaved_out: Vec<f32> = interleave(&channels)?
+            .into_iter()
+            .map(|sample| sample.clamp(-1.0, 1.0) as f32)
+            .collect();
+
+        wav_io::write_to_file(
+            &mut File::create(&args.output)?,
+            &wav_io::new_header(sample_rate, 16, false, channel_count == 1),
+            &interleaved_out,
         )?;
+
+        println!(
+            "Done. Wrote {} frames to `{}`.",
+            channels[0].len(),
+            args.output
+        );
+        return Ok(());
     }
 
-    // Normalize and interleave into interleaved f32 output, skipping the WINDOW_SIZE silence prefix
+    // Prepend WINDOW_SIZE silence so OLA has full overlap before real audio starts
+    for v in &mut samples1 {
+        v.splice(0..0, vec![0.0; WINDOW_SIZE]);
+    }
+    for v in &mut samples2 {
+        v.splice(0..0, vec![0.0; WINDOW_SIZE]);
+    }
+
+    let output_frames = samples1[0].len().min(samples2[0].len());
+    let total_hops = (output_frames + WINDOW_SIZE).div_ceil(HOP_SIZE);
+
+    let morphed = run_nmf_morph(&samples1, &samples2, channel_count, total_hops)?;
+
+    // Interleave into interleaved f32 output, skipping the WINDOW_SIZE silence prefix
     let mut interleaved_out: Vec<f32> = Vec::with_capacity(len1.min(len2) * channel_count);
     for frame in WINDOW_SIZE..output_frames {
-        for channel in 0..channel_count {
-            let norm = norm_acc[channel][frame];
-            let sample = if norm > 1e-9 {
-                (audio_acc[channel][frame] / norm).clamp(-1.0, 1.0)
-            } else {
-                0.0
-            };
-            interleaved_out.push(sample as f32);
+        for channel in morphed.iter() {
+            interleaved_out.push(channel[frame].clamp(-1.0, 1.0) as f32);
         }
     }
 
@@ -169,45 +172,6 @@ fn main() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
-// -------------------------------------------------------------------------------------------------
-// Transport mode
-
-fn run_transport(
-    samples1: &[Vec<f64>],
-    samples2: &[Vec<f64>],
-    channel_count: usize,
-    total_hops: usize,
-    audio_acc: &mut [Vec<f64>],
-    norm_acc: &mut [Vec<f64>],
-) -> Result<(), Box<dyn Error>> {
-    let mut morphers: Vec<AudioTransport> = (0..channel_count)
-        .map(|_| AudioTransport::new(WINDOW_SIZE, FFT_SIZE, HOP_SIZE))
-        .collect::<Result<_, _>>()?;
-
-    let mut frame_buffer1 = vec![0.0f64; WINDOW_SIZE];
-    let mut frame_buffer2 = vec![0.0f64; WINDOW_SIZE];
-
-    for hop in 0..total_hops {
-        let start = hop * HOP_SIZE;
-        let weight = hop as f64 / (total_hops - 1).max(1) as f64;
-
-        for channel in 0..channel_count {
-            extract_window(&samples1[channel], start, &mut frame_buffer1);
-            extract_window(&samples2[channel], start, &mut frame_buffer2);
-
-            let (audio, norm_window) =
-                morphers[channel].process_frame(&frame_buffer1, &frame_buffer2, weight);
-
-            for i in 0..WINDOW_SIZE {
-                audio_acc[channel][start + i] += audio[i];
-                norm_acc[channel][start + i] += norm_window[i];
-            }
-        }
-    }
-
-    Ok(())
-}
-
 // -------------------------------------------------------------------------------------------------
 // NMF morph mode
 
@@ -216,37 +180,31 @@ fn run_nmf_morph(
     samples2: &[Vec<f64>],
     channel_count: usize,
     total_hops: usize,
-    audio_acc: &mut [Vec<f64>],
-    norm_acc: &mut [Vec<f64>],
-) -> Result<(), Box<dyn Error>> {
+) -> Result<Vec<Vec<f64>>, Box<dyn Error>> {
     let bin_count = FFT_SIZE / 2 + 1;
 
     // Separate STFT instances per source per channel — STFT is stateful
-    let mut stfts1: Vec<Stft> = (0..channel_count)
-        .map(|_| Stft::new(WINDOW_SIZE, FFT_SIZE, HOP_SIZE, WindowType::Hann))
-        .collect::<Result<_, _>>()?;
-    let mut stfts2: Vec<Stft> = (0..channel_count)
-        .map(|_| Stft::new(WINDOW_SIZE, FFT_SIZE, HOP_SIZE, WindowType::Hann))
-        .collect::<Result<_, _>>()?;
+    let new_stft = |_| Stft::new(WINDOW_SIZE, FFT_SIZE, HOP_SIZE, WindowType::Hann);
+    let mut stfts1 = MultiChannel::new(channel_count, new_stft)?;
+    let mut stfts2 = MultiChannel::new(channel_count, new_stft)?;
 
     // Build magnitude spectrograms for both sources (n_frames × n_bins, row-major)
-    let mut frame_buffer = vec![0.0f64; WINDOW_SIZE];
+    let mut frames = vec![vec![0.0f64; WINDOW_SIZE]; channel_count];
     let mut spec1_data = vec![vec![0.0f64; total_hops * bin_count]; channel_count];
     let mut spec2_data = vec![vec![0.0f64; total_hops * bin_count]; channel_count];
 
     for hop in 0..total_hops {
         let start = hop * HOP_SIZE;
-        for channel in 0..channel_count {
-            extract_window(&samples1[channel], start, &mut frame_buffer);
-            let s = stfts1[channel].process_frame(&frame_buffer);
-            for bin in 0..bin_count {
-                spec1_data[channel][hop * bin_count + bin] = s.bins[bin].norm();
+        let bins = hop * bin_count..(hop + 1) * bin_count;
+        for (samples, stfts, spec_data) in [
+            (samples1, &mut stfts1, &mut spec1_data),
+            (samples2, &mut stfts2, &mut spec2_data),
+        ] {
+            for (frame, channel) in frames.iter_mut().zip(samples) {
+                extract_window(channel, start, frame);
             }
-
-            extract_window(&samples2[channel], start, &mut frame_buffer);
-            let s = stfts2[channel].process_frame(&frame_buffer);
-            for bin in 0..bin_count {
-                spec2_data[channel][hop * bin_count + bin] = s.bins[bin].norm();
+            for (spectrum, data) in stfts.process_planar(&frames, &()).iter().zip(spec_data) {
+                spectrum.magnitudes_into(&mut data[bins.clone()]);
             }
         }
     }
@@ -290,32 +248,26 @@ fn run_nmf_morph(
 
     // ISTFT window_size = FFT_SIZE, so output frame is FFT_SIZE samples.
     let mut audio_frame = vec![0.0f64; FFT_SIZE];
-    // Periodic Hann window — matches FluCoMa's synthesis window
-    let norm_window: Vec<f64> = (0..FFT_SIZE)
-        .map(|i| {
-            use std::f64::consts::PI;
-            let hann = 0.5 * (1.0 - (2.0 * PI * i as f64 / FFT_SIZE as f64).cos());
-            hann * hann
-        })
-        .collect();
+    let mut overlap_adds: Vec<OverlapAdd> = (0..channel_count)
+        .map(|_| OverlapAdd::new(FFT_SIZE, HOP_SIZE, WindowType::Hann))
+        .collect::<Result<_, _>>()?;
+    let mut output = vec![Vec::with_capacity(total_hops * HOP_SIZE + FFT_SIZE); channel_count];
 
     for hop in 0..total_hops {
-        let start = hop * HOP_SIZE;
         let weight = hop as f64 / (total_hops - 1).max(1) as f64;
 
         for channel in 0..channel_count {
             let raw = morphers[channel].process_frame(weight, -1);
             let spec = ComplexSpectrum { bins: raw.to_vec() };
             istfts[channel].process_frame(&spec, &mut audio_frame);
-
-            for i in 0..FFT_SIZE {
-                audio_acc[channel][start + i] += audio_frame[i];
-                norm_acc[channel][start + i] += norm_window[i];
-            }
+            output[channel].extend_from_slice(overlap_adds[channel].process_frame(&audio_frame));
         }
     }
+    for (channel, ola) in output.iter_mut().zip(&mut overlap_adds) {
+        channel.extend(ola.flush());
+    }
 
-    Ok(())
+    Ok(output)
 }
 
 // -------------------------------------------------------------------------------------------------
@@ -12,8 +12,7 @@
 use std::{error::Error, fs::File, path::Path};
 
 use flucoma_rs::{
-    analyzation::{MelBands, OnsetFunction},
-    fourier::{Stft, WindowType},
+    analyzation::{BufMelBands, BufMelBandsConfig, OnsetFunction},
     segmentation::OnsetSlice,
 };
 
@@ -239,51 +238,30 @@ struct Slice {
 
 /// Compute the mean mel-band vector for a slice `mono[start..end]`.
 fn mean_mel(mono_sample_data: &[f64], start: usize, end: usize, sample_rate: u32) -> Vec<f64> {
-    let hi_hz = sample_rate as f64 / 2.0;
-    let bin_count = FFT_SIZE / 2 + 1;
-
-    let mut stft = Stft::new(WINDOW_SIZE, FFT_SIZE, HOP_SIZE, WindowType::Hann).expect("Stft::new");
-    let mut mel = MelBands::new(
-        NUM_MEL_BANDS,
-        bin_count,
-        MIN_FREQ_HZ,
-        hi_hz,
-        sample_rate as f64,
-        WINDOW_SIZE,
-    )
-    .expect("MelBands::new");
+    let mel = BufMelBands::new(BufMelBandsConfig {
+        n_bands: NUM_MEL_BANDS,
+        lo_hz: MIN_FREQ_HZ,
+        hi_hz: sample_rate as f64 / 2.0,
+        normalize: false,
+        use_power: true,
+        log_output: false,
+        window_size: WINDOW_SIZE,
+        hop_size: HOP_SIZE,
+        fft_size: FFT_SIZE,
+    })
+    .expect("BufMelBands::new");
+    let spectrogram = mel
+        .process(&mono_sample_data[start..end], sample_rate as f64)
+        .expect("BufMelBands::process");
 
     let mut accumulator = vec![0.0f64; NUM_MEL_BANDS];
-    let mut count = 0usize;
-    let mut frame = vec![0.0f64; WINDOW_SIZE];
-
-    let slice_len = end.saturating_sub(start);
-    let total_hops = slice_len.saturating_sub(WINDOW_SIZE) / HOP_SIZE + 1;
-
-    for hop in 0..total_hops {
-        let pos = start + hop * HOP_SIZE;
-        for i in 0..WINDOW_SIZE {
-            frame[i] = if pos + i < end && pos + i < mono_sample_data.len() {
-                mono_sample_data[pos + i]
-            } else {
-                0.0
-            };
-        }
-
-        let spec = stft.process_frame(&frame);
-        let mags = spec.magnitudes();
-        let bands = mel.process_frame(&mags, false, true, false);
-
-        for (a, b) in accumulator.iter_mut().zip(bands.iter()) {
+    for frame in 0..spectrogram.num_frames() {
+        for (a, b) in accumulator.iter_mut().zip(spectrogram.frame(frame)) {
             *a += b;
         }
-        count += 1;
     }
-
-    if count > 0 {
-        for v in &mut accumulator {
-            *v /= count as f64;
-        }
+    for v in &mut accumulator {
+        *v /= spectrogram.num_frames() as f64;
     }
 
     accumulator
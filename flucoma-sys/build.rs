@@ -11,6 +11,12 @@ fn main() {
     let manifest_dir = PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap());
     let flucoma_dir = manifest_dir.join("..").join("vendor").join("flucoma-core");
 
+    // -- record the flucoma-core commit for reproducibility manifests
+
+    if let Some(commit) = git_commit(&flucoma_dir) {
+        println!("cargo:rustc-env=FLUCOMA_CORE_COMMIT={}", commit);
+    }
+
     let profile = match std::env::var("PROFILE").as_deref() {
         Ok("release") => "Release",
         _ => "RelWithDebInfo",
@@ -108,6 +114,39 @@ fn main() {
 
 // -------------------------------------------------------------------------------------------------
 
+/// Query the checked out git commit of `dir`, if it is the root of a git
+/// working copy (such as a checked out submodule).
+///
+/// Git walks up to an enclosing repository when `dir` is not a working copy
+/// itself, so the result is only accepted when `dir` is the top level.
+fn git_commit(dir: &PathBuf) -> Option<String> {
+    let toplevel = PathBuf::from(git(dir, &["rev-parse", "--show-toplevel"])?);
+    if fs::canonicalize(toplevel).ok()? != fs::canonicalize(dir).ok()? {
+        return None;
+    }
+    // re-run when the submodule is moved to another commit
+    let head = PathBuf::from(git(dir, &["rev-parse", "--git-path", "HEAD"])?);
+    println!("cargo:rerun-if-changed={}", dir.join(head).display());
+    git(dir, &["rev-parse", "HEAD"])
+}
+
+/// Run `git -C dir args...` and return its trimmed output, if it succeeds.
+fn git(dir: &PathBuf, args: &[&str]) -> Option<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let output = String::from_utf8(output.stdout).ok()?.trim().to_string();
+    (!output.is_empty()).then_some(output)
+}
+
+// -------------------------------------------------------------------------------------------------
+
 /// Build the ALL_BUILD target inside a cmake sub-directory.
 fn build_cmake_target(dir: &PathBuf, label: &str, profile: &str) {
     let mut cmd = Command::new("cmake");
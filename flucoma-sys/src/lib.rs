@@ -1,5 +1,5 @@
 #![allow(non_upper_case_globals)]
-#![recursion_limit = "512"]
+#![recursion_limit = "1024"]
 /// Raw bindings for flucoma-core algorithms via inline C++ (cpp! macros).
 ///
 /// All handles are opaque `*mut u8` pointers. Do not use these functions
@@ -9,21 +9,32 @@ use cpp::cpp;
 /// Signed index type matching `ptrdiff_t` used by flucoma-core.
 pub type FlucomaIndex = isize;
 
+/// Git commit of the flucoma-core sources this crate was built against, if known.
+pub const FLUCOMA_CORE_COMMIT: Option<&str> = option_env!("FLUCOMA_CORE_COMMIT");
+
 // -------------------------------------------------------------------------------------------------
 // Cpp includes
 
 cpp! {{
     #define FMT_HEADER_ONLY 1
+    #include <algorithm>
     #include <complex>
+    #include <cstring>
+    #include <type_traits>
+    #include <utility>
+    #include <vector>
     #include <flucoma/data/FluidMemory.hpp>
     #include <flucoma/algorithms/public/Envelope.hpp>
     #include <flucoma/algorithms/public/Loudness.hpp>
     #include <flucoma/algorithms/public/STFT.hpp>
     #include <flucoma/algorithms/public/MelBands.hpp>
+    #include <flucoma/algorithms/public/DCT.hpp>
+    #include <flucoma/algorithms/public/ChromaFilterBank.hpp>
+    #include <flucoma/algorithms/public/YINFFT.hpp>
+    #include <flucoma/algorithms/public/SpectralShape.hpp>
     #include <flucoma/algorithms/public/OnsetDetectionFunctions.hpp>
     #include <flucoma/algorithms/public/OnsetSegmentation.hpp>
     #include <flucoma/algorithms/public/AudioTransport.hpp>
-    #include <flucoma/algorithms/public/DataSetQuery.hpp>
     #include <flucoma/algorithms/public/Grid.hpp>
     #include <flucoma/algorithms/public/KDTree.hpp>
     #include <flucoma/algorithms/public/KMeans.hpp>
@@ -38,6 +49,7 @@ cpp! {{
     #include <flucoma/algorithms/public/SKMeans.hpp>
     #include <flucoma/algorithms/public/Standardization.hpp>
     #include <flucoma/algorithms/public/EnvelopeSegmentation.hpp>
+    #include <flucoma/algorithms/public/EnvelopeGate.hpp>
     #include <flucoma/algorithms/public/NoveltyFeature.hpp>
     #include <flucoma/algorithms/public/NoveltySegmentation.hpp>
     #include <flucoma/algorithms/public/SineFeature.hpp>
@@ -45,8 +57,91 @@ cpp! {{
     #include <flucoma/algorithms/public/HPSS.hpp>
     #include <flucoma/algorithms/public/SineExtraction.hpp>
     #include <flucoma/algorithms/public/TransientExtraction.hpp>
+    #include <flucoma/algorithms/util/MedianFilter.hpp>
     using namespace fluid;
     using namespace fluid::algorithm;
+
+    // The segmenters keep the detection function values behind their
+    // decisions in private members. Explicit instantiation is exempt from
+    // access checks, which lets these accessors read them without running a
+    // second detector next to each segmenter.
+    //
+    // This depends on flucoma-core's private layout. A renamed member fails
+    // to compile, a changed member type trips the static_asserts below, and
+    // a changed meaning fails the tests that compare each accessor against
+    // the public detectors (NoveltySlice, OnsetSlice and BufTransientSlice
+    // tests in flucoma-rs).
+
+    // NoveltySegmentation peak-picks over a three-frame buffer; the candidate
+    // peak is the middle entry.
+    double noveltySegCandidate(const NoveltySegmentation& seg);
+    template <auto PeakBuffer>
+    struct NoveltySegPeakAccess
+    {
+        static_assert(
+            std::is_same_v<
+                std::decay_t<decltype((std::declval<const NoveltySegmentation&>().*PeakBuffer)(1))>,
+                double>,
+            "NoveltySegmentation::mPeakBuffer changed type; re-check noveltySegCandidate");
+
+        friend double noveltySegCandidate(const NoveltySegmentation& seg)
+        {
+            return (seg.*PeakBuffer)(1);
+        }
+    };
+    template struct NoveltySegPeakAccess<&NoveltySegmentation::mPeakBuffer>;
+
+    // OnsetSegmentation keeps the last filtered detection function value to
+    // find upward threshold crossings.
+    double onsetSegValue(const OnsetSegmentation& seg);
+    template <auto PrevFuncVal>
+    struct OnsetSegValueAccess
+    {
+        static_assert(
+            std::is_same_v<decltype(PrevFuncVal), double OnsetSegmentation::*>,
+            "OnsetSegmentation::mPrevFuncVal changed type; re-check onsetSegValue");
+
+        friend double onsetSegValue(const OnsetSegmentation& seg)
+        {
+            return seg.*PrevFuncVal;
+        }
+    };
+    template struct OnsetSegValueAccess<&OnsetSegmentation::mPrevFuncVal>;
+
+    // TransientSegmentation thresholds the windowed forward and backward
+    // prediction errors of its TransientExtraction base. The loop lives
+    // outside the friend below because GCC rejects locals in friends defined
+    // inside explicitly instantiated templates.
+    template <typename Forward, typename Backward>
+    double windowedErrorPeak(const Forward& forward, const Backward& backward, ptrdiff_t size)
+    {
+        double peak = 0.0;
+        for (ptrdiff_t i = 0; i < size; i++)
+        {
+            peak = std::max(peak, static_cast<double>(forward[i]));
+            peak = std::max(peak, static_cast<double>(backward[i]));
+        }
+        return peak;
+    }
+    double transientSegPeak(const TransientSegmentation& seg, ptrdiff_t size);
+    template <auto ForwardError, auto BackwardError>
+    struct TransientSegErrorAccess
+    {
+        static_assert(
+            std::is_arithmetic_v<
+                std::decay_t<decltype((std::declval<const TransientSegmentation&>().*ForwardError)[0])>>
+            && std::is_arithmetic_v<
+                std::decay_t<decltype((std::declval<const TransientSegmentation&>().*BackwardError)[0])>>,
+            "TransientExtraction windowed errors changed type; re-check transientSegPeak");
+
+        friend double transientSegPeak(const TransientSegmentation& seg, ptrdiff_t size)
+        {
+            return windowedErrorPeak(seg.*ForwardError, seg.*BackwardError, size);
+        }
+    };
+    template struct TransientSegErrorAccess<
+        &TransientExtraction::mForwardWindowedError,
+        &TransientExtraction::mBackwardWindowedError>;
 }}
 
 // -------------------------------------------------------------------------------------------------
@@ -168,6 +263,26 @@ pub fn stft_create(
     }
 }
 
+/// Re-initialise an existing instance with new parameters, reusing its
+/// allocation.
+pub fn stft_reset(
+    ptr: *mut u8,
+    window_size: FlucomaIndex,
+    fft_size: FlucomaIndex,
+    hop_size: FlucomaIndex,
+    window_type: FlucomaIndex,
+) {
+    unsafe {
+        cpp!([
+            ptr as "STFT*", window_size as "ptrdiff_t", fft_size as "ptrdiff_t",
+            hop_size as "ptrdiff_t", window_type as "ptrdiff_t"
+        ] {
+            ptr->~STFT();
+            new (ptr) STFT(window_size, fft_size, hop_size, window_type);
+        })
+    }
+}
+
 pub fn stft_destroy(ptr: *mut u8) {
     unsafe {
         cpp!([ptr as "STFT*"] {
@@ -216,6 +331,26 @@ pub fn istft_create(
     }
 }
 
+/// Re-initialise an existing instance with new parameters, reusing its
+/// allocation.
+pub fn istft_reset(
+    ptr: *mut u8,
+    window_size: FlucomaIndex,
+    fft_size: FlucomaIndex,
+    hop_size: FlucomaIndex,
+    window_type: FlucomaIndex,
+) {
+    unsafe {
+        cpp!([
+            ptr as "ISTFT*", window_size as "ptrdiff_t", fft_size as "ptrdiff_t",
+            hop_size as "ptrdiff_t", window_type as "ptrdiff_t"
+        ] {
+            ptr->~ISTFT();
+            new (ptr) ISTFT(window_size, fft_size, hop_size, window_type);
+        })
+    }
+}
+
 pub fn istft_destroy(ptr: *mut u8) {
     unsafe {
         cpp!([ptr as "ISTFT*"] {
@@ -311,6 +446,208 @@ pub fn melbands_process_frame(
     }
 }
 
+// -------------------------------------------------------------------------------------------------
+// DCT (MFCC)
+
+pub fn dct_create(max_input_size: FlucomaIndex, max_output_size: FlucomaIndex) -> *mut u8 {
+    unsafe {
+        cpp!([max_input_size as "ptrdiff_t", max_output_size as "ptrdiff_t"] -> *mut u8 as "void*" {
+            return static_cast<void*>(new DCT(max_input_size, max_output_size));
+        })
+    }
+}
+
+pub fn dct_destroy(ptr: *mut u8) {
+    unsafe {
+        cpp!([ptr as "DCT*"] {
+            delete ptr;
+        })
+    }
+}
+
+pub fn dct_init(ptr: *mut u8, input_size: FlucomaIndex, output_size: FlucomaIndex) {
+    unsafe {
+        cpp!([ptr as "DCT*", input_size as "ptrdiff_t", output_size as "ptrdiff_t"] {
+            ptr->init(input_size, output_size);
+        })
+    }
+}
+
+pub fn dct_process_frame(
+    ptr: *mut u8,
+    input: *const f64,
+    input_len: FlucomaIndex,
+    output: *mut f64,
+    output_len: FlucomaIndex,
+) {
+    unsafe {
+        cpp!([
+            ptr as "DCT*",
+            input as "const double*", input_len as "ptrdiff_t",
+            output as "double*", output_len as "ptrdiff_t"
+        ] {
+            FluidTensorView<double, 1> in_v(const_cast<double*>(input), 0, input_len);
+            FluidTensorView<double, 1> out_v(output, 0, output_len);
+            ptr->processFrame(in_v, out_v);
+        })
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+// ChromaFilterBank
+
+pub fn chroma_create(max_chroma: FlucomaIndex, max_fft: FlucomaIndex) -> *mut u8 {
+    unsafe {
+        cpp!([max_chroma as "ptrdiff_t", max_fft as "ptrdiff_t"] -> *mut u8 as "void*" {
+            return static_cast<void*>(new ChromaFilterBank(max_chroma, max_fft));
+        })
+    }
+}
+
+pub fn chroma_destroy(ptr: *mut u8) {
+    unsafe {
+        cpp!([ptr as "ChromaFilterBank*"] {
+            delete ptr;
+        })
+    }
+}
+
+pub fn chroma_init(
+    ptr: *mut u8,
+    n_chroma: FlucomaIndex,
+    n_bins: FlucomaIndex,
+    ref_hz: f64,
+    sample_rate: f64,
+) {
+    unsafe {
+        cpp!([
+            ptr as "ChromaFilterBank*",
+            n_chroma as "ptrdiff_t", n_bins as "ptrdiff_t",
+            ref_hz as "double", sample_rate as "double"
+        ] {
+            ptr->init(n_chroma, n_bins, ref_hz, sample_rate);
+        })
+    }
+}
+
+pub fn chroma_process_frame(
+    ptr: *mut u8,
+    input: *const f64,
+    input_len: FlucomaIndex,
+    output: *mut f64,
+    output_len: FlucomaIndex,
+    min_hz: f64,
+    max_hz: f64,
+    normalize: FlucomaIndex,
+) {
+    unsafe {
+        cpp!([
+            ptr as "ChromaFilterBank*",
+            input as "const double*", input_len as "ptrdiff_t",
+            output as "double*", output_len as "ptrdiff_t",
+            min_hz as "double", max_hz as "double", normalize as "ptrdiff_t"
+        ] {
+            FluidTensorView<double, 1> in_v(const_cast<double*>(input), 0, input_len);
+            FluidTensorView<double, 1> out_v(output, 0, output_len);
+            ptr->processFrame(in_v, out_v, min_hz, max_hz, normalize);
+        })
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+// YINFFT (Pitch)
+
+pub fn yinfft_create(max_input_size: FlucomaIndex) -> *mut u8 {
+    unsafe {
+        cpp!([max_input_size as "ptrdiff_t"] -> *mut u8 as "void*" {
+            return static_cast<void*>(new YINFFT(max_input_size, FluidDefaultAllocator()));
+        })
+    }
+}
+
+pub fn yinfft_destroy(ptr: *mut u8) {
+    unsafe {
+        cpp!([ptr as "YINFFT*"] {
+            delete ptr;
+        })
+    }
+}
+
+/// Writes `[frequency_hz, confidence]` to `output`.
+pub fn yinfft_process_frame(
+    ptr: *mut u8,
+    input: *const f64,
+    input_len: FlucomaIndex,
+    output: *mut f64,
+    min_hz: f64,
+    max_hz: f64,
+    sample_rate: f64,
+) {
+    unsafe {
+        cpp!([
+            ptr as "YINFFT*",
+            input as "const double*", input_len as "ptrdiff_t",
+            output as "double*",
+            min_hz as "double", max_hz as "double", sample_rate as "double"
+        ] {
+            FluidTensorView<double, 1> in_v(const_cast<double*>(input), 0, input_len);
+            FluidTensorView<double, 1> out_v(output, 0, 2);
+            ptr->processFrame(in_v, out_v, min_hz, max_hz, sample_rate, FluidDefaultAllocator());
+        })
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+// SpectralShape
+
+pub fn spectral_shape_create() -> *mut u8 {
+    unsafe {
+        cpp!([] -> *mut u8 as "void*" {
+            return static_cast<void*>(new SpectralShape(FluidDefaultAllocator()));
+        })
+    }
+}
+
+pub fn spectral_shape_destroy(ptr: *mut u8) {
+    unsafe {
+        cpp!([ptr as "SpectralShape*"] {
+            delete ptr;
+        })
+    }
+}
+
+/// Writes the 7 shape descriptors (centroid, spread, skewness, kurtosis,
+/// rolloff, flatness, crest) to `output`.
+#[allow(clippy::too_many_arguments)]
+pub fn spectral_shape_process_frame(
+    ptr: *mut u8,
+    input: *const f64,
+    input_len: FlucomaIndex,
+    output: *mut f64,
+    sample_rate: f64,
+    min_hz: f64,
+    max_hz: f64,
+    rolloff_percent: f64,
+    log_frequency: bool,
+    use_power: bool,
+) {
+    unsafe {
+        cpp!([
+            ptr as "SpectralShape*",
+            input as "const double*", input_len as "ptrdiff_t",
+            output as "double*",
+            sample_rate as "double", min_hz as "double", max_hz as "double",
+            rolloff_percent as "double",
+            log_frequency as "bool", use_power as "bool"
+        ] {
+            FluidTensorView<double, 1> in_v(const_cast<double*>(input), 0, input_len);
+            FluidTensorView<double, 1> out_v(output, 0, 7);
+            ptr->processFrame(in_v, out_v, sample_rate, min_hz, max_hz, rolloff_percent,
+                              log_frequency, use_power, FluidDefaultAllocator());
+        })
+    }
+}
+
 // -------------------------------------------------------------------------------------------------
 // AudioTransport
 
@@ -423,6 +760,47 @@ pub fn nmf_process(
     }
 }
 
+/// Like [`nmf_process`], but starts the updates from the given bases `w0`
+/// (`rank × n_bins`) and activations `h0` (`n_frames × rank`) instead of a
+/// random initialisation, so a factorisation can be run in several chunks of
+/// iterations.
+#[allow(clippy::too_many_arguments)]
+pub fn nmf_process_from(
+    ptr: *mut u8,
+    x: *const f64,
+    n_frames: FlucomaIndex,
+    n_bins: FlucomaIndex,
+    w0: *const f64,
+    h0: *const f64,
+    w1: *mut f64,
+    h1: *mut f64,
+    v1: *mut f64,
+    rank: FlucomaIndex,
+    n_iterations: FlucomaIndex,
+    update_w: bool,
+    update_h: bool,
+) {
+    unsafe {
+        cpp!([
+            ptr as "NMF*",
+            x  as "const double*", n_frames as "ptrdiff_t", n_bins as "ptrdiff_t",
+            w0 as "const double*", h0 as "const double*",
+            w1 as "double*", h1 as "double*", v1 as "double*",
+            rank as "ptrdiff_t", n_iterations as "ptrdiff_t",
+            update_w as "bool", update_h as "bool"
+        ] {
+            FluidTensorView<double, 2> x_v (const_cast<double*>(x),  0, n_frames, n_bins);
+            FluidTensorView<double, 2> w0_v(const_cast<double*>(w0), 0, rank,     n_bins);
+            FluidTensorView<double, 2> h0_v(const_cast<double*>(h0), 0, n_frames, rank);
+            FluidTensorView<double, 2> w1_v(w1,                      0, rank,     n_bins);
+            FluidTensorView<double, 2> h1_v(h1,                      0, n_frames, rank);
+            FluidTensorView<double, 2> v1_v(v1,                      0, n_frames, n_bins);
+            ptr->process(x_v, w1_v, h1_v, v1_v, rank, n_iterations, update_w,
+                         update_h, -1, w0_v, h0_v);
+        })
+    }
+}
+
 pub fn nmf_process_frame(
     ptr: *mut u8,
     input: *const f64,
@@ -643,6 +1021,14 @@ pub fn onset_seg_process_frame(
     }
 }
 
+pub fn onset_seg_value(ptr: *mut u8) -> f64 {
+    unsafe {
+        cpp!([ptr as "OnsetSegmentation*"] -> f64 as "double" {
+            return onsetSegValue(*ptr);
+        })
+    }
+}
+
 // -------------------------------------------------------------------------------------------------
 // AmpSlice
 
@@ -703,6 +1089,119 @@ pub fn amp_seg_process_sample(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
+pub fn amp_seg_process_block(
+    ptr: *mut u8,
+    input: *const f64,
+    output: *mut f64,
+    len: FlucomaIndex,
+    on_threshold: f64,
+    off_threshold: f64,
+    floor: f64,
+    fast_ramp_up: FlucomaIndex,
+    slow_ramp_up: FlucomaIndex,
+    fast_ramp_down: FlucomaIndex,
+    slow_ramp_down: FlucomaIndex,
+    hi_pass_freq: f64,
+    debounce: FlucomaIndex,
+) {
+    unsafe {
+        cpp!([
+            ptr as "EnvelopeSegmentation*",
+            input as "const double*", output as "double*", len as "ptrdiff_t",
+            on_threshold as "double", off_threshold as "double",
+            floor as "double",
+            fast_ramp_up as "ptrdiff_t", slow_ramp_up as "ptrdiff_t",
+            fast_ramp_down as "ptrdiff_t", slow_ramp_down as "ptrdiff_t",
+            hi_pass_freq as "double", debounce as "ptrdiff_t"
+        ] {
+            for (ptrdiff_t i = 0; i < len; ++i) {
+                output[i] = ptr->processSample(input[i], on_threshold, off_threshold, floor,
+                    fast_ramp_up, slow_ramp_up, fast_ramp_down, slow_ramp_down,
+                    hi_pass_freq, debounce);
+            }
+        })
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+// AmpGate
+
+pub fn amp_gate_create(max_size: FlucomaIndex) -> *mut u8 {
+    unsafe {
+        cpp!([max_size as "ptrdiff_t"] -> *mut u8 as "void*" {
+            return static_cast<void*>(new EnvelopeGate(max_size, FluidDefaultAllocator()));
+        })
+    }
+}
+
+pub fn amp_gate_destroy(ptr: *mut u8) {
+    unsafe {
+        cpp!([ptr as "EnvelopeGate*"] {
+            delete ptr;
+        })
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn amp_gate_init(
+    ptr: *mut u8,
+    on_threshold: f64,
+    off_threshold: f64,
+    hi_pass_freq: f64,
+    min_time_above: FlucomaIndex,
+    look_back: FlucomaIndex,
+    min_time_below: FlucomaIndex,
+    look_ahead: FlucomaIndex,
+) {
+    unsafe {
+        cpp!([
+            ptr as "EnvelopeGate*",
+            on_threshold as "double", off_threshold as "double", hi_pass_freq as "double",
+            min_time_above as "ptrdiff_t", look_back as "ptrdiff_t",
+            min_time_below as "ptrdiff_t", look_ahead as "ptrdiff_t"
+        ] {
+            ptr->init(on_threshold, off_threshold, hi_pass_freq,
+                      min_time_above, look_back, min_time_below, look_ahead);
+        })
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn amp_gate_process_sample(
+    ptr: *mut u8,
+    input: f64,
+    on_threshold: f64,
+    off_threshold: f64,
+    ramp_up: FlucomaIndex,
+    ramp_down: FlucomaIndex,
+    hi_pass_freq: f64,
+    min_event_duration: FlucomaIndex,
+    min_silence_duration: FlucomaIndex,
+) -> f64 {
+    unsafe {
+        cpp!([
+            ptr as "EnvelopeGate*",
+            input as "double",
+            on_threshold as "double", off_threshold as "double",
+            ramp_up as "ptrdiff_t", ramp_down as "ptrdiff_t",
+            hi_pass_freq as "double",
+            min_event_duration as "ptrdiff_t", min_silence_duration as "ptrdiff_t"
+        ] -> f64 as "double" {
+            return ptr->processSample(input, on_threshold, off_threshold, ramp_up, ramp_down,
+                                      hi_pass_freq, min_event_duration, min_silence_duration);
+        })
+    }
+}
+
+pub fn amp_gate_latency(ptr: *mut u8) -> FlucomaIndex {
+    unsafe {
+        cpp!([ptr as "EnvelopeGate*"] -> FlucomaIndex as "ptrdiff_t" {
+            return ptr->getLatency();
+        })
+    }
+}
+
 // -------------------------------------------------------------------------------------------------
 // NoveltySlice
 
@@ -764,6 +1263,14 @@ pub fn novelty_seg_process_frame(
     }
 }
 
+pub fn novelty_seg_candidate(ptr: *mut u8) -> f64 {
+    unsafe {
+        cpp!([ptr as "NoveltySegmentation*"] -> f64 as "double" {
+            return noveltySegCandidate(*ptr);
+        })
+    }
+}
+
 // -------------------------------------------------------------------------------------------------
 // NoveltyFeature
 
@@ -962,6 +1469,14 @@ pub fn transient_seg_process(
     }
 }
 
+pub fn transient_seg_detection_peak(ptr: *mut u8, size: FlucomaIndex) -> f64 {
+    unsafe {
+        cpp!([ptr as "TransientSegmentation*", size as "ptrdiff_t"] -> f64 as "double" {
+            return transientSegPeak(*ptr, size);
+        })
+    }
+}
+
 pub fn transient_seg_hop_size(ptr: *mut u8) -> FlucomaIndex {
     unsafe {
         cpp!([ptr as "TransientSegmentation*"] -> FlucomaIndex as "ptrdiff_t" {
@@ -1248,14 +1763,46 @@ pub fn kdtree_destroy(ptr: *mut u8) {
     }
 }
 
-pub fn kdtree_add_node(ptr: *mut u8, id: *const u8, data: *const f64, len: FlucomaIndex) {
+/// Add `count` labelled points and rebuild the tree once.
+///
+/// Ids are passed back to back in `ids` without terminators, with their byte
+/// lengths in `id_lens`. `data` holds `count × dims` row-major values. Returns
+/// `false`, leaving the tree unchanged, if any id is already present.
+pub fn kdtree_add_nodes(
+    ptr: *mut u8,
+    ids: *const u8,
+    id_lens: *const FlucomaIndex,
+    count: FlucomaIndex,
+    data: *const f64,
+    dims: FlucomaIndex,
+) -> bool {
     unsafe {
-        cpp!([ptr as "KDTree*", id as "const char*", data as "const double*", len as "ptrdiff_t"] {
-            FluidTensorView<double, 1> data_v(const_cast<double*>(data), 0, len);
+        cpp!([
+            ptr as "KDTree*",
+            ids as "const char*",
+            id_lens as "const ptrdiff_t*",
+            count as "ptrdiff_t",
+            data as "const double*",
+            dims as "ptrdiff_t"
+        ] -> bool as "bool" {
             auto flat = ptr->toFlat();
             KDTree::DataSet data_set(flat.ids, flat.data);
-            data_set.add(std::string(id), data_v);
+            const char* id = ids;
+            for (ptrdiff_t i = 0; i < count; ++i) {
+                FluidTensorView<double, 1> data_v(const_cast<double*>(data + i * dims), 0, dims);
+                if (!data_set.add(std::string(id, id_lens[i]), data_v)) return false;
+                id += id_lens[i];
+            }
             *ptr = KDTree(data_set);
+            return true;
+        })
+    }
+}
+
+pub fn kdtree_size(ptr: *mut u8) -> FlucomaIndex {
+    unsafe {
+        cpp!([ptr as "KDTree*"] -> FlucomaIndex as "ptrdiff_t" {
+            return ptr->size();
         })
     }
 }
@@ -1267,8 +1814,10 @@ pub fn kdtree_k_nearest(
     k: FlucomaIndex,
     radius: f64,
     out_distances: *mut f64,
-    out_ids: *mut *const u8,
-) {
+    out_id_lens: *mut FlucomaIndex,
+    out_id_bytes: *mut u8,
+    out_id_capacity: FlucomaIndex,
+) -> FlucomaIndex {
     unsafe {
         cpp!([
             ptr as "KDTree*",
@@ -1277,15 +1826,25 @@ pub fn kdtree_k_nearest(
             k as "ptrdiff_t",
             radius as "double",
             out_distances as "double*",
-            out_ids as "const char**"
-        ] {
+            out_id_lens as "ptrdiff_t*",
+            out_id_bytes as "char*",
+            out_id_capacity as "ptrdiff_t"
+        ] -> FlucomaIndex as "ptrdiff_t" {
             FluidTensorView<double, 1> in_v(const_cast<double*>(input), 0, input_len);
             Allocator alloc{};
             auto result = ptr->kNearest(in_v, k, radius, alloc);
-            for (fluid::index i = 0; i < static_cast<fluid::index>(result.first.size()); ++i) {
+            fluid::index n = std::min(k, static_cast<fluid::index>(result.first.size()));
+            fluid::index offset = 0;
+            for (fluid::index i = 0; i < n; ++i) {
+                const std::string& id = *result.second[i];
+                fluid::index len = static_cast<fluid::index>(id.size());
+                if (offset + len > out_id_capacity) return i;
+                std::memcpy(out_id_bytes + offset, id.data(), len);
+                out_id_lens[i] = len;
                 out_distances[i] = result.first[i];
-                out_ids[i] = result.second[i]->c_str();
+                offset += len;
             }
+            return n;
         })
     }
 }
@@ -1533,94 +2092,6 @@ pub fn grid_process(
     }
 }
 
-// -------------------------------------------------------------------------------------------------
-// DataSetQuery
-
-pub fn dataset_query_process(
-    input: *const f64,
-    rows: FlucomaIndex,
-    cols: FlucomaIndex,
-    selected_cols: *const FlucomaIndex,
-    selected_count: FlucomaIndex,
-    cond_cols: *const FlucomaIndex,
-    cond_ops: *const FlucomaIndex,
-    cond_vals: *const f64,
-    cond_and_flags: *const FlucomaIndex,
-    cond_count: FlucomaIndex,
-    limit: FlucomaIndex,
-    out_data: *mut f64,
-    out_ids: *mut FlucomaIndex,
-    out_count: *mut FlucomaIndex,
-) -> bool {
-    unsafe {
-        cpp!([
-            input as "const double*",
-            rows as "ptrdiff_t",
-            cols as "ptrdiff_t",
-            selected_cols as "const ptrdiff_t*",
-            selected_count as "ptrdiff_t",
-            cond_cols as "const ptrdiff_t*",
-            cond_ops as "const ptrdiff_t*",
-            cond_vals as "const double*",
-            cond_and_flags as "const ptrdiff_t*",
-            cond_count as "ptrdiff_t",
-            limit as "ptrdiff_t",
-            out_data as "double*",
-            out_ids as "ptrdiff_t*",
-            out_count as "ptrdiff_t*"
-        ] -> bool as "bool" {
-            if (rows <= 0 || cols <= 0 || selected_count <= 0) return false;
-
-            DataSetQuery::DataSet in_ds(cols);
-            for (ptrdiff_t r = 0; r < rows; ++r) {
-                RealVector point(cols);
-                for (ptrdiff_t c = 0; c < cols; ++c) point(c) = input[r * cols + c];
-                in_ds.add(std::to_string(r), point);
-            }
-
-            DataSetQuery query;
-            for (ptrdiff_t i = 0; i < selected_count; ++i) query.addColumn(selected_cols[i]);
-
-            auto op_str = [](ptrdiff_t op) -> const char* {
-                switch (op) {
-                    case 0: return "==";
-                    case 1: return "!=";
-                    case 2: return "<";
-                    case 3: return "<=";
-                    case 4: return ">";
-                    case 5: return ">=";
-                    default: return "==";
-                }
-            };
-
-            for (ptrdiff_t i = 0; i < cond_count; ++i) {
-                bool conjunction = cond_and_flags[i] != 0;
-                if (!query.addCondition(cond_cols[i], op_str(cond_ops[i]), cond_vals[i], conjunction)) {
-                    return false;
-                }
-            }
-
-            if (limit > 0) query.limit(limit);
-
-            DataSetQuery::DataSet current(0);
-            DataSetQuery::DataSet out_ds(selected_count);
-            query.process(in_ds, current, out_ds);
-
-            ptrdiff_t n = out_ds.size();
-            *out_count = n;
-            auto ids = out_ds.getIds();
-            auto data = out_ds.getData();
-            for (ptrdiff_t r = 0; r < n; ++r) {
-                out_ids[r] = std::stoll(ids(r));
-                for (ptrdiff_t c = 0; c < selected_count; ++c) {
-                    out_data[r * selected_count + c] = data(r, c);
-                }
-            }
-            return true;
-        })
-    }
-}
-
 // -------------------------------------------------------------------------------------------------
 // MultiStats
 
@@ -1755,6 +2226,41 @@ pub fn running_stats_process(
     }
 }
 
+// -------------------------------------------------------------------------------------------------
+// MedianFilter
+
+pub fn median_filter_create(max_size: FlucomaIndex) -> *mut u8 {
+    unsafe {
+        cpp!([max_size as "ptrdiff_t"] -> *mut u8 as "void*" {
+            return static_cast<void*>(new MedianFilter(max_size, FluidDefaultAllocator()));
+        })
+    }
+}
+
+pub fn median_filter_destroy(ptr: *mut u8) {
+    unsafe {
+        cpp!([ptr as "MedianFilter*"] {
+            delete ptr;
+        })
+    }
+}
+
+pub fn median_filter_init(ptr: *mut u8, size: FlucomaIndex) {
+    unsafe {
+        cpp!([ptr as "MedianFilter*", size as "ptrdiff_t"] {
+            ptr->init(size);
+        })
+    }
+}
+
+pub fn median_filter_process_sample(ptr: *mut u8, input: f64) -> f64 {
+    unsafe {
+        cpp!([ptr as "MedianFilter*", input as "double"] -> f64 as "double" {
+            return ptr->processSample(input);
+        })
+    }
+}
+
 // -------------------------------------------------------------------------------------------------
 // Normalization
 
@@ -1829,6 +2335,53 @@ pub fn normalization_initialized(ptr: *mut u8) -> bool {
     }
 }
 
+/// Set a Normalization from known per-feature minima and maxima.
+pub fn normalization_set_model(
+    ptr: *mut u8,
+    min: f64,
+    max: f64,
+    data_min: *const f64,
+    data_max: *const f64,
+    cols: FlucomaIndex,
+) {
+    unsafe {
+        cpp!([
+            ptr as "Normalization*",
+            min as "double",
+            max as "double",
+            data_min as "const double*",
+            data_max as "const double*",
+            cols as "ptrdiff_t"
+        ] {
+            FluidTensorView<double, 1> min_v(const_cast<double*>(data_min), 0, cols);
+            FluidTensorView<double, 1> max_v(const_cast<double*>(data_max), 0, cols);
+            ptr->init(min, max, min_v, max_v);
+        })
+    }
+}
+
+/// Copy the per-feature minima of a fitted Normalization. `len` must equal
+/// the fitted feature count.
+pub fn normalization_data_min(ptr: *mut u8, output: *mut f64, len: FlucomaIndex) {
+    unsafe {
+        cpp!([ptr as "Normalization*", output as "double*", len as "ptrdiff_t"] {
+            FluidTensorView<double, 1> out_v(output, 0, len);
+            ptr->getDataMin(out_v);
+        })
+    }
+}
+
+/// Copy the per-feature maxima of a fitted Normalization. `len` must equal
+/// the fitted feature count.
+pub fn normalization_data_max(ptr: *mut u8, output: *mut f64, len: FlucomaIndex) {
+    unsafe {
+        cpp!([ptr as "Normalization*", output as "double*", len as "ptrdiff_t"] {
+            FluidTensorView<double, 1> out_v(output, 0, len);
+            ptr->getDataMax(out_v);
+        })
+    }
+}
+
 // -------------------------------------------------------------------------------------------------
 // Standardization
 
@@ -1899,6 +2452,49 @@ pub fn standardization_initialized(ptr: *mut u8) -> bool {
     }
 }
 
+/// Set a Standardization from known per-feature means and standard deviations.
+pub fn standardization_set_model(
+    ptr: *mut u8,
+    means: *const f64,
+    stds: *const f64,
+    cols: FlucomaIndex,
+) {
+    unsafe {
+        cpp!([
+            ptr as "Standardization*",
+            means as "const double*",
+            stds as "const double*",
+            cols as "ptrdiff_t"
+        ] {
+            FluidTensorView<double, 1> mean_v(const_cast<double*>(means), 0, cols);
+            FluidTensorView<double, 1> std_v(const_cast<double*>(stds), 0, cols);
+            ptr->init(mean_v, std_v);
+        })
+    }
+}
+
+/// Copy the per-feature means of a fitted Standardization. `len` must equal
+/// the fitted feature count.
+pub fn standardization_means(ptr: *mut u8, output: *mut f64, len: FlucomaIndex) {
+    unsafe {
+        cpp!([ptr as "Standardization*", output as "double*", len as "ptrdiff_t"] {
+            FluidTensorView<double, 1> out_v(output, 0, len);
+            ptr->getMean(out_v);
+        })
+    }
+}
+
+/// Copy the per-feature standard deviations of a fitted Standardization.
+/// `len` must equal the fitted feature count.
+pub fn standardization_stds(ptr: *mut u8, output: *mut f64, len: FlucomaIndex) {
+    unsafe {
+        cpp!([ptr as "Standardization*", output as "double*", len as "ptrdiff_t"] {
+            FluidTensorView<double, 1> out_v(output, 0, len);
+            ptr->getStd(out_v);
+        })
+    }
+}
+
 // -------------------------------------------------------------------------------------------------
 // RobustScaling
 
@@ -1973,6 +2569,39 @@ pub fn robust_scaling_initialized(ptr: *mut u8) -> bool {
     }
 }
 
+/// Copy the per-feature medians of a fitted RobustScaling. `len` must equal
+/// the fitted feature count.
+pub fn robust_scaling_medians(ptr: *mut u8, output: *mut f64, len: FlucomaIndex) {
+    unsafe {
+        cpp!([ptr as "RobustScaling*", output as "double*", len as "ptrdiff_t"] {
+            FluidTensorView<double, 1> out_v(output, 0, len);
+            ptr->getMedian(out_v);
+        })
+    }
+}
+
+/// Copy the per-feature low percentile values of a fitted RobustScaling.
+/// `len` must equal the fitted feature count.
+pub fn robust_scaling_data_low(ptr: *mut u8, output: *mut f64, len: FlucomaIndex) {
+    unsafe {
+        cpp!([ptr as "RobustScaling*", output as "double*", len as "ptrdiff_t"] {
+            FluidTensorView<double, 1> out_v(output, 0, len);
+            ptr->getDataLow(out_v);
+        })
+    }
+}
+
+/// Copy the per-feature high percentile values of a fitted RobustScaling.
+/// `len` must equal the fitted feature count.
+pub fn robust_scaling_data_high(ptr: *mut u8, output: *mut f64, len: FlucomaIndex) {
+    unsafe {
+        cpp!([ptr as "RobustScaling*", output as "double*", len as "ptrdiff_t"] {
+            FluidTensorView<double, 1> out_v(output, 0, len);
+            ptr->getDataHigh(out_v);
+        })
+    }
+}
+
 // -------------------------------------------------------------------------------------------------
 // PCA
 
@@ -2006,6 +2635,34 @@ pub fn pca_fit(ptr: *mut u8, input: *const f64, rows: FlucomaIndex, cols: Flucom
     }
 }
 
+/// Initialise a PCA from an existing model: `bases` is a row-major
+/// `dims × k` matrix of component columns, `values` the `k` singular values
+/// and `mean` the `dims` feature means.
+pub fn pca_set_model(
+    ptr: *mut u8,
+    bases: *const f64,
+    values: *const f64,
+    mean: *const f64,
+    dims: FlucomaIndex,
+    k: FlucomaIndex,
+) {
+    unsafe {
+        cpp!([
+            ptr as "PCA*",
+            bases as "const double*",
+            values as "const double*",
+            mean as "const double*",
+            dims as "ptrdiff_t",
+            k as "ptrdiff_t"
+        ] {
+            FluidTensorView<double, 2> bases_v(const_cast<double*>(bases), 0, dims, k);
+            FluidTensorView<double, 1> values_v(const_cast<double*>(values), 0, k);
+            FluidTensorView<double, 1> mean_v(const_cast<double*>(mean), 0, dims);
+            ptr->init(bases_v, values_v, mean_v);
+        })
+    }
+}
+
 pub fn pca_transform(
     ptr: *mut u8,
     input: *const f64,
@@ -2058,6 +2715,55 @@ pub fn pca_inverse_transform(
     }
 }
 
+/// Project one `dims`-long point onto the first `k` components.
+pub fn pca_transform_frame(
+    ptr: *mut u8,
+    input: *const f64,
+    dims: FlucomaIndex,
+    output: *mut f64,
+    k: FlucomaIndex,
+    whiten: bool,
+) {
+    unsafe {
+        cpp!([
+            ptr as "PCA*",
+            input as "const double*",
+            dims as "ptrdiff_t",
+            output as "double*",
+            k as "ptrdiff_t",
+            whiten as "bool"
+        ] {
+            FluidTensorView<double, 1> in_v(const_cast<double*>(input), 0, dims);
+            FluidTensorView<double, 1> out_v(output, 0, k);
+            ptr->processFrame(in_v, out_v, k, whiten);
+        })
+    }
+}
+
+/// Map one point of `dims` components back to the `dims`-long feature space.
+/// Callers wanting fewer components zero-pad the trailing entries of `input`.
+pub fn pca_inverse_transform_frame(
+    ptr: *mut u8,
+    input: *const f64,
+    output: *mut f64,
+    dims: FlucomaIndex,
+    whiten: bool,
+) {
+    unsafe {
+        cpp!([
+            ptr as "PCA*",
+            input as "const double*",
+            output as "double*",
+            dims as "ptrdiff_t",
+            whiten as "bool"
+        ] {
+            FluidTensorView<double, 1> in_v(const_cast<double*>(input), 0, dims);
+            FluidTensorView<double, 1> out_v(output, 0, dims);
+            ptr->inverseProcessFrame(in_v, out_v, whiten);
+        })
+    }
+}
+
 pub fn pca_initialized(ptr: *mut u8) -> bool {
     unsafe {
         cpp!([ptr as "PCA*"] -> bool as "bool" {
@@ -2073,3 +2779,21 @@ pub fn pca_dims(ptr: *mut u8) -> FlucomaIndex {
         })
     }
 }
+
+pub fn pca_size(ptr: *mut u8) -> FlucomaIndex {
+    unsafe {
+        cpp!([ptr as "PCA*"] -> FlucomaIndex as "ptrdiff_t" {
+            return ptr->size();
+        })
+    }
+}
+
+/// Copy the singular values of a fitted PCA. `len` must equal `pca_size`.
+pub fn pca_values(ptr: *mut u8, output: *mut f64, len: FlucomaIndex) {
+    unsafe {
+        cpp!([ptr as "PCA*", output as "double*", len as "ptrdiff_t"] {
+            FluidTensorView<double, 1> out_v(output, 0, len);
+            ptr->getValues(out_v);
+        })
+    }
+}
@@ -0,0 +1,129 @@
+use std::collections::VecDeque;
+
+use crate::error::FlucomaError;
+
+// -------------------------------------------------------------------------------------------------
+
+/// How an onset detection threshold is interpreted.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ThresholdMode {
+    /// The threshold is compared with the detection function directly.
+    #[default]
+    Absolute,
+    /// The threshold is a factor applied to a running percentile of the
+    /// detection function, e.g. `1.5` × the median of the last `history`
+    /// frames, so one setting works across material at different levels.
+    Relative {
+        /// Number of past frames the percentile is taken over.
+        history: usize,
+        /// Percentile in `0.0..=100.0` (50 = median).
+        percentile: f64,
+    },
+}
+
+/// Running percentile threshold over a detection function.
+///
+/// Each call to [`AdaptiveThreshold::process`] returns `factor` times the
+/// chosen percentile of the previous `history` values (excluding the current
+/// one, so a peak does not raise its own threshold) and then records the
+/// value. Until any value has been seen the threshold is infinite.
+///
+/// ```rust
+/// use flucoma_rs::segmentation::AdaptiveThreshold;
+///
+/// let mut threshold = AdaptiveThreshold::new(4, 50.0, 1.5).unwrap();
+/// for value in [1.0, 2.0, 1.0, 2.0] {
+///     threshold.process(value);
+/// }
+/// assert_eq!(threshold.process(10.0), 2.25);
+/// ```
+#[derive(Debug, Clone)]
+pub struct AdaptiveThreshold {
+    history: VecDeque<f64>,
+    capacity: usize,
+    percentile: f64,
+    factor: f64,
+    sorted: Vec<f64>,
+}
+
+impl AdaptiveThreshold {
+    /// # Errors
+    /// Returns an error if `history == 0` or `percentile` is outside
+    /// `0.0..=100.0`.
+    pub fn new(history: usize, percentile: f64, factor: f64) -> Result<Self, FlucomaError> {
+        if history == 0 {
+            return Err(FlucomaError::InvalidParameter("history must be > 0"));
+        }
+        if !(0.0..=100.0).contains(&percentile) {
+            return Err(FlucomaError::InvalidParameter(
+                "percentile must be in 0..=100",
+            ));
+        }
+        Ok(Self {
+            history: VecDeque::with_capacity(history),
+            capacity: history,
+            percentile,
+            factor,
+            sorted: Vec::with_capacity(history),
+        })
+    }
+
+    /// Return the threshold that applies to `value`, then add `value` to the
+    /// history.
+    pub fn process(&mut self, value: f64) -> f64 {
+        let threshold = self.threshold();
+        if self.history.len() == self.capacity {
+            self.history.pop_front();
+        }
+        self.history.push_back(value);
+        threshold
+    }
+
+    /// The threshold for the next value.
+    pub fn threshold(&mut self) -> f64 {
+        if self.history.is_empty() {
+            return f64::INFINITY;
+        }
+        self.sorted.clear();
+        self.sorted.extend(self.history.iter().copied());
+        self.sorted.sort_by(f64::total_cmp);
+        let rank = self.percentile / 100.0 * (self.sorted.len() - 1) as f64;
+        let lower = rank.floor() as usize;
+        let upper = rank.ceil() as usize;
+        let fraction = rank - lower as f64;
+        let value = self.sorted[lower] + (self.sorted[upper] - self.sorted[lower]) * fraction;
+        self.factor * value
+    }
+
+    pub fn set_factor(&mut self, factor: f64) {
+        self.factor = factor;
+    }
+
+    /// Forget the recorded history.
+    pub fn reset(&mut self) {
+        self.history.clear();
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn adaptive_threshold_tracks_level() {
+        let mut quiet = AdaptiveThreshold::new(8, 50.0, 2.0).unwrap();
+        let mut loud = AdaptiveThreshold::new(8, 50.0, 2.0).unwrap();
+        assert_eq!(quiet.process(0.01), f64::INFINITY);
+        for _ in 0..8 {
+            quiet.process(0.01);
+            loud.process(10.0);
+        }
+        assert!((quiet.threshold() - 0.02).abs() < 1e-12);
+        assert!((loud.threshold() - 20.0).abs() < 1e-12);
+        assert!(AdaptiveThreshold::new(0, 50.0, 1.0).is_err());
+        assert!(AdaptiveThreshold::new(4, 101.0, 1.0).is_err());
+    }
+}
@@ -0,0 +1,182 @@
+use num_complex::Complex64 as Complex;
+
+use crate::error::{check_len, FlucomaError};
+use crate::fft_settings::FftSettings;
+use crate::stft::{ComplexSpectrum, Istft, Stft};
+
+// -------------------------------------------------------------------------------------------------
+
+/// Adaptive spectral whitening (Stowell & Plumbley, 2007).
+///
+/// Each bin is divided by a running memory of its recent peak magnitude,
+/// which jumps up with the signal and decays slowly otherwise. Every bin
+/// thus hovers around unity, so changes in quiet high bands count as much
+/// as those in loud low ones, which sharpens onset detection on dense mixed
+/// material. Magnitudes below `floor` are not boosted further, so silence
+/// and noise stay quiet.
+///
+/// Whitened spectra can be fed to any spectral analyser. For
+/// [`Onset`](crate::analyzation::Onset) and the slicers, which take audio,
+/// [`AdaptiveWhitening::whiten_signal`] resynthesises the whitened spectrum.
+///
+/// ```rust,no_run
+/// use flucoma_rs::analyzation::AdaptiveWhitening;
+/// use flucoma_rs::fourier::FftSettings;
+/// use flucoma_rs::segmentation::{OnsetSliceStream, OnsetSliceStreamConfig};
+///
+/// let settings = FftSettings::default();
+/// let decay = AdaptiveWhitening::decay_for_relaxation(1.0, settings.hop_size, 44100.0);
+/// let mut whitening = AdaptiveWhitening::new(settings.num_bins(), decay, 1e-4).unwrap();
+///
+/// let audio = vec![0.0f64; 44100];
+/// let whitened = whitening.whiten_signal(&audio, &settings).unwrap();
+/// let mut slicer = OnsetSliceStream::new(OnsetSliceStreamConfig::default()).unwrap();
+/// let onsets = slicer.process(&whitened);
+/// ```
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AdaptiveWhitening {
+    decay: f64,
+    floor: f64,
+    /// Peak magnitude memory per bin.
+    peaks: Vec<f64>,
+}
+
+impl AdaptiveWhitening {
+    /// Create a whitening stage for spectra of `n_bins` bins.
+    ///
+    /// # Arguments
+    /// * `n_bins` - Number of spectrum bins (`fft_size / 2 + 1`).
+    /// * `decay`  - Per-frame decay of the peak memory, in `0..=1`; see
+    ///   [`AdaptiveWhitening::decay_for_relaxation`].
+    /// * `floor`  - Smallest peak memory, limiting the gain on quiet bins.
+    ///
+    /// # Errors
+    /// Returns an error if parameters are invalid.
+    pub fn new(n_bins: usize, decay: f64, floor: f64) -> Result<Self, FlucomaError> {
+        if n_bins == 0 {
+            return Err(FlucomaError::InvalidParameter("n_bins must be > 0"));
+        }
+        if !(0.0..=1.0).contains(&decay) {
+            return Err(FlucomaError::InvalidParameter("decay must be in 0..=1"));
+        }
+        if floor.is_nan() || floor <= 0.0 {
+            return Err(FlucomaError::InvalidParameter("floor must be > 0"));
+        }
+        Ok(Self {
+            decay,
+            floor,
+            peaks: vec![floor; n_bins],
+        })
+    }
+
+    /// Per-frame decay which lets a peak fall by 60 dB over
+    /// `relaxation_seconds` at the given hop size and sample rate.
+    pub fn decay_for_relaxation(relaxation_seconds: f64, hop_size: usize, sample_rate: f64) -> f64 {
+        let frames = relaxation_seconds * sample_rate / hop_size as f64;
+        0.001f64.powf(1.0 / frames)
+    }
+
+    pub fn n_bins(&self) -> usize {
+        self.peaks.len()
+    }
+
+    /// Whiten one complex spectrum in place, keeping its phases.
+    ///
+    /// # Panics
+    /// Panics if `spectrum.num_bins() != n_bins`.
+    pub fn process_spectrum(&mut self, spectrum: &mut ComplexSpectrum) {
+        assert_eq!(
+            spectrum.num_bins(),
+            self.peaks.len(),
+            "spectrum bins ({}) must equal n_bins ({})",
+            spectrum.num_bins(),
+            self.peaks.len()
+        );
+        for bin in 0..self.peaks.len() {
+            spectrum[bin] = self.whiten_bin(bin, spectrum[bin]);
+        }
+    }
+
+    /// Whiten one magnitude spectrum in place.
+    ///
+    /// # Panics
+    /// Panics if `magnitudes.len() != n_bins`.
+    pub fn process_magnitudes(&mut self, magnitudes: &mut [f64]) {
+        assert_eq!(
+            magnitudes.len(),
+            self.peaks.len(),
+            "magnitudes length ({}) must equal n_bins ({})",
+            magnitudes.len(),
+            self.peaks.len()
+        );
+        for (bin, magnitude) in magnitudes.iter_mut().enumerate() {
+            *magnitude = self.whiten_bin(bin, Complex::new(*magnitude, 0.0)).re;
+        }
+    }
+
+    /// Analyse `signal`, whiten every frame and resynthesise it, so the
+    /// result can be passed to audio-domain onset detectors. The output has
+    /// the same length as `signal`.
+    ///
+    /// # Errors
+    /// Returns an error if the settings are invalid or do not have
+    /// `n_bins` bins.
+    pub fn whiten_signal(
+        &mut self,
+        signal: &[f64],
+        settings: &FftSettings,
+    ) -> Result<Vec<f64>, FlucomaError> {
+        settings.validate()?;
+        check_len(self.peaks.len(), settings.num_bins())?;
+        let mut spectrogram = Stft::from_settings(settings)?.analyze(signal);
+        for frame in spectrogram.data_mut().chunks_exact_mut(self.peaks.len()) {
+            for (bin, value) in frame.iter_mut().enumerate() {
+                *value = self.whiten_bin(bin, *value);
+            }
+        }
+        Ok(Istft::from_settings(settings)?.synthesize(&spectrogram))
+    }
+
+    /// Forget the peak memory, e.g. between unrelated files.
+    pub fn reset(&mut self) {
+        self.peaks.fill(self.floor);
+    }
+
+    fn whiten_bin(&mut self, bin: usize, value: Complex) -> Complex {
+        let peak = value
+            .norm()
+            .max(self.floor)
+            .max(self.decay * self.peaks[bin]);
+        self.peaks[bin] = peak;
+        value / peak
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn whitening_normalises_bins_to_their_peaks() {
+        let mut whitening = AdaptiveWhitening::new(3, 0.5, 0.01).unwrap();
+        let mut loud = [10.0, 1.0, 0.001];
+        whitening.process_magnitudes(&mut loud);
+        assert_eq!(loud, [1.0, 1.0, 0.1]);
+
+        // The memory decays by half per frame, so quieter frames rise
+        // relative to it until the floor is reached.
+        let mut quieter = [2.5, 1.0, 0.001];
+        whitening.process_magnitudes(&mut quieter);
+        assert_eq!(quieter, [0.5, 1.0, 0.1]);
+
+        let decay = AdaptiveWhitening::decay_for_relaxation(1.0, 441, 44100.0);
+        assert!((decay.powi(100) - 0.001).abs() < 1e-12);
+        assert_eq!(
+            AdaptiveWhitening::new(3, 1.5, 0.01).unwrap_err(),
+            FlucomaError::InvalidParameter("decay must be in 0..=1")
+        );
+    }
+}
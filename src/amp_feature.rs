@@ -2,6 +2,8 @@ use flucoma_sys::{
     amp_feature_create, amp_feature_destroy, amp_feature_init, amp_feature_process_sample,
 };
 
+use crate::error::FlucomaError;
+
 // -------------------------------------------------------------------------------------------------
 
 /// Amplitude envelope follower.
@@ -17,6 +19,8 @@ use flucoma_sys::{
 /// See <https://learn.flucoma.org/reference/ampfeature>
 pub struct AmpFeature {
     inner: *mut u8,
+    floor: f64,
+    hi_pass_freq: f64,
 }
 
 unsafe impl Send for AmpFeature {}
@@ -29,17 +33,23 @@ impl AmpFeature {
     /// * `hi_pass_freq` - Hi-pass filter cutoff in Hz (0.0 to disable).
     ///
     /// # Errors
-    /// Returns an error string if allocation fails.
-    pub fn new(floor: f64, hi_pass_freq: f64) -> Result<Self, &'static str> {
+    /// Returns an error if allocation fails.
+    pub fn new(floor: f64, hi_pass_freq: f64) -> Result<Self, FlucomaError> {
         if hi_pass_freq < 0.0 {
-            return Err("hi_pass_freq must be >= 0.0 (use 0.0 to disable)");
+            return Err(FlucomaError::InvalidParameter(
+                "hi_pass_freq must be >= 0.0 (use 0.0 to disable)",
+            ));
         }
         let inner = amp_feature_create();
         if inner.is_null() {
-            return Err("failed to create AmpFeature instance");
+            return Err(FlucomaError::AllocationFailed);
         }
         amp_feature_init(inner, floor, hi_pass_freq);
-        Ok(Self { inner })
+        Ok(Self {
+            inner,
+            floor,
+            hi_pass_freq,
+        })
     }
 
     /// Process one audio sample and return the envelope value.
@@ -88,6 +98,12 @@ impl Drop for AmpFeature {
     }
 }
 
+impl Clone for AmpFeature {
+    fn clone(&self) -> Self {
+        Self::new(self.floor, self.hi_pass_freq).expect("settings were validated on construction")
+    }
+}
+
 // -------------------------------------------------------------------------------------------------
 
 #[cfg(test)]
@@ -0,0 +1,192 @@
+use flucoma_sys::{
+    amp_gate_create, amp_gate_destroy, amp_gate_init, amp_gate_latency, amp_gate_process_sample,
+};
+
+use crate::error::FlucomaError;
+
+// -------------------------------------------------------------------------------------------------
+
+/// Amplitude gate with hysteresis, minimum durations and look-back/look-ahead,
+/// operating sample by sample.
+///
+/// Two-phase setup:
+/// 1. [`AmpGate::new`] -- allocates and initialises the gate.
+/// 2. Call [`AmpGate::process_sample`] per audio sample.
+///
+/// The output is delayed by [`AmpGate::latency_samples`] samples so that look-ahead
+/// and minimum-duration decisions can be applied.
+///
+/// See <https://learn.flucoma.org/reference/ampgate>
+pub struct AmpGate {
+    inner: *mut u8,
+    latency: usize,
+    on_threshold: f64,
+    off_threshold: f64,
+    hi_pass_freq: f64,
+    min_length_above: usize,
+    look_back: usize,
+    min_length_below: usize,
+    look_ahead: usize,
+}
+
+unsafe impl Send for AmpGate {}
+
+impl AmpGate {
+    /// Create and initialise an amplitude gate.
+    ///
+    /// # Arguments
+    /// * `on_threshold`     - dB level above which the gate opens.
+    /// * `off_threshold`    - dB level below which the gate closes.
+    /// * `hi_pass_freq`     - Hi-pass filter frequency in Hz applied before the follower.
+    /// * `min_length_above` - Samples the envelope must stay above `on_threshold` to open.
+    /// * `look_back`        - Samples to look back for a quieter opening point.
+    /// * `min_length_below` - Samples the envelope must stay below `off_threshold` to close.
+    /// * `look_ahead`       - Samples to look ahead for a quieter closing point.
+    ///
+    /// # Errors
+    /// Returns an error if allocation fails.
+    pub fn new(
+        on_threshold: f64,
+        off_threshold: f64,
+        hi_pass_freq: f64,
+        min_length_above: usize,
+        look_back: usize,
+        min_length_below: usize,
+        look_ahead: usize,
+    ) -> Result<Self, FlucomaError> {
+        if hi_pass_freq < 0.0 {
+            return Err(FlucomaError::InvalidParameter(
+                "hi_pass_freq must be >= 0.0 (use 0.0 to disable)",
+            ));
+        }
+        let max_size = (min_length_above + look_back)
+            .max(min_length_below)
+            .max(look_ahead)
+            .max(1);
+        let inner = amp_gate_create(max_size as isize);
+        if inner.is_null() {
+            return Err(FlucomaError::AllocationFailed);
+        }
+        amp_gate_init(
+            inner,
+            on_threshold,
+            off_threshold,
+            hi_pass_freq,
+            min_length_above as isize,
+            look_back as isize,
+            min_length_below as isize,
+            look_ahead as isize,
+        );
+        let latency = amp_gate_latency(inner).max(0) as usize;
+        Ok(Self {
+            inner,
+            latency,
+            on_threshold,
+            off_threshold,
+            hi_pass_freq,
+            min_length_above,
+            look_back,
+            min_length_below,
+            look_ahead,
+        })
+    }
+
+    /// Process a single audio sample.
+    ///
+    /// # Arguments
+    /// * `sample`             - The audio sample value.
+    /// * `on_threshold`       - dB level above which the gate opens.
+    /// * `off_threshold`      - dB level below which the gate closes.
+    /// * `ramp_up`            - Envelope attack time in samples.
+    /// * `ramp_down`          - Envelope release time in samples.
+    /// * `hi_pass_freq`       - Hi-pass filter frequency in Hz.
+    /// * `min_slice_length`   - Minimum samples the gate stays open.
+    /// * `min_silence_length` - Minimum samples the gate stays closed.
+    ///
+    /// Returns 1.0 while the gate is open, 0.0 otherwise, delayed by
+    /// [`AmpGate::latency_samples`] samples.
+    #[allow(clippy::too_many_arguments)]
+    pub fn process_sample(
+        &mut self,
+        sample: f64,
+        on_threshold: f64,
+        off_threshold: f64,
+        ramp_up: usize,
+        ramp_down: usize,
+        hi_pass_freq: f64,
+        min_slice_length: usize,
+        min_silence_length: usize,
+    ) -> f64 {
+        amp_gate_process_sample(
+            self.inner,
+            sample,
+            on_threshold,
+            off_threshold,
+            ramp_up as isize,
+            ramp_down as isize,
+            hi_pass_freq,
+            min_slice_length as isize,
+            min_silence_length as isize,
+        )
+    }
+
+    /// Delay of the gate output relative to the input, in samples.
+    pub fn latency_samples(&self) -> usize {
+        self.latency
+    }
+}
+
+impl Drop for AmpGate {
+    fn drop(&mut self) {
+        amp_gate_destroy(self.inner);
+    }
+}
+
+impl Clone for AmpGate {
+    fn clone(&self) -> Self {
+        Self::new(
+            self.on_threshold,
+            self.off_threshold,
+            self.hi_pass_freq,
+            self.min_length_above,
+            self.look_back,
+            self.min_length_below,
+            self.look_ahead,
+        )
+        .expect("settings were validated on construction")
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn amp_gate_silence_stays_closed() {
+        let mut gate = AmpGate::new(-30.0, -40.0, 0.0, 1, 0, 1, 0).unwrap();
+        for _ in 0..100 {
+            let val = gate.process_sample(0.0, -30.0, -40.0, 10, 10, 0.0, 1, 1);
+            assert_eq!(val, 0.0, "silence should keep the gate closed, got {val}");
+        }
+    }
+
+    #[test]
+    fn amp_gate_output_is_delayed_by_latency() {
+        let mut gate = AmpGate::new(-30.0, -40.0, 0.0, 1, 0, 1, 64).unwrap();
+        let latency = gate.latency_samples();
+        let onset = 1000;
+        let opened = (0..4000 + latency)
+            .map(|i| if (onset..3000).contains(&i) { 0.5 } else { 0.0 })
+            .map(|sample| gate.process_sample(sample, -30.0, -40.0, 1, 1, 0.0, 1, 1))
+            .position(|value| value > 0.0)
+            .expect("gate never opened");
+        assert!(opened >= latency);
+        let input_index = opened - latency;
+        assert!(
+            input_index.abs_diff(onset) <= 4,
+            "opened at input {input_index}"
+        );
+    }
+}
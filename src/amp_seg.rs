@@ -1,12 +1,62 @@
-use flucoma_sys::{amp_seg_create, amp_seg_destroy, amp_seg_init, amp_seg_process_sample};
+use flucoma_sys::{
+    amp_seg_create, amp_seg_destroy, amp_seg_init, amp_seg_process_block, amp_seg_process_sample,
+};
+
+use crate::error::FlucomaError;
+use crate::frame_analyzer::FrameAnalyzer;
+use crate::sample_f32::widen;
 
 // -------------------------------------------------------------------------------------------------
 
+/// Parameters for [`AmpSlice`]. Times are in samples, levels in dB.
+///
+/// Defaults match FluidAmpSlice.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AmpSliceConfig {
+    /// Level above which an onset is declared.
+    pub on_threshold: f64,
+    /// Level below which the slicer re-arms (must be <= `on_threshold`).
+    pub off_threshold: f64,
+    /// Noise floor; signals below this level are ignored.
+    pub floor: f64,
+    /// Fast envelope attack time.
+    pub fast_ramp_up: usize,
+    /// Slow envelope attack time.
+    pub slow_ramp_up: usize,
+    /// Fast envelope release time.
+    pub fast_ramp_down: usize,
+    /// Slow envelope release time.
+    pub slow_ramp_down: usize,
+    /// Hi-pass filter frequency in Hz applied before the follower.
+    pub hi_pass_freq: f64,
+    /// Minimum samples between successive onsets.
+    pub min_slice_length: usize,
+}
+
+impl Default for AmpSliceConfig {
+    fn default() -> Self {
+        Self {
+            on_threshold: 144.0,
+            off_threshold: -144.0,
+            floor: -144.0,
+            fast_ramp_up: 1,
+            slow_ramp_up: 100,
+            fast_ramp_down: 1,
+            slow_ramp_down: 100,
+            hi_pass_freq: 85.0,
+            min_slice_length: 2,
+        }
+    }
+}
+
 /// Amplitude-envelope-based audio segmenter, operating sample by sample.
 ///
 /// Two-phase setup:
-/// 1. [`AmpSlice::new`] -- allocates and initialises the follower.
-/// 2. Call [`AmpSlice::process_sample`] per audio sample.
+/// 1. [`AmpSlice::new`] or [`AmpSlice::with_config`] -- allocates and
+///    initialises the follower.
+/// 2. Call [`AmpSlice::process_block`] per audio block, or
+///    [`AmpSlice::process_sample`] per sample with explicit parameters.
 ///
 /// Uses a dual-ramp envelope follower with separate fast/slow attack and
 /// release times, plus on/off thresholds for hysteresis.
@@ -14,6 +64,8 @@ use flucoma_sys::{amp_seg_create, amp_seg_destroy, amp_seg_init, amp_seg_process
 /// See <https://learn.flucoma.org/reference/ampslice>
 pub struct AmpSlice {
     inner: *mut u8,
+    config: AmpSliceConfig,
+    scratch: Vec<f64>,
 }
 
 unsafe impl Send for AmpSlice {}
@@ -26,14 +78,133 @@ impl AmpSlice {
     /// * `hi_pass_freq` - Hi-pass filter frequency in Hz applied before the follower.
     ///
     /// # Errors
-    /// Returns an error string if allocation fails.
-    pub fn new(floor: f64, hi_pass_freq: f64) -> Result<Self, &'static str> {
+    /// Returns an error if allocation fails.
+    pub fn new(floor: f64, hi_pass_freq: f64) -> Result<Self, FlucomaError> {
         let inner = amp_seg_create();
         if inner.is_null() {
-            return Err("failed to create AmpSlice instance");
+            return Err(FlucomaError::AllocationFailed);
         }
         amp_seg_init(inner, floor, hi_pass_freq);
-        Ok(Self { inner })
+        Ok(Self {
+            inner,
+            config: AmpSliceConfig {
+                floor,
+                hi_pass_freq,
+                ..Default::default()
+            },
+            scratch: Vec::new(),
+        })
+    }
+
+    /// Create and initialise an envelope segmenter from a full parameter set.
+    ///
+    /// # Errors
+    /// Returns an error if the configuration is invalid or allocation
+    /// fails.
+    pub fn with_config(config: AmpSliceConfig) -> Result<Self, FlucomaError> {
+        validate_config(&config)?;
+        let mut slicer = Self::new(config.floor, config.hi_pass_freq)?;
+        slicer.config = config;
+        Ok(slicer)
+    }
+
+    pub fn config(&self) -> &AmpSliceConfig {
+        &self.config
+    }
+
+    /// Replace all parameters. Changing `floor` or `hi_pass_freq`
+    /// re-initialises the follower.
+    ///
+    /// # Errors
+    /// Returns an error if the configuration is invalid.
+    pub fn set_config(&mut self, config: AmpSliceConfig) -> Result<(), FlucomaError> {
+        validate_config(&config)?;
+        if config.floor != self.config.floor || config.hi_pass_freq != self.config.hi_pass_freq {
+            amp_seg_init(self.inner, config.floor, config.hi_pass_freq);
+        }
+        self.config = config;
+        Ok(())
+    }
+
+    /// Set the onset and re-arm thresholds in dB.
+    ///
+    /// # Errors
+    /// Returns an error if `off_threshold > on_threshold`.
+    pub fn set_thresholds(
+        &mut self,
+        on_threshold: f64,
+        off_threshold: f64,
+    ) -> Result<(), FlucomaError> {
+        self.set_config(AmpSliceConfig {
+            on_threshold,
+            off_threshold,
+            ..self.config
+        })
+    }
+
+    /// Set the fast and slow envelope attack and release times in samples.
+    pub fn set_ramps(
+        &mut self,
+        fast_ramp_up: usize,
+        slow_ramp_up: usize,
+        fast_ramp_down: usize,
+        slow_ramp_down: usize,
+    ) {
+        self.config.fast_ramp_up = fast_ramp_up;
+        self.config.slow_ramp_up = slow_ramp_up;
+        self.config.fast_ramp_down = fast_ramp_down;
+        self.config.slow_ramp_down = slow_ramp_down;
+    }
+
+    /// Set the noise floor in dB. Re-initialises the follower.
+    pub fn set_floor(&mut self, floor: f64) {
+        self.config.floor = floor;
+        amp_seg_init(self.inner, floor, self.config.hi_pass_freq);
+    }
+
+    /// Set the hi-pass filter frequency in Hz. Re-initialises the follower.
+    pub fn set_hi_pass_freq(&mut self, hi_pass_freq: f64) {
+        self.config.hi_pass_freq = hi_pass_freq;
+        amp_seg_init(self.inner, self.config.floor, hi_pass_freq);
+    }
+
+    /// Set the minimum number of samples between successive onsets.
+    pub fn set_min_slice_length(&mut self, min_slice_length: usize) {
+        self.config.min_slice_length = min_slice_length;
+    }
+
+    /// Process a block of audio with the current parameters, looping on the
+    /// C++ side.
+    ///
+    /// Returns one value per input sample: 1.0 on an onset, 0.0 otherwise.
+    pub fn process_block(&mut self, input: &[f64]) -> Vec<f64> {
+        let mut output = vec![0.0; input.len()];
+        let c = &self.config;
+        amp_seg_process_block(
+            self.inner,
+            input.as_ptr(),
+            output.as_mut_ptr(),
+            input.len() as isize,
+            c.on_threshold,
+            c.off_threshold,
+            c.floor,
+            c.fast_ramp_up as isize,
+            c.slow_ramp_up as isize,
+            c.fast_ramp_down as isize,
+            c.slow_ramp_down as isize,
+            c.hi_pass_freq,
+            c.min_slice_length as isize,
+        );
+        output
+    }
+
+    /// Like [`AmpSlice::process_block`], for f32 audio. The samples are
+    /// widened into an internal buffer that is reused across calls.
+    pub fn process_block_f32(&mut self, input: &[f32]) -> Vec<f64> {
+        let mut scratch = std::mem::take(&mut self.scratch);
+        let output = self.process_block(widen(input, &mut scratch));
+        self.scratch = scratch;
+        output
     }
 
     /// Process a single audio sample.
@@ -87,6 +258,31 @@ impl Drop for AmpSlice {
     }
 }
 
+impl Clone for AmpSlice {
+    fn clone(&self) -> Self {
+        Self::with_config(self.config).expect("settings were validated on construction")
+    }
+}
+
+impl FrameAnalyzer for AmpSlice {
+    type Input = [f64];
+    type Params = ();
+    type Output = Vec<f64>;
+
+    fn process(&mut self, input: &[f64], _params: &()) -> Vec<f64> {
+        self.process_block(input)
+    }
+}
+
+fn validate_config(config: &AmpSliceConfig) -> Result<(), FlucomaError> {
+    if config.off_threshold > config.on_threshold {
+        return Err(FlucomaError::InvalidParameter(
+            "off_threshold must be <= on_threshold",
+        ));
+    }
+    Ok(())
+}
+
 // -------------------------------------------------------------------------------------------------
 
 #[cfg(test)]
@@ -119,4 +315,31 @@ mod tests {
         }
         assert!(triggered, "loud signal should eventually trigger an onset");
     }
+
+    #[test]
+    fn env_seg_block_matches_per_sample() {
+        let config = AmpSliceConfig {
+            on_threshold: -10.0,
+            off_threshold: -40.0,
+            floor: -60.0,
+            fast_ramp_up: 1,
+            slow_ramp_up: 2,
+            fast_ramp_down: 2,
+            slow_ramp_down: 4,
+            hi_pass_freq: 20.0,
+            min_slice_length: 1,
+        };
+        let input: Vec<f64> = (0..200)
+            .map(|i| if i % 40 < 20 { 0.0 } else { 0.9 })
+            .collect();
+        let mut block = AmpSlice::with_config(config).unwrap();
+        let mut per_sample = AmpSlice::new(config.floor, config.hi_pass_freq).unwrap();
+        let expected: Vec<f64> = input
+            .iter()
+            .map(|&x| per_sample.process_sample(x, -10.0, -40.0, -60.0, 1, 2, 2, 4, 20.0, 1))
+            .collect();
+        assert_eq!(block.process_block(&input), expected);
+        assert!(block.set_thresholds(-50.0, -40.0).is_err());
+        assert_eq!(block.config().on_threshold, -10.0);
+    }
 }
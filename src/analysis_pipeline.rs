@@ -0,0 +1,425 @@
+use crate::buf_frames::{fill_frame, num_frames, validate_fft};
+use crate::dct::Dct;
+use crate::error::FlucomaError;
+use crate::fft_settings::FftSettings;
+use crate::matrix::Matrix;
+use crate::mel_bands::MelBands;
+use crate::mfcc::Mfcc;
+use crate::running_stats::RunningStats;
+use crate::stft::{Stft, WindowType};
+
+// -------------------------------------------------------------------------------------------------
+
+/// Custom stage function, see [`PipelineBuilder::map`].
+pub type StageFn = Box<dyn FnMut(&[f64]) -> Vec<f64> + Send>;
+
+/// Declarative builder for a [`Pipeline`].
+///
+/// Every pipeline starts with an STFT producing magnitude spectra
+/// (configured with [`PipelineBuilder::stft`], 1024/512/1024 Hann by
+/// default); each further stage consumes the previous stage's output vector.
+/// Dimensions are checked when the pipeline is built.
+///
+/// ```rust,no_run
+/// use flucoma_rs::pipeline::Pipeline;
+///
+/// let mut pipeline = Pipeline::builder(44100.0)
+///     .log_mel_bands(40, 20.0, 20000.0)
+///     .dct(13)
+///     .running_stats(8)
+///     .build()
+///     .unwrap();
+/// assert_eq!(pipeline.output_dims(), 26);
+///
+/// for frame in pipeline.process(&[0.0f64; 4096]) {
+///     println!("{frame:?}");
+/// }
+/// ```
+pub struct PipelineBuilder {
+    sample_rate: f64,
+    window_size: usize,
+    hop_size: usize,
+    fft_size: usize,
+    window_type: WindowType,
+    stages: Vec<StageSpec>,
+}
+
+enum StageSpec {
+    MelBands {
+        n_bands: usize,
+        lo_hz: f64,
+        hi_hz: f64,
+        log_output: bool,
+    },
+    Mfcc {
+        n_coefs: usize,
+        n_bands: usize,
+        lo_hz: f64,
+        hi_hz: f64,
+    },
+    Dct(usize),
+    RunningStats(usize),
+    Map(usize, StageFn),
+}
+
+impl PipelineBuilder {
+    /// Set the STFT that turns audio into magnitude spectra.
+    pub fn stft(
+        mut self,
+        window_size: usize,
+        hop_size: usize,
+        fft_size: usize,
+        window_type: WindowType,
+    ) -> Self {
+        self.window_size = window_size;
+        self.hop_size = hop_size;
+        self.fft_size = fft_size;
+        self.window_type = window_type;
+        self
+    }
+
+    /// Set the STFT from shared [`FftSettings`].
+    pub fn fft_settings(self, settings: &FftSettings) -> Self {
+        self.stft(
+            settings.window_size,
+            settings.hop_size,
+            settings.fft_size,
+            settings.window_type,
+        )
+    }
+
+    /// Mel band magnitudes of a magnitude spectrum.
+    pub fn mel_bands(mut self, n_bands: usize, lo_hz: f64, hi_hz: f64) -> Self {
+        self.stages.push(StageSpec::MelBands {
+            n_bands,
+            lo_hz,
+            hi_hz,
+            log_output: false,
+        });
+        self
+    }
+
+    /// Mel band levels in dB of a magnitude spectrum, as fed to the DCT of
+    /// FluCoMa's MFCC.
+    pub fn log_mel_bands(mut self, n_bands: usize, lo_hz: f64, hi_hz: f64) -> Self {
+        self.stages.push(StageSpec::MelBands {
+            n_bands,
+            lo_hz,
+            hi_hz,
+            log_output: true,
+        });
+        self
+    }
+
+    /// MFCCs of a magnitude spectrum.
+    pub fn mfcc(mut self, n_coefs: usize, n_bands: usize, lo_hz: f64, hi_hz: f64) -> Self {
+        self.stages.push(StageSpec::Mfcc {
+            n_coefs,
+            n_bands,
+            lo_hz,
+            hi_hz,
+        });
+        self
+    }
+
+    /// The first `n_coefs` [`Dct`] coefficients of the previous stage's output.
+    pub fn dct(mut self, n_coefs: usize) -> Self {
+        self.stages.push(StageSpec::Dct(n_coefs));
+        self
+    }
+
+    /// Running mean and standard deviation over the last `history_size`
+    /// outputs of the previous stage, concatenated as `[means..., stds...]`.
+    pub fn running_stats(mut self, history_size: usize) -> Self {
+        self.stages.push(StageSpec::RunningStats(history_size));
+        self
+    }
+
+    /// A custom stage returning `output_dims` values per input vector.
+    pub fn map<F>(mut self, output_dims: usize, f: F) -> Self
+    where
+        F: FnMut(&[f64]) -> Vec<f64> + Send + 'static,
+    {
+        self.stages.push(StageSpec::Map(output_dims, Box::new(f)));
+        self
+    }
+
+    /// # Errors
+    /// Returns an error if the STFT settings or a stage's parameters
+    /// are invalid, or a stage's input dimensions do not fit.
+    pub fn build(self) -> Result<Pipeline, FlucomaError> {
+        validate_fft(self.window_size, self.hop_size, self.fft_size)?;
+        let stft = Stft::new(
+            self.window_size,
+            self.fft_size,
+            self.hop_size,
+            self.window_type,
+        )?;
+        let n_bins = stft.num_bins();
+        let mut dims = n_bins;
+        let mut stages = Vec::with_capacity(self.stages.len());
+        for spec in self.stages {
+            let stage = match spec {
+                StageSpec::MelBands {
+                    n_bands,
+                    lo_hz,
+                    hi_hz,
+                    log_output,
+                } => {
+                    if dims != n_bins {
+                        return Err(FlucomaError::InvalidParameter(
+                            "mel_bands must follow the stft",
+                        ));
+                    }
+                    Stage::MelBands {
+                        bands: MelBands::new(
+                            n_bands,
+                            n_bins,
+                            lo_hz,
+                            hi_hz,
+                            self.sample_rate,
+                            self.window_size,
+                        )?,
+                        log_output,
+                    }
+                }
+                StageSpec::Mfcc {
+                    n_coefs,
+                    n_bands,
+                    lo_hz,
+                    hi_hz,
+                } => {
+                    if dims != n_bins {
+                        return Err(FlucomaError::InvalidParameter("mfcc must follow the stft"));
+                    }
+                    Stage::Mfcc(Mfcc::new(
+                        n_coefs,
+                        n_bands,
+                        n_bins,
+                        lo_hz,
+                        hi_hz,
+                        self.sample_rate,
+                        self.window_size,
+                    )?)
+                }
+                StageSpec::Dct(n_coefs) => Stage::Dct(Dct::new(dims, n_coefs)?),
+                StageSpec::RunningStats(history_size) => {
+                    Stage::RunningStats(RunningStats::new(history_size, dims)?)
+                }
+                StageSpec::Map(output_dims, f) => {
+                    if output_dims == 0 {
+                        return Err(FlucomaError::InvalidParameter(
+                            "map output_dims must be > 0",
+                        ));
+                    }
+                    Stage::Map(output_dims, f)
+                }
+            };
+            dims = stage.output_dims();
+            stages.push(stage);
+        }
+        Ok(Pipeline {
+            stft,
+            stages,
+            output_dims: dims,
+            history: vec![0.0; self.window_size],
+            until_hop: self.hop_size,
+        })
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// A chain of analysis stages run as one object.
+///
+/// [`Pipeline::process`] takes audio chunks of any length and returns one
+/// output vector per completed hop, buffering internally;
+/// [`Pipeline::process_buffer`] runs the same chain over the centred frames
+/// of a whole buffer, as the `Buf*` analysers do.
+pub struct Pipeline {
+    stft: Stft,
+    stages: Vec<Stage>,
+    output_dims: usize,
+    /// The most recent `window_size` samples, oldest first.
+    history: Vec<f64>,
+    until_hop: usize,
+}
+
+impl Pipeline {
+    /// Start building a pipeline for audio at `sample_rate`.
+    pub fn builder(sample_rate: f64) -> PipelineBuilder {
+        PipelineBuilder {
+            sample_rate,
+            window_size: 1024,
+            hop_size: 512,
+            fft_size: 1024,
+            window_type: WindowType::Hann,
+            stages: Vec::new(),
+        }
+    }
+
+    /// Consume a chunk of audio of any length and return the output of
+    /// every hop it completed, oldest first.
+    pub fn process(&mut self, chunk: &[f64]) -> Vec<Vec<f64>> {
+        let mut outputs = Vec::new();
+        let mut offset = 0;
+        while offset < chunk.len() {
+            let take = self.until_hop.min(chunk.len() - offset);
+            let len = self.history.len();
+            let samples = &chunk[offset..offset + take];
+            if take >= len {
+                self.history.copy_from_slice(&samples[take - len..]);
+            } else {
+                self.history.copy_within(take.., 0);
+                self.history[len - take..].copy_from_slice(samples);
+            }
+            offset += take;
+            self.until_hop -= take;
+            if self.until_hop == 0 {
+                self.until_hop = self.stft.hop_size();
+                let frame = std::mem::take(&mut self.history);
+                outputs.push(self.process_frame(&frame));
+                self.history = frame;
+            }
+        }
+        outputs
+    }
+
+    /// Run the pipeline over the centred frames of `signal` and return a
+    /// frames × [`output_dims`](Pipeline::output_dims) matrix. Stage state
+    /// is reset first.
+    ///
+    /// # Errors
+    /// Returns an error if `signal` is empty.
+    pub fn process_buffer(&mut self, signal: &[f64]) -> Result<Matrix, FlucomaError> {
+        if signal.is_empty() {
+            return Err(FlucomaError::InvalidParameter("signal must not be empty"));
+        }
+        self.reset();
+        let hop = self.stft.hop_size();
+        let frames = num_frames(signal.len(), hop);
+        let mut frame = vec![0.0; self.stft.window_size()];
+        let mut data = Vec::with_capacity(frames * self.output_dims);
+        for index in 0..frames {
+            fill_frame(signal, index, hop, &mut frame);
+            data.extend(self.process_frame(&frame));
+        }
+        Matrix::from_vec(data, frames, self.output_dims)
+    }
+
+    /// Run one window of audio through every stage.
+    ///
+    /// # Panics
+    /// Panics if `frame.len() != window_size`, or a custom stage returns the
+    /// wrong number of values.
+    pub fn process_frame(&mut self, frame: &[f64]) -> Vec<f64> {
+        let mut values = self.stft.process_frame(frame).magnitudes();
+        for stage in &mut self.stages {
+            values = stage.process(&values);
+        }
+        values
+    }
+
+    /// Clear buffered audio and stage history.
+    pub fn reset(&mut self) {
+        self.history.fill(0.0);
+        self.until_hop = self.stft.hop_size();
+        for stage in &mut self.stages {
+            if let Stage::RunningStats(stats) = stage {
+                stats.clear();
+            }
+        }
+    }
+
+    /// Number of values in each output vector.
+    pub fn output_dims(&self) -> usize {
+        self.output_dims
+    }
+
+    /// Hop between outputs in samples.
+    pub fn hop_size(&self) -> usize {
+        self.stft.hop_size()
+    }
+}
+
+enum Stage {
+    MelBands { bands: MelBands, log_output: bool },
+    Mfcc(Mfcc),
+    Dct(Dct),
+    RunningStats(RunningStats),
+    Map(usize, StageFn),
+}
+
+impl Stage {
+    fn process(&mut self, input: &[f64]) -> Vec<f64> {
+        match self {
+            Stage::MelBands { bands, log_output } => {
+                bands.process_frame(input, true, false, *log_output)
+            }
+            Stage::Mfcc(mfcc) => mfcc.process_frame(input),
+            Stage::Dct(dct) => dct.process_frame(input),
+            Stage::RunningStats(stats) => {
+                let (mean, std) = stats.process(input);
+                [mean, std].concat()
+            }
+            Stage::Map(dims, f) => {
+                let output = f(input);
+                assert_eq!(
+                    output.len(),
+                    *dims,
+                    "map stage returned {} values, expected {}",
+                    output.len(),
+                    dims
+                );
+                output
+            }
+        }
+    }
+
+    fn output_dims(&self) -> usize {
+        match self {
+            Stage::MelBands { bands, .. } => bands.n_bands(),
+            Stage::Mfcc(mfcc) => mfcc.n_coefs(),
+            Stage::Dct(dct) => dct.n_coefs(),
+            Stage::RunningStats(stats) => 2 * stats.input_size(),
+            Stage::Map(dims, _) => *dims,
+        }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pipeline_stream_matches_frames() {
+        let build = || {
+            Pipeline::builder(44100.0)
+                .stft(512, 256, 512, WindowType::Hann)
+                .mel_bands(20, 20.0, 8000.0)
+                .map(1, |bands| vec![bands.iter().sum()])
+                .build()
+                .unwrap()
+        };
+        let audio: Vec<f64> = (0..4096).map(|i| (i as f64 * 0.05).sin()).collect();
+        let mut streamed = build();
+        assert_eq!(streamed.output_dims(), 1);
+        let mut outputs = Vec::new();
+        for chunk in audio.chunks(100) {
+            outputs.extend(streamed.process(chunk));
+        }
+        assert_eq!(outputs.len(), audio.len() / 256);
+
+        let mut direct = build();
+        let expected = direct.process_frame(&audio[256 * 3..256 * 5]);
+        assert_eq!(outputs[4], expected);
+        assert!(Pipeline::builder(44100.0).dct(13).build().is_ok());
+        assert!(Pipeline::builder(44100.0)
+            .mel_bands(10, 20.0, 8000.0)
+            .mfcc(13, 40, 20.0, 8000.0)
+            .build()
+            .is_err());
+    }
+}
@@ -0,0 +1,342 @@
+//! One-call characterisation of a mono audio buffer.
+
+use crate::error::FlucomaError;
+use crate::fourier::{Stft, WindowType};
+use crate::loudness::Loudness;
+use crate::onset::OnsetFunction;
+use crate::segmentation::OnsetSlice;
+use crate::sine::{Sine, SortBy};
+use crate::tempo::{TempoConfig, TempoEstimator};
+
+const WINDOW_SIZE: usize = 1024;
+const HOP_SIZE: usize = 512;
+/// Momentary loudness window and hop as per ITU-R BS.1770, in seconds.
+const LOUDNESS_WINDOW_SECONDS: f64 = 0.4;
+const LOUDNESS_HOP_SECONDS: f64 = 0.1;
+const ONSET_THRESHOLD: f64 = 0.5;
+const ONSET_FILTER_SIZE: usize = 5;
+const ONSET_DEBOUNCE: usize = 2;
+/// Sinusoidal peaks below this level (dB) do not contribute to the pitch
+/// histogram.
+const PITCH_PEAK_THRESHOLD_DB: f64 = -60.0;
+
+// -------------------------------------------------------------------------------------------------
+
+/// Mean, standard deviation and range of a per-frame descriptor.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SummaryStats {
+    /// Number of frames the statistics were computed over. All other fields
+    /// are zero when this is zero.
+    pub frames: usize,
+    pub mean: f64,
+    /// Population standard deviation.
+    pub std: f64,
+    pub min: f64,
+    pub max: f64,
+}
+
+impl SummaryStats {
+    fn from_values(values: &[f64]) -> Self {
+        if values.is_empty() {
+            return Self::default();
+        }
+        let n = values.len() as f64;
+        let mean = values.iter().sum::<f64>() / n;
+        let var = values.iter().map(|v| (v - mean) * (v - mean)).sum::<f64>() / n;
+        Self {
+            frames: values.len(),
+            mean,
+            std: var.sqrt(),
+            min: values.iter().copied().fold(f64::INFINITY, f64::min),
+            max: values.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+        }
+    }
+}
+
+/// Spectral shape summary over all non-silent STFT frames.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SpectralSummary {
+    /// Magnitude-weighted mean frequency, in Hz.
+    pub centroid_hz: SummaryStats,
+    /// Frequency below which 95% of the spectral energy lies, in Hz.
+    pub rolloff_hz: SummaryStats,
+    /// Ratio of geometric to arithmetic mean of the power spectrum, in
+    /// `[0, 1]`. Close to 1 for noise, close to 0 for tonal content.
+    pub flatness: SummaryStats,
+}
+
+/// Structured characterisation of a sound, produced by [`analyze`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AnalysisReport {
+    pub sample_rate: f64,
+    pub duration_seconds: f64,
+    /// Gated integrated loudness (K-weighted) in dB, or `None` when every
+    /// block falls below the -70 dB absolute gate.
+    pub integrated_loudness_db: Option<f64>,
+    /// Maximum true peak in dBFS.
+    pub true_peak_db: f64,
+    /// Estimated tempo in BPM, or `None` when too few onsets were found.
+    pub tempo_bpm: Option<f64>,
+    /// Number of detected onsets.
+    pub onset_count: usize,
+    /// Onsets per second.
+    pub onset_density: f64,
+    /// Distribution of the strongest sinusoidal peak per frame over the 12
+    /// pitch classes, starting at C. Sums to 1, or is all zeros when no frame
+    /// had a peak above -60 dB.
+    pub pitch_class_histogram: [f64; 12],
+    pub spectral: SpectralSummary,
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Analyze a mono buffer and summarise loudness, peak, tempo, onsets, pitch
+/// content and spectral shape.
+///
+/// Uses 1024-sample windows with a hop of 512 for spectral, onset and pitch
+/// analysis, and 400 ms blocks with a hop of 100 ms for loudness.
+///
+/// ```rust,no_run
+/// use flucoma_rs::report;
+///
+/// let audio = vec![0.0f64; 44100 * 4];
+/// let report = report::analyze(&audio, 44100.0).unwrap();
+/// println!("{:.1} dB, {:?} BPM", report.true_peak_db, report.tempo_bpm);
+/// ```
+///
+/// # Errors
+/// Returns an error if `sample_rate <= 0` or the buffer is shorter than one
+/// 1024-sample analysis window.
+pub fn analyze(buffer: &[f64], sample_rate: f64) -> Result<AnalysisReport, FlucomaError> {
+    if sample_rate <= 0.0 {
+        return Err(FlucomaError::InvalidParameter("sample_rate must be > 0"));
+    }
+    if buffer.len() < WINDOW_SIZE {
+        return Err(FlucomaError::InvalidParameter(
+            "buffer shorter than analysis window",
+        ));
+    }
+    let duration_seconds = buffer.len() as f64 / sample_rate;
+
+    let (block_loudness, true_peak_db) = loudness_blocks(buffer, sample_rate)?;
+    let onset_count = count_onsets(buffer)?;
+    let (pitch_class_histogram, spectral) = spectral_analysis(buffer, sample_rate)?;
+    let tempo_bpm = TempoEstimator::new(TempoConfig::default())
+        .estimate(buffer, buffer.len(), 1, sample_rate)
+        .ok();
+
+    Ok(AnalysisReport {
+        sample_rate,
+        duration_seconds,
+        integrated_loudness_db: integrated_loudness(&block_loudness),
+        true_peak_db,
+        tempo_bpm,
+        onset_count,
+        onset_density: onset_count as f64 / duration_seconds,
+        pitch_class_histogram,
+        spectral,
+    })
+}
+
+/// Per-block K-weighted loudness and the overall true peak.
+fn loudness_blocks(buffer: &[f64], sample_rate: f64) -> Result<(Vec<f64>, f64), FlucomaError> {
+    let window = ((LOUDNESS_WINDOW_SECONDS * sample_rate) as usize).clamp(1, buffer.len());
+    let hop = ((LOUDNESS_HOP_SECONDS * sample_rate) as usize).max(1);
+    let mut loudness = Loudness::new(window, sample_rate)?;
+    let mut blocks = Vec::new();
+    let mut peak = f64::NEG_INFINITY;
+    let mut start = 0;
+    while start + window <= buffer.len() {
+        let result = loudness.process_frame(&buffer[start..start + window], true, true);
+        blocks.push(result.loudness_db);
+        peak = peak.max(result.peak_db);
+        start += hop;
+    }
+    Ok((blocks, peak))
+}
+
+/// BS.1770 gating: drop blocks below -70 dB, then blocks more than 10 dB
+/// below the mean of the remainder.
+fn integrated_loudness(blocks_db: &[f64]) -> Option<f64> {
+    let mean_db = |blocks: &[f64]| {
+        let power = blocks.iter().map(|l| 10f64.powf(l / 10.0)).sum::<f64>() / blocks.len() as f64;
+        10.0 * power.log10()
+    };
+    let gated: Vec<f64> = blocks_db.iter().copied().filter(|&l| l > -70.0).collect();
+    if gated.is_empty() {
+        return None;
+    }
+    let relative_gate = mean_db(&gated) - 10.0;
+    let gated: Vec<f64> = gated.into_iter().filter(|&l| l > relative_gate).collect();
+    Some(mean_db(&gated))
+}
+
+fn count_onsets(buffer: &[f64]) -> Result<usize, FlucomaError> {
+    let mut slicer = OnsetSlice::new(WINDOW_SIZE, WINDOW_SIZE, ONSET_FILTER_SIZE)?;
+    let count = buffer
+        .windows(WINDOW_SIZE)
+        .step_by(HOP_SIZE)
+        .filter(|frame| {
+            slicer.process_frame(
+                frame,
+                OnsetFunction::PowerSpectrum,
+                ONSET_FILTER_SIZE,
+                ONSET_THRESHOLD,
+                ONSET_DEBOUNCE,
+                0,
+            ) > 0.0
+        })
+        .count();
+    Ok(count)
+}
+
+fn spectral_analysis(
+    buffer: &[f64],
+    sample_rate: f64,
+) -> Result<([f64; 12], SpectralSummary), FlucomaError> {
+    let mut stft = Stft::new(WINDOW_SIZE, WINDOW_SIZE, HOP_SIZE, WindowType::Hann)?;
+    let mut sine = Sine::new(WINDOW_SIZE, WINDOW_SIZE)?;
+    let bin_hz = sample_rate / WINDOW_SIZE as f64;
+
+    let mut histogram = [0.0; 12];
+    let mut centroids = Vec::new();
+    let mut rolloffs = Vec::new();
+    let mut flatnesses = Vec::new();
+    let mut freqs = [0.0; 8];
+    let mut mags = [0.0; 8];
+    for frame in buffer.windows(WINDOW_SIZE).step_by(HOP_SIZE) {
+        let spectrum = stft.process_frame(frame);
+        if let Some(shape) = spectral_shape(&spectrum.magnitudes(), bin_hz) {
+            centroids.push(shape.0);
+            rolloffs.push(shape.1);
+            flatnesses.push(shape.2);
+        }
+        let peaks = sine.process_frame(
+            &spectrum.bins,
+            &mut freqs,
+            &mut mags,
+            sample_rate,
+            PITCH_PEAK_THRESHOLD_DB,
+            SortBy::Magnitude,
+        );
+        if peaks > 0 {
+            if let Some(class) = pitch_class(freqs[0]) {
+                histogram[class] += 1.0;
+            }
+        }
+    }
+
+    let total: f64 = histogram.iter().sum();
+    if total > 0.0 {
+        histogram.iter_mut().for_each(|h| *h /= total);
+    }
+    let spectral = SpectralSummary {
+        centroid_hz: SummaryStats::from_values(&centroids),
+        rolloff_hz: SummaryStats::from_values(&rolloffs),
+        flatness: SummaryStats::from_values(&flatnesses),
+    };
+    Ok((histogram, spectral))
+}
+
+/// `(centroid_hz, rolloff_hz, flatness)` of one magnitude spectrum, or `None`
+/// for a silent frame.
+fn spectral_shape(magnitudes: &[f64], bin_hz: f64) -> Option<(f64, f64, f64)> {
+    let mag_sum: f64 = magnitudes.iter().sum();
+    let power_sum: f64 = magnitudes.iter().map(|m| m * m).sum();
+    if mag_sum <= 0.0 || power_sum <= 0.0 {
+        return None;
+    }
+    let centroid = magnitudes
+        .iter()
+        .enumerate()
+        .map(|(k, m)| k as f64 * bin_hz * m)
+        .sum::<f64>()
+        / mag_sum;
+
+    let mut cumulative = 0.0;
+    let mut rolloff_bin = magnitudes.len() - 1;
+    for (k, m) in magnitudes.iter().enumerate() {
+        cumulative += m * m;
+        if cumulative >= 0.95 * power_sum {
+            rolloff_bin = k;
+            break;
+        }
+    }
+
+    let n = magnitudes.len() as f64;
+    let log_mean = magnitudes
+        .iter()
+        .map(|m| (m * m).max(f64::MIN_POSITIVE).ln())
+        .sum::<f64>()
+        / n;
+    let flatness = log_mean.exp() / (power_sum / n);
+    Some((centroid, rolloff_bin as f64 * bin_hz, flatness))
+}
+
+/// Pitch class (0 = C) of the nearest equal-tempered note, ignoring
+/// sub-audio frequencies.
+fn pitch_class(freq_hz: f64) -> Option<usize> {
+    if freq_hz < 20.0 || !freq_hz.is_finite() {
+        return None;
+    }
+    let midi = 69.0 + 12.0 * (freq_hz / 440.0).log2();
+    Some((midi.round() as i64).rem_euclid(12) as usize)
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn integrated_loudness_applies_gates() {
+        assert_eq!(integrated_loudness(&[-80.0, -90.0]), None);
+        // -40 is more than 10 dB below the mean of the louder blocks
+        let l = integrated_loudness(&[-20.0, -20.0, -40.0]).unwrap();
+        assert!((l + 20.0).abs() < 1e-9, "l = {l}");
+    }
+
+    #[test]
+    fn spectral_shape_and_pitch_helpers() {
+        let mut mags = vec![0.0; 513];
+        mags[10] = 1.0;
+        let (centroid, rolloff, flatness) = spectral_shape(&mags, 43.0).unwrap();
+        assert!((centroid - 430.0).abs() < 1e-9);
+        assert!((rolloff - 430.0).abs() < 1e-9);
+        assert!(flatness < 1e-6);
+        assert!(spectral_shape(&[0.0; 4], 43.0).is_none());
+
+        assert_eq!(pitch_class(440.0), Some(9));
+        assert_eq!(pitch_class(261.63), Some(0));
+        assert_eq!(pitch_class(5.0), None);
+    }
+
+    #[test]
+    fn analyze_sine_with_clicks() {
+        use std::f64::consts::PI;
+        let sr = 44100.0;
+        let mut audio: Vec<f64> = (0..44100 * 2)
+            .map(|i| 0.5 * (2.0 * PI * 440.0 * i as f64 / sr).sin())
+            .collect();
+        for click in (0..audio.len()).step_by(22050) {
+            audio[click] = 1.0;
+        }
+        let report = analyze(&audio, sr).unwrap();
+        assert!((report.duration_seconds - 2.0).abs() < 1e-9);
+        assert!(report.integrated_loudness_db.is_some());
+        assert!(report.true_peak_db > -1.0);
+        let dominant = report
+            .pitch_class_histogram
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .map(|(class, _)| class);
+        assert_eq!(dominant, Some(9));
+        assert!(report.spectral.centroid_hz.frames > 0);
+        assert!(analyze(&audio[..100], sr).is_err());
+    }
+}
@@ -0,0 +1,243 @@
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+
+use crate::error::FlucomaError;
+
+// -------------------------------------------------------------------------------------------------
+
+/// Input of a job submitted to an [`AnalysisWorker`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum AnalysisInput {
+    /// Samples to analyse.
+    Buffer(Vec<f64>),
+    /// A file, read on the worker thread by its loader.
+    File(PathBuf),
+}
+
+/// Result of one job, from [`AnalysisWorker::try_recv`] or
+/// [`AnalysisWorker::recv`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnalysisOutput<R> {
+    /// Id returned when the job was submitted.
+    pub id: u64,
+    pub result: Result<R, FlucomaError>,
+}
+
+/// Analysis on a dedicated background thread.
+///
+/// The worker thread creates and owns the analysers, so they never have to
+/// cross threads, and processes submitted jobs in order. Submitting and
+/// polling never wait for FFI work, so both are safe to call from a UI or
+/// audio thread; results stream back as each job completes. Dropping the
+/// worker stops its thread after the job in progress, without waiting.
+///
+/// ```rust,no_run
+/// use flucoma_rs::analyzation::Loudness;
+/// use flucoma_rs::asynchronous::AnalysisWorker;
+///
+/// let mut worker = AnalysisWorker::spawn(
+///     || Loudness::new(1024, 44100.0),
+///     |meter, samples| Ok(meter.process_frame(&samples[..1024], true, true).loudness_db),
+/// )
+/// .unwrap();
+/// let id = worker.submit(vec![0.0; 1024]).unwrap();
+/// // ... later, e.g. once per UI frame:
+/// while let Some(output) = worker.try_recv() {
+///     println!("job {}: {:?}", output.id, output.result);
+/// }
+/// ```
+pub struct AnalysisWorker<R> {
+    jobs: Sender<(u64, AnalysisInput)>,
+    results: Receiver<AnalysisOutput<R>>,
+    next_id: u64,
+    in_flight: usize,
+}
+
+impl<R: Send + 'static> AnalysisWorker<R> {
+    /// Start a worker for in-memory buffers. `make` creates the analyser on
+    /// the worker thread and `analyze` runs one job with it.
+    ///
+    /// File jobs fail with [`FlucomaError::InvalidParameter`]; use
+    /// [`AnalysisWorker::spawn_with_loader`] to accept them.
+    ///
+    /// # Errors
+    /// Returns the error of `make`, or [`FlucomaError::Io`] if the thread cannot be started.
+    pub fn spawn<A, M, F>(make: M, analyze: F) -> Result<Self, FlucomaError>
+    where
+        M: FnOnce() -> Result<A, FlucomaError> + Send + 'static,
+        F: FnMut(&mut A, &[f64]) -> Result<R, FlucomaError> + Send + 'static,
+    {
+        Self::spawn_with_loader(
+            make,
+            |_: &Path| {
+                Err(FlucomaError::InvalidParameter(
+                    "worker was spawned without a file loader",
+                ))
+            },
+            analyze,
+        )
+    }
+
+    /// Like [`AnalysisWorker::spawn`], also accepting file jobs, which are
+    /// read into samples by `load` on the worker thread.
+    ///
+    /// # Errors
+    /// See [`AnalysisWorker::spawn`].
+    pub fn spawn_with_loader<A, M, L, F>(
+        make: M,
+        mut load: L,
+        mut analyze: F,
+    ) -> Result<Self, FlucomaError>
+    where
+        M: FnOnce() -> Result<A, FlucomaError> + Send + 'static,
+        L: FnMut(&Path) -> Result<Vec<f64>, FlucomaError> + Send + 'static,
+        F: FnMut(&mut A, &[f64]) -> Result<R, FlucomaError> + Send + 'static,
+    {
+        let (job_tx, job_rx) = mpsc::channel::<(u64, AnalysisInput)>();
+        let (result_tx, result_rx) = mpsc::channel();
+        let (ready_tx, ready_rx) = mpsc::sync_channel(1);
+        thread::Builder::new()
+            .name("flucoma-analysis".into())
+            .spawn(move || {
+                let mut analyzer = match make() {
+                    Ok(analyzer) => {
+                        let _ = ready_tx.send(Ok(()));
+                        analyzer
+                    }
+                    Err(err) => {
+                        let _ = ready_tx.send(Err(err));
+                        return;
+                    }
+                };
+                for (id, input) in job_rx {
+                    let result = match input {
+                        AnalysisInput::Buffer(samples) => analyze(&mut analyzer, &samples),
+                        AnalysisInput::File(path) => {
+                            load(&path).and_then(|samples| analyze(&mut analyzer, &samples))
+                        }
+                    };
+                    if result_tx.send(AnalysisOutput { id, result }).is_err() {
+                        break;
+                    }
+                }
+            })
+            .map_err(|err| FlucomaError::Io(err.to_string()))?;
+        match ready_rx.recv() {
+            Ok(Ok(())) => Ok(Self {
+                jobs: job_tx,
+                results: result_rx,
+                next_id: 0,
+                in_flight: 0,
+            }),
+            Ok(Err(err)) => Err(err),
+            Err(_) => Err(FlucomaError::InvalidParameter("analyser creation panicked")),
+        }
+    }
+}
+
+impl<R> AnalysisWorker<R> {
+    /// Queue samples for analysis and return the job id. Does not block.
+    ///
+    /// # Errors
+    /// Returns [`FlucomaError::Cancelled`] if the worker thread has stopped,
+    /// e.g. after a panic in the analysis.
+    pub fn submit(&mut self, samples: Vec<f64>) -> Result<u64, FlucomaError> {
+        self.submit_input(AnalysisInput::Buffer(samples))
+    }
+
+    /// Queue a file for analysis and return the job id. Does not block.
+    ///
+    /// # Errors
+    /// See [`AnalysisWorker::submit`].
+    pub fn submit_file<P: Into<PathBuf>>(&mut self, path: P) -> Result<u64, FlucomaError> {
+        self.submit_input(AnalysisInput::File(path.into()))
+    }
+
+    /// Queue a job and return its id. Does not block.
+    ///
+    /// # Errors
+    /// See [`AnalysisWorker::submit`].
+    pub fn submit_input(&mut self, input: AnalysisInput) -> Result<u64, FlucomaError> {
+        let id = self.next_id;
+        self.jobs
+            .send((id, input))
+            .map_err(|_| FlucomaError::Cancelled)?;
+        self.next_id += 1;
+        self.in_flight += 1;
+        Ok(id)
+    }
+
+    /// The next finished result, if any. Does not block.
+    pub fn try_recv(&mut self) -> Option<AnalysisOutput<R>> {
+        let output = self.results.try_recv().ok()?;
+        self.in_flight -= 1;
+        Some(output)
+    }
+
+    /// Wait for the next result. Returns `None` if no jobs are in flight or
+    /// the worker thread has stopped.
+    pub fn recv(&mut self) -> Option<AnalysisOutput<R>> {
+        if self.in_flight == 0 {
+            return None;
+        }
+        let output = self.results.recv().ok()?;
+        self.in_flight -= 1;
+        Some(output)
+    }
+
+    /// Number of submitted jobs whose results have not been received yet.
+    pub fn in_flight(&self) -> usize {
+        self.in_flight
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn analysis_worker_streams_results_in_order() {
+        let mut worker = AnalysisWorker::spawn(
+            || Ok(0.0f64),
+            |total, samples| {
+                *total += samples.iter().sum::<f64>();
+                Ok(*total)
+            },
+        )
+        .unwrap();
+        assert_eq!(worker.submit(vec![1.0, 2.0]).unwrap(), 0);
+        assert_eq!(worker.submit(vec![3.0]).unwrap(), 1);
+        worker.submit_file("missing.wav").unwrap();
+        assert_eq!(worker.in_flight(), 3);
+
+        let results: Vec<_> = std::iter::from_fn(|| worker.recv()).collect();
+        assert_eq!(
+            results[0],
+            AnalysisOutput {
+                id: 0,
+                result: Ok(3.0)
+            }
+        );
+        assert_eq!(
+            results[1],
+            AnalysisOutput {
+                id: 1,
+                result: Ok(6.0)
+            }
+        );
+        assert!(results[2].result.is_err());
+        assert_eq!(worker.in_flight(), 0);
+
+        let failed = AnalysisWorker::<()>::spawn(
+            || Err::<(), _>(FlucomaError::InvalidParameter("bad settings")),
+            |_, _| Ok(()),
+        );
+        assert_eq!(
+            failed.err(),
+            Some(FlucomaError::InvalidParameter("bad settings"))
+        );
+    }
+}
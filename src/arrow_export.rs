@@ -0,0 +1,136 @@
+use std::path::Path;
+
+#[cfg(feature = "arrow")]
+use std::sync::Arc;
+
+#[cfg(feature = "arrow")]
+use arrow_array::{ArrayRef, Float64Array, RecordBatch, StringArray};
+#[cfg(feature = "arrow")]
+use arrow_schema::{DataType, Field, Schema};
+
+use crate::dataset::DataSet;
+use crate::error::FlucomaError;
+use crate::feature_flags::require;
+#[cfg(feature = "arrow")]
+use crate::matrix::Matrix;
+
+// -------------------------------------------------------------------------------------------------
+
+/// Convert a data set to an Arrow record batch with a Utf8 `id` column
+/// followed by one Float64 column per dimension, named after the data set's
+/// columns.
+///
+/// Requires the `arrow` feature.
+#[cfg(feature = "arrow")]
+pub fn dataset_to_record_batch(dataset: &DataSet) -> Result<RecordBatch, FlucomaError> {
+    let ids: ArrayRef = Arc::new(StringArray::from_iter_values(dataset.ids()));
+    let mut fields = vec![Field::new("id", DataType::Utf8, false)];
+    let mut columns = vec![ids];
+    append_columns(
+        &mut fields,
+        &mut columns,
+        dataset.data(),
+        dataset.column_names(),
+    );
+    record_batch(fields, columns)
+}
+
+/// Convert a matrix to an Arrow record batch with one Float64 column per
+/// matrix column, named `c0`, `c1`, ...
+///
+/// Requires the `arrow` feature.
+#[cfg(feature = "arrow")]
+pub fn matrix_to_record_batch(matrix: &Matrix) -> Result<RecordBatch, FlucomaError> {
+    let names: Vec<String> = (0..matrix.cols()).map(|c| format!("c{c}")).collect();
+    let mut fields = Vec::with_capacity(matrix.cols());
+    let mut columns = Vec::with_capacity(matrix.cols());
+    append_columns(&mut fields, &mut columns, matrix.data(), &names);
+    record_batch(fields, columns)
+}
+
+/// Write a data set to a Parquet file at `path`, using the layout of
+/// [`dataset_to_record_batch`].
+///
+/// # Errors
+/// Returns [`FlucomaError::FeatureDisabled`] if the `arrow` feature is not
+/// enabled, or [`FlucomaError::Io`] if the file cannot be written.
+pub fn write_parquet<P: AsRef<Path>>(dataset: &DataSet, path: P) -> Result<(), FlucomaError> {
+    require("arrow")?;
+    #[cfg(feature = "arrow")]
+    {
+        use parquet::arrow::ArrowWriter;
+
+        let batch = dataset_to_record_batch(dataset)?;
+        let file = std::fs::File::create(path).map_err(|e| FlucomaError::Io(e.to_string()))?;
+        let mut writer = ArrowWriter::try_new(file, batch.schema(), None)
+            .map_err(|e| FlucomaError::Io(e.to_string()))?;
+        writer
+            .write(&batch)
+            .map_err(|e| FlucomaError::Io(e.to_string()))?;
+        writer
+            .close()
+            .map_err(|e| FlucomaError::Io(e.to_string()))?;
+    }
+    #[cfg(not(feature = "arrow"))]
+    let _ = (dataset, path);
+    Ok(())
+}
+
+/// De-interleave row-major `data` into one Float64 column per name.
+#[cfg(feature = "arrow")]
+fn append_columns(
+    fields: &mut Vec<Field>,
+    columns: &mut Vec<ArrayRef>,
+    data: &[f64],
+    names: &[String],
+) {
+    let cols = names.len();
+    for (c, name) in names.iter().enumerate() {
+        let values: Float64Array = data.iter().skip(c).step_by(cols).copied().collect();
+        fields.push(Field::new(name.as_str(), DataType::Float64, false));
+        columns.push(Arc::new(values));
+    }
+}
+
+#[cfg(feature = "arrow")]
+fn record_batch(fields: Vec<Field>, columns: Vec<ArrayRef>) -> Result<RecordBatch, FlucomaError> {
+    RecordBatch::try_new(Arc::new(Schema::new(fields)), columns)
+        .map_err(|e| FlucomaError::Io(e.to_string()))
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "arrow")]
+    #[test]
+    fn dataset_record_batch_has_id_and_value_columns() {
+        use arrow_array::Array;
+
+        let m = Matrix::from_vec(vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0], 2, 3).unwrap();
+        let ds = DataSet::from_matrix(&["a", "b"], &m).unwrap();
+        let batch = dataset_to_record_batch(&ds).unwrap();
+        assert_eq!(batch.num_rows(), 2);
+        assert_eq!(batch.num_columns(), 4);
+        assert_eq!(batch.schema().field(2).name(), "c1");
+        let c1 = batch
+            .column(2)
+            .as_any()
+            .downcast_ref::<Float64Array>()
+            .unwrap();
+        assert_eq!(c1.values(), &[2.0, 5.0]);
+        assert_eq!(c1.len(), 2);
+    }
+
+    #[cfg(not(feature = "arrow"))]
+    #[test]
+    fn write_parquet_requires_feature() {
+        let ds = DataSet::new(1).unwrap();
+        assert_eq!(
+            write_parquet(&ds, "unused.parquet").unwrap_err(),
+            FlucomaError::FeatureDisabled("arrow")
+        );
+    }
+}
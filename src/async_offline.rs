@@ -0,0 +1,258 @@
+use std::sync::Arc;
+
+use crate::buf_amp_gate::BufAmpGate;
+use crate::buf_chroma::BufChroma;
+use crate::buf_loudness::BufLoudness;
+use crate::buf_mel_bands::BufMelBands;
+use crate::buf_mfcc::BufMfcc;
+use crate::buf_nmf::{BufNmf, BufNmfResult};
+use crate::buf_novelty_seg::BufNoveltySlice;
+use crate::buf_sines::{BufSines, BufSinesResult};
+use crate::buf_spectral_shape::BufSpectralShape;
+use crate::buf_transient_seg::BufTransientSlice;
+use crate::buf_transients::{BufTransients, BufTransientsResult};
+use crate::error::FlucomaError;
+use crate::feature_matrix::FeatureMatrix;
+
+// -------------------------------------------------------------------------------------------------
+
+/// Run a heavy offline job on tokio's blocking thread pool and await its
+/// result, so async GUI or server code is never stalled by FFI work.
+///
+/// The `process_async` methods of the `Buf*` processors are built on this;
+/// use it directly for custom jobs, e.g. several analyses of one file.
+/// Must be awaited within a tokio runtime.
+///
+/// Requires the `tokio` feature; without it this returns
+/// [`FlucomaError::FeatureDisabled`].
+///
+/// ```rust,no_run
+/// use flucoma_rs::analyzation::{BufMfcc, BufMfccConfig};
+///
+/// # async fn example() -> Result<(), flucoma_rs::FlucomaError> {
+/// let mfcc = BufMfcc::new(BufMfccConfig::default()).unwrap();
+/// let signal = vec![0.0f64; 44100];
+/// let features = mfcc.process_async(signal, 44100.0).await?;
+/// println!("{} frames", features.num_frames());
+/// # Ok(())
+/// # }
+/// ```
+///
+/// # Errors
+/// Returns the job's error, or [`FlucomaError::Cancelled`] if the runtime shut down before the job ran.
+/// A panic in the job is resumed in the awaiting task.
+pub async fn run_blocking<T, F>(job: F) -> Result<T, FlucomaError>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T, FlucomaError> + Send + 'static,
+{
+    #[cfg(feature = "tokio")]
+    {
+        match tokio::task::spawn_blocking(job).await {
+            Ok(result) => result,
+            Err(err) if err.is_panic() => std::panic::resume_unwind(err.into_panic()),
+            Err(_) => Err(FlucomaError::Cancelled),
+        }
+    }
+    #[cfg(not(feature = "tokio"))]
+    {
+        let _ = job;
+        Err(FlucomaError::FeatureDisabled("tokio"))
+    }
+}
+
+impl BufAmpGate {
+    /// Like [`BufAmpGate::process`], run on tokio's blocking thread pool.
+    ///
+    /// # Errors
+    /// As for [`run_blocking`].
+    pub async fn process_async(
+        &self,
+        signal: impl Into<Arc<[f64]>>,
+    ) -> Result<Vec<(usize, usize)>, FlucomaError> {
+        let (this, signal) = (self.clone(), signal.into());
+        run_blocking(move || this.process(&signal)).await
+    }
+}
+
+impl BufChroma {
+    /// Like [`BufChroma::process`], run on tokio's blocking thread pool.
+    ///
+    /// # Errors
+    /// As for [`run_blocking`].
+    pub async fn process_async(
+        &self,
+        signal: impl Into<Arc<[f64]>>,
+        sample_rate: f64,
+    ) -> Result<FeatureMatrix, FlucomaError> {
+        let (this, signal) = (self.clone(), signal.into());
+        run_blocking(move || this.process(&signal, sample_rate)).await
+    }
+}
+
+impl BufLoudness {
+    /// Like [`BufLoudness::process`], run on tokio's blocking thread pool.
+    ///
+    /// # Errors
+    /// As for [`run_blocking`].
+    pub async fn process_async(
+        &self,
+        signal: impl Into<Arc<[f64]>>,
+        sample_rate: f64,
+    ) -> Result<FeatureMatrix, FlucomaError> {
+        let (this, signal) = (self.clone(), signal.into());
+        run_blocking(move || this.process(&signal, sample_rate)).await
+    }
+}
+
+impl BufMelBands {
+    /// Like [`BufMelBands::process`], run on tokio's blocking thread pool.
+    ///
+    /// # Errors
+    /// As for [`run_blocking`].
+    pub async fn process_async(
+        &self,
+        signal: impl Into<Arc<[f64]>>,
+        sample_rate: f64,
+    ) -> Result<FeatureMatrix, FlucomaError> {
+        let (this, signal) = (self.clone(), signal.into());
+        run_blocking(move || this.process(&signal, sample_rate)).await
+    }
+}
+
+impl BufMfcc {
+    /// Like [`BufMfcc::process`], run on tokio's blocking thread pool.
+    ///
+    /// # Errors
+    /// As for [`run_blocking`].
+    pub async fn process_async(
+        &self,
+        signal: impl Into<Arc<[f64]>>,
+        sample_rate: f64,
+    ) -> Result<FeatureMatrix, FlucomaError> {
+        let (this, signal) = (self.clone(), signal.into());
+        run_blocking(move || this.process(&signal, sample_rate)).await
+    }
+}
+
+impl BufNmf {
+    /// Like [`BufNmf::process`], run on tokio's blocking thread pool.
+    ///
+    /// # Errors
+    /// As for [`run_blocking`].
+    pub async fn process_async(
+        &self,
+        signal: impl Into<Arc<[f64]>>,
+    ) -> Result<BufNmfResult, FlucomaError> {
+        let (this, signal) = (self.clone(), signal.into());
+        run_blocking(move || this.process(&signal)).await
+    }
+}
+
+impl BufNoveltySlice {
+    /// Like [`BufNoveltySlice::process`], run on tokio's blocking thread pool.
+    ///
+    /// # Errors
+    /// As for [`run_blocking`].
+    pub async fn process_async(
+        &self,
+        signal: impl Into<Arc<[f64]>>,
+        sample_rate: f64,
+    ) -> Result<Vec<usize>, FlucomaError> {
+        let (this, signal) = (self.clone(), signal.into());
+        run_blocking(move || this.process(&signal, sample_rate)).await
+    }
+}
+
+impl BufSines {
+    /// Like [`BufSines::process`], run on tokio's blocking thread pool.
+    ///
+    /// # Errors
+    /// As for [`run_blocking`].
+    pub async fn process_async(
+        &self,
+        signal: impl Into<Arc<[f64]>>,
+        sample_rate: f64,
+    ) -> Result<BufSinesResult, FlucomaError> {
+        let (this, signal) = (self.clone(), signal.into());
+        run_blocking(move || this.process(&signal, sample_rate)).await
+    }
+}
+
+impl BufSpectralShape {
+    /// Like [`BufSpectralShape::process`], run on tokio's blocking thread pool.
+    ///
+    /// # Errors
+    /// As for [`run_blocking`].
+    pub async fn process_async(
+        &self,
+        signal: impl Into<Arc<[f64]>>,
+        sample_rate: f64,
+    ) -> Result<FeatureMatrix, FlucomaError> {
+        let (this, signal) = (self.clone(), signal.into());
+        run_blocking(move || this.process(&signal, sample_rate)).await
+    }
+}
+
+impl BufTransientSlice {
+    /// Like [`BufTransientSlice::process`], run on tokio's blocking thread pool.
+    ///
+    /// # Errors
+    /// As for [`run_blocking`].
+    pub async fn process_async(
+        &self,
+        signal: impl Into<Arc<[f64]>>,
+    ) -> Result<Vec<usize>, FlucomaError> {
+        let (this, signal) = (self.clone(), signal.into());
+        run_blocking(move || this.process(&signal)).await
+    }
+}
+
+impl BufTransients {
+    /// Like [`BufTransients::process`], run on tokio's blocking thread pool.
+    ///
+    /// # Errors
+    /// As for [`run_blocking`].
+    pub async fn process_async(
+        &self,
+        signal: impl Into<Arc<[f64]>>,
+    ) -> Result<BufTransientsResult, FlucomaError> {
+        let (this, signal) = (self.clone(), signal.into());
+        run_blocking(move || this.process(&signal)).await
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn run_blocking_resolves_to_job_result() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        assert_eq!(runtime.block_on(run_blocking(|| Ok(42))), Ok(42));
+        assert_eq!(
+            runtime.block_on(run_blocking(|| Err::<(), _>(
+                FlucomaError::InvalidParameter("bad settings")
+            ))),
+            Err(FlucomaError::InvalidParameter("bad settings"))
+        );
+    }
+
+    #[cfg(not(feature = "tokio"))]
+    #[test]
+    fn run_blocking_requires_feature() {
+        use std::future::Future;
+        use std::task::{Context, Poll, Waker};
+
+        let mut job = std::pin::pin!(run_blocking(|| Ok(42)));
+        assert_eq!(
+            job.as_mut().poll(&mut Context::from_waker(Waker::noop())),
+            Poll::Ready(Err(FlucomaError::FeatureDisabled("tokio")))
+        );
+    }
+}
@@ -3,6 +3,8 @@ use flucoma_sys::{
     audio_transport_process_frame,
 };
 
+use crate::error::FlucomaError;
+
 // -------------------------------------------------------------------------------------------------
 
 /// Optimal-transport spectral morphing between two audio frames.
@@ -35,20 +37,22 @@ impl AudioTransport {
     /// * `hop_size`    - Hop size between frames in samples (must be > 0).
     ///
     /// # Errors
-    /// Returns an error string if parameters are invalid or allocation fails.
-    pub fn new(window_size: usize, fft_size: usize, hop_size: usize) -> Result<Self, &'static str> {
+    /// Returns an error if parameters are invalid or allocation fails.
+    pub fn new(window_size: usize, fft_size: usize, hop_size: usize) -> Result<Self, FlucomaError> {
         if window_size == 0 {
-            return Err("window_size must be > 0");
+            return Err(FlucomaError::InvalidParameter("window_size must be > 0"));
         }
         if fft_size < window_size {
-            return Err("fft_size must be >= window_size");
+            return Err(FlucomaError::InvalidParameter(
+                "fft_size must be >= window_size",
+            ));
         }
         if hop_size == 0 {
-            return Err("hop_size must be > 0");
+            return Err(FlucomaError::InvalidParameter("hop_size must be > 0"));
         }
         let inner = audio_transport_create(fft_size as isize);
         if inner.is_null() {
-            return Err("failed to create AudioTransport instance");
+            return Err(FlucomaError::AllocationFailed);
         }
         audio_transport_init(
             inner,
@@ -131,6 +135,13 @@ impl Drop for AudioTransport {
     }
 }
 
+impl Clone for AudioTransport {
+    fn clone(&self) -> Self {
+        Self::new(self.window_size, self.fft_size, self.hop_size)
+            .expect("settings were validated on construction")
+    }
+}
+
 // -------------------------------------------------------------------------------------------------
 
 #[cfg(test)]
@@ -0,0 +1,240 @@
+use crate::amp_gate::AmpGate;
+use crate::error::FlucomaError;
+use crate::slice::Slice;
+
+// -------------------------------------------------------------------------------------------------
+
+/// Configuration for [`BufAmpGate`]. Times are in samples, levels in dB.
+///
+/// Defaults match FluidBufAmpGate.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BufAmpGateConfig {
+    /// Envelope attack time.
+    pub ramp_up: usize,
+    /// Envelope release time.
+    pub ramp_down: usize,
+    /// Level above which the gate opens.
+    pub on_threshold: f64,
+    /// Level below which the gate closes.
+    pub off_threshold: f64,
+    /// Minimum length of a region.
+    pub min_slice_length: usize,
+    /// Minimum length of the gap between two regions.
+    pub min_silence_length: usize,
+    /// Time the envelope must stay above `on_threshold` to open the gate.
+    pub min_length_above: usize,
+    /// Time the envelope must stay below `off_threshold` to close the gate.
+    pub min_length_below: usize,
+    /// Time to look back for a quieter opening point.
+    pub look_back: usize,
+    /// Time to look ahead for a quieter closing point.
+    pub look_ahead: usize,
+    /// Hi-pass filter frequency in Hz applied before the follower.
+    pub hi_pass_freq: f64,
+}
+
+impl Default for BufAmpGateConfig {
+    fn default() -> Self {
+        Self {
+            ramp_up: 10,
+            ramp_down: 10,
+            on_threshold: -90.0,
+            off_threshold: -90.0,
+            min_slice_length: 1,
+            min_silence_length: 1,
+            min_length_above: 1,
+            min_length_below: 1,
+            look_back: 0,
+            look_ahead: 0,
+            hi_pass_freq: 85.0,
+        }
+    }
+}
+
+/// Offline amplitude gating of a whole mono buffer.
+///
+/// Returns the `(start, end)` sample ranges (end exclusive) during which the
+/// gate is open, compensated for the gate's latency. Useful for stripping
+/// silence or isolating notes.
+///
+/// See <https://learn.flucoma.org/reference/ampgate>
+///
+/// ```rust,no_run
+/// use flucoma_rs::segmentation::{BufAmpGate, BufAmpGateConfig};
+///
+/// let audio = vec![0.0f64; 44100];
+/// let gate = BufAmpGate::new(BufAmpGateConfig {
+///     on_threshold: -30.0,
+///     off_threshold: -40.0,
+///     ..Default::default()
+/// })
+/// .unwrap();
+/// for (start, end) in gate.process(&audio).unwrap() {
+///     println!("{start}..{end}");
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct BufAmpGate {
+    config: BufAmpGateConfig,
+}
+
+impl BufAmpGate {
+    /// # Errors
+    /// Returns an error if the configuration is invalid.
+    pub fn new(config: BufAmpGateConfig) -> Result<Self, FlucomaError> {
+        validate_config(&config)?;
+        Ok(Self { config })
+    }
+
+    pub fn config(&self) -> &BufAmpGateConfig {
+        &self.config
+    }
+
+    pub fn set_config(&mut self, config: BufAmpGateConfig) -> Result<(), FlucomaError> {
+        validate_config(&config)?;
+        self.config = config;
+        Ok(())
+    }
+
+    /// Gate `signal` and return the open regions as `(start, end)` sample
+    /// ranges, in ascending order. A region still open at the end of the
+    /// buffer ends at `signal.len()`.
+    ///
+    /// # Errors
+    /// Returns an error if the gate cannot be created.
+    pub fn process(&self, signal: &[f64]) -> Result<Vec<(usize, usize)>, FlucomaError> {
+        let c = &self.config;
+        let mut gate = AmpGate::new(
+            c.on_threshold,
+            c.off_threshold,
+            c.hi_pass_freq,
+            c.min_length_above,
+            c.look_back,
+            c.min_length_below,
+            c.look_ahead,
+        )?;
+        let latency = gate.latency_samples();
+        let gate_values: Vec<f64> = signal
+            .iter()
+            .copied()
+            .chain(std::iter::repeat_n(0.0, latency))
+            .map(|sample| {
+                gate.process_sample(
+                    sample,
+                    c.on_threshold,
+                    c.off_threshold,
+                    c.ramp_up,
+                    c.ramp_down,
+                    c.hi_pass_freq,
+                    c.min_slice_length,
+                    c.min_silence_length,
+                )
+            })
+            .collect();
+        Ok(open_regions(&gate_values[latency..], signal.len()))
+    }
+
+    /// Gate `signal` and return the open regions as [`Slice`]s, with
+    /// positions in samples and seconds.
+    ///
+    /// # Errors
+    /// Returns an error if `sample_rate <= 0` or the gate cannot be
+    /// created.
+    pub fn slices(&self, signal: &[f64], sample_rate: f64) -> Result<Vec<Slice>, FlucomaError> {
+        if sample_rate <= 0.0 {
+            return Err(FlucomaError::InvalidParameter("sample_rate must be > 0"));
+        }
+        Ok(self
+            .process(signal)?
+            .into_iter()
+            .map(|(start, end)| Slice::new(start, end, sample_rate))
+            .collect())
+    }
+}
+
+fn validate_config(config: &BufAmpGateConfig) -> Result<(), FlucomaError> {
+    if config.off_threshold > config.on_threshold {
+        return Err(FlucomaError::InvalidParameter(
+            "off_threshold must be <= on_threshold",
+        ));
+    }
+    if config.hi_pass_freq < 0.0 {
+        return Err(FlucomaError::InvalidParameter(
+            "hi_pass_freq must be >= 0.0 (use 0.0 to disable)",
+        ));
+    }
+    if config.min_slice_length == 0 || config.min_silence_length == 0 {
+        return Err(FlucomaError::InvalidParameter(
+            "min_slice_length and min_silence_length must be > 0",
+        ));
+    }
+    Ok(())
+}
+
+/// `(start, end)` ranges of consecutive non-zero values in `gate`, clipped to
+/// `len`.
+fn open_regions(gate: &[f64], len: usize) -> Vec<(usize, usize)> {
+    let mut regions = Vec::new();
+    let mut start = None;
+    for (i, &value) in gate.iter().take(len).enumerate() {
+        match (value > 0.0, start) {
+            (true, None) => start = Some(i),
+            (false, Some(s)) => {
+                regions.push((s, i));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(s) = start {
+        regions.push((s, len));
+    }
+    regions
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn open_regions_from_gate_values() {
+        let gate = [0.0, 1.0, 1.0, 0.0, 0.0, 1.0, 1.0];
+        assert_eq!(open_regions(&gate, 7), vec![(1, 3), (5, 7)]);
+        assert_eq!(open_regions(&gate, 6), vec![(1, 3), (5, 6)]);
+        assert!(open_regions(&[0.0; 4], 4).is_empty());
+
+        let config = BufAmpGateConfig {
+            on_threshold: -40.0,
+            off_threshold: -30.0,
+            ..Default::default()
+        };
+        assert!(BufAmpGate::new(config).is_err());
+    }
+
+    #[test]
+    fn buf_amp_gate_isolates_burst() {
+        let mut audio = vec![0.0f64; 44100];
+        for (i, sample) in audio.iter_mut().enumerate().skip(10000).take(10000) {
+            *sample = 0.5 * (i as f64 * 0.1).sin();
+        }
+        let gate = BufAmpGate::new(BufAmpGateConfig {
+            on_threshold: -30.0,
+            off_threshold: -40.0,
+            hi_pass_freq: 0.0,
+            ..Default::default()
+        })
+        .unwrap();
+        let regions = gate.process(&audio).unwrap();
+        assert_eq!(regions.len(), 1, "regions = {regions:?}");
+        let (start, end) = regions[0];
+        assert!((9900..10200).contains(&start), "start = {start}");
+        assert!((19900..20500).contains(&end), "end = {end}");
+
+        let slices = gate.slices(&audio, 44100.0).unwrap();
+        assert_eq!((slices[0].start_sample, slices[0].end_sample), (start, end));
+        assert!((slices[0].start_sec - start as f64 / 44100.0).abs() < 1e-12);
+    }
+}
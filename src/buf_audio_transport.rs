@@ -0,0 +1,173 @@
+use crate::audio_transport::AudioTransport;
+use crate::buf_frames::{fill_frame, num_frames, validate_fft};
+use crate::error::{check_len, FlucomaError};
+use crate::fft_settings::impl_fft_settings;
+use crate::overlap_add::OverlapAdd;
+use crate::stft::WindowType;
+
+// -------------------------------------------------------------------------------------------------
+
+/// Configuration for [`BufAudioTransport`].
+///
+/// Defaults match FluidBufAudioTransport.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BufAudioTransportConfig {
+    pub window_size: usize,
+    pub hop_size: usize,
+    pub fft_size: usize,
+}
+
+impl Default for BufAudioTransportConfig {
+    fn default() -> Self {
+        Self {
+            window_size: 1024,
+            hop_size: 512,
+            fft_size: 1024,
+        }
+    }
+}
+
+impl_fft_settings!(BufAudioTransportConfig);
+
+/// Whole-buffer optimal-transport morph between two signals.
+///
+/// Runs [`AudioTransport`] over centred frames of both signals and
+/// overlap-adds the result, normalising by the summed squared window. The
+/// interpolation weight follows a breakpoint envelope stretched evenly over
+/// the output: a single value is constant, `[0.0, 1.0]` sweeps linearly
+/// from the first signal to the second.
+///
+/// See <https://learn.flucoma.org/reference/audiotransport>
+///
+/// ```rust,no_run
+/// use flucoma_rs::transformation::{BufAudioTransport, BufAudioTransportConfig};
+///
+/// let source = vec![0.0f64; 44100];
+/// let target = vec![0.0f64; 44100];
+/// let transport = BufAudioTransport::new(BufAudioTransportConfig::default()).unwrap();
+/// let morph = transport.process(&source, &target, &[0.0, 1.0]).unwrap();
+/// assert_eq!(morph.len(), 44100);
+/// ```
+#[derive(Debug, Clone)]
+pub struct BufAudioTransport {
+    config: BufAudioTransportConfig,
+}
+
+impl BufAudioTransport {
+    /// # Errors
+    /// Returns an error if the configuration is invalid.
+    pub fn new(config: BufAudioTransportConfig) -> Result<Self, FlucomaError> {
+        validate_config(&config)?;
+        Ok(Self { config })
+    }
+
+    pub fn config(&self) -> &BufAudioTransportConfig {
+        &self.config
+    }
+
+    pub fn set_config(&mut self, config: BufAudioTransportConfig) -> Result<(), FlucomaError> {
+        validate_config(&config)?;
+        self.config = config;
+        Ok(())
+    }
+
+    /// Morph `source` into `target` following the `weights` envelope
+    /// (0.0 = all `source`, 1.0 = all `target`).
+    ///
+    /// The output is as long as the inputs.
+    ///
+    /// # Errors
+    /// Returns [`FlucomaError::InvalidShape`] if `source` and `target`
+    /// differ in length, or an error if `weights` is empty or the morpher
+    /// cannot be created.
+    pub fn process(
+        &self,
+        source: &[f64],
+        target: &[f64],
+        weights: &[f64],
+    ) -> Result<Vec<f64>, FlucomaError> {
+        check_len(source.len(), target.len())?;
+        if weights.is_empty() {
+            return Err(FlucomaError::InvalidParameter("weights must not be empty"));
+        }
+        let c = &self.config;
+        let mut transport = AudioTransport::new(c.window_size, c.fft_size, c.hop_size)?;
+        let len = source.len();
+        let frames = num_frames(len, c.hop_size);
+        let half = c.window_size / 2;
+
+        let mut ola = OverlapAdd::new(c.window_size, c.hop_size, WindowType::Hann)?;
+        let mut output = Vec::with_capacity((frames - 1) * c.hop_size + c.window_size);
+        let mut frame1 = vec![0.0; c.window_size];
+        let mut frame2 = vec![0.0; c.window_size];
+        for index in 0..frames {
+            fill_frame(source, index, c.hop_size, &mut frame1);
+            fill_frame(target, index, c.hop_size, &mut frame2);
+            let weight = envelope_at(weights, index, frames);
+            let (audio, window_sq) = transport.process_frame(&frame1, &frame2, weight);
+            output.extend_from_slice(ola.process_frame_with_norm(audio, window_sq));
+        }
+        output.extend(ola.flush());
+        Ok(output.into_iter().skip(half).take(len).collect())
+    }
+}
+
+/// Value of a breakpoint envelope, stretched over `frames` frames, at frame
+/// `index`.
+fn envelope_at(weights: &[f64], index: usize, frames: usize) -> f64 {
+    if weights.len() == 1 || frames < 2 {
+        return weights[0];
+    }
+    let position = index as f64 / (frames - 1) as f64 * (weights.len() - 1) as f64;
+    let lower = position.floor() as usize;
+    let upper = (lower + 1).min(weights.len() - 1);
+    let fraction = position - lower as f64;
+    weights[lower] + (weights[upper] - weights[lower]) * fraction
+}
+
+fn validate_config(config: &BufAudioTransportConfig) -> Result<(), FlucomaError> {
+    validate_fft(config.window_size, config.hop_size, config.fft_size)?;
+    if config.hop_size > config.window_size {
+        return Err(FlucomaError::InvalidParameter(
+            "hop_size must be <= window_size",
+        ));
+    }
+    Ok(())
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn envelope_is_stretched_over_frames() {
+        assert_eq!(envelope_at(&[0.3], 5, 10), 0.3);
+        assert_eq!(envelope_at(&[0.0, 1.0], 0, 5), 0.0);
+        assert_eq!(envelope_at(&[0.0, 1.0], 2, 5), 0.5);
+        assert_eq!(envelope_at(&[0.0, 1.0], 4, 5), 1.0);
+        assert_eq!(envelope_at(&[0.0, 1.0, 0.0], 3, 5), 0.5);
+    }
+
+    #[test]
+    fn buf_audio_transport_zero_weight_returns_source() {
+        let source: Vec<f64> = (0..8192).map(|i| 0.5 * (i as f64 * 0.05).sin()).collect();
+        let target: Vec<f64> = (0..8192).map(|i| 0.5 * (i as f64 * 0.2).sin()).collect();
+        let transport = BufAudioTransport::new(BufAudioTransportConfig::default()).unwrap();
+        let morph = transport.process(&source, &target, &[0.0]).unwrap();
+        assert_eq!(morph.len(), source.len());
+        for (a, b) in source.iter().zip(&morph).skip(1024).take(6000) {
+            assert!((a - b).abs() < 1e-3, "{a} != {b}");
+        }
+        assert!(transport.process(&source, &target, &[]).is_err());
+        assert_eq!(
+            transport.process(&source, &target[..8000], &[0.0]),
+            Err(FlucomaError::InvalidShape {
+                expected: 8192,
+                got: 8000
+            })
+        );
+    }
+}
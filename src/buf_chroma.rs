@@ -0,0 +1,161 @@
+use crate::buf_frames::{for_each_magnitude_frame, num_frames, validate_fft};
+use crate::chroma::{Chroma, ChromaNormalization};
+use crate::error::FlucomaError;
+use crate::feature_matrix::FeatureMatrix;
+use crate::fft_settings::impl_fft_settings;
+
+// -------------------------------------------------------------------------------------------------
+
+/// Configuration for [`BufChroma`].
+///
+/// Defaults match FluidBufChroma.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BufChromaConfig {
+    /// Number of pitch classes per octave.
+    pub n_chroma: usize,
+    /// Tuning reference for the first chroma bin, in Hz.
+    pub ref_hz: f64,
+    /// Per-frame normalisation of the output.
+    pub normalization: ChromaNormalization,
+    /// Bins below this frequency are ignored.
+    pub min_hz: f64,
+    /// Bins above this frequency are ignored; `None` for Nyquist.
+    pub max_hz: Option<f64>,
+    pub window_size: usize,
+    pub hop_size: usize,
+    pub fft_size: usize,
+}
+
+impl Default for BufChromaConfig {
+    fn default() -> Self {
+        Self {
+            n_chroma: 12,
+            ref_hz: 440.0,
+            normalization: ChromaNormalization::None,
+            min_hz: 0.0,
+            max_hz: None,
+            window_size: 1024,
+            hop_size: 512,
+            fft_size: 1024,
+        }
+    }
+}
+
+impl_fft_settings!(BufChromaConfig);
+
+/// Whole-buffer chromagram.
+///
+/// Returns a frames × `n_chroma` [`FeatureMatrix`] with columns `chroma0`,
+/// `chroma1`, ..., where `chroma0` is the pitch class of `ref_hz`.
+///
+/// See <https://learn.flucoma.org/reference/chroma>
+///
+/// ```rust,no_run
+/// use flucoma_rs::analyzation::{BufChroma, BufChromaConfig, ChromaNormalization};
+///
+/// let audio = vec![0.0f64; 44100];
+/// let chroma = BufChroma::new(BufChromaConfig {
+///     normalization: ChromaNormalization::Max,
+///     fft_size: 4096,
+///     ..Default::default()
+/// })
+/// .unwrap();
+/// let chromagram = chroma.process(&audio, 44100.0).unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct BufChroma {
+    config: BufChromaConfig,
+}
+
+impl BufChroma {
+    /// # Errors
+    /// Returns an error if the configuration is invalid.
+    pub fn new(config: BufChromaConfig) -> Result<Self, FlucomaError> {
+        validate_config(&config)?;
+        Ok(Self { config })
+    }
+
+    pub fn config(&self) -> &BufChromaConfig {
+        &self.config
+    }
+
+    pub fn set_config(&mut self, config: BufChromaConfig) -> Result<(), FlucomaError> {
+        validate_config(&config)?;
+        self.config = config;
+        Ok(())
+    }
+
+    /// Compute the chromagram of `signal`.
+    ///
+    /// # Errors
+    /// Returns an error if `sample_rate <= 0` or the analysers cannot
+    /// be created.
+    pub fn process(&self, signal: &[f64], sample_rate: f64) -> Result<FeatureMatrix, FlucomaError> {
+        if sample_rate <= 0.0 {
+            return Err(FlucomaError::InvalidParameter("sample_rate must be > 0"));
+        }
+        let c = &self.config;
+        let nyquist = sample_rate / 2.0;
+        let max_hz = c.max_hz.unwrap_or(nyquist).min(nyquist);
+        let mut chroma = Chroma::new(c.n_chroma, c.fft_size / 2 + 1, c.ref_hz, sample_rate)?;
+        let mut data = Vec::with_capacity(num_frames(signal.len(), c.hop_size) * c.n_chroma);
+        for_each_magnitude_frame(
+            signal,
+            c.window_size,
+            c.hop_size,
+            c.fft_size,
+            |magnitudes| {
+                data.extend(chroma.process_frame(magnitudes, c.min_hz, max_hz, c.normalization));
+            },
+        )?;
+        let columns = (0..c.n_chroma).map(|i| format!("chroma{i}")).collect();
+        FeatureMatrix::new(data, columns, sample_rate, c.window_size, c.hop_size)
+    }
+}
+
+fn validate_config(config: &BufChromaConfig) -> Result<(), FlucomaError> {
+    validate_fft(config.window_size, config.hop_size, config.fft_size)?;
+    if config.n_chroma < 2 {
+        return Err(FlucomaError::InvalidParameter("n_chroma must be >= 2"));
+    }
+    if config.ref_hz <= 0.0 {
+        return Err(FlucomaError::InvalidParameter("ref_hz must be > 0"));
+    }
+    if config.min_hz < 0.0 {
+        return Err(FlucomaError::InvalidParameter("min_hz must be >= 0"));
+    }
+    if config.max_hz.is_some_and(|max| max <= config.min_hz) {
+        return Err(FlucomaError::InvalidParameter("max_hz must be > min_hz"));
+    }
+    Ok(())
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buf_chroma_normalized_frames() {
+        use std::f64::consts::PI;
+        let sr = 44100.0;
+        let audio: Vec<f64> = (0..16384)
+            .map(|i| 0.5 * (2.0 * PI * 440.0 * i as f64 / sr).sin())
+            .collect();
+        let chroma = BufChroma::new(BufChromaConfig {
+            normalization: ChromaNormalization::Max,
+            fft_size: 4096,
+            ..Default::default()
+        })
+        .unwrap();
+        let chromagram = chroma.process(&audio, sr).unwrap();
+        assert_eq!(chromagram.num_features(), 12);
+        assert_eq!(chromagram.column_names()[11], "chroma11");
+        let middle = chromagram.frame(chromagram.num_frames() / 2);
+        let max = middle.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        assert!((max - 1.0).abs() < 1e-9, "max = {max}");
+        assert!(middle[0] >= 1.0 - 1e-9, "A should be the strongest class");
+    }
+}
@@ -0,0 +1,195 @@
+use crate::error::FlucomaError;
+
+// -------------------------------------------------------------------------------------------------
+
+/// Mix `source`, scaled by `gain`, into `destination` starting at
+/// `offset`, after scaling the destination content in the overwritten
+/// region `offset..offset + source.len()` by `destination_gain`. Samples
+/// outside that region are left untouched. The destination grows
+/// (zero-filled) as needed.
+///
+/// This mirrors FluidBufCompose: `destination_gain = 0.0` overwrites,
+/// `destination_gain = 1.0` mixes.
+///
+/// ```rust
+/// use flucoma_rs::buffer::compose;
+///
+/// let mut mixdown = vec![1.0; 4];
+/// compose(&mut mixdown, &[0.5, 0.5], 3, 2.0, 1.0);
+/// assert_eq!(mixdown, vec![1.0, 1.0, 1.0, 2.0, 1.0]);
+/// ```
+pub fn compose(
+    destination: &mut Vec<f64>,
+    source: &[f64],
+    offset: usize,
+    gain: f64,
+    destination_gain: f64,
+) {
+    let end = offset + source.len();
+    if destination.len() < end {
+        destination.resize(end, 0.0);
+    }
+    if destination_gain != 1.0 {
+        apply_gain(&mut destination[offset..end], destination_gain);
+    }
+    for (d, s) in destination[offset..end].iter_mut().zip(source) {
+        *d += s * gain;
+    }
+}
+
+/// Sum two signals sample by sample. The result is as long as the longer
+/// input.
+pub fn mix(a: &[f64], b: &[f64]) -> Vec<f64> {
+    let mut output = a.to_vec();
+    compose(&mut output, b, 0, 1.0, 1.0);
+    output
+}
+
+/// Scale `signal` by `gain` in place.
+pub fn apply_gain(signal: &mut [f64], gain: f64) {
+    signal.iter_mut().for_each(|sample| *sample *= gain);
+}
+
+/// Copy of `signal` scaled by `gain`.
+pub fn with_gain(signal: &[f64], gain: f64) -> Vec<f64> {
+    signal.iter().map(|sample| sample * gain).collect()
+}
+
+/// Concatenate signals end to end.
+pub fn concatenate(parts: &[&[f64]]) -> Vec<f64> {
+    parts.concat()
+}
+
+/// Apply a linear fade-in over the first `length` samples (clamped to the
+/// signal length).
+pub fn fade_in(signal: &mut [f64], length: usize) {
+    let length = length.min(signal.len());
+    for (i, sample) in signal[..length].iter_mut().enumerate() {
+        *sample *= i as f64 / length as f64;
+    }
+}
+
+/// Apply a linear fade-out over the last `length` samples (clamped to the
+/// signal length), reaching zero on the final sample.
+pub fn fade_out(signal: &mut [f64], length: usize) {
+    let length = length.min(signal.len());
+    let start = signal.len() - length;
+    for (i, sample) in signal[start..].iter_mut().enumerate() {
+        *sample *= (length - 1 - i) as f64 / length as f64;
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Extract channel `channel` of an interleaved buffer with `num_channels`
+/// channels.
+///
+/// # Errors
+/// Returns an error if `num_channels` is zero, `channel` is out of
+/// range, or the buffer length is not a multiple of `num_channels`.
+pub fn extract_channel(
+    interleaved: &[f64],
+    num_channels: usize,
+    channel: usize,
+) -> Result<Vec<f64>, FlucomaError> {
+    validate_interleaved(interleaved, num_channels)?;
+    if channel >= num_channels {
+        return Err(FlucomaError::InvalidParameter(
+            "channel must be < num_channels",
+        ));
+    }
+    Ok(interleaved
+        .iter()
+        .skip(channel)
+        .step_by(num_channels)
+        .copied()
+        .collect())
+}
+
+/// Split an interleaved buffer into one signal per channel.
+///
+/// # Errors
+/// Returns an error if `num_channels` is zero or the buffer length is
+/// not a multiple of `num_channels`.
+pub fn deinterleave(
+    interleaved: &[f64],
+    num_channels: usize,
+) -> Result<Vec<Vec<f64>>, FlucomaError> {
+    validate_interleaved(interleaved, num_channels)?;
+    (0..num_channels)
+        .map(|channel| extract_channel(interleaved, num_channels, channel))
+        .collect()
+}
+
+/// Interleave equally long channel signals into one buffer.
+///
+/// # Errors
+/// Returns an error if `channels` is empty or the channels differ in
+/// length.
+pub fn interleave<S: AsRef<[f64]>>(channels: &[S]) -> Result<Vec<f64>, FlucomaError> {
+    let first = channels
+        .first()
+        .ok_or(FlucomaError::InvalidParameter("channels must not be empty"))?
+        .as_ref();
+    if channels.iter().any(|c| c.as_ref().len() != first.len()) {
+        return Err(FlucomaError::InvalidParameter(
+            "all channels must have the same length",
+        ));
+    }
+    let mut output = Vec::with_capacity(first.len() * channels.len());
+    for frame in 0..first.len() {
+        output.extend(channels.iter().map(|c| c.as_ref()[frame]));
+    }
+    Ok(output)
+}
+
+fn validate_interleaved(interleaved: &[f64], num_channels: usize) -> Result<(), FlucomaError> {
+    if num_channels == 0 {
+        return Err(FlucomaError::InvalidParameter("num_channels must be > 0"));
+    }
+    if !interleaved.len().is_multiple_of(num_channels) {
+        return Err(FlucomaError::InvalidParameter(
+            "buffer length must be a multiple of num_channels",
+        ));
+    }
+    Ok(())
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compose_overwrites_and_mixes() {
+        let mut destination = vec![1.0, 1.0, 1.0];
+        compose(&mut destination, &[2.0, 2.0], 1, 0.5, 0.0);
+        assert_eq!(destination, vec![1.0, 1.0, 1.0]);
+        compose(&mut destination, &[2.0], 3, 1.0, 0.0);
+        assert_eq!(destination, vec![1.0, 1.0, 1.0, 2.0]);
+        assert_eq!(mix(&[1.0, 2.0, 3.0], &[1.0]), vec![2.0, 2.0, 3.0]);
+        assert_eq!(with_gain(&[1.0, -2.0], 0.5), vec![0.5, -1.0]);
+        assert_eq!(concatenate(&[&[1.0], &[2.0, 3.0]]), vec![1.0, 2.0, 3.0]);
+    }
+
+    #[test]
+    fn fades_reach_zero_at_edges() {
+        let mut signal = vec![1.0; 8];
+        fade_in(&mut signal, 4);
+        fade_out(&mut signal, 4);
+        assert_eq!(signal, vec![0.0, 0.25, 0.5, 0.75, 0.75, 0.5, 0.25, 0.0]);
+    }
+
+    #[test]
+    fn interleave_roundtrip() {
+        let left = vec![1.0, 2.0, 3.0];
+        let right = vec![-1.0, -2.0, -3.0];
+        let interleaved = interleave(&[&left, &right]).unwrap();
+        assert_eq!(interleaved, vec![1.0, -1.0, 2.0, -2.0, 3.0, -3.0]);
+        assert_eq!(extract_channel(&interleaved, 2, 1).unwrap(), right);
+        assert_eq!(deinterleave(&interleaved, 2).unwrap(), vec![left, right]);
+        assert!(extract_channel(&interleaved, 4, 0).is_err());
+        assert!(interleave(&[vec![1.0], vec![]]).is_err());
+    }
+}
@@ -0,0 +1,94 @@
+//! Frame iteration shared by the whole-buffer (`Buf*`) analysers.
+//!
+//! Frames follow FluCoMa's default buffer padding: the signal is zero-padded
+//! by half a window on both ends, so frame `i` is centred on sample
+//! `i * hop` and a buffer of `len` samples yields `len / hop + 1` frames.
+
+use crate::error::FlucomaError;
+use crate::stft::{Stft, WindowType};
+
+// -------------------------------------------------------------------------------------------------
+
+/// Number of centred frames for a buffer of `len` samples.
+pub(crate) fn num_frames(len: usize, hop: usize) -> usize {
+    len / hop + 1
+}
+
+/// Copy centred frame `index` of `signal` into `frame`, zero-filling the
+/// samples which fall outside the buffer. The frame length is the window size.
+pub(crate) fn fill_frame(signal: &[f64], index: usize, hop: usize, frame: &mut [f64]) {
+    let start = (index * hop) as isize - (frame.len() / 2) as isize;
+    fill_block(signal, start, frame);
+}
+
+/// Copy `block.len()` samples of `signal` starting at `start` into `block`,
+/// zero-filling the samples which fall outside the buffer.
+pub(crate) fn fill_block(signal: &[f64], start: isize, block: &mut [f64]) {
+    for (offset, sample) in block.iter_mut().enumerate() {
+        let position = start + offset as isize;
+        *sample = if position >= 0 {
+            signal.get(position as usize).copied().unwrap_or(0.0)
+        } else {
+            0.0
+        };
+    }
+}
+
+/// Run a Hann-windowed STFT over the centred frames of `signal` and call `f`
+/// with the magnitude spectrum (`fft_size / 2 + 1` bins) of each frame.
+pub(crate) fn for_each_magnitude_frame<F: FnMut(&[f64])>(
+    signal: &[f64],
+    window_size: usize,
+    hop_size: usize,
+    fft_size: usize,
+    mut f: F,
+) -> Result<(), FlucomaError> {
+    let mut stft = Stft::new(window_size, fft_size, hop_size, WindowType::Hann)?;
+    let mut frame = vec![0.0; window_size];
+    for index in 0..num_frames(signal.len(), hop_size) {
+        fill_frame(signal, index, hop_size, &mut frame);
+        f(&stft.process_frame(&frame).magnitudes());
+    }
+    Ok(())
+}
+
+/// Validate a window/hop/FFT triple for whole-buffer STFT analysis.
+pub(crate) fn validate_fft(
+    window_size: usize,
+    hop_size: usize,
+    fft_size: usize,
+) -> Result<(), FlucomaError> {
+    if window_size == 0 {
+        return Err(FlucomaError::InvalidParameter("window_size must be > 0"));
+    }
+    if hop_size == 0 {
+        return Err(FlucomaError::InvalidParameter("hop_size must be > 0"));
+    }
+    if fft_size < window_size {
+        return Err(FlucomaError::InvalidParameter(
+            "fft_size must be >= window_size",
+        ));
+    }
+    Ok(())
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn centred_frames_are_zero_padded() {
+        let signal: Vec<f64> = (1..=6).map(f64::from).collect();
+        assert_eq!(num_frames(signal.len(), 2), 4);
+
+        let mut frame = [0.0; 4];
+        fill_frame(&signal, 0, 2, &mut frame);
+        assert_eq!(frame, [0.0, 0.0, 1.0, 2.0]);
+        fill_frame(&signal, 1, 2, &mut frame);
+        assert_eq!(frame, [1.0, 2.0, 3.0, 4.0]);
+        fill_frame(&signal, 3, 2, &mut frame);
+        assert_eq!(frame, [5.0, 6.0, 0.0, 0.0]);
+    }
+}
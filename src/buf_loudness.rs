@@ -0,0 +1,123 @@
+use crate::buf_frames::{fill_frame, num_frames};
+use crate::error::FlucomaError;
+use crate::feature_matrix::FeatureMatrix;
+use crate::loudness::Loudness;
+
+// -------------------------------------------------------------------------------------------------
+
+/// Configuration for [`BufLoudness`].
+///
+/// Defaults match FluidBufLoudness.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BufLoudnessConfig {
+    /// Apply K-weighting (as per EBU R128).
+    pub k_weighting: bool,
+    /// Use true peak detection; otherwise the absolute sample maximum.
+    pub true_peak: bool,
+    pub window_size: usize,
+    pub hop_size: usize,
+}
+
+impl Default for BufLoudnessConfig {
+    fn default() -> Self {
+        Self {
+            k_weighting: true,
+            true_peak: true,
+            window_size: 1024,
+            hop_size: 512,
+        }
+    }
+}
+
+/// Whole-buffer loudness and peak curves.
+///
+/// Returns a frames × 2 [`FeatureMatrix`] with columns `loudness` and `peak`
+/// (both in dBFS). The matrix records the window and hop used, so the curves
+/// can be aligned with other descriptors or used as weights for statistics.
+///
+/// See <https://learn.flucoma.org/reference/loudness>
+///
+/// ```rust,no_run
+/// use flucoma_rs::analyzation::{BufLoudness, BufLoudnessConfig};
+///
+/// let audio = vec![0.0f64; 44100];
+/// let loudness = BufLoudness::new(BufLoudnessConfig::default()).unwrap();
+/// let curves = loudness.process(&audio, 44100.0).unwrap();
+/// println!("{} frames, hop {}", curves.num_frames(), curves.hop_size());
+/// ```
+#[derive(Debug, Clone)]
+pub struct BufLoudness {
+    config: BufLoudnessConfig,
+}
+
+impl BufLoudness {
+    /// # Errors
+    /// Returns an error if the configuration is invalid.
+    pub fn new(config: BufLoudnessConfig) -> Result<Self, FlucomaError> {
+        validate_config(&config)?;
+        Ok(Self { config })
+    }
+
+    pub fn config(&self) -> &BufLoudnessConfig {
+        &self.config
+    }
+
+    pub fn set_config(&mut self, config: BufLoudnessConfig) -> Result<(), FlucomaError> {
+        validate_config(&config)?;
+        self.config = config;
+        Ok(())
+    }
+
+    /// Compute the loudness and peak curves of `signal`.
+    ///
+    /// # Errors
+    /// Returns an error if `sample_rate <= 0` or the analyser cannot
+    /// be created.
+    pub fn process(&self, signal: &[f64], sample_rate: f64) -> Result<FeatureMatrix, FlucomaError> {
+        let c = &self.config;
+        let mut loudness = Loudness::new(c.window_size, sample_rate)?;
+        let frames = num_frames(signal.len(), c.hop_size);
+        let mut frame = vec![0.0; c.window_size];
+        let mut data = Vec::with_capacity(frames * 2);
+        for index in 0..frames {
+            fill_frame(signal, index, c.hop_size, &mut frame);
+            let result = loudness.process_frame(&frame, c.k_weighting, c.true_peak);
+            data.extend([result.loudness_db, result.peak_db]);
+        }
+        let columns = vec!["loudness".to_owned(), "peak".to_owned()];
+        FeatureMatrix::new(data, columns, sample_rate, c.window_size, c.hop_size)
+    }
+}
+
+fn validate_config(config: &BufLoudnessConfig) -> Result<(), FlucomaError> {
+    if config.window_size == 0 {
+        return Err(FlucomaError::InvalidParameter("window_size must be > 0"));
+    }
+    if config.hop_size == 0 {
+        return Err(FlucomaError::InvalidParameter("hop_size must be > 0"));
+    }
+    Ok(())
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buf_loudness_curves_follow_level() {
+        let mut audio = vec![0.0f64; 8192];
+        for (i, sample) in audio.iter_mut().enumerate().skip(4096) {
+            *sample = 0.5 * (i as f64 * 0.1).sin();
+        }
+        let loudness = BufLoudness::new(BufLoudnessConfig::default()).unwrap();
+        let curves = loudness.process(&audio, 44100.0).unwrap();
+        assert_eq!(curves.num_frames(), 8192 / 512 + 1);
+        assert_eq!(curves.column_names(), &["loudness", "peak"]);
+        assert_eq!(curves.window_size(), 1024);
+        assert!(curves.frame(12)[0] > curves.frame(2)[0]);
+        assert!(curves.frame(12)[1] > -7.0);
+    }
+}
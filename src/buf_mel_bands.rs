@@ -0,0 +1,151 @@
+use crate::buf_frames::{for_each_magnitude_frame, num_frames, validate_fft};
+use crate::error::FlucomaError;
+use crate::feature_matrix::FeatureMatrix;
+use crate::fft_settings::impl_fft_settings;
+use crate::mel_bands::MelBands;
+
+// -------------------------------------------------------------------------------------------------
+
+/// Configuration for [`BufMelBands`].
+///
+/// Defaults match FluidBufMelBands.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BufMelBandsConfig {
+    /// Number of mel bands (must be >= 2).
+    pub n_bands: usize,
+    /// Low-frequency edge of the filter bank in Hz.
+    pub lo_hz: f64,
+    /// High-frequency edge of the filter bank in Hz, clamped to Nyquist.
+    pub hi_hz: f64,
+    /// Normalise by magnitude (area-normalised filters).
+    pub normalize: bool,
+    /// Square the magnitudes (power spectrum input).
+    pub use_power: bool,
+    /// Return band energies in dB.
+    pub log_output: bool,
+    pub window_size: usize,
+    pub hop_size: usize,
+    pub fft_size: usize,
+}
+
+impl Default for BufMelBandsConfig {
+    fn default() -> Self {
+        Self {
+            n_bands: 40,
+            lo_hz: 20.0,
+            hi_hz: 20000.0,
+            normalize: true,
+            use_power: false,
+            log_output: false,
+            window_size: 1024,
+            hop_size: 512,
+            fft_size: 1024,
+        }
+    }
+}
+
+impl_fft_settings!(BufMelBandsConfig);
+
+/// Whole-buffer mel spectrogram.
+///
+/// Runs STFT and [`MelBands`] over every frame of a mono buffer and returns
+/// a frames × bands [`FeatureMatrix`] with columns `band0`, `band1`, ...
+///
+/// See <https://learn.flucoma.org/reference/melbands>
+///
+/// ```rust,no_run
+/// use flucoma_rs::analyzation::{BufMelBands, BufMelBandsConfig};
+///
+/// let audio = vec![0.0f64; 44100];
+/// let mel = BufMelBands::new(BufMelBandsConfig::default()).unwrap();
+/// let spectrogram = mel.process(&audio, 44100.0).unwrap();
+/// for i in 0..spectrogram.num_frames() {
+///     println!("{:.3}s: {:?}", spectrogram.frame_time(i), spectrogram.frame(i));
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct BufMelBands {
+    config: BufMelBandsConfig,
+}
+
+impl BufMelBands {
+    /// # Errors
+    /// Returns an error if the configuration is invalid.
+    pub fn new(config: BufMelBandsConfig) -> Result<Self, FlucomaError> {
+        validate_config(&config)?;
+        Ok(Self { config })
+    }
+
+    pub fn config(&self) -> &BufMelBandsConfig {
+        &self.config
+    }
+
+    pub fn set_config(&mut self, config: BufMelBandsConfig) -> Result<(), FlucomaError> {
+        validate_config(&config)?;
+        self.config = config;
+        Ok(())
+    }
+
+    /// Compute the mel spectrogram of `signal`.
+    ///
+    /// # Errors
+    /// Returns an error if `sample_rate <= 0` or the analysers cannot
+    /// be created.
+    pub fn process(&self, signal: &[f64], sample_rate: f64) -> Result<FeatureMatrix, FlucomaError> {
+        if sample_rate <= 0.0 {
+            return Err(FlucomaError::InvalidParameter("sample_rate must be > 0"));
+        }
+        let c = &self.config;
+        let mut mel = MelBands::new(
+            c.n_bands,
+            c.fft_size / 2 + 1,
+            c.lo_hz,
+            c.hi_hz.min(sample_rate / 2.0),
+            sample_rate,
+            c.window_size,
+        )?;
+        let mut data = Vec::with_capacity(num_frames(signal.len(), c.hop_size) * c.n_bands);
+        for_each_magnitude_frame(
+            signal,
+            c.window_size,
+            c.hop_size,
+            c.fft_size,
+            |magnitudes| {
+                data.extend(mel.process_frame(magnitudes, c.normalize, c.use_power, c.log_output));
+            },
+        )?;
+        let columns = (0..c.n_bands).map(|i| format!("band{i}")).collect();
+        FeatureMatrix::new(data, columns, sample_rate, c.window_size, c.hop_size)
+    }
+}
+
+fn validate_config(config: &BufMelBandsConfig) -> Result<(), FlucomaError> {
+    validate_fft(config.window_size, config.hop_size, config.fft_size)?;
+    if config.n_bands < 2 {
+        return Err(FlucomaError::InvalidParameter("n_bands must be >= 2"));
+    }
+    if config.lo_hz >= config.hi_hz {
+        return Err(FlucomaError::InvalidParameter("lo_hz must be < hi_hz"));
+    }
+    Ok(())
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buf_mel_bands_shape_and_times() {
+        let audio = vec![0.0f64; 4096];
+        let mel = BufMelBands::new(BufMelBandsConfig::default()).unwrap();
+        let spectrogram = mel.process(&audio, 44100.0).unwrap();
+        assert_eq!(spectrogram.num_frames(), 4096 / 512 + 1);
+        assert_eq!(spectrogram.num_features(), 40);
+        assert_eq!(spectrogram.column_names()[1], "band1");
+        assert!((spectrogram.frame_time(2) - 1024.0 / 44100.0).abs() < 1e-12);
+        assert!(spectrogram.matrix().data().iter().all(|v| v.abs() < 1e-10));
+    }
+}
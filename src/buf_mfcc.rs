@@ -0,0 +1,214 @@
+use crate::buf_frames::{for_each_magnitude_frame, num_frames, validate_fft};
+use crate::error::FlucomaError;
+use crate::feature_matrix::FeatureMatrix;
+use crate::fft_settings::impl_fft_settings;
+use crate::mfcc::Mfcc;
+
+// -------------------------------------------------------------------------------------------------
+
+/// Configuration for [`BufMfcc`].
+///
+/// Defaults match FluidBufMFCC.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BufMfccConfig {
+    /// Number of coefficients returned per frame.
+    pub num_coefs: usize,
+    /// Index of the first returned coefficient. Set to 1 to drop the
+    /// loudness-dependent 0th coefficient.
+    pub start_coef: usize,
+    /// Number of mel bands the DCT is taken over.
+    pub n_bands: usize,
+    /// Low-frequency edge of the filter bank in Hz.
+    pub lo_hz: f64,
+    /// High-frequency edge of the filter bank in Hz, clamped to Nyquist.
+    pub hi_hz: f64,
+    /// Append the per-frame deltas (centred first differences) of the
+    /// coefficients as extra columns.
+    pub deltas: bool,
+    pub window_size: usize,
+    pub hop_size: usize,
+    pub fft_size: usize,
+}
+
+impl Default for BufMfccConfig {
+    fn default() -> Self {
+        Self {
+            num_coefs: 13,
+            start_coef: 0,
+            n_bands: 40,
+            lo_hz: 20.0,
+            hi_hz: 20000.0,
+            deltas: false,
+            window_size: 1024,
+            hop_size: 512,
+            fft_size: 1024,
+        }
+    }
+}
+
+impl_fft_settings!(BufMfccConfig);
+
+/// Whole-buffer MFCC extraction.
+///
+/// Returns a frames × `num_coefs` [`FeatureMatrix`] with columns named after
+/// the coefficient index (`mfcc0`, `mfcc1`, ...). With
+/// [`BufMfccConfig::deltas`] set, `num_coefs` further columns (`delta0`,
+/// `delta1`, ...) hold the deltas.
+///
+/// See <https://learn.flucoma.org/reference/mfcc>
+///
+/// ```rust,no_run
+/// use flucoma_rs::analyzation::{BufMfcc, BufMfccConfig};
+///
+/// let audio = vec![0.0f64; 44100];
+/// let mfcc = BufMfcc::new(BufMfccConfig {
+///     start_coef: 1,
+///     deltas: true,
+///     ..Default::default()
+/// })
+/// .unwrap();
+/// let features = mfcc.process(&audio, 44100.0).unwrap();
+/// assert_eq!(features.column_names()[0], "mfcc1");
+/// ```
+#[derive(Debug, Clone)]
+pub struct BufMfcc {
+    config: BufMfccConfig,
+}
+
+impl BufMfcc {
+    /// # Errors
+    /// Returns an error if the configuration is invalid.
+    pub fn new(config: BufMfccConfig) -> Result<Self, FlucomaError> {
+        validate_config(&config)?;
+        Ok(Self { config })
+    }
+
+    pub fn config(&self) -> &BufMfccConfig {
+        &self.config
+    }
+
+    pub fn set_config(&mut self, config: BufMfccConfig) -> Result<(), FlucomaError> {
+        validate_config(&config)?;
+        self.config = config;
+        Ok(())
+    }
+
+    /// Compute the MFCCs of `signal`.
+    ///
+    /// # Errors
+    /// Returns an error if `sample_rate <= 0` or the analysers cannot
+    /// be created.
+    pub fn process(&self, signal: &[f64], sample_rate: f64) -> Result<FeatureMatrix, FlucomaError> {
+        if sample_rate <= 0.0 {
+            return Err(FlucomaError::InvalidParameter("sample_rate must be > 0"));
+        }
+        let c = &self.config;
+        let mut mfcc = Mfcc::new(
+            c.start_coef + c.num_coefs,
+            c.n_bands,
+            c.fft_size / 2 + 1,
+            c.lo_hz,
+            c.hi_hz.min(sample_rate / 2.0),
+            sample_rate,
+            c.window_size,
+        )?;
+        let mut coefs = Vec::with_capacity(num_frames(signal.len(), c.hop_size) * c.num_coefs);
+        for_each_magnitude_frame(
+            signal,
+            c.window_size,
+            c.hop_size,
+            c.fft_size,
+            |magnitudes| {
+                coefs.extend_from_slice(&mfcc.process_frame(magnitudes)[c.start_coef..]);
+            },
+        )?;
+
+        let indices = c.start_coef..c.start_coef + c.num_coefs;
+        let mut columns: Vec<String> = indices.clone().map(|i| format!("mfcc{i}")).collect();
+        let data = if c.deltas {
+            columns.extend(indices.map(|i| format!("delta{i}")));
+            let deltas = deltas(&coefs, c.num_coefs);
+            coefs
+                .chunks_exact(c.num_coefs)
+                .zip(deltas.chunks_exact(c.num_coefs))
+                .flat_map(|(frame, delta)| frame.iter().chain(delta).copied())
+                .collect()
+        } else {
+            coefs
+        };
+        FeatureMatrix::new(data, columns, sample_rate, c.window_size, c.hop_size)
+    }
+}
+
+fn validate_config(config: &BufMfccConfig) -> Result<(), FlucomaError> {
+    validate_fft(config.window_size, config.hop_size, config.fft_size)?;
+    if config.num_coefs == 0 {
+        return Err(FlucomaError::InvalidParameter("num_coefs must be > 0"));
+    }
+    if config.start_coef + config.num_coefs > config.n_bands {
+        return Err(FlucomaError::InvalidParameter(
+            "start_coef + num_coefs must be <= n_bands",
+        ));
+    }
+    if config.lo_hz >= config.hi_hz {
+        return Err(FlucomaError::InvalidParameter("lo_hz must be < hi_hz"));
+    }
+    Ok(())
+}
+
+/// Centred first differences of row-major `frames`, using one-sided
+/// differences at the first and last frame.
+fn deltas(frames: &[f64], cols: usize) -> Vec<f64> {
+    let rows = frames.len() / cols;
+    let mut out = vec![0.0; frames.len()];
+    if rows < 2 {
+        return out;
+    }
+    for row in 0..rows {
+        let prev = row.saturating_sub(1);
+        let next = (row + 1).min(rows - 1);
+        let span = (next - prev) as f64;
+        for col in 0..cols {
+            out[row * cols + col] = (frames[next * cols + col] - frames[prev * cols + col]) / span;
+        }
+    }
+    out
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deltas_are_centred_differences() {
+        let frames = [0.0, 10.0, 1.0, 10.0, 4.0, 10.0];
+        assert_eq!(deltas(&frames, 2), vec![1.0, 0.0, 2.0, 0.0, 3.0, 0.0]);
+        assert_eq!(deltas(&[5.0, 6.0], 2), vec![0.0, 0.0]);
+
+        let config = BufMfccConfig {
+            start_coef: 30,
+            ..Default::default()
+        };
+        assert!(BufMfcc::new(config).is_err());
+    }
+
+    #[test]
+    fn buf_mfcc_selects_coefficients_and_appends_deltas() {
+        let audio: Vec<f64> = (0..8192).map(|i| (i as f64 * 0.05).sin()).collect();
+        let mfcc = BufMfcc::new(BufMfccConfig {
+            num_coefs: 12,
+            start_coef: 1,
+            deltas: true,
+            ..Default::default()
+        })
+        .unwrap();
+        let features = mfcc.process(&audio, 44100.0).unwrap();
+        assert_eq!(features.num_frames(), 8192 / 512 + 1);
+        assert_eq!(features.num_features(), 24);
+        assert_eq!(features.column_names()[0], "mfcc1");
+        assert_eq!(features.column_names()[12], "delta1");
+    }
+}
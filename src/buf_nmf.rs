@@ -0,0 +1,262 @@
+use crate::buf_frames::validate_fft;
+use crate::error::FlucomaError;
+use crate::fft_settings::impl_fft_settings;
+use crate::matrix::Matrix;
+use crate::nmf::Nmf;
+use crate::progress::Progress;
+use crate::stft::{Istft, Stft, WindowType};
+
+// -------------------------------------------------------------------------------------------------
+
+/// Number of NMF updates between progress reports.
+const ITERATION_CHUNK: usize = 10;
+
+/// Configuration for [`BufNmf`].
+///
+/// Defaults match FluidBufNMF.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BufNmfConfig {
+    /// Number of components to decompose into.
+    pub components: usize,
+    /// Number of multiplicative-update iterations.
+    pub iterations: usize,
+    /// Seed for the random initialisation; -1 for a random seed, or the
+    /// [global seed](crate::set_global_seed) if one is set.
+    pub seed: isize,
+    pub window_size: usize,
+    pub hop_size: usize,
+    pub fft_size: usize,
+}
+
+impl Default for BufNmfConfig {
+    fn default() -> Self {
+        Self {
+            components: 1,
+            iterations: 100,
+            seed: -1,
+            window_size: 1024,
+            hop_size: 512,
+            fft_size: 1024,
+        }
+    }
+}
+
+impl_fft_settings!(BufNmfConfig);
+
+/// Result of a [`BufNmf`] decomposition.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BufNmfResult {
+    /// One resynthesised audio buffer per component, each as long as the input.
+    pub components: Vec<Vec<f64>>,
+    /// Spectral bases W, shape `components × n_bins`.
+    pub bases: Matrix,
+    /// Activations H, shape `n_frames × components`.
+    pub activations: Matrix,
+}
+
+/// Whole-buffer NMF decomposition of audio into components.
+///
+/// Runs STFT → NMF on the magnitude spectrogram → per-component soft masks
+/// applied to the complex spectrogram → ISTFT, mirroring FluidBufNMF. The
+/// components sum back to (approximately) the input signal.
+///
+/// See <https://learn.flucoma.org/reference/bufnmf>
+///
+/// ```rust,no_run
+/// use flucoma_rs::decomposition::{BufNmf, BufNmfConfig};
+///
+/// let audio = vec![0.0f64; 44100];
+/// let nmf = BufNmf::new(BufNmfConfig {
+///     components: 3,
+///     seed: 42,
+///     ..Default::default()
+/// })
+/// .unwrap();
+/// let result = nmf.process(&audio).unwrap();
+/// assert_eq!(result.components.len(), 3);
+/// assert_eq!(result.bases.rows(), 3);
+/// ```
+#[derive(Debug, Clone)]
+pub struct BufNmf {
+    config: BufNmfConfig,
+}
+
+impl BufNmf {
+    /// # Errors
+    /// Returns an error if the configuration is invalid.
+    pub fn new(config: BufNmfConfig) -> Result<Self, FlucomaError> {
+        validate_config(&config)?;
+        Ok(Self { config })
+    }
+
+    pub fn config(&self) -> &BufNmfConfig {
+        &self.config
+    }
+
+    pub fn set_config(&mut self, config: BufNmfConfig) -> Result<(), FlucomaError> {
+        validate_config(&config)?;
+        self.config = config;
+        Ok(())
+    }
+
+    /// Decompose `signal` into the configured number of components.
+    ///
+    /// # Errors
+    /// Returns an error if the STFT or NMF processors cannot be
+    /// created.
+    pub fn process(&self, signal: &[f64]) -> Result<BufNmfResult, FlucomaError> {
+        self.process_with_progress(signal, &mut Progress::new())
+    }
+
+    /// Like [`BufNmf::process`], reporting progress after the analysis, every
+    /// few factorisation iterations and each resynthesised component, and
+    /// stopping early if `progress` is cancelled.
+    ///
+    /// # Errors
+    /// Returns [`FlucomaError::Cancelled`] if cancelled, or as for
+    /// [`BufNmf::process`].
+    pub fn process_with_progress(
+        &self,
+        signal: &[f64],
+        progress: &mut Progress,
+    ) -> Result<BufNmfResult, FlucomaError> {
+        let c = &self.config;
+        let mut stft = Stft::new(c.window_size, c.fft_size, c.hop_size, WindowType::Hann)?;
+        let mut istft = Istft::new(c.window_size, c.fft_size, c.hop_size, WindowType::Hann)?;
+        let spectrogram = stft.analyze(signal);
+        progress.report(0.1)?;
+        let magnitudes = spectrogram.magnitudes();
+        let mut nmf = Nmf::new()?;
+        let mut done = c.iterations.min(ITERATION_CHUNK);
+        let mut result = nmf.process(&magnitudes, c.components, done, true, true, c.seed);
+        progress.report(0.1 + 0.4 * done as f64 / c.iterations as f64)?;
+        while done < c.iterations {
+            let chunk = (c.iterations - done).min(ITERATION_CHUNK);
+            result = nmf.process_from(&magnitudes, &result, chunk, true, true);
+            done += chunk;
+            progress.report(0.1 + 0.4 * done as f64 / c.iterations as f64)?;
+        }
+
+        let n_bins = spectrogram.num_bins();
+        let bases = result.bases.data();
+        let activations = result.activations.data();
+        let estimate = result.estimate.data();
+        let components = (0..c.components)
+            .map(|k| -> Result<Vec<f64>, FlucomaError> {
+                let mut masked = spectrogram.clone();
+                for frame in 0..masked.num_frames() {
+                    let gain = activations[frame * c.components + k];
+                    for (bin, value) in masked.frame_mut(frame).iter_mut().enumerate() {
+                        let total = estimate[frame * n_bins + bin];
+                        let mask = if total > 0.0 {
+                            gain * bases[k * n_bins + bin] / total
+                        } else {
+                            1.0 / c.components as f64
+                        };
+                        *value *= mask;
+                    }
+                }
+                let component = istft.synthesize(&masked);
+                progress.report(0.5 + 0.5 * (k + 1) as f64 / c.components as f64)?;
+                Ok(component)
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(BufNmfResult {
+            components,
+            bases: result.bases,
+            activations: result.activations,
+        })
+    }
+}
+
+fn validate_config(config: &BufNmfConfig) -> Result<(), FlucomaError> {
+    validate_fft(config.window_size, config.hop_size, config.fft_size)?;
+    if config.components == 0 {
+        return Err(FlucomaError::InvalidParameter("components must be > 0"));
+    }
+    if config.iterations == 0 {
+        return Err(FlucomaError::InvalidParameter("iterations must be > 0"));
+    }
+    Ok(())
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::progress::CancellationToken;
+
+    #[test]
+    fn buf_nmf_components_sum_to_input() {
+        let audio: Vec<f64> = (0..8192)
+            .map(|i| (i as f64 * 0.03).sin() + 0.5 * (i as f64 * 0.4).sin())
+            .collect();
+        let nmf = BufNmf::new(BufNmfConfig {
+            components: 2,
+            iterations: 50,
+            seed: 7,
+            ..Default::default()
+        })
+        .unwrap();
+        let result = nmf.process(&audio).unwrap();
+        assert_eq!(result.components.len(), 2);
+        assert_eq!(result.bases.rows(), 2);
+        assert_eq!(result.activations.cols(), 2);
+        for (i, sample) in audio.iter().enumerate().take(7000).skip(1024) {
+            let sum = result.components[0][i] + result.components[1][i];
+            assert!((sum - sample).abs() < 1e-3, "sample {i}: {sum}");
+        }
+        assert!(BufNmf::new(BufNmfConfig {
+            components: 0,
+            ..Default::default()
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn buf_nmf_factorises_in_chunks_and_cancels_between_them() {
+        let audio: Vec<f64> = (0..8192).map(|i| (i as f64 * 0.03).sin()).collect();
+        let config = BufNmfConfig {
+            components: 2,
+            iterations: 35,
+            seed: 7,
+            ..Default::default()
+        };
+        let nmf = BufNmf::new(config.clone()).unwrap();
+        let result = nmf.process(&audio).unwrap();
+
+        // Chunking the updates gives the same factorisation as one call.
+        let mut stft = Stft::new(1024, 1024, 512, WindowType::Hann).unwrap();
+        let magnitudes = stft.analyze(&audio).magnitudes();
+        let whole = Nmf::new()
+            .unwrap()
+            .process(&magnitudes, 2, 35, true, true, 7);
+        for (a, b) in whole.bases.data().iter().zip(result.bases.data()) {
+            assert!((a - b).abs() < 1e-9 * a.abs().max(1.0), "{a} vs {b}");
+        }
+
+        let token = CancellationToken::new();
+        let mut reports = Vec::new();
+        let mut progress = Progress::new()
+            .on_progress(|fraction| {
+                reports.push(fraction);
+                if fraction > 0.3 {
+                    token.cancel();
+                }
+            })
+            .cancel_with(token.clone());
+        assert_eq!(
+            nmf.process_with_progress(&audio, &mut progress)
+                .unwrap_err(),
+            FlucomaError::Cancelled
+        );
+        drop(progress);
+        // The analysis, 10 of the 35 iterations, then cancelled after 20.
+        assert_eq!(reports.len(), 3);
+        assert!(reports[2] < 0.5, "{reports:?}");
+    }
+}
@@ -0,0 +1,246 @@
+use crate::buf_frames::{fill_frame, num_frames, validate_fft};
+use crate::error::FlucomaError;
+use crate::fft_settings::impl_fft_settings;
+use crate::novelty_audio_seg::{NoveltyAlgorithm, NoveltyAudioSlice, NoveltyAudioSliceConfig};
+use crate::slice::{backtrack_points, slices_from_onsets, Slice, SlicePoint};
+
+// -------------------------------------------------------------------------------------------------
+
+/// Configuration for [`BufNoveltySlice`].
+///
+/// Defaults match FluidBufNoveltySlice.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BufNoveltySliceConfig {
+    pub algorithm: NoveltyAlgorithm,
+    /// Size of the checkerboard kernel in frames (odd).
+    pub kernel_size: usize,
+    /// Novelty value above which a slice point is declared.
+    pub threshold: f64,
+    /// Median filter size applied to the novelty curve in frames (odd).
+    pub filter_size: usize,
+    /// Minimum frames between successive slice points.
+    pub min_slice_length: usize,
+    pub window_size: usize,
+    pub hop_size: usize,
+    pub fft_size: usize,
+    /// Move each slice point back to the preceding energy minimum, measured
+    /// over `hop_size` frames (see
+    /// [`backtrack_onsets`](crate::segmentation::backtrack_onsets)).
+    pub backtrack: bool,
+}
+
+impl Default for BufNoveltySliceConfig {
+    fn default() -> Self {
+        Self {
+            algorithm: NoveltyAlgorithm::Spectrum,
+            kernel_size: 3,
+            threshold: 0.5,
+            filter_size: 1,
+            min_slice_length: 2,
+            window_size: 1024,
+            hop_size: 512,
+            fft_size: 1024,
+            backtrack: false,
+        }
+    }
+}
+
+impl_fft_settings!(BufNoveltySliceConfig);
+
+/// Offline novelty slicing of a whole mono buffer.
+///
+/// Runs [`NoveltyAudioSlice`] over the centred frames of the buffer and
+/// returns slice points in samples, compensated for the latency of the
+/// novelty kernel, the median filter and peak picking.
+///
+/// See <https://learn.flucoma.org/reference/noveltyslice>
+///
+/// ```rust,no_run
+/// use flucoma_rs::segmentation::{BufNoveltySlice, BufNoveltySliceConfig, NoveltyAlgorithm};
+///
+/// let audio = vec![0.0f64; 44100];
+/// let slicer = BufNoveltySlice::new(BufNoveltySliceConfig {
+///     algorithm: NoveltyAlgorithm::Mfcc,
+///     ..Default::default()
+/// })
+/// .unwrap();
+/// let slices = slicer.process(&audio, 44100.0).unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct BufNoveltySlice {
+    config: BufNoveltySliceConfig,
+}
+
+impl BufNoveltySlice {
+    /// # Errors
+    /// Returns an error if the configuration is invalid.
+    pub fn new(config: BufNoveltySliceConfig) -> Result<Self, FlucomaError> {
+        validate_config(&config)?;
+        Ok(Self { config })
+    }
+
+    pub fn config(&self) -> &BufNoveltySliceConfig {
+        &self.config
+    }
+
+    pub fn set_config(&mut self, config: BufNoveltySliceConfig) -> Result<(), FlucomaError> {
+        validate_config(&config)?;
+        self.config = config;
+        Ok(())
+    }
+
+    /// Slice `signal` and return the slice points in samples, in ascending
+    /// order.
+    ///
+    /// # Errors
+    /// Returns an error if `sample_rate <= 0` or an analyser cannot
+    /// be created.
+    pub fn process(&self, signal: &[f64], sample_rate: f64) -> Result<Vec<usize>, FlucomaError> {
+        let points = self.process_scored(signal, sample_rate)?;
+        Ok(points.iter().map(|point| point.position).collect())
+    }
+
+    /// Slice `signal` and return the slice points with their confidence: the
+    /// height of the novelty peak above `threshold` (see [`SlicePoint`]).
+    ///
+    /// # Errors
+    /// See [`BufNoveltySlice::process`].
+    pub fn process_scored(
+        &self,
+        signal: &[f64],
+        sample_rate: f64,
+    ) -> Result<Vec<SlicePoint>, FlucomaError> {
+        if sample_rate <= 0.0 {
+            return Err(FlucomaError::InvalidParameter("sample_rate must be > 0"));
+        }
+        let config = &self.config;
+        let mut slicer = NoveltyAudioSlice::new(
+            NoveltyAudioSliceConfig {
+                algorithm: config.algorithm,
+                kernel_size: config.kernel_size,
+                threshold: config.threshold,
+                filter_size: config.filter_size,
+                min_slice_length: config.min_slice_length,
+                window_size: config.window_size,
+                fft_size: config.fft_size,
+            },
+            sample_rate,
+        )?;
+
+        let latency = slicer.latency_frames();
+        let mut frame = vec![0.0; config.window_size];
+        let mut points: Vec<SlicePoint> = Vec::new();
+        for index in 0..num_frames(signal.len(), config.hop_size) {
+            fill_frame(signal, index, config.hop_size, &mut frame);
+            let detected = slicer.process_frame(&frame);
+            if detected > 0.0 && index >= latency {
+                let position = (index - latency) * config.hop_size;
+                if points.last().is_none_or(|last| last.position != position) {
+                    points.push(SlicePoint {
+                        position,
+                        confidence: slicer.novelty() - config.threshold,
+                    });
+                }
+            }
+        }
+        if config.backtrack {
+            points = backtrack_points(signal, &points, config.hop_size);
+        }
+        Ok(points)
+    }
+
+    /// Slice `signal` into contiguous [`Slice`]s covering the whole buffer,
+    /// with positions in samples and seconds.
+    ///
+    /// # Errors
+    /// See [`BufNoveltySlice::process`].
+    pub fn slices(&self, signal: &[f64], sample_rate: f64) -> Result<Vec<Slice>, FlucomaError> {
+        let onsets = self.process(signal, sample_rate)?;
+        Ok(slices_from_onsets(&onsets, signal.len(), sample_rate))
+    }
+}
+
+fn validate_config(config: &BufNoveltySliceConfig) -> Result<(), FlucomaError> {
+    validate_fft(config.window_size, config.hop_size, config.fft_size)?;
+    if config.kernel_size == 0 || config.kernel_size.is_multiple_of(2) {
+        return Err(FlucomaError::InvalidParameter(
+            "kernel_size must be odd and > 0",
+        ));
+    }
+    if config.filter_size == 0 || config.filter_size.is_multiple_of(2) {
+        return Err(FlucomaError::InvalidParameter(
+            "filter_size must be odd and > 0",
+        ));
+    }
+    Ok(())
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buf_novelty_slice_rejects_even_kernel() {
+        let config = BufNoveltySliceConfig {
+            kernel_size: 4,
+            ..Default::default()
+        };
+        assert!(BufNoveltySlice::new(config).is_err());
+    }
+
+    #[test]
+    fn buf_novelty_slice_finds_change_in_every_algorithm() {
+        use std::f64::consts::PI;
+        let sr = 44100.0;
+        // noise-like burst (period 101 samples, ~437 Hz) followed by a
+        // 1 kHz sine, so every feature changes at the midpoint
+        let audio: Vec<f64> = (0..44100)
+            .map(|i| {
+                if i < 22050 {
+                    ((i * 7919) % 101) as f64 / 50.0 - 1.0
+                } else {
+                    0.5 * (2.0 * PI * 1000.0 * i as f64 / sr).sin()
+                }
+            })
+            .collect();
+        for algorithm in [
+            NoveltyAlgorithm::Spectrum,
+            NoveltyAlgorithm::Mfcc,
+            NoveltyAlgorithm::Chroma,
+            NoveltyAlgorithm::Pitch,
+            NoveltyAlgorithm::Loudness,
+        ] {
+            let slicer = BufNoveltySlice::new(BufNoveltySliceConfig {
+                algorithm,
+                kernel_size: 9,
+                threshold: 0.0,
+                ..Default::default()
+            })
+            .unwrap();
+            let slices = slicer.process(&audio, sr).unwrap();
+            assert!(slices.windows(2).all(|w| w[0] < w[1]));
+            assert!(slices.iter().all(|&s| s <= audio.len()));
+            assert!(
+                slices.iter().any(|&s| s.abs_diff(22050) <= 4 * 512),
+                "{algorithm:?}: no slice near 22050 in {slices:?}"
+            );
+            let scored = slicer.process_scored(&audio, sr).unwrap();
+            assert_eq!(scored.len(), slices.len());
+            assert!(scored.iter().all(|p| p.confidence > 0.0));
+            if algorithm == NoveltyAlgorithm::Spectrum {
+                // The change in material is the strongest novelty peak.
+                let strongest = scored
+                    .iter()
+                    .max_by(|a, b| a.confidence.total_cmp(&b.confidence))
+                    .unwrap();
+                assert!(
+                    strongest.position.abs_diff(22050) < 4 * 512,
+                    "{strongest:?}"
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,155 @@
+use std::ops::Range;
+
+use crate::error::FlucomaError;
+use crate::matrix::Matrix;
+
+// -------------------------------------------------------------------------------------------------
+
+/// Flatten a frames × features matrix frame by frame into a single row:
+/// `[f0 of frame 0, f1 of frame 0, ..., f0 of frame 1, ...]`.
+///
+/// This is FluidBufFlatten's default (`axis = 1`) order.
+pub fn flatten_frames(matrix: &Matrix) -> Vec<f64> {
+    matrix.data().to_vec()
+}
+
+/// Flatten a frames × features matrix feature by feature into a single row:
+/// `[frame 0 of f0, frame 1 of f0, ..., frame 0 of f1, ...]`.
+///
+/// This is FluidBufFlatten's `axis = 0` order.
+pub fn flatten_features(matrix: &Matrix) -> Vec<f64> {
+    matrix.transpose().data().to_vec()
+}
+
+/// Select rows (frames) and columns (features) of `matrix` by index, in the
+/// given order. An empty index list selects everything, as in FluidBufSelect.
+///
+/// ```rust
+/// use flucoma_rs::data::{select, Matrix};
+///
+/// let m = Matrix::from_vec(vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0], 2, 3).unwrap();
+/// let picked = select(&m, &[], &[2, 0]).unwrap();
+/// assert_eq!(picked.data(), &[3.0, 1.0, 6.0, 4.0]);
+/// ```
+///
+/// # Errors
+/// Returns an error if any index is out of range.
+pub fn select(matrix: &Matrix, rows: &[usize], cols: &[usize]) -> Result<Matrix, FlucomaError> {
+    let rows: Vec<usize> = if rows.is_empty() {
+        (0..matrix.rows()).collect()
+    } else {
+        rows.to_vec()
+    };
+    let cols: Vec<usize> = if cols.is_empty() {
+        (0..matrix.cols()).collect()
+    } else {
+        cols.to_vec()
+    };
+    if rows.iter().any(|&r| r >= matrix.rows()) {
+        return Err(FlucomaError::InvalidParameter("row index out of range"));
+    }
+    if cols.iter().any(|&c| c >= matrix.cols()) {
+        return Err(FlucomaError::InvalidParameter("column index out of range"));
+    }
+    let data = rows
+        .iter()
+        .flat_map(|&r| {
+            cols.iter()
+                .map(move |&c| matrix.data()[r * matrix.cols() + c])
+        })
+        .collect();
+    Matrix::from_vec(data, rows.len(), cols.len())
+}
+
+/// Select a contiguous block of rows (frames) and columns (features).
+///
+/// # Errors
+/// Returns an error if either range is empty or out of bounds.
+pub fn select_range(
+    matrix: &Matrix,
+    rows: Range<usize>,
+    cols: Range<usize>,
+) -> Result<Matrix, FlucomaError> {
+    if rows.is_empty() || cols.is_empty() {
+        return Err(FlucomaError::InvalidParameter(
+            "selection ranges must not be empty",
+        ));
+    }
+    if rows.end > matrix.rows() || cols.end > matrix.cols() {
+        return Err(FlucomaError::InvalidParameter(
+            "selection range out of bounds",
+        ));
+    }
+    let data = rows
+        .clone()
+        .flat_map(|r| {
+            let row = r * matrix.cols();
+            &matrix.data()[row + cols.start..row + cols.end]
+        })
+        .copied()
+        .collect();
+    Matrix::from_vec(data, rows.len(), cols.len())
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Convert channel-major data (each channel's frames contiguous, as taken by
+/// [`BufStats`](crate::data::BufStats)) into a row-major frames × channels
+/// matrix.
+///
+/// # Errors
+/// Returns an error if `data` is empty or its length is not a
+/// multiple of `num_channels`.
+pub fn channel_major_to_matrix(data: &[f64], num_channels: usize) -> Result<Matrix, FlucomaError> {
+    if num_channels == 0 || !data.len().is_multiple_of(num_channels) {
+        return Err(FlucomaError::InvalidParameter(
+            "data length must be a non-zero multiple of num_channels",
+        ));
+    }
+    Matrix::from_vec(data.to_vec(), num_channels, data.len() / num_channels)
+        .map(|channels| channels.transpose())
+}
+
+/// Convert a row-major frames × channels matrix into channel-major data.
+pub fn matrix_to_channel_major(matrix: &Matrix) -> Vec<f64> {
+    flatten_features(matrix)
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn two_by_three() -> Matrix {
+        Matrix::from_vec(vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0], 2, 3).unwrap()
+    }
+
+    #[test]
+    fn flatten_orders() {
+        let m = two_by_three();
+        assert_eq!(flatten_frames(&m), vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+        assert_eq!(flatten_features(&m), vec![1.0, 4.0, 2.0, 5.0, 3.0, 6.0]);
+    }
+
+    #[test]
+    fn select_by_index_and_range() {
+        let m = two_by_three();
+        assert_eq!(select(&m, &[1], &[]).unwrap().data(), &[4.0, 5.0, 6.0]);
+        assert!(select(&m, &[2], &[]).is_err());
+        let block = select_range(&m, 0..2, 1..3).unwrap();
+        assert_eq!((block.rows(), block.cols()), (2, 2));
+        assert_eq!(block.data(), &[2.0, 3.0, 5.0, 6.0]);
+        assert!(select_range(&m, 0..3, 0..1).is_err());
+    }
+
+    #[test]
+    fn channel_major_roundtrip() {
+        let channel_major = vec![1.0, 2.0, 3.0, 10.0, 20.0, 30.0];
+        let m = channel_major_to_matrix(&channel_major, 2).unwrap();
+        assert_eq!((m.rows(), m.cols()), (3, 2));
+        assert_eq!(m.data(), &[1.0, 10.0, 2.0, 20.0, 3.0, 30.0]);
+        assert_eq!(matrix_to_channel_major(&m), channel_major);
+        assert!(channel_major_to_matrix(&channel_major, 4).is_err());
+    }
+}
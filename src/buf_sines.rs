@@ -0,0 +1,215 @@
+use crate::buf_frames::validate_fft;
+use crate::error::FlucomaError;
+use crate::fft_settings::impl_fft_settings;
+use crate::sine_extraction::{SineExtraction, SineExtractionParams};
+use crate::spectrogram::Spectrogram;
+use crate::stft::{ComplexSpectrum, Istft, Stft, WindowType};
+
+// -------------------------------------------------------------------------------------------------
+
+/// Configuration for [`BufSines`].
+///
+/// Defaults match FluidBufSines.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BufSinesConfig {
+    /// Peak detection threshold in dB.
+    pub detection_threshold: f64,
+    /// Minimum track length in frames before a partial is reported.
+    pub min_track_length: usize,
+    /// Amplitude threshold for track birth at low frequencies, in dB.
+    pub birth_low_threshold: f64,
+    /// Amplitude threshold for track birth at high frequencies, in dB.
+    pub birth_high_threshold: f64,
+    /// Tracking algorithm: 0 = greedy, 1 = Hungarian.
+    pub track_method: usize,
+    /// Maximum amplitude difference for a track match, in dB.
+    pub track_magnitude_range: f64,
+    /// Maximum frequency difference for a track match, in Hz.
+    pub track_frequency_range: f64,
+    /// Track matching probability threshold (0.0–1.0).
+    pub track_probability: f64,
+    /// Bandwidth in bins for partial synthesis.
+    pub bandwidth: usize,
+    pub window_size: usize,
+    pub hop_size: usize,
+    pub fft_size: usize,
+}
+
+impl Default for BufSinesConfig {
+    fn default() -> Self {
+        Self {
+            detection_threshold: -96.0,
+            min_track_length: 15,
+            birth_low_threshold: -24.0,
+            birth_high_threshold: -60.0,
+            track_method: 0,
+            track_magnitude_range: 15.0,
+            track_frequency_range: 50.0,
+            track_probability: 0.5,
+            bandwidth: 76,
+            window_size: 1024,
+            hop_size: 512,
+            fft_size: 1024,
+        }
+    }
+}
+
+impl_fft_settings!(BufSinesConfig);
+
+/// Result of a [`BufSines`] separation.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BufSinesResult {
+    /// Sinusoidal content, as long as the input.
+    pub sines: Vec<f64>,
+    /// Everything else, as long as the input.
+    pub residual: Vec<f64>,
+}
+
+/// Whole-buffer sinusoidal separation into sines and residual.
+///
+/// The tracker's `min_track_length` frames of latency are compensated by
+/// running extra frames past the end of the signal, so both outputs are
+/// time-aligned with the input and sum back to it.
+///
+/// See <https://learn.flucoma.org/reference/sines>
+///
+/// ```rust,no_run
+/// use flucoma_rs::decomposition::{BufSines, BufSinesConfig};
+///
+/// let audio = vec![0.0f64; 44100];
+/// let sines = BufSines::new(BufSinesConfig::default()).unwrap();
+/// let result = sines.process(&audio, 44100.0).unwrap();
+/// assert_eq!(result.sines.len(), audio.len());
+/// ```
+#[derive(Debug, Clone)]
+pub struct BufSines {
+    config: BufSinesConfig,
+}
+
+impl BufSines {
+    /// # Errors
+    /// Returns an error if the configuration is invalid.
+    pub fn new(config: BufSinesConfig) -> Result<Self, FlucomaError> {
+        validate_config(&config)?;
+        Ok(Self { config })
+    }
+
+    pub fn config(&self) -> &BufSinesConfig {
+        &self.config
+    }
+
+    pub fn set_config(&mut self, config: BufSinesConfig) -> Result<(), FlucomaError> {
+        validate_config(&config)?;
+        self.config = config;
+        Ok(())
+    }
+
+    /// Separate `signal` into sines and residual.
+    ///
+    /// # Errors
+    /// Returns an error if `sample_rate <= 0` or the processors cannot
+    /// be created.
+    pub fn process(
+        &self,
+        signal: &[f64],
+        sample_rate: f64,
+    ) -> Result<BufSinesResult, FlucomaError> {
+        if sample_rate <= 0.0 {
+            return Err(FlucomaError::InvalidParameter("sample_rate must be > 0"));
+        }
+        let c = &self.config;
+        let params = SineExtractionParams {
+            sample_rate,
+            detection_threshold: c.detection_threshold,
+            min_track_length: c.min_track_length,
+            birth_low_threshold: c.birth_low_threshold,
+            birth_high_threshold: c.birth_high_threshold,
+            track_method: c.track_method,
+            zeta_a: c.track_magnitude_range,
+            zeta_f: c.track_frequency_range,
+            delta: c.track_probability,
+            bandwidth: c.bandwidth,
+        };
+        let mut stft = Stft::new(c.window_size, c.fft_size, c.hop_size, WindowType::Hann)?;
+        let mut istft = Istft::new(c.window_size, c.fft_size, c.hop_size, WindowType::Hann)?;
+        let mut extraction = SineExtraction::new(c.window_size, c.fft_size, c.fft_size)?;
+
+        // Run `min_track_length` extra frames so the delayed output covers the
+        // whole signal, then drop the leading frames to re-align it.
+        let mut padded = signal.to_vec();
+        padded.resize(signal.len() + c.min_track_length * c.hop_size, 0.0);
+        let spectrogram = stft.analyze(&padded);
+        let mut sines = Vec::with_capacity(spectrogram.num_frames());
+        let mut residual = Vec::with_capacity(spectrogram.num_frames());
+        for index in 0..spectrogram.num_frames() {
+            let (s, r) = extraction.process_frame(spectrogram.frame(index), &params);
+            if index >= c.min_track_length {
+                sines.push(ComplexSpectrum { bins: s.to_vec() });
+                residual.push(ComplexSpectrum { bins: r.to_vec() });
+            }
+        }
+
+        let mut synthesize = |frames| {
+            let aligned = Spectrogram::from_frames(
+                frames,
+                c.window_size,
+                c.fft_size,
+                c.hop_size,
+                WindowType::Hann,
+                signal.len(),
+            );
+            istft.synthesize(&aligned)
+        };
+        Ok(BufSinesResult {
+            sines: synthesize(sines),
+            residual: synthesize(residual),
+        })
+    }
+}
+
+fn validate_config(config: &BufSinesConfig) -> Result<(), FlucomaError> {
+    validate_fft(config.window_size, config.hop_size, config.fft_size)?;
+    if !config.fft_size.is_multiple_of(2) {
+        return Err(FlucomaError::InvalidParameter("fft_size must be even"));
+    }
+    if config.track_method > 1 {
+        return Err(FlucomaError::InvalidParameter(
+            "track_method must be 0 (greedy) or 1 (Hungarian)",
+        ));
+    }
+    if !(0.0..=1.0).contains(&config.track_probability) {
+        return Err(FlucomaError::InvalidParameter(
+            "track_probability must be in [0, 1]",
+        ));
+    }
+    Ok(())
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buf_sines_outputs_sum_to_input() {
+        use std::f64::consts::PI;
+        let sr = 44100.0;
+        let audio: Vec<f64> = (0..22050)
+            .map(|i| 0.5 * (2.0 * PI * 440.0 * i as f64 / sr).sin())
+            .collect();
+        let sines = BufSines::new(BufSinesConfig::default()).unwrap();
+        let result = sines.process(&audio, sr).unwrap();
+        assert_eq!(result.sines.len(), audio.len());
+        assert_eq!(result.residual.len(), audio.len());
+        for (i, sample) in audio.iter().enumerate().skip(1024).take(16384) {
+            let sum = result.sines[i] + result.residual[i];
+            assert!((sum - sample).abs() < 1e-3, "sample {i}: {sum}");
+        }
+        let sine_energy: f64 = result.sines.iter().map(|x| x * x).sum();
+        let residual_energy: f64 = result.residual.iter().map(|x| x * x).sum();
+        assert!(sine_energy > residual_energy);
+    }
+}
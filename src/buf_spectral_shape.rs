@@ -0,0 +1,160 @@
+use crate::buf_frames::{for_each_magnitude_frame, num_frames, validate_fft};
+use crate::error::FlucomaError;
+use crate::feature_matrix::FeatureMatrix;
+use crate::fft_settings::impl_fft_settings;
+use crate::spectral_shape::{SpectralShape, SpectralShapeResult};
+
+// -------------------------------------------------------------------------------------------------
+
+/// Configuration for [`BufSpectralShape`].
+///
+/// Defaults match FluidBufSpectralShape.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BufSpectralShapeConfig {
+    /// Bins below this frequency are ignored.
+    pub min_hz: f64,
+    /// Bins above this frequency are ignored; `None` for Nyquist.
+    pub max_hz: Option<f64>,
+    /// Energy percentage for the rolloff descriptor.
+    pub rolloff_percent: f64,
+    /// Report frequencies as MIDI note numbers instead of Hz.
+    pub log_frequency: bool,
+    /// Square the magnitudes (power spectrum input).
+    pub use_power: bool,
+    pub window_size: usize,
+    pub hop_size: usize,
+    pub fft_size: usize,
+}
+
+impl Default for BufSpectralShapeConfig {
+    fn default() -> Self {
+        Self {
+            min_hz: 0.0,
+            max_hz: None,
+            rolloff_percent: 95.0,
+            log_frequency: false,
+            use_power: false,
+            window_size: 1024,
+            hop_size: 512,
+            fft_size: 1024,
+        }
+    }
+}
+
+impl_fft_settings!(BufSpectralShapeConfig);
+
+/// Whole-buffer spectral shape descriptors.
+///
+/// Returns a frames × 7 [`FeatureMatrix`] whose columns are named after
+/// [`SpectralShapeResult::NAMES`], ready to be summarised with
+/// [`BufStats`](crate::data::BufStats).
+///
+/// See <https://learn.flucoma.org/reference/spectralshape>
+///
+/// ```rust,no_run
+/// use flucoma_rs::analyzation::{BufSpectralShape, BufSpectralShapeConfig};
+///
+/// let audio = vec![0.0f64; 44100];
+/// let shape = BufSpectralShape::new(BufSpectralShapeConfig::default()).unwrap();
+/// let descriptors = shape.process(&audio, 44100.0).unwrap();
+/// assert_eq!(descriptors.column_names()[0], "centroid");
+/// ```
+#[derive(Debug, Clone)]
+pub struct BufSpectralShape {
+    config: BufSpectralShapeConfig,
+}
+
+impl BufSpectralShape {
+    /// # Errors
+    /// Returns an error if the configuration is invalid.
+    pub fn new(config: BufSpectralShapeConfig) -> Result<Self, FlucomaError> {
+        validate_config(&config)?;
+        Ok(Self { config })
+    }
+
+    pub fn config(&self) -> &BufSpectralShapeConfig {
+        &self.config
+    }
+
+    pub fn set_config(&mut self, config: BufSpectralShapeConfig) -> Result<(), FlucomaError> {
+        validate_config(&config)?;
+        self.config = config;
+        Ok(())
+    }
+
+    /// Compute the spectral shape descriptors of `signal`.
+    ///
+    /// # Errors
+    /// Returns an error if `sample_rate <= 0` or the analysers cannot
+    /// be created.
+    pub fn process(&self, signal: &[f64], sample_rate: f64) -> Result<FeatureMatrix, FlucomaError> {
+        if sample_rate <= 0.0 {
+            return Err(FlucomaError::InvalidParameter("sample_rate must be > 0"));
+        }
+        let c = &self.config;
+        let nyquist = sample_rate / 2.0;
+        let max_hz = c.max_hz.unwrap_or(nyquist).min(nyquist);
+        let mut shape = SpectralShape::new(c.fft_size / 2 + 1)?;
+        let mut data = Vec::with_capacity(num_frames(signal.len(), c.hop_size) * 7);
+        for_each_magnitude_frame(
+            signal,
+            c.window_size,
+            c.hop_size,
+            c.fft_size,
+            |magnitudes| {
+                let result = shape.process_frame(
+                    magnitudes,
+                    sample_rate,
+                    c.min_hz,
+                    max_hz,
+                    c.rolloff_percent,
+                    c.log_frequency,
+                    c.use_power,
+                );
+                data.extend(result.to_array());
+            },
+        )?;
+        let columns = SpectralShapeResult::NAMES.map(str::to_owned).to_vec();
+        FeatureMatrix::new(data, columns, sample_rate, c.window_size, c.hop_size)
+    }
+}
+
+fn validate_config(config: &BufSpectralShapeConfig) -> Result<(), FlucomaError> {
+    validate_fft(config.window_size, config.hop_size, config.fft_size)?;
+    if config.min_hz < 0.0 {
+        return Err(FlucomaError::InvalidParameter("min_hz must be >= 0"));
+    }
+    if config.max_hz.is_some_and(|max| max <= config.min_hz) {
+        return Err(FlucomaError::InvalidParameter("max_hz must be > min_hz"));
+    }
+    if !(0.0..=100.0).contains(&config.rolloff_percent) {
+        return Err(FlucomaError::InvalidParameter(
+            "rolloff_percent must be in [0, 100]",
+        ));
+    }
+    Ok(())
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buf_spectral_shape_columns() {
+        let audio: Vec<f64> = (0..4096).map(|i| (i as f64 * 0.2).sin()).collect();
+        let shape = BufSpectralShape::new(BufSpectralShapeConfig::default()).unwrap();
+        let descriptors = shape.process(&audio, 44100.0).unwrap();
+        assert_eq!(descriptors.num_features(), 7);
+        assert_eq!(descriptors.column_names()[4], "rolloff");
+        assert_eq!(descriptors.num_frames(), 4096 / 512 + 1);
+
+        let config = BufSpectralShapeConfig {
+            rolloff_percent: 120.0,
+            ..Default::default()
+        };
+        assert!(BufSpectralShape::new(config).is_err());
+    }
+}
@@ -0,0 +1,237 @@
+use crate::buf_frames::fill_block;
+use crate::error::FlucomaError;
+use crate::slice::{backtrack_points, slices_from_onsets, Slice, SlicePoint};
+use crate::transient_seg::TransientSlice;
+
+// -------------------------------------------------------------------------------------------------
+
+/// Configuration for [`BufTransientSlice`].
+///
+/// Defaults match FluidBufTransientSlice.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BufTransientSliceConfig {
+    /// AR model order.
+    pub order: usize,
+    /// Analysis block size in samples (must be > `order`).
+    pub block_size: usize,
+    /// Look-ahead padding in samples (must be > `order`).
+    pub pad_size: usize,
+    /// Spectral power threshold for residual detection.
+    pub power: f64,
+    /// Upper detection threshold.
+    pub thresh_hi: f64,
+    /// Lower detection threshold (hysteresis).
+    pub thresh_lo: f64,
+    /// Half-width of the peak-detection window in frames.
+    pub half_window: usize,
+    /// Minimum frames between successive detections.
+    pub hold: usize,
+    /// Minimum segment length in samples.
+    pub min_segment: usize,
+    /// Move each onset back to the preceding energy minimum, measured over
+    /// `pad_size` frames (see
+    /// [`backtrack_onsets`](crate::segmentation::backtrack_onsets)).
+    pub backtrack: bool,
+}
+
+impl Default for BufTransientSliceConfig {
+    fn default() -> Self {
+        Self {
+            order: 20,
+            block_size: 256,
+            pad_size: 128,
+            power: 1.0,
+            thresh_hi: 2.0,
+            thresh_lo: 1.1,
+            half_window: 7,
+            hold: 25,
+            min_segment: 1000,
+            backtrack: false,
+        }
+    }
+}
+
+/// Offline transient slicing of a whole mono buffer.
+///
+/// Feeds the buffer to [`TransientSlice`] block by block, supplying the
+/// look-ahead padding around each block (zeros beyond the buffer edges), and
+/// collects the detected onsets.
+///
+/// See <https://learn.flucoma.org/reference/transientslice>
+///
+/// ```rust,no_run
+/// use flucoma_rs::segmentation::{BufTransientSlice, BufTransientSliceConfig};
+///
+/// let audio = vec![0.0f64; 44100];
+/// let slicer = BufTransientSlice::new(BufTransientSliceConfig::default()).unwrap();
+/// let onsets = slicer.process(&audio).unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct BufTransientSlice {
+    config: BufTransientSliceConfig,
+}
+
+impl BufTransientSlice {
+    /// # Errors
+    /// Returns an error if the configuration is invalid.
+    pub fn new(config: BufTransientSliceConfig) -> Result<Self, FlucomaError> {
+        validate_config(&config)?;
+        Ok(Self { config })
+    }
+
+    pub fn config(&self) -> &BufTransientSliceConfig {
+        &self.config
+    }
+
+    pub fn set_config(&mut self, config: BufTransientSliceConfig) -> Result<(), FlucomaError> {
+        validate_config(&config)?;
+        self.config = config;
+        Ok(())
+    }
+
+    /// Detect transients in `signal` and return their onset sample indices,
+    /// in ascending order.
+    ///
+    /// Each block of `hop_size` output samples is analysed with
+    /// `(input_size - hop_size) / 2` samples of context on either side.
+    ///
+    /// # Errors
+    /// Returns an error if the segmenter cannot be created.
+    pub fn process(&self, signal: &[f64]) -> Result<Vec<usize>, FlucomaError> {
+        let points = self.process_scored(signal)?;
+        Ok(points.iter().map(|point| point.position).collect())
+    }
+
+    /// Detect transients in `signal` and return them with their confidence:
+    /// the height of the segmenter's detection function (the windowed
+    /// prediction error) above `thresh_hi`, at its peak in the block where
+    /// the onset was found (see [`SlicePoint`]).
+    ///
+    /// # Errors
+    /// See [`BufTransientSlice::process`].
+    pub fn process_scored(&self, signal: &[f64]) -> Result<Vec<SlicePoint>, FlucomaError> {
+        let config = &self.config;
+        let mut slicer = TransientSlice::new(config.order, config.block_size, config.pad_size)?;
+        slicer.set_detection_parameters(
+            config.power,
+            config.thresh_hi,
+            config.thresh_lo,
+            config.half_window,
+            config.hold,
+            config.min_segment,
+        );
+        let hop = slicer.hop_size();
+        let lead = ((slicer.input_size() - hop) / 2) as isize;
+
+        let mut block = vec![0.0; slicer.input_size()];
+        let mut output = vec![0.0; hop];
+        let mut points = Vec::new();
+        let mut position = 0;
+        while position < signal.len() {
+            fill_block(signal, position as isize - lead, &mut block);
+            slicer.process_into(&block, &mut output);
+            let confidence = (slicer.detection_peak() - config.thresh_hi).max(0.0);
+            points.extend(
+                output
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, &v)| v > 0.0)
+                    .map(|(i, _)| position + i)
+                    .filter(|&i| i < signal.len())
+                    .map(|position| SlicePoint {
+                        position,
+                        confidence,
+                    }),
+            );
+            position += hop;
+        }
+        if config.backtrack {
+            points = backtrack_points(signal, &points, config.pad_size);
+        }
+        Ok(points)
+    }
+
+    /// Slice `signal` into contiguous [`Slice`]s covering the whole buffer,
+    /// with positions in samples and seconds.
+    ///
+    /// # Errors
+    /// Returns an error if `sample_rate <= 0` or the segmenter cannot
+    /// be created.
+    pub fn slices(&self, signal: &[f64], sample_rate: f64) -> Result<Vec<Slice>, FlucomaError> {
+        if sample_rate <= 0.0 {
+            return Err(FlucomaError::InvalidParameter("sample_rate must be > 0"));
+        }
+        let onsets = self.process(signal)?;
+        Ok(slices_from_onsets(&onsets, signal.len(), sample_rate))
+    }
+}
+
+fn validate_config(config: &BufTransientSliceConfig) -> Result<(), FlucomaError> {
+    if config.order == 0 {
+        return Err(FlucomaError::InvalidParameter("order must be > 0"));
+    }
+    if config.block_size <= config.order {
+        return Err(FlucomaError::InvalidParameter("block_size must be > order"));
+    }
+    if config.pad_size <= config.order {
+        return Err(FlucomaError::InvalidParameter("pad_size must be > order"));
+    }
+    Ok(())
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buf_transient_slice_rejects_small_block() {
+        let config = BufTransientSliceConfig {
+            block_size: 10,
+            ..Default::default()
+        };
+        assert!(BufTransientSlice::new(config).is_err());
+    }
+
+    #[test]
+    fn buf_transient_slice_finds_clicks() {
+        let mut audio: Vec<f64> = (0..22050).map(|i| 0.01 * (i as f64 * 0.05).sin()).collect();
+        let clicks: Vec<usize> = (2000..audio.len()).step_by(5000).collect();
+        for &click in &clicks {
+            audio[click] = 1.0;
+        }
+        let slicer = BufTransientSlice::new(BufTransientSliceConfig::default()).unwrap();
+        let onsets = slicer.process(&audio).unwrap();
+        assert_eq!(onsets.len(), clicks.len(), "{onsets:?}");
+        for (&onset, &click) in onsets.iter().zip(&clicks) {
+            assert!(
+                onset.abs_diff(click) <= 32,
+                "slice at {onset} for click at {click}"
+            );
+        }
+        let scored = slicer.process_scored(&audio).unwrap();
+        let positions: Vec<usize> = scored.iter().map(|p| p.position).collect();
+        assert_eq!(positions, onsets);
+        assert!(slicer.process(&[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn buf_transient_slice_confidence_ranks_clicks() {
+        let mut audio: Vec<f64> = (0..22050).map(|i| 0.01 * (i as f64 * 0.05).sin()).collect();
+        audio[5000] = 1.0;
+        audio[15000] = 0.2;
+        let slicer = BufTransientSlice::new(BufTransientSliceConfig::default()).unwrap();
+        let scored = slicer.process_scored(&audio).unwrap();
+        let near = |click: usize| {
+            scored
+                .iter()
+                .find(|p| p.position.abs_diff(click) < 64)
+                .unwrap_or_else(|| panic!("no transient near {click}: {scored:?}"))
+        };
+        let (loud, soft) = (near(5000), near(15000));
+        assert!(soft.confidence > 0.0, "{soft:?}");
+        assert!(loud.confidence > soft.confidence, "{loud:?} vs {soft:?}");
+    }
+}
@@ -0,0 +1,193 @@
+use crate::buf_frames::fill_block;
+use crate::error::FlucomaError;
+use crate::transient_extraction::TransientExtraction;
+
+// -------------------------------------------------------------------------------------------------
+
+/// Configuration for [`BufTransients`].
+///
+/// Defaults match FluidBufTransients.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BufTransientsConfig {
+    /// AR model order.
+    pub order: usize,
+    /// Analysis block size in samples (must be > `order`).
+    pub block_size: usize,
+    /// Look-ahead padding in samples (must be > `order`).
+    pub pad_size: usize,
+    /// Spectral power threshold for residual detection.
+    pub power: f64,
+    /// Upper detection threshold.
+    pub thresh_hi: f64,
+    /// Lower detection threshold (hysteresis).
+    pub thresh_lo: f64,
+    /// Half-width of the peak-detection window in frames.
+    pub half_window: usize,
+    /// Minimum frames between successive detections.
+    pub hold: usize,
+}
+
+impl Default for BufTransientsConfig {
+    fn default() -> Self {
+        Self {
+            order: 20,
+            block_size: 256,
+            pad_size: 128,
+            power: 1.0,
+            thresh_hi: 2.0,
+            thresh_lo: 1.1,
+            half_window: 7,
+            hold: 25,
+        }
+    }
+}
+
+/// Result of a [`BufTransients`] separation.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BufTransientsResult {
+    /// Transient content, as long as the input.
+    pub transients: Vec<f64>,
+    /// Everything else, as long as the input.
+    pub residual: Vec<f64>,
+}
+
+/// Offline transient/residual separation of a whole mono buffer.
+///
+/// Feeds the buffer to [`TransientExtraction`] block by block, supplying the
+/// look-ahead padding around each block (zeros beyond the buffer edges), and
+/// stitches the hop-sized outputs back into two signals aligned with the
+/// input.
+///
+/// See <https://learn.flucoma.org/reference/transients>
+///
+/// ```rust,no_run
+/// use flucoma_rs::decomposition::{BufTransients, BufTransientsConfig};
+///
+/// let audio = vec![0.0f64; 44100];
+/// let transients = BufTransients::new(BufTransientsConfig::default()).unwrap();
+/// let result = transients.process(&audio).unwrap();
+/// assert_eq!(result.residual.len(), audio.len());
+/// ```
+#[derive(Debug, Clone)]
+pub struct BufTransients {
+    config: BufTransientsConfig,
+}
+
+impl BufTransients {
+    /// # Errors
+    /// Returns an error if the configuration is invalid.
+    pub fn new(config: BufTransientsConfig) -> Result<Self, FlucomaError> {
+        validate_config(&config)?;
+        Ok(Self { config })
+    }
+
+    pub fn config(&self) -> &BufTransientsConfig {
+        &self.config
+    }
+
+    pub fn set_config(&mut self, config: BufTransientsConfig) -> Result<(), FlucomaError> {
+        validate_config(&config)?;
+        self.config = config;
+        Ok(())
+    }
+
+    /// Separate `signal` into transients and residual.
+    ///
+    /// Each block of `hop_size` output samples is analysed with
+    /// `(input_size - hop_size) / 2` samples of context on either side.
+    ///
+    /// # Errors
+    /// Returns an error if the extractor cannot be created.
+    pub fn process(&self, signal: &[f64]) -> Result<BufTransientsResult, FlucomaError> {
+        let config = &self.config;
+        let mut extraction =
+            TransientExtraction::new(config.order, config.block_size, config.pad_size)?;
+        extraction.set_detection_parameters(
+            config.power,
+            config.thresh_hi,
+            config.thresh_lo,
+            config.half_window,
+            config.hold,
+        );
+        let hop = extraction.hop_size();
+        let lead = ((extraction.input_size() - hop) / 2) as isize;
+
+        let mut block = vec![0.0; extraction.input_size()];
+        let mut transients = Vec::with_capacity(signal.len() + hop);
+        let mut residual = Vec::with_capacity(signal.len() + hop);
+        let mut position = 0;
+        while position < signal.len() {
+            fill_block(signal, position as isize - lead, &mut block);
+            let (t, r) = extraction.process(&block);
+            transients.extend_from_slice(t);
+            residual.extend_from_slice(r);
+            position += hop;
+        }
+        transients.truncate(signal.len());
+        residual.truncate(signal.len());
+        Ok(BufTransientsResult {
+            transients,
+            residual,
+        })
+    }
+}
+
+fn validate_config(config: &BufTransientsConfig) -> Result<(), FlucomaError> {
+    if config.order == 0 {
+        return Err(FlucomaError::InvalidParameter("order must be > 0"));
+    }
+    if config.block_size <= config.order {
+        return Err(FlucomaError::InvalidParameter("block_size must be > order"));
+    }
+    if config.pad_size <= config.order {
+        return Err(FlucomaError::InvalidParameter("pad_size must be > order"));
+    }
+    Ok(())
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buf_transients_partitions_input_and_isolates_click() {
+        let mut audio: Vec<f64> = (0..10000).map(|i| 0.1 * (i as f64 * 0.05).sin()).collect();
+        audio[5000] = 1.0;
+        let transients = BufTransients::new(BufTransientsConfig::default()).unwrap();
+        let result = transients.process(&audio).unwrap();
+        assert_eq!(result.transients.len(), audio.len());
+        assert_eq!(result.residual.len(), audio.len());
+        assert!(result.transients.iter().all(|v| v.is_finite()));
+
+        // The two outputs partition the input sample for sample.
+        for (i, x) in audio.iter().enumerate() {
+            let sum = result.transients[i] + result.residual[i];
+            assert!((sum - x).abs() < 1e-6, "sample {i}: {sum} != {x}");
+        }
+        // The click is taken out of the residual and into the transients.
+        let loudest = (0..audio.len())
+            .max_by(|&a, &b| {
+                result.transients[a]
+                    .abs()
+                    .total_cmp(&result.transients[b].abs())
+            })
+            .unwrap();
+        assert!(
+            loudest.abs_diff(5000) <= 2,
+            "loudest transient at {loudest}"
+        );
+        assert!(result.transients[5000].abs() > 0.5);
+        assert!(result.residual[5000].abs() < 0.5);
+        assert!(transients.process(&[]).unwrap().transients.is_empty());
+
+        let config = BufTransientsConfig {
+            pad_size: 10,
+            ..Default::default()
+        };
+        assert!(BufTransients::new(config).is_err());
+    }
+}
@@ -1,11 +1,21 @@
+use std::fmt;
+use std::str::FromStr;
+
 use flucoma_sys::{multistats_create, multistats_destroy, multistats_init, multistats_process, FlucomaIndex};
 
+use crate::error::FlucomaError;
+use crate::feature_matrix::FeatureMatrix;
+use crate::matrix::Matrix;
 use crate::multi_stats::{outputs_from_raw, zero_outputs, MultiStatsOutput};
+use crate::param_names::{display_name, parse_name};
+use crate::slice::Slice;
+use crate::slice_context::SliceContext;
 
 const STATS_PER_DERIVATIVE: usize = 7;
 
 /// Configuration for [`BufStats`].
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BufStatsConfig {
     pub start_frame: usize,
     pub num_frames: Option<usize>,
@@ -34,6 +44,39 @@ impl Default for BufStatsConfig {
     }
 }
 
+/// Layout of a [`Matrix`] passed to [`BufStats::process_matrix`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DataOrientation {
+    /// Rows are frames and columns are features, as produced by the `Buf*`
+    /// extractors. Each column is summarised as one channel.
+    #[default]
+    FramesByFeatures,
+    /// Rows are channels and columns are frames, i.e. the channel-major
+    /// layout taken by [`BufStats::process`].
+    ChannelsByFrames,
+}
+
+const DATA_ORIENTATION_NAMES: &[(&str, DataOrientation)] = &[
+    ("frames-by-features", DataOrientation::FramesByFeatures),
+    ("channels-by-frames", DataOrientation::ChannelsByFrames),
+];
+
+impl FromStr for DataOrientation {
+    type Err = FlucomaError;
+
+    /// Parse a name such as `"frames-by-features"`, ignoring case and separators.
+    fn from_str(name: &str) -> Result<Self, FlucomaError> {
+        parse_name(name, DATA_ORIENTATION_NAMES, "unknown data orientation")
+    }
+}
+
+impl fmt::Display for DataOrientation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(display_name(self, DATA_ORIENTATION_NAMES))
+    }
+}
+
 /// BufStats-style offline statistics wrapper built on `MultiStats`.
 ///
 /// Input layout is channel-major:
@@ -47,11 +90,11 @@ pub struct BufStats {
 unsafe impl Send for BufStats {}
 
 impl BufStats {
-    pub fn new(config: BufStatsConfig) -> Result<Self, &'static str> {
+    pub fn new(config: BufStatsConfig) -> Result<Self, FlucomaError> {
         validate_config(&config)?;
         let inner = multistats_create();
         if inner.is_null() {
-            return Err("failed to create MultiStats instance");
+            return Err(FlucomaError::AllocationFailed);
         }
         Ok(Self { inner, config })
     }
@@ -60,7 +103,7 @@ impl BufStats {
         &self.config
     }
 
-    pub fn set_config(&mut self, config: BufStatsConfig) -> Result<(), &'static str> {
+    pub fn set_config(&mut self, config: BufStatsConfig) -> Result<(), FlucomaError> {
         validate_config(&config)?;
         self.config = config;
         Ok(())
@@ -75,48 +118,64 @@ impl BufStats {
         source_num_frames: usize,
         source_num_channels: usize,
         weights: Option<&[f64]>,
-    ) -> Result<Vec<MultiStatsOutput>, &'static str> {
+    ) -> Result<Vec<MultiStatsOutput>, FlucomaError> {
         if source_num_frames == 0 {
-            return Err("source_num_frames must be > 0");
+            return Err(FlucomaError::InvalidParameter(
+                "source_num_frames must be > 0",
+            ));
         }
         if source_num_channels == 0 {
-            return Err("source_num_channels must be > 0");
+            return Err(FlucomaError::InvalidParameter(
+                "source_num_channels must be > 0",
+            ));
         }
         if source.len() != source_num_frames * source_num_channels {
-            return Err("source length does not match source_num_frames * source_num_channels");
+            return Err(FlucomaError::InvalidParameter(
+                "source length does not match source_num_frames * source_num_channels",
+            ));
         }
 
         let start_frame = self.config.start_frame;
         if start_frame >= source_num_frames {
-            return Err("start_frame out of range");
+            return Err(FlucomaError::InvalidParameter("start_frame out of range"));
         }
         let selected_num_frames = self
             .config
             .num_frames
             .unwrap_or(source_num_frames.saturating_sub(start_frame));
         if selected_num_frames == 0 {
-            return Err("selected frame span must be > 0");
+            return Err(FlucomaError::InvalidParameter(
+                "selected frame span must be > 0",
+            ));
         }
         if start_frame + selected_num_frames > source_num_frames {
-            return Err("start_frame + num_frames out of range");
+            return Err(FlucomaError::InvalidParameter(
+                "start_frame + num_frames out of range",
+            ));
         }
         if selected_num_frames <= self.config.num_derivatives as usize {
-            return Err("selected frame span must be > num_derivatives");
+            return Err(FlucomaError::InvalidParameter(
+                "selected frame span must be > num_derivatives",
+            ));
         }
 
         let start_channel = self.config.start_channel;
         if start_channel >= source_num_channels {
-            return Err("start_channel out of range");
+            return Err(FlucomaError::InvalidParameter("start_channel out of range"));
         }
         let selected_num_channels = self
             .config
             .num_channels
             .unwrap_or(source_num_channels.saturating_sub(start_channel));
         if selected_num_channels == 0 {
-            return Err("selected channel count must be > 0");
+            return Err(FlucomaError::InvalidParameter(
+                "selected channel count must be > 0",
+            ));
         }
         if start_channel + selected_num_channels > source_num_channels {
-            return Err("start_channel + num_channels out of range");
+            return Err(FlucomaError::InvalidParameter(
+                "start_channel + num_channels out of range",
+            ));
         }
 
         let mut selected_source = vec![0.0; selected_num_channels * selected_num_frames];
@@ -131,7 +190,9 @@ impl BufStats {
 
         if let Some(weight_slice) = weights {
             if weight_slice.len() != selected_num_frames {
-                return Err("weights length must match selected frame span");
+                return Err(FlucomaError::InvalidParameter(
+                    "weights length must match selected frame span",
+                ));
             }
             if !weight_slice.iter().copied().any(|value| value > 0.0) {
                 return Ok(zero_outputs(
@@ -173,6 +234,69 @@ impl BufStats {
             self.config.num_derivatives,
         ))
     }
+
+    /// Compute summary statistics over a matrix with the given orientation.
+    ///
+    /// `start_channel`/`num_channels` select features and
+    /// `start_frame`/`num_frames` select frames, whatever the orientation.
+    pub fn process_matrix(
+        &mut self,
+        matrix: &Matrix,
+        orientation: DataOrientation,
+        weights: Option<&[f64]>,
+    ) -> Result<Vec<MultiStatsOutput>, FlucomaError> {
+        match orientation {
+            DataOrientation::FramesByFeatures => {
+                let source = matrix.transpose();
+                self.process(source.data(), matrix.rows(), matrix.cols(), weights)
+            }
+            DataOrientation::ChannelsByFrames => {
+                self.process(matrix.data(), matrix.cols(), matrix.rows(), weights)
+            }
+        }
+    }
+
+    /// Compute summary statistics of each feature column of a
+    /// [`FeatureMatrix`].
+    pub fn process_features(
+        &mut self,
+        features: &FeatureMatrix,
+        weights: Option<&[f64]>,
+    ) -> Result<Vec<MultiStatsOutput>, FlucomaError> {
+        self.process_matrix(
+            features.matrix(),
+            DataOrientation::FramesByFeatures,
+            weights,
+        )
+    }
+
+    /// Compute summary statistics of one slice of a mono `signal`, extended
+    /// by `context` and weighted by its taper, so the analysis sees the full
+    /// attack without the context counting as much as the slice itself.
+    ///
+    /// The slice replaces the configured frame and channel selection.
+    pub fn process_slice(
+        &mut self,
+        signal: &[f64],
+        slice: &Slice,
+        context: &SliceContext,
+        sample_rate: f64,
+    ) -> Result<MultiStatsOutput, FlucomaError> {
+        let (first, last) = (slice.start_sample, slice.end_sample);
+        let (start, end) = context.bounds(first, last, signal.len(), sample_rate);
+        let weights = context.gains(first, last, signal.len(), sample_rate);
+        let config = BufStatsConfig {
+            start_frame: 0,
+            num_frames: None,
+            start_channel: 0,
+            num_channels: None,
+            ..self.config.clone()
+        };
+        let config = std::mem::replace(&mut self.config, config);
+        let result = self.process(&signal[start..end], end - start, 1, Some(&weights));
+        self.config = config;
+        Ok(result?.remove(0))
+    }
 }
 
 impl Drop for BufStats {
@@ -181,24 +305,36 @@ impl Drop for BufStats {
     }
 }
 
-fn validate_config(config: &BufStatsConfig) -> Result<(), &'static str> {
+fn validate_config(config: &BufStatsConfig) -> Result<(), FlucomaError> {
     if config.num_derivatives > 2 {
-        return Err("num_derivatives must be in [0, 2]");
+        return Err(FlucomaError::InvalidParameter(
+            "num_derivatives must be in [0, 2]",
+        ));
     }
     if !(0.0..=100.0).contains(&config.low_percentile) {
-        return Err("low_percentile must be in [0, 100]");
+        return Err(FlucomaError::InvalidParameter(
+            "low_percentile must be in [0, 100]",
+        ));
     }
     if !(0.0..=100.0).contains(&config.middle_percentile) {
-        return Err("middle_percentile must be in [0, 100]");
+        return Err(FlucomaError::InvalidParameter(
+            "middle_percentile must be in [0, 100]",
+        ));
     }
     if !(0.0..=100.0).contains(&config.high_percentile) {
-        return Err("high_percentile must be in [0, 100]");
+        return Err(FlucomaError::InvalidParameter(
+            "high_percentile must be in [0, 100]",
+        ));
     }
     if config.low_percentile > config.middle_percentile {
-        return Err("low_percentile must be <= middle_percentile");
+        return Err(FlucomaError::InvalidParameter(
+            "low_percentile must be <= middle_percentile",
+        ));
     }
     if config.middle_percentile > config.high_percentile {
-        return Err("middle_percentile must be <= high_percentile");
+        return Err(FlucomaError::InvalidParameter(
+            "middle_percentile must be <= high_percentile",
+        ));
     }
     Ok(())
 }
@@ -240,6 +376,27 @@ mod tests {
         assert!((channels[0].stats.mean - 1.0).abs() < 1e-9);
     }
 
+    #[test]
+    fn matrix_orientations_agree() {
+        let mut stats = BufStats::new(BufStatsConfig::default()).unwrap();
+        let frames_by_features =
+            Matrix::from_vec(vec![1.0, 10.0, 2.0, 20.0, 3.0, 30.0], 3, 2).unwrap();
+        let by_frames = stats
+            .process_matrix(&frames_by_features, DataOrientation::FramesByFeatures, None)
+            .unwrap();
+        let by_channels = stats
+            .process_matrix(
+                &frames_by_features.transpose(),
+                DataOrientation::ChannelsByFrames,
+                None,
+            )
+            .unwrap();
+        assert_eq!(by_frames.len(), 2);
+        assert!((by_frames[0].stats.mean - 2.0).abs() < 1e-12);
+        assert!((by_frames[1].stats.mean - 20.0).abs() < 1e-12);
+        assert_eq!(by_frames, by_channels);
+    }
+
     #[test]
     fn non_positive_weights_return_zeroed_outputs() {
         let config = BufStatsConfig {
@@ -254,4 +411,35 @@ mod tests {
         assert_eq!(channels[0].stats.std, 0.0);
         assert_eq!(channels[0].derivative_1.unwrap().mean, 0.0);
     }
+
+    #[test]
+    fn slice_context_is_tapered_into_slice_stats() {
+        use crate::slice_context::ContextTaper;
+
+        let mut signal = vec![0.0; 300];
+        signal[100..200].fill(1.0);
+        let slice = Slice::new(100, 200, 1000.0);
+        let mut stats = BufStats::new(BufStatsConfig {
+            start_frame: 250,
+            ..BufStatsConfig::default()
+        })
+        .unwrap();
+
+        let bare = stats
+            .process_slice(&signal, &slice, &SliceContext::default(), 1000.0)
+            .unwrap();
+        assert!((bare.stats.mean - 1.0).abs() < 1e-12);
+
+        // 50 samples of silence either side
+        let flat = SliceContext::new(50.0, 50.0, ContextTaper::None);
+        let flat = stats.process_slice(&signal, &slice, &flat, 1000.0).unwrap();
+        assert!((flat.stats.mean - 0.5).abs() < 1e-12);
+
+        let tapered = SliceContext::new(50.0, 50.0, ContextTaper::Linear);
+        let tapered = stats
+            .process_slice(&signal, &slice, &tapered, 1000.0)
+            .unwrap();
+        assert!(tapered.stats.mean > flat.stats.mean && tapered.stats.mean < 1.0);
+        assert_eq!(stats.config().start_frame, 250);
+    }
 }
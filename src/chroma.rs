@@ -0,0 +1,246 @@
+use std::fmt;
+use std::str::FromStr;
+
+use flucoma_sys::{chroma_create, chroma_destroy, chroma_init, chroma_process_frame};
+
+use crate::error::{check_len, FlucomaError};
+use crate::param_names::{display_name, parse_name};
+
+// -------------------------------------------------------------------------------------------------
+
+/// Per-frame normalisation of a chroma vector.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[repr(isize)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ChromaNormalization {
+    /// Raw filter bank energies.
+    #[default]
+    None = 0,
+    /// Divide by the sum of all bins.
+    Sum = 1,
+    /// Divide by the largest bin.
+    Max = 2,
+}
+
+const CHROMA_NORMALIZATION_NAMES: &[(&str, ChromaNormalization)] = &[
+    ("none", ChromaNormalization::None),
+    ("sum", ChromaNormalization::Sum),
+    ("max", ChromaNormalization::Max),
+];
+
+impl FromStr for ChromaNormalization {
+    type Err = FlucomaError;
+
+    /// Parse a name such as `"sum"`, ignoring case and separators.
+    fn from_str(name: &str) -> Result<Self, FlucomaError> {
+        parse_name(
+            name,
+            CHROMA_NORMALIZATION_NAMES,
+            "unknown chroma normalisation",
+        )
+    }
+}
+
+impl fmt::Display for ChromaNormalization {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(display_name(self, CHROMA_NORMALIZATION_NAMES))
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Chroma filter bank -- folds a magnitude spectrum into pitch classes.
+///
+/// Call [`Chroma::process_frame`] with magnitude spectra (not raw complex).
+///
+/// See <https://learn.flucoma.org/reference/chroma>
+pub struct Chroma {
+    inner: *mut u8,
+    n_bins: usize,
+    n_chroma: usize,
+    ref_hz: f64,
+    sample_rate: f64,
+}
+
+unsafe impl Send for Chroma {}
+
+impl Chroma {
+    /// Create and fully initialise a chroma filter bank.
+    ///
+    /// # Arguments
+    /// * `n_chroma`    - Number of pitch classes per octave (e.g. 12, must be >= 2).
+    /// * `n_bins`      - Number of FFT magnitude bins (`fft_size / 2 + 1`).
+    /// * `ref_hz`      - Tuning reference for the first chroma bin, in Hz (e.g. 440).
+    /// * `sample_rate` - Audio sample rate in Hz.
+    ///
+    /// # Errors
+    /// Returns an error if parameters are invalid.
+    pub fn new(
+        n_chroma: usize,
+        n_bins: usize,
+        ref_hz: f64,
+        sample_rate: f64,
+    ) -> Result<Self, FlucomaError> {
+        if n_chroma < 2 {
+            return Err(FlucomaError::InvalidParameter("n_chroma must be >= 2"));
+        }
+        if n_bins < 2 {
+            return Err(FlucomaError::InvalidParameter("n_bins must be >= 2"));
+        }
+        if ref_hz <= 0.0 {
+            return Err(FlucomaError::InvalidParameter("ref_hz must be > 0"));
+        }
+        if sample_rate <= 0.0 {
+            return Err(FlucomaError::InvalidParameter("sample_rate must be > 0"));
+        }
+        let inner = chroma_create(n_chroma as isize, ((n_bins - 1) * 2) as isize);
+        if inner.is_null() {
+            return Err(FlucomaError::AllocationFailed);
+        }
+        chroma_init(
+            inner,
+            n_chroma as isize,
+            n_bins as isize,
+            ref_hz,
+            sample_rate,
+        );
+        Ok(Self {
+            inner,
+            n_bins,
+            n_chroma,
+            ref_hz,
+            sample_rate,
+        })
+    }
+
+    /// Process a magnitude spectrum frame and return `n_chroma` values.
+    ///
+    /// # Arguments
+    /// * `magnitudes`    - Magnitude spectrum; must have exactly `n_bins` values.
+    /// * `min_hz`        - Bins below this frequency are ignored.
+    /// * `max_hz`        - Bins above this frequency are ignored.
+    /// * `normalization` - Per-frame normalisation of the output.
+    ///
+    /// # Panics
+    /// Panics if `magnitudes.len() != n_bins`.
+    pub fn process_frame(
+        &mut self,
+        magnitudes: &[f64],
+        min_hz: f64,
+        max_hz: f64,
+        normalization: ChromaNormalization,
+    ) -> Vec<f64> {
+        let mut output = vec![0.0f64; self.n_chroma];
+        self.process_into(magnitudes, min_hz, max_hz, normalization, &mut output);
+        output
+    }
+
+    /// Like [`Chroma::process_frame`], but returns
+    /// [`FlucomaError::InvalidShape`] instead of panicking if
+    /// `magnitudes.len() != n_bins`.
+    pub fn try_process_frame(
+        &mut self,
+        magnitudes: &[f64],
+        min_hz: f64,
+        max_hz: f64,
+        normalization: ChromaNormalization,
+    ) -> Result<Vec<f64>, FlucomaError> {
+        check_len(self.n_bins, magnitudes.len())?;
+        Ok(self.process_frame(magnitudes, min_hz, max_hz, normalization))
+    }
+
+    /// Like [`Chroma::process_frame`], but writes the pitch-class energies
+    /// into `output` instead of allocating, for use on an audio thread.
+    ///
+    /// # Panics
+    /// Panics if `magnitudes.len() != n_bins` or `output.len() != n_chroma`.
+    pub fn process_into(
+        &mut self,
+        magnitudes: &[f64],
+        min_hz: f64,
+        max_hz: f64,
+        normalization: ChromaNormalization,
+        output: &mut [f64],
+    ) {
+        assert_eq!(
+            magnitudes.len(),
+            self.n_bins,
+            "magnitudes length ({}) must equal n_bins ({})",
+            magnitudes.len(),
+            self.n_bins
+        );
+        assert_eq!(
+            output.len(),
+            self.n_chroma,
+            "output length ({}) must equal n_chroma ({})",
+            output.len(),
+            self.n_chroma
+        );
+        chroma_process_frame(
+            self.inner,
+            magnitudes.as_ptr(),
+            magnitudes.len() as isize,
+            output.as_mut_ptr(),
+            output.len() as isize,
+            min_hz,
+            max_hz,
+            normalization as isize,
+        );
+    }
+
+    /// Number of pitch classes in each output frame.
+    pub fn n_chroma(&self) -> usize {
+        self.n_chroma
+    }
+
+    /// Number of FFT magnitude bins expected as input (`fft_size / 2 + 1`).
+    pub fn n_bins(&self) -> usize {
+        self.n_bins
+    }
+}
+
+impl Drop for Chroma {
+    fn drop(&mut self) {
+        chroma_destroy(self.inner);
+    }
+}
+
+impl Clone for Chroma {
+    fn clone(&self) -> Self {
+        Self::new(self.n_chroma, self.n_bins, self.ref_hz, self.sample_rate)
+            .expect("settings were validated on construction")
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chroma_output_count_and_normalization() {
+        let n_bins = 2049;
+        let mut chroma = Chroma::new(12, n_bins, 440.0, 44100.0).unwrap();
+        let mut mags = vec![0.0f64; n_bins];
+        // 440 Hz at 44.1 kHz with a 4096-point FFT
+        mags[41] = 1.0;
+        let values = chroma.process_frame(&mags, 0.0, 22050.0, ChromaNormalization::Max);
+        assert_eq!(values.len(), 12);
+        let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        assert!((max - 1.0).abs() < 1e-9, "max = {max}");
+    }
+
+    #[test]
+    fn chroma_process_into_matches_process_frame() {
+        let n_bins = 2049;
+        let mut chroma = Chroma::new(12, n_bins, 440.0, 44100.0).unwrap();
+        let mut mags = vec![0.0f64; n_bins];
+        mags[41] = 1.0;
+        mags[62] = 0.5;
+        let expected = chroma.process_frame(&mags, 0.0, 22050.0, ChromaNormalization::Sum);
+        let mut output = vec![0.0f64; 12];
+        chroma.process_into(&mags, 0.0, 22050.0, ChromaNormalization::Sum, &mut output);
+        assert_eq!(output, expected);
+    }
+}
@@ -0,0 +1,131 @@
+use std::borrow::Cow;
+
+use crate::error::FlucomaError;
+use crate::matrix::Matrix;
+
+// -------------------------------------------------------------------------------------------------
+
+/// Scalers that can be restricted to a subset of feature columns, e.g. to
+/// scale MFCCs but leave a cluster-id column untouched.
+///
+/// The mask applies to fitting and transforming alike. Unmasked columns pass
+/// through unchanged, and the fitted parameters describe the masked columns
+/// only, in mask order. Because of that, changing the mask discards the
+/// fitted model and any `partial_fit` state: set the mask before fitting,
+/// and refit after changing it.
+pub trait ColumnMasked {
+    /// Restrict the scaler to the given feature columns. `None` scales every
+    /// column. Setting the mask it already has keeps the fitted model.
+    ///
+    /// # Errors
+    /// Returns an error if the mask is empty or contains duplicates.
+    fn set_columns(&mut self, columns: Option<Vec<usize>>) -> Result<(), FlucomaError>;
+
+    /// The column mask set by [`ColumnMasked::set_columns`], if any.
+    fn columns(&self) -> Option<&[usize]>;
+}
+
+/// Validate `columns` and store it in `mask`. Returns whether the mask
+/// changed, in which case the caller must discard its fitted state.
+pub(crate) fn update_mask(
+    mask: &mut Option<Vec<usize>>,
+    columns: Option<Vec<usize>>,
+) -> Result<bool, FlucomaError> {
+    if let Some(columns) = &columns {
+        validate_columns(columns)?;
+    }
+    if *mask == columns {
+        return Ok(false);
+    }
+    *mask = columns;
+    Ok(true)
+}
+
+/// Check a scaler column mask: it must be non-empty and free of duplicates.
+fn validate_columns(columns: &[usize]) -> Result<(), FlucomaError> {
+    if columns.is_empty() {
+        return Err(FlucomaError::InvalidParameter("columns must not be empty"));
+    }
+    if columns
+        .iter()
+        .enumerate()
+        .any(|(i, c)| columns[..i].contains(c))
+    {
+        return Err(FlucomaError::InvalidParameter(
+            "columns must not contain duplicates",
+        ));
+    }
+    Ok(())
+}
+
+/// The masked columns of `data`, in mask order, or all of `data` without a
+/// mask.
+pub(crate) fn select_columns<'a>(
+    data: &'a Matrix,
+    columns: Option<&[usize]>,
+) -> Result<Cow<'a, Matrix>, FlucomaError> {
+    let Some(columns) = columns else {
+        return Ok(Cow::Borrowed(data));
+    };
+    if columns.iter().any(|&c| c >= data.cols()) {
+        return Err(FlucomaError::InvalidParameter(
+            "columns must be < data cols",
+        ));
+    }
+    let selected = data
+        .data()
+        .chunks_exact(data.cols())
+        .flat_map(|row| columns.iter().map(|&c| row[c]))
+        .collect();
+    Matrix::from_vec(selected, data.rows(), columns.len()).map(Cow::Owned)
+}
+
+/// Write the `scaled` columns back into a copy of `data`, leaving unmasked
+/// columns untouched. Without a mask, `scaled` is returned as is.
+pub(crate) fn replace_columns(data: &Matrix, columns: Option<&[usize]>, scaled: Matrix) -> Matrix {
+    let Some(columns) = columns else {
+        return scaled;
+    };
+    let mut out = data.clone();
+    let cols = out.cols();
+    for (row, scaled_row) in out
+        .data_mut()
+        .chunks_exact_mut(cols)
+        .zip(scaled.data().chunks_exact(columns.len()))
+    {
+        for (&c, &value) in columns.iter().zip(scaled_row) {
+            row[c] = value;
+        }
+    }
+    out
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn masked_columns_round_trip() {
+        let data = Matrix::from_vec(vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0], 2, 3).unwrap();
+        let columns = [2, 0];
+        let selected = select_columns(&data, Some(&columns)).unwrap();
+        assert_eq!(selected.data(), &[3.0, 1.0, 6.0, 4.0]);
+
+        let scaled = Matrix::from_vec(vec![30.0, 10.0, 60.0, 40.0], 2, 2).unwrap();
+        let merged = replace_columns(&data, Some(&columns), scaled);
+        assert_eq!(merged.data(), &[10.0, 2.0, 30.0, 40.0, 5.0, 60.0]);
+
+        assert!(select_columns(&data, Some(&[3])).is_err());
+        assert!(validate_columns(&[1, 1]).is_err());
+        assert!(validate_columns(&[]).is_err());
+
+        let mut mask = None;
+        assert!(update_mask(&mut mask, Some(vec![0, 2])).unwrap());
+        assert!(!update_mask(&mut mask, Some(vec![0, 2])).unwrap());
+        assert!(update_mask(&mut mask, Some(vec![2, 0])).unwrap());
+        assert!(update_mask(&mut mask, Some(vec![])).is_err());
+        assert_eq!(mask, Some(vec![2, 0]));
+    }
+}
@@ -0,0 +1,472 @@
+use std::path::Path;
+
+#[cfg(feature = "corpus")]
+use std::time::UNIX_EPOCH;
+
+#[cfg(feature = "corpus")]
+use rusqlite::{params, Connection, OptionalExtension};
+
+#[cfg(feature = "corpus")]
+use crate::dataset::DataSet;
+#[cfg(feature = "corpus")]
+use crate::dataset_query::Predicate;
+use crate::error::FlucomaError;
+
+// -------------------------------------------------------------------------------------------------
+
+/// A slice of an audio file stored in a [`Corpus`], with its descriptors.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CorpusSlice {
+    /// Row id of the slice in the store.
+    pub id: i64,
+    /// Path of the source file, as it was passed to [`Corpus::replace_file`].
+    pub path: String,
+    /// First sample of the slice.
+    pub start: usize,
+    /// One past the last sample of the slice.
+    pub end: usize,
+    /// Descriptor vector, [`Corpus::dims`] values.
+    pub descriptors: Vec<f64>,
+}
+
+/// Persistent SQLite store of audio slices and their descriptors, for sample
+/// libraries too large to re-analyse or keep in memory as a
+/// [`DataSet`](crate::data::DataSet).
+///
+/// Slices are stored per source file together with the file's modification
+/// time, so [`Corpus::needs_analysis`] and [`Corpus::replace_file`] support
+/// incremental re-analysis: only files which changed since they were last
+/// analysed have to be processed again.
+///
+/// Requires the `corpus` feature; without it [`Corpus::open`] returns
+/// [`FlucomaError::FeatureDisabled`].
+///
+/// ```rust,no_run
+/// use flucoma_rs::corpus::Corpus;
+///
+/// let mut corpus = Corpus::open("library.sqlite").unwrap();
+/// let path = "kicks/kick01.wav";
+/// if corpus.needs_analysis(path).unwrap() {
+///     // analyse the file into (start, end, descriptors) slices ...
+///     let slices = vec![(0, 4410, vec![0.1, 0.2]), (4410, 8820, vec![0.3, 0.4])];
+///     corpus.replace_file(path, &slices).unwrap();
+/// }
+/// let similar = corpus.nearest(&[0.1, 0.25], 5).unwrap();
+/// ```
+pub struct Corpus {
+    #[cfg(feature = "corpus")]
+    conn: Connection,
+    #[cfg(not(feature = "corpus"))]
+    _disabled: std::convert::Infallible,
+}
+
+#[cfg(not(feature = "corpus"))]
+impl Corpus {
+    /// Open or create a corpus database at `path`.
+    ///
+    /// # Errors
+    /// Always returns [`FlucomaError::FeatureDisabled`] in this build.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, FlucomaError> {
+        let _ = path;
+        Err(FlucomaError::FeatureDisabled("corpus"))
+    }
+
+    /// Create a corpus which lives in memory only.
+    ///
+    /// # Errors
+    /// Always returns [`FlucomaError::FeatureDisabled`] in this build.
+    pub fn open_in_memory() -> Result<Self, FlucomaError> {
+        Err(FlucomaError::FeatureDisabled("corpus"))
+    }
+}
+
+#[cfg(feature = "corpus")]
+impl Corpus {
+    /// Open or create a corpus database at `path`.
+    ///
+    /// # Errors
+    /// Returns [`FlucomaError::Io`] if the database cannot be opened or
+    /// initialised.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, FlucomaError> {
+        Self::init(Connection::open(path).map_err(db_error)?)
+    }
+
+    /// Create a corpus which lives in memory only.
+    ///
+    /// # Errors
+    /// Returns [`FlucomaError::Io`] if the database cannot be initialised.
+    pub fn open_in_memory() -> Result<Self, FlucomaError> {
+        Self::init(Connection::open_in_memory().map_err(db_error)?)
+    }
+
+    fn init(conn: Connection) -> Result<Self, FlucomaError> {
+        conn.execute_batch(
+            "PRAGMA foreign_keys = ON;
+             CREATE TABLE IF NOT EXISTS meta (key TEXT PRIMARY KEY, value INTEGER NOT NULL);
+             CREATE TABLE IF NOT EXISTS files (
+                 id INTEGER PRIMARY KEY,
+                 path TEXT NOT NULL UNIQUE,
+                 modified INTEGER
+             );
+             CREATE TABLE IF NOT EXISTS slices (
+                 id INTEGER PRIMARY KEY,
+                 file_id INTEGER NOT NULL REFERENCES files(id) ON DELETE CASCADE,
+                 start INTEGER NOT NULL,
+                 end INTEGER NOT NULL,
+                 descriptors BLOB NOT NULL
+             );
+             CREATE INDEX IF NOT EXISTS slices_file ON slices(file_id);",
+        )
+        .map_err(db_error)?;
+        Ok(Self { conn })
+    }
+
+    /// Descriptor dimensionality, fixed by the first stored slice, or `None`
+    /// for an empty corpus.
+    ///
+    /// # Errors
+    /// Returns [`FlucomaError::Io`] if the query fails.
+    pub fn dims(&self) -> Result<Option<usize>, FlucomaError> {
+        self.conn
+            .query_row("SELECT value FROM meta WHERE key = 'dims'", [], |row| {
+                row.get::<_, i64>(0)
+            })
+            .optional()
+            .map(|dims| dims.map(|d| d as usize))
+            .map_err(db_error)
+    }
+
+    /// Returns `true` if `path` has never been analysed, or its modification
+    /// time on disk differs from the one recorded by
+    /// [`Corpus::replace_file`].
+    ///
+    /// # Errors
+    /// Returns [`FlucomaError::Io`] if the file metadata or database cannot be
+    /// read.
+    pub fn needs_analysis<P: AsRef<Path>>(&self, path: P) -> Result<bool, FlucomaError> {
+        let path = path.as_ref();
+        let modified = modified_secs(path)?;
+        let stored: Option<Option<i64>> = self
+            .conn
+            .query_row(
+                "SELECT modified FROM files WHERE path = ?1",
+                [path_key(path)],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(db_error)?;
+        Ok(stored.flatten() != modified)
+    }
+
+    /// Replace all slices of `path` with `slices` of `(start, end,
+    /// descriptors)`, and record the file's current modification time (if the
+    /// file exists).
+    ///
+    /// The update is atomic: on error the stored slices are unchanged.
+    ///
+    /// # Errors
+    /// Returns [`FlucomaError::InvalidShape`] if descriptor lengths differ
+    /// from [`Corpus::dims`] or from each other,
+    /// [`FlucomaError::InvalidParameter`] if a slice has `end < start`, or
+    /// [`FlucomaError::Io`] if the database update fails.
+    pub fn replace_file<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+        slices: &[(usize, usize, Vec<f64>)],
+    ) -> Result<(), FlucomaError> {
+        let path = path.as_ref();
+        let mut dims = self.dims()?;
+        for (start, end, descriptors) in slices {
+            if end < start {
+                return Err(FlucomaError::InvalidParameter("slice end must be >= start"));
+            }
+            match dims {
+                Some(d) if d != descriptors.len() => {
+                    return Err(FlucomaError::InvalidShape {
+                        expected: d,
+                        got: descriptors.len(),
+                    });
+                }
+                None if descriptors.is_empty() => {
+                    return Err(FlucomaError::InvalidParameter(
+                        "descriptors must not be empty",
+                    ));
+                }
+                None => dims = Some(descriptors.len()),
+                _ => {}
+            }
+        }
+        let modified = if path.exists() {
+            modified_secs(path)?
+        } else {
+            None
+        };
+
+        let tx = self.conn.transaction().map_err(db_error)?;
+        if let Some(d) = dims {
+            tx.execute(
+                "INSERT OR IGNORE INTO meta (key, value) VALUES ('dims', ?1)",
+                [d as i64],
+            )
+            .map_err(db_error)?;
+        }
+        tx.execute(
+            "INSERT INTO files (path, modified) VALUES (?1, ?2)
+             ON CONFLICT(path) DO UPDATE SET modified = excluded.modified",
+            params![path_key(path), modified],
+        )
+        .map_err(db_error)?;
+        let file_id: i64 = tx
+            .query_row(
+                "SELECT id FROM files WHERE path = ?1",
+                [path_key(path)],
+                |row| row.get(0),
+            )
+            .map_err(db_error)?;
+        tx.execute("DELETE FROM slices WHERE file_id = ?1", [file_id])
+            .map_err(db_error)?;
+        {
+            let mut insert = tx
+                .prepare(
+                    "INSERT INTO slices (file_id, start, end, descriptors)
+                     VALUES (?1, ?2, ?3, ?4)",
+                )
+                .map_err(db_error)?;
+            for (start, end, descriptors) in slices {
+                insert
+                    .execute(params![
+                        file_id,
+                        *start as i64,
+                        *end as i64,
+                        encode(descriptors)
+                    ])
+                    .map_err(db_error)?;
+            }
+        }
+        tx.commit().map_err(db_error)
+    }
+
+    /// Remove a file and all its slices. Returns `false` if it was not
+    /// stored.
+    ///
+    /// # Errors
+    /// Returns [`FlucomaError::Io`] if the database update fails.
+    pub fn remove_file<P: AsRef<Path>>(&mut self, path: P) -> Result<bool, FlucomaError> {
+        let removed = self
+            .conn
+            .execute(
+                "DELETE FROM files WHERE path = ?1",
+                [path_key(path.as_ref())],
+            )
+            .map_err(db_error)?;
+        Ok(removed > 0)
+    }
+
+    /// All stored slices, ordered by file and start.
+    ///
+    /// # Errors
+    /// Returns [`FlucomaError::Io`] if the query fails.
+    pub fn slices(&self) -> Result<Vec<CorpusSlice>, FlucomaError> {
+        let mut out = Vec::new();
+        self.for_each_slice(|slice| out.push(slice))?;
+        Ok(out)
+    }
+
+    /// Slices matching `predicate`, which is evaluated against the descriptors;
+    /// [`Predicate::id`] patterns match the file path.
+    ///
+    /// # Errors
+    /// Returns [`FlucomaError::InvalidParameter`] if the predicate references
+    /// a column outside the descriptors, or [`FlucomaError::Io`] if the query
+    /// fails.
+    pub fn select(&self, predicate: &Predicate) -> Result<Vec<CorpusSlice>, FlucomaError> {
+        let dims = self.dims()?.unwrap_or(0);
+        if predicate.max_column().is_some_and(|c| c >= dims) {
+            return Err(FlucomaError::InvalidParameter(
+                "predicate column out of range",
+            ));
+        }
+        let mut out = Vec::new();
+        self.for_each_slice(|slice| {
+            if predicate.matches_point(&slice.path, &slice.descriptors) {
+                out.push(slice);
+            }
+        })?;
+        Ok(out)
+    }
+
+    /// The `k` slices closest to `query` in Euclidean distance, nearest
+    /// first, with their distances.
+    ///
+    /// Slices are streamed from the database, so memory use is bounded by
+    /// `k` rather than the corpus size.
+    ///
+    /// # Errors
+    /// Returns [`FlucomaError::InvalidShape`] if `query.len()` differs from
+    /// [`Corpus::dims`], or [`FlucomaError::Io`] if the query fails.
+    pub fn nearest(
+        &self,
+        query: &[f64],
+        k: usize,
+    ) -> Result<Vec<(CorpusSlice, f64)>, FlucomaError> {
+        if let Some(dims) = self.dims()? {
+            if query.len() != dims {
+                return Err(FlucomaError::InvalidShape {
+                    expected: dims,
+                    got: query.len(),
+                });
+            }
+        }
+        let mut best: Vec<(CorpusSlice, f64)> = Vec::with_capacity(k + 1);
+        self.for_each_slice(|slice| {
+            let distance = query
+                .iter()
+                .zip(&slice.descriptors)
+                .map(|(a, b)| (a - b) * (a - b))
+                .sum::<f64>()
+                .sqrt();
+            if best.len() < k || best.last().is_some_and(|(_, d)| distance < *d) {
+                let at = best.partition_point(|(_, d)| *d <= distance);
+                best.insert(at, (slice, distance));
+                best.truncate(k);
+            }
+        })?;
+        Ok(best)
+    }
+
+    /// Load all slices into a [`DataSet`] keyed by slice id, e.g. to build a
+    /// [`KDTree`](crate::search::KDTree) or fit a model.
+    ///
+    /// # Errors
+    /// Returns [`FlucomaError::InvalidParameter`] if the corpus is empty, or
+    /// [`FlucomaError::Io`] if the query fails.
+    pub fn to_dataset(&self) -> Result<DataSet, FlucomaError> {
+        let dims = self
+            .dims()?
+            .ok_or(FlucomaError::InvalidParameter("corpus is empty"))?;
+        let mut dataset = DataSet::new(dims)?;
+        let mut result = Ok(());
+        self.for_each_slice(|slice| {
+            if result.is_ok() {
+                result = dataset.add(&slice.id.to_string(), &slice.descriptors);
+            }
+        })?;
+        result.map(|_| dataset)
+    }
+
+    fn for_each_slice<F: FnMut(CorpusSlice)>(&self, mut f: F) -> Result<(), FlucomaError> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT s.id, f.path, s.start, s.end, s.descriptors
+                 FROM slices s JOIN files f ON s.file_id = f.id
+                 ORDER BY f.path, s.start, s.id",
+            )
+            .map_err(db_error)?;
+        let mut rows = stmt.query([]).map_err(db_error)?;
+        while let Some(row) = rows.next().map_err(db_error)? {
+            let blob: Vec<u8> = row.get(4).map_err(db_error)?;
+            f(CorpusSlice {
+                id: row.get(0).map_err(db_error)?,
+                path: row.get(1).map_err(db_error)?,
+                start: row.get::<_, i64>(2).map_err(db_error)? as usize,
+                end: row.get::<_, i64>(3).map_err(db_error)? as usize,
+                descriptors: decode(&blob),
+            });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "corpus")]
+fn path_key(path: &Path) -> String {
+    path.to_string_lossy().into_owned()
+}
+
+/// Modification time in whole seconds since the Unix epoch, if available.
+#[cfg(feature = "corpus")]
+fn modified_secs(path: &Path) -> Result<Option<i64>, FlucomaError> {
+    let metadata = std::fs::metadata(path).map_err(|e| FlucomaError::Io(e.to_string()))?;
+    Ok(metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs() as i64))
+}
+
+#[cfg(feature = "corpus")]
+fn encode(values: &[f64]) -> Vec<u8> {
+    values.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+#[cfg(feature = "corpus")]
+fn decode(bytes: &[u8]) -> Vec<f64> {
+    bytes
+        .chunks_exact(8)
+        .map(|b| f64::from_le_bytes(b.try_into().unwrap()))
+        .collect()
+}
+
+#[cfg(feature = "corpus")]
+fn db_error(e: rusqlite::Error) -> FlucomaError {
+    FlucomaError::Io(e.to_string())
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "corpus")]
+    #[test]
+    fn corpus_replace_select_and_nearest() {
+        use crate::dataset_query::{ComparisonOp, IdPattern};
+
+        let mut corpus = Corpus::open_in_memory().unwrap();
+        corpus
+            .replace_file(
+                "kicks/a.wav",
+                &[(0, 100, vec![0.0, 1.0]), (100, 200, vec![1.0, 1.0])],
+            )
+            .unwrap();
+        corpus
+            .replace_file("snares/b.wav", &[(0, 50, vec![5.0, 5.0])])
+            .unwrap();
+        assert_eq!(corpus.dims().unwrap(), Some(2));
+        assert!(corpus.replace_file("x.wav", &[(0, 1, vec![1.0])]).is_err());
+        assert_eq!(corpus.slices().unwrap().len(), 3);
+
+        let kicks = corpus
+            .select(&Predicate::id(IdPattern::Glob("kicks/*".into())))
+            .unwrap();
+        assert_eq!(kicks.len(), 2);
+        let loud = corpus
+            .select(&Predicate::new(0, ComparisonOp::Gt, 0.5))
+            .unwrap();
+        assert_eq!(loud.len(), 2);
+
+        let nearest = corpus.nearest(&[0.9, 1.0], 2).unwrap();
+        assert_eq!((nearest[0].0.start, nearest[0].0.end), (100, 200));
+        assert_eq!(nearest[1].0.start, 0);
+
+        corpus
+            .replace_file("kicks/a.wav", &[(0, 10, vec![2.0, 2.0])])
+            .unwrap();
+        assert_eq!(corpus.slices().unwrap().len(), 2);
+        assert!(corpus.remove_file("snares/b.wav").unwrap());
+        assert_eq!(corpus.to_dataset().unwrap().len(), 1);
+        // Files which do not exist on disk cannot be checked for changes.
+        assert!(corpus.needs_analysis("kicks/a.wav").is_err());
+    }
+
+    #[cfg(not(feature = "corpus"))]
+    #[test]
+    fn corpus_requires_feature() {
+        assert_eq!(
+            Corpus::open_in_memory().err(),
+            Some(FlucomaError::FeatureDisabled("corpus"))
+        );
+    }
+}
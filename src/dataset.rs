@@ -0,0 +1,574 @@
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hasher};
+
+use crate::error::FlucomaError;
+use crate::matrix::Matrix;
+use crate::seed::resolve_seed;
+
+// -------------------------------------------------------------------------------------------------
+
+/// How [`DataSet::merge`] handles ids present in both data sets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MergePolicy {
+    /// Fail with [`FlucomaError::DuplicateId`] and leave the data set
+    /// unchanged.
+    #[default]
+    Reject,
+    /// Replace the existing point with the incoming one.
+    Overwrite,
+    /// Keep the existing point and ignore the incoming one.
+    KeepExisting,
+}
+
+/// Labelled collection of fixed-size points, the Rust counterpart of a
+/// FluidDataSet.
+///
+/// Points are stored row-major in insertion order and looked up by their
+/// unique string id. Columns carry names, `c0`, `c1`, ... unless set with
+/// [`DataSet::set_column_names`], so pipelines can select descriptors by
+/// name.
+///
+/// See <https://learn.flucoma.org/reference/dataset>
+#[derive(Debug, Clone, PartialEq)]
+pub struct DataSet {
+    cols: usize,
+    columns: Vec<String>,
+    /// `true` for columns still carrying their default `c{index}` name.
+    generated_names: Vec<bool>,
+    ids: Vec<String>,
+    data: Vec<f64>,
+    rows: HashMap<String, usize>,
+}
+
+impl DataSet {
+    /// Create an empty data set for points with `cols` dimensions.
+    ///
+    /// # Errors
+    /// Returns an error if `cols == 0`.
+    pub fn new(cols: usize) -> Result<Self, FlucomaError> {
+        if cols == 0 {
+            return Err(FlucomaError::InvalidParameter("cols must be > 0"));
+        }
+        Ok(Self {
+            cols,
+            columns: (0..cols).map(|c| format!("c{c}")).collect(),
+            generated_names: vec![true; cols],
+            ids: Vec::new(),
+            data: Vec::new(),
+            rows: HashMap::new(),
+        })
+    }
+
+    /// Build a data set from ids and a matrix with one row per id.
+    ///
+    /// # Errors
+    /// Returns an error if `ids.len() != matrix.rows()` or ids are repeated.
+    pub fn from_matrix<S: AsRef<str>>(ids: &[S], matrix: &Matrix) -> Result<Self, FlucomaError> {
+        if ids.len() != matrix.rows() {
+            return Err(FlucomaError::InvalidShape {
+                expected: matrix.rows(),
+                got: ids.len(),
+            });
+        }
+        let mut dataset = Self::new(matrix.cols())?;
+        for (id, point) in ids.iter().zip(matrix.data().chunks_exact(matrix.cols())) {
+            dataset.add(id.as_ref(), point)?;
+        }
+        Ok(dataset)
+    }
+
+    /// Add a labelled point.
+    ///
+    /// # Errors
+    /// Returns an error if `point.len()` differs from [`DataSet::cols`] or if
+    /// `id` is already present.
+    pub fn add(&mut self, id: &str, point: &[f64]) -> Result<(), FlucomaError> {
+        if point.len() != self.cols {
+            return Err(FlucomaError::InvalidShape {
+                expected: self.cols,
+                got: point.len(),
+            });
+        }
+        if self.rows.contains_key(id) {
+            return Err(FlucomaError::DuplicateId(id.to_owned()));
+        }
+        self.rows.insert(id.to_owned(), self.ids.len());
+        self.ids.push(id.to_owned());
+        self.data.extend_from_slice(point);
+        Ok(())
+    }
+
+    /// Name the columns.
+    ///
+    /// # Errors
+    /// Returns an error if `names.len()` differs from [`DataSet::cols`] or
+    /// names are repeated. The current names are kept in that case.
+    pub fn set_column_names<S: AsRef<str>>(&mut self, names: &[S]) -> Result<(), FlucomaError> {
+        if names.len() != self.cols {
+            return Err(FlucomaError::InvalidShape {
+                expected: self.cols,
+                got: names.len(),
+            });
+        }
+        let names: Vec<String> = names.iter().map(|n| n.as_ref().to_owned()).collect();
+        if !all_unique(&names) {
+            return Err(FlucomaError::InvalidParameter(
+                "column names must be unique",
+            ));
+        }
+        self.columns = names;
+        self.generated_names = vec![false; self.cols];
+        Ok(())
+    }
+
+    /// Column names, in column order.
+    pub fn column_names(&self) -> &[String] {
+        &self.columns
+    }
+
+    /// Index of the column called `name`.
+    pub fn column_index(&self, name: &str) -> Option<usize> {
+        self.columns.iter().position(|c| c == name)
+    }
+
+    /// New data set with only the named columns, in the given order.
+    ///
+    /// ```rust
+    /// use flucoma_rs::data::DataSet;
+    ///
+    /// let mut ds = DataSet::new(3).unwrap();
+    /// ds.set_column_names(&["mfcc_0", "centroid_mean", "pitch_mean"]).unwrap();
+    /// ds.add("slice-0", &[1.0, 2.0, 3.0]).unwrap();
+    /// let selected = ds.select_columns(&["pitch_mean", "centroid_mean"]).unwrap();
+    /// assert_eq!(selected.get("slice-0"), Some(&[3.0, 2.0][..]));
+    /// ```
+    ///
+    /// # Errors
+    /// Returns [`FlucomaError::UnknownColumn`] if a name is not present, or an
+    /// error if `names` is empty or repeats a name.
+    pub fn select_columns<S: AsRef<str>>(&self, names: &[S]) -> Result<DataSet, FlucomaError> {
+        let indices = names
+            .iter()
+            .map(|name| {
+                self.column_index(name.as_ref())
+                    .ok_or_else(|| FlucomaError::UnknownColumn(name.as_ref().to_owned()))
+            })
+            .collect::<Result<Vec<usize>, _>>()?;
+        let mut out = self.empty_with_columns(&indices)?;
+        out.ids = self.ids.clone();
+        out.rows = self.rows.clone();
+        out.data.reserve(self.len() * indices.len());
+        for (_, point) in self.iter() {
+            out.data.extend(indices.iter().map(|&c| point[c]));
+        }
+        Ok(out)
+    }
+
+    /// Look up a point by id.
+    pub fn get(&self, id: &str) -> Option<&[f64]> {
+        self.rows.get(id).map(|&row| self.point(row))
+    }
+
+    /// Returns `true` if a point with this id is present.
+    pub fn contains(&self, id: &str) -> bool {
+        self.rows.contains_key(id)
+    }
+
+    /// Point at row `row`, in insertion order.
+    ///
+    /// # Panics
+    /// Panics if `row >= self.len()`.
+    pub fn point(&self, row: usize) -> &[f64] {
+        &self.data[row * self.cols..(row + 1) * self.cols]
+    }
+
+    /// Iterate over all `(id, point)` pairs in insertion order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &[f64])> + '_ {
+        self.ids
+            .iter()
+            .map(String::as_str)
+            .zip(self.data.chunks_exact(self.cols))
+    }
+
+    /// Ids in insertion order.
+    pub fn ids(&self) -> &[String] {
+        &self.ids
+    }
+
+    /// Flat row-major data, `len() × cols()`.
+    pub fn data(&self) -> &[f64] {
+        &self.data
+    }
+
+    /// Copy the points into a `len() × cols()` matrix, or `None` when empty.
+    pub fn to_matrix(&self) -> Option<Matrix> {
+        Matrix::from_vec(self.data.clone(), self.len(), self.cols).ok()
+    }
+
+    /// Number of points.
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    /// Returns `true` if the data set holds no points.
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    /// Dimensionality of the points.
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// Add all points of `other`, resolving shared ids with `policy`, like
+    /// FluidDataSet `merge`. New ids are appended in `other`'s order.
+    ///
+    /// # Errors
+    /// Returns an error if the dimensionalities differ, or if `policy` is
+    /// [`MergePolicy::Reject`] and an id is present in both. The data set is
+    /// unchanged on error.
+    pub fn merge(&mut self, other: &DataSet, policy: MergePolicy) -> Result<(), FlucomaError> {
+        if other.cols != self.cols {
+            return Err(FlucomaError::InvalidShape {
+                expected: self.cols,
+                got: other.cols,
+            });
+        }
+        if policy == MergePolicy::Reject {
+            if let Some(id) = other.ids.iter().find(|id| self.contains(id)) {
+                return Err(FlucomaError::DuplicateId(id.clone()));
+            }
+        }
+        for (id, point) in other.iter() {
+            match self.rows.get(id) {
+                Some(&row) => {
+                    if policy == MergePolicy::Overwrite {
+                        self.data[row * self.cols..(row + 1) * self.cols].copy_from_slice(point);
+                    }
+                }
+                None => self.add(id, point)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Concatenate the columns of `other` after the columns of this data set,
+    /// matching points by id. Both data sets must hold the same ids; the
+    /// result keeps this data set's order.
+    ///
+    /// Column names are kept, except that default names (`c{index}`) are
+    /// renumbered for their position in the result.
+    ///
+    /// # Errors
+    /// Returns an error if the data sets hold different ids or the resulting
+    /// column names are not unique.
+    pub fn concat_columns(&self, other: &DataSet) -> Result<DataSet, FlucomaError> {
+        if other.len() != self.len() {
+            return Err(FlucomaError::InvalidShape {
+                expected: self.len(),
+                got: other.len(),
+            });
+        }
+        let cols = self.cols + other.cols;
+        let generated_names: Vec<bool> = self
+            .generated_names
+            .iter()
+            .chain(&other.generated_names)
+            .copied()
+            .collect();
+        // Default names follow the column to its new index.
+        let columns: Vec<String> = self
+            .columns
+            .iter()
+            .chain(&other.columns)
+            .zip(&generated_names)
+            .enumerate()
+            .map(|(index, (name, &generated))| {
+                if generated {
+                    format!("c{index}")
+                } else {
+                    name.clone()
+                }
+            })
+            .collect();
+        if !all_unique(&columns) {
+            return Err(FlucomaError::InvalidParameter(
+                "column names must be unique",
+            ));
+        }
+        let mut data = Vec::with_capacity(self.len() * cols);
+        for (id, point) in self.iter() {
+            let rest = other
+                .get(id)
+                .ok_or_else(|| FlucomaError::UnknownId(id.to_owned()))?;
+            data.extend_from_slice(point);
+            data.extend_from_slice(rest);
+        }
+        Ok(DataSet {
+            cols,
+            columns,
+            generated_names,
+            ids: self.ids.clone(),
+            data,
+            rows: self.rows.clone(),
+        })
+    }
+
+    /// Keep only the points for which `keep(id, point)` returns `true`,
+    /// preserving order.
+    pub fn retain<F: FnMut(&str, &[f64]) -> bool>(&mut self, mut keep: F) {
+        let rows: Vec<usize> = (0..self.len())
+            .filter(|&row| keep(&self.ids[row], self.point(row)))
+            .collect();
+        if rows.len() != self.len() {
+            *self = self.subset(&rows);
+        }
+    }
+
+    /// Randomly split into `(train, test)` subsets with disjoint ids, where
+    /// `train` holds `round(len() * ratio)` points.
+    ///
+    /// The split is deterministic for a given non-negative `seed`; `-1`
+    /// splits at random, or with the [global seed](crate::set_global_seed)
+    /// if one is set. Both subsets keep the original insertion order.
+    ///
+    /// # Errors
+    /// Returns an error if `ratio` is not in `[0, 1]`.
+    pub fn split(&self, ratio: f64, seed: isize) -> Result<(DataSet, DataSet), FlucomaError> {
+        if !(0.0..=1.0).contains(&ratio) {
+            return Err(FlucomaError::InvalidParameter("ratio must be in [0, 1]"));
+        }
+        let order = self.shuffled_rows(seed);
+        let n_train = (self.len() as f64 * ratio).round() as usize;
+        let (train, test) = order.split_at(n_train);
+        Ok((self.subset(train), self.subset(test)))
+    }
+
+    /// Randomly partition into `k` folds for cross-validation.
+    ///
+    /// Returns `k` `(train, test)` pairs. Each point appears in exactly one
+    /// test set, fold sizes differ by at most one, and within a pair the ids
+    /// are disjoint. `seed` works as in [`DataSet::split`].
+    ///
+    /// # Errors
+    /// Returns an error if `k < 2` or `k > len()`.
+    pub fn k_folds(&self, k: usize, seed: isize) -> Result<Vec<(DataSet, DataSet)>, FlucomaError> {
+        if k < 2 || k > self.len() {
+            return Err(FlucomaError::InvalidParameter("k must be >= 2 and <= len"));
+        }
+        let order = self.shuffled_rows(seed);
+        let mut folds = Vec::with_capacity(k);
+        for fold in 0..k {
+            let start = fold * order.len() / k;
+            let end = (fold + 1) * order.len() / k;
+            let train: Vec<usize> = order[..start]
+                .iter()
+                .chain(&order[end..])
+                .copied()
+                .collect();
+            folds.push((self.subset(&train), self.subset(&order[start..end])));
+        }
+        Ok(folds)
+    }
+
+    /// Row indices in a seeded random order (Fisher-Yates with splitmix64).
+    fn shuffled_rows(&self, seed: isize) -> Vec<usize> {
+        let mut state = match resolve_seed(seed) {
+            seed if seed >= 0 => seed as u64,
+            _ => RandomState::new().build_hasher().finish(),
+        };
+        let mut next = || {
+            state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+            let mut z = state;
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+            z ^ (z >> 31)
+        };
+        let mut rows: Vec<usize> = (0..self.len()).collect();
+        for i in (1..rows.len()).rev() {
+            let j = (next() % (i as u64 + 1)) as usize;
+            rows.swap(i, j);
+        }
+        rows
+    }
+
+    /// Empty data set with the given columns of this one, keeping their
+    /// names.
+    ///
+    /// # Errors
+    /// Returns an error if `columns` is empty or repeats a column.
+    pub(crate) fn empty_with_columns(&self, columns: &[usize]) -> Result<DataSet, FlucomaError> {
+        let mut out = DataSet::new(columns.len())?;
+        let names: Vec<&str> = columns.iter().map(|&c| self.columns[c].as_str()).collect();
+        out.set_column_names(&names)?;
+        out.generated_names = columns.iter().map(|&c| self.generated_names[c]).collect();
+        Ok(out)
+    }
+
+    /// New data set with the given rows, in insertion order.
+    fn subset(&self, rows: &[usize]) -> DataSet {
+        let mut rows = rows.to_vec();
+        rows.sort_unstable();
+        let mut out = DataSet {
+            cols: self.cols,
+            columns: self.columns.clone(),
+            generated_names: self.generated_names.clone(),
+            ids: Vec::with_capacity(rows.len()),
+            data: Vec::with_capacity(rows.len() * self.cols),
+            rows: HashMap::with_capacity(rows.len()),
+        };
+        for row in rows {
+            out.rows.insert(self.ids[row].clone(), out.ids.len());
+            out.ids.push(self.ids[row].clone());
+            out.data.extend_from_slice(self.point(row));
+        }
+        out
+    }
+}
+
+fn all_unique(names: &[String]) -> bool {
+    let mut seen = std::collections::HashSet::with_capacity(names.len());
+    names.iter().all(|name| seen.insert(name))
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dataset_add_and_lookup() {
+        let mut ds = DataSet::new(2).unwrap();
+        ds.add("a", &[1.0, 2.0]).unwrap();
+        ds.add("b", &[3.0, 4.0]).unwrap();
+        assert_eq!(ds.len(), 2);
+        assert_eq!(ds.get("b"), Some(&[3.0, 4.0][..]));
+        assert_eq!(ds.point(0), &[1.0, 2.0]);
+        assert_eq!(
+            ds.add("a", &[0.0, 0.0]).unwrap_err(),
+            FlucomaError::DuplicateId("a".to_owned())
+        );
+        assert!(ds.add("c", &[0.0]).is_err());
+        let ids: Vec<&str> = ds.iter().map(|(id, _)| id).collect();
+        assert_eq!(ids, vec!["a", "b"]);
+    }
+
+    #[test]
+    fn dataset_split_and_folds_are_disjoint() {
+        let mut ds = DataSet::new(1).unwrap();
+        for i in 0..10 {
+            ds.add(&format!("p{i}"), &[i as f64]).unwrap();
+        }
+        let (train, test) = ds.split(0.7, 42).unwrap();
+        assert_eq!((train.len(), test.len()), (7, 3));
+        assert!(test.ids().iter().all(|id| !train.contains(id)));
+        assert_eq!(ds.split(0.7, 42).unwrap(), (train, test));
+        assert!(ds.split(1.5, 0).is_err());
+        let (train, test) = ds.split(0.7, -1).unwrap();
+        assert_eq!((train.len(), test.len()), (7, 3));
+
+        let folds = ds.k_folds(3, 7).unwrap();
+        assert_eq!(folds.len(), 3);
+        let mut tested: Vec<String> = Vec::new();
+        for (train, test) in &folds {
+            assert_eq!(train.len() + test.len(), 10);
+            assert!(test.ids().iter().all(|id| !train.contains(id)));
+            tested.extend(test.ids().iter().cloned());
+        }
+        tested.sort();
+        let mut all = ds.ids().to_vec();
+        all.sort();
+        assert_eq!(tested, all);
+        assert!(ds.k_folds(1, 0).is_err());
+    }
+
+    #[test]
+    fn dataset_merge_concat_and_retain() {
+        let m = Matrix::from_vec(vec![1.0, 2.0, 3.0, 4.0], 2, 2).unwrap();
+        let mut a = DataSet::from_matrix(&["x", "y"], &m).unwrap();
+        let mut b = DataSet::new(2).unwrap();
+        b.add("y", &[9.0, 9.0]).unwrap();
+        b.add("z", &[5.0, 6.0]).unwrap();
+
+        assert_eq!(
+            a.merge(&b, MergePolicy::Reject).unwrap_err(),
+            FlucomaError::DuplicateId("y".to_owned())
+        );
+        assert_eq!(a.len(), 2);
+        let mut kept = a.clone();
+        kept.merge(&b, MergePolicy::KeepExisting).unwrap();
+        assert_eq!(kept.get("y"), Some(&[3.0, 4.0][..]));
+        a.merge(&b, MergePolicy::Overwrite).unwrap();
+        assert_eq!(a.ids(), &["x", "y", "z"]);
+        assert_eq!(a.get("y"), Some(&[9.0, 9.0][..]));
+
+        let mut labels = DataSet::new(1).unwrap();
+        for (id, label) in [("z", 2.0), ("x", 0.0), ("y", 1.0)] {
+            labels.add(id, &[label]).unwrap();
+        }
+        let joined = a.concat_columns(&labels).unwrap();
+        assert_eq!(joined.cols(), 3);
+        assert_eq!(joined.get("x"), Some(&[1.0, 2.0, 0.0][..]));
+        assert!(a.concat_columns(&b).is_err());
+
+        a.retain(|id, point| id != "x" && point[0] > 6.0);
+        assert_eq!(a.ids(), &["y"]);
+        assert_eq!(a.get("y"), Some(&[9.0, 9.0][..]));
+    }
+
+    #[test]
+    fn dataset_named_columns() {
+        let mut ds = DataSet::new(3).unwrap();
+        assert_eq!(ds.column_names(), &["c0", "c1", "c2"]);
+        ds.set_column_names(&["mfcc_0", "centroid_mean", "pitch_mean"])
+            .unwrap();
+        assert!(ds.set_column_names(&["a", "a", "b"]).is_err());
+        assert_eq!(ds.column_index("pitch_mean"), Some(2));
+        ds.add("x", &[1.0, 2.0, 3.0]).unwrap();
+
+        let selected = ds.select_columns(&["pitch_mean", "mfcc_0"]).unwrap();
+        assert_eq!(selected.column_names(), &["pitch_mean", "mfcc_0"]);
+        assert_eq!(selected.get("x"), Some(&[3.0, 1.0][..]));
+        assert_eq!(
+            ds.select_columns(&["loudness"]).unwrap_err(),
+            FlucomaError::UnknownColumn("loudness".to_owned())
+        );
+
+        let mut labels = DataSet::new(1).unwrap();
+        labels.add("x", &[7.0]).unwrap();
+        let joined = ds.concat_columns(&labels).unwrap();
+        assert_eq!(
+            joined.column_names(),
+            &["mfcc_0", "centroid_mean", "pitch_mean", "c3"]
+        );
+        assert!(ds.concat_columns(&ds).is_err());
+
+        // Only generated names are renumbered, even when a chosen name
+        // looks like one.
+        let mut named = DataSet::new(2).unwrap();
+        named.set_column_names(&["c1", "gain"]).unwrap();
+        named.add("x", &[0.0, 1.0]).unwrap();
+        let joined = labels.concat_columns(&named).unwrap();
+        assert_eq!(joined.column_names(), &["c0", "c1", "gain"]);
+        let mut unnamed = DataSet::new(2).unwrap();
+        unnamed.add("x", &[2.0, 3.0]).unwrap();
+        let reordered = unnamed.select_columns(&["c1", "c0"]).unwrap();
+        assert_eq!(reordered.column_names(), &["c1", "c0"]);
+        assert_eq!(
+            reordered.concat_columns(&named).unwrap_err(),
+            FlucomaError::InvalidParameter("column names must be unique")
+        );
+    }
+
+    #[test]
+    fn dataset_matrix_round_trip() {
+        let m = Matrix::from_vec(vec![1.0, 2.0, 3.0, 4.0], 2, 2).unwrap();
+        let ds = DataSet::from_matrix(&["x", "y"], &m).unwrap();
+        assert_eq!(ds.to_matrix().unwrap(), m);
+        assert!(DataSet::new(3).unwrap().to_matrix().is_none());
+        assert!(DataSet::from_matrix(&["x"], &m).is_err());
+    }
+}
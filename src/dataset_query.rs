@@ -1,7 +1,11 @@
-use flucoma_sys::dataset_query_process;
+use std::collections::HashMap;
 
-#[derive(Debug, Clone, Copy)]
+use crate::dataset::DataSet;
+use crate::error::FlucomaError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(isize)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ComparisonOp {
     Eq = 0,
     Ne = 1,
@@ -11,16 +15,165 @@ pub enum ComparisonOp {
     Ge = 5,
 }
 
-#[derive(Debug, Clone, Copy)]
-pub struct QueryCondition {
-    pub column: usize,
-    pub op: ComparisonOp,
-    pub value: f64,
-    /// `true` = AND condition list, `false` = OR condition list.
-    pub and_group: bool,
+impl ComparisonOp {
+    /// Returns `lhs <op> rhs`.
+    pub fn apply(self, lhs: f64, rhs: f64) -> bool {
+        match self {
+            Self::Eq => lhs == rhs,
+            Self::Ne => lhs != rhs,
+            Self::Lt => lhs < rhs,
+            Self::Le => lhs <= rhs,
+            Self::Gt => lhs > rhs,
+            Self::Ge => lhs >= rhs,
+        }
+    }
+}
+
+/// Match rule for point ids.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum IdPattern {
+    /// Id equals the string.
+    Exact(String),
+    /// Id starts with the string.
+    Prefix(String),
+    /// Shell-style pattern where `*` matches any run of characters and `?`
+    /// matches a single character, e.g. `"kick-*"`.
+    Glob(String),
+}
+
+impl IdPattern {
+    /// Returns `true` if `id` matches the pattern.
+    pub fn matches(&self, id: &str) -> bool {
+        match self {
+            Self::Exact(s) => id == s,
+            Self::Prefix(s) => id.starts_with(s.as_str()),
+            Self::Glob(s) => glob_match(s, id),
+        }
+    }
+}
+
+/// Iterative wildcard matching with single-star backtracking.
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let p: Vec<char> = pattern.chars().collect();
+    let t: Vec<char> = text.chars().collect();
+    let (mut pi, mut ti) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while ti < t.len() {
+        if pi < p.len() && (p[pi] == '?' || p[pi] == t[ti]) {
+            pi += 1;
+            ti += 1;
+        } else if pi < p.len() && p[pi] == '*' {
+            star = Some((pi, ti));
+            pi += 1;
+        } else if let Some((star_pi, star_ti)) = star {
+            pi = star_pi + 1;
+            ti = star_ti + 1;
+            star = Some((star_pi, star_ti + 1));
+        } else {
+            return false;
+        }
+    }
+    p[pi..].iter().all(|&c| c == '*')
+}
+
+/// Boolean filter expression over the columns of a row.
+///
+/// Predicates can be nested arbitrarily:
+///
+/// ```rust
+/// use flucoma_rs::data::{ComparisonOp::*, Predicate};
+///
+/// // (col0 > 0.5 AND col1 < 10) OR (col2 == 3)
+/// let p = Predicate::new(0, Gt, 0.5)
+///     .and(Predicate::new(1, Lt, 10.0))
+///     .or(Predicate::new(2, Eq, 3.0));
+/// assert!(p.matches(&[1.0, 5.0, 0.0]));
+/// assert!(p.matches(&[0.0, 50.0, 3.0]));
+/// assert!(!p.matches(&[0.0, 5.0, 0.0]));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Predicate {
+    Compare {
+        column: usize,
+        op: ComparisonOp,
+        value: f64,
+    },
+    /// Match on the point id. Only usable with
+    /// [`DataSetQuery::execute_dataset`].
+    Id(IdPattern),
+    And(Box<Predicate>, Box<Predicate>),
+    Or(Box<Predicate>, Box<Predicate>),
+}
+
+impl Predicate {
+    /// Single comparison `row[column] <op> value`.
+    pub fn new(column: usize, op: ComparisonOp, value: f64) -> Self {
+        Self::Compare { column, op, value }
+    }
+
+    /// Id match, see [`IdPattern`].
+    pub fn id(pattern: IdPattern) -> Self {
+        Self::Id(pattern)
+    }
+
+    /// Combine with `other`, matching when both match.
+    pub fn and(self, other: Predicate) -> Self {
+        Self::And(Box::new(self), Box::new(other))
+    }
+
+    /// Combine with `other`, matching when either matches.
+    pub fn or(self, other: Predicate) -> Self {
+        Self::Or(Box::new(self), Box::new(other))
+    }
+
+    /// Evaluate the predicate for a single unlabelled row. Id predicates never
+    /// match.
+    ///
+    /// # Panics
+    /// Panics if a referenced column is out of range for `row`.
+    pub fn matches(&self, row: &[f64]) -> bool {
+        self.eval(None, row)
+    }
+
+    /// Evaluate the predicate for a labelled point.
+    ///
+    /// # Panics
+    /// Panics if a referenced column is out of range for `point`.
+    pub fn matches_point(&self, id: &str, point: &[f64]) -> bool {
+        self.eval(Some(id), point)
+    }
+
+    fn eval(&self, id: Option<&str>, row: &[f64]) -> bool {
+        match self {
+            Self::Compare { column, op, value } => op.apply(row[*column], *value),
+            Self::Id(pattern) => id.is_some_and(|id| pattern.matches(id)),
+            Self::And(lhs, rhs) => lhs.eval(id, row) && rhs.eval(id, row),
+            Self::Or(lhs, rhs) => lhs.eval(id, row) || rhs.eval(id, row),
+        }
+    }
+
+    /// Largest column index referenced by the predicate, if any.
+    pub(crate) fn max_column(&self) -> Option<usize> {
+        match self {
+            Self::Compare { column, .. } => Some(*column),
+            Self::Id(_) => None,
+            Self::And(lhs, rhs) | Self::Or(lhs, rhs) => lhs.max_column().max(rhs.max_column()),
+        }
+    }
+
+    fn uses_ids(&self) -> bool {
+        match self {
+            Self::Compare { .. } => false,
+            Self::Id(_) => true,
+            Self::And(lhs, rhs) | Self::Or(lhs, rhs) => lhs.uses_ids() || rhs.uses_ids(),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DataSetQueryResult {
     pub data: Vec<f64>,
     pub rows: usize,
@@ -28,102 +181,595 @@ pub struct DataSetQueryResult {
     pub source_indices: Vec<usize>,
 }
 
-pub struct DataSetQuery;
+/// Per-group summary computed by [`DataSetQuery::aggregate`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Aggregate {
+    /// Number of rows in the group.
+    Count,
+    /// Mean of a column.
+    Mean(usize),
+    /// Minimum of a column.
+    Min(usize),
+    /// Maximum of a column.
+    Max(usize),
+}
+
+impl Aggregate {
+    fn column(self) -> Option<usize> {
+        match self {
+            Self::Count => None,
+            Self::Mean(c) | Self::Min(c) | Self::Max(c) => Some(c),
+        }
+    }
+}
+
+/// Result of [`DataSetQuery::aggregate`]: one row per distinct group value,
+/// one column per requested [`Aggregate`].
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GroupSummary {
+    /// Distinct values of the group column, ascending. Row `i` of `data`
+    /// summarises `groups[i]`.
+    pub groups: Vec<f64>,
+    /// Row-major `rows × cols` summary values.
+    pub data: Vec<f64>,
+    pub rows: usize,
+    pub cols: usize,
+}
+
+/// Filter, sort, project and paginate the rows of a row-major data set.
+///
+/// Conditions chain from left to right: `filter(a).and(b).or(c)` matches
+/// `(a AND b) OR c`. Use [`DataSetQuery::and_where`] and
+/// [`DataSetQuery::or_where`] with a [`Predicate`] for nested groups.
+///
+/// ```rust
+/// use flucoma_rs::data::{ComparisonOp::*, DataSetQuery};
+///
+/// let data = [
+///     0.0, 10.0, 100.0, //
+///     1.0, 20.0, 200.0, //
+///     2.0, 30.0, 300.0,
+/// ];
+/// let result = DataSetQuery::new()
+///     .filter(0, Gt, 0.5)
+///     .and(1, Lt, 25.0)
+///     .or(2, Eq, 300.0)
+///     .select(&[1, 2])
+///     .limit(10)
+///     .execute(&data, 3, 3)
+///     .unwrap();
+/// assert_eq!(result.source_indices, vec![1, 2]);
+/// assert_eq!(result.data, vec![20.0, 200.0, 30.0, 300.0]);
+/// ```
+///
+/// See <https://learn.flucoma.org/reference/datasetquery>
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DataSetQuery {
+    predicate: Option<Predicate>,
+    columns: Vec<usize>,
+    order: Option<(usize, bool)>,
+    offset: usize,
+    limit: Option<usize>,
+}
 
 impl DataSetQuery {
+    /// Create a query which matches all rows and selects all columns.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Require `row[column] <op> value`. Conventionally the first condition
+    /// of a chain; like [`DataSetQuery::and`], it is combined with any
+    /// conditions added before it rather than replacing them.
+    pub fn filter(self, column: usize, op: ComparisonOp, value: f64) -> Self {
+        self.and(column, op, value)
+    }
+
+    /// Require `row[column] <op> value` in addition to the conditions so far.
+    pub fn and(self, column: usize, op: ComparisonOp, value: f64) -> Self {
+        self.and_where(Predicate::new(column, op, value))
+    }
+
+    /// Also match rows where `row[column] <op> value`.
+    pub fn or(self, column: usize, op: ComparisonOp, value: f64) -> Self {
+        self.or_where(Predicate::new(column, op, value))
+    }
+
+    /// Require `predicate` in addition to the conditions so far.
+    pub fn and_where(mut self, predicate: Predicate) -> Self {
+        self.predicate = Some(match self.predicate.take() {
+            Some(current) => current.and(predicate),
+            None => predicate,
+        });
+        self
+    }
+
+    /// Also match rows where `predicate` holds.
+    pub fn or_where(mut self, predicate: Predicate) -> Self {
+        self.predicate = Some(match self.predicate.take() {
+            Some(current) => current.or(predicate),
+            None => predicate,
+        });
+        self
+    }
+
+    /// Require the point id to match `pattern`, in addition to the conditions so
+    /// far.
+    pub fn filter_id(self, pattern: IdPattern) -> Self {
+        self.and_where(Predicate::id(pattern))
+    }
+
+    /// Columns to copy into the result, in output order. When never called,
+    /// all columns are selected.
+    pub fn select(mut self, columns: &[usize]) -> Self {
+        self.columns = columns.to_vec();
+        self
+    }
+
+    /// Sort matching rows by the value in `column`, ascending or descending.
+    ///
+    /// The sort is stable, so rows with equal values keep their source order.
+    /// NaN values sort after all numbers when ascending.
+    pub fn order_by(mut self, column: usize, ascending: bool) -> Self {
+        self.order = Some((column, ascending));
+        self
+    }
+
+    /// Skip the first `n` matching rows, after sorting.
+    pub fn offset(mut self, n: usize) -> Self {
+        self.offset = n;
+        self
+    }
+
+    /// Return at most `n` matching rows, after sorting and offset.
+    pub fn limit(mut self, n: usize) -> Self {
+        self.limit = Some(n);
+        self
+    }
+
+    /// Run the query over `rows × cols` row-major `data`.
+    ///
+    /// # Errors
+    /// Returns an error if the shape is invalid, a selected or filtered column
+    /// is out of range, or the query filters by id.
     pub fn execute(
+        &self,
         data: &[f64],
         rows: usize,
         cols: usize,
-        selected_columns: &[usize],
-        conditions: &[QueryCondition],
-        limit: Option<usize>,
-    ) -> Result<DataSetQueryResult, &'static str> {
+    ) -> Result<DataSetQueryResult, FlucomaError> {
         if rows == 0 || cols == 0 {
-            return Err("rows and cols must be > 0");
+            return Err(FlucomaError::InvalidParameter("rows and cols must be > 0"));
         }
         if data.len() != rows * cols {
-            return Err("data length does not match rows * cols");
+            return Err(FlucomaError::InvalidParameter(
+                "data length does not match rows * cols",
+            ));
         }
-        if selected_columns.is_empty() {
-            return Err("selected_columns cannot be empty");
+        if self.predicate.as_ref().is_some_and(Predicate::uses_ids) {
+            return Err(FlucomaError::InvalidParameter(
+                "id predicates require a DataSet",
+            ));
         }
-        if selected_columns.iter().any(|&c| c >= cols) {
-            return Err("selected column out of range");
+        let columns = self.columns(cols)?;
+
+        let mut out_data = Vec::new();
+        let mut source_indices = Vec::new();
+        for index in self.matching_rows(data.chunks_exact(cols).map(|row| (None, row))) {
+            let row = &data[index * cols..(index + 1) * cols];
+            out_data.extend(columns.iter().map(|&c| row[c]));
+            source_indices.push(index);
         }
-        if conditions.iter().any(|c| c.column >= cols) {
-            return Err("condition column out of range");
+
+        Ok(DataSetQueryResult {
+            data: out_data,
+            rows: source_indices.len(),
+            cols: columns.len(),
+            source_indices,
+        })
+    }
+
+    /// Run the query over a [`DataSet`], returning the matching points with
+    /// their ids and the selected columns.
+    ///
+    /// # Errors
+    /// Returns an error if a selected or filtered column is out of range, or
+    /// a column is selected twice.
+    pub fn execute_dataset(&self, dataset: &DataSet) -> Result<DataSet, FlucomaError> {
+        let columns = self.columns(dataset.cols())?;
+        let mut out = dataset.empty_with_columns(&columns)?;
+        let mut point = Vec::with_capacity(columns.len());
+        let matches = self.matching_rows(dataset.iter().map(|(id, row)| (Some(id), row)));
+        for index in matches {
+            let row = dataset.point(index);
+            point.clear();
+            point.extend(columns.iter().map(|&c| row[c]));
+            out.add(&dataset.ids()[index], &point)?;
         }
+        Ok(out)
+    }
 
-        let selected_cols: Vec<isize> = selected_columns.iter().map(|&x| x as isize).collect();
-        let cond_cols: Vec<isize> = conditions.iter().map(|c| c.column as isize).collect();
-        let cond_ops: Vec<isize> = conditions.iter().map(|c| c.op as isize).collect();
-        let cond_vals: Vec<f64> = conditions.iter().map(|c| c.value).collect();
-        let cond_and: Vec<isize> = conditions
-            .iter()
-            .map(|c| if c.and_group { 1 } else { 0 })
+    /// Group the matching rows of `rows × cols` row-major `data` by the value
+    /// in `group_column` and compute `aggregates` for each group.
+    ///
+    /// Filtering, sorting, offset and limit are applied before grouping;
+    /// column selection is ignored.
+    ///
+    /// ```rust
+    /// use flucoma_rs::data::{Aggregate, DataSetQuery};
+    ///
+    /// // columns: cluster, loudness
+    /// let data = [0.0, -20.0, 1.0, -10.0, 0.0, -30.0];
+    /// let summary = DataSetQuery::new()
+    ///     .aggregate(&data, 3, 2, 0, &[Aggregate::Count, Aggregate::Mean(1)])
+    ///     .unwrap();
+    /// assert_eq!(summary.groups, vec![0.0, 1.0]);
+    /// assert_eq!(summary.data, vec![2.0, -25.0, 1.0, -10.0]);
+    /// ```
+    ///
+    /// # Errors
+    /// Returns an error if the shape is invalid, `aggregates` is empty, or a
+    /// referenced column is out of range.
+    pub fn aggregate(
+        &self,
+        data: &[f64],
+        rows: usize,
+        cols: usize,
+        group_column: usize,
+        aggregates: &[Aggregate],
+    ) -> Result<GroupSummary, FlucomaError> {
+        if rows == 0 || cols == 0 {
+            return Err(FlucomaError::InvalidParameter("rows and cols must be > 0"));
+        }
+        if data.len() != rows * cols {
+            return Err(FlucomaError::InvalidParameter(
+                "data length does not match rows * cols",
+            ));
+        }
+        if self.predicate.as_ref().is_some_and(Predicate::uses_ids) {
+            return Err(FlucomaError::InvalidParameter(
+                "id predicates require a DataSet",
+            ));
+        }
+        self.columns(cols)?;
+        validate_aggregates(cols, group_column, aggregates)?;
+        let indices = self.matching_rows(data.chunks_exact(cols).map(|row| (None, row)));
+        Ok(summarize(
+            indices.iter().map(|&i| &data[i * cols..(i + 1) * cols]),
+            group_column,
+            aggregates,
+        ))
+    }
+
+    /// Like [`DataSetQuery::aggregate`], over the points of a [`DataSet`].
+    ///
+    /// # Errors
+    /// Returns an error if `aggregates` is empty or a referenced column is out
+    /// of range.
+    pub fn aggregate_dataset(
+        &self,
+        dataset: &DataSet,
+        group_column: usize,
+        aggregates: &[Aggregate],
+    ) -> Result<GroupSummary, FlucomaError> {
+        self.columns(dataset.cols())?;
+        validate_aggregates(dataset.cols(), group_column, aggregates)?;
+        let indices = self.matching_rows(dataset.iter().map(|(id, row)| (Some(id), row)));
+        Ok(summarize(
+            indices.iter().map(|&i| dataset.point(i)),
+            group_column,
+            aggregates,
+        ))
+    }
+
+    /// Validate the selection and predicate against `cols` and return the
+    /// output column indices.
+    fn columns(&self, cols: usize) -> Result<Vec<usize>, FlucomaError> {
+        if self.columns.iter().any(|&c| c >= cols) {
+            return Err(FlucomaError::InvalidParameter(
+                "selected column out of range",
+            ));
+        }
+        if self
+            .predicate
+            .as_ref()
+            .and_then(Predicate::max_column)
+            .is_some_and(|c| c >= cols)
+        {
+            return Err(FlucomaError::InvalidParameter(
+                "condition column out of range",
+            ));
+        }
+        if self.order.is_some_and(|(c, _)| c >= cols) {
+            return Err(FlucomaError::InvalidParameter("order column out of range"));
+        }
+        if self.columns.is_empty() {
+            Ok((0..cols).collect())
+        } else {
+            Ok(self.columns.clone())
+        }
+    }
+
+    /// Indices of the rows passing the predicate, sorted and paginated.
+    fn matching_rows<'a>(
+        &self,
+        rows: impl Iterator<Item = (Option<&'a str>, &'a [f64])>,
+    ) -> Vec<usize> {
+        let limit = self.limit.unwrap_or(usize::MAX);
+        let matches = rows
+            .enumerate()
+            .filter(|(_, (id, row))| self.predicate.as_ref().is_none_or(|p| p.eval(*id, row)));
+
+        let Some((column, ascending)) = self.order else {
+            return matches
+                .map(|(index, _)| index)
+                .skip(self.offset)
+                .take(limit)
+                .collect();
+        };
+        let mut keyed: Vec<(usize, f64)> = matches
+            .map(|(index, (_, row))| (index, row[column]))
             .collect();
+        keyed.sort_by(|a, b| {
+            let ordering = a.1.total_cmp(&b.1);
+            if ascending {
+                ordering
+            } else {
+                ordering.reverse()
+            }
+        });
+        keyed
+            .into_iter()
+            .map(|(index, _)| index)
+            .skip(self.offset)
+            .take(limit)
+            .collect()
+    }
+}
 
-        let mut out_data = vec![0.0; rows * selected_columns.len()];
-        let mut out_ids = vec![0isize; rows];
-        let mut out_count = 0isize;
-
-        let ok = dataset_query_process(
-            data.as_ptr(),
-            rows as isize,
-            cols as isize,
-            selected_cols.as_ptr(),
-            selected_cols.len() as isize,
-            cond_cols.as_ptr(),
-            cond_ops.as_ptr(),
-            cond_vals.as_ptr(),
-            cond_and.as_ptr(),
-            cond_cols.len() as isize,
-            limit.unwrap_or(0) as isize,
-            out_data.as_mut_ptr(),
-            out_ids.as_mut_ptr(),
-            &mut out_count as *mut isize,
-        );
-        if !ok {
-            return Err("dataset query failed");
-        }
-
-        let count = out_count as usize;
-        out_data.truncate(count * selected_columns.len());
-        out_ids.truncate(count);
-        Ok(DataSetQueryResult {
-            data: out_data,
-            rows: count,
-            cols: selected_columns.len(),
-            source_indices: out_ids.into_iter().map(|x| x as usize).collect(),
+fn validate_aggregates(
+    cols: usize,
+    group_column: usize,
+    aggregates: &[Aggregate],
+) -> Result<(), FlucomaError> {
+    if aggregates.is_empty() {
+        return Err(FlucomaError::InvalidParameter("aggregates cannot be empty"));
+    }
+    if group_column >= cols {
+        return Err(FlucomaError::InvalidParameter("group column out of range"));
+    }
+    if aggregates
+        .iter()
+        .filter_map(|a| a.column())
+        .any(|c| c >= cols)
+    {
+        return Err(FlucomaError::InvalidParameter(
+            "aggregate column out of range",
+        ));
+    }
+    Ok(())
+}
+
+fn summarize<'a>(
+    rows: impl Iterator<Item = &'a [f64]>,
+    group_column: usize,
+    aggregates: &[Aggregate],
+) -> GroupSummary {
+    struct Group {
+        key: f64,
+        count: usize,
+        acc: Vec<f64>,
+    }
+
+    let initial: Vec<f64> = aggregates
+        .iter()
+        .map(|a| match a {
+            Aggregate::Min(_) => f64::INFINITY,
+            Aggregate::Max(_) => f64::NEG_INFINITY,
+            Aggregate::Count | Aggregate::Mean(_) => 0.0,
         })
+        .collect();
+    let mut groups: Vec<Group> = Vec::new();
+    let mut lookup: HashMap<u64, usize> = HashMap::new();
+    for row in rows {
+        // Fold -0.0 into 0.0 so both land in the same group.
+        let key = row[group_column] + 0.0;
+        let index = *lookup.entry(key.to_bits()).or_insert_with(|| {
+            groups.push(Group {
+                key,
+                count: 0,
+                acc: initial.clone(),
+            });
+            groups.len() - 1
+        });
+        let group = &mut groups[index];
+        group.count += 1;
+        for (acc, aggregate) in group.acc.iter_mut().zip(aggregates) {
+            match *aggregate {
+                Aggregate::Count => {}
+                Aggregate::Mean(c) => *acc += row[c],
+                Aggregate::Min(c) => *acc = acc.min(row[c]),
+                Aggregate::Max(c) => *acc = acc.max(row[c]),
+            }
+        }
+    }
+    groups.sort_by(|a, b| a.key.total_cmp(&b.key));
+
+    let mut data = Vec::with_capacity(groups.len() * aggregates.len());
+    for group in &groups {
+        for (acc, aggregate) in group.acc.iter().zip(aggregates) {
+            data.push(match aggregate {
+                Aggregate::Count => group.count as f64,
+                Aggregate::Mean(_) => acc / group.count as f64,
+                Aggregate::Min(_) | Aggregate::Max(_) => *acc,
+            });
+        }
+    }
+    GroupSummary {
+        groups: groups.iter().map(|g| g.key).collect(),
+        data,
+        rows: groups.len(),
+        cols: aggregates.len(),
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::ComparisonOp::*;
     use super::*;
 
+    // 5x3 row-major
+    const DATA: [f64; 15] = [
+        0.0, 10.0, 100.0, //
+        1.0, 20.0, 200.0, //
+        2.0, 30.0, 300.0, //
+        3.0, 40.0, 400.0, //
+        4.0, 50.0, 500.0,
+    ];
+
     #[test]
     fn query_select_and_filter() {
-        // 5x3 row-major
-        let data = vec![
-            0.0, 10.0, 100.0, //
-            1.0, 20.0, 200.0, //
-            2.0, 30.0, 300.0, //
-            3.0, 40.0, 400.0, //
-            4.0, 50.0, 500.0,
-        ];
-        let conditions = [QueryCondition {
-            column: 0,
-            op: ComparisonOp::Ge,
-            value: 2.0,
-            and_group: true,
-        }];
-        let res = DataSetQuery::execute(&data, 5, 3, &[1, 2], &conditions, Some(2)).unwrap();
+        let res = DataSetQuery::new()
+            .filter(0, Ge, 2.0)
+            .select(&[1, 2])
+            .limit(2)
+            .execute(&DATA, 5, 3)
+            .unwrap();
         assert_eq!(res.rows, 2);
         assert_eq!(res.cols, 2);
-        assert_eq!(res.data.len(), 4);
-        assert_eq!(res.source_indices.len(), 2);
+        assert_eq!(res.data, vec![30.0, 300.0, 40.0, 400.0]);
+        assert_eq!(res.source_indices, vec![2, 3]);
+
+        // `filter` after other conditions narrows them instead of replacing them.
+        let both = DataSetQuery::new()
+            .and(0, Ge, 2.0)
+            .filter(1, Lt, 40.0)
+            .execute(&DATA, 5, 3)
+            .unwrap();
+        assert_eq!(both.source_indices, vec![2]);
+    }
+
+    #[test]
+    fn query_order_offset_limit() {
+        let page = DataSetQuery::new()
+            .filter(0, Ne, 2.0)
+            .order_by(1, false)
+            .offset(1)
+            .limit(2)
+            .select(&[0])
+            .execute(&DATA, 5, 3)
+            .unwrap();
+        assert_eq!(page.source_indices, vec![3, 1]);
+        assert_eq!(page.data, vec![3.0, 1.0]);
+
+        let past_end = DataSetQuery::new().offset(10).execute(&DATA, 5, 3).unwrap();
+        assert_eq!(past_end.rows, 0);
+        assert!(DataSetQuery::new()
+            .order_by(3, true)
+            .execute(&DATA, 5, 3)
+            .is_err());
+    }
+
+    #[test]
+    fn query_aggregate_by_group() {
+        // columns: cluster, value
+        let data = [1.0, 4.0, 0.0, 1.0, 1.0, 2.0, 0.0, 3.0, 2.0, 9.0];
+        let summary = DataSetQuery::new()
+            .filter(1, Lt, 9.0)
+            .aggregate(
+                &data,
+                5,
+                2,
+                0,
+                &[
+                    Aggregate::Count,
+                    Aggregate::Mean(1),
+                    Aggregate::Min(1),
+                    Aggregate::Max(1),
+                ],
+            )
+            .unwrap();
+        assert_eq!(summary.groups, vec![0.0, 1.0]);
+        assert_eq!(summary.rows, 2);
+        assert_eq!(summary.cols, 4);
+        assert_eq!(summary.data, vec![2.0, 2.0, 1.0, 3.0, 2.0, 3.0, 2.0, 4.0]);
+
+        assert!(DataSetQuery::new()
+            .aggregate(&data, 5, 2, 2, &[Aggregate::Count])
+            .is_err());
+        assert!(DataSetQuery::new().aggregate(&data, 5, 2, 0, &[]).is_err());
+    }
+
+    #[test]
+    fn query_nested_groups() {
+        // (col0 < 1 OR col0 > 3) AND col1 != 50
+        let res = DataSetQuery::new()
+            .and_where(Predicate::new(0, Lt, 1.0).or(Predicate::new(0, Gt, 3.0)))
+            .and(1, Ne, 50.0)
+            .execute(&DATA, 5, 3)
+            .unwrap();
+        assert_eq!(res.source_indices, vec![0]);
+        assert_eq!(res.cols, 3);
+    }
+
+    #[test]
+    fn query_dataset_by_id_preserves_ids() {
+        let mut ds = DataSet::new(3).unwrap();
+        for (id, row) in ["kick-1", "snare-1", "kick-2", "hat-1", "kick-10"]
+            .iter()
+            .zip(DATA.chunks_exact(3))
+        {
+            ds.add(id, row).unwrap();
+        }
+        let out = DataSetQuery::new()
+            .filter_id(IdPattern::Glob("kick-?".to_owned()))
+            .or_where(Predicate::id(IdPattern::Exact("hat-1".to_owned())))
+            .select(&[2])
+            .execute_dataset(&ds)
+            .unwrap();
+        assert_eq!(out.ids(), &["kick-1", "kick-2", "hat-1"]);
+        assert_eq!(out.get("hat-1"), Some(&[400.0][..]));
+
+        let prefixed = DataSetQuery::new()
+            .filter_id(IdPattern::Prefix("kick".to_owned()))
+            .and(0, Gt, 0.0)
+            .execute_dataset(&ds)
+            .unwrap();
+        assert_eq!(prefixed.ids(), &["kick-2", "kick-10"]);
+        assert_eq!(prefixed.cols(), 3);
+        assert_eq!(prefixed.column_names(), ds.column_names());
+        assert!(DataSetQuery::new()
+            .select(&[1, 1])
+            .execute_dataset(&ds)
+            .is_err());
+
+        let raw = DataSetQuery::new().filter_id(IdPattern::Exact("kick-1".to_owned()));
+        assert!(raw.execute(&DATA, 5, 3).is_err());
+    }
+
+    #[test]
+    fn glob_wildcards() {
+        assert!(glob_match("*", ""));
+        assert!(glob_match("a*c", "abbbc"));
+        assert!(glob_match("a?c", "abc"));
+        assert!(!glob_match("a?c", "abbc"));
+        assert!(glob_match("*-1*", "kick-12"));
+        assert!(!glob_match("kick", "kick-1"));
+    }
+
+    #[test]
+    fn query_rejects_out_of_range_columns() {
+        assert!(DataSetQuery::new()
+            .select(&[3])
+            .execute(&DATA, 5, 3)
+            .is_err());
+        assert!(DataSetQuery::new()
+            .filter(0, Eq, 0.0)
+            .or(7, Eq, 0.0)
+            .execute(&DATA, 5, 3)
+            .is_err());
     }
 }
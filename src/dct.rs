@@ -0,0 +1,160 @@
+use flucoma_sys::{dct_create, dct_destroy, dct_init, dct_process_frame};
+
+use crate::error::{check_len, FlucomaError};
+use crate::frame_analyzer::FrameAnalyzer;
+
+// -------------------------------------------------------------------------------------------------
+
+/// Discrete Cosine Transform (DCT-II) of a frame, keeping the first
+/// `n_coefs` coefficients. [`Mfcc`](crate::analyzation::Mfcc) takes it over
+/// log mel band energies.
+///
+/// See <https://learn.flucoma.org/reference/mfcc>
+///
+/// ```rust,no_run
+/// use flucoma_rs::fourier::Dct;
+///
+/// let mut dct = Dct::new(40, 13).unwrap();
+/// let coefs = dct.process_frame(&[0.0f64; 40]);
+/// assert_eq!(coefs.len(), 13);
+/// ```
+pub struct Dct {
+    inner: *mut u8,
+    input_size: usize,
+    n_coefs: usize,
+}
+
+unsafe impl Send for Dct {}
+
+impl Dct {
+    /// Create a DCT over frames of `input_size` values.
+    ///
+    /// # Arguments
+    /// * `input_size` - Number of values in each input frame.
+    /// * `n_coefs`    - Number of coefficients returned (must be > 0 and <= `input_size`).
+    ///
+    /// # Errors
+    /// Returns an error if `n_coefs` is out of range or allocation fails.
+    pub fn new(input_size: usize, n_coefs: usize) -> Result<Self, FlucomaError> {
+        if n_coefs == 0 || n_coefs > input_size {
+            return Err(FlucomaError::InvalidParameter(
+                "dct n_coefs must be > 0 and <= its input size",
+            ));
+        }
+        let inner = dct_create(input_size as isize, n_coefs as isize);
+        if inner.is_null() {
+            return Err(FlucomaError::AllocationFailed);
+        }
+        dct_init(inner, input_size as isize, n_coefs as isize);
+        Ok(Self {
+            inner,
+            input_size,
+            n_coefs,
+        })
+    }
+
+    /// Transform one frame and return its first `n_coefs` coefficients.
+    ///
+    /// # Panics
+    /// Panics if `input.len() != input_size`.
+    pub fn process_frame(&mut self, input: &[f64]) -> Vec<f64> {
+        let mut output = vec![0.0f64; self.n_coefs];
+        self.process_into(input, &mut output);
+        output
+    }
+
+    /// Like [`Dct::process_frame`], but returns
+    /// [`FlucomaError::InvalidShape`] instead of panicking if
+    /// `input.len() != input_size`.
+    pub fn try_process_frame(&mut self, input: &[f64]) -> Result<Vec<f64>, FlucomaError> {
+        check_len(self.input_size, input.len())?;
+        Ok(self.process_frame(input))
+    }
+
+    /// Like [`Dct::process_frame`], but writes the coefficients into
+    /// `output` instead of allocating, for use on an audio thread.
+    ///
+    /// # Panics
+    /// Panics if `input.len() != input_size` or `output.len() != n_coefs`.
+    pub fn process_into(&mut self, input: &[f64], output: &mut [f64]) {
+        assert_eq!(
+            input.len(),
+            self.input_size,
+            "input length ({}) must equal input_size ({})",
+            input.len(),
+            self.input_size
+        );
+        assert_eq!(
+            output.len(),
+            self.n_coefs,
+            "output length ({}) must equal n_coefs ({})",
+            output.len(),
+            self.n_coefs
+        );
+        dct_process_frame(
+            self.inner,
+            input.as_ptr(),
+            input.len() as isize,
+            output.as_mut_ptr(),
+            output.len() as isize,
+        );
+    }
+
+    /// Number of values in each input frame.
+    pub fn input_size(&self) -> usize {
+        self.input_size
+    }
+
+    /// Number of coefficients in each output frame.
+    pub fn n_coefs(&self) -> usize {
+        self.n_coefs
+    }
+}
+
+impl Drop for Dct {
+    fn drop(&mut self) {
+        dct_destroy(self.inner);
+    }
+}
+
+impl Clone for Dct {
+    fn clone(&self) -> Self {
+        Self::new(self.input_size, self.n_coefs).expect("settings were validated on construction")
+    }
+}
+
+impl FrameAnalyzer for Dct {
+    type Input = [f64];
+    type Params = ();
+    type Output = Vec<f64>;
+
+    fn process(&mut self, input: &[f64], _params: &()) -> Vec<f64> {
+        self.process_frame(input)
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dct_silence_and_constant_input() {
+        let mut dct = Dct::new(16, 4).unwrap();
+        assert_eq!(dct.process_frame(&[0.0; 16]), vec![0.0; 4]);
+
+        // A constant frame only has a DC component.
+        let coefs = dct.process_frame(&[1.0; 16]);
+        assert!(coefs[0].abs() > 1e-6, "dc = {}", coefs[0]);
+        for (k, c) in coefs.iter().enumerate().skip(1) {
+            assert!(c.abs() < 1e-9, "coef {k} = {c}");
+        }
+
+        let mut output = [0.0; 4];
+        dct.process_into(&[1.0; 16], &mut output);
+        assert_eq!(output.to_vec(), coefs);
+        assert!(dct.try_process_frame(&[0.0; 8]).is_err());
+        assert!(Dct::new(4, 8).is_err());
+    }
+}
@@ -0,0 +1,147 @@
+use crate::buf_frames::{fill_frame, num_frames};
+use crate::error::FlucomaError;
+use crate::feature_matrix::FeatureMatrix;
+
+// -------------------------------------------------------------------------------------------------
+
+/// Configuration for [`Envelope`].
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EnvelopeConfig {
+    pub window_size: usize,
+    pub hop_size: usize,
+    /// Lowest reported level in dBFS, e.g. for silent frames.
+    pub floor_db: f64,
+}
+
+impl Default for EnvelopeConfig {
+    fn default() -> Self {
+        Self {
+            window_size: 1024,
+            hop_size: 512,
+            floor_db: -120.0,
+        }
+    }
+}
+
+/// Whole-buffer RMS and peak envelope.
+///
+/// Returns a frames × 2 [`FeatureMatrix`] with columns `rms` and `peak`
+/// (both in dBFS), framed like the other whole-buffer analysers. Unlike
+/// [`BufLoudness`](crate::analyzation::BufLoudness) there is no
+/// K-weighting or oversampling, so it is cheap enough for quick overviews.
+/// [`Envelope::rms_weights`] gives the linear RMS per frame, ready to use as
+/// [`BufStats`](crate::data::BufStats) weights.
+///
+/// ```rust,no_run
+/// use flucoma_rs::analyzation::{Envelope, EnvelopeConfig};
+///
+/// let audio = vec![0.0f64; 44100];
+/// let envelope = Envelope::new(EnvelopeConfig::default()).unwrap();
+/// let curves = envelope.process(&audio, 44100.0).unwrap();
+/// let weights = envelope.rms_weights(&audio);
+/// assert_eq!(weights.len(), curves.num_frames());
+/// ```
+#[derive(Debug, Clone)]
+pub struct Envelope {
+    config: EnvelopeConfig,
+}
+
+impl Envelope {
+    /// # Errors
+    /// Returns an error if the configuration is invalid.
+    pub fn new(config: EnvelopeConfig) -> Result<Self, FlucomaError> {
+        validate_config(&config)?;
+        Ok(Self { config })
+    }
+
+    pub fn config(&self) -> &EnvelopeConfig {
+        &self.config
+    }
+
+    pub fn set_config(&mut self, config: EnvelopeConfig) -> Result<(), FlucomaError> {
+        validate_config(&config)?;
+        self.config = config;
+        Ok(())
+    }
+
+    /// Compute the RMS and peak curves of `signal` in dBFS.
+    ///
+    /// # Errors
+    /// Returns an error if `sample_rate <= 0`.
+    pub fn process(&self, signal: &[f64], sample_rate: f64) -> Result<FeatureMatrix, FlucomaError> {
+        if sample_rate <= 0.0 {
+            return Err(FlucomaError::InvalidParameter("sample_rate must be > 0"));
+        }
+        let c = &self.config;
+        let to_db = |level: f64| (20.0 * level.log10()).max(c.floor_db);
+        let mut data = Vec::new();
+        self.for_each_frame(signal, |frame| {
+            let peak = frame.iter().fold(0.0f64, |max, x| max.max(x.abs()));
+            data.extend([to_db(rms(frame)), to_db(peak)]);
+        });
+        let columns = vec!["rms".to_owned(), "peak".to_owned()];
+        FeatureMatrix::new(data, columns, sample_rate, c.window_size, c.hop_size)
+    }
+
+    /// Linear RMS of every frame of `signal`.
+    pub fn rms_weights(&self, signal: &[f64]) -> Vec<f64> {
+        let mut weights = Vec::new();
+        self.for_each_frame(signal, |frame| weights.push(rms(frame)));
+        weights
+    }
+
+    fn for_each_frame(&self, signal: &[f64], mut f: impl FnMut(&[f64])) {
+        let c = &self.config;
+        let mut frame = vec![0.0; c.window_size];
+        for index in 0..num_frames(signal.len(), c.hop_size) {
+            fill_frame(signal, index, c.hop_size, &mut frame);
+            f(&frame);
+        }
+    }
+}
+
+fn rms(frame: &[f64]) -> f64 {
+    (frame.iter().map(|x| x * x).sum::<f64>() / frame.len() as f64).sqrt()
+}
+
+fn validate_config(config: &EnvelopeConfig) -> Result<(), FlucomaError> {
+    if config.window_size == 0 {
+        return Err(FlucomaError::InvalidParameter("window_size must be > 0"));
+    }
+    if config.hop_size == 0 {
+        return Err(FlucomaError::InvalidParameter("hop_size must be > 0"));
+    }
+    if config.floor_db.is_nan() {
+        return Err(FlucomaError::InvalidParameter("floor_db must not be NaN"));
+    }
+    Ok(())
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn envelope_follows_level() {
+        let mut audio = vec![0.0f64; 8192];
+        for sample in audio.iter_mut().skip(4096).step_by(2) {
+            *sample = 0.5;
+        }
+        let envelope = Envelope::new(EnvelopeConfig::default()).unwrap();
+        let curves = envelope.process(&audio, 44100.0).unwrap();
+        assert_eq!(curves.num_frames(), 8192 / 512 + 1);
+        assert_eq!(curves.column_names(), &["rms", "peak"]);
+        assert_eq!(curves.frame(2), &[-120.0, -120.0]);
+        // Every other sample at 0.5 is -6 dB peak and -9 dB RMS.
+        let loud = curves.frame(12);
+        assert!((loud[0] + 9.03).abs() < 0.01 && (loud[1] + 6.02).abs() < 0.01);
+
+        let weights = envelope.rms_weights(&audio);
+        assert_eq!(weights.len(), curves.num_frames());
+        assert_eq!(weights[2], 0.0);
+        assert!((weights[12] - 0.5f64.sqrt() * 0.5).abs() < 1e-12);
+    }
+}
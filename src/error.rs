@@ -0,0 +1,66 @@
+use std::fmt;
+
+// -------------------------------------------------------------------------------------------------
+
+/// Error type returned by fallible flucoma-rs operations.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FlucomaError {
+    /// A parameter is outside of its valid range.
+    InvalidParameter(&'static str),
+    /// An input buffer or vector has the wrong length.
+    InvalidShape { expected: usize, got: usize },
+    /// A value of the input at `row`, `col` lies outside the range a model
+    /// was fitted on.
+    OutOfRange { row: usize, col: usize },
+    /// The underlying flucoma-core object could not be created.
+    AllocationFailed,
+    /// A model was used before being fitted.
+    NotFitted,
+    /// An id is already present in the target container.
+    DuplicateId(String),
+    /// An id was not found in the target container.
+    UnknownId(String),
+    /// A column name was not found.
+    UnknownColumn(String),
+    /// The operation needs an optional cargo feature which is not enabled.
+    FeatureDisabled(&'static str),
+    /// Reading or writing an external file format failed.
+    Io(String),
+    /// A long-running job was stopped through its
+    /// [`CancellationToken`](crate::CancellationToken).
+    Cancelled,
+}
+
+impl fmt::Display for FlucomaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidParameter(msg) => write!(f, "invalid parameter: {msg}"),
+            Self::InvalidShape { expected, got } => {
+                write!(f, "invalid shape: expected {expected}, got {got}")
+            }
+            Self::OutOfRange { row, col } => {
+                write!(f, "outside the fitted range at row {row}, column {col}")
+            }
+            Self::AllocationFailed => write!(f, "failed to allocate flucoma-core instance"),
+            Self::NotFitted => write!(f, "model is not fitted"),
+            Self::DuplicateId(id) => write!(f, "duplicate id: {id:?}"),
+            Self::UnknownId(id) => write!(f, "unknown id: {id:?}"),
+            Self::UnknownColumn(name) => write!(f, "unknown column: {name:?}"),
+            Self::FeatureDisabled(name) => {
+                write!(f, "cargo feature `{name}` is not enabled in this build")
+            }
+            Self::Io(msg) => write!(f, "i/o error: {msg}"),
+            Self::Cancelled => write!(f, "operation was cancelled"),
+        }
+    }
+}
+
+impl std::error::Error for FlucomaError {}
+
+/// Checks an input length, returning [`FlucomaError::InvalidShape`] on mismatch.
+pub(crate) fn check_len(expected: usize, got: usize) -> Result<(), FlucomaError> {
+    if got != expected {
+        return Err(FlucomaError::InvalidShape { expected, got });
+    }
+    Ok(())
+}
@@ -0,0 +1,230 @@
+//! Model evaluation helpers for regression and classification outputs.
+//!
+//! Predictions and ground truth are either passed as position-aligned slices,
+//! or as `(id, value)` pairs which are first aligned with [`align_by_id`].
+
+use std::collections::{HashMap, HashSet};
+
+use crate::error::FlucomaError;
+use crate::matrix::Matrix;
+
+// -------------------------------------------------------------------------------------------------
+
+/// Regression error summary.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RegressionMetrics {
+    /// Mean squared error.
+    pub mse: f64,
+    /// Mean absolute error.
+    pub mae: f64,
+    /// Coefficient of determination. `1.0` is a perfect fit; `0.0` is no
+    /// better than predicting the mean. Set to `0.0` when the ground truth has
+    /// zero variance.
+    pub r2: f64,
+}
+
+/// Classification accuracy and confusion matrix.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ClassificationMetrics {
+    /// Fraction of correctly classified points, in `[0, 1]`.
+    pub accuracy: f64,
+    /// Sorted union of all labels seen in truth and predictions. Gives the
+    /// row/column order of `confusion`.
+    pub labels: Vec<String>,
+    /// `labels.len() × labels.len()` counts: row = true label, column =
+    /// predicted label.
+    pub confusion: Matrix,
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Compute MSE, MAE and R² for position-aligned values.
+///
+/// Multi-output predictions can be passed flattened, as long as both slices
+/// use the same layout.
+///
+/// # Errors
+/// Returns an error if the inputs are empty or their lengths differ.
+pub fn regression(truth: &[f64], predicted: &[f64]) -> Result<RegressionMetrics, FlucomaError> {
+    check_lengths(truth.len(), predicted.len())?;
+    let n = truth.len() as f64;
+    let mut sq_sum = 0.0;
+    let mut abs_sum = 0.0;
+    for (t, p) in truth.iter().zip(predicted) {
+        let d = p - t;
+        sq_sum += d * d;
+        abs_sum += d.abs();
+    }
+    let mean = truth.iter().sum::<f64>() / n;
+    let total: f64 = truth.iter().map(|t| (t - mean) * (t - mean)).sum();
+    let r2 = if total > 0.0 {
+        1.0 - sq_sum / total
+    } else {
+        0.0
+    };
+    Ok(RegressionMetrics {
+        mse: sq_sum / n,
+        mae: abs_sum / n,
+        r2,
+    })
+}
+
+/// Compute accuracy and the confusion matrix for position-aligned labels.
+///
+/// # Errors
+/// Returns an error if the inputs are empty or their lengths differ.
+pub fn classification<S: AsRef<str>>(
+    truth: &[S],
+    predicted: &[S],
+) -> Result<ClassificationMetrics, FlucomaError> {
+    check_lengths(truth.len(), predicted.len())?;
+    let mut labels: Vec<String> = truth
+        .iter()
+        .chain(predicted)
+        .map(|l| l.as_ref().to_owned())
+        .collect();
+    labels.sort();
+    labels.dedup();
+    let index: HashMap<&str, usize> = labels
+        .iter()
+        .enumerate()
+        .map(|(i, l)| (l.as_str(), i))
+        .collect();
+
+    let n_labels = labels.len();
+    let mut confusion = Matrix::new(n_labels, n_labels);
+    let mut correct = 0usize;
+    for (t, p) in truth.iter().zip(predicted) {
+        let row = index[t.as_ref()];
+        let col = index[p.as_ref()];
+        confusion.data_mut()[row * n_labels + col] += 1.0;
+        if row == col {
+            correct += 1;
+        }
+    }
+    Ok(ClassificationMetrics {
+        accuracy: correct as f64 / truth.len() as f64,
+        labels,
+        confusion,
+    })
+}
+
+/// Pair up `(id, value)` entries from truth and predictions by id.
+///
+/// Returns `(truth_values, predicted_values)` in the order of `truth`, ready
+/// to pass to [`regression`] or [`classification`].
+///
+/// # Errors
+/// Returns an error if the two sets do not contain exactly the same ids, or if
+/// an id is repeated within `truth` or `predicted`.
+pub fn align_by_id<K: AsRef<str>, T: Clone>(
+    truth: &[(K, T)],
+    predicted: &[(K, T)],
+) -> Result<(Vec<T>, Vec<T>), FlucomaError> {
+    if truth.len() != predicted.len() {
+        return Err(FlucomaError::InvalidShape {
+            expected: truth.len(),
+            got: predicted.len(),
+        });
+    }
+    let mut lookup: HashMap<&str, &T> = HashMap::with_capacity(predicted.len());
+    for (id, value) in predicted {
+        if lookup.insert(id.as_ref(), value).is_some() {
+            return Err(FlucomaError::DuplicateId(id.as_ref().to_owned()));
+        }
+    }
+    let mut seen = HashSet::with_capacity(truth.len());
+    let mut aligned_truth = Vec::with_capacity(truth.len());
+    let mut aligned_predicted = Vec::with_capacity(truth.len());
+    for (id, value) in truth {
+        if !seen.insert(id.as_ref()) {
+            return Err(FlucomaError::DuplicateId(id.as_ref().to_owned()));
+        }
+        let prediction = lookup
+            .get(id.as_ref())
+            .ok_or_else(|| FlucomaError::UnknownId(id.as_ref().to_owned()))?;
+        aligned_truth.push(value.clone());
+        aligned_predicted.push((*prediction).clone());
+    }
+    Ok((aligned_truth, aligned_predicted))
+}
+
+fn check_lengths(truth: usize, predicted: usize) -> Result<(), FlucomaError> {
+    if truth == 0 {
+        return Err(FlucomaError::InvalidParameter("inputs must not be empty"));
+    }
+    if truth != predicted {
+        return Err(FlucomaError::InvalidShape {
+            expected: truth,
+            got: predicted,
+        });
+    }
+    Ok(())
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn regression_perfect_fit() {
+        let m = regression(&[1.0, 2.0, 3.0], &[1.0, 2.0, 3.0]).unwrap();
+        assert_eq!(m.mse, 0.0);
+        assert_eq!(m.mae, 0.0);
+        assert!((m.r2 - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn regression_known_errors() {
+        let m = regression(&[1.0, 2.0, 3.0], &[2.0, 2.0, 1.0]).unwrap();
+        assert!((m.mse - 5.0 / 3.0).abs() < 1e-12);
+        assert!((m.mae - 1.0).abs() < 1e-12);
+        // total sum of squares = 2, residual = 5
+        assert!((m.r2 - (1.0 - 5.0 / 2.0)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn classification_confusion_counts() {
+        let truth = ["a", "a", "b", "b"];
+        let predicted = ["a", "b", "b", "b"];
+        let m = classification(&truth, &predicted).unwrap();
+        assert_eq!(m.labels, vec!["a".to_owned(), "b".to_owned()]);
+        assert!((m.accuracy - 0.75).abs() < 1e-12);
+        assert_eq!(m.confusion.data(), &[1.0, 1.0, 0.0, 2.0]);
+    }
+
+    #[test]
+    fn align_by_id_reorders_predictions() {
+        let truth = [("x", 1.0), ("y", 2.0)];
+        let predicted = [("y", 2.5), ("x", 0.5)];
+        let (t, p) = align_by_id(&truth, &predicted).unwrap();
+        assert_eq!(t, vec![1.0, 2.0]);
+        assert_eq!(p, vec![0.5, 2.5]);
+    }
+
+    #[test]
+    fn align_by_id_rejects_missing_ids() {
+        let truth = [("x", 1.0), ("y", 2.0)];
+        let predicted = [("x", 1.0), ("z", 2.0)];
+        let err = align_by_id(&truth, &predicted).unwrap_err();
+        assert_eq!(err, FlucomaError::UnknownId("y".to_owned()));
+    }
+
+    #[test]
+    fn align_by_id_rejects_duplicate_truth_ids() {
+        let truth = [("x", 1.0), ("x", 2.0)];
+        let predicted = [("x", 1.0), ("y", 2.0)];
+        let err = align_by_id(&truth, &predicted).unwrap_err();
+        assert_eq!(err, FlucomaError::DuplicateId("x".to_owned()));
+    }
+
+    #[test]
+    fn mismatched_lengths_fail() {
+        assert!(regression(&[1.0], &[1.0, 2.0]).is_err());
+        assert!(classification::<&str>(&[], &[]).is_err());
+    }
+}
@@ -0,0 +1,67 @@
+use crate::error::FlucomaError;
+
+// -------------------------------------------------------------------------------------------------
+
+/// Optional cargo features and whether they were enabled at compile time.
+///
+/// Modules that depend on an optional feature stay available in every build;
+/// their entry points call [`require`] and return
+/// [`FlucomaError::FeatureDisabled`] when the feature is missing, so a single
+/// binary can probe for optional capabilities at runtime.
+const FEATURES: &[(&str, bool)] = &[
+    ("serde", cfg!(feature = "serde")),
+    ("arrow", cfg!(feature = "arrow")),
+    ("corpus", cfg!(feature = "corpus")),
+    ("parallel", cfg!(feature = "parallel")),
+    ("tokio", cfg!(feature = "tokio")),
+];
+
+/// Names of all optional features which are enabled in this build.
+pub fn enabled() -> Vec<&'static str> {
+    FEATURES
+        .iter()
+        .filter(|(_, on)| *on)
+        .map(|(name, _)| *name)
+        .collect()
+}
+
+/// Returns `true` if the optional cargo feature `name` is enabled in this build.
+pub fn is_enabled(name: &str) -> bool {
+    FEATURES.iter().any(|(feature, on)| *feature == name && *on)
+}
+
+/// Check that the optional cargo feature `name` is enabled.
+///
+/// # Errors
+/// Returns [`FlucomaError::FeatureDisabled`] if the feature is unknown or was
+/// not enabled at compile time.
+pub fn require(name: &'static str) -> Result<(), FlucomaError> {
+    if is_enabled(name) {
+        Ok(())
+    } else {
+        Err(FlucomaError::FeatureDisabled(name))
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unknown_feature_is_disabled() {
+        assert!(!is_enabled("no-such-feature"));
+        assert_eq!(
+            require("no-such-feature").unwrap_err(),
+            FlucomaError::FeatureDisabled("no-such-feature")
+        );
+    }
+
+    #[test]
+    fn enabled_lists_only_enabled_features() {
+        for name in enabled() {
+            assert!(is_enabled(name));
+        }
+    }
+}
@@ -0,0 +1,123 @@
+use crate::error::FlucomaError;
+use crate::matrix::Matrix;
+
+// -------------------------------------------------------------------------------------------------
+
+/// Frames × features matrix produced by the whole-buffer (`Buf*`) analysers,
+/// together with the analysis settings needed to place each frame in time.
+///
+/// Frame `i` is centred on sample `i * hop_size` of the analysed buffer.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FeatureMatrix {
+    matrix: Matrix,
+    columns: Vec<String>,
+    sample_rate: f64,
+    window_size: usize,
+    hop_size: usize,
+}
+
+impl FeatureMatrix {
+    /// Wrap row-major frame data. `data.len()` must be a non-zero multiple
+    /// of `columns.len()`.
+    pub(crate) fn new(
+        data: Vec<f64>,
+        columns: Vec<String>,
+        sample_rate: f64,
+        window_size: usize,
+        hop_size: usize,
+    ) -> Result<Self, FlucomaError> {
+        if columns.is_empty() {
+            return Err(FlucomaError::InvalidParameter(
+                "feature matrix must have at least one column",
+            ));
+        }
+        let rows = data.len() / columns.len();
+        let matrix = Matrix::from_vec(data, rows, columns.len())?;
+        Ok(Self {
+            matrix,
+            columns,
+            sample_rate,
+            window_size,
+            hop_size,
+        })
+    }
+
+    /// The frames × features data.
+    pub fn matrix(&self) -> &Matrix {
+        &self.matrix
+    }
+
+    /// Consume and return the frames × features data.
+    pub fn into_matrix(self) -> Matrix {
+        self.matrix
+    }
+
+    /// Number of frames (rows).
+    pub fn num_frames(&self) -> usize {
+        self.matrix.rows()
+    }
+
+    /// Number of features per frame (columns).
+    pub fn num_features(&self) -> usize {
+        self.matrix.cols()
+    }
+
+    /// Features of frame `index`.
+    ///
+    /// # Panics
+    /// Panics if `index >= num_frames()`.
+    pub fn frame(&self, index: usize) -> &[f64] {
+        let cols = self.matrix.cols();
+        &self.matrix.data()[index * cols..(index + 1) * cols]
+    }
+
+    /// Name of each feature column.
+    pub fn column_names(&self) -> &[String] {
+        &self.columns
+    }
+
+    /// Centre time of frame `index`, in seconds.
+    pub fn frame_time(&self, index: usize) -> f64 {
+        (index * self.hop_size) as f64 / self.sample_rate
+    }
+
+    /// Centre time of every frame, in seconds.
+    pub fn frame_times(&self) -> Vec<f64> {
+        (0..self.num_frames()).map(|i| self.frame_time(i)).collect()
+    }
+
+    /// Sample rate of the analysed buffer, in Hz.
+    pub fn sample_rate(&self) -> f64 {
+        self.sample_rate
+    }
+
+    /// Analysis window size in samples.
+    pub fn window_size(&self) -> usize {
+        self.window_size
+    }
+
+    /// Hop between successive frames in samples.
+    pub fn hop_size(&self) -> usize {
+        self.hop_size
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn feature_matrix_frames_and_times() {
+        let columns = vec!["a".to_owned(), "b".to_owned()];
+        let features =
+            FeatureMatrix::new(vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0], columns, 1000.0, 4, 2).unwrap();
+        assert_eq!(features.num_frames(), 3);
+        assert_eq!(features.num_features(), 2);
+        assert_eq!(features.frame(1), &[3.0, 4.0]);
+        assert_eq!(features.frame_times(), vec![0.0, 0.002, 0.004]);
+        assert!(FeatureMatrix::new(vec![1.0], vec![], 1000.0, 4, 2).is_err());
+    }
+}
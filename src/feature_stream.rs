@@ -0,0 +1,252 @@
+use std::io::{self, Read, Write};
+
+// -------------------------------------------------------------------------------------------------
+
+const MAGIC: &[u8; 4] = b"FLFS";
+const VERSION: u32 = 1;
+
+/// Writes feature frames to a byte stream, one frame at a time.
+///
+/// The format is a 12 byte header (`b"FLFS"`, a little-endian `u32` version
+/// and a little-endian `u32` frame dimensionality) followed by frames of
+/// `n_dims` little-endian `f64` values. It is meant for long analyses whose
+/// feature matrix should not be kept in memory, and is read back with
+/// [`FeatureStreamReader`].
+///
+/// ```rust
+/// use flucoma_rs::data::{FeatureStreamReader, FeatureStreamWriter};
+///
+/// let mut writer = FeatureStreamWriter::new(Vec::new(), 2).unwrap();
+/// writer.write_frame(&[0.5, 1.0]).unwrap();
+/// let bytes = writer.finish().unwrap();
+///
+/// let mut reader = FeatureStreamReader::new(bytes.as_slice()).unwrap();
+/// let mut frame = [0.0; 2];
+/// assert!(reader.read_frame(&mut frame).unwrap());
+/// assert_eq!(frame, [0.5, 1.0]);
+/// assert!(!reader.read_frame(&mut frame).unwrap());
+/// ```
+pub struct FeatureStreamWriter<W: Write> {
+    writer: W,
+    n_dims: usize,
+    frames: usize,
+}
+
+impl<W: Write> FeatureStreamWriter<W> {
+    /// Write the stream header and return a writer for `n_dims`-sized frames.
+    ///
+    /// # Errors
+    /// Returns an error if `n_dims` is zero or too large, or writing fails.
+    pub fn new(mut writer: W, n_dims: usize) -> io::Result<Self> {
+        let dims = u32::try_from(n_dims)
+            .ok()
+            .filter(|&d| d > 0)
+            .ok_or_else(|| invalid_input("n_dims must be > 0 and fit in a u32"))?;
+        writer.write_all(MAGIC)?;
+        writer.write_all(&VERSION.to_le_bytes())?;
+        writer.write_all(&dims.to_le_bytes())?;
+        Ok(Self {
+            writer,
+            n_dims,
+            frames: 0,
+        })
+    }
+
+    /// Append one frame.
+    ///
+    /// # Errors
+    /// Returns an error if `frame.len() != n_dims` or writing fails.
+    pub fn write_frame(&mut self, frame: &[f64]) -> io::Result<()> {
+        if frame.len() != self.n_dims {
+            return Err(invalid_input("frame length must equal n_dims"));
+        }
+        for value in frame {
+            self.writer.write_all(&value.to_le_bytes())?;
+        }
+        self.frames += 1;
+        Ok(())
+    }
+
+    /// Dimensionality of each frame.
+    pub fn n_dims(&self) -> usize {
+        self.n_dims
+    }
+
+    /// Number of frames written so far.
+    pub fn frames_written(&self) -> usize {
+        self.frames
+    }
+
+    /// Flush and return the underlying writer.
+    ///
+    /// # Errors
+    /// Returns an error if flushing fails.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Reads feature frames written by [`FeatureStreamWriter`].
+///
+/// Frames are decoded on demand, so memory use is bounded by the caller's
+/// frame or chunk buffer rather than the length of the stream.
+pub struct FeatureStreamReader<R: Read> {
+    reader: R,
+    n_dims: usize,
+    frames: usize,
+    bytes: Vec<u8>,
+}
+
+impl<R: Read> FeatureStreamReader<R> {
+    /// Read and validate the stream header.
+    ///
+    /// # Errors
+    /// Returns an [`io::ErrorKind::InvalidData`] error if the header is not a
+    /// supported feature stream header, or any error from the reader.
+    pub fn new(mut reader: R) -> io::Result<Self> {
+        let mut header = [0u8; 12];
+        reader.read_exact(&mut header)?;
+        if &header[..4] != MAGIC {
+            return Err(invalid_data("not a feature stream"));
+        }
+        let version = u32::from_le_bytes(header[4..8].try_into().unwrap());
+        if version != VERSION {
+            return Err(invalid_data("unsupported feature stream version"));
+        }
+        let n_dims = u32::from_le_bytes(header[8..12].try_into().unwrap()) as usize;
+        if n_dims == 0 {
+            return Err(invalid_data("feature stream has zero dimensions"));
+        }
+        Ok(Self {
+            reader,
+            n_dims,
+            frames: 0,
+            bytes: vec![0; n_dims * 8],
+        })
+    }
+
+    /// Dimensionality of each frame.
+    pub fn n_dims(&self) -> usize {
+        self.n_dims
+    }
+
+    /// Number of frames read so far.
+    pub fn frames_read(&self) -> usize {
+        self.frames
+    }
+
+    /// Read the next frame into `frame`.
+    ///
+    /// Returns `false` at the end of the stream.
+    ///
+    /// # Errors
+    /// Returns an error if `frame.len() != n_dims`, the stream ends in the
+    /// middle of a frame, or reading fails.
+    pub fn read_frame(&mut self, frame: &mut [f64]) -> io::Result<bool> {
+        if frame.len() != self.n_dims {
+            return Err(invalid_input("frame length must equal n_dims"));
+        }
+        let mut filled = 0;
+        while filled < self.bytes.len() {
+            match self.reader.read(&mut self.bytes[filled..]) {
+                Ok(0) => break,
+                Ok(n) => filled += n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        if filled == 0 {
+            return Ok(false);
+        }
+        if filled < self.bytes.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "feature stream ends in the middle of a frame",
+            ));
+        }
+        for (value, bytes) in frame.iter_mut().zip(self.bytes.chunks_exact(8)) {
+            *value = f64::from_le_bytes(bytes.try_into().unwrap());
+        }
+        self.frames += 1;
+        Ok(true)
+    }
+
+    /// Read up to `max_frames` frames into `out`, replacing its contents with
+    /// row-major frame data.
+    ///
+    /// Returns the number of frames read, which is `0` at the end of the
+    /// stream.
+    ///
+    /// # Errors
+    /// Returns an error under the same conditions as
+    /// [`FeatureStreamReader::read_frame`].
+    pub fn read_chunk(&mut self, out: &mut Vec<f64>, max_frames: usize) -> io::Result<usize> {
+        out.clear();
+        out.resize(max_frames * self.n_dims, 0.0);
+        let mut count = 0;
+        while count < max_frames {
+            let start = count * self.n_dims;
+            if !self.read_frame(&mut out[start..start + self.n_dims])? {
+                break;
+            }
+            count += 1;
+        }
+        out.truncate(count * self.n_dims);
+        Ok(count)
+    }
+}
+
+fn invalid_input(msg: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, msg)
+}
+
+fn invalid_data(msg: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn feature_stream_round_trip_in_chunks() {
+        let mut writer = FeatureStreamWriter::new(Vec::new(), 3).unwrap();
+        for i in 0..5 {
+            let x = i as f64;
+            writer.write_frame(&[x, x + 0.5, -x]).unwrap();
+        }
+        assert!(writer.write_frame(&[1.0]).is_err());
+        assert_eq!(writer.frames_written(), 5);
+        let bytes = writer.finish().unwrap();
+
+        let mut reader = FeatureStreamReader::new(bytes.as_slice()).unwrap();
+        assert_eq!(reader.n_dims(), 3);
+        let mut chunk = Vec::new();
+        assert_eq!(reader.read_chunk(&mut chunk, 2).unwrap(), 2);
+        assert_eq!(chunk, vec![0.0, 0.5, 0.0, 1.0, 1.5, -1.0]);
+        assert_eq!(reader.read_chunk(&mut chunk, 2).unwrap(), 2);
+        assert_eq!(reader.read_chunk(&mut chunk, 2).unwrap(), 1);
+        assert_eq!(chunk, vec![4.0, 4.5, -4.0]);
+        assert_eq!(reader.read_chunk(&mut chunk, 2).unwrap(), 0);
+        assert_eq!(reader.frames_read(), 5);
+    }
+
+    #[test]
+    fn feature_stream_rejects_bad_input() {
+        assert!(FeatureStreamReader::new(&b"NOPE\x01\0\0\0\x02\0\0\0"[..]).is_err());
+
+        let mut bytes = FeatureStreamWriter::new(Vec::new(), 2)
+            .unwrap()
+            .finish()
+            .unwrap();
+        bytes.extend_from_slice(&1.0f64.to_le_bytes());
+        let mut reader = FeatureStreamReader::new(bytes.as_slice()).unwrap();
+        let err = reader.read_frame(&mut [0.0; 2]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+}
@@ -0,0 +1,154 @@
+use crate::error::FlucomaError;
+use crate::stft::{validate_params, WindowType};
+
+// -------------------------------------------------------------------------------------------------
+
+/// STFT framing of a spectral analysis, shared by [`Stft`](crate::fourier::Stft),
+/// [`Istft`](crate::fourier::Istft), [`MelBands`](crate::analyzation::MelBands)
+/// and the whole-buffer configs, so a pipeline can be configured from one
+/// consistent value.
+///
+/// Defaults to a 1024 sample Hann window, a 1024 point FFT and a hop of 512.
+///
+/// ```rust,no_run
+/// use flucoma_rs::analyzation::{BufMfcc, BufMfccConfig, MelBands};
+/// use flucoma_rs::fourier::{FftSettings, Stft};
+///
+/// let fft = FftSettings {
+///     fft_size: 2048,
+///     ..FftSettings::default()
+/// };
+/// let stft = Stft::from_settings(&fft).unwrap();
+/// let mel = MelBands::builder(44100.0).fft_settings(&fft).build().unwrap();
+/// let mfcc = BufMfcc::new(BufMfccConfig::default().with_fft_settings(&fft).unwrap()).unwrap();
+/// assert_eq!(stft.num_bins(), mel.n_bins());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FftSettings {
+    /// Analysis window length in samples.
+    pub window_size: usize,
+    /// FFT size, at least `window_size`.
+    pub fft_size: usize,
+    /// Hop between successive frames in samples.
+    pub hop_size: usize,
+    pub window_type: WindowType,
+}
+
+impl Default for FftSettings {
+    fn default() -> Self {
+        Self {
+            window_size: 1024,
+            fft_size: 1024,
+            hop_size: 512,
+            window_type: WindowType::Hann,
+        }
+    }
+}
+
+impl FftSettings {
+    /// Number of spectrum bins (`fft_size / 2 + 1`).
+    pub fn num_bins(&self) -> usize {
+        self.fft_size / 2 + 1
+    }
+
+    /// Check that the sizes form a valid STFT.
+    ///
+    /// # Errors
+    /// Returns [`FlucomaError::InvalidParameter`] naming the invalid size.
+    pub fn validate(&self) -> Result<(), FlucomaError> {
+        validate_params(self.window_size, self.fft_size, self.hop_size)
+    }
+}
+
+/// Implement `fft_settings()` and `with_fft_settings()` for a whole-buffer or
+/// streaming config with `window_size`, `fft_size` and `hop_size` fields whose
+/// analysis always runs with a Hann window.
+macro_rules! impl_fft_settings {
+    ($config:ty) => {
+        impl $config {
+            /// The window, hop and FFT size as
+            /// [`FftSettings`](crate::fourier::FftSettings), with the Hann
+            /// window used by the analysis.
+            pub fn fft_settings(&self) -> $crate::fft_settings::FftSettings {
+                $crate::fft_settings::FftSettings {
+                    window_size: self.window_size,
+                    fft_size: self.fft_size,
+                    hop_size: self.hop_size,
+                    window_type: $crate::stft::WindowType::Hann,
+                }
+            }
+
+            /// Take the window, hop and FFT size from shared
+            /// [`FftSettings`](crate::fourier::FftSettings).
+            ///
+            /// # Errors
+            /// Returns [`FlucomaError::InvalidParameter`](crate::FlucomaError::InvalidParameter)
+            /// if `window_type` is not Hann, the only window this analysis
+            /// supports.
+            pub fn with_fft_settings(
+                mut self,
+                settings: &$crate::fft_settings::FftSettings,
+            ) -> Result<Self, $crate::error::FlucomaError> {
+                if settings.window_type != $crate::stft::WindowType::Hann {
+                    return Err($crate::error::FlucomaError::InvalidParameter(
+                        "window_type must be Hann",
+                    ));
+                }
+                self.window_size = settings.window_size;
+                self.fft_size = settings.fft_size;
+                self.hop_size = settings.hop_size;
+                Ok(self)
+            }
+        }
+    };
+}
+
+pub(crate) use impl_fft_settings;
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::buf_mfcc::BufMfccConfig;
+
+    #[test]
+    fn fft_settings_round_trip_through_configs() {
+        let fft = FftSettings {
+            window_size: 2048,
+            fft_size: 4096,
+            hop_size: 256,
+            window_type: WindowType::Hann,
+        };
+        assert_eq!(fft.num_bins(), 2049);
+        assert_eq!(fft.validate(), Ok(()));
+
+        let config = BufMfccConfig::default().with_fft_settings(&fft).unwrap();
+        assert_eq!(
+            (config.window_size, config.fft_size, config.hop_size),
+            (2048, 4096, 256)
+        );
+        assert_eq!(config.fft_settings(), fft);
+
+        let hamming = FftSettings {
+            window_type: WindowType::Hamming,
+            ..fft
+        };
+        assert_eq!(
+            BufMfccConfig::default().with_fft_settings(&hamming).err(),
+            Some(FlucomaError::InvalidParameter("window_type must be Hann"))
+        );
+
+        let invalid = FftSettings {
+            fft_size: 512,
+            ..fft
+        };
+        assert_eq!(
+            invalid.validate(),
+            Err(FlucomaError::InvalidParameter(
+                "fft_size must be >= window_size"
+            ))
+        );
+    }
+}
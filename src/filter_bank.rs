@@ -0,0 +1,320 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::error::{check_len, FlucomaError};
+use crate::frame_analyzer::FrameAnalyzer;
+use crate::mel_bands::MelBandsParams;
+use crate::param_names::{display_name, parse_name};
+
+// -------------------------------------------------------------------------------------------------
+
+/// Perceptual frequency scale on which [`FilterBank`] spaces its bands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FilterScale {
+    /// Mel scale, as used by [`MelBands`](crate::analyzation::MelBands).
+    #[default]
+    Mel,
+    /// Bark critical-band scale (Traunmüller's formula).
+    Bark,
+    /// Equivalent rectangular bandwidth rate (Glasberg & Moore).
+    Erb,
+}
+
+const FILTER_SCALE_NAMES: &[(&str, FilterScale)] = &[
+    ("mel", FilterScale::Mel),
+    ("bark", FilterScale::Bark),
+    ("erb", FilterScale::Erb),
+];
+
+impl FromStr for FilterScale {
+    type Err = FlucomaError;
+
+    /// Parse a name such as `"mel"` or `"erb"`, ignoring case and separators.
+    fn from_str(name: &str) -> Result<Self, FlucomaError> {
+        parse_name(name, FILTER_SCALE_NAMES, "unknown filter scale")
+    }
+}
+
+impl fmt::Display for FilterScale {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(display_name(self, FILTER_SCALE_NAMES))
+    }
+}
+
+impl FilterScale {
+    /// Convert a frequency in Hz to this scale.
+    pub fn from_hz(self, hz: f64) -> f64 {
+        match self {
+            Self::Mel => 1127.01048 * (hz / 700.0).ln_1p(),
+            Self::Bark => 26.81 * hz / (1960.0 + hz) - 0.53,
+            Self::Erb => 21.4 * (0.00437 * hz).ln_1p() / std::f64::consts::LN_10,
+        }
+    }
+
+    /// Convert a value on this scale back to Hz.
+    pub fn to_hz(self, value: f64) -> f64 {
+        match self {
+            Self::Mel => 700.0 * (value / 1127.01048).exp_m1(),
+            Self::Bark => 1960.0 * (value + 0.53) / (26.28 - value),
+            Self::Erb => (10f64.powf(value / 21.4) - 1.0) / 0.00437,
+        }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Triangular filter bank on a mel, Bark or ERB scale, computed in Rust.
+///
+/// Converts a magnitude spectrum into band energies like
+/// [`MelBands`](crate::analyzation::MelBands), with the same per-frame
+/// settings, but the bands can be spaced on any [`FilterScale`]. The
+/// `n_bands` triangles overlap by half and are equally spaced on the scale
+/// between `lo_hz` and `hi_hz`.
+///
+/// ```rust,no_run
+/// use flucoma_rs::analyzation::{FilterBank, FilterScale, MelBandsParams};
+///
+/// let mut bark = FilterBank::new(FilterScale::Bark, 24, 513, 20.0, 15500.0, 44100.0).unwrap();
+/// let magnitudes = vec![0.0f64; 513];
+/// let bands = bark.process_frame(&magnitudes, &MelBandsParams::default());
+/// assert_eq!(bands.len(), 24);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FilterBank {
+    scale: FilterScale,
+    n_bands: usize,
+    n_bins: usize,
+    lo_hz: f64,
+    hi_hz: f64,
+    /// `n_bands × n_bins` triangle weights, row-major.
+    filters: Vec<f64>,
+}
+
+impl FilterBank {
+    /// Create a filter bank.
+    ///
+    /// # Arguments
+    /// * `scale`       - Frequency scale the bands are spaced on.
+    /// * `n_bands`     - Number of bands (must be >= 2).
+    /// * `n_bins`      - Number of FFT magnitude bins (`fft_size / 2 + 1`).
+    /// * `lo_hz`       - Low-frequency edge of the filter bank in Hz.
+    /// * `hi_hz`       - High-frequency edge of the filter bank in Hz, at
+    ///   most Nyquist.
+    /// * `sample_rate` - Audio sample rate in Hz.
+    ///
+    /// # Errors
+    /// Returns an error if parameters are invalid.
+    pub fn new(
+        scale: FilterScale,
+        n_bands: usize,
+        n_bins: usize,
+        lo_hz: f64,
+        hi_hz: f64,
+        sample_rate: f64,
+    ) -> Result<Self, FlucomaError> {
+        if n_bands < 2 {
+            return Err(FlucomaError::InvalidParameter("n_bands must be >= 2"));
+        }
+        if n_bins < 2 {
+            return Err(FlucomaError::InvalidParameter("n_bins must be >= 2"));
+        }
+        if sample_rate <= 0.0 {
+            return Err(FlucomaError::InvalidParameter("sample_rate must be > 0"));
+        }
+        if lo_hz < 0.0 {
+            return Err(FlucomaError::InvalidParameter("lo_hz must be >= 0"));
+        }
+        if lo_hz >= hi_hz {
+            return Err(FlucomaError::InvalidParameter("lo_hz must be < hi_hz"));
+        }
+        if hi_hz > sample_rate / 2.0 {
+            return Err(FlucomaError::InvalidParameter(
+                "hi_hz must be <= sample_rate / 2",
+            ));
+        }
+        Ok(Self {
+            scale,
+            n_bands,
+            n_bins,
+            lo_hz,
+            hi_hz,
+            filters: triangular_filters(scale, n_bands, n_bins, lo_hz, hi_hz, sample_rate),
+        })
+    }
+
+    pub fn scale(&self) -> FilterScale {
+        self.scale
+    }
+
+    /// Number of bands in each output frame.
+    pub fn n_bands(&self) -> usize {
+        self.n_bands
+    }
+
+    /// Number of FFT magnitude bins expected as input (`fft_size / 2 + 1`).
+    pub fn n_bins(&self) -> usize {
+        self.n_bins
+    }
+
+    /// Centre frequency of each band in Hz.
+    pub fn center_frequencies(&self) -> Vec<f64> {
+        band_edges(self.scale, self.n_bands, self.lo_hz, self.hi_hz)[1..=self.n_bands].to_vec()
+    }
+
+    /// The `n_bins` weights of one band's filter.
+    ///
+    /// # Panics
+    /// Panics if `band >= n_bands()`.
+    pub fn weights(&self, band: usize) -> &[f64] {
+        &self.filters[band * self.n_bins..][..self.n_bins]
+    }
+
+    /// Process a magnitude spectrum frame and return band energies.
+    ///
+    /// # Panics
+    /// Panics if `magnitudes.len() != n_bins`.
+    pub fn process_frame(&self, magnitudes: &[f64], params: &MelBandsParams) -> Vec<f64> {
+        let mut output = vec![0.0; self.n_bands];
+        self.process_into(magnitudes, params, &mut output);
+        output
+    }
+
+    /// Like [`FilterBank::process_frame`], but returns
+    /// [`FlucomaError::InvalidShape`] instead of panicking if
+    /// `magnitudes.len() != n_bins`.
+    pub fn try_process_frame(
+        &self,
+        magnitudes: &[f64],
+        params: &MelBandsParams,
+    ) -> Result<Vec<f64>, FlucomaError> {
+        check_len(self.n_bins, magnitudes.len())?;
+        Ok(self.process_frame(magnitudes, params))
+    }
+
+    /// Like [`FilterBank::process_frame`], but writes the band energies into
+    /// `output` instead of allocating, for use on an audio thread.
+    ///
+    /// # Panics
+    /// Panics if `magnitudes.len() != n_bins` or `output.len() != n_bands`.
+    pub fn process_into(&self, magnitudes: &[f64], params: &MelBandsParams, output: &mut [f64]) {
+        assert_eq!(
+            magnitudes.len(),
+            self.n_bins,
+            "magnitudes length ({}) must equal n_bins ({})",
+            magnitudes.len(),
+            self.n_bins
+        );
+        assert_eq!(
+            output.len(),
+            self.n_bands,
+            "output length ({}) must equal n_bands ({})",
+            output.len(),
+            self.n_bands
+        );
+        for (band, value) in output.iter_mut().enumerate() {
+            let weights = self.weights(band);
+            let mut energy: f64 = weights
+                .iter()
+                .zip(magnitudes)
+                .map(|(w, m)| if params.use_power { w * m * m } else { w * m })
+                .sum();
+            if params.mag_norm {
+                let area: f64 = weights.iter().sum();
+                if area > 0.0 {
+                    energy /= area;
+                }
+            }
+            *value = if params.log_output {
+                20.0 * energy.max(1e-10).log10()
+            } else {
+                energy
+            };
+        }
+    }
+}
+
+impl FrameAnalyzer for FilterBank {
+    type Input = [f64];
+    type Params = MelBandsParams;
+    type Output = Vec<f64>;
+
+    fn process(&mut self, magnitudes: &[f64], params: &MelBandsParams) -> Vec<f64> {
+        self.process_frame(magnitudes, params)
+    }
+}
+
+/// The `n_bands + 2` band edges in Hz, equally spaced on `scale`.
+fn band_edges(scale: FilterScale, n_bands: usize, lo_hz: f64, hi_hz: f64) -> Vec<f64> {
+    let (lo, hi) = (scale.from_hz(lo_hz), scale.from_hz(hi_hz));
+    (0..n_bands + 2)
+        .map(|i| scale.to_hz(lo + (hi - lo) * i as f64 / (n_bands + 1) as f64))
+        .collect()
+}
+
+/// Triangular filter weights, `n_bands × n_bins` row-major. On the mel
+/// scale these match the filters built by flucoma-core's `MelBands::init`.
+pub(crate) fn triangular_filters(
+    scale: FilterScale,
+    n_bands: usize,
+    n_bins: usize,
+    lo_hz: f64,
+    hi_hz: f64,
+    sample_rate: f64,
+) -> Vec<f64> {
+    let edges = band_edges(scale, n_bands, lo_hz, hi_hz);
+    let bin_hz = sample_rate / 2.0 / (n_bins - 1) as f64;
+    let mut filters = vec![0.0; n_bands * n_bins];
+    for band in 0..n_bands {
+        let (lo, center, hi) = (edges[band], edges[band + 1], edges[band + 2]);
+        for bin in 0..n_bins {
+            let hz = bin as f64 * bin_hz;
+            let rising = (hz - lo) / (center - lo);
+            let falling = (hi - hz) / (hi - center);
+            filters[band * n_bins + bin] = rising.min(falling).max(0.0);
+        }
+    }
+    filters
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filter_scales_round_trip() {
+        for scale in [FilterScale::Mel, FilterScale::Bark, FilterScale::Erb] {
+            for hz in [50.0, 1000.0, 12000.0] {
+                let back = scale.to_hz(scale.from_hz(hz));
+                assert!((back - hz).abs() < 1e-6, "{scale}: {hz} -> {back}");
+            }
+        }
+        // 1 kHz is about 8.5 Bark and 15.6 ERB.
+        assert!((FilterScale::Bark.from_hz(1000.0) - 8.53).abs() < 0.05);
+        assert!((FilterScale::Erb.from_hz(1000.0) - 15.62).abs() < 0.05);
+        assert_eq!("ERB".parse::<FilterScale>(), Ok(FilterScale::Erb));
+    }
+
+    #[test]
+    fn filter_bank_measures_band_energy() {
+        let mut erb = FilterBank::new(FilterScale::Erb, 30, 513, 50.0, 20000.0, 44100.0).unwrap();
+        let centers = erb.center_frequencies();
+        assert!(centers.windows(2).all(|w| w[0] < w[1]));
+
+        // A single bin excites only the bands whose triangles cover it.
+        let mut magnitudes = vec![0.0; 513];
+        let bin = 93; // about 4 kHz
+        magnitudes[bin] = 1.0;
+        let bands = erb.process(&magnitudes, &MelBandsParams::default());
+        for (band, &energy) in bands.iter().enumerate() {
+            assert_eq!(energy > 0.0, erb.weights(band)[bin] > 0.0);
+        }
+        assert_eq!(
+            FilterBank::new(FilterScale::Bark, 24, 513, 20.0, 30000.0, 44100.0).unwrap_err(),
+            FlucomaError::InvalidParameter("hi_hz must be <= sample_rate / 2")
+        );
+    }
+}
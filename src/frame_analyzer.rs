@@ -0,0 +1,82 @@
+// -------------------------------------------------------------------------------------------------
+
+/// Common interface of the frame-based analysers, so generic pipelines,
+/// multichannel wrappers and test harnesses can treat them uniformly.
+///
+/// Settings fixed at construction stay on the analyser; settings the
+/// underlying algorithm takes per frame are passed as `Params`, whose
+/// `Default` matches the FluCoMa object's defaults. Analysers without
+/// per-frame settings use `()`.
+///
+/// The analysers also implement `Clone`, so per-voice or per-channel
+/// instances can be spawned from a configured prototype. A clone is a new
+/// native instance with the same settings; frame history and filter state
+/// start fresh rather than being copied.
+///
+/// ```rust,no_run
+/// use flucoma_rs::analyzation::{FrameAnalyzer, MelBands, MelBandsParams};
+///
+/// fn analyze_all<A: FrameAnalyzer<Input = [f64]>>(
+///     analyzer: &mut A,
+///     frames: &[Vec<f64>],
+///     params: &A::Params,
+/// ) -> Vec<A::Output> {
+///     frames.iter().map(|frame| analyzer.process(frame, params)).collect()
+/// }
+///
+/// let mut mel = MelBands::new(40, 513, 20.0, 20000.0, 44100.0, 1024).unwrap();
+/// let bands = analyze_all(&mut mel, &[vec![0.0; 513]], &MelBandsParams::default());
+/// ```
+pub trait FrameAnalyzer {
+    /// One frame of input, e.g. `[f64]` for audio or magnitude spectra.
+    type Input: ?Sized;
+    /// Per-frame settings.
+    type Params;
+    /// Result for one frame.
+    type Output;
+
+    /// Analyse one frame.
+    ///
+    /// # Panics
+    /// Panics under the same conditions as the analyser's own
+    /// `process_frame`, e.g. if the input length does not match.
+    fn process(&mut self, input: &Self::Input, params: &Self::Params) -> Self::Output;
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A pure-Rust analyser, so the generic plumbing can be tested without
+    /// the C++ library.
+    struct Energy;
+
+    impl FrameAnalyzer for Energy {
+        type Input = [f64];
+        type Params = f64;
+        type Output = f64;
+
+        fn process(&mut self, input: &[f64], gain: &f64) -> f64 {
+            gain * input.iter().map(|x| x * x).sum::<f64>()
+        }
+    }
+
+    fn run<A: FrameAnalyzer<Input = [f64]>>(
+        analyzer: &mut A,
+        frames: &[Vec<f64>],
+        params: &A::Params,
+    ) -> Vec<A::Output> {
+        frames
+            .iter()
+            .map(|frame| analyzer.process(frame, params))
+            .collect()
+    }
+
+    #[test]
+    fn frame_analyzer_is_usable_generically() {
+        let frames = vec![vec![1.0, 2.0], vec![0.5, 0.5]];
+        assert_eq!(run(&mut Energy, &frames, &2.0), vec![10.0, 1.0]);
+    }
+}
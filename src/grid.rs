@@ -1,6 +1,95 @@
+use std::fmt;
+use std::str::FromStr;
+
 use flucoma_sys::grid_process;
 
+use crate::error::FlucomaError;
+use crate::param_names::{display_name, parse_name};
+
+/// Axis constrained by [`GridConfig::extent`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[repr(isize)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GridAxis {
+    /// `extent` limits the number of columns.
+    #[default]
+    Horizontal = 0,
+    /// `extent` limits the number of rows.
+    Vertical = 1,
+}
+
+const GRID_AXIS_NAMES: &[(&str, GridAxis)] = &[
+    ("horizontal", GridAxis::Horizontal),
+    ("vertical", GridAxis::Vertical),
+];
+
+impl FromStr for GridAxis {
+    type Err = FlucomaError;
+
+    /// Parse a name such as `"vertical"`, ignoring case and separators.
+    fn from_str(name: &str) -> Result<Self, FlucomaError> {
+        parse_name(name, GRID_AXIS_NAMES, "unknown grid axis")
+    }
+}
+
+impl fmt::Display for GridAxis {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(display_name(self, GRID_AXIS_NAMES))
+    }
+}
+
+/// Parameters for [`Grid::layout`], mirroring FluidGrid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GridConfig {
+    /// Oversampling factor: the grid has `over_sample` times more cells than
+    /// points, leaving gaps between dissimilar points.
+    pub over_sample: usize,
+    /// Maximum number of cells along `axis`, or `0` for a square-ish grid.
+    pub extent: usize,
+    /// Axis constrained by `extent`.
+    pub axis: GridAxis,
+}
+
+impl Default for GridConfig {
+    fn default() -> Self {
+        Self {
+            over_sample: 1,
+            extent: 0,
+            axis: GridAxis::Horizontal,
+        }
+    }
+}
+
+/// Result of [`Grid::layout`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GridLayout {
+    /// Row-major `[x0,y0, x1,y1, ...]` grid coordinates, as returned by
+    /// [`Grid::process`].
+    pub coords: Vec<f64>,
+    /// `(column, row)` cell of each input point, in input order.
+    pub cells: Vec<(usize, usize)>,
+    /// Number of grid columns.
+    pub width: usize,
+    /// Number of grid rows.
+    pub height: usize,
+}
+
+impl GridLayout {
+    /// Row-major cell index `row * width + column` of input point `point`.
+    ///
+    /// # Panics
+    /// Panics if `point` is out of range.
+    pub fn cell_index(&self, point: usize) -> usize {
+        let (column, row) = self.cells[point];
+        row * self.width + column
+    }
+}
+
 /// Grid redistribution for 2D point sets.
+///
+/// See <https://learn.flucoma.org/reference/grid>
 pub struct Grid;
 
 impl Grid {
@@ -13,18 +102,20 @@ impl Grid {
         over_sample: usize,
         extent: usize,
         axis: usize,
-    ) -> Result<Vec<f64>, &'static str> {
+    ) -> Result<Vec<f64>, FlucomaError> {
         if rows == 0 {
-            return Err("rows must be > 0");
+            return Err(FlucomaError::InvalidParameter("rows must be > 0"));
         }
         if input.len() != rows * 2 {
-            return Err("input length must be rows * 2");
+            return Err(FlucomaError::InvalidParameter(
+                "input length must be rows * 2",
+            ));
         }
         if over_sample == 0 {
-            return Err("over_sample must be > 0");
+            return Err(FlucomaError::InvalidParameter("over_sample must be > 0"));
         }
         if axis > 1 {
-            return Err("axis must be 0 or 1");
+            return Err(FlucomaError::InvalidParameter("axis must be 0 or 1"));
         }
         let mut out = vec![0.0; rows * 2];
         let ok = grid_process(
@@ -36,10 +127,49 @@ impl Grid {
             out.as_mut_ptr(),
         );
         if !ok {
-            return Err("grid process failed");
+            return Err(FlucomaError::InvalidParameter("grid process failed"));
         }
         Ok(out)
     }
+
+    /// Redistribute 2D points to grid cells and return the cell assignment
+    /// together with the grid dimensions.
+    ///
+    /// `input` must be row-major `[x0,y0, x1,y1, ...]`.
+    ///
+    /// # Errors
+    /// Returns an error if parameters are invalid or the underlying
+    /// grid assignment fails.
+    pub fn layout(
+        input: &[f64],
+        rows: usize,
+        config: &GridConfig,
+    ) -> Result<GridLayout, FlucomaError> {
+        let coords = Self::process(
+            input,
+            rows,
+            config.over_sample,
+            config.extent,
+            config.axis as usize,
+        )?;
+        let cells: Vec<(usize, usize)> = coords
+            .chunks_exact(2)
+            .map(|xy| {
+                (
+                    xy[0].round().max(0.0) as usize,
+                    xy[1].round().max(0.0) as usize,
+                )
+            })
+            .collect();
+        let width = cells.iter().map(|c| c.0 + 1).max().unwrap_or(0);
+        let height = cells.iter().map(|c| c.1 + 1).max().unwrap_or(0);
+        Ok(GridLayout {
+            coords,
+            cells,
+            width,
+            height,
+        })
+    }
 }
 
 #[cfg(test)]
@@ -54,4 +184,16 @@ mod tests {
         // Grid coordinates are non-negative
         assert!(out.iter().all(|&v| v >= 0.0));
     }
+
+    #[test]
+    fn grid_layout_assigns_unique_cells() {
+        let input = vec![0.0, 0.0, 1.0, 1.0, 0.2, 0.8, 0.8, 0.2];
+        let layout = Grid::layout(&input, 4, &GridConfig::default()).unwrap();
+        assert_eq!(layout.cells.len(), 4);
+        assert!(layout.width * layout.height >= 4);
+        let mut indices: Vec<usize> = (0..4).map(|i| layout.cell_index(i)).collect();
+        indices.sort();
+        indices.dedup();
+        assert_eq!(indices.len(), 4);
+    }
 }
@@ -0,0 +1,152 @@
+use num_complex::Complex64 as Complex;
+
+use crate::buf_frames::num_frames;
+use crate::error::{check_len, FlucomaError};
+use crate::fft_settings::FftSettings;
+use crate::matrix::Matrix;
+use crate::progress::Progress;
+use crate::spectrogram::Spectrogram;
+use crate::stft::{Istft, Stft};
+
+// -------------------------------------------------------------------------------------------------
+
+/// Reconstruct audio from a frames × bins magnitude spectrogram by estimating
+/// its phases with the Griffin-Lim algorithm.
+///
+/// Starting from zero phases, each iteration resynthesises the signal,
+/// re-analyses it and keeps the new phases with the target magnitudes, so
+/// more iterations give a more consistent spectrogram. Frames follow
+/// [`Stft::analyze`]: `magnitudes` must have `settings.num_bins()` columns
+/// and as many rows as [`Stft::analyze`] produces for `signal_len` samples,
+/// and the output is `signal_len` samples long.
+///
+/// ```rust,no_run
+/// use flucoma_rs::fourier::{griffin_lim, FftSettings, Stft};
+///
+/// let settings = FftSettings::default();
+/// let audio = vec![0.0f64; 44100];
+/// let mut magnitudes = Stft::from_settings(&settings)
+///     .unwrap()
+///     .analyze(&audio)
+///     .magnitudes();
+/// for value in magnitudes.data_mut() {
+///     *value = value.powf(1.5); // some magnitude-only processing
+/// }
+/// let resynthesized = griffin_lim(&magnitudes, 32, audio.len(), &settings).unwrap();
+/// assert_eq!(resynthesized.len(), audio.len());
+/// ```
+///
+/// # Errors
+/// Returns an error if the settings are invalid, `magnitudes` does not
+/// have `settings.num_bins()` columns, or its row count does not match
+/// `signal_len`.
+pub fn griffin_lim(
+    magnitudes: &Matrix,
+    iterations: usize,
+    signal_len: usize,
+    settings: &FftSettings,
+) -> Result<Vec<f64>, FlucomaError> {
+    griffin_lim_with_progress(
+        magnitudes,
+        iterations,
+        signal_len,
+        settings,
+        &mut Progress::new(),
+    )
+}
+
+/// Like [`griffin_lim`], reporting progress after each iteration and
+/// stopping early if `progress` is cancelled.
+///
+/// # Errors
+/// Returns [`FlucomaError::Cancelled`] if cancelled, or as for
+/// [`griffin_lim`].
+pub fn griffin_lim_with_progress(
+    magnitudes: &Matrix,
+    iterations: usize,
+    signal_len: usize,
+    settings: &FftSettings,
+    progress: &mut Progress,
+) -> Result<Vec<f64>, FlucomaError> {
+    settings.validate()?;
+    check_len(settings.num_bins(), magnitudes.cols())?;
+    check_len(num_frames(signal_len, settings.hop_size), magnitudes.rows())?;
+    let mut stft = Stft::from_settings(settings)?;
+    let mut istft = Istft::from_settings(settings)?;
+    let mut spectrogram = Spectrogram::zeros(
+        magnitudes.rows(),
+        settings.window_size,
+        settings.fft_size,
+        settings.hop_size,
+        settings.window_type,
+    )?
+    .with_signal_len(signal_len);
+    for (bin, &magnitude) in spectrogram.data_mut().iter_mut().zip(magnitudes.data()) {
+        *bin = Complex::new(magnitude, 0.0);
+    }
+    for iteration in 0..iterations {
+        let estimate = stft.analyze(&istft.synthesize(&spectrogram));
+        let targets = magnitudes.data().iter().zip(estimate.data());
+        for (bin, (&magnitude, estimated)) in spectrogram.data_mut().iter_mut().zip(targets) {
+            let norm = estimated.norm();
+            *bin = if norm > 1e-12 {
+                estimated * (magnitude / norm)
+            } else {
+                Complex::new(magnitude, 0.0)
+            };
+        }
+        progress.report((iteration + 1) as f64 / iterations as f64)?;
+    }
+    Ok(istft.synthesize(&spectrogram))
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn griffin_lim_rejects_wrong_bin_count() {
+        let magnitudes = Matrix::from_vec(vec![0.0; 10 * 300], 10, 300).unwrap();
+        assert_eq!(
+            griffin_lim(&magnitudes, 4, 9 * 512, &FftSettings::default()).unwrap_err(),
+            FlucomaError::InvalidShape {
+                expected: 513,
+                got: 300
+            }
+        );
+        let magnitudes = Matrix::from_vec(vec![0.0; 10 * 513], 10, 513).unwrap();
+        assert!(griffin_lim(&magnitudes, 4, 44100, &FftSettings::default()).is_err());
+    }
+
+    #[test]
+    fn griffin_lim_reduces_spectral_error() {
+        let settings = FftSettings {
+            window_size: 512,
+            fft_size: 512,
+            hop_size: 128,
+            ..FftSettings::default()
+        };
+        // Not a multiple of the hop, so the output has to be padded.
+        let signal: Vec<f64> = (0..8000)
+            .map(|i| (i as f64 * 0.07).sin() + 0.5 * (i as f64 * 0.31).sin())
+            .collect();
+        let mut stft = Stft::from_settings(&settings).unwrap();
+        let target = stft.analyze(&signal).magnitudes();
+        let mut spectral_error = |audio: &[f64]| -> f64 {
+            let estimate = stft.analyze(audio).magnitudes();
+            let diff: f64 = estimate
+                .data()
+                .iter()
+                .zip(target.data())
+                .map(|(a, b)| (a - b).powi(2))
+                .sum();
+            diff.sqrt()
+        };
+        let rough = griffin_lim(&target, 0, signal.len(), &settings).unwrap();
+        let refined = griffin_lim(&target, 30, signal.len(), &settings).unwrap();
+        assert_eq!(refined.len(), signal.len());
+        assert!(spectral_error(&refined) < spectral_error(&rough));
+    }
+}
@@ -1,6 +1,12 @@
+use std::fmt;
+use std::str::FromStr;
+
 use flucoma_sys::{hpss_create, hpss_destroy, hpss_init, hpss_process_frame};
 use num_complex::Complex64;
 
+use crate::error::FlucomaError;
+use crate::param_names::{display_name, parse_name};
+
 // -------------------------------------------------------------------------------------------------
 
 /// HPSS separation mode.
@@ -8,6 +14,7 @@ use num_complex::Complex64;
 /// Controls how harmonic and percussive masks are computed.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 #[repr(isize)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum HpssMode {
     /// Binary masking: energy goes to whichever component dominates. No residual.
     #[default]
@@ -18,11 +25,33 @@ pub enum HpssMode {
     Advanced = 2,
 }
 
+const HPSS_MODE_NAMES: &[(&str, HpssMode)] = &[
+    ("classic", HpssMode::Classic),
+    ("coupled", HpssMode::Coupled),
+    ("advanced", HpssMode::Advanced),
+];
+
+impl FromStr for HpssMode {
+    type Err = FlucomaError;
+
+    /// Parse a name such as `"classic"`, ignoring case and separators.
+    fn from_str(name: &str) -> Result<Self, FlucomaError> {
+        parse_name(name, HPSS_MODE_NAMES, "unknown HPSS mode")
+    }
+}
+
+impl fmt::Display for HpssMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(display_name(self, HPSS_MODE_NAMES))
+    }
+}
+
 /// Per-frame parameters for [`Hpss::process_frame`].
 ///
 /// `h_size` and `v_size` are fixed at construction time and therefore not included here;
 /// see [`Hpss::new`].
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct HpssParams {
     /// Separation mode.
     pub mode: HpssMode,
@@ -77,6 +106,7 @@ impl Default for HpssParams {
 /// See <https://learn.flucoma.org/reference/hpss>
 pub struct Hpss {
     inner: *mut u8,
+    fft_size: usize,
     n_bins: usize,
     /// Horizontal (time) filter size, fixed at construction.
     h_size: usize,
@@ -100,25 +130,32 @@ impl Hpss {
     /// * `v_size`   - Vertical (frequency) median filter length in bins. Must be odd and ≥ 1.
     ///
     /// # Errors
-    /// Returns an error string if parameters are invalid or allocation fails.
-    pub fn new(fft_size: usize, h_size: usize, v_size: usize) -> Result<Self, &'static str> {
+    /// Returns an error if parameters are invalid or allocation fails.
+    pub fn new(fft_size: usize, h_size: usize, v_size: usize) -> Result<Self, FlucomaError> {
         if fft_size == 0 || !fft_size.is_multiple_of(2) {
-            return Err("fft_size must be > 0 and even");
+            return Err(FlucomaError::InvalidParameter(
+                "fft_size must be > 0 and even",
+            ));
         }
         if h_size == 0 || h_size.is_multiple_of(2) {
-            return Err("h_size must be odd and >= 1");
+            return Err(FlucomaError::InvalidParameter(
+                "h_size must be odd and >= 1",
+            ));
         }
         if v_size == 0 || v_size.is_multiple_of(2) {
-            return Err("v_size must be odd and >= 1");
+            return Err(FlucomaError::InvalidParameter(
+                "v_size must be odd and >= 1",
+            ));
         }
         let n_bins = fft_size / 2 + 1;
         let inner = hpss_create(fft_size as isize, h_size as isize);
         if inner.is_null() {
-            return Err("failed to create HPSS instance");
+            return Err(FlucomaError::AllocationFailed);
         }
         hpss_init(inner, n_bins as isize, h_size as isize);
         Ok(Self {
             inner,
+            fft_size,
             n_bins,
             h_size,
             v_size,
@@ -196,6 +233,14 @@ impl Hpss {
     pub fn v_size(&self) -> usize {
         self.v_size
     }
+
+    /// Delay of the separated output in frames, from the centred horizontal
+    /// median filter. Multiply by the hop and add the STFT's
+    /// [`latency_samples`](crate::fourier::Stft::latency_samples) for the
+    /// total delay in samples.
+    pub fn latency_frames(&self) -> usize {
+        (self.h_size - 1) / 2
+    }
 }
 
 impl Drop for Hpss {
@@ -204,6 +249,13 @@ impl Drop for Hpss {
     }
 }
 
+impl Clone for Hpss {
+    fn clone(&self) -> Self {
+        Self::new(self.fft_size, self.h_size, self.v_size)
+            .expect("settings were validated on construction")
+    }
+}
+
 // -------------------------------------------------------------------------------------------------
 
 #[cfg(test)]
@@ -263,4 +315,23 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn hpss_output_is_delayed_by_latency() {
+        let mut hpss = Hpss::new(512, 9, 9).unwrap();
+        let n_bins = hpss.n_bins();
+        let click = 12;
+        let energies: Vec<f64> = (0..40)
+            .map(|i| {
+                let value = if i == click { 1.0 } else { 0.0 };
+                let input = vec![Complex64::new(value, 0.0); n_bins];
+                let (h, p, r) = hpss.process_frame(&input, &HpssParams::default());
+                h.iter().chain(p).chain(r).map(|v| v.norm_sqr()).sum()
+            })
+            .collect();
+        let loudest = (0..energies.len())
+            .max_by(|&a, &b| energies[a].total_cmp(&energies[b]))
+            .unwrap();
+        assert_eq!(loudest, click + hpss.latency_frames(), "{energies:?}");
+    }
 }
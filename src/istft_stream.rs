@@ -0,0 +1,171 @@
+use std::collections::VecDeque;
+
+use crate::error::FlucomaError;
+use crate::overlap_add::OverlapAdd;
+use crate::stft::{ComplexSpectrum, Istft, WindowType};
+
+// -------------------------------------------------------------------------------------------------
+
+/// Pull-based streaming resynthesis.
+///
+/// Push [`ComplexSpectrum`] frames one hop apart with
+/// [`IstftStream::push`] and pull contiguous audio with
+/// [`IstftStream::pull`], in whatever block size the caller needs. Frames
+/// are resynthesised by [`Istft`], overlap-added and normalised by the
+/// summed squared window internally, so a spectral processing chain needs no
+/// buffer stitching of its own.
+///
+/// Pulled sample `n` is aligned with sample `n` of the first pushed frame.
+/// For frames centred on their hop position, as produced by
+/// [`Stft::analyze`](crate::fourier::Stft::analyze), call
+/// [`IstftStream::skip`] with `window_size / 2` first.
+///
+/// ```rust,no_run
+/// use flucoma_rs::fourier::{IstftStream, Stft, WindowType};
+///
+/// let mut stft = Stft::new(1024, 1024, 256, WindowType::Hann).unwrap();
+/// let mut istft = IstftStream::new(1024, 1024, 256, WindowType::Hann).unwrap();
+/// let frame = vec![0.0f64; 1024];
+/// istft.push(&stft.process_frame(&frame));
+/// let mut block = [0.0f64; 64];
+/// while istft.available() >= block.len() {
+///     istft.pull(&mut block);
+/// }
+/// ```
+pub struct IstftStream {
+    istft: Istft,
+    ola: OverlapAdd,
+    frame: Vec<f64>,
+    ready: VecDeque<f64>,
+    skip: usize,
+}
+
+impl IstftStream {
+    /// Create a streaming synthesiser. Arguments are as for [`Istft::new`].
+    ///
+    /// # Errors
+    /// Returns an error if the parameters are invalid or allocation
+    /// fails.
+    pub fn new(
+        window_size: usize,
+        fft_size: usize,
+        hop_size: usize,
+        window_type: WindowType,
+    ) -> Result<Self, FlucomaError> {
+        let istft = Istft::new(window_size, fft_size, hop_size, window_type)?;
+        let ola = OverlapAdd::new(window_size, hop_size, window_type)?;
+        Ok(Self {
+            istft,
+            ola,
+            frame: vec![0.0; window_size],
+            ready: VecDeque::with_capacity(window_size),
+            skip: 0,
+        })
+    }
+
+    /// Resynthesise one frame and queue the samples it completes.
+    ///
+    /// # Panics
+    /// Panics if `spectrum.num_bins()` does not match the synthesiser.
+    pub fn push(&mut self, spectrum: &ComplexSpectrum) {
+        self.istft.process_frame(spectrum, &mut self.frame);
+        let completed = self.ola.process_frame(&self.frame);
+        let dropped = self.skip.min(completed.len());
+        self.skip -= dropped;
+        self.ready.extend(&completed[dropped..]);
+    }
+
+    /// Fill `output` with queued samples and return how many were written,
+    /// which is less than `output.len()` if not enough are available.
+    pub fn pull(&mut self, output: &mut [f64]) -> usize {
+        let count = output.len().min(self.ready.len());
+        for (out, sample) in output.iter_mut().zip(self.ready.drain(..count)) {
+            *out = sample;
+        }
+        count
+    }
+
+    /// Number of samples ready to be pulled.
+    pub fn available(&self) -> usize {
+        self.ready.len()
+    }
+
+    /// Discard the next `samples` output samples, e.g. to compensate for
+    /// centred analysis frames.
+    pub fn skip(&mut self, samples: usize) {
+        let dropped = samples.min(self.ready.len());
+        self.ready.drain(..dropped);
+        self.skip += samples - dropped;
+    }
+
+    /// Queue the remaining overlap tail, as if the stream ended.
+    pub fn finish(&mut self) {
+        let tail = self.ola.flush();
+        let dropped = self.skip.min(tail.len());
+        self.skip -= dropped;
+        self.ready.extend(&tail[dropped..]);
+    }
+
+    /// Clear all queued and accumulated audio.
+    pub fn reset(&mut self) {
+        self.ola.reset();
+        self.ready.clear();
+        self.skip = 0;
+    }
+
+    /// Delay in samples between audio entering a streaming analysis with
+    /// the same settings and leaving this synthesiser: one full window.
+    pub fn latency_samples(&self) -> usize {
+        self.istft.window_size()
+    }
+
+    /// Hop between pushed frames in samples.
+    pub fn hop_size(&self) -> usize {
+        self.istft.hop_size()
+    }
+}
+
+impl Clone for IstftStream {
+    fn clone(&self) -> Self {
+        let mut ola = self.ola.clone();
+        ola.reset();
+        Self {
+            istft: self.istft.clone(),
+            ola,
+            frame: vec![0.0; self.frame.len()],
+            ready: VecDeque::with_capacity(self.frame.len()),
+            skip: 0,
+        }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stft::Stft;
+
+    #[test]
+    fn istft_stream_reconstructs_input() {
+        let signal: Vec<f64> = (0..8192).map(|i| (i as f64 * 0.03).sin()).collect();
+        let mut stft = Stft::new(512, 512, 128, WindowType::Hann).unwrap();
+        let mut istft = IstftStream::new(512, 512, 128, WindowType::Hann).unwrap();
+        let mut output = Vec::new();
+        let mut block = [0.0f64; 100];
+        for start in (0..=signal.len() - 512).step_by(128) {
+            istft.push(&stft.process_frame(&signal[start..start + 512]));
+            while istft.available() >= block.len() {
+                let n = istft.pull(&mut block);
+                output.extend_from_slice(&block[..n]);
+            }
+        }
+        istft.finish();
+        let n = istft.pull(&mut vec![0.0; istft.available()]);
+        assert_eq!(istft.available(), 0);
+        assert_eq!(output.len() + n, signal.len());
+        for (a, b) in signal.iter().zip(&output).skip(512).take(6000) {
+            assert!((a - b).abs() < 1e-6, "{a} != {b}");
+        }
+    }
+}
@@ -1,78 +1,253 @@
+use std::cell::Cell;
+use std::collections::{HashMap, HashSet};
+
 use flucoma_sys as sys;
 // isize and FlucomaIndex are technically identical
 // prefer using FlucomaIndex to explicitly imply FFI usage
 use flucoma_sys::FlucomaIndex;
 
+use crate::error::FlucomaError;
+
+/// K-D tree for nearest-neighbour search over labelled points.
+///
+/// Added points are kept on the Rust side, so the tree can be used to look
+/// up stored points by id with [`KDTree::get`] and [`KDTree::iter`]. The
+/// underlying tree is rebuilt lazily, once, by the first query after any
+/// number of additions.
+///
+/// See <https://learn.flucoma.org/reference/kdtree>
 pub struct KDTree {
     inner: *mut u8,
     dims: FlucomaIndex,
+    /// Ids in insertion order.
+    ids: Vec<String>,
+    /// Row-major point data, `ids.len() × dims`.
+    points: Vec<f64>,
+    /// Id -> row lookup.
+    rows: HashMap<String, usize>,
+    /// Byte length of the longest stored id, used to size kNN id buffers.
+    max_id_len: usize,
+    /// Number of leading points already in the native tree.
+    built: Cell<usize>,
 }
 
+/// Result of a [`KDTree::k_nearest`] query, ordered from nearest to farthest.
+///
+/// `distances` and `ids` always have the same length, which is at most the
+/// requested `k` and at most [`KDTree::len`].
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct KNNResult {
     pub distances: Vec<f64>,
     pub ids: Vec<String>,
 }
 
 impl KDTree {
-    pub fn new(dims: usize) -> Self {
-        Self {
-            inner: sys::kdtree_create(dims as FlucomaIndex),
+    /// Create an empty tree for points with `dims` dimensions.
+    ///
+    /// # Errors
+    /// Returns an error if `dims == 0` or allocation fails.
+    pub fn new(dims: usize) -> Result<Self, FlucomaError> {
+        if dims == 0 {
+            return Err(FlucomaError::InvalidParameter("dims must be > 0"));
+        }
+        let inner = sys::kdtree_create(dims as FlucomaIndex);
+        if inner.is_null() {
+            return Err(FlucomaError::AllocationFailed);
+        }
+        Ok(Self {
+            inner,
             dims: dims as FlucomaIndex,
+            ids: Vec::new(),
+            points: Vec::new(),
+            rows: HashMap::new(),
+            max_id_len: 0,
+            built: Cell::new(0),
+        })
+    }
+
+    /// Add a labelled point to the tree.
+    ///
+    /// # Errors
+    /// Returns an error if `data.len()` differs from the tree dimensions or if
+    /// `id` is already present in the tree.
+    pub fn add(&mut self, id: &str, data: &[f64]) -> Result<(), FlucomaError> {
+        self.check_dims(data.len())?;
+        if self.rows.contains_key(id) {
+            return Err(FlucomaError::DuplicateId(id.to_owned()));
         }
+        self.push(id, data);
+        Ok(())
     }
 
-    pub fn add(&mut self, id: &str, data: &[f64]) {
-        assert_eq!(
-            data.len() as FlucomaIndex,
-            self.dims,
-            "Input dimensions ({}) do not match KDTree dimensions ({})",
-            data.len(),
-            self.dims
-        );
-        let c_id = std::ffi::CString::new(id).expect("CString::new failed");
-        sys::kdtree_add_node(
-            self.inner,
-            c_id.as_ptr() as *const u8,
-            data.as_ptr(),
-            data.len() as FlucomaIndex,
-        );
+    /// Add many labelled points.
+    ///
+    /// `data` holds `ids.len() × dims` row-major values. Either all points are
+    /// added, or, on error, none are.
+    ///
+    /// # Errors
+    /// Returns an error if `data.len()` is not `ids.len() × dims` or if an id is
+    /// already present in the tree or repeated within `ids`.
+    pub fn add_batch<S: AsRef<str>>(
+        &mut self,
+        ids: &[S],
+        data: &[f64],
+    ) -> Result<(), FlucomaError> {
+        let dims = self.dims();
+        if data.len() != ids.len() * dims {
+            return Err(FlucomaError::InvalidShape {
+                expected: ids.len() * dims,
+                got: data.len(),
+            });
+        }
+        if ids.is_empty() {
+            return Ok(());
+        }
+
+        let mut seen = HashSet::with_capacity(ids.len());
+        for id in ids {
+            let id = id.as_ref();
+            if self.rows.contains_key(id) || !seen.insert(id) {
+                return Err(FlucomaError::DuplicateId(id.to_owned()));
+            }
+        }
+        for (id, point) in ids.iter().zip(data.chunks_exact(dims)) {
+            self.push(id.as_ref(), point);
+        }
+        Ok(())
     }
 
-    pub fn k_nearest(&self, input: &[f64], k: usize) -> KNNResult {
-        assert_eq!(
-            input.len() as FlucomaIndex,
-            self.dims,
-            "Input dimensions ({}) do not match KDTree dimensions ({})",
-            input.len(),
-            self.dims
-        );
+    /// Look up a stored point by id.
+    pub fn get(&self, id: &str) -> Option<&[f64]> {
+        self.rows.get(id).map(|&row| self.point(row))
+    }
+
+    /// Returns `true` if a point with this id is stored in the tree.
+    pub fn contains(&self, id: &str) -> bool {
+        self.rows.contains_key(id)
+    }
+
+    /// Iterate over all stored `(id, point)` pairs in insertion order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &[f64])> + '_ {
+        self.ids
+            .iter()
+            .enumerate()
+            .map(move |(row, id)| (id.as_str(), self.point(row)))
+    }
+
+    /// Find the `k` nearest points to `input`.
+    ///
+    /// When `k` exceeds the number of stored points, all points are returned.
+    /// Querying an empty tree returns an empty result.
+    ///
+    /// # Errors
+    /// Returns an error if `k == 0` or `input.len()` differs from the tree
+    /// dimensions.
+    pub fn k_nearest(&self, input: &[f64], k: usize) -> Result<KNNResult, FlucomaError> {
+        self.check_dims(input.len())?;
+        if k == 0 {
+            return Err(FlucomaError::InvalidParameter("k must be > 0"));
+        }
+        let k = k.min(self.len());
+        if k == 0 {
+            return Ok(KNNResult {
+                distances: Vec::new(),
+                ids: Vec::new(),
+            });
+        }
+        self.sync_tree();
+
+        // Ids are copied into an owned buffer on the C++ side, so the result never
+        // references memory owned by the tree.
         let mut distances = vec![0.0; k];
-        let mut id_ptrs = vec![std::ptr::null::<u8>(); k];
+        let mut id_lens = vec![0 as FlucomaIndex; k];
+        let mut id_bytes = vec![0u8; k * self.max_id_len];
 
-        sys::kdtree_k_nearest(
+        let count = sys::kdtree_k_nearest(
             self.inner,
             input.as_ptr(),
             input.len() as FlucomaIndex,
             k as FlucomaIndex,
             0.0,
             distances.as_mut_ptr(),
-            id_ptrs.as_mut_ptr(),
-        );
+            id_lens.as_mut_ptr(),
+            id_bytes.as_mut_ptr(),
+            id_bytes.len() as FlucomaIndex,
+        ) as usize;
+
+        distances.truncate(count);
+        let mut ids = Vec::with_capacity(count);
+        let mut offset = 0;
+        for &len in &id_lens[..count] {
+            let end = offset + len as usize;
+            ids.push(String::from_utf8_lossy(&id_bytes[offset..end]).into_owned());
+            offset = end;
+        }
+
+        Ok(KNNResult { distances, ids })
+    }
+
+    /// Number of points stored in the tree.
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    /// Returns `true` if the tree holds no points.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Dimensionality of the stored points.
+    pub fn dims(&self) -> usize {
+        self.dims as usize
+    }
+
+    fn push(&mut self, id: &str, data: &[f64]) {
+        self.max_id_len = self.max_id_len.max(id.len());
+        self.rows.insert(id.to_owned(), self.ids.len());
+        self.ids.push(id.to_owned());
+        self.points.extend_from_slice(data);
+    }
 
-        let ids: Vec<String> = id_ptrs
-            .into_iter()
-            .take_while(|&p| !p.is_null())
-            .map(|p| unsafe {
-                std::ffi::CStr::from_ptr(p as *const std::os::raw::c_char)
-                    .to_string_lossy()
-                    .into_owned()
-            })
-            .collect();
+    /// Add the points stored since the last query to the native tree, which
+    /// rebuilds it once for all of them.
+    fn sync_tree(&self) {
+        let built = self.built.get();
+        if built == self.len() {
+            return;
+        }
+        let pending = &self.ids[built..];
+        let mut id_bytes = Vec::with_capacity(pending.iter().map(String::len).sum());
+        let mut id_lens = Vec::with_capacity(pending.len());
+        for id in pending {
+            id_bytes.extend_from_slice(id.as_bytes());
+            id_lens.push(id.len() as FlucomaIndex);
+        }
+        let added = sys::kdtree_add_nodes(
+            self.inner,
+            id_bytes.as_ptr(),
+            id_lens.as_ptr(),
+            pending.len() as FlucomaIndex,
+            self.points[built * self.dims()..].as_ptr(),
+            self.dims,
+        );
+        // Ids were checked for duplicates when they were added.
+        assert!(added, "flucoma-core rejected unique ids");
+        self.built.set(self.len());
+    }
 
-        // Shrink distances to match actual returned IDs count
-        distances.truncate(ids.len());
+    fn point(&self, row: usize) -> &[f64] {
+        let dims = self.dims as usize;
+        &self.points[row * dims..(row + 1) * dims]
+    }
 
-        KNNResult { distances, ids }
+    fn check_dims(&self, len: usize) -> Result<(), FlucomaError> {
+        if len as FlucomaIndex != self.dims {
+            return Err(FlucomaError::InvalidShape {
+                expected: self.dims as usize,
+                got: len,
+            });
+        }
+        Ok(())
     }
 }
 
@@ -91,22 +266,112 @@ mod tests {
 
     #[test]
     fn kdtree_add_and_search() {
-        let mut tree = KDTree::new(2);
-        tree.add("origin", &[0.0, 0.0]);
-        tree.add("right", &[10.0, 0.0]);
-        tree.add("up", &[0.0, 10.0]);
-        tree.add("diagonal", &[7.0, 7.0]);
+        let mut tree = KDTree::new(2).unwrap();
+        tree.add("origin", &[0.0, 0.0]).unwrap();
+        tree.add("right", &[10.0, 0.0]).unwrap();
+        tree.add("up", &[0.0, 10.0]).unwrap();
+        tree.add("diagonal", &[7.0, 7.0]).unwrap();
 
         let target = [1.0, 1.0];
-        let result = tree.k_nearest(&target, 2);
+        let result = tree.k_nearest(&target, 2).unwrap();
         assert_eq!(result.ids.len(), 2);
         assert_eq!(result.ids[0], "origin");
 
         let target2 = [8.0, 2.0];
-        let result2 = tree.k_nearest(&target2, 1);
+        let result2 = tree.k_nearest(&target2, 1).unwrap();
         assert_eq!(result2.ids.len(), 1);
         // [8.0, 2.0] is distance sqrt((8-10)^2 + (2-0)^2) = sqrt(4+4) = sqrt(8) to "right"
         // [8.0, 2.0] is distance sqrt((8-7)^2 + (2-7)^2) = sqrt(1+25) = sqrt(26) to "diagonal"
         assert_eq!(result2.ids[0], "right");
+
+        // Additions are only built into the tree by the next query.
+        tree.add("near", &[1.1, 1.1]).unwrap();
+        tree.add("far", &[100.0, 100.0]).unwrap();
+        assert_eq!(tree.built.get(), 4);
+        assert_eq!(tree.k_nearest(&target, 1).unwrap().ids, vec!["near"]);
+        assert_eq!(tree.built.get(), 6);
+    }
+
+    #[test]
+    fn kdtree_k_larger_than_size_returns_all_points() {
+        let mut tree = KDTree::new(1).unwrap();
+        tree.add("a", &[0.0]).unwrap();
+        tree.add("b", &[1.0]).unwrap();
+        let result = tree.k_nearest(&[0.2], 10).unwrap();
+        assert_eq!(result.ids.len(), 2);
+        assert_eq!(result.distances.len(), 2);
+        assert_eq!(result.ids[0], "a");
+    }
+
+    #[test]
+    fn kdtree_empty_tree_returns_empty_result() {
+        let tree = KDTree::new(3).unwrap();
+        assert!(tree.is_empty());
+        let result = tree.k_nearest(&[0.0, 0.0, 0.0], 4).unwrap();
+        assert!(result.ids.is_empty());
+        assert!(result.distances.is_empty());
+    }
+
+    #[test]
+    fn kdtree_rejects_invalid_input() {
+        assert!(KDTree::new(0).is_err());
+        let mut tree = KDTree::new(2).unwrap();
+        assert_eq!(
+            tree.add("short", &[1.0]).unwrap_err(),
+            FlucomaError::InvalidShape {
+                expected: 2,
+                got: 1
+            }
+        );
+        tree.add("a", &[0.0, 0.0]).unwrap();
+        assert_eq!(
+            tree.add("a", &[1.0, 1.0]).unwrap_err(),
+            FlucomaError::DuplicateId("a".to_owned())
+        );
+        assert!(tree.k_nearest(&[0.0, 0.0], 0).is_err());
+    }
+
+    #[test]
+    fn kdtree_add_batch_is_all_or_nothing() {
+        let mut tree = KDTree::new(2).unwrap();
+        tree.add_batch(&["a", "b", "c"], &[0.0, 0.0, 1.0, 1.0, 5.0, 5.0])
+            .unwrap();
+        assert_eq!(tree.len(), 3);
+        assert_eq!(tree.get("c"), Some(&[5.0, 5.0][..]));
+        assert_eq!(tree.k_nearest(&[4.0, 4.0], 1).unwrap().ids, vec!["c"]);
+
+        assert_eq!(
+            tree.add_batch(&["d", "d"], &[0.0; 4]).unwrap_err(),
+            FlucomaError::DuplicateId("d".to_owned())
+        );
+        assert!(tree.add_batch(&["e", "a"], &[0.0; 4]).is_err());
+        assert!(tree.add_batch(&["e"], &[0.0; 3]).is_err());
+        assert_eq!(tree.len(), 3);
+        assert!(!tree.contains("e"));
+    }
+
+    #[test]
+    fn kdtree_result_ids_outlive_tree_mutation() {
+        let mut tree = KDTree::new(1).unwrap();
+        tree.add("short", &[0.0]).unwrap();
+        tree.add("a-much-longer-identifier", &[1.0]).unwrap();
+        let result = tree.k_nearest(&[0.9], 2).unwrap();
+        for i in 0..100 {
+            tree.add(&format!("extra-{i}"), &[i as f64 + 10.0]).unwrap();
+        }
+        drop(tree);
+        assert_eq!(result.ids, vec!["a-much-longer-identifier", "short"]);
+    }
+
+    #[test]
+    fn kdtree_get_and_iter_return_stored_points() {
+        let mut tree = KDTree::new(2).unwrap();
+        tree.add("a", &[1.0, 2.0]).unwrap();
+        tree.add("b", &[3.0, 4.0]).unwrap();
+        assert_eq!(tree.get("b"), Some(&[3.0, 4.0][..]));
+        assert_eq!(tree.get("missing"), None);
+        assert!(tree.contains("a"));
+        let pairs: Vec<(&str, &[f64])> = tree.iter().collect();
+        assert_eq!(pairs, vec![("a", &[1.0, 2.0][..]), ("b", &[3.0, 4.0][..])]);
     }
 }
@@ -1,21 +1,53 @@
+use std::fmt;
+use std::str::FromStr;
+
 use flucoma_sys::{
     kmeans_create, kmeans_destroy, kmeans_fit, skmeans_create, skmeans_destroy, skmeans_encode,
     skmeans_fit, FlucomaIndex,
 };
 
-#[derive(Debug, Clone, Copy)]
+use crate::error::{check_len, FlucomaError};
+use crate::param_names::{display_name, parse_name};
+use crate::seed::resolve_seed;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(isize)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum KMeansInit {
     RandomPartition = 0,
     RandomPoint = 1,
     RandomSampling = 2,
 }
 
+const KMEANS_INIT_NAMES: &[(&str, KMeansInit)] = &[
+    ("random-partition", KMeansInit::RandomPartition),
+    ("random-point", KMeansInit::RandomPoint),
+    ("random-sampling", KMeansInit::RandomSampling),
+];
+
+impl FromStr for KMeansInit {
+    type Err = FlucomaError;
+
+    /// Parse a name such as `"random-point"`, ignoring case and separators.
+    fn from_str(name: &str) -> Result<Self, FlucomaError> {
+        parse_name(name, KMEANS_INIT_NAMES, "unknown initialisation")
+    }
+}
+
+impl fmt::Display for KMeansInit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(display_name(self, KMEANS_INIT_NAMES))
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct KMeansConfig {
     pub k: usize,
     pub max_iter: usize,
     pub init: KMeansInit,
+    /// Seed for the initialisation; -1 for a random seed, or the
+    /// [global seed](crate::set_global_seed) if one is set.
     pub seed: isize,
 }
 
@@ -31,6 +63,7 @@ impl Default for KMeansConfig {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct KMeansResult {
     pub means: Vec<f64>,
     pub assignments: Vec<usize>,
@@ -40,6 +73,9 @@ pub struct KMeansResult {
 
 pub struct KMeans {
     inner: *mut u8,
+    /// Fitted `k × dims` cluster means, row-major.
+    means: Vec<f64>,
+    dims: usize,
 }
 
 pub struct SKMeans {
@@ -53,12 +89,32 @@ unsafe impl Send for KMeans {}
 unsafe impl Send for SKMeans {}
 
 impl KMeans {
-    pub fn new() -> Result<Self, &'static str> {
+    pub fn new() -> Result<Self, FlucomaError> {
         let inner = kmeans_create();
         if inner.is_null() {
-            return Err("failed to create KMeans instance");
+            return Err(FlucomaError::AllocationFailed);
         }
-        Ok(Self { inner })
+        Ok(Self {
+            inner,
+            means: Vec::new(),
+            dims: 0,
+        })
+    }
+
+    /// Create a model from a previous [`KMeans::fit`] result, e.g. one
+    /// loaded from disk, ready for [`KMeans::predict_point`].
+    ///
+    /// # Errors
+    /// Returns an error if the result's shape is inconsistent.
+    pub fn from_result(result: &KMeansResult) -> Result<Self, FlucomaError> {
+        if result.k == 0 || result.dims == 0 {
+            return Err(FlucomaError::InvalidParameter("k and dims must be > 0"));
+        }
+        check_len(result.k * result.dims, result.means.len())?;
+        let mut kmeans = Self::new()?;
+        kmeans.means.clone_from(&result.means);
+        kmeans.dims = result.dims;
+        Ok(kmeans)
     }
 
     pub fn fit(
@@ -67,7 +123,7 @@ impl KMeans {
         rows: usize,
         dims: usize,
         config: KMeansConfig,
-    ) -> Result<KMeansResult, &'static str> {
+    ) -> Result<KMeansResult, FlucomaError> {
         validate_kmeans_input(data, rows, dims, config)?;
         let k = config.k;
         let mut means = vec![0.0; k * dims];
@@ -80,10 +136,12 @@ impl KMeans {
             k as FlucomaIndex,
             config.max_iter as FlucomaIndex,
             config.init as FlucomaIndex,
-            config.seed as FlucomaIndex,
+            resolve_seed(config.seed),
             means.as_mut_ptr(),
             assignments.as_mut_ptr(),
         );
+        self.means.clone_from(&means);
+        self.dims = dims;
         Ok(KMeansResult {
             means,
             assignments: assignments.into_iter().map(|x| x as usize).collect(),
@@ -91,6 +149,40 @@ impl KMeans {
             dims,
         })
     }
+
+    /// Find the cluster whose mean is nearest to a single point, returning
+    /// `(cluster, euclidean_distance)`.
+    ///
+    /// Does not allocate, so it can run per audio block, e.g. to trigger the
+    /// corpus cluster nearest the current input.
+    ///
+    /// # Errors
+    /// Returns an error if the model is not fitted or `point.len() != dims`.
+    pub fn predict_point(&self, point: &[f64]) -> Result<(usize, f64), FlucomaError> {
+        if !self.is_fitted() {
+            return Err(FlucomaError::NotFitted);
+        }
+        check_len(self.dims, point.len())?;
+        let (cluster, squared) = self
+            .means
+            .chunks_exact(self.dims)
+            .map(|mean| {
+                mean.iter()
+                    .zip(point)
+                    .map(|(m, x)| (x - m) * (x - m))
+                    .sum::<f64>()
+            })
+            .enumerate()
+            .fold(
+                (0, f64::INFINITY),
+                |best, (i, d)| if d < best.1 { (i, d) } else { best },
+            );
+        Ok((cluster, squared.sqrt()))
+    }
+
+    pub fn is_fitted(&self) -> bool {
+        !self.means.is_empty()
+    }
 }
 
 impl Drop for KMeans {
@@ -100,10 +192,10 @@ impl Drop for KMeans {
 }
 
 impl SKMeans {
-    pub fn new() -> Result<Self, &'static str> {
+    pub fn new() -> Result<Self, FlucomaError> {
         let inner = skmeans_create();
         if inner.is_null() {
-            return Err("failed to create SKMeans instance");
+            return Err(FlucomaError::AllocationFailed);
         }
         Ok(Self { inner, k: 0 })
     }
@@ -114,7 +206,7 @@ impl SKMeans {
         rows: usize,
         dims: usize,
         config: KMeansConfig,
-    ) -> Result<KMeansResult, &'static str> {
+    ) -> Result<KMeansResult, FlucomaError> {
         validate_kmeans_input(data, rows, dims, config)?;
         let k = config.k;
         let mut means = vec![0.0; k * dims];
@@ -127,7 +219,7 @@ impl SKMeans {
             k as FlucomaIndex,
             config.max_iter as FlucomaIndex,
             config.init as FlucomaIndex,
-            config.seed as FlucomaIndex,
+            resolve_seed(config.seed),
             means.as_mut_ptr(),
             assignments.as_mut_ptr(),
         );
@@ -146,15 +238,17 @@ impl SKMeans {
         rows: usize,
         dims: usize,
         alpha: f64,
-    ) -> Result<Vec<f64>, &'static str> {
+    ) -> Result<Vec<f64>, FlucomaError> {
         if self.k == 0 {
-            return Err("SKMeans is not fitted");
+            return Err(FlucomaError::NotFitted);
         }
         if rows == 0 || dims == 0 {
-            return Err("rows and dims must be > 0");
+            return Err(FlucomaError::InvalidParameter("rows and dims must be > 0"));
         }
         if data.len() != rows * dims {
-            return Err("data length does not match rows * dims");
+            return Err(FlucomaError::InvalidParameter(
+                "data length does not match rows * dims",
+            ));
         }
         let mut out = vec![0.0; rows * self.k];
         skmeans_encode(
@@ -181,21 +275,23 @@ fn validate_kmeans_input(
     rows: usize,
     dims: usize,
     config: KMeansConfig,
-) -> Result<(), &'static str> {
+) -> Result<(), FlucomaError> {
     if rows == 0 || dims == 0 {
-        return Err("rows and dims must be > 0");
+        return Err(FlucomaError::InvalidParameter("rows and dims must be > 0"));
     }
     if data.len() != rows * dims {
-        return Err("data length does not match rows * dims");
+        return Err(FlucomaError::InvalidParameter(
+            "data length does not match rows * dims",
+        ));
     }
     if config.k == 0 {
-        return Err("k must be > 0");
+        return Err(FlucomaError::InvalidParameter("k must be > 0"));
     }
     if config.k > rows {
-        return Err("k must be <= rows");
+        return Err(FlucomaError::InvalidParameter("k must be <= rows"));
     }
     if config.max_iter == 0 {
-        return Err("max_iter must be > 0");
+        return Err(FlucomaError::InvalidParameter("max_iter must be > 0"));
     }
     Ok(())
 }
@@ -204,6 +300,26 @@ fn validate_kmeans_input(
 mod tests {
     use super::*;
 
+    #[test]
+    fn predict_point_finds_nearest_mean() {
+        let result = KMeansResult {
+            means: vec![0.0, 0.0, 10.0, 10.0],
+            assignments: Vec::new(),
+            k: 2,
+            dims: 2,
+        };
+        let kmeans = KMeans::from_result(&result).unwrap();
+        assert_eq!(kmeans.predict_point(&[9.0, 10.0]).unwrap(), (1, 1.0));
+        assert_eq!(kmeans.predict_point(&[3.0, 4.0]).unwrap(), (0, 5.0));
+        assert_eq!(
+            kmeans.predict_point(&[1.0]).unwrap_err(),
+            FlucomaError::InvalidShape {
+                expected: 2,
+                got: 1
+            }
+        );
+    }
+
     #[test]
     fn kmeans_fit_basic() {
         let data = vec![
@@ -45,103 +45,301 @@
 //!
 //! println!("Onset value: {:.4}", value);
 //!```
+//!
+//! ## Latency
+//!
+//! Streaming processors report their algorithmic delay so outputs can be
+//! aligned with the input timeline. Processors fed audio at a hop they know
+//! report it in samples with `latency_samples()`; processors fed frames at a
+//! hop chosen by the caller report it in frames with `latency_frames()`.
 
+mod adaptive_threshold;
+mod adaptive_whitening;
 mod amp_feature;
+mod amp_gate;
 mod amp_seg;
+mod analysis_pipeline;
+mod analysis_report;
+mod analysis_worker;
+mod async_offline;
+mod arrow_export;
 mod audio_transport;
 mod bufstats;
+mod buf_amp_gate;
+mod buf_audio_transport;
+mod buf_chroma;
+mod buf_compose;
+mod buf_frames;
+mod buf_loudness;
+mod buf_mel_bands;
+mod buf_mfcc;
+mod buf_nmf;
+mod buf_novelty_seg;
+mod buf_select;
+mod buf_sines;
+mod buf_spectral_shape;
+mod buf_transient_seg;
+mod buf_transients;
+mod chroma;
+mod column_mask;
+mod corpus_store;
+mod dataset;
 mod dataset_query;
+mod dct;
+mod envelope;
+mod error;
+mod evaluate;
+mod feature_flags;
+mod feature_matrix;
+mod feature_stream;
+mod fft_settings;
+mod filter_bank;
+mod frame_analyzer;
 mod grid;
+mod griffin_lim;
 mod hpss;
+mod istft_stream;
 mod kdtree;
 mod kmeans;
+mod linalg;
 mod loudness;
+mod loudness_meter;
+mod manifest;
 mod matrix;
 mod mel_bands;
+mod mfcc;
 mod mds;
+mod median_filter;
 mod multi_stats;
+mod multichannel;
 mod nmf;
 mod nmf_filter;
 mod nmf_morph;
 mod normalize;
+mod npy_export;
+mod novelty_audio_seg;
 mod novelty_feature;
 mod novelty_seg;
 mod onset;
 mod onset_seg;
+mod onset_stream;
+mod overlap_add;
+mod param_names;
+mod parallel_map;
 mod pca;
+mod phase_vocoder;
+mod pitch;
+mod progress;
+mod quality;
+mod replay;
 mod robust_scale;
 mod running_stats;
+mod sample_f32;
+mod seed;
 mod sine;
 mod standardize;
 mod sine_extraction;
+mod slice;
+mod slice_context;
+mod spectral_shape;
+mod spectrogram;
 mod stft;
+mod transform;
 mod transient_extraction;
 mod tempo;
 mod transient_seg;
+mod transient_stream;
+mod true_peak;
+
+pub use error::FlucomaError;
+pub use progress::{CancellationToken, Progress};
+pub use seed::{global_seed, set_global_seed};
+pub use manifest::{Manifest, ManifestEntry};
 
 /// Raw data processing and helper types.
 pub mod data {
-    pub use super::bufstats::{BufStats, BufStatsConfig};
+    pub use super::buf_select::{
+        channel_major_to_matrix, flatten_features, flatten_frames, matrix_to_channel_major, select,
+        select_range,
+    };
+    pub use super::bufstats::{BufStats, BufStatsConfig, DataOrientation};
+    pub use super::column_mask::ColumnMasked;
+    pub use super::dataset::{DataSet, MergePolicy};
     pub use super::dataset_query::{
-        ComparisonOp, DataSetQuery, DataSetQueryResult, QueryCondition,
+        Aggregate, ComparisonOp, DataSetQuery, DataSetQueryResult, GroupSummary, IdPattern,
+        Predicate,
     };
-    pub use super::grid::Grid;
+    pub use super::feature_matrix::FeatureMatrix;
+    pub use super::feature_stream::{FeatureStreamReader, FeatureStreamWriter};
+    pub use super::grid::{Grid, GridAxis, GridConfig, GridLayout};
     pub use super::kmeans::{KMeans, KMeansConfig, KMeansInit, KMeansResult, SKMeans};
     pub use super::matrix::Matrix;
-    pub use super::mds::{Mds, MdsDistance};
+    pub use super::mds::{Mds, MdsDistance, MdsProjection};
+    pub use super::median_filter::MedianFilter;
     pub use super::multi_stats::{
         MultiStats, MultiStatsConfig, MultiStatsOutput, MultiStatsValues,
     };
-    pub use super::normalize::Normalize;
+    pub use super::normalize::{Normalize, OutOfRangePolicy};
     pub use super::pca::{Pca, PcaConfig, PcaScaler};
     pub use super::robust_scale::RobustScale;
-    pub use super::running_stats::RunningStats;
+    pub use super::running_stats::{RunningStats, RunningStatsState};
     pub use super::standardize::Standardize;
+    pub use super::transform::{Transform, TransformPipeline};
+
+    /// Model evaluation metrics.
+    pub mod evaluate {
+        pub use crate::evaluate::{
+            align_by_id, classification, regression, ClassificationMetrics, RegressionMetrics,
+        };
+    }
+}
+
+/// Buffer composition: mixing, gain, fades, concatenation and channel
+/// (de)interleaving.
+pub mod buffer {
+    pub use super::buf_compose::{
+        apply_gain, compose, concatenate, deinterleave, extract_channel, fade_in, fade_out,
+        interleave, mix, with_gain,
+    };
 }
 
 /// Fast Fourier transform types and functions.
 pub mod fourier {
-    pub use super::stft::{ComplexSpectrum, Istft, Stft, WindowType};
+    pub use super::dct::Dct;
+    pub use super::fft_settings::FftSettings;
+    pub use super::griffin_lim::{griffin_lim, griffin_lim_with_progress};
+    pub use super::istft_stream::IstftStream;
+    pub use super::overlap_add::OverlapAdd;
+    pub use super::spectrogram::Spectrogram;
+    pub use super::stft::{
+        ComplexSpectrum, FixedIstft, FixedStft, Istft, IstftBuilder, Stft, StftBuilder, WindowType,
+    };
     pub use num_complex::Complex64 as Complex;
 }
 
 /// Audio feature extraction.
 pub mod analyzation {
+    pub use super::adaptive_whitening::AdaptiveWhitening;
     pub use super::amp_feature::AmpFeature;
-    pub use super::loudness::Loudness;
-    pub use super::mel_bands::MelBands;
+    pub use super::buf_chroma::{BufChroma, BufChromaConfig};
+    pub use super::buf_loudness::{BufLoudness, BufLoudnessConfig};
+    pub use super::buf_mel_bands::{BufMelBands, BufMelBandsConfig};
+    pub use super::buf_mfcc::{BufMfcc, BufMfccConfig};
+    pub use super::buf_spectral_shape::{BufSpectralShape, BufSpectralShapeConfig};
+    pub use super::chroma::{Chroma, ChromaNormalization};
+    pub use super::envelope::{Envelope, EnvelopeConfig};
+    pub use super::filter_bank::{FilterBank, FilterScale};
+    pub use super::frame_analyzer::FrameAnalyzer;
+    pub use super::loudness::{Loudness, LoudnessParams, LoudnessState};
+    pub use super::loudness_meter::{normalization_gain, LoudnessMeter, NormalizationGain};
+    pub use super::mel_bands::{MelBands, MelBandsBuilder, MelBandsParams};
+    pub use super::mfcc::Mfcc;
+    pub use super::multichannel::MultiChannel;
     pub use super::novelty_feature::Novelty;
-    pub use super::onset::{Onset, OnsetFunction};
+    pub use super::onset::{Onset, OnsetFunction, OnsetParams};
+    pub use super::pitch::{Pitch, PitchParams, PitchResult};
+    pub use super::quality::FrameQuality;
     pub use super::sine::{Sine, SortBy};
+    pub use super::spectral_shape::{SpectralShape, SpectralShapeParams, SpectralShapeResult};
     pub use super::tempo::{
         TempoAlternative, TempoConfig, TempoEstimate, TempoEstimator, TempoOnsetMetrics,
     };
+    pub use super::true_peak::TruePeakMeter;
+}
+
+/// Declarative chains of analysis stages.
+pub mod pipeline {
+    pub use super::analysis_pipeline::{Pipeline, PipelineBuilder, StageFn};
+}
+
+/// One-call analysis reports.
+pub mod report {
+    pub use super::analysis_report::{analyze, AnalysisReport, SpectralSummary, SummaryStats};
 }
 
 /// Spectral transformation.
 pub mod transformation {
     pub use super::audio_transport::AudioTransport;
+    pub use super::buf_audio_transport::{BufAudioTransport, BufAudioTransportConfig};
     pub use super::nmf_filter::{NMFFilter, NmfResult};
     pub use super::nmf_morph::NMFMorph;
 }
 
 /// Audio source separation and decomposition.
 pub mod decomposition {
+    pub use super::buf_nmf::{BufNmf, BufNmfConfig, BufNmfResult};
+    pub use super::buf_sines::{BufSines, BufSinesConfig, BufSinesResult};
+    pub use super::buf_transients::{BufTransients, BufTransientsConfig, BufTransientsResult};
     pub use super::hpss::{Hpss, HpssMode, HpssParams};
     pub use super::nmf::Nmf;
     pub use super::nmf_filter::NmfResult;
+    pub use super::phase_vocoder::PhaseVocoder;
     pub use super::sine_extraction::{SineExtraction, SineExtractionParams};
     pub use super::transient_extraction::TransientExtraction;
 }
 
 /// Onset segmentation.
 pub mod segmentation {
-    pub use super::amp_seg::AmpSlice;
-    pub use super::novelty_seg::NoveltySlice;
-    pub use super::onset_seg::OnsetSlice;
+    pub use super::adaptive_threshold::{AdaptiveThreshold, ThresholdMode};
+    pub use super::amp_gate::AmpGate;
+    pub use super::amp_seg::{AmpSlice, AmpSliceConfig};
+    pub use super::buf_amp_gate::{BufAmpGate, BufAmpGateConfig};
+    pub use super::buf_novelty_seg::{BufNoveltySlice, BufNoveltySliceConfig};
+    pub use super::buf_transient_seg::{BufTransientSlice, BufTransientSliceConfig};
+    pub use super::novelty_audio_seg::{
+        NoveltyAlgorithm, NoveltyAudioSlice, NoveltyAudioSliceConfig,
+    };
+    pub use super::novelty_seg::{NoveltySlice, NoveltySliceState};
+    pub use super::onset_seg::{OnsetSlice, OnsetSliceParams};
+    pub use super::onset_stream::{
+        OnsetEvent, OnsetSliceStream, OnsetSliceStreamConfig, OnsetSliceStreamState,
+    };
+    pub use super::slice::{
+        backtrack_onsets, clamp_slices, merge_short_slices, snap_slices_to_zero_crossings,
+        split_long_slices, Slice, SlicePoint,
+    };
+    pub use super::slice_context::{ContextTaper, SliceContext};
     pub use super::transient_seg::TransientSlice;
+    pub use super::transient_stream::{
+        TransientSliceStream, TransientSliceStreamConfig, TransientSliceStreamState,
+    };
+}
+
+/// Persistent slice and descriptor storage.
+pub mod corpus {
+    pub use super::corpus_store::{Corpus, CorpusSlice};
+}
+
+/// Export of data sets and matrices to external formats.
+pub mod export {
+    #[cfg(feature = "arrow")]
+    pub use super::arrow_export::{dataset_to_record_batch, matrix_to_record_batch};
+    pub use super::arrow_export::write_parquet;
+    pub use super::npy_export::{write_npy, write_npz};
+}
+
+/// Multi-core analysis of many files or slices; requires the `parallel`
+/// feature.
+pub mod parallel {
+    pub use super::parallel_map::{
+        analyze_to_dataset, analyze_to_dataset_with_progress, map_parallel,
+        map_parallel_with_progress,
+    };
+}
+
+/// Off-thread analysis: a background worker thread, and awaitable jobs with
+/// the `tokio` feature, which also adds `process_async` to the `Buf*`
+/// processors.
+pub mod asynchronous {
+    pub use super::analysis_worker::{AnalysisInput, AnalysisOutput, AnalysisWorker};
+    pub use super::async_offline::run_blocking;
+}
+
+/// Runtime queries for optional cargo features.
+pub mod features {
+    pub use super::feature_flags::{enabled, is_enabled, require};
 }
 
 pub mod search {
-    pub use super::kdtree::KDTree;
+    pub use super::kdtree::{KDTree, KNNResult};
 }
@@ -0,0 +1,218 @@
+//! Small dense linear-algebra helpers for the Rust-side parts of the data
+//! algorithms. Matrices are row-major `Vec<f64>`.
+
+// -------------------------------------------------------------------------------------------------
+
+/// Eigen-decomposition of a symmetric `n × n` matrix using cyclic Jacobi
+/// rotations.
+///
+/// Returns `(values, vectors)` sorted by descending eigenvalue, where column
+/// `k` of the row-major `vectors` is the eigenvector for `values[k]`.
+pub(crate) fn symmetric_eigen(a: &[f64], n: usize) -> (Vec<f64>, Vec<f64>) {
+    debug_assert_eq!(a.len(), n * n);
+    let mut a = a.to_vec();
+    let mut v = identity(n);
+    jacobi_sweeps(&mut a, &mut v, n);
+
+    let mut order: Vec<usize> = (0..n).collect();
+    order.sort_by(|&i, &j| a[j * n + j].total_cmp(&a[i * n + i]));
+    let values = order.iter().map(|&i| a[i * n + i]).collect();
+    let mut vectors = vec![0.0; n * n];
+    for (col, &src) in order.iter().enumerate() {
+        for row in 0..n {
+            vectors[row * n + col] = v[row * n + src];
+        }
+    }
+    (values, vectors)
+}
+
+/// Most cyclic Jacobi sweeps run by [`symmetric_eigen`].
+const MAX_SWEEPS: usize = 100;
+
+/// Diagonalise the symmetric `a` in place with cyclic Jacobi rotations,
+/// accumulating them into `v`, and return the number of sweeps run.
+///
+/// Both tolerances are relative to the matrix, so the number of sweeps does
+/// not depend on the scale of the data.
+fn jacobi_sweeps(a: &mut [f64], v: &mut [f64], n: usize) -> usize {
+    // The Frobenius norm is invariant under the rotations.
+    let norm2: f64 = a.iter().map(|x| x * x).sum();
+    for sweep in 0..MAX_SWEEPS {
+        let off: f64 = (0..n)
+            .flat_map(|p| (p + 1..n).map(move |q| (p, q)))
+            .map(|(p, q)| a[p * n + q] * a[p * n + q])
+            .sum();
+        if off <= f64::EPSILON * f64::EPSILON * norm2 {
+            return sweep;
+        }
+        for p in 0..n {
+            for q in p + 1..n {
+                let apq = a[p * n + q];
+                if apq.abs() <= f64::EPSILON * (a[p * n + p] * a[q * n + q]).abs().sqrt() {
+                    // Negligible next to the diagonal: drop it rather than
+                    // rotating by a rounding-level angle.
+                    a[p * n + q] = 0.0;
+                    a[q * n + p] = 0.0;
+                    continue;
+                }
+                let theta = (a[q * n + q] - a[p * n + p]) / (2.0 * apq);
+                let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+                let c = 1.0 / (t * t + 1.0).sqrt();
+                let s = t * c;
+                for k in 0..n {
+                    let akp = a[k * n + p];
+                    let akq = a[k * n + q];
+                    a[k * n + p] = c * akp - s * akq;
+                    a[k * n + q] = s * akp + c * akq;
+                }
+                for k in 0..n {
+                    let apk = a[p * n + k];
+                    let aqk = a[q * n + k];
+                    a[p * n + k] = c * apk - s * aqk;
+                    a[q * n + k] = s * apk + c * aqk;
+                }
+                for k in 0..n {
+                    let vkp = v[k * n + p];
+                    let vkq = v[k * n + q];
+                    v[k * n + p] = c * vkp - s * vkq;
+                    v[k * n + q] = s * vkp + c * vkq;
+                }
+            }
+        }
+    }
+    MAX_SWEEPS
+}
+
+/// Orthogonal `dims × dims` matrix `R` minimising `‖X R − Y‖` for centred
+/// row-major `rows × dims` point sets `x` and `y` (orthogonal Procrustes).
+pub(crate) fn procrustes_rotation(x: &[f64], y: &[f64], dims: usize) -> Vec<f64> {
+    // M = Xᵀ Y = U Σ Vᵀ, R = U Vᵀ. The SVD is derived from the eigen-
+    // decomposition of MᵀM = V Σ² Vᵀ, with U = M V Σ⁻¹.
+    let mut m = vec![0.0; dims * dims];
+    for (xr, yr) in x.chunks_exact(dims).zip(y.chunks_exact(dims)) {
+        for i in 0..dims {
+            for j in 0..dims {
+                m[i * dims + j] += xr[i] * yr[j];
+            }
+        }
+    }
+    let mut mtm = vec![0.0; dims * dims];
+    for i in 0..dims {
+        for j in 0..dims {
+            mtm[i * dims + j] = (0..dims).map(|k| m[k * dims + i] * m[k * dims + j]).sum();
+        }
+    }
+    let (values, v) = symmetric_eigen(&mtm, dims);
+    let scale = values.first().copied().unwrap_or(0.0).max(0.0).sqrt();
+
+    // Columns of U, completed to an orthonormal basis where Σ vanishes.
+    let mut u_cols: Vec<Vec<f64>> = Vec::with_capacity(dims);
+    for (k, &value) in values.iter().enumerate() {
+        let sigma = value.max(0.0).sqrt();
+        if sigma > 1e-12 * scale.max(1e-300) {
+            let col = (0..dims)
+                .map(|i| {
+                    (0..dims)
+                        .map(|j| m[i * dims + j] * v[j * dims + k])
+                        .sum::<f64>()
+                        / sigma
+                })
+                .collect();
+            u_cols.push(col);
+        } else {
+            u_cols.push(complete_basis(&u_cols, dims));
+        }
+    }
+
+    let mut r = vec![0.0; dims * dims];
+    for i in 0..dims {
+        for j in 0..dims {
+            r[i * dims + j] = (0..dims).map(|k| u_cols[k][i] * v[j * dims + k]).sum();
+        }
+    }
+    r
+}
+
+/// A unit vector orthogonal to all vectors in `basis`.
+fn complete_basis(basis: &[Vec<f64>], dims: usize) -> Vec<f64> {
+    for axis in 0..dims {
+        let mut candidate = vec![0.0; dims];
+        candidate[axis] = 1.0;
+        for b in basis {
+            let dot: f64 = candidate.iter().zip(b).map(|(c, b)| c * b).sum();
+            candidate.iter_mut().zip(b).for_each(|(c, b)| *c -= dot * b);
+        }
+        let norm = candidate.iter().map(|c| c * c).sum::<f64>().sqrt();
+        if norm > 1e-6 {
+            candidate.iter_mut().for_each(|c| *c /= norm);
+            return candidate;
+        }
+    }
+    vec![0.0; dims]
+}
+
+fn identity(n: usize) -> Vec<f64> {
+    let mut m = vec![0.0; n * n];
+    for i in 0..n {
+        m[i * n + i] = 1.0;
+    }
+    m
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn symmetric_eigen_sorted_and_orthonormal() {
+        let a = [2.0, 1.0, 0.0, 1.0, 2.0, 0.0, 0.0, 0.0, 5.0];
+        let (values, vectors) = symmetric_eigen(&a, 3);
+        assert!((values[0] - 5.0).abs() < 1e-12);
+        assert!((values[1] - 3.0).abs() < 1e-12);
+        assert!((values[2] - 1.0).abs() < 1e-12);
+        // A v = λ v for each column
+        for k in 0..3 {
+            for i in 0..3 {
+                let av: f64 = (0..3).map(|j| a[i * 3 + j] * vectors[j * 3 + k]).sum();
+                assert!((av - values[k] * vectors[i * 3 + k]).abs() < 1e-10);
+            }
+        }
+    }
+
+    #[test]
+    fn jacobi_convergence_does_not_depend_on_scale() {
+        let n = 6;
+        let mut a = vec![0.0; n * n];
+        for i in 0..n {
+            for j in 0..n {
+                a[i * n + j] = 1.0 / (i + j + 1) as f64 + if i == j { 1.0 } else { 0.0 };
+            }
+        }
+        let sweeps = |a: &[f64]| jacobi_sweeps(&mut a.to_vec(), &mut identity(n), n);
+        let unit = sweeps(&a);
+        assert!(unit < 20, "{unit} sweeps");
+        for scale in [1e-9, 1e9] {
+            let scaled: Vec<f64> = a.iter().map(|x| x * scale).collect();
+            assert!(sweeps(&scaled) <= unit + 1);
+            let (values, _) = symmetric_eigen(&scaled, n);
+            let (expected, _) = symmetric_eigen(&a, n);
+            for (v, e) in values.iter().zip(&expected) {
+                assert!((v / scale - e).abs() < 1e-12, "{v} vs {e}");
+            }
+        }
+    }
+
+    #[test]
+    fn procrustes_recovers_rotation() {
+        let y = [1.0, 0.0, -1.0, 0.0, 0.0, 2.0, 0.0, -2.0];
+        // x is y rotated by 90 degrees: (a, b) -> (-b, a)
+        let x: Vec<f64> = y.chunks_exact(2).flat_map(|p| [-p[1], p[0]]).collect();
+        let r = procrustes_rotation(&x, &y, 2);
+        for (xr, yr) in x.chunks_exact(2).zip(y.chunks_exact(2)) {
+            let rx = [xr[0] * r[0] + xr[1] * r[2], xr[0] * r[1] + xr[1] * r[3]];
+            assert!((rx[0] - yr[0]).abs() < 1e-10 && (rx[1] - yr[1]).abs() < 1e-10);
+        }
+    }
+}
@@ -1,24 +1,73 @@
 use flucoma_sys::{loudness_create, loudness_destroy, loudness_init, loudness_process_frame};
 
+use crate::error::{check_len, FlucomaError};
+use crate::frame_analyzer::FrameAnalyzer;
+use crate::quality::FrameQuality;
+use crate::sample_f32::widen;
+
 // -------------------------------------------------------------------------------------------------
 
 /// Loudness measurement result (EBU R128-style).
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LoudnessResult {
     /// Integrated loudness in dBFS (K-weighted if enabled).
     pub loudness_db: f64,
     /// Peak level in dBFS (true peak if enabled, otherwise absolute max).
     pub peak_db: f64,
+    /// Reliability flags for the input frame and the measured values.
+    pub quality: FrameQuality,
 }
 
 // -------------------------------------------------------------------------------------------------
 
+/// Per-frame settings of [`Loudness`], defaulting to the FluCoMa object's
+/// defaults.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LoudnessParams {
+    /// Apply the K-weighting filter.
+    pub k_weighting: bool,
+    /// Measure true (oversampled) peak instead of the sample peak.
+    pub true_peak: bool,
+}
+
+impl Default for LoudnessParams {
+    fn default() -> Self {
+        Self {
+            k_weighting: true,
+            true_peak: true,
+        }
+    }
+}
+
+/// Snapshot of a [`Loudness`] analyser, from [`Loudness::save_state`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LoudnessState {
+    frame_size: usize,
+    sample_rate: f64,
+    /// The settings `last_frame` was processed with.
+    params: LoudnessParams,
+    last_frame: Vec<f64>,
+}
+
 /// Measures loudness and peak level of audio frames.
 ///
+/// For gated integrated loudness over a stream, including weighted
+/// multichannel summation, see
+/// [`LoudnessMeter`](crate::analyzation::LoudnessMeter).
+///
 /// See <https://learn.flucoma.org/reference/loudness>
 pub struct Loudness {
     inner: *mut u8,
     frame_size: usize,
+    sample_rate: f64,
+    scratch: Vec<f64>,
+    /// The last frame processed and its settings, replayed by
+    /// [`Loudness::restore_state`]; only kept while state recording is on.
+    last_frame: Option<Vec<f64>>,
+    last_params: LoudnessParams,
 }
 
 unsafe impl Send for Loudness {}
@@ -31,20 +80,27 @@ impl Loudness {
     /// * `sample_rate` - Audio sample rate in Hz.
     ///
     /// # Errors
-    /// Returns an error string if parameters are invalid.
-    pub fn new(frame_size: usize, sample_rate: f64) -> Result<Self, &'static str> {
+    /// Returns an error if parameters are invalid.
+    pub fn new(frame_size: usize, sample_rate: f64) -> Result<Self, FlucomaError> {
         if frame_size == 0 {
-            return Err("frame_size must be > 0");
+            return Err(FlucomaError::InvalidParameter("frame_size must be > 0"));
         }
         if sample_rate <= 0.0 {
-            return Err("sample_rate must be > 0");
+            return Err(FlucomaError::InvalidParameter("sample_rate must be > 0"));
         }
         let inner = loudness_create(frame_size as isize);
         if inner.is_null() {
-            return Err("failed to create Loudness instance");
+            return Err(FlucomaError::AllocationFailed);
         }
         loudness_init(inner, frame_size as isize, sample_rate);
-        Ok(Self { inner, frame_size })
+        Ok(Self {
+            inner,
+            frame_size,
+            sample_rate,
+            scratch: Vec::with_capacity(frame_size),
+            last_frame: None,
+            last_params: LoudnessParams::default(),
+        })
     }
 
     /// Process a single audio frame.
@@ -70,6 +126,14 @@ impl Loudness {
             input.len(),
             self.frame_size
         );
+        if let Some(last_frame) = &mut self.last_frame {
+            last_frame.clear();
+            last_frame.extend_from_slice(input);
+            self.last_params = LoudnessParams {
+                k_weighting,
+                true_peak,
+            };
+        }
         let mut out = [0.0f64; 2];
         loudness_process_frame(
             self.inner,
@@ -82,7 +146,95 @@ impl Loudness {
         LoudnessResult {
             loudness_db: out[0],
             peak_db: out[1],
+            quality: FrameQuality::inspect(input) | FrameQuality::inspect_output(&out),
+        }
+    }
+
+    /// Like [`Loudness::process_frame`], but returns
+    /// [`FlucomaError::InvalidShape`] instead of panicking if
+    /// `input.len() != frame_size`.
+    pub fn try_process_frame(
+        &mut self,
+        input: &[f64],
+        k_weighting: bool,
+        true_peak: bool,
+    ) -> Result<LoudnessResult, FlucomaError> {
+        check_len(self.frame_size, input.len())?;
+        Ok(self.process_frame(input, k_weighting, true_peak))
+    }
+
+    /// Keep a copy of each processed frame so [`Loudness::save_state`] can
+    /// capture the filter history. Off by default, so analysers that are
+    /// never checkpointed do not copy every frame.
+    pub fn set_state_recording(&mut self, enabled: bool) {
+        match (enabled, &self.last_frame) {
+            (true, None) => self.last_frame = Some(Vec::with_capacity(self.frame_size)),
+            (false, Some(_)) => self.last_frame = None,
+            _ => {}
+        }
+    }
+
+    /// Capture the filter history so a stream can be resumed later, e.g.
+    /// after restarting a long-running meter.
+    ///
+    /// Only frames processed while state recording is enabled (see
+    /// [`Loudness::set_state_recording`]) are captured; otherwise the state
+    /// restores freshly initialised filters.
+    pub fn save_state(&self) -> LoudnessState {
+        LoudnessState {
+            frame_size: self.frame_size,
+            sample_rate: self.sample_rate,
+            params: self.last_params,
+            last_frame: self.last_frame.clone().unwrap_or_default(),
+        }
+    }
+
+    /// Resume from a state captured by [`Loudness::save_state`]. The
+    /// K-weighting and true-peak filters are re-primed by replaying the last
+    /// frame processed before the snapshot, with the settings it was
+    /// processed with.
+    ///
+    /// # Errors
+    /// Returns an error if the state was saved from an analyser with
+    /// a different `frame_size` or sample rate.
+    pub fn restore_state(&mut self, state: &LoudnessState) -> Result<(), FlucomaError> {
+        if state.frame_size != self.frame_size || state.sample_rate != self.sample_rate {
+            return Err(FlucomaError::InvalidParameter(
+                "state was saved with a different frame_size or sample_rate",
+            ));
+        }
+        self.reset();
+        if !state.last_frame.is_empty() {
+            let params = state.params;
+            self.process_frame(&state.last_frame, params.k_weighting, params.true_peak);
         }
+        Ok(())
+    }
+
+    /// Clear the K-weighting and true-peak filter history, as if newly
+    /// created.
+    pub fn reset(&mut self) {
+        loudness_init(self.inner, self.frame_size as isize, self.sample_rate);
+        if let Some(last_frame) = &mut self.last_frame {
+            last_frame.clear();
+        }
+    }
+
+    /// Like [`Loudness::process_frame`], for f32 audio. The samples are
+    /// widened into an internal buffer, so no allocation happens per call.
+    ///
+    /// # Panics
+    /// Panics if `input.len() != frame_size`.
+    pub fn process_frame_f32(
+        &mut self,
+        input: &[f32],
+        k_weighting: bool,
+        true_peak: bool,
+    ) -> LoudnessResult {
+        let mut scratch = std::mem::take(&mut self.scratch);
+        let result = self.process_frame(widen(input, &mut scratch), k_weighting, true_peak);
+        self.scratch = scratch;
+        result
     }
 
     /// Analysis frame size in samples.
@@ -97,6 +249,25 @@ impl Drop for Loudness {
     }
 }
 
+impl Clone for Loudness {
+    fn clone(&self) -> Self {
+        let mut clone = Self::new(self.frame_size, self.sample_rate)
+            .expect("settings were validated on construction");
+        clone.set_state_recording(self.last_frame.is_some());
+        clone
+    }
+}
+
+impl FrameAnalyzer for Loudness {
+    type Input = [f64];
+    type Params = LoudnessParams;
+    type Output = LoudnessResult;
+
+    fn process(&mut self, input: &[f64], params: &LoudnessParams) -> LoudnessResult {
+        self.process_frame(input, params.k_weighting, params.true_peak)
+    }
+}
+
 // -------------------------------------------------------------------------------------------------
 
 #[cfg(test)]
@@ -111,6 +282,7 @@ mod tests {
         // Silence produces a very low (negative) loudness value
         assert!(r.loudness_db < -60.0, "loudness_db = {}", r.loudness_db);
         assert!(r.peak_db < -60.0, "peak_db = {}", r.peak_db);
+        assert!(r.quality.contains(FrameQuality::SILENT));
     }
 
     #[test]
@@ -134,4 +306,40 @@ mod tests {
             r.peak_db
         );
     }
+
+    #[test]
+    fn restored_loudness_matches_uninterrupted_stream() {
+        use std::f64::consts::PI;
+        // Long frames let the K-weighting filter history decay within one
+        // replayed frame.
+        let n = 4096usize;
+        let signal: Vec<f64> = (0..4 * n)
+            .map(|i| 0.5 * (2.0 * PI * 97.0 * i as f64 / 44100.0).sin())
+            .collect();
+        let frames: Vec<&[f64]> = signal.chunks_exact(n).collect();
+
+        let mut original = Loudness::new(n, 44100.0).unwrap();
+        original.set_state_recording(true);
+        for frame in &frames[..3] {
+            original.process_frame(frame, true, false);
+        }
+        let state = original.save_state();
+        let expected = original.process_frame(frames[3], true, false);
+
+        let mut resumed = Loudness::new(n, 44100.0).unwrap();
+        resumed.restore_state(&state).unwrap();
+        let result = resumed.process_frame(frames[3], true, false);
+        assert!(
+            (result.loudness_db - expected.loudness_db).abs() < 1e-6,
+            "{} != {}",
+            result.loudness_db,
+            expected.loudness_db
+        );
+        assert!((result.peak_db - expected.peak_db).abs() < 1e-6);
+
+        // Without recording there is no history to replay.
+        let mut unrecorded = Loudness::new(n, 44100.0).unwrap();
+        unrecorded.process_frame(frames[0], true, false);
+        assert!(unrecorded.save_state().last_frame.is_empty());
+    }
 }
@@ -0,0 +1,450 @@
+use std::collections::VecDeque;
+
+use crate::error::FlucomaError;
+use crate::loudness::Loudness;
+use crate::true_peak::TruePeakMeter;
+
+/// Loudness is measured over consecutive 100 ms sub-blocks, whose mean
+/// powers are combined into the overlapping windows of ITU-R BS.1770.
+const SUB_BLOCK_SECONDS: f64 = 0.1;
+/// Sub-blocks in the 400 ms momentary window, which is also the gating block.
+const MOMENTARY_BLOCKS: usize = 4;
+/// Sub-blocks in the 3 s short-term window.
+const SHORT_TERM_BLOCKS: usize = 30;
+/// Blocks at or below this loudness are ignored (absolute gate).
+const ABSOLUTE_GATE_LUFS: f64 = -70.0;
+/// Blocks more than this far below the absolute-gated mean are ignored.
+const RELATIVE_GATE_LU: f64 = 10.0;
+/// Resolution and upper end of the block loudness histogram.
+const HISTOGRAM_STEP_LU: f64 = 0.1;
+const HISTOGRAM_MAX_LUFS: f64 = 30.0;
+
+// -------------------------------------------------------------------------------------------------
+
+/// Streaming EBU R128 loudness meter over arbitrarily long input.
+///
+/// Audio is fed in chunks of any length. The K-weighted power of every
+/// 100 ms of input is measured with [`Loudness`] and combined into the
+/// standard windows, which update every 100 ms:
+///
+/// * [`LoudnessMeter::momentary_lufs`] over the last 400 ms,
+/// * [`LoudnessMeter::short_term_lufs`] over the last 3 s,
+/// * [`LoudnessMeter::integrated_lufs`] over everything so far, with the
+///   BS.1770 absolute (−70 LUFS) and relative (−10 LU) gates applied to the
+///   momentary blocks. These are kept in a histogram with 0.1 LU
+///   resolution, so memory stays constant however long the stream runs.
+///
+/// Multichannel meters filter every channel separately and sum the channel
+/// powers with the BS.1770 weights, e.g. [`LoudnessMeter::SURROUND_5_1`],
+/// so stereo and surround programmes read correct absolute values without
+/// a downmix.
+///
+/// ```rust,no_run
+/// use flucoma_rs::analyzation::LoudnessMeter;
+///
+/// let mut meter = LoudnessMeter::new(48000.0).unwrap();
+/// for chunk in vec![0.1f64; 48000 * 60].chunks(512) {
+///     meter.process(chunk);
+///     println!("M {:?} S {:?}", meter.momentary_lufs(), meter.short_term_lufs());
+/// }
+/// println!("I {:?} LUFS", meter.integrated_lufs());
+///
+/// let mut stereo = LoudnessMeter::with_channel_weights(48000.0, &LoudnessMeter::STEREO).unwrap();
+/// stereo.process(&vec![0.1f64; 2 * 48000]); // L R L R ...
+/// ```
+pub struct LoudnessMeter {
+    /// One K-weighting analyser per channel.
+    loudness: Vec<Loudness>,
+    weights: Vec<f64>,
+    sub_block_size: usize,
+    /// Samples of the 100 ms sub-block in progress, per channel.
+    sub_blocks: Vec<Vec<f64>>,
+    /// Weighted mean powers of the most recent sub-blocks, oldest first.
+    recent_powers: VecDeque<f64>,
+    histogram: GatingHistogram,
+}
+
+impl LoudnessMeter {
+    /// Channel weights for stereo (L, R).
+    pub const STEREO: [f64; 2] = [1.0, 1.0];
+    /// Channel weights for 5.1 in L, R, C, LFE, Ls, Rs order. The LFE channel
+    /// is excluded and the surrounds are boosted by about 1.5 dB.
+    pub const SURROUND_5_1: [f64; 6] = [1.0, 1.0, 1.0, 0.0, 1.41, 1.41];
+
+    /// Create a meter for mono audio at `sample_rate`.
+    ///
+    /// # Errors
+    /// Returns an error if `sample_rate` is too low for a 100 ms hop.
+    pub fn new(sample_rate: f64) -> Result<Self, FlucomaError> {
+        Self::with_channel_weights(sample_rate, &[1.0])
+    }
+
+    /// Create a meter for `weights.len()` channels at `sample_rate`, summing
+    /// the channel powers with `weights`.
+    ///
+    /// # Errors
+    /// Returns an error if `weights` is empty or has a negative or
+    /// non-finite weight, or if `sample_rate` is too low for a 100 ms hop.
+    pub fn with_channel_weights(sample_rate: f64, weights: &[f64]) -> Result<Self, FlucomaError> {
+        if weights.is_empty() {
+            return Err(FlucomaError::InvalidParameter("weights must not be empty"));
+        }
+        if weights.iter().any(|w| !w.is_finite() || *w < 0.0) {
+            return Err(FlucomaError::InvalidParameter(
+                "weights must be finite and >= 0",
+            ));
+        }
+        if sample_rate.is_nan() || sample_rate < 10.0 {
+            return Err(FlucomaError::InvalidParameter("sample_rate must be >= 10"));
+        }
+        let sub_block_size = (SUB_BLOCK_SECONDS * sample_rate).round() as usize;
+        Ok(Self {
+            loudness: weights
+                .iter()
+                .map(|_| Loudness::new(sub_block_size, sample_rate))
+                .collect::<Result<_, _>>()?,
+            weights: weights.to_vec(),
+            sub_block_size,
+            sub_blocks: vec![Vec::with_capacity(sub_block_size); weights.len()],
+            recent_powers: VecDeque::with_capacity(SHORT_TERM_BLOCKS),
+            histogram: GatingHistogram::new(),
+        })
+    }
+
+    pub fn n_channels(&self) -> usize {
+        self.weights.len()
+    }
+
+    /// Consume a chunk of interleaved audio (`L R L R ...`) of any length.
+    ///
+    /// # Panics
+    /// Panics if `chunk.len()` is not a multiple of `n_channels()`.
+    pub fn process(&mut self, chunk: &[f64]) {
+        let n_channels = self.n_channels();
+        assert_eq!(
+            chunk.len() % n_channels,
+            0,
+            "chunk length ({}) must be a multiple of n_channels ({})",
+            chunk.len(),
+            n_channels
+        );
+        for frame in chunk.chunks_exact(n_channels) {
+            self.push_frame(|channel| frame[channel]);
+        }
+    }
+
+    /// Consume one slice of audio per channel, all of the same length.
+    ///
+    /// # Panics
+    /// Panics if `channels.len() != n_channels()` or the slices differ in
+    /// length.
+    pub fn process_planar<C: AsRef<[f64]>>(&mut self, channels: &[C]) {
+        assert_eq!(
+            channels.len(),
+            self.n_channels(),
+            "number of channels ({}) must equal n_channels ({})",
+            channels.len(),
+            self.n_channels()
+        );
+        let len = channels[0].as_ref().len();
+        assert!(
+            channels.iter().all(|c| c.as_ref().len() == len),
+            "channel slices must have the same length"
+        );
+        for index in 0..len {
+            self.push_frame(|channel| channels[channel].as_ref()[index]);
+        }
+    }
+
+    /// Append one sample per channel, measuring a sub-block once full.
+    fn push_frame(&mut self, sample: impl Fn(usize) -> f64) {
+        for (channel, block) in self.sub_blocks.iter_mut().enumerate() {
+            block.push(sample(channel));
+        }
+        if self.sub_blocks[0].len() < self.sub_block_size {
+            return;
+        }
+        let mut power = 0.0;
+        for ((loudness, block), weight) in self
+            .loudness
+            .iter_mut()
+            .zip(&mut self.sub_blocks)
+            .zip(&self.weights)
+        {
+            let result = loudness.process_frame(block, true, false);
+            power += weight * 10f64.powf(result.loudness_db / 10.0);
+            block.clear();
+        }
+        if self.recent_powers.len() == SHORT_TERM_BLOCKS {
+            self.recent_powers.pop_front();
+        }
+        self.recent_powers.push_back(power);
+        if let Some(momentary) = self.momentary_lufs() {
+            self.histogram.add(momentary);
+        }
+    }
+
+    /// Loudness in LUFS of the last 400 ms, or `None` until 400 ms have
+    /// been processed.
+    pub fn momentary_lufs(&self) -> Option<f64> {
+        self.window_lufs(MOMENTARY_BLOCKS)
+    }
+
+    /// Loudness in LUFS of the last 3 s, or `None` until 3 s have been
+    /// processed.
+    pub fn short_term_lufs(&self) -> Option<f64> {
+        self.window_lufs(SHORT_TERM_BLOCKS)
+    }
+
+    /// Loudness of the mean power of the last `blocks` sub-blocks.
+    fn window_lufs(&self, blocks: usize) -> Option<f64> {
+        if self.recent_powers.len() < blocks {
+            return None;
+        }
+        let skip = self.recent_powers.len() - blocks;
+        let power = self.recent_powers.iter().skip(skip).sum::<f64>() / blocks as f64;
+        Some(10.0 * power.log10())
+    }
+
+    /// Gated integrated loudness in LUFS of everything processed so far, or
+    /// `None` until a block above the absolute gate has been measured.
+    pub fn integrated_lufs(&self) -> Option<f64> {
+        self.histogram.integrated()
+    }
+
+    /// Forget all processed audio and start a new measurement.
+    pub fn reset(&mut self) {
+        for loudness in &mut self.loudness {
+            loudness.reset();
+        }
+        for block in &mut self.sub_blocks {
+            block.clear();
+        }
+        self.recent_powers.clear();
+        self.histogram = GatingHistogram::new();
+    }
+}
+
+/// Gain bringing a buffer to a target loudness, from [`normalization_gain`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NormalizationGain {
+    /// Linear gain to multiply the buffer by.
+    pub gain: f64,
+    /// Gated integrated loudness of the buffer before normalization, in LUFS.
+    pub integrated_lufs: f64,
+    /// True peak of the buffer after applying `gain`, in dBFS.
+    pub true_peak_db: f64,
+    /// Whether `true_peak_db` exceeds the ceiling, so the normalized buffer
+    /// needs a limiter to avoid clipping.
+    pub needs_limiting: bool,
+}
+
+/// Measure the integrated loudness and true peak of `buffer` and compute the
+/// linear gain that brings it to `target_lufs`.
+///
+/// Returns `None` if the buffer is shorter than 400 ms or entirely below the
+/// −70 LUFS gate, since it has no measurable loudness.
+///
+/// ```rust,no_run
+/// use flucoma_rs::analyzation::normalization_gain;
+///
+/// let slice = vec![0.1f64; 44100];
+/// if let Some(norm) = normalization_gain(&slice, 44100.0, -23.0, -1.0).unwrap() {
+///     let normalized: Vec<f64> = slice.iter().map(|x| x * norm.gain).collect();
+///     if norm.needs_limiting {
+///         println!("limit to -1 dBFS: peak would be {:.1}", norm.true_peak_db);
+///     }
+/// }
+/// ```
+///
+/// # Errors
+/// Returns an error if `target_lufs` or `true_peak_ceiling_db` is not
+/// finite, or as for [`LoudnessMeter::new`].
+pub fn normalization_gain(
+    buffer: &[f64],
+    sample_rate: f64,
+    target_lufs: f64,
+    true_peak_ceiling_db: f64,
+) -> Result<Option<NormalizationGain>, FlucomaError> {
+    if !target_lufs.is_finite() {
+        return Err(FlucomaError::InvalidParameter("target_lufs must be finite"));
+    }
+    if !true_peak_ceiling_db.is_finite() {
+        return Err(FlucomaError::InvalidParameter(
+            "true_peak_ceiling_db must be finite",
+        ));
+    }
+    let mut meter = LoudnessMeter::new(sample_rate)?;
+    meter.process(buffer);
+    let Some(integrated_lufs) = meter.integrated_lufs() else {
+        return Ok(None);
+    };
+    let gain_db = target_lufs - integrated_lufs;
+    let true_peak_db = TruePeakMeter::new().process_frame(buffer) + gain_db;
+    Ok(Some(NormalizationGain {
+        gain: 10f64.powf(gain_db / 20.0),
+        integrated_lufs,
+        true_peak_db,
+        needs_limiting: true_peak_db > true_peak_ceiling_db,
+    }))
+}
+
+/// Counts of gating blocks per loudness step above the absolute gate.
+#[derive(Debug, Clone)]
+struct GatingHistogram {
+    counts: Vec<u64>,
+}
+
+impl GatingHistogram {
+    fn new() -> Self {
+        let steps =
+            ((HISTOGRAM_MAX_LUFS - ABSOLUTE_GATE_LUFS) / HISTOGRAM_STEP_LU).round() as usize;
+        Self {
+            counts: vec![0; steps + 1],
+        }
+    }
+
+    fn add(&mut self, block_lufs: f64) {
+        if block_lufs.is_nan() || block_lufs <= ABSOLUTE_GATE_LUFS {
+            return;
+        }
+        let index = ((block_lufs - ABSOLUTE_GATE_LUFS) / HISTOGRAM_STEP_LU).round() as usize;
+        let index = index.min(self.counts.len() - 1);
+        self.counts[index] += 1;
+    }
+
+    fn level(index: usize) -> f64 {
+        ABSOLUTE_GATE_LUFS + index as f64 * HISTOGRAM_STEP_LU
+    }
+
+    /// Mean loudness of the blocks at or above `gate`, as the loudness of
+    /// their mean power.
+    fn mean_above(&self, gate: f64) -> Option<f64> {
+        let (count, power) = self
+            .counts
+            .iter()
+            .enumerate()
+            .filter(|&(index, &count)| count > 0 && Self::level(index) > gate)
+            .fold((0u64, 0.0), |(n, p), (index, &count)| {
+                (
+                    n + count,
+                    p + count as f64 * 10f64.powf(Self::level(index) / 10.0),
+                )
+            });
+        (count > 0).then(|| 10.0 * (power / count as f64).log10())
+    }
+
+    fn integrated(&self) -> Option<f64> {
+        let ungated = self.mean_above(ABSOLUTE_GATE_LUFS)?;
+        self.mean_above(ungated - RELATIVE_GATE_LU)
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gating_histogram_applies_gates() {
+        let mut histogram = GatingHistogram::new();
+        assert_eq!(histogram.integrated(), None);
+        histogram.add(-80.0);
+        histogram.add(f64::NEG_INFINITY);
+        assert_eq!(histogram.integrated(), None);
+
+        histogram.add(-20.0);
+        histogram.add(-20.0);
+        histogram.add(-40.0);
+        let integrated = histogram.integrated().unwrap();
+        assert!((integrated + 20.0).abs() < 1e-9, "{integrated}");
+
+        histogram.add(-23.0);
+        histogram.add(-23.0);
+        let integrated = histogram.integrated().unwrap();
+        assert!(integrated < -20.0 && integrated > -23.0);
+    }
+
+    #[test]
+    fn loudness_meter_measures_steady_tone() {
+        let sample_rate = 48000.0;
+        let tone: Vec<f64> = (0..48000 * 5)
+            .map(|i| 0.1 * (std::f64::consts::TAU * 1000.0 * i as f64 / sample_rate).sin())
+            .collect();
+        let mut meter = LoudnessMeter::new(sample_rate).unwrap();
+        for chunk in tone.chunks(1000) {
+            meter.process(chunk);
+        }
+        // A 1 kHz sine at -20 dBFS peak reads about -23 LUFS.
+        let integrated = meter.integrated_lufs().unwrap();
+        assert!((integrated + 23.0).abs() < 1.0, "{integrated}");
+        for window in [meter.momentary_lufs(), meter.short_term_lufs()] {
+            assert!((window.unwrap() - integrated).abs() < 0.2);
+        }
+        meter.reset();
+        meter.process(&tone[..48000]);
+        assert!(meter.momentary_lufs().is_some());
+        assert_eq!(meter.short_term_lufs(), None);
+        assert!(meter.integrated_lufs().is_some());
+
+        // Nothing of the earlier signal is left in the filters either.
+        let mut fresh = LoudnessMeter::new(sample_rate).unwrap();
+        fresh.process(&tone[..48000]);
+        assert_eq!(meter.momentary_lufs(), fresh.momentary_lufs());
+        assert_eq!(meter.integrated_lufs(), fresh.integrated_lufs());
+    }
+
+    #[test]
+    fn loudness_meter_sums_weighted_channels() {
+        let sample_rate = 48000.0;
+        let tone: Vec<f64> = (0..48000 * 2)
+            .map(|i| 0.1 * (std::f64::consts::TAU * 1000.0 * i as f64 / sample_rate).sin())
+            .collect();
+        let mut mono = LoudnessMeter::new(sample_rate).unwrap();
+        mono.process(&tone);
+        let mono = mono.integrated_lufs().unwrap();
+
+        // The same signal in both channels is twice the power: +3 LU.
+        let mut stereo =
+            LoudnessMeter::with_channel_weights(sample_rate, &LoudnessMeter::STEREO).unwrap();
+        let interleaved: Vec<f64> = tone.iter().flat_map(|&x| [x, x]).collect();
+        stereo.process(&interleaved);
+        let stereo = stereo.integrated_lufs().unwrap();
+        assert!(
+            (stereo - mono - 10.0 * 2f64.log10()).abs() < 0.1,
+            "{stereo} {mono}"
+        );
+
+        // Only the LFE channel carries signal, which 5.1 ignores.
+        let silence = vec![0.0; tone.len()];
+        let mut surround =
+            LoudnessMeter::with_channel_weights(sample_rate, &LoudnessMeter::SURROUND_5_1).unwrap();
+        surround.process_planar(&[&silence, &silence, &silence, &tone, &silence, &silence]);
+        assert_eq!(surround.integrated_lufs(), None);
+        assert!(LoudnessMeter::with_channel_weights(sample_rate, &[]).is_err());
+    }
+
+    #[test]
+    fn normalization_gain_reaches_target() {
+        let sample_rate = 48000.0;
+        let tone: Vec<f64> = (0..48000 * 2)
+            .map(|i| 0.1 * (std::f64::consts::TAU * 1000.0 * i as f64 / sample_rate).sin())
+            .collect();
+        let norm = normalization_gain(&tone, sample_rate, -14.0, -1.0)
+            .unwrap()
+            .unwrap();
+        assert!((norm.gain - 10f64.powf((-14.0 - norm.integrated_lufs) / 20.0)).abs() < 1e-12);
+        // Peak -20 dBFS raised by about 9 dB stays under the ceiling.
+        assert!(!norm.needs_limiting, "{}", norm.true_peak_db);
+        let loud = normalization_gain(&tone, sample_rate, 0.0, -1.0)
+            .unwrap()
+            .unwrap();
+        assert!(loud.needs_limiting);
+        assert_eq!(
+            normalization_gain(&[0.0; 48000], sample_rate, -14.0, -1.0),
+            Ok(None)
+        );
+    }
+}
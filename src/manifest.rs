@@ -0,0 +1,109 @@
+use std::fmt;
+
+// -------------------------------------------------------------------------------------------------
+
+/// One algorithm invocation recorded in a [`Manifest`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ManifestEntry {
+    /// Algorithm name, e.g. `"KMeans"`.
+    pub algorithm: String,
+    /// `Debug` representation of the configuration used.
+    pub config: String,
+    /// Random seed, for stochastic algorithms.
+    pub seed: Option<isize>,
+}
+
+/// Reproducibility manifest for an analysis run.
+///
+/// Captures the crate version, the flucoma-core commit the crate was built
+/// against and the configurations and seeds of all recorded algorithm runs.
+/// The [`Display`](fmt::Display) output is a small TOML document which can be
+/// stored next to, or embedded into, serialized analysis results.
+///
+/// ```rust,no_run
+/// use flucoma_rs::Manifest;
+/// use flucoma_rs::data::KMeansConfig;
+///
+/// let config = KMeansConfig::default();
+/// let mut manifest = Manifest::new();
+/// manifest.record("KMeans", &config, Some(config.seed));
+/// println!("{manifest}");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Manifest {
+    /// Version of the flucoma-rs crate.
+    pub crate_version: String,
+    /// Git commit of flucoma-core, when it was known at build time.
+    pub flucoma_core_commit: Option<String>,
+    /// Recorded algorithm runs, in recording order.
+    pub entries: Vec<ManifestEntry>,
+}
+
+impl Manifest {
+    /// Create an empty manifest for the current build.
+    pub fn new() -> Self {
+        Self {
+            crate_version: env!("CARGO_PKG_VERSION").to_string(),
+            flucoma_core_commit: flucoma_sys::FLUCOMA_CORE_COMMIT.map(String::from),
+            entries: Vec::new(),
+        }
+    }
+
+    /// Record an algorithm run with its configuration and optional seed.
+    pub fn record<C: fmt::Debug>(&mut self, algorithm: &str, config: &C, seed: Option<isize>) {
+        self.entries.push(ManifestEntry {
+            algorithm: algorithm.to_string(),
+            config: format!("{config:?}"),
+            seed,
+        });
+    }
+}
+
+impl Default for Manifest {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Display for Manifest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "crate_version = {:?}", self.crate_version)?;
+        if let Some(commit) = &self.flucoma_core_commit {
+            writeln!(f, "flucoma_core_commit = {:?}", commit)?;
+        }
+        for entry in &self.entries {
+            writeln!(f)?;
+            writeln!(f, "[[entries]]")?;
+            writeln!(f, "algorithm = {:?}", entry.algorithm)?;
+            writeln!(f, "config = {:?}", entry.config)?;
+            if let Some(seed) = entry.seed {
+                writeln!(f, "seed = {seed}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn manifest_records_entries_in_order() {
+        let mut manifest = Manifest::new();
+        manifest.record("KMeans", &(8usize, 64usize), Some(1234));
+        manifest.record("Mds", &"euclidean", None);
+        assert_eq!(manifest.crate_version, env!("CARGO_PKG_VERSION"));
+        assert_eq!(manifest.entries.len(), 2);
+        assert_eq!(manifest.entries[0].config, "(8, 64)");
+        assert_eq!(manifest.entries[1].seed, None);
+
+        let text = manifest.to_string();
+        assert!(text.contains("algorithm = \"KMeans\""));
+        assert!(text.contains("seed = 1234"));
+    }
+}
@@ -1,3 +1,5 @@
+use crate::error::FlucomaError;
+
 // -------------------------------------------------------------------------------------------------
 
 /// Row-major `f64` matrix.
@@ -8,6 +10,8 @@
 /// Data is stored in **row-major** (C) order: element `(r, c)` is at index
 /// `r * cols + c`.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "MatrixRepr"))]
 pub struct Matrix {
     data: Vec<f64>,
     rows: usize,
@@ -34,15 +38,17 @@ impl Matrix {
     /// # Errors
     /// Returns an error if `data.len() != rows * cols`, or if either dimension
     /// is zero.
-    pub fn from_vec(data: Vec<f64>, rows: usize, cols: usize) -> Result<Self, &'static str> {
+    pub fn from_vec(data: Vec<f64>, rows: usize, cols: usize) -> Result<Self, FlucomaError> {
         if rows == 0 {
-            return Err("rows must be > 0");
+            return Err(FlucomaError::InvalidParameter("rows must be > 0"));
         }
         if cols == 0 {
-            return Err("cols must be > 0");
+            return Err(FlucomaError::InvalidParameter("cols must be > 0"));
         }
         if data.len() != rows * cols {
-            return Err("data length does not match rows * cols");
+            return Err(FlucomaError::InvalidParameter(
+                "data length does not match rows * cols",
+            ));
         }
         Ok(Self { data, rows, cols })
     }
@@ -85,6 +91,25 @@ impl Matrix {
     }
 }
 
+/// Unchecked serialized form of [`Matrix`], validated by
+/// [`Matrix::from_vec`] on deserialization.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct MatrixRepr {
+    data: Vec<f64>,
+    rows: usize,
+    cols: usize,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<MatrixRepr> for Matrix {
+    type Error = FlucomaError;
+
+    fn try_from(repr: MatrixRepr) -> Result<Self, FlucomaError> {
+        Matrix::from_vec(repr.data, repr.rows, repr.cols)
+    }
+}
+
 // -------------------------------------------------------------------------------------------------
 
 #[cfg(test)]
@@ -122,4 +147,13 @@ mod tests {
         assert_eq!(t.cols(), 2);
         assert_eq!(t.data(), &[1.0, 4.0, 2.0, 5.0, 3.0, 6.0]);
     }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn deserialization_checks_shape() {
+        let repr = |data: Vec<f64>, rows, cols| MatrixRepr { data, rows, cols };
+        assert!(Matrix::try_from(repr(vec![1.0, 2.0], 1, 2)).is_ok());
+        assert!(Matrix::try_from(repr(vec![1.0, 2.0], 2, 2)).is_err());
+        assert!(Matrix::try_from(repr(vec![], 0, 2)).is_err());
+    }
 }
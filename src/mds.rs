@@ -1,7 +1,16 @@
+use std::fmt;
+use std::str::FromStr;
+
 use flucoma_sys::{mds_create, mds_destroy, mds_process, FlucomaIndex};
 
-#[derive(Debug, Clone, Copy)]
+use crate::error::FlucomaError;
+use crate::linalg::{procrustes_rotation, symmetric_eigen};
+use crate::param_names::{display_name, parse_name};
+use crate::progress::Progress;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(isize)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum MdsDistance {
     Manhattan = 0,
     Euclidean = 1,
@@ -13,6 +22,46 @@ pub enum MdsDistance {
     JensenShannon = 7,
 }
 
+const MDS_DISTANCE_NAMES: &[(&str, MdsDistance)] = &[
+    ("manhattan", MdsDistance::Manhattan),
+    ("euclidean", MdsDistance::Euclidean),
+    ("squared-euclidean", MdsDistance::SquaredEuclidean),
+    ("max", MdsDistance::Max),
+    ("min", MdsDistance::Min),
+    ("kullback-leibler", MdsDistance::KullbackLeibler),
+    ("cosine", MdsDistance::Cosine),
+    ("jensen-shannon", MdsDistance::JensenShannon),
+];
+
+impl FromStr for MdsDistance {
+    type Err = FlucomaError;
+
+    /// Parse a name such as `"euclidean"` or `"cosine"`, ignoring case and separators.
+    fn from_str(name: &str) -> Result<Self, FlucomaError> {
+        parse_name(name, MDS_DISTANCE_NAMES, "unknown distance")
+    }
+}
+
+impl fmt::Display for MdsDistance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(display_name(self, MDS_DISTANCE_NAMES))
+    }
+}
+
+/// Embedding and goodness-of-fit returned by [`Mds::project_with_details`].
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MdsProjection {
+    /// Row-major `rows × target_dims` embedding.
+    pub embedding: Vec<f64>,
+    /// Normalised Kruskal stress:
+    /// `sqrt(Σ (d_ij - δ_ij)² / Σ d_ij²)`, where `d_ij` are the input
+    /// distances under the chosen metric and `δ_ij` the Euclidean distances
+    /// in the embedding. `0.0` is a perfect fit; values above ~0.2 indicate
+    /// a poor one.
+    pub stress: f64,
+}
+
 /// Multidimensional scaling projection for row-major datasets.
 pub struct Mds {
     inner: *mut u8,
@@ -22,10 +71,10 @@ pub struct Mds {
 unsafe impl Send for Mds {}
 
 impl Mds {
-    pub fn new() -> Result<Self, &'static str> {
+    pub fn new() -> Result<Self, FlucomaError> {
         let inner = mds_create();
         if inner.is_null() {
-            return Err("failed to create MDS instance");
+            return Err(FlucomaError::AllocationFailed);
         }
         Ok(Self { inner })
     }
@@ -37,18 +86,22 @@ impl Mds {
         cols: usize,
         target_dims: usize,
         distance: MdsDistance,
-    ) -> Result<Vec<f64>, &'static str> {
+    ) -> Result<Vec<f64>, FlucomaError> {
         if rows == 0 || cols == 0 {
-            return Err("rows and cols must be > 0");
+            return Err(FlucomaError::InvalidParameter("rows and cols must be > 0"));
         }
         if data.len() != rows * cols {
-            return Err("data length does not match rows * cols");
+            return Err(FlucomaError::InvalidParameter(
+                "data length does not match rows * cols",
+            ));
         }
         if target_dims == 0 {
-            return Err("target_dims must be > 0");
+            return Err(FlucomaError::InvalidParameter("target_dims must be > 0"));
         }
         if target_dims > rows {
-            return Err("target_dims must be <= rows");
+            return Err(FlucomaError::InvalidParameter(
+                "target_dims must be <= rows",
+            ));
         }
 
         let mut out = vec![0.0; rows * target_dims];
@@ -63,6 +116,316 @@ impl Mds {
         );
         Ok(out)
     }
+
+    /// Like [`Mds::project`], additionally returning the stress of the
+    /// embedding so projections and distance metrics can be compared.
+    pub fn project_with_details(
+        &mut self,
+        data: &[f64],
+        rows: usize,
+        cols: usize,
+        target_dims: usize,
+        distance: MdsDistance,
+    ) -> Result<MdsProjection, FlucomaError> {
+        let embedding = self.project(data, rows, cols, target_dims, distance)?;
+        let stress = stress(data, cols, &embedding, target_dims, distance);
+        Ok(MdsProjection { embedding, stress })
+    }
+
+    /// Project `data` and align the result with a previous embedding, so a
+    /// corpus map stays visually stable when points are added.
+    ///
+    /// `previous` holds the earlier row-major `target_dims` embedding of the
+    /// first `previous.len() / target_dims` rows of `data` (i.e. existing points
+    /// come first, in the same order, followed by new points). The new
+    /// embedding is rotated, reflected and translated to best match
+    /// `previous` on those shared rows; distances within the embedding are
+    /// unchanged.
+    ///
+    /// # Errors
+    /// Returns an error if the inputs are invalid, or `previous` is empty, not
+    /// a whole number of rows, or longer than the new embedding.
+    #[allow(clippy::too_many_arguments)]
+    pub fn project_warm(
+        &mut self,
+        data: &[f64],
+        rows: usize,
+        cols: usize,
+        target_dims: usize,
+        distance: MdsDistance,
+        previous: &[f64],
+    ) -> Result<Vec<f64>, FlucomaError> {
+        if target_dims == 0 || previous.is_empty() || !previous.len().is_multiple_of(target_dims) {
+            return Err(FlucomaError::InvalidParameter(
+                "previous must hold whole rows of target_dims values",
+            ));
+        }
+        if previous.len() > rows * target_dims {
+            return Err(FlucomaError::InvalidParameter(
+                "previous has more rows than data",
+            ));
+        }
+        let embedding = self.project(data, rows, cols, target_dims, distance)?;
+        Ok(align_to(&embedding, previous, target_dims))
+    }
+
+    /// Approximate projection for large datasets using landmark MDS
+    /// (de Silva & Tenenbaum).
+    ///
+    /// Classical MDS is run on `n_landmarks` points chosen by max-min
+    /// (farthest point) selection, and every other point is placed by
+    /// distance-based triangulation against the landmarks. Memory use is
+    /// `O(n_landmarks² + rows × n_landmarks)` rather than `O(rows²)`, and the
+    /// result is deterministic. A few hundred landmarks is usually enough.
+    ///
+    /// # Arguments
+    /// * `n_landmarks` - Number of landmark points. Must be `> target_dims`
+    ///   and `<= rows`.
+    ///
+    /// # Errors
+    /// Returns an error if parameters are invalid.
+    pub fn project_landmark(
+        &self,
+        data: &[f64],
+        rows: usize,
+        cols: usize,
+        target_dims: usize,
+        distance: MdsDistance,
+        n_landmarks: usize,
+    ) -> Result<Vec<f64>, FlucomaError> {
+        validate_landmark(data, rows, cols, target_dims, n_landmarks)?;
+        self.project_landmark_with_progress(
+            data,
+            rows,
+            cols,
+            target_dims,
+            distance,
+            n_landmarks,
+            &mut Progress::new(),
+        )
+    }
+
+    /// Like [`Mds::project_landmark`], reporting progress after every
+    /// landmark and every placed point, and stopping early if `progress` is
+    /// cancelled.
+    ///
+    /// # Errors
+    /// Returns [`FlucomaError::Cancelled`] if cancelled, or as for
+    /// [`Mds::project_landmark`].
+    #[allow(clippy::too_many_arguments)]
+    pub fn project_landmark_with_progress(
+        &self,
+        data: &[f64],
+        rows: usize,
+        cols: usize,
+        target_dims: usize,
+        distance: MdsDistance,
+        n_landmarks: usize,
+        progress: &mut Progress,
+    ) -> Result<Vec<f64>, FlucomaError> {
+        validate_landmark(data, rows, cols, target_dims, n_landmarks)?;
+        landmark_mds(data, cols, target_dims, distance, n_landmarks, progress)
+    }
+}
+
+fn validate_landmark(
+    data: &[f64],
+    rows: usize,
+    cols: usize,
+    target_dims: usize,
+    n_landmarks: usize,
+) -> Result<(), FlucomaError> {
+    if rows == 0 || cols == 0 {
+        return Err(FlucomaError::InvalidParameter("rows and cols must be > 0"));
+    }
+    if data.len() != rows * cols {
+        return Err(FlucomaError::InvalidParameter(
+            "data length does not match rows * cols",
+        ));
+    }
+    if target_dims == 0 {
+        return Err(FlucomaError::InvalidParameter("target_dims must be > 0"));
+    }
+    if n_landmarks <= target_dims || n_landmarks > rows {
+        return Err(FlucomaError::InvalidParameter(
+            "n_landmarks must be > target_dims and <= rows",
+        ));
+    }
+    Ok(())
+}
+
+/// Landmark MDS embedding of row-major `data`. Selecting the landmarks and
+/// placing the points each count for half of the reported progress.
+fn landmark_mds(
+    data: &[f64],
+    cols: usize,
+    target_dims: usize,
+    metric: MdsDistance,
+    n_landmarks: usize,
+    progress: &mut Progress,
+) -> Result<Vec<f64>, FlucomaError> {
+    let points: Vec<&[f64]> = data.chunks_exact(cols).collect();
+
+    // Max-min landmark selection, starting from the first point.
+    let mut landmarks = Vec::with_capacity(n_landmarks);
+    let mut nearest = vec![f64::INFINITY; points.len()];
+    let mut next = 0;
+    while landmarks.len() < n_landmarks {
+        landmarks.push(next);
+        for (d, point) in nearest.iter_mut().zip(&points) {
+            *d = d.min(distance(points[next], point, metric));
+        }
+        next = (0..points.len())
+            .max_by(|&a, &b| nearest[a].total_cmp(&nearest[b]))
+            .unwrap_or(0);
+        progress.report(0.5 * landmarks.len() as f64 / n_landmarks as f64)?;
+    }
+
+    // Classical MDS on the double-centred squared landmark distances.
+    let n = n_landmarks;
+    let mut squared = vec![0.0; n * n];
+    for i in 0..n {
+        for j in i + 1..n {
+            let d = distance(points[landmarks[i]], points[landmarks[j]], metric);
+            squared[i * n + j] = d * d;
+            squared[j * n + i] = d * d;
+        }
+    }
+    let column_means: Vec<f64> = (0..n)
+        .map(|j| (0..n).map(|i| squared[i * n + j]).sum::<f64>() / n as f64)
+        .collect();
+    let grand_mean = column_means.iter().sum::<f64>() / n as f64;
+    let mut b = vec![0.0; n * n];
+    for i in 0..n {
+        for j in 0..n {
+            b[i * n + j] =
+                -0.5 * (squared[i * n + j] - column_means[i] - column_means[j] + grand_mean);
+        }
+    }
+    let (values, vectors) = symmetric_eigen(&b, n);
+
+    // Pseudo-inverse rows v_k / sqrt(λ_k); dimensions with non-positive
+    // eigenvalues carry no structure and stay at zero.
+    let pinv: Vec<Vec<f64>> = (0..target_dims)
+        .map(|k| {
+            let scale = if values[k] > 0.0 {
+                values[k].sqrt().recip()
+            } else {
+                0.0
+            };
+            (0..n).map(|i| vectors[i * n + k] * scale).collect()
+        })
+        .collect();
+
+    let mut out = Vec::with_capacity(points.len() * target_dims);
+    let mut offsets = vec![0.0; n];
+    for (i, point) in points.iter().enumerate() {
+        for (offset, (&l, mean)) in offsets.iter_mut().zip(landmarks.iter().zip(&column_means)) {
+            let d = distance(point, points[l], metric);
+            *offset = d * d - mean;
+        }
+        for row in &pinv {
+            let x: f64 = row.iter().zip(&offsets).map(|(p, o)| p * o).sum();
+            out.push(-0.5 * x);
+        }
+        progress.report(0.5 + 0.5 * (i + 1) as f64 / points.len() as f64)?;
+    }
+    Ok(out)
+}
+
+/// Rigidly align `embedding` to `reference`, which covers its leading rows.
+fn align_to(embedding: &[f64], reference: &[f64], dims: usize) -> Vec<f64> {
+    let shared = reference.len() / dims;
+    let centroid = |points: &[f64]| -> Vec<f64> {
+        let mut c = vec![0.0; dims];
+        for row in points.chunks_exact(dims) {
+            c.iter_mut().zip(row).for_each(|(c, x)| *c += x);
+        }
+        c.iter_mut().for_each(|c| *c /= shared as f64);
+        c
+    };
+    let new_centroid = centroid(&embedding[..reference.len()]);
+    let ref_centroid = centroid(reference);
+    let center = |points: &[f64], c: &[f64]| -> Vec<f64> {
+        points
+            .chunks_exact(dims)
+            .flat_map(|row| row.iter().zip(c).map(|(x, c)| x - c))
+            .collect()
+    };
+    let x = center(&embedding[..reference.len()], &new_centroid);
+    let y = center(reference, &ref_centroid);
+    let r = procrustes_rotation(&x, &y, dims);
+
+    let mut out = Vec::with_capacity(embedding.len());
+    for row in center(embedding, &new_centroid).chunks_exact(dims) {
+        for j in 0..dims {
+            let rotated: f64 = (0..dims).map(|k| row[k] * r[k * dims + j]).sum();
+            out.push(rotated + ref_centroid[j]);
+        }
+    }
+    out
+}
+
+/// Normalised Kruskal stress between input distances and embedding distances.
+fn stress(
+    data: &[f64],
+    cols: usize,
+    embedding: &[f64],
+    target_dims: usize,
+    metric: MdsDistance,
+) -> f64 {
+    let points: Vec<&[f64]> = data.chunks_exact(cols).collect();
+    let embedded: Vec<&[f64]> = embedding.chunks_exact(target_dims).collect();
+    let mut residual = 0.0;
+    let mut total = 0.0;
+    for i in 0..points.len() {
+        for j in i + 1..points.len() {
+            let d = distance(points[i], points[j], metric);
+            let delta = distance(embedded[i], embedded[j], MdsDistance::Euclidean);
+            residual += (d - delta) * (d - delta);
+            total += d * d;
+        }
+    }
+    if total > 0.0 {
+        (residual / total).sqrt()
+    } else {
+        0.0
+    }
+}
+
+/// Distance between two points, following the flucoma-core definitions.
+/// Divergences clamp values to a small epsilon before taking logarithms.
+fn distance(a: &[f64], b: &[f64], metric: MdsDistance) -> f64 {
+    const EPSILON: f64 = 1e-10;
+    let kl = |x: &[f64], y: &[f64]| -> f64 {
+        x.iter()
+            .zip(y)
+            .map(|(&p, &q)| p * (p.max(EPSILON).ln() - q.max(EPSILON).ln()))
+            .sum()
+    };
+    let abs_diffs = || a.iter().zip(b).map(|(x, y)| (x - y).abs());
+    match metric {
+        MdsDistance::Manhattan => abs_diffs().sum(),
+        MdsDistance::Euclidean => abs_diffs().map(|d| d * d).sum::<f64>().sqrt(),
+        MdsDistance::SquaredEuclidean => abs_diffs().map(|d| d * d).sum(),
+        MdsDistance::Max => abs_diffs().fold(0.0, f64::max),
+        MdsDistance::Min => abs_diffs().fold(f64::INFINITY, f64::min),
+        MdsDistance::KullbackLeibler => kl(a, b) + kl(b, a),
+        MdsDistance::Cosine => {
+            let dot: f64 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+            let norm = a.iter().map(|x| x * x).sum::<f64>().sqrt()
+                * b.iter().map(|y| y * y).sum::<f64>().sqrt();
+            if norm > 0.0 {
+                1.0 - dot / norm
+            } else {
+                0.0
+            }
+        }
+        MdsDistance::JensenShannon => {
+            let mid: Vec<f64> = a.iter().zip(b).map(|(x, y)| 0.5 * (x + y)).collect();
+            (0.5 * (kl(a, &mid) + kl(b, &mid))).max(0.0).sqrt()
+        }
+    }
 }
 
 impl Drop for Mds {
@@ -91,6 +454,51 @@ mod tests {
         assert!(out.iter().all(|v| v.is_finite()));
     }
 
+    #[test]
+    fn mds_stress_is_zero_for_exact_embedding() {
+        // A 2D square embedded in 2D is reproduced exactly.
+        let data = vec![0.0, 0.0, 0.0, 1.0, 1.0, 0.0, 1.0, 1.0];
+        assert!(stress(&data, 2, &data, 2, MdsDistance::Euclidean) < 1e-12);
+        // Collapsing everything onto one point loses all structure.
+        let collapsed = vec![0.0; 8];
+        let s = stress(&data, 2, &collapsed, 2, MdsDistance::Euclidean);
+        assert!((s - 1.0).abs() < 1e-12);
+
+        let mut mds = Mds::new().unwrap();
+        let projection = mds
+            .project_with_details(&data, 4, 2, 2, MdsDistance::Euclidean)
+            .unwrap();
+        assert_eq!(projection.embedding.len(), 8);
+        assert!(projection.stress < 1e-6);
+    }
+
+    #[test]
+    fn landmark_mds_preserves_planar_distances() {
+        // Points on a tilted plane in 3D are exactly representable in 2D.
+        let data: Vec<f64> = (0..30)
+            .flat_map(|i| {
+                let (u, v) = ((i % 6) as f64, (i / 6) as f64 * 0.7);
+                [u, v, 0.5 * u - 0.25 * v]
+            })
+            .collect();
+        let out =
+            landmark_mds(&data, 3, 2, MdsDistance::Euclidean, 6, &mut Progress::new()).unwrap();
+        assert_eq!(out.len(), 60);
+        assert!(stress(&data, 3, &out, 2, MdsDistance::Euclidean) < 1e-8);
+    }
+
+    #[test]
+    fn align_to_undoes_rotation_and_translation() {
+        let reference = vec![0.0, 0.0, 2.0, 0.0, 0.0, 1.0];
+        // reference reflected across x, translated by (5, 5), plus one new point
+        let embedding = vec![5.0, 5.0, 7.0, 5.0, 5.0, 4.0, 7.0, 4.0];
+        let aligned = align_to(&embedding, &reference, 2);
+        let expected = [0.0, 0.0, 2.0, 0.0, 0.0, 1.0, 2.0, 1.0];
+        for (a, e) in aligned.iter().zip(expected) {
+            assert!((a - e).abs() < 1e-9, "{aligned:?}");
+        }
+    }
+
     #[test]
     fn mds_rejects_invalid_target_dims() {
         let data = vec![0.0, 0.0, 1.0, 1.0];
@@ -98,6 +506,9 @@ mod tests {
         let err = mds
             .project(&data, 2, 2, 3, MdsDistance::Euclidean)
             .unwrap_err();
-        assert_eq!(err, "target_dims must be <= rows");
+        assert_eq!(
+            err,
+            FlucomaError::InvalidParameter("target_dims must be <= rows")
+        );
     }
 }
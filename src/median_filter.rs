@@ -0,0 +1,176 @@
+use flucoma_sys::{
+    median_filter_create, median_filter_destroy, median_filter_init, median_filter_process_sample,
+};
+
+use crate::error::{check_len, FlucomaError};
+
+/// Running median over the last `size` frames of a descriptor stream.
+///
+/// Wraps the median filter flucoma-core uses inside its onset and novelty
+/// algorithms, with one independent filter per dimension, so streams such
+/// as pitch or spectral centroid can be smoothed the same way. Outliers
+/// shorter than half the filter length are removed without blurring steps,
+/// at the cost of `(size - 1) / 2` frames of latency.
+///
+/// ```rust,no_run
+/// use flucoma_rs::data::MedianFilter;
+///
+/// // Smooth pitch and confidence over 5 frames.
+/// let mut filter = MedianFilter::new(5, 2).unwrap();
+/// for frame in [[440.0, 0.9], [880.0, 0.2], [441.0, 0.9]] {
+///     let smoothed = filter.process(&frame);
+///     println!("{smoothed:?}");
+/// }
+/// ```
+pub struct MedianFilter {
+    /// One native filter per dimension.
+    filters: Vec<*mut u8>,
+    size: usize,
+    output: Vec<f64>,
+}
+
+// SAFETY: flucoma algorithms are thread-safe to move between threads.
+unsafe impl Send for MedianFilter {}
+
+impl MedianFilter {
+    /// Create a median filter over `size` frames of `dims`-dimensional
+    /// vectors.
+    ///
+    /// # Errors
+    /// Returns an error if `size` is not an odd number >= 3 or `dims == 0`.
+    pub fn new(size: usize, dims: usize) -> Result<Self, FlucomaError> {
+        if size < 3 || size.is_multiple_of(2) {
+            return Err(FlucomaError::InvalidParameter(
+                "size must be an odd number >= 3",
+            ));
+        }
+        if dims == 0 {
+            return Err(FlucomaError::InvalidParameter("dims must be > 0"));
+        }
+        let mut filters = Vec::with_capacity(dims);
+        for _ in 0..dims {
+            let inner = median_filter_create(size as isize);
+            if inner.is_null() {
+                filters.into_iter().for_each(median_filter_destroy);
+                return Err(FlucomaError::AllocationFailed);
+            }
+            median_filter_init(inner, size as isize);
+            filters.push(inner);
+        }
+        Ok(Self {
+            filters,
+            size,
+            output: vec![0.0; dims],
+        })
+    }
+
+    /// Push one frame and return the median of each dimension over the last
+    /// `size` frames.
+    ///
+    /// Returned slice points to an internal buffer and is valid until the
+    /// next call.
+    ///
+    /// # Panics
+    /// Panics if `input.len() != dims`.
+    pub fn process(&mut self, input: &[f64]) -> &[f64] {
+        assert_eq!(
+            input.len(),
+            self.filters.len(),
+            "input length ({}) must equal dims ({})",
+            input.len(),
+            self.filters.len()
+        );
+        for ((&filter, &value), out) in self.filters.iter().zip(input).zip(&mut self.output) {
+            *out = median_filter_process_sample(filter, value);
+        }
+        &self.output
+    }
+
+    /// Like [`MedianFilter::process`], but returns
+    /// [`FlucomaError::InvalidShape`] instead of panicking if
+    /// `input.len() != dims`.
+    pub fn try_process(&mut self, input: &[f64]) -> Result<&[f64], FlucomaError> {
+        check_len(self.filters.len(), input.len())?;
+        Ok(self.process(input))
+    }
+
+    /// Reset the filter history.
+    pub fn clear(&mut self) {
+        for &filter in &self.filters {
+            median_filter_init(filter, self.size as isize);
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn dims(&self) -> usize {
+        self.filters.len()
+    }
+
+    /// Frames by which the output lags the input.
+    pub fn latency_frames(&self) -> usize {
+        (self.size - 1) / 2
+    }
+}
+
+impl Clone for MedianFilter {
+    fn clone(&self) -> Self {
+        Self::new(self.size, self.filters.len()).expect("settings were validated on construction")
+    }
+}
+
+impl Drop for MedianFilter {
+    fn drop(&mut self) {
+        for &filter in &self.filters {
+            median_filter_destroy(filter);
+        }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn median_filter_rejects_even_sizes() {
+        assert_eq!(
+            MedianFilter::new(4, 1).err(),
+            Some(FlucomaError::InvalidParameter(
+                "size must be an odd number >= 3"
+            ))
+        );
+        assert_eq!(
+            MedianFilter::new(3, 0).err(),
+            Some(FlucomaError::InvalidParameter("dims must be > 0"))
+        );
+    }
+
+    #[test]
+    fn median_filter_removes_outliers() {
+        let mut filter = MedianFilter::new(3, 2).unwrap();
+        let frames = [
+            [1.0, 5.0],
+            [1.0, 5.0],
+            [100.0, 5.0],
+            [1.0, -50.0],
+            [1.0, 5.0],
+        ];
+        let outputs: Vec<Vec<f64>> = frames.iter().map(|f| filter.process(f).to_vec()).collect();
+        assert_eq!(outputs[3], vec![1.0, 5.0]);
+        assert_eq!(outputs[4], vec![1.0, 5.0]);
+    }
+
+    #[test]
+    fn median_filter_step_is_delayed_by_latency() {
+        let mut filter = MedianFilter::new(5, 1).unwrap();
+        let step = 10;
+        let rose = (0..30)
+            .position(|i| filter.process(&[if i >= step { 1.0 } else { 0.0 }])[0] > 0.5)
+            .unwrap();
+        assert_eq!(rose, step + filter.latency_frames());
+    }
+}
@@ -1,7 +1,38 @@
+use std::sync::OnceLock;
+
 use flucoma_sys::{melbands_create, melbands_destroy, melbands_init, melbands_process_frame};
 
+use crate::error::{check_len, FlucomaError};
+use crate::fft_settings::FftSettings;
+use crate::filter_bank::{triangular_filters, FilterScale};
+use crate::frame_analyzer::FrameAnalyzer;
+use crate::linalg::symmetric_eigen;
+
 // -------------------------------------------------------------------------------------------------
 
+/// Per-frame settings of [`MelBands`], defaulting to the FluCoMa object's
+/// defaults.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MelBandsParams {
+    /// Normalise band energies by the filter areas.
+    pub mag_norm: bool,
+    /// Sum power instead of magnitudes.
+    pub use_power: bool,
+    /// Convert the output to dB.
+    pub log_output: bool,
+}
+
+impl Default for MelBandsParams {
+    fn default() -> Self {
+        Self {
+            mag_norm: true,
+            use_power: false,
+            log_output: false,
+        }
+    }
+}
+
 /// Mel-scaled filter bank -- converts a magnitude spectrum into mel band energies.
 ///
 /// Call [`MelBands::process_frame`] with magnitude spectra (not raw complex).
@@ -11,6 +42,13 @@ pub struct MelBands {
     inner: *mut u8,
     n_bins: usize,
     n_bands: usize,
+    lo_hz: f64,
+    hi_hz: f64,
+    sample_rate: f64,
+    window_size: usize,
+    /// `n_bins × n_bands` pseudo-inverse of the filter bank, computed on the
+    /// first call to [`MelBands::inverse`].
+    pseudo_inverse: OnceLock<Vec<f64>>,
 }
 
 unsafe impl Send for MelBands {}
@@ -27,7 +65,7 @@ impl MelBands {
     /// * `window_size` - Analysis window size (for amplitude normalisation).
     ///
     /// # Errors
-    /// Returns an error string if parameters are invalid.
+    /// Returns an error if parameters are invalid.
     pub fn new(
         n_bands: usize,
         n_bins: usize,
@@ -35,25 +73,25 @@ impl MelBands {
         hi_hz: f64,
         sample_rate: f64,
         window_size: usize,
-    ) -> Result<Self, &'static str> {
+    ) -> Result<Self, FlucomaError> {
         if n_bands < 2 {
-            return Err("n_bands must be >= 2");
+            return Err(FlucomaError::InvalidParameter("n_bands must be >= 2"));
         }
         if n_bins == 0 {
-            return Err("n_bins must be > 0");
+            return Err(FlucomaError::InvalidParameter("n_bins must be > 0"));
         }
         if lo_hz >= hi_hz {
-            return Err("lo_hz must be < hi_hz");
+            return Err(FlucomaError::InvalidParameter("lo_hz must be < hi_hz"));
         }
         if sample_rate <= 0.0 {
-            return Err("sample_rate must be > 0");
+            return Err(FlucomaError::InvalidParameter("sample_rate must be > 0"));
         }
         if window_size == 0 {
-            return Err("window_size must be > 0");
+            return Err(FlucomaError::InvalidParameter("window_size must be > 0"));
         }
         let inner = melbands_create(n_bands as isize, ((n_bins - 1) * 2) as isize);
         if inner.is_null() {
-            return Err("failed to create MelBands instance");
+            return Err(FlucomaError::AllocationFailed);
         }
         melbands_init(
             inner,
@@ -68,9 +106,40 @@ impl MelBands {
             inner,
             n_bins,
             n_bands,
+            lo_hz,
+            hi_hz,
+            sample_rate,
+            window_size,
+            pseudo_inverse: OnceLock::new(),
         })
     }
 
+    /// Start building a mel filter bank for audio at `sample_rate`, see
+    /// [`MelBandsBuilder`].
+    ///
+    /// ```rust,no_run
+    /// use flucoma_rs::analyzation::MelBands;
+    ///
+    /// let mel = MelBands::builder(44100.0)
+    ///     .bands(64)
+    ///     .window(1024)
+    ///     .fft(2048)
+    ///     .hi_hz(16000.0)
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(mel.n_bins(), 1025);
+    /// ```
+    pub fn builder(sample_rate: f64) -> MelBandsBuilder {
+        MelBandsBuilder {
+            sample_rate,
+            n_bands: 40,
+            lo_hz: 20.0,
+            hi_hz: None,
+            window_size: 1024,
+            fft_size: None,
+        }
+    }
+
     /// Process a magnitude spectrum frame and return mel band energies.
     ///
     /// # Arguments
@@ -88,6 +157,38 @@ impl MelBands {
         use_power: bool,
         log_output: bool,
     ) -> Vec<f64> {
+        let mut output = vec![0.0f64; self.n_bands];
+        self.process_into(magnitudes, mag_norm, use_power, log_output, &mut output);
+        output
+    }
+
+    /// Like [`MelBands::process_frame`], but returns
+    /// [`FlucomaError::InvalidShape`] instead of panicking if
+    /// `magnitudes.len() != n_bins`.
+    pub fn try_process_frame(
+        &mut self,
+        magnitudes: &[f64],
+        mag_norm: bool,
+        use_power: bool,
+        log_output: bool,
+    ) -> Result<Vec<f64>, FlucomaError> {
+        check_len(self.n_bins, magnitudes.len())?;
+        Ok(self.process_frame(magnitudes, mag_norm, use_power, log_output))
+    }
+
+    /// Like [`MelBands::process_frame`], but writes the band energies into
+    /// `output` instead of allocating, for use on an audio thread.
+    ///
+    /// # Panics
+    /// Panics if `magnitudes.len() != n_bins` or `output.len() != n_bands`.
+    pub fn process_into(
+        &mut self,
+        magnitudes: &[f64],
+        mag_norm: bool,
+        use_power: bool,
+        log_output: bool,
+        output: &mut [f64],
+    ) {
         assert_eq!(
             magnitudes.len(),
             self.n_bins,
@@ -95,7 +196,13 @@ impl MelBands {
             magnitudes.len(),
             self.n_bins
         );
-        let mut output = vec![0.0f64; self.n_bands];
+        assert_eq!(
+            output.len(),
+            self.n_bands,
+            "output length ({}) must equal n_bands ({})",
+            output.len(),
+            self.n_bands
+        );
         melbands_process_frame(
             self.inner,
             magnitudes.as_ptr(),
@@ -106,7 +213,6 @@ impl MelBands {
             use_power,
             log_output,
         );
-        output
     }
 
     /// Number of mel bands in each output frame.
@@ -118,6 +224,80 @@ impl MelBands {
     pub fn n_bins(&self) -> usize {
         self.n_bins
     }
+
+    /// Approximate the magnitude spectrum of mel band energies by applying
+    /// the Moore-Penrose pseudo-inverse of the filter bank, e.g. to
+    /// resynthesise a modified mel spectrogram with
+    /// [`griffin_lim`](crate::fourier::griffin_lim).
+    ///
+    /// `bands` must be linear band energies as returned by
+    /// [`MelBands::process_frame`] with `mag_norm`, `use_power` and
+    /// `log_output` all false. Negative estimates are clamped to zero. The
+    /// pseudo-inverse is computed once, on the first call.
+    ///
+    /// # Panics
+    /// Panics if `bands.len() != n_bands`.
+    pub fn inverse(&self, bands: &[f64]) -> Vec<f64> {
+        assert_eq!(
+            bands.len(),
+            self.n_bands,
+            "bands length ({}) must equal n_bands ({})",
+            bands.len(),
+            self.n_bands
+        );
+        let pseudo_inverse = self.pseudo_inverse.get_or_init(|| {
+            let filters = triangular_filters(
+                FilterScale::Mel,
+                self.n_bands,
+                self.n_bins,
+                self.lo_hz,
+                self.hi_hz,
+                self.sample_rate,
+            );
+            pseudo_inverse(&filters, self.n_bands, self.n_bins)
+        });
+        pseudo_inverse
+            .chunks_exact(self.n_bands)
+            .map(|row| {
+                let magnitude: f64 = row.iter().zip(bands).map(|(w, b)| w * b).sum();
+                magnitude.max(0.0)
+            })
+            .collect()
+    }
+}
+
+/// `cols × rows` pseudo-inverse `Fᵀ (F Fᵀ)⁺` of a row-major `rows × cols`
+/// matrix with `rows <= cols`.
+fn pseudo_inverse(filters: &[f64], rows: usize, cols: usize) -> Vec<f64> {
+    let mut gram = vec![0.0; rows * rows];
+    for i in 0..rows {
+        for j in 0..rows {
+            let (a, b) = (&filters[i * cols..][..cols], &filters[j * cols..][..cols]);
+            gram[i * rows + j] = a.iter().zip(b).map(|(x, y)| x * y).sum();
+        }
+    }
+    let (values, vectors) = symmetric_eigen(&gram, rows);
+    let tolerance = values.first().copied().unwrap_or(0.0) * 1e-10;
+    let mut gram_inverse = vec![0.0; rows * rows];
+    for (k, &value) in values.iter().enumerate() {
+        if value <= tolerance {
+            continue;
+        }
+        for i in 0..rows {
+            for j in 0..rows {
+                gram_inverse[i * rows + j] += vectors[i * rows + k] * vectors[j * rows + k] / value;
+            }
+        }
+    }
+    let mut result = vec![0.0; cols * rows];
+    for col in 0..cols {
+        for j in 0..rows {
+            result[col * rows + j] = (0..rows)
+                .map(|i| filters[i * cols + col] * gram_inverse[i * rows + j])
+                .sum();
+        }
+    }
+    result
 }
 
 impl Drop for MelBands {
@@ -126,6 +306,129 @@ impl Drop for MelBands {
     }
 }
 
+impl Clone for MelBands {
+    fn clone(&self) -> Self {
+        Self::new(
+            self.n_bands,
+            self.n_bins,
+            self.lo_hz,
+            self.hi_hz,
+            self.sample_rate,
+            self.window_size,
+        )
+        .expect("settings were validated on construction")
+    }
+}
+
+impl FrameAnalyzer for MelBands {
+    type Input = [f64];
+    type Params = MelBandsParams;
+    type Output = Vec<f64>;
+
+    fn process(&mut self, magnitudes: &[f64], params: &MelBandsParams) -> Vec<f64> {
+        self.process_frame(
+            magnitudes,
+            params.mag_norm,
+            params.use_power,
+            params.log_output,
+        )
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Named-parameter construction of a [`MelBands`], created with
+/// [`MelBands::builder`].
+///
+/// Defaults to 40 bands from 20 Hz to Nyquist over a 1024 sample window.
+/// Unless set, the FFT size equals the window size.
+#[derive(Debug, Clone, Copy)]
+pub struct MelBandsBuilder {
+    sample_rate: f64,
+    n_bands: usize,
+    lo_hz: f64,
+    hi_hz: Option<f64>,
+    window_size: usize,
+    fft_size: Option<usize>,
+}
+
+impl MelBandsBuilder {
+    /// Number of mel bands.
+    pub fn bands(mut self, n_bands: usize) -> Self {
+        self.n_bands = n_bands;
+        self
+    }
+
+    /// Low-frequency edge of the filter bank in Hz.
+    pub fn lo_hz(mut self, lo_hz: f64) -> Self {
+        self.lo_hz = lo_hz;
+        self
+    }
+
+    /// High-frequency edge of the filter bank in Hz, at most Nyquist.
+    pub fn hi_hz(mut self, hi_hz: f64) -> Self {
+        self.hi_hz = Some(hi_hz);
+        self
+    }
+
+    /// Analysis window length of the magnitude spectra in samples.
+    pub fn window(mut self, window_size: usize) -> Self {
+        self.window_size = window_size;
+        self
+    }
+
+    /// FFT size of the magnitude spectra, at least the window size.
+    pub fn fft(mut self, fft_size: usize) -> Self {
+        self.fft_size = Some(fft_size);
+        self
+    }
+
+    /// Take the window and FFT size from shared [`FftSettings`].
+    pub fn fft_settings(mut self, settings: &FftSettings) -> Self {
+        self.window_size = settings.window_size;
+        self.fft_size = Some(settings.fft_size);
+        self
+    }
+
+    /// Create the filter bank.
+    ///
+    /// # Errors
+    /// Returns [`FlucomaError::InvalidParameter`] naming the first invalid
+    /// setting.
+    pub fn build(self) -> Result<MelBands, FlucomaError> {
+        let nyquist = self.sample_rate / 2.0;
+        let hi_hz = self.hi_hz.unwrap_or(nyquist);
+        let fft_size = self.fft_size.unwrap_or(self.window_size);
+        if self.sample_rate <= 0.0 {
+            return Err(FlucomaError::InvalidParameter("sample_rate must be > 0"));
+        }
+        if self.window_size == 0 {
+            return Err(FlucomaError::InvalidParameter("window_size must be > 0"));
+        }
+        if fft_size < self.window_size {
+            return Err(FlucomaError::InvalidParameter(
+                "fft_size must be >= window_size",
+            ));
+        }
+        if self.lo_hz < 0.0 {
+            return Err(FlucomaError::InvalidParameter("lo_hz must be >= 0"));
+        }
+        if hi_hz > nyquist {
+            return Err(FlucomaError::InvalidParameter(
+                "hi_hz must be <= sample_rate / 2",
+            ));
+        }
+        MelBands::new(
+            self.n_bands,
+            fft_size / 2 + 1,
+            self.lo_hz,
+            hi_hz,
+            self.sample_rate,
+            self.window_size,
+        )
+    }
+}
+
 // -------------------------------------------------------------------------------------------------
 
 #[cfg(test)]
@@ -143,6 +446,69 @@ mod tests {
         assert_eq!(bands.len(), n_bands);
     }
 
+    #[test]
+    fn melbands_builder_names_invalid_field() {
+        let builder = MelBands::builder(44100.0);
+        assert_eq!(
+            builder.hi_hz(30000.0).build().err(),
+            Some(FlucomaError::InvalidParameter(
+                "hi_hz must be <= sample_rate / 2"
+            ))
+        );
+        assert_eq!(
+            builder.window(2048).fft(1024).build().err(),
+            Some(FlucomaError::InvalidParameter(
+                "fft_size must be >= window_size"
+            ))
+        );
+        assert_eq!(
+            builder.bands(1).build().err(),
+            Some(FlucomaError::InvalidParameter("n_bands must be >= 2"))
+        );
+        let mel = builder.bands(20).fft(2048).build().unwrap();
+        assert_eq!((mel.n_bands(), mel.n_bins()), (20, 1025));
+    }
+
+    #[test]
+    fn mel_filter_pseudo_inverse_recovers_bands() {
+        let (n_bands, n_bins) = (20, 257);
+        let filters = triangular_filters(FilterScale::Mel, n_bands, n_bins, 20.0, 11025.0, 22050.0);
+        let peak = filters[..n_bins].iter().copied().fold(0.0, f64::max);
+        assert!(peak > 0.5 && peak <= 1.0);
+
+        let inverse = pseudo_inverse(&filters, n_bands, n_bins);
+        let bands: Vec<f64> = (0..n_bands).map(|i| 1.0 + i as f64).collect();
+        let magnitudes: Vec<f64> = inverse
+            .chunks_exact(n_bands)
+            .map(|row| row.iter().zip(&bands).map(|(w, b)| w * b).sum())
+            .collect();
+        for (band, expected) in bands.iter().enumerate() {
+            let row = &filters[band * n_bins..][..n_bins];
+            let got: f64 = row.iter().zip(&magnitudes).map(|(w, m)| w * m).sum();
+            assert!(
+                (got - expected).abs() < 1e-6,
+                "band {band}: {got} != {expected}"
+            );
+        }
+    }
+
+    #[test]
+    fn melbands_inverse_round_trips_through_process_frame() {
+        let (n_bands, n_bins) = (20, 257);
+        let mut mel = MelBands::new(n_bands, n_bins, 20.0, 11025.0, 22050.0, 512).unwrap();
+        let magnitudes: Vec<f64> = (0..n_bins)
+            .map(|i| 1.0 + 0.5 * (i as f64 * 0.05).cos())
+            .collect();
+        let bands = mel.process_frame(&magnitudes, false, false, false);
+        let restored = mel.process_frame(&mel.inverse(&bands), false, false, false);
+        for (band, (got, expected)) in restored.iter().zip(&bands).enumerate() {
+            assert!(
+                (got - expected).abs() <= 1e-3 * expected.abs().max(1e-9),
+                "band {band}: {got} != {expected}"
+            );
+        }
+    }
+
     #[test]
     fn melbands_silent_spectrum() {
         let n_bands = 40usize;
@@ -155,4 +521,15 @@ mod tests {
             assert!(v.abs() < 1e-10, "expected zero band, got {}", v);
         }
     }
+
+    #[test]
+    fn melbands_process_into_matches_process_frame() {
+        let n_bins = 513;
+        let mut mel = MelBands::new(40, n_bins, 80.0, 8000.0, 44100.0, 1024).unwrap();
+        let magnitudes: Vec<f64> = (0..n_bins).map(|i| 1.0 / (1.0 + i as f64)).collect();
+        let expected = mel.process_frame(&magnitudes, true, false, true);
+        let mut output = vec![0.0f64; 40];
+        mel.process_into(&magnitudes, true, false, true, &mut output);
+        assert_eq!(output, expected);
+    }
 }
@@ -0,0 +1,159 @@
+use crate::dct::Dct;
+use crate::error::{check_len, FlucomaError};
+use crate::frame_analyzer::FrameAnalyzer;
+use crate::mel_bands::MelBands;
+
+// -------------------------------------------------------------------------------------------------
+
+/// Mel-frequency cepstral coefficients -- a DCT of log mel band energies.
+///
+/// Call [`Mfcc::process_frame`] with magnitude spectra (not raw complex).
+///
+/// See <https://learn.flucoma.org/reference/mfcc>
+pub struct Mfcc {
+    dct: Dct,
+    mel_bands: MelBands,
+    bands: Vec<f64>,
+}
+
+impl Mfcc {
+    /// Create and fully initialise an MFCC analyser.
+    ///
+    /// # Arguments
+    /// * `n_coefs`     - Number of cepstral coefficients (must be >= 2 and <= `n_bands`).
+    /// * `n_bands`     - Number of mel bands the DCT is taken over (must be >= 2).
+    /// * `n_bins`      - Number of FFT magnitude bins (`fft_size / 2 + 1`).
+    /// * `lo_hz`       - Low-frequency edge of the filter bank in Hz.
+    /// * `hi_hz`       - High-frequency edge of the filter bank in Hz.
+    /// * `sample_rate` - Audio sample rate in Hz.
+    /// * `window_size` - Analysis window size (for amplitude normalisation).
+    ///
+    /// # Errors
+    /// Returns an error if parameters are invalid.
+    pub fn new(
+        n_coefs: usize,
+        n_bands: usize,
+        n_bins: usize,
+        lo_hz: f64,
+        hi_hz: f64,
+        sample_rate: f64,
+        window_size: usize,
+    ) -> Result<Self, FlucomaError> {
+        if n_coefs < 2 {
+            return Err(FlucomaError::InvalidParameter("n_coefs must be >= 2"));
+        }
+        if n_coefs > n_bands {
+            return Err(FlucomaError::InvalidParameter("n_coefs must be <= n_bands"));
+        }
+        let mel_bands = MelBands::new(n_bands, n_bins, lo_hz, hi_hz, sample_rate, window_size)?;
+        Self::with_mel_bands(n_coefs, mel_bands)
+    }
+
+    fn with_mel_bands(n_coefs: usize, mel_bands: MelBands) -> Result<Self, FlucomaError> {
+        let n_bands = mel_bands.n_bands();
+        Ok(Self {
+            dct: Dct::new(n_bands, n_coefs)?,
+            mel_bands,
+            bands: vec![0.0; n_bands],
+        })
+    }
+
+    /// Process a magnitude spectrum frame and return `n_coefs` coefficients.
+    ///
+    /// Mel bands are magnitude-normalised and converted to dB before the DCT,
+    /// as in FluCoMa's MFCC object.
+    ///
+    /// # Panics
+    /// Panics if `magnitudes.len() != n_bins`.
+    pub fn process_frame(&mut self, magnitudes: &[f64]) -> Vec<f64> {
+        let mut output = vec![0.0f64; self.n_coefs()];
+        self.process_into(magnitudes, &mut output);
+        output
+    }
+
+    /// Like [`Mfcc::process_frame`], but returns
+    /// [`FlucomaError::InvalidShape`] instead of panicking if
+    /// `magnitudes.len() != n_bins`.
+    pub fn try_process_frame(&mut self, magnitudes: &[f64]) -> Result<Vec<f64>, FlucomaError> {
+        check_len(self.n_bins(), magnitudes.len())?;
+        Ok(self.process_frame(magnitudes))
+    }
+
+    /// Like [`Mfcc::process_frame`], but writes the coefficients into
+    /// `output` instead of allocating, for use on an audio thread.
+    ///
+    /// # Panics
+    /// Panics if `magnitudes.len() != n_bins` or `output.len() != n_coefs`.
+    pub fn process_into(&mut self, magnitudes: &[f64], output: &mut [f64]) {
+        assert_eq!(
+            output.len(),
+            self.n_coefs(),
+            "output length ({}) must equal n_coefs ({})",
+            output.len(),
+            self.n_coefs()
+        );
+        self.mel_bands
+            .process_into(magnitudes, true, false, true, &mut self.bands);
+        self.dct.process_into(&self.bands, output);
+    }
+
+    /// Number of coefficients in each output frame.
+    pub fn n_coefs(&self) -> usize {
+        self.dct.n_coefs()
+    }
+
+    /// Number of mel bands the DCT is taken over.
+    pub fn n_bands(&self) -> usize {
+        self.mel_bands.n_bands()
+    }
+
+    /// Number of FFT magnitude bins expected as input (`fft_size / 2 + 1`).
+    pub fn n_bins(&self) -> usize {
+        self.mel_bands.n_bins()
+    }
+}
+
+impl Clone for Mfcc {
+    fn clone(&self) -> Self {
+        Self::with_mel_bands(self.n_coefs(), self.mel_bands.clone())
+            .expect("settings were validated on construction")
+    }
+}
+
+impl FrameAnalyzer for Mfcc {
+    type Input = [f64];
+    type Params = ();
+    type Output = Vec<f64>;
+
+    fn process(&mut self, magnitudes: &[f64], _params: &()) -> Vec<f64> {
+        self.process_frame(magnitudes)
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mfcc_output_count() {
+        let n_bins = 513;
+        let mut mfcc = Mfcc::new(13, 40, n_bins, 20.0, 8000.0, 44100.0, 1024).unwrap();
+        let coefs = mfcc.process_frame(&vec![1.0f64; n_bins]);
+        assert_eq!(coefs.len(), 13);
+        assert!(coefs.iter().all(|c| c.is_finite()));
+        assert!(Mfcc::new(41, 40, n_bins, 20.0, 8000.0, 44100.0, 1024).is_err());
+    }
+
+    #[test]
+    fn mfcc_process_into_matches_process_frame() {
+        let n_bins = 513;
+        let magnitudes: Vec<f64> = (0..n_bins).map(|i| 1.0 / (1.0 + i as f64)).collect();
+        let mut mfcc = Mfcc::new(13, 40, n_bins, 20.0, 8000.0, 44100.0, 1024).unwrap();
+        let expected = mfcc.process_frame(&magnitudes);
+        let mut output = [0.0f64; 13];
+        mfcc.process_into(&magnitudes, &mut output);
+        assert_eq!(output.to_vec(), expected);
+    }
+}
@@ -2,10 +2,13 @@ use flucoma_sys::{
     multistats_create, multistats_destroy, multistats_init, multistats_process, FlucomaIndex,
 };
 
+use crate::error::FlucomaError;
+
 const STATS_PER_DERIVATIVE: usize = 7;
 
 /// Configuration for [`MultiStats`].
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MultiStatsConfig {
     pub num_derivatives: u8,
     pub low_percentile: f64,
@@ -28,6 +31,7 @@ impl Default for MultiStatsConfig {
 
 /// Seven summary statistics for one derivative order.
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MultiStatsValues {
     pub mean: f64,
     pub std: f64,
@@ -66,6 +70,7 @@ impl MultiStatsValues {
 
 /// Per-channel output of `MultiStats`/`BufStats`, with optional derivatives.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MultiStatsOutput {
     pub stats: MultiStatsValues,
     pub derivative_1: Option<MultiStatsValues>,
@@ -116,11 +121,11 @@ pub struct MultiStats {
 unsafe impl Send for MultiStats {}
 
 impl MultiStats {
-    pub fn new(config: MultiStatsConfig) -> Result<Self, &'static str> {
+    pub fn new(config: MultiStatsConfig) -> Result<Self, FlucomaError> {
         validate_config(&config)?;
         let inner = multistats_create();
         if inner.is_null() {
-            return Err("failed to create MultiStats instance");
+            return Err(FlucomaError::AllocationFailed);
         }
         Ok(Self { inner, config })
     }
@@ -129,7 +134,7 @@ impl MultiStats {
         &self.config
     }
 
-    pub fn set_config(&mut self, config: MultiStatsConfig) -> Result<(), &'static str> {
+    pub fn set_config(&mut self, config: MultiStatsConfig) -> Result<(), FlucomaError> {
         validate_config(&config)?;
         self.config = config;
         Ok(())
@@ -144,22 +149,28 @@ impl MultiStats {
         num_frames: usize,
         num_channels: usize,
         weights: Option<&[f64]>,
-    ) -> Result<Vec<MultiStatsOutput>, &'static str> {
+    ) -> Result<Vec<MultiStatsOutput>, FlucomaError> {
         if num_frames == 0 {
-            return Err("num_frames must be > 0");
+            return Err(FlucomaError::InvalidParameter("num_frames must be > 0"));
         }
         if num_channels == 0 {
-            return Err("num_channels must be > 0");
+            return Err(FlucomaError::InvalidParameter("num_channels must be > 0"));
         }
         if input.len() != num_frames * num_channels {
-            return Err("input length does not match num_frames * num_channels");
+            return Err(FlucomaError::InvalidParameter(
+                "input length does not match num_frames * num_channels",
+            ));
         }
         if num_frames <= self.config.num_derivatives as usize {
-            return Err("num_frames must be > num_derivatives");
+            return Err(FlucomaError::InvalidParameter(
+                "num_frames must be > num_derivatives",
+            ));
         }
         if let Some(weight_slice) = weights {
             if weight_slice.len() != num_frames {
-                return Err("weights length must equal num_frames");
+                return Err(FlucomaError::InvalidParameter(
+                    "weights length must equal num_frames",
+                ));
             }
             if !weight_slice.iter().copied().any(|value| value > 0.0) {
                 return Ok(zero_outputs(num_channels, self.config.num_derivatives));
@@ -206,24 +217,36 @@ impl Drop for MultiStats {
     }
 }
 
-fn validate_config(config: &MultiStatsConfig) -> Result<(), &'static str> {
+fn validate_config(config: &MultiStatsConfig) -> Result<(), FlucomaError> {
     if config.num_derivatives > 2 {
-        return Err("num_derivatives must be in [0, 2]");
+        return Err(FlucomaError::InvalidParameter(
+            "num_derivatives must be in [0, 2]",
+        ));
     }
     if !(0.0..=100.0).contains(&config.low_percentile) {
-        return Err("low_percentile must be in [0, 100]");
+        return Err(FlucomaError::InvalidParameter(
+            "low_percentile must be in [0, 100]",
+        ));
     }
     if !(0.0..=100.0).contains(&config.middle_percentile) {
-        return Err("middle_percentile must be in [0, 100]");
+        return Err(FlucomaError::InvalidParameter(
+            "middle_percentile must be in [0, 100]",
+        ));
     }
     if !(0.0..=100.0).contains(&config.high_percentile) {
-        return Err("high_percentile must be in [0, 100]");
+        return Err(FlucomaError::InvalidParameter(
+            "high_percentile must be in [0, 100]",
+        ));
     }
     if config.low_percentile > config.middle_percentile {
-        return Err("low_percentile must be <= middle_percentile");
+        return Err(FlucomaError::InvalidParameter(
+            "low_percentile must be <= middle_percentile",
+        ));
     }
     if config.middle_percentile > config.high_percentile {
-        return Err("middle_percentile must be <= high_percentile");
+        return Err(FlucomaError::InvalidParameter(
+            "middle_percentile must be <= high_percentile",
+        ));
     }
     Ok(())
 }
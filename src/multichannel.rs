@@ -0,0 +1,173 @@
+use crate::error::FlucomaError;
+use crate::frame_analyzer::FrameAnalyzer;
+
+// -------------------------------------------------------------------------------------------------
+
+/// One [`FrameAnalyzer`] per channel, driven together.
+///
+/// Owns an independent instance for every channel, so stateful analysers
+/// (STFT history, onset debouncing, loudness filters) never mix channels.
+/// Frames can be passed planar, one slice per channel, or interleaved, and
+/// results come back in channel order.
+///
+/// ```rust,no_run
+/// use flucoma_rs::analyzation::{Loudness, LoudnessParams, MultiChannel};
+///
+/// let mut meters = MultiChannel::new(2, |_| Loudness::new(1024, 44100.0)).unwrap();
+/// let stereo = vec![0.0f64; 2 * 1024]; // L R L R ...
+/// let results = meters.process_interleaved(&stereo, &LoudnessParams::default());
+/// println!("left {:.1} dB, right {:.1} dB", results[0].loudness_db, results[1].loudness_db);
+/// ```
+#[derive(Clone)]
+pub struct MultiChannel<A> {
+    channels: Vec<A>,
+    scratch: Vec<f64>,
+}
+
+impl<A> MultiChannel<A> {
+    /// Create `n_channels` analysers, calling `make` with each channel index.
+    ///
+    /// # Errors
+    /// Returns an error if `n_channels == 0`, or the first error
+    /// returned by `make`.
+    pub fn new(
+        n_channels: usize,
+        make: impl FnMut(usize) -> Result<A, FlucomaError>,
+    ) -> Result<Self, FlucomaError> {
+        if n_channels == 0 {
+            return Err(FlucomaError::InvalidParameter("n_channels must be > 0"));
+        }
+        let channels = (0..n_channels).map(make).collect::<Result<_, _>>()?;
+        Ok(Self::from_channels(channels))
+    }
+
+    /// Wrap analysers that were created individually.
+    ///
+    /// # Panics
+    /// Panics if `channels` is empty.
+    pub fn from_channels(channels: Vec<A>) -> Self {
+        assert!(!channels.is_empty(), "channels must not be empty");
+        Self {
+            channels,
+            scratch: Vec::new(),
+        }
+    }
+
+    pub fn n_channels(&self) -> usize {
+        self.channels.len()
+    }
+
+    /// The analyser for one channel, e.g. to read its settings.
+    ///
+    /// # Panics
+    /// Panics if `channel >= n_channels()`.
+    pub fn channel(&self, channel: usize) -> &A {
+        &self.channels[channel]
+    }
+
+    /// The analyser for one channel, e.g. to change its settings.
+    ///
+    /// # Panics
+    /// Panics if `channel >= n_channels()`.
+    pub fn channel_mut(&mut self, channel: usize) -> &mut A {
+        &mut self.channels[channel]
+    }
+
+    /// All analysers in channel order.
+    pub fn channels_mut(&mut self) -> &mut [A] {
+        &mut self.channels
+    }
+
+    pub fn into_channels(self) -> Vec<A> {
+        self.channels
+    }
+}
+
+impl<A: FrameAnalyzer<Input = [f64]>> MultiChannel<A> {
+    /// Analyse one frame per channel, given as separate slices.
+    ///
+    /// # Panics
+    /// Panics if `frames.len() != n_channels()`, or if a channel's analyser
+    /// rejects its frame.
+    pub fn process_planar<F: AsRef<[f64]>>(
+        &mut self,
+        frames: &[F],
+        params: &A::Params,
+    ) -> Vec<A::Output> {
+        assert_eq!(
+            frames.len(),
+            self.channels.len(),
+            "number of frames ({}) must equal n_channels ({})",
+            frames.len(),
+            self.channels.len()
+        );
+        self.channels
+            .iter_mut()
+            .zip(frames)
+            .map(|(analyzer, frame)| analyzer.process(frame.as_ref(), params))
+            .collect()
+    }
+
+    /// Analyse one interleaved multichannel frame (`L R L R ...`).
+    ///
+    /// Each channel is de-interleaved into an internal buffer that is reused
+    /// across calls.
+    ///
+    /// # Panics
+    /// Panics if `frame.len()` is not a multiple of `n_channels()`, or if a
+    /// channel's analyser rejects its frame.
+    pub fn process_interleaved(&mut self, frame: &[f64], params: &A::Params) -> Vec<A::Output> {
+        let n_channels = self.channels.len();
+        assert!(
+            frame.len().is_multiple_of(n_channels),
+            "frame length ({}) must be a multiple of n_channels ({})",
+            frame.len(),
+            n_channels
+        );
+        let mut outputs = Vec::with_capacity(n_channels);
+        for (channel, analyzer) in self.channels.iter_mut().enumerate() {
+            self.scratch.clear();
+            self.scratch
+                .extend(frame.iter().skip(channel).step_by(n_channels));
+            outputs.push(analyzer.process(&self.scratch, params));
+        }
+        outputs
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Sums its input and remembers the running total, so per-channel state
+    /// can be checked without the C++ library.
+    struct Accumulator(f64);
+
+    impl FrameAnalyzer for Accumulator {
+        type Input = [f64];
+        type Params = ();
+        type Output = f64;
+
+        fn process(&mut self, input: &[f64], _params: &()) -> f64 {
+            self.0 += input.iter().sum::<f64>();
+            self.0
+        }
+    }
+
+    #[test]
+    fn multichannel_keeps_channels_apart() {
+        let mut multi = MultiChannel::new(2, |_| Ok(Accumulator(0.0))).unwrap();
+        assert_eq!(
+            multi.process_interleaved(&[1.0, 10.0, 2.0, 20.0], &()),
+            vec![3.0, 30.0]
+        );
+        assert_eq!(
+            multi.process_planar(&[[1.0, 1.0], [5.0, 5.0]], &()),
+            vec![5.0, 40.0]
+        );
+        assert_eq!(multi.channel(1).0, 40.0);
+        assert!(MultiChannel::<Accumulator>::new(0, |_| Ok(Accumulator(0.0))).is_err());
+    }
+}
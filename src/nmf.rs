@@ -1,7 +1,9 @@
-use flucoma_sys::{nmf_create, nmf_destroy, nmf_process};
+use flucoma_sys::{nmf_create, nmf_destroy, nmf_process, nmf_process_from};
 
+use crate::error::FlucomaError;
 use crate::matrix::Matrix;
 use crate::nmf_filter::NmfResult;
+use crate::seed::resolve_seed;
 
 // -------------------------------------------------------------------------------------------------
 
@@ -38,11 +40,11 @@ impl Nmf {
     /// Create a new NMF processor.
     ///
     /// # Errors
-    /// Returns an error string if allocation fails.
-    pub fn new() -> Result<Self, &'static str> {
+    /// Returns an error if allocation fails.
+    pub fn new() -> Result<Self, FlucomaError> {
         let inner = nmf_create();
         if inner.is_null() {
-            return Err("failed to create NMF instance");
+            return Err(FlucomaError::AllocationFailed);
         }
         Ok(Self { inner })
     }
@@ -59,7 +61,8 @@ impl Nmf {
     /// * `n_iterations` - Number of multiplicative-update iterations.
     /// * `update_w`     - Whether to update the bases matrix W during iteration.
     /// * `update_h`     - Whether to update the activations matrix H during iteration.
-    /// * `random_seed`  - Seed for random initialisation. Use -1 for a random seed.
+    /// * `random_seed`  - Seed for random initialisation. Use -1 for a random seed, or the
+    ///   [global seed](crate::set_global_seed) if one is set.
     ///
     /// Returns an [`NmfResult`] with shapes:
     /// - `bases`:       `rank × n_bins`
@@ -75,7 +78,7 @@ impl Nmf {
         n_iterations: usize,
         update_w: bool,
         update_h: bool,
-        random_seed: i64,
+        random_seed: isize,
     ) -> NmfResult {
         assert!(rank > 0, "rank must be > 0");
         let n_frames = spectrogram.rows();
@@ -95,7 +98,46 @@ impl Nmf {
             n_iterations.max(1) as isize,
             update_w,
             update_h,
-            random_seed as isize,
+            resolve_seed(random_seed),
+        );
+        NmfResult {
+            bases: Matrix::from_vec(w, rank, n_bins).unwrap(),
+            activations: Matrix::from_vec(h, n_frames, rank).unwrap(),
+            estimate: Matrix::from_vec(v, n_frames, n_bins).unwrap(),
+        }
+    }
+
+    /// Continue a factorisation of `spectrogram` for `n_iterations` more
+    /// updates, starting from the bases and activations of `previous`, so
+    /// long factorisations can report progress between chunks.
+    pub(crate) fn process_from(
+        &mut self,
+        spectrogram: &Matrix,
+        previous: &NmfResult,
+        n_iterations: usize,
+        update_w: bool,
+        update_h: bool,
+    ) -> NmfResult {
+        let rank = previous.bases.rows();
+        let n_frames = spectrogram.rows();
+        let n_bins = spectrogram.cols();
+        let mut w = vec![0.0f64; rank * n_bins];
+        let mut h = vec![0.0f64; n_frames * rank];
+        let mut v = vec![0.0f64; n_frames * n_bins];
+        nmf_process_from(
+            self.inner,
+            spectrogram.data().as_ptr(),
+            n_frames as isize,
+            n_bins as isize,
+            previous.bases.data().as_ptr(),
+            previous.activations.data().as_ptr(),
+            w.as_mut_ptr(),
+            h.as_mut_ptr(),
+            v.as_mut_ptr(),
+            rank as isize,
+            n_iterations.max(1) as isize,
+            update_w,
+            update_h,
         );
         NmfResult {
             bases: Matrix::from_vec(w, rank, n_bins).unwrap(),
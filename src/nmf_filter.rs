@@ -1,10 +1,13 @@
 use flucoma_sys::{nmf_create, nmf_destroy, nmf_process, nmf_process_frame};
 
+use crate::error::FlucomaError;
 use crate::matrix::Matrix;
+use crate::seed::resolve_seed;
 
 // -------------------------------------------------------------------------------------------------
 
 /// Result of an offline NMF batch decomposition via [`NMFFilter::process`].
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NmfResult {
     /// Bases matrix W, shape `rank × n_bins`.
     pub bases: Matrix,
@@ -44,16 +47,16 @@ impl NMFFilter {
     /// # Arguments
     /// * `n_bins` - Number of FFT bins (magnitude spectrum length). Must be > 0.
     /// * `rank`   - Number of NMF components / dictionary columns. Must be > 0.
-    pub fn new(n_bins: usize, rank: usize) -> Result<Self, &'static str> {
+    pub fn new(n_bins: usize, rank: usize) -> Result<Self, FlucomaError> {
         if n_bins == 0 {
-            return Err("n_bins must be > 0");
+            return Err(FlucomaError::InvalidParameter("n_bins must be > 0"));
         }
         if rank == 0 {
-            return Err("rank must be > 0");
+            return Err(FlucomaError::InvalidParameter("rank must be > 0"));
         }
         let inner = nmf_create();
         if inner.is_null() {
-            return Err("failed to create NMF instance");
+            return Err(FlucomaError::AllocationFailed);
         }
         Ok(Self {
             inner,
@@ -84,7 +87,7 @@ impl NMFFilter {
         magnitudes: &[f64],
         bases: &Matrix,
         n_iterations: usize,
-        random_seed: i64,
+        random_seed: isize,
     ) -> (&'a [f64], &'a [f64]) {
         assert_eq!(
             magnitudes.len(),
@@ -118,7 +121,7 @@ impl NMFFilter {
             self.buf.as_mut_ptr(),
             unsafe { self.buf.as_mut_ptr().add(self.rank) },
             n_iter,
-            random_seed as isize,
+            resolve_seed(random_seed),
         );
         let (activations, estimate) = self.buf.split_at(self.rank);
         (activations, estimate)
@@ -147,7 +150,7 @@ impl NMFFilter {
         spectrogram: &Matrix,
         rank: usize,
         n_iterations: usize,
-        random_seed: i64,
+        random_seed: isize,
     ) -> NmfResult {
         assert!(rank > 0, "rank must be > 0");
         assert_eq!(
@@ -181,7 +184,7 @@ impl NMFFilter {
             n_iterations.max(1) as isize,
             true, // update_w
             true, // update_h
-            random_seed as isize,
+            resolve_seed(random_seed),
         );
         NmfResult {
             bases: Matrix::from_vec(w, rank, n_bins).unwrap(),
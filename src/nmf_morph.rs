@@ -2,7 +2,9 @@ use num_complex::Complex64 as Complex;
 
 use flucoma_sys::{nmf_morph_create, nmf_morph_destroy, nmf_morph_init, nmf_morph_process_frame};
 
+use crate::error::FlucomaError;
 use crate::matrix::Matrix;
+use crate::seed::resolve_seed;
 
 // -------------------------------------------------------------------------------------------------
 
@@ -34,13 +36,13 @@ impl NMFMorph {
     ///
     /// # Arguments
     /// * `max_fft_size` - Maximum FFT size that will be used. Must be > 0.
-    pub fn new(max_fft_size: usize) -> Result<Self, &'static str> {
+    pub fn new(max_fft_size: usize) -> Result<Self, FlucomaError> {
         if max_fft_size == 0 {
-            return Err("max_fft_size must be > 0");
+            return Err(FlucomaError::InvalidParameter("max_fft_size must be > 0"));
         }
         let inner = nmf_morph_create(max_fft_size as isize);
         if inner.is_null() {
-            return Err("failed to create NMFMorph instance");
+            return Err(FlucomaError::AllocationFailed);
         }
         Ok(Self {
             inner,
@@ -69,7 +71,7 @@ impl NMFMorph {
     /// * `assign`   - Use Hungarian assignment to match W1/W2 components optimally.
     ///
     /// # Errors
-    /// Returns an error string if dimension constraints are violated.
+    /// Returns an error if dimension constraints are violated.
     #[allow(clippy::too_many_arguments)]
     pub fn init(
         &mut self,
@@ -80,31 +82,43 @@ impl NMFMorph {
         fft_size: usize,
         hop_size: usize,
         assign: bool,
-    ) -> Result<(), &'static str> {
+    ) -> Result<(), FlucomaError> {
         if w1.rows() != w2.rows() {
-            return Err("w1 and w2 must have the same number of rows (rank)");
+            return Err(FlucomaError::InvalidParameter(
+                "w1 and w2 must have the same number of rows (rank)",
+            ));
         }
         if h.rows() != w1.rows() {
-            return Err("h.rows() must equal rank (w1.rows())");
+            return Err(FlucomaError::InvalidParameter(
+                "h.rows() must equal rank (w1.rows())",
+            ));
         }
         if window_size == 0 {
-            return Err("win_size must be > 0");
+            return Err(FlucomaError::InvalidParameter("win_size must be > 0"));
         }
         if fft_size < window_size {
-            return Err("fft_size must be >= win_size");
+            return Err(FlucomaError::InvalidParameter(
+                "fft_size must be >= win_size",
+            ));
         }
         if fft_size > self.max_fft_size {
-            return Err("fft_size must be <= max_fft_size");
+            return Err(FlucomaError::InvalidParameter(
+                "fft_size must be <= max_fft_size",
+            ));
         }
         if hop_size == 0 {
-            return Err("hop_size must be > 0");
+            return Err(FlucomaError::InvalidParameter("hop_size must be > 0"));
         }
         let n_bins = fft_size / 2 + 1;
         if w1.cols() != n_bins {
-            return Err("w1.cols() must equal fft_size / 2 + 1");
+            return Err(FlucomaError::InvalidParameter(
+                "w1.cols() must equal fft_size / 2 + 1",
+            ));
         }
         if w2.cols() != n_bins {
-            return Err("w2.cols() must equal fft_size / 2 + 1");
+            return Err(FlucomaError::InvalidParameter(
+                "w2.cols() must equal fft_size / 2 + 1",
+            ));
         }
         nmf_morph_init(
             self.inner,
@@ -141,7 +155,7 @@ impl NMFMorph {
     ///
     /// # Panics
     /// Panics if `init` has not been called yet.
-    pub fn process_frame(&mut self, interpolation: f64, seed: i64) -> &[Complex] {
+    pub fn process_frame(&mut self, interpolation: f64, seed: isize) -> &[Complex] {
         assert!(
             self.num_bins > 0,
             "NMFMorph::init must be called before process_frame"
@@ -152,7 +166,7 @@ impl NMFMorph {
             self.buf.as_mut_ptr() as *mut f64,
             self.num_bins as isize,
             interpolation,
-            seed as isize,
+            resolve_seed(seed),
         );
         &self.buf
     }
@@ -1,9 +1,26 @@
 use flucoma_sys::{
-    normalization_create, normalization_destroy, normalization_fit, normalization_initialized,
-    normalization_process, FlucomaIndex,
+    normalization_create, normalization_data_max, normalization_data_min, normalization_destroy,
+    normalization_fit, normalization_initialized, normalization_process, normalization_set_model,
+    FlucomaIndex,
 };
 
+use crate::column_mask::{replace_columns, select_columns, update_mask, ColumnMasked};
+use crate::error::FlucomaError;
 use crate::matrix::Matrix;
+use crate::transform::Transform;
+
+/// How [`Normalize::transform`] treats values outside the fitted data range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum OutOfRangePolicy {
+    /// Extrapolate linearly beyond the output range, as flucoma-core does.
+    #[default]
+    PassThrough,
+    /// Clamp results to the output range.
+    Clamp,
+    /// Fail with [`FlucomaError::OutOfRange`].
+    Error,
+}
 
 /// Min-max normalizer for dataset-style matrices.
 ///
@@ -19,6 +36,12 @@ pub struct Normalize {
     min: f64,
     max: f64,
     cols: Option<usize>,
+    /// Feature columns to scale, or `None` for all of them.
+    columns: Option<Vec<usize>>,
+    out_of_range: OutOfRangePolicy,
+    /// Running per-feature `(min, max)` of the chunks seen by
+    /// [`Normalize::partial_fit`].
+    running: Option<(Vec<f64>, Vec<f64>)>,
 }
 
 // SAFETY: flucoma algorithms are thread-safe to move between threads.
@@ -29,24 +52,30 @@ impl Normalize {
     ///
     /// # Errors
     /// Returns an error if `min == max`.
-    pub fn new(min: f64, max: f64) -> Result<Self, &'static str> {
+    pub fn new(min: f64, max: f64) -> Result<Self, FlucomaError> {
         if min == max {
-            return Err("min and max must be different");
+            return Err(FlucomaError::InvalidParameter(
+                "min and max must be different",
+            ));
         }
         let inner = normalization_create();
         if inner.is_null() {
-            return Err("failed to create Normalization instance");
+            return Err(FlucomaError::AllocationFailed);
         }
         Ok(Self {
             inner,
             min,
             max,
             cols: None,
+            running: None,
+            columns: None,
+            out_of_range: OutOfRangePolicy::default(),
         })
     }
 
     /// Fit the normalizer from a row-major matrix.
-    pub fn fit(&mut self, data: &Matrix) -> Result<(), &'static str> {
+    pub fn fit(&mut self, data: &Matrix) -> Result<(), FlucomaError> {
+        let data = select_columns(data, self.columns.as_deref())?;
         normalization_fit(
             self.inner,
             self.min,
@@ -56,50 +85,198 @@ impl Normalize {
             data.cols() as FlucomaIndex,
         );
         self.cols = Some(data.cols());
+        self.running = None;
+        Ok(())
+    }
+
+    /// Update the fitted range with another chunk of rows, so the normalizer
+    /// can be fitted over streams or datasets too large to hold in memory.
+    ///
+    /// Only the running per-feature minimum and maximum are kept, and the
+    /// model is updated after every chunk, so it can be used for
+    /// [`Normalize::transform`] at any point. The result matches
+    /// [`Normalize::fit`] on all rows seen so far. A call to
+    /// [`Normalize::fit`] discards the accumulated state.
+    ///
+    /// # Errors
+    /// Returns an error if the chunk is empty or its column count differs
+    /// from earlier chunks.
+    pub fn partial_fit(&mut self, chunk: &Matrix) -> Result<(), FlucomaError> {
+        let chunk = select_columns(chunk, self.columns.as_deref())?;
+        if chunk.rows() == 0 || chunk.cols() == 0 {
+            return Err(FlucomaError::InvalidParameter("chunk must not be empty"));
+        }
+        let cols = chunk.cols();
+        let (data_min, data_max) = self
+            .running
+            .get_or_insert_with(|| (vec![f64::INFINITY; cols], vec![f64::NEG_INFINITY; cols]));
+        if data_min.len() != cols {
+            return Err(FlucomaError::InvalidParameter(
+                "cols must match previous chunks",
+            ));
+        }
+        for row in chunk.data().chunks_exact(cols) {
+            for ((lo, hi), &x) in data_min.iter_mut().zip(data_max.iter_mut()).zip(row) {
+                *lo = lo.min(x);
+                *hi = hi.max(x);
+            }
+        }
+        normalization_set_model(
+            self.inner,
+            self.min,
+            self.max,
+            data_min.as_ptr(),
+            data_max.as_ptr(),
+            cols as FlucomaIndex,
+        );
+        self.cols = Some(cols);
         Ok(())
     }
 
     /// Transform a matrix into the fitted output range.
     ///
+    /// Values outside the fitted data range are handled according to
+    /// [`Normalize::set_out_of_range`].
+    ///
     /// # Errors
-    /// Returns an error if the normalizer has not been fitted yet, or if the
-    /// matrix column count differs from the fitted feature dimension.
-    pub fn transform(&self, data: &Matrix) -> Result<Matrix, &'static str> {
+    /// Returns an error if the normalizer has not been fitted yet, if the
+    /// matrix column count differs from the fitted feature dimension, or if
+    /// a value is out of range under [`OutOfRangePolicy::Error`].
+    pub fn transform(&self, data: &Matrix) -> Result<Matrix, FlucomaError> {
         self.process_internal(data, false)
     }
 
     /// Undo a previous min-max transform.
-    pub fn inverse_transform(&self, data: &Matrix) -> Result<Matrix, &'static str> {
+    pub fn inverse_transform(&self, data: &Matrix) -> Result<Matrix, FlucomaError> {
         self.process_internal(data, true)
     }
 
     /// Fit the normalizer and transform the same matrix in one step.
-    pub fn fit_transform(&mut self, data: &Matrix) -> Result<Matrix, &'static str> {
+    pub fn fit_transform(&mut self, data: &Matrix) -> Result<Matrix, FlucomaError> {
         self.fit(data)?;
         self.transform(data)
     }
 
     pub fn is_fitted(&self) -> bool {
-        normalization_initialized(self.inner)
+        self.cols.is_some() && normalization_initialized(self.inner)
+    }
+
+    /// Set how [`Normalize::transform`] treats values outside the fitted data
+    /// range, e.g. new points beyond the extremes of the training set. The
+    /// default, [`OutOfRangePolicy::PassThrough`], extrapolates.
+    pub fn set_out_of_range(&mut self, policy: OutOfRangePolicy) {
+        self.out_of_range = policy;
+    }
+
+    pub fn out_of_range(&self) -> OutOfRangePolicy {
+        self.out_of_range
     }
 
-    fn process_internal(&self, data: &Matrix, inverse: bool) -> Result<Matrix, &'static str> {
+    /// The configured output range `(min, max)`.
+    pub fn output_range(&self) -> (f64, f64) {
+        (self.min, self.max)
+    }
+
+    /// Per-feature minimum learned by [`Normalize::fit`], or `None` if the
+    /// normalizer is not fitted.
+    pub fn data_min(&self) -> Option<Vec<f64>> {
+        self.fitted_vector(normalization_data_min)
+    }
+
+    /// Per-feature maximum learned by [`Normalize::fit`], or `None` if the
+    /// normalizer is not fitted.
+    ///
+    /// Together with [`Normalize::output_range`] this fully describes the
+    /// transform: `min + (x - data_min) / (data_max - data_min) * (max - min)`.
+    pub fn data_max(&self) -> Option<Vec<f64>> {
+        self.fitted_vector(normalization_data_max)
+    }
+
+    fn fitted_vector(&self, get: fn(*mut u8, *mut f64, FlucomaIndex)) -> Option<Vec<f64>> {
+        let cols = self.cols.filter(|_| self.is_fitted())?;
+        let mut values = vec![0.0; cols];
+        get(self.inner, values.as_mut_ptr(), cols as FlucomaIndex);
+        Some(values)
+    }
+
+    fn process_internal(&self, data: &Matrix, inverse: bool) -> Result<Matrix, FlucomaError> {
         if !self.is_fitted() {
-            return Err("normalizer is not fitted");
+            return Err(FlucomaError::NotFitted);
         }
-        if self.cols != Some(data.cols()) {
-            return Err("cols must match fitted feature dimension");
+        let columns = self.columns.as_deref();
+        let selected = select_columns(data, columns)?;
+        if self.cols != Some(selected.cols()) {
+            return Err(FlucomaError::InvalidParameter(
+                "cols must match fitted feature dimension",
+            ));
         }
-        let mut out = Matrix::new(data.rows(), data.cols());
+        let mut out = Matrix::new(selected.rows(), selected.cols());
         normalization_process(
             self.inner,
-            data.data().as_ptr(),
-            data.rows() as FlucomaIndex,
-            data.cols() as FlucomaIndex,
+            selected.data().as_ptr(),
+            selected.rows() as FlucomaIndex,
+            selected.cols() as FlucomaIndex,
             out.data_mut().as_mut_ptr(),
             inverse,
         );
-        Ok(out)
+        if !inverse {
+            self.apply_out_of_range(&mut out)?;
+        }
+        Ok(replace_columns(data, columns, out))
+    }
+
+    fn apply_out_of_range(&self, out: &mut Matrix) -> Result<(), FlucomaError> {
+        let (lo, hi) = (self.min.min(self.max), self.min.max(self.max));
+        match self.out_of_range {
+            OutOfRangePolicy::PassThrough => {}
+            OutOfRangePolicy::Clamp => out.data_mut().iter_mut().for_each(|x| *x = x.clamp(lo, hi)),
+            OutOfRangePolicy::Error => {
+                // Allow for rounding at the edges of the fitted range.
+                let tolerance = (hi - lo) * 1e-9;
+                if let Some(index) = out
+                    .data()
+                    .iter()
+                    .position(|x| *x < lo - tolerance || *x > hi + tolerance)
+                {
+                    let (row, col) = (index / out.cols(), index % out.cols());
+                    let col = self.columns.as_ref().map_or(col, |columns| columns[col]);
+                    return Err(FlucomaError::OutOfRange { row, col });
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ColumnMasked for Normalize {
+    fn set_columns(&mut self, columns: Option<Vec<usize>>) -> Result<(), FlucomaError> {
+        if update_mask(&mut self.columns, columns)? {
+            self.cols = None;
+            self.running = None;
+        }
+        Ok(())
+    }
+
+    fn columns(&self) -> Option<&[usize]> {
+        self.columns.as_deref()
+    }
+}
+
+impl Transform for Normalize {
+    fn fit(&mut self, data: &Matrix) -> Result<(), FlucomaError> {
+        Normalize::fit(self, data)
+    }
+
+    fn transform(&self, data: &Matrix) -> Result<Matrix, FlucomaError> {
+        Normalize::transform(self, data)
+    }
+
+    fn inverse_transform(&self, data: &Matrix) -> Result<Matrix, FlucomaError> {
+        Normalize::inverse_transform(self, data)
+    }
+
+    fn is_fitted(&self) -> bool {
+        Normalize::is_fitted(self)
     }
 }
 
@@ -125,11 +302,109 @@ mod tests {
         }
     }
 
+    #[test]
+    fn fitted_parameters_describe_transform() {
+        let data = Matrix::from_vec(vec![1.0, 10.0, 3.0, 20.0, 5.0, 30.0], 3, 2).unwrap();
+        let mut n = Normalize::new(-1.0, 1.0).unwrap();
+        assert_eq!(n.data_min(), None);
+        n.fit(&data).unwrap();
+        assert_eq!(n.data_min(), Some(vec![1.0, 10.0]));
+        assert_eq!(n.data_max(), Some(vec![5.0, 30.0]));
+        assert_eq!(n.output_range(), (-1.0, 1.0));
+    }
+
+    #[test]
+    fn partial_fit_rejects_mismatched_cols() {
+        let mut n = Normalize::new(0.0, 1.0).unwrap();
+        n.partial_fit(&Matrix::from_vec(vec![1.0, 10.0, 5.0, 30.0], 2, 2).unwrap())
+            .unwrap();
+        n.partial_fit(&Matrix::from_vec(vec![-1.0, 20.0], 1, 2).unwrap())
+            .unwrap();
+        assert_eq!(n.data_min(), Some(vec![-1.0, 10.0]));
+        assert_eq!(
+            n.partial_fit(&Matrix::new(1, 3)).unwrap_err(),
+            FlucomaError::InvalidParameter("cols must match previous chunks")
+        );
+    }
+
+    #[test]
+    fn chunked_partial_fit_matches_fit() {
+        // The middle column is constant, so its fitted range is zero.
+        let data = Matrix::from_vec(
+            vec![
+                1.0, 4.0, 10.0, 3.0, 4.0, 20.0, 5.0, 4.0, 30.0, 7.0, 4.0, -4.0,
+            ],
+            4,
+            3,
+        )
+        .unwrap();
+        let mut whole = Normalize::new(0.0, 1.0).unwrap();
+        whole.fit(&data).unwrap();
+        let mut chunked = Normalize::new(0.0, 1.0).unwrap();
+        for rows in data.data().chunks(6) {
+            chunked
+                .partial_fit(&Matrix::from_vec(rows.to_vec(), 2, 3).unwrap())
+                .unwrap();
+        }
+        assert_eq!(chunked.data_min(), whole.data_min());
+        assert_eq!(chunked.data_max(), whole.data_max());
+        let expected = whole.transform(&data).unwrap();
+        let got = chunked.transform(&data).unwrap();
+        for (a, b) in expected.data().iter().zip(got.data()) {
+            assert!(
+                b.is_finite() && (a - b).abs() < 1e-9,
+                "expected {a}, got {b}"
+            );
+        }
+    }
+
+    #[test]
+    fn column_mask_leaves_other_columns_untouched() {
+        let data = Matrix::from_vec(vec![1.0, 7.0, 10.0, 3.0, 2.0, 30.0], 2, 3).unwrap();
+        let mut n = Normalize::new(0.0, 1.0).unwrap();
+        n.set_columns(Some(vec![2, 0])).unwrap();
+        let scaled = n.fit_transform(&data).unwrap();
+        assert_eq!(scaled.data(), &[0.0, 7.0, 0.0, 1.0, 2.0, 1.0]);
+        assert_eq!(n.data_min(), Some(vec![10.0, 1.0]));
+        assert_eq!(n.inverse_transform(&scaled).unwrap(), data);
+
+        // Reordering the mask would swap the fitted ranges between columns.
+        n.set_columns(Some(vec![2, 0])).unwrap();
+        assert!(n.is_fitted());
+        n.set_columns(Some(vec![0, 2])).unwrap();
+        assert!(!n.is_fitted());
+        assert_eq!(n.transform(&data).unwrap_err(), FlucomaError::NotFitted);
+    }
+
+    #[test]
+    fn out_of_range_policy_applies_to_new_points() {
+        let data = Matrix::from_vec(vec![0.0, 10.0], 2, 1).unwrap();
+        let outside = Matrix::from_vec(vec![-5.0, 5.0, 20.0], 3, 1).unwrap();
+        let mut n = Normalize::new(0.0, 1.0).unwrap();
+        n.fit(&data).unwrap();
+        assert_eq!(n.transform(&outside).unwrap().data(), &[-0.5, 0.5, 2.0]);
+
+        n.set_out_of_range(OutOfRangePolicy::Clamp);
+        assert_eq!(n.transform(&outside).unwrap().data(), &[0.0, 0.5, 1.0]);
+
+        n.set_out_of_range(OutOfRangePolicy::Error);
+        assert!(n.transform(&data).is_ok());
+        assert_eq!(
+            n.transform(&outside).unwrap_err(),
+            FlucomaError::OutOfRange { row: 0, col: 0 }
+        );
+        let late = Matrix::from_vec(vec![5.0, 20.0], 2, 1).unwrap();
+        assert_eq!(
+            n.transform(&late).unwrap_err(),
+            FlucomaError::OutOfRange { row: 1, col: 0 }
+        );
+    }
+
     #[test]
     fn transform_before_fit_fails() {
         let n = Normalize::new(0.0, 1.0).unwrap();
         let data = Matrix::from_vec(vec![1.0, 2.0], 1, 2).unwrap();
         let err = n.transform(&data).unwrap_err();
-        assert_eq!(err, "normalizer is not fitted");
+        assert_eq!(err, FlucomaError::NotFitted);
     }
 }
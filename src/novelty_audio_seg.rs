@@ -0,0 +1,422 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::chroma::{Chroma, ChromaNormalization};
+use crate::error::FlucomaError;
+use crate::frame_analyzer::FrameAnalyzer;
+use crate::loudness::Loudness;
+use crate::mfcc::Mfcc;
+use crate::novelty_seg::NoveltySlice;
+use crate::param_names::{display_name, parse_name};
+use crate::pitch::Pitch;
+use crate::sample_f32::widen;
+use crate::stft::{ComplexSpectrum, Stft, WindowType};
+
+const MFCC_COEFS: usize = 13;
+const MFCC_BANDS: usize = 40;
+const MFCC_LO_HZ: f64 = 20.0;
+const MFCC_HI_HZ: f64 = 20000.0;
+const CHROMA_BINS: usize = 12;
+const CHROMA_REF_HZ: f64 = 440.0;
+const PITCH_LO_HZ: f64 = 20.0;
+const PITCH_HI_HZ: f64 = 10000.0;
+
+// -------------------------------------------------------------------------------------------------
+
+/// Feature the novelty curve is computed on, matching the `algorithm`
+/// parameter of FluidNoveltySlice and FluidBufNoveltySlice.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum NoveltyAlgorithm {
+    /// Magnitude spectrum (`fft_size / 2 + 1` dimensions).
+    #[default]
+    Spectrum,
+    /// 13 MFCCs over 40 mel bands.
+    Mfcc,
+    /// 12-bin chroma, sum-normalised per frame.
+    Chroma,
+    /// Pitch and pitch confidence.
+    Pitch,
+    /// K-weighted loudness and true peak, in dB.
+    Loudness,
+}
+
+const NOVELTY_ALGORITHM_NAMES: &[(&str, NoveltyAlgorithm)] = &[
+    ("spectrum", NoveltyAlgorithm::Spectrum),
+    ("mfcc", NoveltyAlgorithm::Mfcc),
+    ("chroma", NoveltyAlgorithm::Chroma),
+    ("pitch", NoveltyAlgorithm::Pitch),
+    ("loudness", NoveltyAlgorithm::Loudness),
+];
+
+impl FromStr for NoveltyAlgorithm {
+    type Err = FlucomaError;
+
+    /// Parse a name such as `"mfcc"`, ignoring case and separators.
+    fn from_str(name: &str) -> Result<Self, FlucomaError> {
+        parse_name(name, NOVELTY_ALGORITHM_NAMES, "unknown novelty algorithm")
+    }
+}
+
+impl fmt::Display for NoveltyAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(display_name(self, NOVELTY_ALGORITHM_NAMES))
+    }
+}
+
+/// Configuration for [`NoveltyAudioSlice`].
+///
+/// Defaults match FluidNoveltySlice.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NoveltyAudioSliceConfig {
+    pub algorithm: NoveltyAlgorithm,
+    /// Size of the checkerboard kernel in frames (odd).
+    pub kernel_size: usize,
+    /// Novelty value above which a slice point is declared.
+    pub threshold: f64,
+    /// Median filter size applied to the novelty curve in frames (odd).
+    pub filter_size: usize,
+    /// Minimum frames between successive slice points.
+    pub min_slice_length: usize,
+    pub window_size: usize,
+    pub fft_size: usize,
+}
+
+impl Default for NoveltyAudioSliceConfig {
+    fn default() -> Self {
+        Self {
+            algorithm: NoveltyAlgorithm::Spectrum,
+            kernel_size: 3,
+            threshold: 0.5,
+            filter_size: 1,
+            min_slice_length: 2,
+            window_size: 1024,
+            fft_size: 1024,
+        }
+    }
+}
+
+/// Novelty slicing of raw audio frames.
+///
+/// Owns the STFT and the feature stage selected by
+/// [`NoveltyAudioSliceConfig::algorithm`] and feeds the features to
+/// [`NoveltySlice`], so callers pass windows of audio instead of building the
+/// feature stream themselves, as with FluidNoveltySlice.
+///
+/// Detections are reported [`latency_frames`](NoveltyAudioSlice::latency_frames)
+/// frames after the frame they belong to.
+///
+/// See <https://learn.flucoma.org/reference/noveltyslice>
+///
+/// ```rust,no_run
+/// use flucoma_rs::segmentation::{NoveltyAlgorithm, NoveltyAudioSlice, NoveltyAudioSliceConfig};
+///
+/// let mut slicer = NoveltyAudioSlice::new(
+///     NoveltyAudioSliceConfig {
+///         algorithm: NoveltyAlgorithm::Chroma,
+///         ..Default::default()
+///     },
+///     44100.0,
+/// )
+/// .unwrap();
+/// let frame = vec![0.0f64; 1024];
+/// if slicer.process_frame(&frame) > 0.0 {
+///     println!("slice");
+/// }
+/// ```
+pub struct NoveltyAudioSlice {
+    config: NoveltyAudioSliceConfig,
+    sample_rate: f64,
+    features: FeatureExtractor,
+    slicer: NoveltySlice,
+    scratch: Vec<f64>,
+}
+
+impl NoveltyAudioSlice {
+    /// # Errors
+    /// Returns an error if the configuration or `sample_rate` is
+    /// invalid, or an analyser cannot be created.
+    pub fn new(config: NoveltyAudioSliceConfig, sample_rate: f64) -> Result<Self, FlucomaError> {
+        if sample_rate <= 0.0 {
+            return Err(FlucomaError::InvalidParameter("sample_rate must be > 0"));
+        }
+        let features = FeatureExtractor::new(&config, sample_rate)?;
+        let slicer = NoveltySlice::new(config.kernel_size, features.n_dims(), config.filter_size)?;
+        let scratch = Vec::with_capacity(config.window_size);
+        Ok(Self {
+            config,
+            sample_rate,
+            features,
+            slicer,
+            scratch,
+        })
+    }
+
+    pub fn config(&self) -> &NoveltyAudioSliceConfig {
+        &self.config
+    }
+
+    /// Process one window of audio. The features are computed into buffers
+    /// allocated on construction, so no allocation happens per call.
+    ///
+    /// Returns 1.0 when a slice point is detected, 0.0 otherwise.
+    ///
+    /// # Panics
+    /// Panics if `frame.len() != window_size`.
+    pub fn process_frame(&mut self, frame: &[f64]) -> f64 {
+        let values = self.features.process(frame, self.sample_rate);
+        self.slicer
+            .process_frame(values, self.config.threshold, self.config.min_slice_length)
+    }
+
+    /// Like [`NoveltyAudioSlice::process_frame`], for f32 audio. The samples
+    /// are widened into a buffer reserved for one window on construction
+    /// and reused, so this does not allocate per call either.
+    ///
+    /// # Panics
+    /// Panics if `frame.len() != window_size`.
+    pub fn process_frame_f32(&mut self, frame: &[f32]) -> f64 {
+        let mut scratch = std::mem::take(&mut self.scratch);
+        let value = self.process_frame(widen(frame, &mut scratch));
+        self.scratch = scratch;
+        value
+    }
+
+    /// Novelty curve value computed by the last call to
+    /// [`NoveltyAudioSlice::process_frame`]; see [`NoveltySlice::novelty`].
+    pub fn novelty(&self) -> f64 {
+        self.slicer.novelty()
+    }
+
+    /// Number of frames between a change in the audio and its detection:
+    /// the delay of the novelty kernel, the median filter and peak picking.
+    /// Frames are fed at the caller's hop, so the delay in samples is this
+    /// many hops plus one analysis window.
+    pub fn latency_frames(&self) -> usize {
+        self.slicer.latency_frames()
+    }
+
+    /// Dimensionality of the features fed to the novelty curve.
+    pub fn n_dims(&self) -> usize {
+        self.features.n_dims()
+    }
+}
+
+impl Clone for NoveltyAudioSlice {
+    fn clone(&self) -> Self {
+        Self::new(self.config.clone(), self.sample_rate)
+            .expect("settings were validated on construction")
+    }
+}
+
+impl FrameAnalyzer for NoveltyAudioSlice {
+    type Input = [f64];
+    type Params = ();
+    type Output = f64;
+
+    fn process(&mut self, frame: &[f64], _params: &()) -> f64 {
+        self.process_frame(frame)
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Per-frame feature extraction for the selected [`NoveltyAlgorithm`],
+/// writing into buffers allocated on construction.
+struct FeatureExtractor {
+    stage: FeatureStage,
+    values: Vec<f64>,
+}
+
+enum FeatureStage {
+    /// Features computed from the magnitude spectrum of the frame.
+    Spectral {
+        stft: Stft,
+        spectrum: ComplexSpectrum,
+        magnitudes: Vec<f64>,
+        feature: SpectralFeature,
+    },
+    /// Loudness runs on the audio frame directly.
+    Loudness(Loudness),
+}
+
+enum SpectralFeature {
+    Magnitudes,
+    Mfcc(Mfcc),
+    Chroma(Chroma),
+    Pitch(Pitch),
+}
+
+impl FeatureExtractor {
+    fn new(config: &NoveltyAudioSliceConfig, sample_rate: f64) -> Result<Self, FlucomaError> {
+        if config.algorithm == NoveltyAlgorithm::Loudness {
+            return Ok(Self {
+                stage: FeatureStage::Loudness(Loudness::new(config.window_size, sample_rate)?),
+                values: vec![0.0; 2],
+            });
+        }
+        // The hop is irrelevant to frame-by-frame analysis.
+        let stft = Stft::new(
+            config.window_size,
+            config.fft_size,
+            config.window_size,
+            WindowType::Hann,
+        )?;
+        let n_bins = stft.num_bins();
+        let (feature, n_dims) = match config.algorithm {
+            NoveltyAlgorithm::Mfcc => (
+                SpectralFeature::Mfcc(Mfcc::new(
+                    MFCC_COEFS,
+                    MFCC_BANDS,
+                    n_bins,
+                    MFCC_LO_HZ,
+                    MFCC_HI_HZ.min(sample_rate / 2.0),
+                    sample_rate,
+                    config.window_size,
+                )?),
+                MFCC_COEFS,
+            ),
+            NoveltyAlgorithm::Chroma => (
+                SpectralFeature::Chroma(Chroma::new(
+                    CHROMA_BINS,
+                    n_bins,
+                    CHROMA_REF_HZ,
+                    sample_rate,
+                )?),
+                CHROMA_BINS,
+            ),
+            NoveltyAlgorithm::Pitch => (SpectralFeature::Pitch(Pitch::new(n_bins)?), 2),
+            NoveltyAlgorithm::Spectrum | NoveltyAlgorithm::Loudness => {
+                (SpectralFeature::Magnitudes, n_bins)
+            }
+        };
+        Ok(Self {
+            stage: FeatureStage::Spectral {
+                stft,
+                spectrum: ComplexSpectrum::zeros(n_bins),
+                magnitudes: vec![0.0; n_bins],
+                feature,
+            },
+            values: vec![0.0; n_dims],
+        })
+    }
+
+    fn n_dims(&self) -> usize {
+        self.values.len()
+    }
+
+    fn process(&mut self, frame: &[f64], sample_rate: f64) -> &[f64] {
+        let values = &mut self.values;
+        match &mut self.stage {
+            FeatureStage::Loudness(loudness) => {
+                let result = loudness.process_frame(frame, true, true);
+                values[0] = result.loudness_db;
+                values[1] = result.peak_db;
+            }
+            FeatureStage::Spectral {
+                stft,
+                spectrum,
+                magnitudes,
+                feature,
+            } => {
+                stft.process_into(frame, spectrum);
+                spectrum.magnitudes_into(magnitudes);
+                match feature {
+                    SpectralFeature::Magnitudes => values.copy_from_slice(magnitudes),
+                    SpectralFeature::Mfcc(mfcc) => mfcc.process_into(magnitudes, values),
+                    SpectralFeature::Chroma(chroma) => chroma.process_into(
+                        magnitudes,
+                        0.0,
+                        sample_rate / 2.0,
+                        ChromaNormalization::Sum,
+                        values,
+                    ),
+                    SpectralFeature::Pitch(pitch) => {
+                        let result =
+                            pitch.process_frame(magnitudes, PITCH_LO_HZ, PITCH_HI_HZ, sample_rate);
+                        values[0] = result.frequency_hz;
+                        values[1] = result.confidence;
+                    }
+                }
+            }
+        }
+        &self.values
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn novelty_audio_slice_detects_change() {
+        let mut slicer = NoveltyAudioSlice::new(
+            NoveltyAudioSliceConfig {
+                kernel_size: 9,
+                threshold: 0.1,
+                ..Default::default()
+            },
+            44100.0,
+        )
+        .unwrap();
+        assert_eq!(slicer.n_dims(), 513);
+        assert_eq!(slicer.latency_frames(), 5);
+        let quiet = vec![0.0f64; 1024];
+        let loud: Vec<f64> = (0..1024).map(|i| (i as f64 * 0.3).sin()).collect();
+        let detections: f64 = (0..40)
+            .map(|i| slicer.process_frame(if i < 20 { &quiet } else { &loud }))
+            .sum();
+        assert!(detections >= 1.0);
+    }
+
+    #[test]
+    fn novelty_audio_slice_feature_dims() {
+        let frame: Vec<f64> = (0..1024).map(|i| (i as f64 * 0.05).sin()).collect();
+        for (algorithm, n_dims) in [
+            (NoveltyAlgorithm::Spectrum, 513),
+            (NoveltyAlgorithm::Mfcc, MFCC_COEFS),
+            (NoveltyAlgorithm::Chroma, CHROMA_BINS),
+            (NoveltyAlgorithm::Pitch, 2),
+            (NoveltyAlgorithm::Loudness, 2),
+        ] {
+            let mut slicer = NoveltyAudioSlice::new(
+                NoveltyAudioSliceConfig {
+                    algorithm,
+                    ..Default::default()
+                },
+                44100.0,
+            )
+            .unwrap();
+            assert_eq!(slicer.n_dims(), n_dims, "{algorithm}");
+            for _ in 0..4 {
+                slicer.process_frame(&frame);
+            }
+            assert!(slicer.novelty().is_finite(), "{algorithm}");
+        }
+    }
+
+    #[test]
+    fn novelty_audio_slice_f32_matches_f64() {
+        let config = NoveltyAudioSliceConfig {
+            kernel_size: 9,
+            threshold: 0.1,
+            ..Default::default()
+        };
+        let mut wide = NoveltyAudioSlice::new(config.clone(), 44100.0).unwrap();
+        let mut narrow = NoveltyAudioSlice::new(config, 44100.0).unwrap();
+        let quiet = vec![0.0f32; 1024];
+        let loud: Vec<f32> = (0..1024).map(|i| (i as f32 * 0.3).sin()).collect();
+        for i in 0..30 {
+            let frame = if i < 15 { &quiet } else { &loud };
+            let widened: Vec<f64> = frame.iter().map(|&x| f64::from(x)).collect();
+            assert_eq!(
+                narrow.process_frame_f32(frame),
+                wide.process_frame(&widened)
+            );
+            assert_eq!(narrow.novelty(), wide.novelty());
+        }
+    }
+}
@@ -3,6 +3,9 @@ use flucoma_sys::{
     novelty_feature_process_frame,
 };
 
+use crate::error::{check_len, FlucomaError};
+use crate::frame_analyzer::FrameAnalyzer;
+
 // -------------------------------------------------------------------------------------------------
 
 /// Novelty-curve feature extractor for feature streams.
@@ -17,7 +20,9 @@ use flucoma_sys::{
 /// See <https://learn.flucoma.org/reference/noveltyfeature>
 pub struct Novelty {
     inner: *mut u8,
+    kernel_size: usize,
     n_dims: usize,
+    filter_size: usize,
 }
 
 unsafe impl Send for Novelty {}
@@ -31,26 +36,30 @@ impl Novelty {
     /// * `filter_size`  - Median filter size applied to the novelty curve (must be odd, >= 1).
     ///
     /// # Errors
-    /// Returns an error string if parameters are invalid or allocation fails.
+    /// Returns an error if parameters are invalid or allocation fails.
     pub fn new(
         kernel_size: usize,
         n_dims: usize,
         filter_size: usize,
-    ) -> Result<Self, &'static str> {
+    ) -> Result<Self, FlucomaError> {
         if kernel_size == 0 || kernel_size.is_multiple_of(2) {
-            return Err("kernel_size must be odd and > 0");
+            return Err(FlucomaError::InvalidParameter(
+                "kernel_size must be odd and > 0",
+            ));
         }
         if n_dims == 0 {
-            return Err("n_dims must be > 0");
+            return Err(FlucomaError::InvalidParameter("n_dims must be > 0"));
         }
         if filter_size == 0 || filter_size.is_multiple_of(2) {
-            return Err("filter_size must be odd and > 0");
+            return Err(FlucomaError::InvalidParameter(
+                "filter_size must be odd and > 0",
+            ));
         }
 
         let inner =
             novelty_feature_create(kernel_size as isize, n_dims as isize, filter_size as isize);
         if inner.is_null() {
-            return Err("failed to create Novelty instance");
+            return Err(FlucomaError::AllocationFailed);
         }
         novelty_feature_init(
             inner,
@@ -58,7 +67,12 @@ impl Novelty {
             filter_size as isize,
             n_dims as isize,
         );
-        Ok(Self { inner, n_dims })
+        Ok(Self {
+            inner,
+            kernel_size,
+            n_dims,
+            filter_size,
+        })
     }
 
     /// Process one feature frame and return the novelty value.
@@ -81,6 +95,14 @@ impl Novelty {
         novelty_feature_process_frame(self.inner, input.as_ptr(), input.len() as isize)
     }
 
+    /// Like [`Novelty::process_frame`], but returns
+    /// [`FlucomaError::InvalidShape`] instead of panicking if
+    /// `input.len() != n_dims`.
+    pub fn try_process_frame(&mut self, input: &[f64]) -> Result<f64, FlucomaError> {
+        check_len(self.n_dims, input.len())?;
+        Ok(self.process_frame(input))
+    }
+
     /// Dimensionality of the input feature vectors.
     pub fn n_dims(&self) -> usize {
         self.n_dims
@@ -93,6 +115,23 @@ impl Drop for Novelty {
     }
 }
 
+impl Clone for Novelty {
+    fn clone(&self) -> Self {
+        Self::new(self.kernel_size, self.n_dims, self.filter_size)
+            .expect("settings were validated on construction")
+    }
+}
+
+impl FrameAnalyzer for Novelty {
+    type Input = [f64];
+    type Params = ();
+    type Output = f64;
+
+    fn process(&mut self, input: &[f64], _params: &()) -> f64 {
+        self.process_frame(input)
+    }
+}
+
 // -------------------------------------------------------------------------------------------------
 
 #[cfg(test)]
@@ -1,7 +1,14 @@
+use std::io::Read;
+
 use flucoma_sys::{
-    novelty_seg_create, novelty_seg_destroy, novelty_seg_init, novelty_seg_process_frame,
+    novelty_seg_candidate, novelty_seg_create, novelty_seg_destroy, novelty_seg_init,
+    novelty_seg_process_frame,
 };
 
+use crate::error::{check_len, FlucomaError};
+use crate::feature_stream::FeatureStreamReader;
+use crate::replay::ReplayHistory;
+
 // -------------------------------------------------------------------------------------------------
 
 /// Novelty-curve segmenter for feature streams.
@@ -12,12 +19,35 @@ use flucoma_sys::{
 ///
 /// Each call to `process_frame` takes a feature vector of length `n_dims` (e.g.
 /// mel bands or MFCCs). The algorithm computes a self-similarity novelty curve
-/// internally and returns 1.0 when a peak above `threshold` is detected.
+/// internally and returns 1.0 when a peak above `threshold` is detected. The
+/// curve value behind each decision is available from
+/// [`NoveltySlice::novelty`].
 ///
 /// See <https://learn.flucoma.org/reference/noveltyslice>
 pub struct NoveltySlice {
     inner: *mut u8,
     n_dims: usize,
+    kernel_size: usize,
+    filter_size: usize,
+    /// Enough recent frames to rebuild the kernel, filter and peak-picking
+    /// history in [`NoveltySlice::restore_state`]; only kept while state
+    /// recording is on.
+    recent: Option<ReplayHistory>,
+    threshold: f64,
+    min_slice_length: usize,
+}
+
+/// Snapshot of a [`NoveltySlice`], from [`NoveltySlice::save_state`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NoveltySliceState {
+    kernel_size: usize,
+    n_dims: usize,
+    filter_size: usize,
+    /// Recent feature frames, oldest first, row-major.
+    frames: Vec<f64>,
+    threshold: f64,
+    min_slice_length: usize,
 }
 
 unsafe impl Send for NoveltySlice {}
@@ -31,25 +61,29 @@ impl NoveltySlice {
     /// * `filter_size`  - Median filter size applied to the novelty curve (must be odd, >= 1)
     ///
     /// # Errors
-    /// Returns an error string if parameters are invalid or allocation fails.
+    /// Returns an error if parameters are invalid or allocation fails.
     pub fn new(
         kernel_size: usize,
         n_dims: usize,
         filter_size: usize,
-    ) -> Result<Self, &'static str> {
+    ) -> Result<Self, FlucomaError> {
         if kernel_size == 0 || kernel_size.is_multiple_of(2) {
-            return Err("kernel_size must be odd and > 0");
+            return Err(FlucomaError::InvalidParameter(
+                "kernel_size must be odd and > 0",
+            ));
         }
         if n_dims == 0 {
-            return Err("n_dims must be > 0");
+            return Err(FlucomaError::InvalidParameter("n_dims must be > 0"));
         }
         if filter_size == 0 || filter_size.is_multiple_of(2) {
-            return Err("filter_size must be odd and > 0");
+            return Err(FlucomaError::InvalidParameter(
+                "filter_size must be odd and > 0",
+            ));
         }
 
         let inner = novelty_seg_create(kernel_size as isize, n_dims as isize, filter_size as isize);
         if inner.is_null() {
-            return Err("failed to create NoveltySlice instance");
+            return Err(FlucomaError::AllocationFailed);
         }
         novelty_seg_init(
             inner,
@@ -57,7 +91,15 @@ impl NoveltySlice {
             filter_size as isize,
             n_dims as isize,
         );
-        Ok(Self { inner, n_dims })
+        Ok(Self {
+            inner,
+            n_dims,
+            kernel_size,
+            filter_size,
+            recent: None,
+            threshold: 0.0,
+            min_slice_length: 0,
+        })
     }
 
     /// Process one feature frame.
@@ -79,6 +121,11 @@ impl NoveltySlice {
             input.len(),
             self.n_dims
         );
+        if let Some(recent) = &mut self.recent {
+            recent.extend(input.iter().copied());
+        }
+        self.threshold = threshold;
+        self.min_slice_length = min_slice_length;
         novelty_seg_process_frame(
             self.inner,
             input.as_ptr(),
@@ -88,10 +135,149 @@ impl NoveltySlice {
         )
     }
 
+    /// Like [`NoveltySlice::process_frame`], but returns
+    /// [`FlucomaError::InvalidShape`] instead of panicking if
+    /// `input.len() != n_dims`.
+    pub fn try_process_frame(
+        &mut self,
+        input: &[f64],
+        threshold: f64,
+        min_slice_length: usize,
+    ) -> Result<f64, FlucomaError> {
+        check_len(self.n_dims, input.len())?;
+        Ok(self.process_frame(input, threshold, min_slice_length))
+    }
+
+    /// Keep the recent feature frames so [`NoveltySlice::save_state`] can
+    /// capture them. Off by default, so segmenters that are never
+    /// checkpointed do not copy every frame.
+    pub fn set_state_recording(&mut self, enabled: bool) {
+        match (enabled, &self.recent) {
+            (true, None) => {
+                let frames = self.kernel_size + self.filter_size + 2;
+                self.recent = Some(ReplayHistory::new(frames * self.n_dims));
+            }
+            (false, Some(_)) => self.recent = None,
+            _ => {}
+        }
+    }
+
+    /// Capture the recent feature history so segmentation can be resumed
+    /// later, e.g. after restarting a long-running analysis.
+    ///
+    /// Only frames processed while state recording is enabled (see
+    /// [`NoveltySlice::set_state_recording`]) are captured.
+    pub fn save_state(&self) -> NoveltySliceState {
+        NoveltySliceState {
+            kernel_size: self.kernel_size,
+            n_dims: self.n_dims,
+            filter_size: self.filter_size,
+            frames: self
+                .recent
+                .as_ref()
+                .map_or_else(Vec::new, |recent| recent.latest(recent.len())),
+            threshold: self.threshold,
+            min_slice_length: self.min_slice_length,
+        }
+    }
+
+    /// Resume from a state captured by [`NoveltySlice::save_state`], by
+    /// re-initialising the segmenter and replaying the recorded frames.
+    ///
+    /// # Errors
+    /// Returns an error if the state was saved from a segmenter with
+    /// different settings.
+    pub fn restore_state(&mut self, state: &NoveltySliceState) -> Result<(), FlucomaError> {
+        if (state.kernel_size, state.n_dims, state.filter_size)
+            != (self.kernel_size, self.n_dims, self.filter_size)
+        {
+            return Err(FlucomaError::InvalidParameter(
+                "state was saved with a different kernel_size, n_dims or filter_size",
+            ));
+        }
+        novelty_seg_init(
+            self.inner,
+            self.kernel_size as isize,
+            self.filter_size as isize,
+            self.n_dims as isize,
+        );
+        if let Some(recent) = &mut self.recent {
+            recent.clear();
+        }
+        for frame in state.frames.chunks_exact(self.n_dims) {
+            self.process_frame(frame, state.threshold, state.min_slice_length);
+        }
+        Ok(())
+    }
+
+    /// Run the segmenter over a serialized feature stream, reading at most
+    /// `chunk_frames` frames into memory at a time.
+    ///
+    /// Returns the indices of the frames (counted from the start of the stream)
+    /// at which slice points were declared. Memory use is bounded by
+    /// `chunk_frames * n_dims`, so multi-hour feature streams can be segmented
+    /// without loading the full feature matrix.
+    ///
+    /// # Arguments
+    /// * `reader`          - Stream of frames written by
+    ///   [`FeatureStreamWriter`](crate::data::FeatureStreamWriter).
+    /// * `threshold`       - Novelty value above which a slice point is declared.
+    /// * `min_slice_length`- Minimum frames between successive slice points.
+    /// * `chunk_frames`    - Number of frames decoded per read.
+    ///
+    /// # Errors
+    /// Returns an error if the stream dimensionality differs from `n_dims`,
+    /// `chunk_frames` is zero, or reading the stream fails.
+    pub fn process_stream<R: Read>(
+        &mut self,
+        reader: &mut FeatureStreamReader<R>,
+        threshold: f64,
+        min_slice_length: usize,
+        chunk_frames: usize,
+    ) -> Result<Vec<usize>, FlucomaError> {
+        check_len(self.n_dims, reader.n_dims())?;
+        if chunk_frames == 0 {
+            return Err(FlucomaError::InvalidParameter("chunk_frames must be > 0"));
+        }
+        let mut slices = Vec::new();
+        let mut chunk = Vec::with_capacity(chunk_frames * self.n_dims);
+        let mut index = reader.frames_read();
+        while reader
+            .read_chunk(&mut chunk, chunk_frames)
+            .map_err(|e| FlucomaError::Io(e.to_string()))?
+            > 0
+        {
+            for frame in chunk.chunks_exact(self.n_dims) {
+                if self.process_frame(frame, threshold, min_slice_length) > 0.0 {
+                    slices.push(index);
+                }
+                index += 1;
+            }
+        }
+        Ok(slices)
+    }
+
+    /// Novelty curve value compared against `threshold` by the last call to
+    /// [`NoveltySlice::process_frame`] (0.0 before the first frames).
+    ///
+    /// Peak picking needs one frame of look-ahead, so this is the smoothed
+    /// curve value of the previous frame: the candidate peak the segmenter
+    /// just accepted or rejected. Useful for displaying or logging the curve
+    /// that drives the slicing.
+    pub fn novelty(&self) -> f64 {
+        novelty_seg_candidate(self.inner)
+    }
+
     /// Dimensionality of the input feature vectors.
     pub fn n_dims(&self) -> usize {
         self.n_dims
     }
+
+    /// Number of frames between a change in the features and its detection:
+    /// the delay of the novelty kernel, the median filter and peak picking.
+    pub fn latency_frames(&self) -> usize {
+        self.kernel_size / 2 + self.filter_size / 2 + 1
+    }
 }
 
 impl Drop for NoveltySlice {
@@ -100,6 +286,15 @@ impl Drop for NoveltySlice {
     }
 }
 
+impl Clone for NoveltySlice {
+    fn clone(&self) -> Self {
+        let mut clone = Self::new(self.kernel_size, self.n_dims, self.filter_size)
+            .expect("settings were validated on construction");
+        clone.set_state_recording(self.recent.is_some());
+        clone
+    }
+}
+
 // -------------------------------------------------------------------------------------------------
 
 #[cfg(test)]
@@ -133,5 +328,114 @@ mod tests {
             triggered,
             "alternating signal should trigger at least one novelty slice"
         );
+        assert!(slice.novelty() > 0.0);
+    }
+
+    #[test]
+    fn novelty_seg_stream_matches_frame_processing() {
+        use crate::data::FeatureStreamWriter;
+
+        const N_DIMS: usize = 4;
+        let frames: Vec<Vec<f64>> = (0..60)
+            .map(|i| vec![if i % 20 < 10 { 0.0 } else { 1.0 }; N_DIMS])
+            .collect();
+        let mut writer = FeatureStreamWriter::new(Vec::new(), N_DIMS).unwrap();
+        for frame in &frames {
+            writer.write_frame(frame).unwrap();
+        }
+        let bytes = writer.finish().unwrap();
+
+        let mut direct = NoveltySlice::new(3, N_DIMS, 1).unwrap();
+        let expected: Vec<usize> = frames
+            .iter()
+            .enumerate()
+            .filter(|(_, f)| direct.process_frame(f, 0.01, 1) > 0.0)
+            .map(|(i, _)| i)
+            .collect();
+
+        let mut streamed = NoveltySlice::new(3, N_DIMS, 1).unwrap();
+        let mut reader = FeatureStreamReader::new(bytes.as_slice()).unwrap();
+        let slices = streamed.process_stream(&mut reader, 0.01, 1, 7).unwrap();
+        assert_eq!(slices, expected);
+
+        let mut wrong_dims = NoveltySlice::new(3, N_DIMS + 1, 1).unwrap();
+        let mut reader = FeatureStreamReader::new(bytes.as_slice()).unwrap();
+        assert_eq!(
+            wrong_dims.process_stream(&mut reader, 0.01, 1, 7),
+            Err(FlucomaError::InvalidShape {
+                expected: N_DIMS + 1,
+                got: N_DIMS
+            })
+        );
+    }
+
+    #[test]
+    fn restored_novelty_seg_matches_uninterrupted_stream() {
+        const N_DIMS: usize = 4;
+        let frames: Vec<Vec<f64>> = (0..80)
+            .map(|i| vec![if i % 20 < 10 { 0.0 } else { 1.0 }; N_DIMS])
+            .collect();
+        let run = |slice: &mut NoveltySlice, frames: &[Vec<f64>]| -> Vec<(f64, f64)> {
+            frames
+                .iter()
+                .map(|f| (slice.process_frame(f, 0.01, 1), slice.novelty()))
+                .collect()
+        };
+
+        let mut original = NoveltySlice::new(3, N_DIMS, 3).unwrap();
+        original.set_state_recording(true);
+        run(&mut original, &frames[..35]);
+        let state = original.save_state();
+        let expected = run(&mut original, &frames[35..]);
+        assert!(expected.iter().any(|&(detected, _)| detected > 0.0));
+
+        let mut resumed = NoveltySlice::new(3, N_DIMS, 3).unwrap();
+        resumed.restore_state(&state).unwrap();
+        assert_eq!(run(&mut resumed, &frames[35..]), expected);
+
+        let unrecorded = NoveltySlice::new(3, N_DIMS, 3).unwrap();
+        assert!(unrecorded.save_state().frames.is_empty());
+    }
+
+    #[test]
+    fn novelty_seg_detects_step_after_latency() {
+        const N_DIMS: usize = 4;
+        let mut slice = NoveltySlice::new(5, N_DIMS, 3).unwrap();
+        let step = 20;
+        let detected = (0..60)
+            .position(|i| {
+                let frame = vec![if i >= step { 1.0 } else { 0.0 }; N_DIMS];
+                slice.process_frame(&frame, 0.01, 1) > 0.0
+            })
+            .expect("step not detected");
+        assert!(detected >= slice.latency_frames());
+        let change = detected - slice.latency_frames();
+        assert!(
+            change.abs_diff(step) <= 1,
+            "detected change at frame {change}"
+        );
+    }
+
+    #[test]
+    fn novelty_is_the_delayed_novelty_feature_curve() {
+        // Guards the private-member accessor behind NoveltySlice::novelty.
+        use crate::novelty_feature::Novelty;
+
+        const N_DIMS: usize = 4;
+        let mut slice = NoveltySlice::new(5, N_DIMS, 3).unwrap();
+        let mut curve = Novelty::new(5, N_DIMS, 3).unwrap();
+        let mut previous = 0.0;
+        for i in 0..60 {
+            let frame: Vec<f64> = (0..N_DIMS)
+                .map(|d| ((i * (d + 1)) as f64 * 0.7).sin())
+                .collect();
+            slice.process_frame(&frame, 0.5, 1);
+            assert!(
+                (slice.novelty() - previous).abs() < 1e-9,
+                "frame {i}: {} vs {previous}",
+                slice.novelty()
+            );
+            previous = curve.process_frame(&frame);
+        }
     }
 }
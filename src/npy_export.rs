@@ -0,0 +1,233 @@
+use std::io::Write;
+
+use crate::dataset::DataSet;
+use crate::error::FlucomaError;
+use crate::matrix::Matrix;
+
+// -------------------------------------------------------------------------------------------------
+
+/// Write a matrix as a NumPy `.npy` array of shape `(rows, cols)` and dtype
+/// `float64`, readable with `numpy.load`.
+///
+/// # Errors
+/// Returns [`FlucomaError::Io`] if writing fails.
+pub fn write_npy<W: Write>(mut writer: W, matrix: &Matrix) -> Result<(), FlucomaError> {
+    writer
+        .write_all(&npy_f64(matrix.data(), &[matrix.rows(), matrix.cols()]))
+        .map_err(io_error)
+}
+
+/// Write a data set as a NumPy `.npz` archive, readable with `numpy.load`.
+///
+/// The archive holds three arrays:
+/// * `data`    - `float64` array of shape `(len, cols)`.
+/// * `ids`     - unicode array of the point ids, in row order.
+/// * `columns` - unicode array of the column names.
+///
+/// Entries are stored uncompressed, and the archive must be smaller than
+/// 4 GiB.
+///
+/// # Errors
+/// Returns [`FlucomaError::Io`] if writing fails.
+pub fn write_npz<W: Write>(writer: W, dataset: &DataSet) -> Result<(), FlucomaError> {
+    let entries = [
+        (
+            "data.npy",
+            npy_f64(dataset.data(), &[dataset.len(), dataset.cols()]),
+        ),
+        ("ids.npy", npy_unicode(dataset.ids())),
+        ("columns.npy", npy_unicode(dataset.column_names())),
+    ];
+    write_stored_zip(writer, &entries)
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// `.npy` version 1.0 header for `descr` and `shape`, padded so the data
+/// starts on a 64 byte boundary.
+fn npy_header(descr: &str, shape: &[usize]) -> Vec<u8> {
+    let shape = match shape {
+        [n] => format!("({n},)"),
+        _ => format!(
+            "({})",
+            shape
+                .iter()
+                .map(usize::to_string)
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    };
+    let mut dict = format!("{{'descr': '{descr}', 'fortran_order': False, 'shape': {shape}, }}");
+    let unpadded = 10 + dict.len() + 1;
+    dict.extend(std::iter::repeat_n(
+        ' ',
+        unpadded.next_multiple_of(64) - unpadded,
+    ));
+    dict.push('\n');
+
+    let mut out = b"\x93NUMPY\x01\x00".to_vec();
+    out.extend_from_slice(&(dict.len() as u16).to_le_bytes());
+    out.extend_from_slice(dict.as_bytes());
+    out
+}
+
+fn npy_f64(data: &[f64], shape: &[usize]) -> Vec<u8> {
+    let mut out = npy_header("<f8", shape);
+    out.reserve(data.len() * 8);
+    for value in data {
+        out.extend_from_slice(&value.to_le_bytes());
+    }
+    out
+}
+
+/// Fixed-width UTF-32 (`<U{width}`) array of strings.
+fn npy_unicode<S: AsRef<str>>(strings: &[S]) -> Vec<u8> {
+    let width = strings
+        .iter()
+        .map(|s| s.as_ref().chars().count())
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    let mut out = npy_header(&format!("<U{width}"), &[strings.len()]);
+    for s in strings {
+        let s = s.as_ref();
+        for ch in s.chars() {
+            out.extend_from_slice(&(ch as u32).to_le_bytes());
+        }
+        let padding = width - s.chars().count();
+        out.extend(std::iter::repeat_n(0u8, padding * 4));
+    }
+    out
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Write a ZIP archive of uncompressed (stored) entries.
+fn write_stored_zip<W: Write>(
+    mut writer: W,
+    entries: &[(&str, Vec<u8>)],
+) -> Result<(), FlucomaError> {
+    // DOS date 1980-01-01, time 00:00.
+    const DOS_DATE: u16 = 0x21;
+    let too_large = || FlucomaError::Io("npz archive exceeds 4 GiB".to_owned());
+
+    let mut offset = 0u32;
+    let mut central = Vec::new();
+    for (name, data) in entries {
+        let crc = crc32(data);
+        let size = u32::try_from(data.len()).map_err(|_| too_large())?;
+        let mut local = Vec::with_capacity(30 + name.len());
+        local.extend_from_slice(&0x0403_4b50u32.to_le_bytes());
+        local.extend_from_slice(&20u16.to_le_bytes()); // version needed
+        local.extend_from_slice(&0u16.to_le_bytes()); // flags
+        local.extend_from_slice(&0u16.to_le_bytes()); // method: stored
+        local.extend_from_slice(&0u16.to_le_bytes()); // time
+        local.extend_from_slice(&DOS_DATE.to_le_bytes());
+        local.extend_from_slice(&crc.to_le_bytes());
+        local.extend_from_slice(&size.to_le_bytes());
+        local.extend_from_slice(&size.to_le_bytes());
+        local.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        local.extend_from_slice(&0u16.to_le_bytes()); // extra length
+        local.extend_from_slice(name.as_bytes());
+        writer.write_all(&local).map_err(io_error)?;
+        writer.write_all(data).map_err(io_error)?;
+
+        central.extend_from_slice(&0x0201_4b50u32.to_le_bytes());
+        central.extend_from_slice(&20u16.to_le_bytes()); // version made by
+        central.extend_from_slice(&local[4..30]);
+        central.extend_from_slice(&0u16.to_le_bytes()); // comment length
+        central.extend_from_slice(&0u16.to_le_bytes()); // disk number
+        central.extend_from_slice(&0u16.to_le_bytes()); // internal attributes
+        central.extend_from_slice(&0u32.to_le_bytes()); // external attributes
+        central.extend_from_slice(&offset.to_le_bytes());
+        central.extend_from_slice(name.as_bytes());
+
+        offset = u32::try_from(local.len() + data.len())
+            .ok()
+            .and_then(|len| offset.checked_add(len))
+            .ok_or_else(too_large)?;
+    }
+
+    let count = entries.len() as u16;
+    let mut end = Vec::with_capacity(22);
+    end.extend_from_slice(&0x0605_4b50u32.to_le_bytes());
+    end.extend_from_slice(&0u16.to_le_bytes()); // disk number
+    end.extend_from_slice(&0u16.to_le_bytes()); // central directory disk
+    end.extend_from_slice(&count.to_le_bytes());
+    end.extend_from_slice(&count.to_le_bytes());
+    end.extend_from_slice(&(central.len() as u32).to_le_bytes());
+    end.extend_from_slice(&offset.to_le_bytes());
+    end.extend_from_slice(&0u16.to_le_bytes()); // comment length
+    writer.write_all(&central).map_err(io_error)?;
+    writer.write_all(&end).map_err(io_error)?;
+    writer.flush().map_err(io_error)
+}
+
+/// CRC-32 (IEEE 802.3), as used by ZIP.
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+fn io_error(e: std::io::Error) -> FlucomaError {
+    FlucomaError::Io(e.to_string())
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn npy_header_is_aligned_and_describes_shape() {
+        let mut out = Vec::new();
+        let m = Matrix::from_vec(vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0], 2, 3).unwrap();
+        write_npy(&mut out, &m).unwrap();
+        assert_eq!(&out[..8], b"\x93NUMPY\x01\x00");
+        let header_len = u16::from_le_bytes([out[8], out[9]]) as usize;
+        assert_eq!((10 + header_len) % 64, 0);
+        let header = std::str::from_utf8(&out[10..10 + header_len]).unwrap();
+        assert!(header.starts_with("{'descr': '<f8', 'fortran_order': False, 'shape': (2, 3), }"));
+        assert!(header.ends_with('\n'));
+        assert_eq!(out.len(), 10 + header_len + 6 * 8);
+        assert_eq!(
+            &out[10 + header_len..10 + header_len + 8],
+            &1.0f64.to_le_bytes()
+        );
+
+        let ids = npy_unicode(&["ab", "c"]);
+        let header_len = u16::from_le_bytes([ids[8], ids[9]]) as usize;
+        let header = std::str::from_utf8(&ids[10..10 + header_len]).unwrap();
+        assert!(header.contains("'descr': '<U2'"));
+        assert_eq!(ids.len(), 10 + header_len + 2 * 2 * 4);
+    }
+
+    #[test]
+    fn npz_is_a_stored_zip_of_three_arrays() {
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+
+        let m = Matrix::from_vec(vec![1.0, 2.0], 1, 2).unwrap();
+        let mut ds = DataSet::from_matrix(&["slice-0"], &m).unwrap();
+        ds.set_column_names(&["centroid", "flatness"]).unwrap();
+        let mut out = Vec::new();
+        write_npz(&mut out, &ds).unwrap();
+        assert_eq!(&out[..4], b"PK\x03\x04");
+        let end = &out[out.len() - 22..];
+        assert_eq!(&end[..4], b"PK\x05\x06");
+        assert_eq!(u16::from_le_bytes([end[10], end[11]]), 3);
+        for name in ["data.npy", "ids.npy", "columns.npy"] {
+            assert!(out.windows(name.len()).any(|w| w == name.as_bytes()));
+        }
+    }
+}
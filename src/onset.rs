@@ -1,5 +1,13 @@
+use std::fmt;
+use std::str::FromStr;
+
 use flucoma_sys::{onset_create, onset_destroy, onset_init, onset_process_frame};
 
+use crate::error::FlucomaError;
+use crate::frame_analyzer::FrameAnalyzer;
+use crate::param_names::{display_name, parse_name};
+use crate::sample_f32::widen;
+
 // -------------------------------------------------------------------------------------------------
 
 /// Onset detection function selector.
@@ -7,6 +15,7 @@ use flucoma_sys::{onset_create, onset_destroy, onset_init, onset_process_frame};
 /// Each variant computes a different measure of spectral change between frames.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 #[repr(isize)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum OnsetFunction {
     /// Power of the difference between successive spectra.
     #[default]
@@ -31,8 +40,63 @@ pub enum OnsetFunction {
     NormPower = 9,
 }
 
+const ONSET_FUNCTION_NAMES: &[(&str, OnsetFunction)] = &[
+    ("power", OnsetFunction::PowerSpectrum),
+    ("hfc", OnsetFunction::HighFrequency),
+    ("complex", OnsetFunction::ComplexDomain),
+    ("rectified-complex", OnsetFunction::RectifiedComplex),
+    ("phase-dev", OnsetFunction::PhaseDev),
+    ("weighted-phase-dev", OnsetFunction::WeightedPhaseDev),
+    ("mkl", OnsetFunction::ModKL),
+    ("itakura-saito", OnsetFunction::ItakuraSaito),
+    ("cosine", OnsetFunction::Cosine),
+    ("norm-power", OnsetFunction::NormPower),
+    ("power-spectrum", OnsetFunction::PowerSpectrum),
+    ("high-frequency", OnsetFunction::HighFrequency),
+    ("complex-domain", OnsetFunction::ComplexDomain),
+    ("mod-kl", OnsetFunction::ModKL),
+];
+
+impl FromStr for OnsetFunction {
+    type Err = FlucomaError;
+
+    /// Parse a name such as `"power"` or `"hfc"`, ignoring case and separators.
+    fn from_str(name: &str) -> Result<Self, FlucomaError> {
+        parse_name(name, ONSET_FUNCTION_NAMES, "unknown onset function")
+    }
+}
+
+impl fmt::Display for OnsetFunction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(display_name(self, ONSET_FUNCTION_NAMES))
+    }
+}
+
 // -------------------------------------------------------------------------------------------------
 
+/// Per-frame settings of [`Onset`], defaulting to the FluCoMa object's
+/// defaults.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OnsetParams {
+    /// Detection function.
+    pub function: OnsetFunction,
+    /// Median filter length applied to the detection function.
+    pub filter_size: usize,
+    /// Frame distance used by the differential functions (0 = adjacent).
+    pub frame_delta: usize,
+}
+
+impl Default for OnsetParams {
+    fn default() -> Self {
+        Self {
+            function: OnsetFunction::PowerSpectrum,
+            filter_size: 5,
+            frame_delta: 0,
+        }
+    }
+}
+
 /// Computes frame-by-frame onset detection values from audio.
 ///
 /// Two-phase setup:
@@ -40,13 +104,18 @@ pub enum OnsetFunction {
 /// 2. Call [`Onset::process_frame`] per frame.
 ///
 /// The algorithm maintains internal frame history for differential functions.
+/// On dense mixed material, whitening the input first with
+/// [`AdaptiveWhitening`](crate::analyzation::AdaptiveWhitening) often makes
+/// onsets stand out much more clearly.
 ///
 /// See <https://learn.flucoma.org/reference/onsetfeature>
 pub struct Onset {
     inner: *mut u8,
     window_size: usize,
     fft_size: usize,
+    filter_size: usize,
     max_filter_size: usize,
+    scratch: Vec<f64>,
 }
 
 unsafe impl Send for Onset {}
@@ -61,22 +130,24 @@ impl Onset {
     ///   (use 0 or 1 to disable, minimum effective value is 3).
     ///
     /// # Errors
-    /// Returns an error string if parameters are invalid.
+    /// Returns an error if parameters are invalid.
     pub fn new(
         window_size: usize,
         fft_size: usize,
         filter_size: usize,
-    ) -> Result<Self, &'static str> {
+    ) -> Result<Self, FlucomaError> {
         if window_size == 0 {
-            return Err("window_size must be > 0");
+            return Err(FlucomaError::InvalidParameter("window_size must be > 0"));
         }
         if fft_size < window_size {
-            return Err("fft_size must be >= window_size");
+            return Err(FlucomaError::InvalidParameter(
+                "fft_size must be >= window_size",
+            ));
         }
         let max_filter = filter_size.max(3);
         let inner = onset_create(fft_size as isize, max_filter as isize);
         if inner.is_null() {
-            return Err("failed to create OnsetDetectionFunctions instance");
+            return Err(FlucomaError::AllocationFailed);
         }
         onset_init(
             inner,
@@ -88,7 +159,9 @@ impl Onset {
             inner,
             window_size,
             fft_size,
+            filter_size,
             max_filter_size: max_filter,
+            scratch: Vec::with_capacity(window_size),
         })
     }
 
@@ -137,6 +210,64 @@ impl Onset {
         )
     }
 
+    /// Like [`Onset::process_frame`], but returns an error instead of
+    /// panicking.
+    ///
+    /// # Errors
+    /// Returns [`FlucomaError::InvalidShape`] if `input` is too short, or
+    /// [`FlucomaError::InvalidParameter`] if `filter_size > max_filter_size`.
+    pub fn try_process_frame(
+        &mut self,
+        input: &[f64],
+        function: OnsetFunction,
+        filter_size: usize,
+        frame_delta: usize,
+    ) -> Result<f64, FlucomaError> {
+        let min_len = self.window_size + frame_delta;
+        if input.len() < min_len {
+            return Err(FlucomaError::InvalidShape {
+                expected: min_len,
+                got: input.len(),
+            });
+        }
+        if filter_size > self.max_filter_size {
+            return Err(FlucomaError::InvalidParameter(
+                "filter_size must be <= max_filter_size",
+            ));
+        }
+        Ok(self.process_frame(input, function, filter_size, frame_delta))
+    }
+
+    /// Like [`Onset::process_frame`], for f32 audio. The samples are
+    /// widened into an internal buffer, so no allocation happens per call
+    /// once it has grown to the input length.
+    ///
+    /// # Panics
+    /// Panics under the same conditions as [`Onset::process_frame`].
+    pub fn process_frame_f32(
+        &mut self,
+        input: &[f32],
+        function: OnsetFunction,
+        filter_size: usize,
+        frame_delta: usize,
+    ) -> f64 {
+        let mut scratch = std::mem::take(&mut self.scratch);
+        let input = widen(input, &mut scratch);
+        let value = self.process_frame(input, function, filter_size, frame_delta);
+        self.scratch = scratch;
+        value
+    }
+
+    /// Clear the detection function history, as if newly created.
+    pub fn reset(&mut self) {
+        onset_init(
+            self.inner,
+            self.window_size as isize,
+            self.fft_size as isize,
+            self.filter_size as isize,
+        );
+    }
+
     /// Analysis window size in samples.
     pub fn window_size(&self) -> usize {
         self.window_size
@@ -154,6 +285,28 @@ impl Drop for Onset {
     }
 }
 
+impl Clone for Onset {
+    fn clone(&self) -> Self {
+        Self::new(self.window_size, self.fft_size, self.filter_size)
+            .expect("settings were validated on construction")
+    }
+}
+
+impl FrameAnalyzer for Onset {
+    type Input = [f64];
+    type Params = OnsetParams;
+    type Output = f64;
+
+    fn process(&mut self, input: &[f64], params: &OnsetParams) -> f64 {
+        self.process_frame(
+            input,
+            params.function,
+            params.filter_size,
+            params.frame_delta,
+        )
+    }
+}
+
 // -------------------------------------------------------------------------------------------------
 
 #[cfg(test)]
@@ -1,9 +1,43 @@
-use flucoma_sys::{onset_seg_create, onset_seg_destroy, onset_seg_init, onset_seg_process_frame};
+use flucoma_sys::{
+    onset_seg_create, onset_seg_destroy, onset_seg_init, onset_seg_process_frame, onset_seg_value,
+};
 
+use crate::error::FlucomaError;
+use crate::frame_analyzer::FrameAnalyzer;
 pub use crate::onset::OnsetFunction;
+use crate::sample_f32::widen;
 
 // -------------------------------------------------------------------------------------------------
 
+/// Per-frame settings of [`OnsetSlice`], defaulting to the FluCoMa object's
+/// defaults.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OnsetSliceParams {
+    /// Onset detection function.
+    pub function: OnsetFunction,
+    /// Median filter size for this frame (0 to disable).
+    pub filter_size: usize,
+    /// Detection threshold. Values above trigger an onset.
+    pub threshold: f64,
+    /// Minimum number of frames between successive onsets.
+    pub debounce: usize,
+    /// History offset in samples (0 for most functions).
+    pub frame_delta: usize,
+}
+
+impl Default for OnsetSliceParams {
+    fn default() -> Self {
+        Self {
+            function: OnsetFunction::PowerSpectrum,
+            filter_size: 5,
+            threshold: 0.5,
+            debounce: 2,
+            frame_delta: 0,
+        }
+    }
+}
+
 /// Detects onsets in an audio stream and returns a binary decision per frame.
 ///
 /// Two-phase setup:
@@ -19,7 +53,9 @@ pub struct OnsetSlice {
     inner: *mut u8,
     window_size: usize,
     fft_size: usize,
+    filter_size: usize,
     max_filter_size: usize,
+    scratch: Vec<f64>,
 }
 
 unsafe impl Send for OnsetSlice {}
@@ -34,22 +70,24 @@ impl OnsetSlice {
     ///   (use 0 or 1 to disable, minimum effective value is 3).
     ///
     /// # Errors
-    /// Returns an error string if parameters are invalid or allocation fails.
+    /// Returns an error if parameters are invalid or allocation fails.
     pub fn new(
         window_size: usize,
         fft_size: usize,
         filter_size: usize,
-    ) -> Result<Self, &'static str> {
+    ) -> Result<Self, FlucomaError> {
         if window_size == 0 {
-            return Err("window_size must be > 0");
+            return Err(FlucomaError::InvalidParameter("window_size must be > 0"));
         }
         if fft_size < window_size {
-            return Err("fft_size must be >= window_size");
+            return Err(FlucomaError::InvalidParameter(
+                "fft_size must be >= window_size",
+            ));
         }
         let max_filter = filter_size.max(3);
         let inner = onset_seg_create(fft_size as isize, max_filter as isize);
         if inner.is_null() {
-            return Err("failed to create OnsetSlice instance");
+            return Err(FlucomaError::AllocationFailed);
         }
         onset_seg_init(
             inner,
@@ -61,7 +99,9 @@ impl OnsetSlice {
             inner,
             window_size,
             fft_size,
+            filter_size,
             max_filter_size: max_filter,
+            scratch: Vec::with_capacity(window_size),
         })
     }
 
@@ -114,6 +154,89 @@ impl OnsetSlice {
         )
     }
 
+    /// Like [`OnsetSlice::process_frame`], but returns an error instead of
+    /// panicking.
+    ///
+    /// # Errors
+    /// Returns [`FlucomaError::InvalidShape`] if `input` is too short, or
+    /// [`FlucomaError::InvalidParameter`] if `filter_size > max_filter_size`.
+    pub fn try_process_frame(
+        &mut self,
+        input: &[f64],
+        function: OnsetFunction,
+        filter_size: usize,
+        threshold: f64,
+        debounce: usize,
+        frame_delta: usize,
+    ) -> Result<f64, FlucomaError> {
+        let min_len = self.window_size + frame_delta;
+        if input.len() < min_len {
+            return Err(FlucomaError::InvalidShape {
+                expected: min_len,
+                got: input.len(),
+            });
+        }
+        if filter_size > self.max_filter_size {
+            return Err(FlucomaError::InvalidParameter(
+                "filter_size must be <= max_filter_size",
+            ));
+        }
+        Ok(self.process_frame(
+            input,
+            function,
+            filter_size,
+            threshold,
+            debounce,
+            frame_delta,
+        ))
+    }
+
+    /// Like [`OnsetSlice::process_frame`], for f32 audio. The samples are
+    /// widened into an internal buffer, so no allocation happens per call
+    /// once it has grown to the input length.
+    ///
+    /// # Panics
+    /// Panics under the same conditions as [`OnsetSlice::process_frame`].
+    pub fn process_frame_f32(
+        &mut self,
+        input: &[f32],
+        function: OnsetFunction,
+        filter_size: usize,
+        threshold: f64,
+        debounce: usize,
+        frame_delta: usize,
+    ) -> f64 {
+        let mut scratch = std::mem::take(&mut self.scratch);
+        let input = widen(input, &mut scratch);
+        let value = self.process_frame(
+            input,
+            function,
+            filter_size,
+            threshold,
+            debounce,
+            frame_delta,
+        );
+        self.scratch = scratch;
+        value
+    }
+
+    /// Clear the detection history and debounce state, as if newly created.
+    pub fn reset(&mut self) {
+        onset_seg_init(
+            self.inner,
+            self.window_size as isize,
+            self.fft_size as isize,
+            self.filter_size as isize,
+        );
+    }
+
+    /// Filtered detection function value computed by the last call to
+    /// [`OnsetSlice::process_frame`] (0.0 before the first frame): the value
+    /// compared against `threshold`.
+    pub fn detection_value(&self) -> f64 {
+        onset_seg_value(self.inner)
+    }
+
     /// Analysis window size in samples.
     pub fn window_size(&self) -> usize {
         self.window_size
@@ -123,6 +246,12 @@ impl OnsetSlice {
     pub fn fft_size(&self) -> usize {
         self.fft_size
     }
+
+    /// Delay in samples between an onset in a stream and its detection: one
+    /// analysis window.
+    pub fn latency_samples(&self) -> usize {
+        self.window_size
+    }
 }
 
 impl Drop for OnsetSlice {
@@ -131,6 +260,30 @@ impl Drop for OnsetSlice {
     }
 }
 
+impl Clone for OnsetSlice {
+    fn clone(&self) -> Self {
+        Self::new(self.window_size, self.fft_size, self.filter_size)
+            .expect("settings were validated on construction")
+    }
+}
+
+impl FrameAnalyzer for OnsetSlice {
+    type Input = [f64];
+    type Params = OnsetSliceParams;
+    type Output = f64;
+
+    fn process(&mut self, input: &[f64], params: &OnsetSliceParams) -> f64 {
+        self.process_frame(
+            input,
+            params.function,
+            params.filter_size,
+            params.threshold,
+            params.debounce,
+            params.frame_delta,
+        )
+    }
+}
+
 // -------------------------------------------------------------------------------------------------
 
 #[cfg(test)]
@@ -157,4 +310,26 @@ mod tests {
         let val = slice.process_frame(&impulse, OnsetFunction::PowerSpectrum, 0, 0.01, 0, 0);
         assert!(val == 1.0 || val == 0.0, "expected 0.0 or 1.0, got {val}");
     }
+
+    #[test]
+    fn detection_value_matches_onset() {
+        // Guards the private-member accessor behind OnsetSlice::detection_value.
+        use crate::onset::Onset;
+
+        let mut slice = OnsetSlice::new(1024, 1024, 5).unwrap();
+        let mut odf = Onset::new(1024, 1024, 5).unwrap();
+        for i in 0..20 {
+            let gain = if i % 5 == 0 { 1.0 } else { 0.1 };
+            let frame: Vec<f64> = (0..1024)
+                .map(|n| gain * ((n + i * 512) as f64 * 0.03).sin())
+                .collect();
+            slice.process_frame(&frame, OnsetFunction::PowerSpectrum, 5, 0.5, 2, 0);
+            let expected = odf.process_frame(&frame, OnsetFunction::PowerSpectrum, 5, 0);
+            let value = slice.detection_value();
+            assert!(
+                (value - expected).abs() <= 1e-9 * expected.abs().max(1.0),
+                "frame {i}: {value} vs {expected}"
+            );
+        }
+    }
 }
@@ -0,0 +1,554 @@
+use crate::adaptive_threshold::{AdaptiveThreshold, ThresholdMode};
+use crate::error::FlucomaError;
+use crate::fft_settings::impl_fft_settings;
+use crate::onset::{Onset, OnsetFunction};
+use crate::onset_seg::OnsetSlice;
+use crate::replay::{replay_len, ReplayHistory};
+
+// -------------------------------------------------------------------------------------------------
+
+/// Configuration for [`OnsetSliceStream`].
+///
+/// Defaults match FluidOnsetSlice.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OnsetSliceStreamConfig {
+    /// Onset detection function.
+    pub function: OnsetFunction,
+    /// Detection threshold; an absolute value or a factor depending on
+    /// `threshold_mode`.
+    pub threshold: f64,
+    /// Whether `threshold` is absolute or relative to the recent detection
+    /// function.
+    pub threshold_mode: ThresholdMode,
+    /// Minimum number of frames between successive onsets.
+    pub min_slice_length: usize,
+    /// Median filter size for background subtraction (0 or 1 to disable).
+    pub filter_size: usize,
+    /// History offset in samples for the difference functions.
+    pub frame_delta: usize,
+    pub window_size: usize,
+    pub hop_size: usize,
+    pub fft_size: usize,
+}
+
+impl Default for OnsetSliceStreamConfig {
+    fn default() -> Self {
+        Self {
+            function: OnsetFunction::PowerSpectrum,
+            threshold: 0.5,
+            threshold_mode: ThresholdMode::Absolute,
+            min_slice_length: 2,
+            filter_size: 5,
+            frame_delta: 0,
+            window_size: 1024,
+            hop_size: 512,
+            fft_size: 1024,
+        }
+    }
+}
+
+impl_fft_settings!(OnsetSliceStreamConfig);
+
+/// An onset reported by [`OnsetSliceStream::process`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OnsetEvent {
+    /// Index in the current chunk of the sample that completed the detecting
+    /// frame, i.e. where a realtime trigger should fire.
+    pub offset: usize,
+    /// Absolute stream position of the first sample of the detecting frame.
+    pub position: u64,
+    /// Height of the detection function above the threshold in force at the
+    /// detecting frame, in the units of the detection function; never
+    /// negative, and larger values are stronger onsets (see
+    /// [`SlicePoint`](crate::segmentation::SlicePoint)).
+    pub confidence: f64,
+}
+
+/// Snapshot of an [`OnsetSliceStream`], from
+/// [`OnsetSliceStream::save_state`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OnsetSliceStreamState {
+    window_size: usize,
+    hop_size: usize,
+    frame_delta: usize,
+    consumed: u64,
+    until_hop: usize,
+    /// Recent audio, starting on a hop boundary.
+    recent: Vec<f64>,
+}
+
+/// Streaming onset slicing over arbitrarily sized audio chunks.
+///
+/// Wraps [`OnsetSlice`] with an internal ring buffer, so callers can feed
+/// whatever block size their audio callback delivers (e.g. 64 samples) and
+/// receive onset events as soon as a hop completes.
+///
+/// With [`ThresholdMode::Relative`] the detection function is computed by
+/// [`Onset`] and an onset fires when it rises above `threshold` times its
+/// running percentile, honouring `min_slice_length` as in the absolute mode.
+///
+/// ```rust,no_run
+/// use flucoma_rs::segmentation::{OnsetSliceStream, OnsetSliceStreamConfig};
+///
+/// let mut stream = OnsetSliceStream::new(OnsetSliceStreamConfig::default()).unwrap();
+/// let block = [0.0f64; 64];
+/// for event in stream.process(&block) {
+///     println!("onset at sample {}", event.position);
+/// }
+/// ```
+pub struct OnsetSliceStream {
+    detector: Detector,
+    config: OnsetSliceStreamConfig,
+    /// The most recent `window_size + frame_delta` samples, oldest first.
+    history: Vec<f64>,
+    until_hop: usize,
+    consumed: u64,
+    /// Enough recent audio to rebuild the detector state on restore; only
+    /// kept while state recording is on.
+    recent: Option<ReplayHistory>,
+}
+
+impl OnsetSliceStream {
+    /// # Errors
+    /// Returns an error if the configuration is invalid or the
+    /// segmenter cannot be created.
+    pub fn new(config: OnsetSliceStreamConfig) -> Result<Self, FlucomaError> {
+        if config.hop_size == 0 {
+            return Err(FlucomaError::InvalidParameter("hop_size must be > 0"));
+        }
+        let detector = match config.threshold_mode {
+            ThresholdMode::Absolute => Detector::Absolute {
+                slicer: OnsetSlice::new(config.window_size, config.fft_size, config.filter_size)?,
+            },
+            ThresholdMode::Relative {
+                history,
+                percentile,
+            } => Detector::Relative {
+                odf: Onset::new(config.window_size, config.fft_size, config.filter_size)?,
+                threshold: AdaptiveThreshold::new(history, percentile, config.threshold)?,
+                above: false,
+                since_onset: config.min_slice_length,
+            },
+        };
+        Ok(Self {
+            detector,
+            history: vec![0.0; config.window_size + config.frame_delta],
+            recent: None,
+            until_hop: config.hop_size,
+            consumed: 0,
+            config,
+        })
+    }
+
+    pub fn config(&self) -> &OnsetSliceStreamConfig {
+        &self.config
+    }
+
+    /// Consume a chunk of audio of any length and return the onsets detected
+    /// in the hops it completed.
+    pub fn process(&mut self, chunk: &[f64]) -> Vec<OnsetEvent> {
+        self.process_samples(chunk)
+    }
+
+    /// Like [`OnsetSliceStream::process`], for f32 audio.
+    pub fn process_f32(&mut self, chunk: &[f32]) -> Vec<OnsetEvent> {
+        self.process_samples(chunk)
+    }
+
+    /// Clear the buffered audio and detector state and restart stream
+    /// positions at zero.
+    pub fn reset(&mut self) {
+        self.history.fill(0.0);
+        self.until_hop = self.config.hop_size;
+        self.consumed = 0;
+        if let Some(recent) = &mut self.recent {
+            recent.clear();
+        }
+        match &mut self.detector {
+            Detector::Absolute { slicer } => slicer.reset(),
+            Detector::Relative {
+                odf,
+                threshold,
+                above,
+                since_onset,
+            } => {
+                odf.reset();
+                threshold.reset();
+                *above = false;
+                *since_onset = self.config.min_slice_length;
+            }
+        }
+    }
+
+    /// Delay in samples between the start of a detecting frame, reported as
+    /// [`OnsetEvent::position`], and the sample that completes it: one
+    /// analysis window.
+    pub fn latency_samples(&self) -> usize {
+        self.config.window_size
+    }
+
+    /// Total number of samples consumed since creation or the last reset.
+    pub fn samples_consumed(&self) -> u64 {
+        self.consumed
+    }
+
+    /// Keep enough recent audio for [`OnsetSliceStream::save_state`] to
+    /// rebuild the detector. Off by default, so streams that are never
+    /// checkpointed do not copy their input.
+    pub fn set_state_recording(&mut self, enabled: bool) {
+        match (enabled, &self.recent) {
+            (true, None) => {
+                let c = &self.config;
+                let adaptive_history = match c.threshold_mode {
+                    ThresholdMode::Absolute => 0,
+                    ThresholdMode::Relative { history, .. } => history,
+                };
+                let replay_hops = c.filter_size.max(c.min_slice_length) + adaptive_history + 2;
+                self.recent = Some(ReplayHistory::new(
+                    c.window_size + c.frame_delta + replay_hops * c.hop_size,
+                ));
+            }
+            (false, Some(_)) => self.recent = None,
+            _ => {}
+        }
+    }
+
+    /// Capture the stream position and recent audio so slicing can be
+    /// resumed later, e.g. after restarting a long-running session.
+    ///
+    /// Only audio processed while state recording is enabled (see
+    /// [`OnsetSliceStream::set_state_recording`]) is captured; otherwise
+    /// the state only restores the stream position.
+    pub fn save_state(&self) -> OnsetSliceStreamState {
+        let recent = self.recent.as_ref().map_or_else(Vec::new, |recent| {
+            recent.latest(replay_len(
+                recent.len(),
+                self.config.hop_size,
+                self.until_hop,
+            ))
+        });
+        OnsetSliceStreamState {
+            window_size: self.config.window_size,
+            hop_size: self.config.hop_size,
+            frame_delta: self.config.frame_delta,
+            consumed: self.consumed,
+            until_hop: self.until_hop,
+            recent,
+        }
+    }
+
+    /// Resume from a state captured by [`OnsetSliceStream::save_state`].
+    ///
+    /// The detector is reset and the recorded audio replayed through it, so
+    /// subsequent onsets and positions continue as if the stream had never
+    /// stopped.
+    ///
+    /// # Errors
+    /// Returns an error if the state was saved from a stream with a
+    /// different window, hop or frame delta.
+    pub fn restore_state(&mut self, state: &OnsetSliceStreamState) -> Result<(), FlucomaError> {
+        let c = &self.config;
+        if (state.window_size, state.hop_size, state.frame_delta)
+            != (c.window_size, c.hop_size, c.frame_delta)
+        {
+            return Err(FlucomaError::InvalidParameter(
+                "state was saved with a different window_size, hop_size or frame_delta",
+            ));
+        }
+        self.reset();
+        self.process_samples(&state.recent);
+        self.consumed = state.consumed;
+        self.until_hop = state.until_hop;
+        Ok(())
+    }
+
+    fn process_samples<S: Copy + Into<f64>>(&mut self, chunk: &[S]) -> Vec<OnsetEvent> {
+        if let Some(recent) = &mut self.recent {
+            recent.extend(chunk.iter().map(|&sample| sample.into()));
+        }
+        let mut events = Vec::new();
+        let mut offset = 0;
+        while offset < chunk.len() {
+            let take = self.until_hop.min(chunk.len() - offset);
+            self.push(&chunk[offset..offset + take]);
+            offset += take;
+            self.until_hop -= take;
+            if self.until_hop == 0 {
+                self.until_hop = self.config.hop_size;
+                let c = &self.config;
+                if let Some(confidence) = self.detector.process(&self.history, c) {
+                    events.push(OnsetEvent {
+                        offset: offset - 1,
+                        position: self.consumed.saturating_sub(c.window_size as u64),
+                        confidence,
+                    });
+                }
+            }
+        }
+        events
+    }
+
+    fn push<S: Copy + Into<f64>>(&mut self, samples: &[S]) {
+        let len = self.history.len();
+        let keep = len.saturating_sub(samples.len());
+        self.history.copy_within(len - keep.., 0);
+        let recent = &samples[samples.len().saturating_sub(len)..];
+        for (slot, &sample) in self.history[keep..].iter_mut().zip(recent) {
+            *slot = sample.into();
+        }
+        self.consumed += samples.len() as u64;
+    }
+}
+
+impl Clone for OnsetSliceStream {
+    fn clone(&self) -> Self {
+        let mut clone =
+            Self::new(self.config.clone()).expect("settings were validated on construction");
+        clone.set_state_recording(self.recent.is_some());
+        clone
+    }
+}
+
+/// Per-frame onset decision for either threshold mode.
+enum Detector {
+    Absolute {
+        slicer: OnsetSlice,
+    },
+    Relative {
+        odf: Onset,
+        threshold: AdaptiveThreshold,
+        /// Whether the previous frame was above its threshold.
+        above: bool,
+        /// Frames since the last onset.
+        since_onset: usize,
+    },
+}
+
+impl Detector {
+    /// Returns the confidence of an onset detected in `frame`, if any.
+    fn process(&mut self, frame: &[f64], c: &OnsetSliceStreamConfig) -> Option<f64> {
+        match self {
+            Detector::Absolute { slicer } => {
+                let detected = slicer.process_frame(
+                    frame,
+                    c.function,
+                    c.filter_size,
+                    c.threshold,
+                    c.min_slice_length,
+                    c.frame_delta,
+                );
+                (detected > 0.0).then(|| slicer.detection_value() - c.threshold)
+            }
+            Detector::Relative {
+                odf,
+                threshold,
+                above,
+                since_onset,
+            } => {
+                let value = odf.process_frame(frame, c.function, c.filter_size, c.frame_delta);
+                let limit = threshold.process(value);
+                let now_above = value > limit;
+                let detected = now_above && !*above && *since_onset >= c.min_slice_length;
+                *above = now_above;
+                *since_onset = if detected {
+                    0
+                } else {
+                    since_onset.saturating_add(1)
+                };
+                detected.then_some(value - limit)
+            }
+        }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn onset_stream_is_chunk_size_agnostic() {
+        let mut audio = vec![0.0f64; 16384];
+        for click in [4000, 10000] {
+            for (i, sample) in audio.iter_mut().enumerate().skip(click).take(400) {
+                *sample = (i as f64 * 0.7).sin();
+            }
+        }
+        let run = |chunk_size: usize| {
+            let mut stream = OnsetSliceStream::new(OnsetSliceStreamConfig::default()).unwrap();
+            let mut positions = Vec::new();
+            for chunk in audio.chunks(chunk_size) {
+                positions.extend(stream.process(chunk).iter().map(|e| e.position));
+            }
+            assert_eq!(stream.samples_consumed(), audio.len() as u64);
+            positions
+        };
+        let reference = run(512);
+        assert!(!reference.is_empty());
+        assert_eq!(run(64), reference);
+        assert_eq!(run(1000), reference);
+
+        let audio_f32: Vec<f32> = audio.iter().map(|&x| x as f32).collect();
+        let mut stream = OnsetSliceStream::new(OnsetSliceStreamConfig::default()).unwrap();
+        let positions: Vec<u64> = audio_f32
+            .chunks(300)
+            .flat_map(|chunk| stream.process_f32(chunk))
+            .map(|e| e.position)
+            .collect();
+        assert_eq!(positions, reference);
+    }
+
+    #[test]
+    fn onset_confidence_is_height_above_threshold() {
+        let mut audio = vec![0.0f64; 16384];
+        for (click, gain) in [(4000, 1.0), (10000, 0.2)] {
+            for (i, sample) in audio.iter_mut().enumerate().skip(click).take(400) {
+                *sample = gain * (i as f64 * 0.7).sin();
+            }
+        }
+        let config = OnsetSliceStreamConfig::default();
+        let mut stream = OnsetSliceStream::new(config.clone()).unwrap();
+        let events = stream.process(&audio);
+        let first = |from: u64| {
+            *events
+                .iter()
+                .find(|e| e.position + config.window_size as u64 >= from)
+                .unwrap_or_else(|| panic!("no onset after {from}: {events:?}"))
+        };
+        let (loud, soft) = (first(4000), first(10000));
+        assert!(soft.position < 10400, "{events:?}");
+        assert!(soft.confidence > 0.0, "{events:?}");
+        assert!(loud.confidence > soft.confidence, "{events:?}");
+
+        // The confidence is the slicer's own detection value at the
+        // detecting frame, less the threshold.
+        let mut slicer =
+            OnsetSlice::new(config.window_size, config.fft_size, config.filter_size).unwrap();
+        for event in &events {
+            let start = event.position as usize;
+            let frame_end = start + config.window_size;
+            let mut expected = None;
+            for end in (config.hop_size..=frame_end).step_by(config.hop_size) {
+                let mut frame = vec![0.0; config.window_size];
+                let from = end.saturating_sub(config.window_size);
+                frame[config.window_size - (end - from)..].copy_from_slice(&audio[from..end]);
+                slicer.process_frame(
+                    &frame,
+                    config.function,
+                    config.filter_size,
+                    config.threshold,
+                    config.min_slice_length,
+                    config.frame_delta,
+                );
+                expected = Some(slicer.detection_value() - config.threshold);
+            }
+            slicer =
+                OnsetSlice::new(config.window_size, config.fft_size, config.filter_size).unwrap();
+            let expected = expected.unwrap();
+            assert!(
+                (event.confidence - expected).abs() < 1e-9,
+                "{event:?} vs {expected}"
+            );
+        }
+    }
+
+    #[test]
+    fn onset_stream_reports_frame_start_and_resets() {
+        let mut audio = vec![0.0f64; 16384];
+        for click in [4000, 10000] {
+            for (i, sample) in audio.iter_mut().enumerate().skip(click).take(400) {
+                *sample = (i as f64 * 0.7).sin();
+            }
+        }
+        let mut stream = OnsetSliceStream::new(OnsetSliceStreamConfig::default()).unwrap();
+        let latency = stream.latency_samples() as u64;
+        let mut consumed = 0u64;
+        let mut events = Vec::new();
+        for chunk in audio.chunks(100) {
+            for event in stream.process(chunk) {
+                // The event fires on the sample completing its frame.
+                assert_eq!(consumed + event.offset as u64 + 1, event.position + latency);
+                events.push(event);
+            }
+            consumed += chunk.len() as u64;
+        }
+        for click in [4000, 10000] {
+            assert!(
+                events
+                    .iter()
+                    .any(|e| e.position <= click && click < e.position + latency),
+                "no frame covering {click}: {events:?}"
+            );
+        }
+
+        // Stop inside a burst so the detector holds state, then reset.
+        stream.process(&audio[..10200]);
+        stream.reset();
+        assert_eq!(stream.samples_consumed(), 0);
+        assert_eq!(stream.process(&audio), events);
+    }
+
+    #[test]
+    fn restored_onset_stream_resumes_detection() {
+        let mut audio = vec![0.0f64; 16384];
+        for click in [4000, 10000] {
+            for (i, sample) in audio.iter_mut().enumerate().skip(click).take(400) {
+                *sample = (i as f64 * 0.7).sin();
+            }
+        }
+        let positions =
+            |events: Vec<OnsetEvent>| -> Vec<u64> { events.iter().map(|e| e.position).collect() };
+        let mut stream = OnsetSliceStream::new(OnsetSliceStreamConfig::default()).unwrap();
+        stream.set_state_recording(true);
+        stream.process(&audio[..7000]);
+        let state = stream.save_state();
+        let expected = positions(stream.process(&audio[7000..]));
+        assert!(!expected.is_empty());
+
+        let mut resumed = OnsetSliceStream::new(OnsetSliceStreamConfig::default()).unwrap();
+        resumed.restore_state(&state).unwrap();
+        assert_eq!(resumed.samples_consumed(), 7000);
+        assert_eq!(positions(resumed.process(&audio[7000..])), expected);
+    }
+
+    #[test]
+    fn relative_threshold_ignores_level() {
+        let burst = |gain: f64| {
+            let mut audio = vec![0.0f64; 16384];
+            for (i, sample) in audio.iter_mut().enumerate() {
+                *sample = gain * 0.01 * ((i * 7919) % 101) as f64 / 101.0;
+            }
+            for (i, sample) in audio.iter_mut().enumerate().skip(8000).take(400) {
+                *sample = gain * (i as f64 * 0.7).sin();
+            }
+            audio
+        };
+        let config = OnsetSliceStreamConfig {
+            threshold: 4.0,
+            threshold_mode: ThresholdMode::Relative {
+                history: 16,
+                percentile: 50.0,
+            },
+            ..Default::default()
+        };
+        let mut positions = Vec::new();
+        for gain in [0.01, 1.0] {
+            let mut stream = OnsetSliceStream::new(config.clone()).unwrap();
+            let events = stream.process(&burst(gain));
+            positions.push(events.iter().map(|e| e.position).collect::<Vec<_>>());
+        }
+        assert!(!positions[0].is_empty());
+        assert_eq!(positions[0], positions[1]);
+
+        let audio = burst(1.0);
+        let mut stream = OnsetSliceStream::new(config).unwrap();
+        let events = stream.process(&audio);
+        stream.process(&audio[..8200]);
+        stream.reset();
+        assert_eq!(stream.process(&audio), events);
+    }
+}
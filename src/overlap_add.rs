@@ -0,0 +1,200 @@
+use crate::error::FlucomaError;
+use crate::spectrogram::window_values;
+use crate::stft::WindowType;
+
+// -------------------------------------------------------------------------------------------------
+
+/// Streaming overlap-add of synthesised frames.
+///
+/// Accumulates frames `hop_size` apart together with their normalisation
+/// window (the squared window, for frames analysed and resynthesised with
+/// the same window), and hands out each run of `hop_size` samples as soon as
+/// no later frame can overlap it. Output sample `k` after the first frame is
+/// aligned with sample `k` of that frame.
+///
+/// Use [`OverlapAdd::process_frame`] for [`Istft`](crate::fourier::Istft)
+/// output and [`OverlapAdd::process_frame_with_norm`] where the processor
+/// supplies its own normalisation window, as
+/// [`AudioTransport`](crate::transformation::AudioTransport) does.
+///
+/// ```rust
+/// use flucoma_rs::fourier::{OverlapAdd, WindowType};
+///
+/// let mut ola = OverlapAdd::new(8, 4, WindowType::Hann).unwrap();
+/// let frame = [1.0f64; 8];
+/// let first = ola.process_frame(&frame).to_vec();
+/// assert_eq!(first.len(), 4);
+/// let tail = ola.flush();
+/// assert_eq!(tail.len(), 4);
+/// ```
+#[derive(Debug, Clone)]
+pub struct OverlapAdd {
+    hop_size: usize,
+    norm_window: Vec<f64>,
+    audio: Vec<f64>,
+    norm: Vec<f64>,
+    output: Vec<f64>,
+}
+
+impl OverlapAdd {
+    /// Create an accumulator for frames of `frame_size` samples, `hop_size`
+    /// apart, normalised by the square of `window_type`.
+    ///
+    /// # Errors
+    /// Returns an error if `frame_size == 0` or `hop_size` is 0 or
+    /// larger than `frame_size`.
+    pub fn new(
+        frame_size: usize,
+        hop_size: usize,
+        window_type: WindowType,
+    ) -> Result<Self, FlucomaError> {
+        if frame_size == 0 {
+            return Err(FlucomaError::InvalidParameter("frame_size must be > 0"));
+        }
+        if hop_size == 0 || hop_size > frame_size {
+            return Err(FlucomaError::InvalidParameter(
+                "hop_size must be > 0 and <= frame_size",
+            ));
+        }
+        let norm_window = window_values(window_type, frame_size)
+            .into_iter()
+            .map(|w| w * w)
+            .collect();
+        Ok(Self {
+            hop_size,
+            norm_window,
+            audio: vec![0.0; frame_size],
+            norm: vec![0.0; frame_size],
+            output: vec![0.0; hop_size],
+        })
+    }
+
+    /// Add a frame normalised by the squared window and return the
+    /// `hop_size` samples it completes. The slice is valid until the next
+    /// call.
+    ///
+    /// # Panics
+    /// Panics if `frame.len() != frame_size`.
+    pub fn process_frame(&mut self, frame: &[f64]) -> &[f64] {
+        self.accumulate(frame, None);
+        self.advance()
+    }
+
+    /// Add a frame with its own normalisation window and return the
+    /// `hop_size` samples it completes. The slice is valid until the next
+    /// call.
+    ///
+    /// # Panics
+    /// Panics if `frame.len()` or `norm.len()` differs from `frame_size`.
+    pub fn process_frame_with_norm(&mut self, frame: &[f64], norm: &[f64]) -> &[f64] {
+        self.accumulate(frame, Some(norm));
+        self.advance()
+    }
+
+    /// Return the `frame_size - hop_size` samples still accumulating, as if
+    /// the signal ended, and clear the accumulator.
+    pub fn flush(&mut self) -> Vec<f64> {
+        let tail_len = self.audio.len() - self.hop_size;
+        let tail = self.audio[..tail_len]
+            .iter()
+            .zip(&self.norm)
+            .map(|(&sample, &n)| normalize(sample, n))
+            .collect();
+        self.reset();
+        tail
+    }
+
+    /// Clear the accumulated frames.
+    pub fn reset(&mut self) {
+        self.audio.fill(0.0);
+        self.norm.fill(0.0);
+    }
+
+    /// Frame length in samples.
+    pub fn frame_size(&self) -> usize {
+        self.audio.len()
+    }
+
+    /// Hop between frames in samples.
+    pub fn hop_size(&self) -> usize {
+        self.hop_size
+    }
+
+    fn accumulate(&mut self, frame: &[f64], norm: Option<&[f64]>) {
+        let norm = norm.unwrap_or(&self.norm_window);
+        assert_eq!(
+            frame.len(),
+            self.audio.len(),
+            "frame length ({}) must equal frame_size ({})",
+            frame.len(),
+            self.audio.len()
+        );
+        assert_eq!(
+            norm.len(),
+            self.norm.len(),
+            "norm length ({}) must equal frame_size ({})",
+            norm.len(),
+            self.norm.len()
+        );
+        for (acc, &sample) in self.audio.iter_mut().zip(frame) {
+            *acc += sample;
+        }
+        for (acc, &w) in self.norm.iter_mut().zip(norm) {
+            *acc += w;
+        }
+    }
+
+    fn advance(&mut self) -> &[f64] {
+        let hop = self.hop_size;
+        for (out, (&sample, &n)) in self
+            .output
+            .iter_mut()
+            .zip(self.audio.iter().zip(&self.norm))
+        {
+            *out = normalize(sample, n);
+        }
+        let len = self.audio.len();
+        self.audio.copy_within(hop.., 0);
+        self.audio[len - hop..].fill(0.0);
+        self.norm.copy_within(hop.., 0);
+        self.norm[len - hop..].fill(0.0);
+        &self.output
+    }
+}
+
+fn normalize(sample: f64, norm: f64) -> f64 {
+    if norm > 1e-10 {
+        sample / norm
+    } else {
+        0.0
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overlap_add_reconstructs_windowed_frames() {
+        let signal: Vec<f64> = (0..4096).map(|i| (i as f64 * 0.01).sin()).collect();
+        let window = window_values(WindowType::Hann, 512);
+        let mut ola = OverlapAdd::new(512, 128, WindowType::Hann).unwrap();
+        let mut output = Vec::new();
+        for start in (0..=signal.len() - 512).step_by(128) {
+            let frame: Vec<f64> = signal[start..start + 512]
+                .iter()
+                .zip(&window)
+                .map(|(s, w)| s * w * w)
+                .collect();
+            output.extend_from_slice(ola.process_frame(&frame));
+        }
+        output.extend(ola.flush());
+        assert_eq!(output.len(), signal.len());
+        for (a, b) in signal.iter().zip(&output).skip(512).take(3000) {
+            assert!((a - b).abs() < 1e-9, "{a} != {b}");
+        }
+        assert!(OverlapAdd::new(512, 1024, WindowType::Hann).is_err());
+    }
+}
@@ -0,0 +1,304 @@
+use crate::dataset::DataSet;
+use crate::error::FlucomaError;
+use crate::progress::Progress;
+
+// -------------------------------------------------------------------------------------------------
+
+/// Analyse `items` on all cores and return one result per item, in order.
+///
+/// Analysers wrap native instances that cannot be shared between threads,
+/// so `make` creates one analyser per rayon worker thread, up front, and
+/// `analyze` receives the current thread's analyser mutably together with
+/// the item, e.g. a file path or a slice of a buffer. `analyze` may itself
+/// use rayon; an item which starts on a thread whose analyser is already in
+/// use gets a new one from `make`.
+///
+/// Requires the `parallel` feature; without it this returns
+/// [`FlucomaError::FeatureDisabled`].
+///
+/// ```rust,no_run
+/// use flucoma_rs::analyzation::Loudness;
+/// use flucoma_rs::parallel::map_parallel;
+///
+/// let slices: Vec<Vec<f64>> = vec![vec![0.0; 1024]; 1000];
+/// let peaks = map_parallel(
+///     &slices,
+///     || Loudness::new(1024, 44100.0),
+///     |meter, slice| Ok(meter.process_frame(slice, true, true).peak_db),
+/// )
+/// .unwrap();
+/// ```
+///
+/// # Errors
+/// Returns the first error returned by `make` or `analyze`.
+pub fn map_parallel<T, A, R, M, F>(items: &[T], make: M, analyze: F) -> Result<Vec<R>, FlucomaError>
+where
+    T: Sync,
+    A: Send,
+    R: Send,
+    M: Fn() -> Result<A, FlucomaError> + Sync + Send,
+    F: Fn(&mut A, &T) -> Result<R, FlucomaError> + Sync + Send,
+{
+    map_parallel_with_progress(items, make, analyze, &mut Progress::new())
+}
+
+/// Like [`map_parallel`], reporting the fraction of items analysed after
+/// each item and stopping early if `progress` is cancelled.
+///
+/// # Errors
+/// Returns [`FlucomaError::Cancelled`] if cancelled, or as for
+/// [`map_parallel`].
+pub fn map_parallel_with_progress<T, A, R, M, F>(
+    items: &[T],
+    make: M,
+    analyze: F,
+    progress: &mut Progress,
+) -> Result<Vec<R>, FlucomaError>
+where
+    T: Sync,
+    A: Send,
+    R: Send,
+    M: Fn() -> Result<A, FlucomaError> + Sync + Send,
+    F: Fn(&mut A, &T) -> Result<R, FlucomaError> + Sync + Send,
+{
+    #[cfg(feature = "parallel")]
+    {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Mutex;
+
+        use rayon::prelude::*;
+
+        if items.is_empty() {
+            return Ok(Vec::new());
+        }
+        // One analyser slot per pool thread. Items take the analyser out of
+        // the slot rather than holding its lock while `analyze` runs, since
+        // nested rayon work in `analyze` can run another item on the same
+        // thread, which would then deadlock on the lock.
+        let slots = rayon::broadcast(|_| make().map(|analyzer| Mutex::new(Some(analyzer))))
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()?;
+        let done = AtomicUsize::new(0);
+        let progress = Mutex::new(progress);
+        let report = |progress: &Mutex<&mut Progress>| {
+            let done = done.fetch_add(1, Ordering::Relaxed) + 1;
+            let mut progress = progress.lock().unwrap_or_else(|err| err.into_inner());
+            progress.report(done as f64 / items.len() as f64)
+        };
+        items
+            .par_iter()
+            .map(|item| {
+                if progress
+                    .lock()
+                    .is_ok_and(|progress| progress.is_cancelled())
+                {
+                    return Err(FlucomaError::Cancelled);
+                }
+                let slot = &slots[rayon::current_thread_index().unwrap_or(0)];
+                let taken = slot.lock().unwrap_or_else(|err| err.into_inner()).take();
+                let mut analyzer = match taken {
+                    Some(analyzer) => analyzer,
+                    None => make()?,
+                };
+                let result = analyze(&mut analyzer, item);
+                slot.lock()
+                    .unwrap_or_else(|err| err.into_inner())
+                    .get_or_insert(analyzer);
+                let result = result?;
+                report(&progress)?;
+                Ok(result)
+            })
+            .collect()
+    }
+    #[cfg(not(feature = "parallel"))]
+    {
+        let _ = (items, make, analyze, progress);
+        Err(FlucomaError::FeatureDisabled("parallel"))
+    }
+}
+
+/// Analyse labelled items on all cores and collect the descriptor vectors
+/// into a [`DataSet`], in item order.
+///
+/// Each item is an `(id, input)` pair; `analyze` returns the descriptors for
+/// one input, which must have the same length for every item. See
+/// [`map_parallel`] for how analysers are created.
+///
+/// ```rust,no_run
+/// use flucoma_rs::analyzation::MelBands;
+/// use flucoma_rs::fourier::{Stft, WindowType};
+/// use flucoma_rs::parallel::analyze_to_dataset;
+///
+/// let slices: Vec<(String, Vec<f64>)> = (0..1000)
+///     .map(|i| (format!("slice-{i}"), vec![0.0; 1024]))
+///     .collect();
+/// let dataset = analyze_to_dataset(
+///     &slices,
+///     || {
+///         let stft = Stft::new(1024, 1024, 512, WindowType::Hann)?;
+///         let mel = MelBands::new(40, 513, 20.0, 20000.0, 44100.0, 1024)?;
+///         Ok((stft, mel))
+///     },
+///     |(stft, mel), slice| {
+///         let mags = stft.process_frame(slice).magnitudes();
+///         Ok(mel.process_frame(&mags, true, false, true))
+///     },
+/// )
+/// .unwrap();
+/// assert_eq!(dataset.cols(), 40);
+/// ```
+///
+/// # Errors
+/// Returns an error if `items` is empty, an id is repeated, descriptor
+/// lengths differ, or as for [`map_parallel`].
+pub fn analyze_to_dataset<I, T, A, M, F>(
+    items: &[(I, T)],
+    make: M,
+    analyze: F,
+) -> Result<DataSet, FlucomaError>
+where
+    I: AsRef<str> + Sync,
+    T: Sync,
+    A: Send,
+    M: Fn() -> Result<A, FlucomaError> + Sync + Send,
+    F: Fn(&mut A, &T) -> Result<Vec<f64>, FlucomaError> + Sync + Send,
+{
+    analyze_to_dataset_with_progress(items, make, analyze, &mut Progress::new())
+}
+
+/// Like [`analyze_to_dataset`], with progress reporting and cancellation as
+/// for [`map_parallel_with_progress`].
+///
+/// # Errors
+/// Returns [`FlucomaError::Cancelled`] if cancelled, or as for
+/// [`analyze_to_dataset`].
+pub fn analyze_to_dataset_with_progress<I, T, A, M, F>(
+    items: &[(I, T)],
+    make: M,
+    analyze: F,
+    progress: &mut Progress,
+) -> Result<DataSet, FlucomaError>
+where
+    I: AsRef<str> + Sync,
+    T: Sync,
+    A: Send,
+    M: Fn() -> Result<A, FlucomaError> + Sync + Send,
+    F: Fn(&mut A, &T) -> Result<Vec<f64>, FlucomaError> + Sync + Send,
+{
+    if items.is_empty() {
+        return Err(FlucomaError::InvalidParameter("items must not be empty"));
+    }
+    let points = map_parallel_with_progress(
+        items,
+        make,
+        |analyzer, (_, input)| analyze(analyzer, input),
+        progress,
+    )?;
+    let mut dataset = DataSet::new(points[0].len())?;
+    for ((id, _), point) in items.iter().zip(&points) {
+        dataset.add(id.as_ref(), point)?;
+    }
+    Ok(dataset)
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn analyze_to_dataset_keeps_item_order() {
+        let items: Vec<(String, Vec<f64>)> = (0..100)
+            .map(|i| (format!("item-{i}"), vec![i as f64; 4]))
+            .collect();
+        let dataset = analyze_to_dataset(
+            &items,
+            || Ok(Vec::<f64>::new()),
+            |scratch, input| {
+                scratch.clear();
+                scratch.extend(input.iter().map(|x| x * 2.0));
+                Ok(vec![scratch.iter().sum(), scratch[0]])
+            },
+        )
+        .unwrap();
+        assert_eq!(dataset.len(), 100);
+        assert_eq!(dataset.get("item-7"), Some(&[56.0, 14.0][..]));
+        assert_eq!(dataset.ids()[99], "item-99");
+
+        let failed = map_parallel(
+            &items,
+            || Err::<(), _>(FlucomaError::InvalidParameter("bad settings")),
+            |_, _| Ok(0),
+        );
+        assert_eq!(
+            failed.unwrap_err(),
+            FlucomaError::InvalidParameter("bad settings")
+        );
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn map_parallel_makes_one_analyzer_per_thread() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let items: Vec<usize> = (0..10_000).collect();
+        let made = AtomicUsize::new(0);
+        let squares = map_parallel(
+            &items,
+            || {
+                made.fetch_add(1, Ordering::Relaxed);
+                Ok(())
+            },
+            |_, x| Ok(x * x),
+        )
+        .unwrap();
+        assert_eq!(squares[99], 99 * 99);
+        assert_eq!(made.into_inner(), rayon::current_num_threads());
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn map_parallel_allows_nested_rayon_in_analyze() {
+        use rayon::prelude::*;
+
+        let items: Vec<usize> = (0..1000).collect();
+        let sums = map_parallel(
+            &items,
+            || Ok(()),
+            |_, &n| Ok((0..n).into_par_iter().sum::<usize>()),
+        )
+        .unwrap();
+        assert_eq!(sums[999], 999 * 998 / 2);
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn map_parallel_reports_progress_and_cancels() {
+        use crate::progress::CancellationToken;
+
+        let items: Vec<usize> = (0..50).collect();
+        let mut last = 0.0;
+        let mut progress = Progress::new().on_progress(|fraction| last = f64::max(last, fraction));
+        map_parallel_with_progress(&items, || Ok(()), |_, x| Ok(*x), &mut progress).unwrap();
+        drop(progress);
+        assert_eq!(last, 1.0);
+
+        let token = CancellationToken::new();
+        token.cancel();
+        let mut progress = Progress::new().cancel_with(token);
+        let cancelled = map_parallel_with_progress(&items, || Ok(()), |_, x| Ok(*x), &mut progress);
+        assert_eq!(cancelled.unwrap_err(), FlucomaError::Cancelled);
+    }
+
+    #[cfg(not(feature = "parallel"))]
+    #[test]
+    fn parallel_requires_feature() {
+        let items = [("a", 1.0)];
+        assert_eq!(
+            analyze_to_dataset(&items, || Ok(()), |_, x| Ok(vec![*x])).err(),
+            Some(FlucomaError::FeatureDisabled("parallel"))
+        );
+    }
+}
@@ -0,0 +1,59 @@
+//! Names of enum parameters, for `FromStr` and `Display` so CLI tools and
+//! config files can refer to e.g. `"hann"` or `"hfc"` instead of numeric
+//! variants.
+
+use crate::error::FlucomaError;
+
+// -------------------------------------------------------------------------------------------------
+
+/// Look up `name` in `names`, ignoring case and `-`, `_` or space
+/// separators, so `"PhaseDev"`, `"phase_dev"` and `"phase-dev"` all match.
+pub(crate) fn parse_name<T: Copy>(
+    name: &str,
+    names: &[(&str, T)],
+    unknown: &'static str,
+) -> Result<T, FlucomaError> {
+    names
+        .iter()
+        .find(|(candidate, _)| normalized(candidate).eq(normalized(name)))
+        .map(|&(_, value)| value)
+        .ok_or(FlucomaError::InvalidParameter(unknown))
+}
+
+/// The first name of `value` in `names`.
+pub(crate) fn display_name<T: PartialEq>(value: &T, names: &[(&'static str, T)]) -> &'static str {
+    names
+        .iter()
+        .find(|(_, candidate)| candidate == value)
+        .map(|&(name, _)| name)
+        .expect("every variant has a name")
+}
+
+fn normalized(name: &str) -> impl Iterator<Item = char> + '_ {
+    name.chars()
+        .filter(|c| !matches!(c, '-' | '_' | ' '))
+        .map(|c| c.to_ascii_lowercase())
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use crate::analyzation::OnsetFunction;
+    use crate::fourier::WindowType;
+
+    use super::*;
+
+    #[test]
+    fn enum_names_round_trip() {
+        assert_eq!("Hann".parse(), Ok(WindowType::Hann));
+        assert_eq!(WindowType::Blackman.to_string(), "blackman");
+        assert_eq!("hfc".parse(), Ok(OnsetFunction::HighFrequency));
+        assert_eq!("phase_dev".parse(), Ok(OnsetFunction::PhaseDev));
+        assert_eq!(OnsetFunction::PhaseDev.to_string(), "phase-dev");
+        assert_eq!(
+            "kaiser".parse::<WindowType>(),
+            Err(FlucomaError::InvalidParameter("unknown window type"))
+        );
+    }
+}
@@ -1,16 +1,22 @@
+use std::cell::Cell;
+
 use flucoma_sys::{
     pca_create, pca_destroy, pca_dims, pca_fit, pca_initialized, pca_inverse_transform,
-    pca_transform, FlucomaIndex,
+    pca_inverse_transform_frame, pca_set_model, pca_size, pca_transform, pca_transform_frame,
+    pca_values, FlucomaIndex,
 };
 
+use crate::error::{check_len, FlucomaError};
+use crate::linalg::symmetric_eigen;
 use crate::matrix::Matrix;
 use crate::normalize::Normalize;
 use crate::robust_scale::RobustScale;
 use crate::standardize::Standardize;
+use crate::transform::Transform;
 
 /// Optional preprocessing scaler applied before PCA fit/transform.
-#[derive(Debug, Clone, Copy)]
-#[derive(Default)]
+#[derive(Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum PcaScaler {
     #[default]
     None,
@@ -25,9 +31,9 @@ pub enum PcaScaler {
     },
 }
 
-
 /// PCA settings.
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PcaConfig {
     pub whiten: bool,
     pub scaler: PcaScaler,
@@ -49,6 +55,59 @@ enum FittedScaler {
     RobustScale(RobustScale),
 }
 
+/// Running mean and centred scatter matrix for incremental fitting, merged
+/// chunk by chunk with the pairwise update of Chan et al.
+struct ScatterAccumulator {
+    count: usize,
+    mean: Vec<f64>,
+    scatter: Vec<f64>,
+}
+
+impl ScatterAccumulator {
+    fn new(cols: usize) -> Self {
+        Self {
+            count: 0,
+            mean: vec![0.0; cols],
+            scatter: vec![0.0; cols * cols],
+        }
+    }
+
+    fn update(&mut self, chunk: &Matrix) {
+        let cols = self.mean.len();
+        let n_b = chunk.rows();
+        if n_b == 0 {
+            return;
+        }
+        let mut mean_b = vec![0.0; cols];
+        for row in chunk.data().chunks_exact(cols) {
+            mean_b.iter_mut().zip(row).for_each(|(m, x)| *m += x);
+        }
+        mean_b.iter_mut().for_each(|m| *m /= n_b as f64);
+        for row in chunk.data().chunks_exact(cols) {
+            for i in 0..cols {
+                let di = row[i] - mean_b[i];
+                for j in 0..cols {
+                    self.scatter[i * cols + j] += di * (row[j] - mean_b[j]);
+                }
+            }
+        }
+
+        let n_a = self.count as f64;
+        let n = n_a + n_b as f64;
+        let delta: Vec<f64> = mean_b.iter().zip(&self.mean).map(|(b, a)| b - a).collect();
+        let weight = n_a * n_b as f64 / n;
+        for i in 0..cols {
+            for j in 0..cols {
+                self.scatter[i * cols + j] += delta[i] * delta[j] * weight;
+            }
+        }
+        for (m, d) in self.mean.iter_mut().zip(&delta) {
+            *m += d * n_b as f64 / n;
+        }
+        self.count += n_b;
+    }
+}
+
 /// Principal Component Analysis with optional scaler preprocessing.
 ///
 /// Learns a linear projection from a row-major matrix and can project new
@@ -61,6 +120,10 @@ pub struct Pca {
     config: PcaConfig,
     dims: Option<usize>,
     fitted_scaler: Option<FittedScaler>,
+    accumulator: Option<ScatterAccumulator>,
+    /// Set by [`Pca::partial_fit`] until the accumulated scatter matrix has
+    /// been decomposed into the model.
+    model_stale: Cell<bool>,
 }
 
 // SAFETY: flucoma algorithms are thread-safe to move between threads.
@@ -71,17 +134,19 @@ impl Pca {
     ///
     /// # Errors
     /// Returns an error if the scaler configuration is invalid.
-    pub fn new(config: PcaConfig) -> Result<Self, &'static str> {
+    pub fn new(config: PcaConfig) -> Result<Self, FlucomaError> {
         validate_scaler_config(config.scaler)?;
         let inner = pca_create();
         if inner.is_null() {
-            return Err("failed to create PCA instance");
+            return Err(FlucomaError::AllocationFailed);
         }
         Ok(Self {
             inner,
             config,
             dims: None,
             fitted_scaler: None,
+            accumulator: None,
+            model_stale: Cell::new(false),
         })
     }
 
@@ -90,7 +155,7 @@ impl Pca {
     }
 
     /// Fit the PCA model from a row-major matrix.
-    pub fn fit(&mut self, data: &Matrix) -> Result<(), &'static str> {
+    pub fn fit(&mut self, data: &Matrix) -> Result<(), FlucomaError> {
         let (scaled_data, fitted_scaler) = self.fit_scaler_and_transform(data)?;
         pca_fit(
             self.inner,
@@ -100,28 +165,106 @@ impl Pca {
         );
         self.dims = Some(data.cols());
         self.fitted_scaler = Some(fitted_scaler);
+        self.accumulator = None;
+        self.model_stale.set(false);
         Ok(())
     }
 
+    /// Update the model with another chunk of rows, so PCA can be fitted over
+    /// datasets too large to hold in memory.
+    ///
+    /// Only the running mean and a `cols × cols` scatter matrix are kept.
+    /// They are decomposed into a model on the first use after a chunk, not
+    /// per chunk, so the model can be used for [`Pca::transform`] at any
+    /// point. The result matches [`Pca::fit`] on all rows seen so far, up to
+    /// the sign of each component. A call to [`Pca::fit`] discards the
+    /// accumulated state.
+    ///
+    /// # Errors
+    /// Returns an error if a scaler is configured (scalers need the full
+    /// dataset to fit), the chunk is empty, or its column count differs from
+    /// earlier chunks.
+    pub fn partial_fit(&mut self, chunk: &Matrix) -> Result<(), FlucomaError> {
+        if !matches!(self.config.scaler, PcaScaler::None) {
+            return Err(FlucomaError::InvalidParameter(
+                "partial_fit does not support a scaler",
+            ));
+        }
+        if chunk.rows() == 0 || chunk.cols() == 0 {
+            return Err(FlucomaError::InvalidParameter("chunk must not be empty"));
+        }
+        let cols = chunk.cols();
+        let accumulator = self
+            .accumulator
+            .get_or_insert_with(|| ScatterAccumulator::new(cols));
+        if accumulator.mean.len() != cols {
+            return Err(FlucomaError::InvalidParameter(
+                "cols must match previous chunks",
+            ));
+        }
+        accumulator.update(chunk);
+        self.dims = Some(cols);
+        self.fitted_scaler = Some(FittedScaler::None);
+        self.model_stale.set(true);
+        Ok(())
+    }
+
+    /// Decompose the scatter matrix accumulated by [`Pca::partial_fit`] into
+    /// the model, if chunks were added since it was last decomposed.
+    fn sync_model(&self) {
+        if !self.model_stale.replace(false) {
+            return;
+        }
+        let Some(accumulator) = &self.accumulator else {
+            return;
+        };
+        let cols = accumulator.mean.len();
+        // Singular values of the centred data are the square roots of the
+        // scatter matrix eigenvalues, and its eigenvectors are the bases.
+        let (eigenvalues, bases) = symmetric_eigen(&accumulator.scatter, cols);
+        let values: Vec<f64> = eigenvalues.iter().map(|v| v.max(0.0).sqrt()).collect();
+        pca_set_model(
+            self.inner,
+            bases.as_ptr(),
+            values.as_ptr(),
+            accumulator.mean.as_ptr(),
+            cols as FlucomaIndex,
+            cols as FlucomaIndex,
+        );
+    }
+
+    /// Decompose the chunks added by [`Pca::partial_fit`] into the model now
+    /// rather than on its next use, e.g. before handing the model to an
+    /// audio thread. Does nothing if there are no new chunks.
+    pub fn finalize(&self) {
+        self.sync_model();
+    }
+
     /// Fit the model and project the same matrix in one step.
     pub fn fit_transform(
         &mut self,
         data: &Matrix,
         target_dims: usize,
-    ) -> Result<(Matrix, f64), &'static str> {
+    ) -> Result<(Matrix, f64), FlucomaError> {
         self.fit(data)?;
         self.transform(data, target_dims)
     }
 
     /// Project a matrix to `target_dims`; returns
     /// `(projected_matrix, explained_variance_ratio)`.
-    pub fn transform(&self, data: &Matrix, target_dims: usize) -> Result<(Matrix, f64), &'static str> {
+    pub fn transform(
+        &self,
+        data: &Matrix,
+        target_dims: usize,
+    ) -> Result<(Matrix, f64), FlucomaError> {
         self.ensure_fitted(data.cols())?;
         if target_dims == 0 {
-            return Err("target_dims must be > 0");
+            return Err(FlucomaError::InvalidParameter("target_dims must be > 0"));
         }
         if target_dims > data.cols() {
-            return Err("target_dims must be <= input cols");
+            return Err(FlucomaError::InvalidParameter(
+                "target_dims must be <= input cols",
+            ));
         }
 
         let scaled_data = self.apply_scaler_transform(data)?;
@@ -138,11 +281,162 @@ impl Pca {
         Ok((out, explained))
     }
 
+    /// Project a single point to `target_dims`, e.g. a live descriptor frame
+    /// every block, without building a one-row [`Matrix`].
+    ///
+    /// # Errors
+    /// Returns an error if the model is not fitted, `point.len()` does not
+    /// match the fitted dimension, or `target_dims` is not in
+    /// `1..=point.len()`.
+    pub fn transform_point(
+        &self,
+        point: &[f64],
+        target_dims: usize,
+    ) -> Result<Vec<f64>, FlucomaError> {
+        let mut out = vec![0.0; target_dims];
+        self.transform_point_into(point, &mut out)?;
+        Ok(out)
+    }
+
+    /// Like [`Pca::transform_point`], but projects onto the first
+    /// `out.len()` components and writes them into `out`. Does not allocate
+    /// unless a [`PcaScaler`] is configured, so it can run on an audio
+    /// thread.
+    ///
+    /// The first use after [`Pca::partial_fit`] also runs the `O(cols³)`
+    /// decomposition of the accumulated chunks; call [`Pca::finalize`] off
+    /// the audio thread beforehand.
+    pub fn transform_point_into(&self, point: &[f64], out: &mut [f64]) -> Result<(), FlucomaError> {
+        self.ensure_fitted(point.len())?;
+        if out.is_empty() {
+            return Err(FlucomaError::InvalidParameter("target_dims must be > 0"));
+        }
+        if out.len() > point.len() {
+            return Err(FlucomaError::InvalidParameter(
+                "target_dims must be <= input cols",
+            ));
+        }
+        let scaled;
+        let point = if self.has_scaler() {
+            scaled =
+                self.apply_scaler_transform(&Matrix::from_vec(point.to_vec(), 1, point.len())?)?;
+            scaled.data()
+        } else {
+            point
+        };
+        pca_transform_frame(
+            self.inner,
+            point.as_ptr(),
+            point.len() as FlucomaIndex,
+            out.as_mut_ptr(),
+            out.len() as FlucomaIndex,
+            self.config.whiten,
+        );
+        Ok(())
+    }
+
+    /// Map a single projected point back to the original feature space.
+    /// `projected` may hold fewer components than were fitted; the missing
+    /// ones are taken as zero.
+    ///
+    /// # Errors
+    /// Returns an error if the model is not fitted or `projected` is longer
+    /// than the fitted dimension.
+    pub fn inverse_transform_point(&self, projected: &[f64]) -> Result<Vec<f64>, FlucomaError> {
+        let dims = self.dims.ok_or(FlucomaError::NotFitted)?;
+        if projected.len() > dims {
+            return Err(FlucomaError::InvalidParameter(
+                "projected_cols must be <= fitted dims",
+            ));
+        }
+        let mut padded = vec![0.0; dims];
+        padded[..projected.len()].copy_from_slice(projected);
+        let mut out = vec![0.0; dims];
+        self.inverse_transform_point_into(&padded, &mut out)?;
+        Ok(out)
+    }
+
+    /// Like [`Pca::inverse_transform_point`], but reads a `projected` point
+    /// and writes into `out`, both of the fitted dimension. Unused trailing
+    /// components of `projected` must be zero. Does not allocate unless a
+    /// [`PcaScaler`] is configured. As for [`Pca::transform_point_into`],
+    /// call [`Pca::finalize`] after [`Pca::partial_fit`] before using it on
+    /// an audio thread.
+    pub fn inverse_transform_point_into(
+        &self,
+        projected: &[f64],
+        out: &mut [f64],
+    ) -> Result<(), FlucomaError> {
+        let dims = self.dims.ok_or(FlucomaError::NotFitted)?;
+        self.ensure_fitted(dims)?;
+        check_len(dims, projected.len())?;
+        check_len(dims, out.len())?;
+        pca_inverse_transform_frame(
+            self.inner,
+            projected.as_ptr(),
+            out.as_mut_ptr(),
+            dims as FlucomaIndex,
+            self.config.whiten,
+        );
+        if self.has_scaler() {
+            let restored =
+                self.apply_scaler_inverse_transform(&Matrix::from_vec(out.to_vec(), 1, dims)?)?;
+            out.copy_from_slice(restored.data());
+        }
+        Ok(())
+    }
+
+    /// Project a matrix onto the smallest number of components whose
+    /// cumulative explained variance reaches `min_ratio` (e.g. `0.95`).
+    ///
+    /// Returns `(k, projected_matrix)`, where `projected_matrix` has `k`
+    /// columns.
+    ///
+    /// # Errors
+    /// Returns an error if the model is not fitted, `cols` does not match the
+    /// fitted dimension, or `min_ratio` is not in `(0, 1]`.
+    pub fn transform_keep_variance(
+        &self,
+        data: &Matrix,
+        min_ratio: f64,
+    ) -> Result<(usize, Matrix), FlucomaError> {
+        if !(min_ratio > 0.0 && min_ratio <= 1.0) {
+            return Err(FlucomaError::InvalidParameter(
+                "min_ratio must be in (0, 1]",
+            ));
+        }
+        let ratios = self
+            .explained_variance_ratios()
+            .ok_or(FlucomaError::NotFitted)?;
+        let k = components_for_variance(&ratios, min_ratio).min(data.cols());
+        let (projected, _) = self.transform(data, k)?;
+        Ok((k, projected))
+    }
+
+    /// Fraction of the total variance explained by each principal component,
+    /// in decreasing order, or `None` if the model is not fitted.
+    pub fn explained_variance_ratios(&self) -> Option<Vec<f64>> {
+        if !self.is_fitted() {
+            return None;
+        }
+        let size = pca_size(self.inner) as usize;
+        let mut values = vec![0.0; size];
+        pca_values(self.inner, values.as_mut_ptr(), size as FlucomaIndex);
+        let total: f64 = values.iter().map(|v| v * v).sum();
+        if total <= 0.0 {
+            return Some(vec![0.0; size]);
+        }
+        Some(values.iter().map(|v| v * v / total).collect())
+    }
+
     /// Inverse-transform projected PCA data back to the original feature space.
-    pub fn inverse_transform(&self, projected: &Matrix) -> Result<Matrix, &'static str> {
-        let cols = self.dims.ok_or("PCA is not fitted")?;
+    pub fn inverse_transform(&self, projected: &Matrix) -> Result<Matrix, FlucomaError> {
+        let cols = self.dims.ok_or(FlucomaError::NotFitted)?;
+        self.sync_model();
         if projected.cols() > cols {
-            return Err("projected_cols must be <= fitted dims");
+            return Err(FlucomaError::InvalidParameter(
+                "projected_cols must be <= fitted dims",
+            ));
         }
 
         // Upstream PCA inverse expects an input matrix with full `dims` columns,
@@ -153,7 +447,8 @@ impl Pca {
             let src_end = src_start + projected.cols();
             let dst_start = r * cols;
             let dst_end = dst_start + projected.cols();
-            padded.data_mut()[dst_start..dst_end].copy_from_slice(&projected.data()[src_start..src_end]);
+            padded.data_mut()[dst_start..dst_end]
+                .copy_from_slice(&projected.data()[src_start..src_end]);
         }
 
         let mut recon_scaled = Matrix::new(projected.rows(), cols);
@@ -170,6 +465,7 @@ impl Pca {
     }
 
     pub fn is_fitted(&self) -> bool {
+        self.sync_model();
         pca_initialized(self.inner)
     }
 
@@ -180,12 +476,14 @@ impl Pca {
         Some(pca_dims(self.inner) as usize)
     }
 
-    fn ensure_fitted(&self, cols: usize) -> Result<(), &'static str> {
+    fn ensure_fitted(&self, cols: usize) -> Result<(), FlucomaError> {
         if !self.is_fitted() {
-            return Err("PCA is not fitted");
+            return Err(FlucomaError::NotFitted);
         }
         if self.dims != Some(cols) {
-            return Err("cols must match fitted feature dimension");
+            return Err(FlucomaError::InvalidParameter(
+                "cols must match fitted feature dimension",
+            ));
         }
         Ok(())
     }
@@ -193,7 +491,7 @@ impl Pca {
     fn fit_scaler_and_transform(
         &self,
         data: &Matrix,
-    ) -> Result<(Matrix, FittedScaler), &'static str> {
+    ) -> Result<(Matrix, FittedScaler), FlucomaError> {
         match self.config.scaler {
             PcaScaler::None => Ok((data.clone(), FittedScaler::None)),
             PcaScaler::Normalize { min, max } => {
@@ -217,8 +515,12 @@ impl Pca {
         }
     }
 
-    fn apply_scaler_transform(&self, data: &Matrix) -> Result<Matrix, &'static str> {
-        match self.fitted_scaler.as_ref().ok_or("PCA is not fitted")? {
+    fn has_scaler(&self) -> bool {
+        !matches!(self.fitted_scaler, None | Some(FittedScaler::None))
+    }
+
+    fn apply_scaler_transform(&self, data: &Matrix) -> Result<Matrix, FlucomaError> {
+        match self.fitted_scaler.as_ref().ok_or(FlucomaError::NotFitted)? {
             FittedScaler::None => Ok(data.clone()),
             FittedScaler::Normalize(n) => n.transform(data),
             FittedScaler::Standardize(s) => s.transform(data),
@@ -226,8 +528,8 @@ impl Pca {
         }
     }
 
-    fn apply_scaler_inverse_transform(&self, data: &Matrix) -> Result<Matrix, &'static str> {
-        match self.fitted_scaler.as_ref().ok_or("PCA is not fitted")? {
+    fn apply_scaler_inverse_transform(&self, data: &Matrix) -> Result<Matrix, FlucomaError> {
+        match self.fitted_scaler.as_ref().ok_or(FlucomaError::NotFitted)? {
             FittedScaler::None => Ok(data.clone()),
             FittedScaler::Normalize(n) => n.inverse_transform(data),
             FittedScaler::Standardize(s) => s.inverse_transform(data),
@@ -236,18 +538,42 @@ impl Pca {
     }
 }
 
+/// Projects onto every fitted component, so the transform is invertible
+/// within a [`TransformPipeline`](crate::data::TransformPipeline). Use
+/// [`Pca::transform`] directly to reduce the number of dimensions.
+impl Transform for Pca {
+    fn fit(&mut self, data: &Matrix) -> Result<(), FlucomaError> {
+        Pca::fit(self, data)
+    }
+
+    fn transform(&self, data: &Matrix) -> Result<Matrix, FlucomaError> {
+        let dims = self.dims.ok_or(FlucomaError::NotFitted)?;
+        Pca::transform(self, data, dims).map(|(projected, _)| projected)
+    }
+
+    fn inverse_transform(&self, data: &Matrix) -> Result<Matrix, FlucomaError> {
+        Pca::inverse_transform(self, data)
+    }
+
+    fn is_fitted(&self) -> bool {
+        Pca::is_fitted(self)
+    }
+}
+
 impl Drop for Pca {
     fn drop(&mut self) {
         pca_destroy(self.inner);
     }
 }
 
-fn validate_scaler_config(scaler: PcaScaler) -> Result<(), &'static str> {
+fn validate_scaler_config(scaler: PcaScaler) -> Result<(), FlucomaError> {
     match scaler {
         PcaScaler::None | PcaScaler::Standardize => Ok(()),
         PcaScaler::Normalize { min, max } => {
             if min == max {
-                return Err("Normalize scaler requires min != max");
+                return Err(FlucomaError::InvalidParameter(
+                    "Normalize scaler requires min != max",
+                ));
             }
             Ok(())
         }
@@ -256,35 +582,145 @@ fn validate_scaler_config(scaler: PcaScaler) -> Result<(), &'static str> {
             high_percentile,
         } => {
             if !(0.0..=100.0).contains(&low_percentile) {
-                return Err("RobustScale low_percentile must be in [0, 100]");
+                return Err(FlucomaError::InvalidParameter(
+                    "RobustScale low_percentile must be in [0, 100]",
+                ));
             }
             if !(0.0..=100.0).contains(&high_percentile) {
-                return Err("RobustScale high_percentile must be in [0, 100]");
+                return Err(FlucomaError::InvalidParameter(
+                    "RobustScale high_percentile must be in [0, 100]",
+                ));
             }
             if low_percentile > high_percentile {
-                return Err("RobustScale low_percentile must be <= high_percentile");
+                return Err(FlucomaError::InvalidParameter(
+                    "RobustScale low_percentile must be <= high_percentile",
+                ));
             }
             Ok(())
         }
     }
 }
 
+/// Smallest number of leading components whose cumulative ratio reaches
+/// `min_ratio`, and at least one.
+fn components_for_variance(ratios: &[f64], min_ratio: f64) -> usize {
+    let mut cumulative = 0.0;
+    for (i, ratio) in ratios.iter().enumerate() {
+        cumulative += ratio;
+        // Tolerate rounding when min_ratio is 1.0.
+        if cumulative >= min_ratio - 1e-12 {
+            return i + 1;
+        }
+    }
+    ratios.len().max(1)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn scatter_accumulator_matches_single_pass() {
+        let data = sample_data();
+        let mut whole = ScatterAccumulator::new(3);
+        whole.update(&data);
+
+        let mut chunked = ScatterAccumulator::new(3);
+        for rows in data.data().chunks(9) {
+            chunked.update(&Matrix::from_vec(rows.to_vec(), rows.len() / 3, 3).unwrap());
+        }
+        assert_eq!(chunked.count, 8);
+        for (a, b) in whole.mean.iter().zip(&chunked.mean) {
+            assert!((a - b).abs() < 1e-12);
+        }
+        for (a, b) in whole.scatter.iter().zip(&chunked.scatter) {
+            assert!((a - b).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn pca_partial_fit_rejects_scaler_and_mismatched_cols() {
+        let data = sample_data();
+        let mut p = Pca::new(PcaConfig::default()).unwrap();
+        p.partial_fit(&data).unwrap();
+        let (proj, _) = p.transform(&data, 2).unwrap();
+        assert_eq!(proj.cols(), 2);
+        assert!(p.partial_fit(&Matrix::new(2, 4)).is_err());
+
+        let mut scaled = Pca::new(PcaConfig {
+            whiten: false,
+            scaler: PcaScaler::Standardize,
+        })
+        .unwrap();
+        assert!(scaled.partial_fit(&data).is_err());
+    }
+
+    #[test]
+    fn pca_partial_fit_matches_fit() {
+        let data = sample_data();
+        let mut whole = Pca::new(PcaConfig::default()).unwrap();
+        whole.fit(&data).unwrap();
+        let mut chunked = Pca::new(PcaConfig::default()).unwrap();
+        for rows in data.data().chunks(9) {
+            chunked
+                .partial_fit(&Matrix::from_vec(rows.to_vec(), rows.len() / 3, 3).unwrap())
+                .unwrap();
+        }
+        let ratios = whole.explained_variance_ratios().unwrap();
+        for (a, b) in ratios
+            .iter()
+            .zip(chunked.explained_variance_ratios().unwrap())
+        {
+            assert!((a - b).abs() < 1e-9, "expected {a}, got {b}");
+        }
+        // Components are only defined up to their sign.
+        let (expected, _) = whole.transform(&data, 3).unwrap();
+        let (got, _) = chunked.transform(&data, 3).unwrap();
+        for (a, b) in expected.data().iter().zip(got.data()) {
+            assert!((a.abs() - b.abs()).abs() < 1e-9, "expected ±{a}, got {b}");
+        }
+        let restored = chunked.inverse_transform(&got).unwrap();
+        for (a, b) in data.data().iter().zip(restored.data()) {
+            assert!((a - b).abs() < 1e-9, "expected {a}, got {b}");
+        }
+    }
+
+    #[test]
+    fn components_for_variance_picks_smallest_k() {
+        let ratios = [0.6, 0.3, 0.08, 0.02];
+        assert_eq!(components_for_variance(&ratios, 0.5), 1);
+        assert_eq!(components_for_variance(&ratios, 0.9), 2);
+        assert_eq!(components_for_variance(&ratios, 0.95), 3);
+        assert_eq!(components_for_variance(&ratios, 1.0), 4);
+    }
+
+    #[test]
+    fn pca_transform_keep_variance_returns_k_columns() {
+        let data = sample_data();
+        let mut p = Pca::new(PcaConfig::default()).unwrap();
+        p.fit(&data).unwrap();
+        let (k, proj) = p.transform_keep_variance(&data, 0.9).unwrap();
+        assert!((1..=3).contains(&k));
+        assert_eq!(proj.cols(), k);
+        assert!(p.transform_keep_variance(&data, 0.0).is_err());
+    }
+
     fn sample_data() -> Matrix {
         // 8 x 3 row-major
-        Matrix::from_vec(vec![
-            1.0, 2.0, 0.9, //
-            1.2, 2.2, 1.1, //
-            0.8, 1.7, 0.7, //
-            3.0, 3.2, 2.9, //
-            2.8, 3.0, 2.6, //
-            10.0, -8.0, 9.0, //
-            2.9, 3.1, 2.7, //
-            1.1, 2.1, 1.0,
-        ], 8, 3)
+        Matrix::from_vec(
+            vec![
+                1.0, 2.0, 0.9, //
+                1.2, 2.2, 1.1, //
+                0.8, 1.7, 0.7, //
+                3.0, 3.2, 2.9, //
+                2.8, 3.0, 2.6, //
+                10.0, -8.0, 9.0, //
+                2.9, 3.1, 2.7, //
+                1.1, 2.1, 1.0,
+            ],
+            8,
+            3,
+        )
         .unwrap()
     }
 
@@ -329,4 +765,61 @@ mod tests {
         assert_eq!(proj.rows(), 8);
         assert_eq!(proj.cols(), 2);
     }
+
+    #[test]
+    fn transform_point_matches_matrix_transform() {
+        let data = sample_data();
+        let mut p = Pca::new(PcaConfig {
+            whiten: false,
+            scaler: PcaScaler::Standardize,
+        })
+        .unwrap();
+        let (proj, _) = p.fit_transform(&data, 2).unwrap();
+        let point = p.transform_point(&data.data()[9..12], 2).unwrap();
+        for (a, b) in point.iter().zip(&proj.data()[6..8]) {
+            assert!((a - b).abs() < 1e-9, "expected {b}, got {a}");
+        }
+        let inv = p.inverse_transform(&proj).unwrap();
+        let inv_point = p.inverse_transform_point(&point).unwrap();
+        for (a, b) in inv_point.iter().zip(&inv.data()[9..12]) {
+            assert!((a - b).abs() < 1e-9, "expected {b}, got {a}");
+        }
+        assert!(p.transform_point(&[1.0, 2.0], 2).is_err());
+        assert!(p.inverse_transform_point(&[0.0; 4]).is_err());
+        let mut out = [0.0; 3];
+        assert!(p.inverse_transform_point_into(&point, &mut out).is_err());
+    }
+
+    #[test]
+    fn transform_point_matches_matrix_transform_without_scaler() {
+        let data = sample_data();
+        let mut p = Pca::new(PcaConfig::default()).unwrap();
+        let (proj, _) = p.fit_transform(&data, 2).unwrap();
+        let point = p.transform_point(&data.data()[9..12], 2).unwrap();
+        for (a, b) in point.iter().zip(&proj.data()[6..8]) {
+            assert!((a - b).abs() < 1e-9, "expected {b}, got {a}");
+        }
+        let inv = p.inverse_transform(&proj).unwrap();
+        let padded = [point[0], point[1], 0.0];
+        let mut inv_point = [0.0; 3];
+        p.inverse_transform_point_into(&padded, &mut inv_point)
+            .unwrap();
+        for (a, b) in inv_point.iter().zip(&inv.data()[9..12]) {
+            assert!((a - b).abs() < 1e-9, "expected {b}, got {a}");
+        }
+    }
+
+    #[test]
+    fn finalize_decomposes_partial_fit_ahead_of_transform_point() {
+        let data = sample_data();
+        let mut p = Pca::new(PcaConfig::default()).unwrap();
+        p.partial_fit(&data).unwrap();
+        assert!(p.model_stale.get());
+        p.finalize();
+        assert!(!p.model_stale.get());
+        let mut out = [0.0; 2];
+        p.transform_point_into(&data.data()[9..12], &mut out)
+            .unwrap();
+        assert!(!p.model_stale.get());
+    }
 }
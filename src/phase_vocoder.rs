@@ -0,0 +1,196 @@
+use std::f64::consts::{PI, TAU};
+
+use num_complex::Complex64 as Complex;
+
+use crate::error::FlucomaError;
+use crate::fft_settings::FftSettings;
+use crate::spectrogram::Spectrogram;
+use crate::stft::{ComplexSpectrum, Istft, Stft, WindowType};
+
+// -------------------------------------------------------------------------------------------------
+
+/// Phase-vocoder time stretching and pitch shifting of whole buffers.
+///
+/// The signal is analysed with a hop of `hop_size / factor` and resynthesised
+/// with `hop_size`, advancing each bin's phase by its measured instantaneous
+/// frequency so partials stay continuous across the new frame spacing.
+/// Pitch shifting stretches by the pitch ratio and resamples back to the
+/// original length.
+///
+/// The analysis hop is rounded to whole samples, so the effective stretch
+/// factor is `hop_size / round(hop_size / factor)`. Transients smear at large
+/// factors; at least 4× overlap (the default, 2048 window with a 512 hop)
+/// is recommended.
+///
+/// ```rust,no_run
+/// use flucoma_rs::decomposition::PhaseVocoder;
+///
+/// let audio = vec![0.0f64; 44100];
+/// let vocoder = PhaseVocoder::default();
+/// let slower = vocoder.time_stretch(&audio, 1.5).unwrap();
+/// assert_eq!(slower.len(), 66150);
+/// let higher = vocoder.pitch_shift(&audio, 7.0).unwrap();
+/// assert_eq!(higher.len(), audio.len());
+/// ```
+#[derive(Debug, Clone)]
+pub struct PhaseVocoder {
+    settings: FftSettings,
+}
+
+impl Default for PhaseVocoder {
+    fn default() -> Self {
+        Self {
+            settings: FftSettings {
+                window_size: 2048,
+                fft_size: 2048,
+                hop_size: 512,
+                window_type: WindowType::Hann,
+            },
+        }
+    }
+}
+
+impl PhaseVocoder {
+    /// Create a phase vocoder synthesising frames with `settings`.
+    ///
+    /// # Errors
+    /// Returns an error if the settings are invalid.
+    pub fn new(settings: FftSettings) -> Result<Self, FlucomaError> {
+        settings.validate()?;
+        Ok(Self { settings })
+    }
+
+    pub fn settings(&self) -> &FftSettings {
+        &self.settings
+    }
+
+    /// Change the duration of `signal` by `factor` without changing its
+    /// pitch: `2.0` doubles the length, `0.5` halves it. The output has
+    /// `round(signal.len() * factor)` samples.
+    ///
+    /// # Errors
+    /// Returns an error if `factor` is not positive and finite.
+    pub fn time_stretch(&self, signal: &[f64], factor: f64) -> Result<Vec<f64>, FlucomaError> {
+        if !factor.is_finite() || factor <= 0.0 {
+            return Err(FlucomaError::InvalidParameter(
+                "factor must be positive and finite",
+            ));
+        }
+        let synthesis_hop = self.settings.hop_size;
+        let analysis_hop = ((synthesis_hop as f64 / factor).round() as usize).max(1);
+        let analysis = Stft::from_settings(&FftSettings {
+            hop_size: analysis_hop,
+            ..self.settings
+        })?
+        .analyze(signal);
+
+        let num_bins = self.settings.num_bins();
+        let mut last_phases = vec![0.0; num_bins];
+        let mut synthesis_phases = vec![0.0; num_bins];
+        let ratio = synthesis_hop as f64 / analysis_hop as f64;
+        let frames = (0..analysis.num_frames())
+            .map(|index| {
+                let mut output = ComplexSpectrum::zeros(num_bins);
+                for (bin, value) in analysis.frame(index).iter().enumerate() {
+                    let (magnitude, phase) = value.to_polar();
+                    if index == 0 {
+                        synthesis_phases[bin] = phase;
+                    } else {
+                        let expected =
+                            TAU * bin as f64 * analysis_hop as f64 / self.settings.fft_size as f64;
+                        let deviation = wrap_phase(phase - last_phases[bin] - expected);
+                        synthesis_phases[bin] += (expected + deviation) * ratio;
+                    }
+                    last_phases[bin] = phase;
+                    output[bin] = Complex::from_polar(magnitude, synthesis_phases[bin]);
+                }
+                output
+            })
+            .collect();
+        let stretched = Spectrogram::from_frames(
+            frames,
+            self.settings.window_size,
+            self.settings.fft_size,
+            synthesis_hop,
+            self.settings.window_type,
+            (signal.len() as f64 * factor).round() as usize,
+        );
+        Ok(Istft::from_settings(&self.settings)?.synthesize(&stretched))
+    }
+
+    /// Transpose `signal` by `semitones` while keeping its duration.
+    ///
+    /// # Errors
+    /// Returns an error if `semitones` is not finite.
+    pub fn pitch_shift(&self, signal: &[f64], semitones: f64) -> Result<Vec<f64>, FlucomaError> {
+        if !semitones.is_finite() {
+            return Err(FlucomaError::InvalidParameter("semitones must be finite"));
+        }
+        let stretched = self.time_stretch(signal, 2f64.powf(semitones / 12.0))?;
+        Ok(resample(&stretched, signal.len()))
+    }
+}
+
+/// Wrap a phase difference into `-PI..=PI`.
+fn wrap_phase(phase: f64) -> f64 {
+    phase - TAU * ((phase + PI) / TAU).floor()
+}
+
+/// Linearly interpolate `input` to `len` samples spanning the same duration.
+fn resample(input: &[f64], len: usize) -> Vec<f64> {
+    if input.is_empty() {
+        return vec![0.0; len];
+    }
+    let step = input.len() as f64 / len.max(1) as f64;
+    (0..len)
+        .map(|i| {
+            let position = i as f64 * step;
+            let index = position as usize;
+            let fraction = position - index as f64;
+            let a = input[index.min(input.len() - 1)];
+            let b = input[(index + 1).min(input.len() - 1)];
+            a + (b - a) * fraction
+        })
+        .collect()
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn phase_helpers() {
+        assert!(
+            (wrap_phase(3.0 * PI) - PI).abs() < 1e-12 || (wrap_phase(3.0 * PI) + PI).abs() < 1e-12
+        );
+        assert!((wrap_phase(0.5) - 0.5).abs() < 1e-12);
+        assert!((wrap_phase(-TAU + 0.25) - 0.25).abs() < 1e-12);
+        assert_eq!(resample(&[0.0, 2.0, 4.0, 6.0], 8)[1], 1.0);
+        assert_eq!(resample(&[0.0, 1.0, 2.0, 3.0], 2), vec![0.0, 2.0]);
+        assert_eq!(
+            PhaseVocoder::default()
+                .time_stretch(&[0.0; 16], 0.0)
+                .unwrap_err(),
+            FlucomaError::InvalidParameter("factor must be positive and finite")
+        );
+    }
+
+    #[test]
+    fn time_stretch_keeps_pitch() {
+        let vocoder = PhaseVocoder::default();
+        let signal: Vec<f64> = (0..22050)
+            .map(|i| (TAU * 441.0 * i as f64 / 44100.0).sin())
+            .collect();
+        let stretched = vocoder.time_stretch(&signal, 2.0).unwrap();
+        assert_eq!(stretched.len(), 44100);
+        // A 441 Hz sine crosses zero upwards every 100 samples.
+        let middle = &stretched[10000..30000];
+        let crossings = middle
+            .windows(2)
+            .filter(|w| w[0] < 0.0 && w[1] >= 0.0)
+            .count();
+        assert!((195..=205).contains(&crossings), "{crossings} crossings");
+    }
+}
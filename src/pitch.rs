@@ -0,0 +1,196 @@
+use flucoma_sys::{yinfft_create, yinfft_destroy, yinfft_process_frame};
+
+use crate::error::{check_len, FlucomaError};
+use crate::frame_analyzer::FrameAnalyzer;
+use crate::quality::FrameQuality;
+
+// -------------------------------------------------------------------------------------------------
+
+/// Fundamental frequency estimate for one frame.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PitchResult {
+    /// Estimated fundamental frequency in Hz.
+    pub frequency_hz: f64,
+    /// Confidence of the estimate in `[0, 1]`.
+    pub confidence: f64,
+    /// Reliability flags of the frame, including
+    /// [`FrameQuality::UNVOICED`] when no pitch was found.
+    pub quality: FrameQuality,
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Per-frame settings of [`Pitch`], defaulting to the FluCoMa object's
+/// defaults.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PitchParams {
+    /// Lowest frequency searched, in Hz.
+    pub min_hz: f64,
+    /// Highest frequency searched, in Hz.
+    pub max_hz: f64,
+    /// Sample rate of the analysed audio in Hz.
+    pub sample_rate: f64,
+}
+
+impl Default for PitchParams {
+    fn default() -> Self {
+        Self {
+            min_hz: 20.0,
+            max_hz: 10000.0,
+            sample_rate: 44100.0,
+        }
+    }
+}
+
+/// Monophonic pitch tracker (YIN computed in the frequency domain).
+///
+/// Call [`Pitch::process_frame`] with magnitude spectra (not raw complex).
+///
+/// See <https://learn.flucoma.org/reference/pitch>
+pub struct Pitch {
+    inner: *mut u8,
+    n_bins: usize,
+}
+
+unsafe impl Send for Pitch {}
+
+impl Pitch {
+    /// Create a pitch tracker.
+    ///
+    /// # Arguments
+    /// * `n_bins` - Number of FFT magnitude bins (`fft_size / 2 + 1`).
+    ///
+    /// # Errors
+    /// Returns an error if parameters are invalid.
+    pub fn new(n_bins: usize) -> Result<Self, FlucomaError> {
+        if n_bins < 2 {
+            return Err(FlucomaError::InvalidParameter("n_bins must be >= 2"));
+        }
+        let inner = yinfft_create(n_bins as isize);
+        if inner.is_null() {
+            return Err(FlucomaError::AllocationFailed);
+        }
+        Ok(Self { inner, n_bins })
+    }
+
+    /// Estimate the pitch of a magnitude spectrum frame.
+    ///
+    /// # Arguments
+    /// * `magnitudes`  - Magnitude spectrum; must have exactly `n_bins` values.
+    /// * `min_hz`      - Lowest candidate frequency in Hz.
+    /// * `max_hz`      - Highest candidate frequency in Hz.
+    /// * `sample_rate` - Audio sample rate in Hz.
+    ///
+    /// # Panics
+    /// Panics if `magnitudes.len() != n_bins`.
+    pub fn process_frame(
+        &mut self,
+        magnitudes: &[f64],
+        min_hz: f64,
+        max_hz: f64,
+        sample_rate: f64,
+    ) -> PitchResult {
+        assert_eq!(
+            magnitudes.len(),
+            self.n_bins,
+            "magnitudes length ({}) must equal n_bins ({})",
+            magnitudes.len(),
+            self.n_bins
+        );
+        let mut out = [0.0f64; 2];
+        yinfft_process_frame(
+            self.inner,
+            magnitudes.as_ptr(),
+            magnitudes.len() as isize,
+            out.as_mut_ptr(),
+            min_hz,
+            max_hz,
+            sample_rate,
+        );
+        let mut quality =
+            FrameQuality::inspect_spectrum(magnitudes) | FrameQuality::inspect_output(&out);
+        if !(out[0] > 0.0 && out[1] > 0.0) {
+            quality |= FrameQuality::UNVOICED;
+        }
+        PitchResult {
+            frequency_hz: out[0],
+            confidence: out[1],
+            quality,
+        }
+    }
+
+    /// Like [`Pitch::process_frame`], but returns
+    /// [`FlucomaError::InvalidShape`] instead of panicking if
+    /// `magnitudes.len() != n_bins`.
+    pub fn try_process_frame(
+        &mut self,
+        magnitudes: &[f64],
+        min_hz: f64,
+        max_hz: f64,
+        sample_rate: f64,
+    ) -> Result<PitchResult, FlucomaError> {
+        check_len(self.n_bins, magnitudes.len())?;
+        Ok(self.process_frame(magnitudes, min_hz, max_hz, sample_rate))
+    }
+
+    /// Number of FFT magnitude bins expected as input (`fft_size / 2 + 1`).
+    pub fn n_bins(&self) -> usize {
+        self.n_bins
+    }
+}
+
+impl Drop for Pitch {
+    fn drop(&mut self) {
+        yinfft_destroy(self.inner);
+    }
+}
+
+impl Clone for Pitch {
+    fn clone(&self) -> Self {
+        Self::new(self.n_bins).expect("settings were validated on construction")
+    }
+}
+
+impl FrameAnalyzer for Pitch {
+    type Input = [f64];
+    type Params = PitchParams;
+    type Output = PitchResult;
+
+    fn process(&mut self, magnitudes: &[f64], params: &PitchParams) -> PitchResult {
+        self.process_frame(magnitudes, params.min_hz, params.max_hz, params.sample_rate)
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pitch_of_harmonic_spectrum() {
+        let n_bins = 1025;
+        let mut pitch = Pitch::new(n_bins).unwrap();
+        // harmonics of ~215 Hz at 44.1 kHz with a 2048-point FFT
+        let mut mags = vec![0.0f64; n_bins];
+        for h in 1..=5 {
+            mags[10 * h] = 1.0 / h as f64;
+        }
+        let result = pitch.process_frame(&mags, 50.0, 2000.0, 44100.0);
+        let expected = 10.0 * 44100.0 / 2048.0;
+        assert!(
+            (result.frequency_hz - expected).abs() < 10.0,
+            "frequency_hz = {}, expected ~{expected:.1}",
+            result.frequency_hz
+        );
+        assert!((0.0..=1.0).contains(&result.confidence));
+        assert!(result.quality.is_reliable(), "{}", result.quality);
+
+        let silence = pitch.process_frame(&vec![0.0; n_bins], 50.0, 2000.0, 44100.0);
+        assert!(silence
+            .quality
+            .contains(FrameQuality::SILENT | FrameQuality::UNVOICED));
+    }
+}
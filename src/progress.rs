@@ -0,0 +1,112 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use crate::error::FlucomaError;
+
+// -------------------------------------------------------------------------------------------------
+
+/// Shared flag for cancelling a long-running job from another thread.
+///
+/// Clones share the same flag, so one clone can be handed to the job via
+/// [`Progress::cancel_with`] while another stays with e.g. a UI's cancel
+/// button.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Request cancellation. Jobs notice at their next frame or iteration.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Progress reporting and cancellation for long offline jobs.
+///
+/// Passed to the `*_with_progress` variants of whole-buffer operations,
+/// which call the progress callback with the fraction complete in
+/// `0.0..=1.0` and return [`FlucomaError::Cancelled`] as soon as they see
+/// that the token was cancelled.
+///
+/// ```rust,no_run
+/// use flucoma_rs::decomposition::{BufNmf, BufNmfConfig};
+/// use flucoma_rs::{CancellationToken, Progress};
+///
+/// let token = CancellationToken::new();
+/// let mut progress = Progress::new()
+///     .on_progress(|fraction| println!("{:.0}%", fraction * 100.0))
+///     .cancel_with(token.clone());
+/// // `token.cancel()` from another thread stops the job.
+/// let nmf = BufNmf::new(BufNmfConfig::default()).unwrap();
+/// let result = nmf.process_with_progress(&vec![0.0; 44100], &mut progress);
+/// ```
+#[derive(Default)]
+pub struct Progress<'a> {
+    callback: Option<Box<dyn FnMut(f64) + Send + 'a>>,
+    token: Option<CancellationToken>,
+}
+
+impl<'a> Progress<'a> {
+    /// Neither report progress nor allow cancellation.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Call `callback` with the fraction complete as the job advances.
+    pub fn on_progress(mut self, callback: impl FnMut(f64) + Send + 'a) -> Self {
+        self.callback = Some(Box::new(callback));
+        self
+    }
+
+    /// Stop the job when `token` is cancelled.
+    pub fn cancel_with(mut self, token: CancellationToken) -> Self {
+        self.token = Some(token);
+        self
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.token
+            .as_ref()
+            .is_some_and(CancellationToken::is_cancelled)
+    }
+
+    /// Report `fraction` complete, then check for cancellation.
+    pub(crate) fn report(&mut self, fraction: f64) -> Result<(), FlucomaError> {
+        if let Some(callback) = &mut self.callback {
+            callback(fraction.clamp(0.0, 1.0));
+        }
+        if self.is_cancelled() {
+            return Err(FlucomaError::Cancelled);
+        }
+        Ok(())
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn progress_reports_and_cancels() {
+        let mut seen = Vec::new();
+        let token = CancellationToken::new();
+        let mut progress = Progress::new()
+            .on_progress(|fraction| seen.push(fraction))
+            .cancel_with(token.clone());
+        assert_eq!(progress.report(0.5), Ok(()));
+        token.cancel();
+        assert_eq!(progress.report(1.5), Err(FlucomaError::Cancelled));
+        drop(progress);
+        assert_eq!(seen, vec![0.5, 1.0]);
+        assert_eq!(Progress::new().report(0.1), Ok(()));
+    }
+}
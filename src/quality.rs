@@ -0,0 +1,199 @@
+use std::fmt;
+use std::ops::{BitOr, BitOrAssign};
+
+// -------------------------------------------------------------------------------------------------
+
+/// Per-frame reliability flags attached to analysis outputs.
+///
+/// A frame with no flags set is considered reliable. Flags are combined with
+/// `|`, and [`FrameQuality::weights`] turns a sequence of flags into `0.0` /
+/// `1.0` weights for [`BufStats`](crate::data::BufStats), so unreliable frames
+/// can be excluded from statistics and training data.
+///
+/// ```rust
+/// use flucoma_rs::analyzation::FrameQuality;
+///
+/// let flags = [
+///     FrameQuality::inspect(&[0.1, -0.2]),
+///     FrameQuality::inspect(&[1.0, -0.2]),
+/// ];
+/// assert!(flags[0].is_reliable());
+/// assert!(flags[1].contains(FrameQuality::CLIPPED));
+/// assert_eq!(FrameQuality::weights(&flags, FrameQuality::CLIPPED), vec![1.0, 0.0]);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FrameQuality(u8);
+
+impl FrameQuality {
+    /// No issues detected.
+    pub const RELIABLE: Self = Self(0);
+    /// At least one input sample reached or exceeded full scale (`|x| >= 1`).
+    pub const CLIPPED: Self = Self(1);
+    /// The input contains subnormal values, which are slow to process and
+    /// usually indicate decaying filter state rather than signal.
+    pub const DENORMAL: Self = Self(1 << 1);
+    /// The input or output contains NaN or infinite values.
+    pub const NON_FINITE: Self = Self(1 << 2);
+    /// All input samples are exactly zero.
+    pub const SILENT: Self = Self(1 << 3);
+    /// No pitch could be detected in the frame. Set by
+    /// [`Pitch`](crate::analyzation::Pitch).
+    pub const UNVOICED: Self = Self(1 << 4);
+
+    /// Inspect raw input samples for clipping, subnormals, non-finite values
+    /// and digital silence.
+    pub fn inspect(samples: &[f64]) -> Self {
+        let mut quality = Self::RELIABLE;
+        let mut silent = true;
+        for &x in samples {
+            if !x.is_finite() {
+                quality |= Self::NON_FINITE;
+                silent = false;
+                continue;
+            }
+            if x.abs() >= 1.0 {
+                quality |= Self::CLIPPED;
+            }
+            if x.is_subnormal() {
+                quality |= Self::DENORMAL;
+            }
+            if x != 0.0 {
+                silent = false;
+            }
+        }
+        if silent {
+            quality |= Self::SILENT;
+        }
+        quality
+    }
+
+    /// Inspect a magnitude spectrum for subnormals, non-finite values and
+    /// silence. Magnitudes have no full scale, so clipping is not flagged.
+    pub(crate) fn inspect_spectrum(magnitudes: &[f64]) -> Self {
+        let quality = Self::inspect(magnitudes);
+        Self(quality.0 & !Self::CLIPPED.0)
+    }
+
+    /// Inspect analysis output values, flagging [`FrameQuality::NON_FINITE`]
+    /// when any value is NaN or infinite.
+    pub fn inspect_output(values: &[f64]) -> Self {
+        if values.iter().all(|v| v.is_finite()) {
+            Self::RELIABLE
+        } else {
+            Self::NON_FINITE
+        }
+    }
+
+    /// Returns `true` if no flags are set.
+    pub fn is_reliable(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Returns `true` if all flags in `other` are set.
+    pub fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Returns `true` if any flag in `other` is set.
+    pub fn intersects(self, other: Self) -> bool {
+        self.0 & other.0 != 0
+    }
+
+    /// Raw bit representation.
+    pub fn bits(self) -> u8 {
+        self.0
+    }
+
+    /// Per-frame weights which are `0.0` for frames flagged with any of
+    /// `exclude` and `1.0` otherwise.
+    pub fn weights(flags: &[Self], exclude: Self) -> Vec<f64> {
+        flags
+            .iter()
+            .map(|q| if q.intersects(exclude) { 0.0 } else { 1.0 })
+            .collect()
+    }
+}
+
+impl BitOr for FrameQuality {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for FrameQuality {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+impl fmt::Display for FrameQuality {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_reliable() {
+            return write!(f, "reliable");
+        }
+        let names = [
+            (Self::CLIPPED, "clipped"),
+            (Self::DENORMAL, "denormal"),
+            (Self::NON_FINITE, "non-finite"),
+            (Self::SILENT, "silent"),
+            (Self::UNVOICED, "unvoiced"),
+        ];
+        let mut first = true;
+        for (flag, name) in names {
+            if self.contains(flag) {
+                if !first {
+                    write!(f, "|")?;
+                }
+                write!(f, "{name}")?;
+                first = false;
+            }
+        }
+        Ok(())
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inspect_flags_problem_frames() {
+        assert!(FrameQuality::inspect(&[0.5, -0.5]).is_reliable());
+        assert_eq!(FrameQuality::inspect(&[0.0, 0.0]), FrameQuality::SILENT);
+        assert_eq!(
+            FrameQuality::inspect(&[f64::MIN_POSITIVE / 2.0, -1.5]),
+            FrameQuality::DENORMAL | FrameQuality::CLIPPED
+        );
+        assert_eq!(
+            FrameQuality::inspect(&[0.0, f64::NAN]),
+            FrameQuality::NON_FINITE
+        );
+        assert_eq!(
+            FrameQuality::inspect_spectrum(&[0.0, 2.0]),
+            FrameQuality::RELIABLE
+        );
+        assert_eq!(
+            FrameQuality::inspect_output(&[1.0, f64::INFINITY]),
+            FrameQuality::NON_FINITE
+        );
+    }
+
+    #[test]
+    fn weights_exclude_selected_flags() {
+        let flags = [
+            FrameQuality::RELIABLE,
+            FrameQuality::SILENT,
+            FrameQuality::CLIPPED | FrameQuality::UNVOICED,
+        ];
+        assert_eq!(
+            FrameQuality::weights(&flags, FrameQuality::CLIPPED | FrameQuality::NON_FINITE),
+            vec![1.0, 1.0, 0.0]
+        );
+        assert_eq!(flags[2].to_string(), "clipped|unvoiced");
+    }
+}
@@ -0,0 +1,89 @@
+use std::collections::VecDeque;
+
+// -------------------------------------------------------------------------------------------------
+
+/// The most recent input samples of a stateful processor, kept so its native
+/// state can be rebuilt on `restore_state` by resetting and replaying them.
+///
+/// The buffer is allocated once with its full capacity, so recording on an
+/// audio thread does not allocate. Streams only keep one after
+/// `set_state_recording(true)`, so callers who never checkpoint pay nothing.
+#[derive(Debug, Clone)]
+pub(crate) struct ReplayHistory {
+    samples: VecDeque<f64>,
+    capacity: usize,
+}
+
+impl ReplayHistory {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            samples: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Record `samples`, dropping the oldest ones beyond the capacity.
+    pub fn extend<I>(&mut self, samples: I)
+    where
+        I: IntoIterator<Item = f64>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        let samples = samples.into_iter();
+        let incoming = samples.len().min(self.capacity);
+        let skip = samples.len() - incoming;
+        let overflow = (self.samples.len() + incoming).saturating_sub(self.capacity);
+        self.samples.drain(..overflow);
+        self.samples.extend(samples.skip(skip));
+    }
+
+    /// The last `len` recorded samples (or all of them), oldest first.
+    pub fn latest(&self, len: usize) -> Vec<f64> {
+        let skip = self.samples.len().saturating_sub(len);
+        self.samples.iter().skip(skip).copied().collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn clear(&mut self) {
+        self.samples.clear();
+    }
+}
+
+/// Number of samples to replay into a hop-based stream so that it ends in
+/// the same hop phase: the largest length up to `available` that leaves
+/// `until_hop` samples before the next hop, given replay starts on a hop
+/// boundary.
+pub(crate) fn replay_len(available: usize, hop_size: usize, until_hop: usize) -> usize {
+    let phase = (hop_size - until_hop) % hop_size;
+    if available < phase {
+        return available;
+    }
+    available - (available - phase) % hop_size
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replay_history_keeps_latest_samples() {
+        let mut history = ReplayHistory::new(4);
+        history.extend([1.0, 2.0, 3.0]);
+        history.extend([4.0, 5.0, 6.0]);
+        assert_eq!(history.latest(10), vec![3.0, 4.0, 5.0, 6.0]);
+        history.extend([7.0, 8.0, 9.0, 10.0, 11.0]);
+        assert_eq!(history.latest(10), vec![8.0, 9.0, 10.0, 11.0]);
+        history.extend([1.0, 2.0, 3.0]);
+        history.extend([4.0, 5.0, 6.0]);
+        assert_eq!(history.len(), 4);
+        assert_eq!(history.latest(10), vec![3.0, 4.0, 5.0, 6.0]);
+        assert_eq!(history.latest(2), vec![5.0, 6.0]);
+        assert_eq!(replay_len(10, 4, 4), 8);
+        assert_eq!(replay_len(10, 4, 1), 7);
+        assert_eq!(replay_len(3, 4, 1), 3);
+    }
+}
@@ -1,9 +1,13 @@
 use flucoma_sys::{
-    robust_scaling_create, robust_scaling_destroy, robust_scaling_fit, robust_scaling_initialized,
+    robust_scaling_create, robust_scaling_data_high, robust_scaling_data_low,
+    robust_scaling_destroy, robust_scaling_fit, robust_scaling_initialized, robust_scaling_medians,
     robust_scaling_process, FlucomaIndex,
 };
 
+use crate::column_mask::{replace_columns, select_columns, update_mask, ColumnMasked};
+use crate::error::FlucomaError;
 use crate::matrix::Matrix;
+use crate::transform::Transform;
 
 /// Percentile-based robust scaler for dataset-style matrices.
 ///
@@ -19,6 +23,8 @@ pub struct RobustScale {
     low_percentile: f64,
     high_percentile: f64,
     cols: Option<usize>,
+    /// Feature columns to scale, or `None` for all of them.
+    columns: Option<Vec<usize>>,
 }
 
 // SAFETY: flucoma algorithms are thread-safe to move between threads.
@@ -29,30 +35,38 @@ impl RobustScale {
     ///
     /// # Errors
     /// Returns an error if the percentile range is invalid.
-    pub fn new(low_percentile: f64, high_percentile: f64) -> Result<Self, &'static str> {
+    pub fn new(low_percentile: f64, high_percentile: f64) -> Result<Self, FlucomaError> {
         if !(0.0..=100.0).contains(&low_percentile) {
-            return Err("low_percentile must be in [0, 100]");
+            return Err(FlucomaError::InvalidParameter(
+                "low_percentile must be in [0, 100]",
+            ));
         }
         if !(0.0..=100.0).contains(&high_percentile) {
-            return Err("high_percentile must be in [0, 100]");
+            return Err(FlucomaError::InvalidParameter(
+                "high_percentile must be in [0, 100]",
+            ));
         }
         if low_percentile > high_percentile {
-            return Err("low_percentile must be <= high_percentile");
+            return Err(FlucomaError::InvalidParameter(
+                "low_percentile must be <= high_percentile",
+            ));
         }
         let inner = robust_scaling_create();
         if inner.is_null() {
-            return Err("failed to create RobustScaling instance");
+            return Err(FlucomaError::AllocationFailed);
         }
         Ok(Self {
             inner,
             low_percentile,
             high_percentile,
             cols: None,
+            columns: None,
         })
     }
 
     /// Fit the scaler from a row-major matrix.
-    pub fn fit(&mut self, data: &Matrix) -> Result<(), &'static str> {
+    pub fn fit(&mut self, data: &Matrix) -> Result<(), FlucomaError> {
+        let data = select_columns(data, self.columns.as_deref())?;
         robust_scaling_fit(
             self.inner,
             self.low_percentile,
@@ -66,42 +80,111 @@ impl RobustScale {
     }
 
     /// Transform a matrix using the fitted percentile statistics.
-    pub fn transform(&self, data: &Matrix) -> Result<Matrix, &'static str> {
+    pub fn transform(&self, data: &Matrix) -> Result<Matrix, FlucomaError> {
         self.process_internal(data, false)
     }
 
     /// Undo a previous robust scaling step.
-    pub fn inverse_transform(&self, data: &Matrix) -> Result<Matrix, &'static str> {
+    pub fn inverse_transform(&self, data: &Matrix) -> Result<Matrix, FlucomaError> {
         self.process_internal(data, true)
     }
 
     /// Fit the scaler and transform the same matrix in one step.
-    pub fn fit_transform(&mut self, data: &Matrix) -> Result<Matrix, &'static str> {
+    pub fn fit_transform(&mut self, data: &Matrix) -> Result<Matrix, FlucomaError> {
         self.fit(data)?;
         self.transform(data)
     }
 
     pub fn is_fitted(&self) -> bool {
-        robust_scaling_initialized(self.inner)
+        self.cols.is_some() && robust_scaling_initialized(self.inner)
     }
 
-    fn process_internal(&self, data: &Matrix, inverse: bool) -> Result<Matrix, &'static str> {
+    /// The configured `(low, high)` percentiles.
+    pub fn percentiles(&self) -> (f64, f64) {
+        (self.low_percentile, self.high_percentile)
+    }
+
+    /// Per-feature median learned by [`RobustScale::fit`], or `None` if the
+    /// scaler is not fitted.
+    pub fn medians(&self) -> Option<Vec<f64>> {
+        self.fitted_vector(robust_scaling_medians)
+    }
+
+    /// Per-feature value at the low percentile, or `None` if the scaler is
+    /// not fitted.
+    pub fn data_low(&self) -> Option<Vec<f64>> {
+        self.fitted_vector(robust_scaling_data_low)
+    }
+
+    /// Per-feature value at the high percentile, or `None` if the scaler is
+    /// not fitted.
+    ///
+    /// Together with [`RobustScale::medians`] and [`RobustScale::data_low`]
+    /// this fully describes the transform:
+    /// `(x - median) / (data_high - data_low)`.
+    pub fn data_high(&self) -> Option<Vec<f64>> {
+        self.fitted_vector(robust_scaling_data_high)
+    }
+
+    fn fitted_vector(&self, get: fn(*mut u8, *mut f64, FlucomaIndex)) -> Option<Vec<f64>> {
+        let cols = self.cols.filter(|_| self.is_fitted())?;
+        let mut values = vec![0.0; cols];
+        get(self.inner, values.as_mut_ptr(), cols as FlucomaIndex);
+        Some(values)
+    }
+
+    fn process_internal(&self, data: &Matrix, inverse: bool) -> Result<Matrix, FlucomaError> {
         if !self.is_fitted() {
-            return Err("robust scaler is not fitted");
+            return Err(FlucomaError::NotFitted);
         }
-        if self.cols != Some(data.cols()) {
-            return Err("cols must match fitted feature dimension");
+        let columns = self.columns.as_deref();
+        let selected = select_columns(data, columns)?;
+        if self.cols != Some(selected.cols()) {
+            return Err(FlucomaError::InvalidParameter(
+                "cols must match fitted feature dimension",
+            ));
         }
-        let mut out = Matrix::new(data.rows(), data.cols());
+        let mut out = Matrix::new(selected.rows(), selected.cols());
         robust_scaling_process(
             self.inner,
-            data.data().as_ptr(),
-            data.rows() as FlucomaIndex,
-            data.cols() as FlucomaIndex,
+            selected.data().as_ptr(),
+            selected.rows() as FlucomaIndex,
+            selected.cols() as FlucomaIndex,
             out.data_mut().as_mut_ptr(),
             inverse,
         );
-        Ok(out)
+        Ok(replace_columns(data, columns, out))
+    }
+}
+
+impl ColumnMasked for RobustScale {
+    fn set_columns(&mut self, columns: Option<Vec<usize>>) -> Result<(), FlucomaError> {
+        if update_mask(&mut self.columns, columns)? {
+            self.cols = None;
+        }
+        Ok(())
+    }
+
+    fn columns(&self) -> Option<&[usize]> {
+        self.columns.as_deref()
+    }
+}
+
+impl Transform for RobustScale {
+    fn fit(&mut self, data: &Matrix) -> Result<(), FlucomaError> {
+        RobustScale::fit(self, data)
+    }
+
+    fn transform(&self, data: &Matrix) -> Result<Matrix, FlucomaError> {
+        RobustScale::transform(self, data)
+    }
+
+    fn inverse_transform(&self, data: &Matrix) -> Result<Matrix, FlucomaError> {
+        RobustScale::inverse_transform(self, data)
+    }
+
+    fn is_fitted(&self) -> bool {
+        RobustScale::is_fitted(self)
     }
 }
 
@@ -127,11 +210,23 @@ mod tests {
         }
     }
 
+    #[test]
+    fn fitted_parameters_are_per_feature() {
+        let data = Matrix::from_vec(vec![1.0, 10.0, 3.0, 20.0, 5.0, 30.0], 3, 2).unwrap();
+        let mut r = RobustScale::new(0.0, 100.0).unwrap();
+        assert_eq!(r.medians(), None);
+        r.fit(&data).unwrap();
+        assert_eq!(r.percentiles(), (0.0, 100.0));
+        assert_eq!(r.medians(), Some(vec![3.0, 20.0]));
+        assert_eq!(r.data_low(), Some(vec![1.0, 10.0]));
+        assert_eq!(r.data_high(), Some(vec![5.0, 30.0]));
+    }
+
     #[test]
     fn transform_before_fit_fails() {
         let r = RobustScale::new(25.0, 75.0).unwrap();
         let data = Matrix::from_vec(vec![1.0, 2.0], 1, 2).unwrap();
         let err = r.transform(&data).unwrap_err();
-        assert_eq!(err, "robust scaler is not fitted");
+        assert_eq!(err, FlucomaError::NotFitted);
     }
 }
@@ -2,6 +2,9 @@ use flucoma_sys::{
     running_stats_create, running_stats_destroy, running_stats_init, running_stats_process,
 };
 
+use crate::error::{check_len, FlucomaError};
+use crate::replay::ReplayHistory;
+
 /// Incremental running mean and sample standard deviation.
 pub struct RunningStats {
     inner: *mut u8,
@@ -9,6 +12,18 @@ pub struct RunningStats {
     input_size: usize,
     mean_buf: Vec<f64>,
     stddev_buf: Vec<f64>,
+    /// The inputs currently in the window, for [`RunningStats::save_state`].
+    recent: ReplayHistory,
+}
+
+/// Snapshot of a [`RunningStats`] window, from [`RunningStats::save_state`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RunningStatsState {
+    history_size: usize,
+    input_size: usize,
+    /// Inputs in the window, oldest first, row-major.
+    inputs: Vec<f64>,
 }
 
 // SAFETY: flucoma algorithms are thread-safe to move between threads.
@@ -16,16 +31,16 @@ unsafe impl Send for RunningStats {}
 
 impl RunningStats {
     /// Create and initialize a running statistics processor.
-    pub fn new(history_size: usize, input_size: usize) -> Result<Self, &'static str> {
+    pub fn new(history_size: usize, input_size: usize) -> Result<Self, FlucomaError> {
         if history_size < 2 {
-            return Err("history_size must be >= 2");
+            return Err(FlucomaError::InvalidParameter("history_size must be >= 2"));
         }
         if input_size == 0 {
-            return Err("input_size must be > 0");
+            return Err(FlucomaError::InvalidParameter("input_size must be > 0"));
         }
         let inner = running_stats_create();
         if inner.is_null() {
-            return Err("failed to create RunningStats instance");
+            return Err(FlucomaError::AllocationFailed);
         }
         running_stats_init(inner, history_size as isize, input_size as isize);
         Ok(Self {
@@ -34,6 +49,7 @@ impl RunningStats {
             input_size,
             mean_buf: vec![0.0; input_size],
             stddev_buf: vec![0.0; input_size],
+            recent: ReplayHistory::new(history_size * input_size),
         })
     }
 
@@ -55,9 +71,21 @@ impl RunningStats {
             self.mean_buf.as_mut_ptr(),
             self.stddev_buf.as_mut_ptr(),
         );
+        self.recent.extend(input.iter().copied());
         (&self.mean_buf, &self.stddev_buf)
     }
 
+    /// Like [`RunningStats::process`], but returns
+    /// [`FlucomaError::InvalidShape`] instead of panicking if
+    /// `input.len() != input_size`.
+    pub fn try_process<'a>(
+        &'a mut self,
+        input: &[f64],
+    ) -> Result<(&'a [f64], &'a [f64]), FlucomaError> {
+        check_len(self.input_size, input.len())?;
+        Ok(self.process(input))
+    }
+
     /// Reset internal history.
     pub fn clear(&mut self) {
         running_stats_init(
@@ -65,6 +93,35 @@ impl RunningStats {
             self.history_size as isize,
             self.input_size as isize,
         );
+        self.recent.clear();
+    }
+
+    /// Capture the current window so the statistics can be resumed later,
+    /// e.g. after restarting a long-running analysis.
+    pub fn save_state(&self) -> RunningStatsState {
+        RunningStatsState {
+            history_size: self.history_size,
+            input_size: self.input_size,
+            inputs: self.recent.latest(self.history_size * self.input_size),
+        }
+    }
+
+    /// Replace the window with one captured by [`RunningStats::save_state`].
+    ///
+    /// # Errors
+    /// Returns an error if the state was saved from a processor with a
+    /// different `history_size` or `input_size`.
+    pub fn restore_state(&mut self, state: &RunningStatsState) -> Result<(), FlucomaError> {
+        if state.history_size != self.history_size || state.input_size != self.input_size {
+            return Err(FlucomaError::InvalidParameter(
+                "state was saved with a different history_size or input_size",
+            ));
+        }
+        self.clear();
+        for input in state.inputs.chunks_exact(self.input_size) {
+            let _ = self.process(input);
+        }
+        Ok(())
     }
 
     pub fn history_size(&self) -> usize {
@@ -112,6 +169,24 @@ mod tests {
         assert!(stddev[1].abs() < 1e-12);
     }
 
+    #[test]
+    fn restored_state_resumes_statistics() {
+        let mut rs = RunningStats::new(3, 1).unwrap();
+        for x in [1.0, 2.0, 3.0, 4.0] {
+            let _ = rs.process(&[x]);
+        }
+        let state = rs.save_state();
+        let mut resumed = RunningStats::new(3, 1).unwrap();
+        resumed.restore_state(&state).unwrap();
+        let expected = rs.process(&[5.0]).0.to_vec();
+        assert!((resumed.process(&[5.0]).0[0] - expected[0]).abs() < 1e-12);
+        assert!((expected[0] - 4.0).abs() < 1e-12);
+        assert!(RunningStats::new(4, 1)
+            .unwrap()
+            .restore_state(&state)
+            .is_err());
+    }
+
     #[test]
     fn nan_input_is_cleaned_to_zero() {
         let mut rs = RunningStats::new(4, 1).unwrap();
@@ -0,0 +1,25 @@
+// -------------------------------------------------------------------------------------------------
+
+/// Convert `input` into the reusable `scratch` buffer and return it, so the
+/// `_f32` entry points can hand f64 frames to the C++ side without
+/// allocating once `scratch` has grown to the frame size.
+pub(crate) fn widen<'a>(input: &[f32], scratch: &'a mut Vec<f64>) -> &'a [f64] {
+    scratch.clear();
+    scratch.extend(input.iter().map(|&sample| f64::from(sample)));
+    scratch
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn widen_reuses_scratch() {
+        let mut scratch = Vec::with_capacity(4);
+        assert_eq!(widen(&[0.5, -1.0, 0.25], &mut scratch), &[0.5, -1.0, 0.25]);
+        assert_eq!(widen(&[2.0], &mut scratch), &[2.0]);
+        assert_eq!(scratch.capacity(), 4);
+    }
+}
@@ -1,11 +1,18 @@
+use std::fmt;
+use std::str::FromStr;
+
 use flucoma_sys::{sine_create, sine_destroy, sine_init, sine_process_frame};
 use num_complex::Complex64 as Complex;
 
+use crate::error::FlucomaError;
+use crate::param_names::{display_name, parse_name};
+
 // -------------------------------------------------------------------------------------------------
 
 /// Peak sort order for [`Sine::process_frame`].
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 #[repr(isize)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SortBy {
     /// Sort detected peaks by frequency (bin index), ascending.
     #[default]
@@ -14,6 +21,26 @@ pub enum SortBy {
     Magnitude = 1,
 }
 
+const SORT_BY_NAMES: &[(&str, SortBy)] = &[
+    ("frequency", SortBy::Frequency),
+    ("magnitude", SortBy::Magnitude),
+];
+
+impl FromStr for SortBy {
+    type Err = FlucomaError;
+
+    /// Parse a name such as `"magnitude"`, ignoring case and separators.
+    fn from_str(name: &str) -> Result<Self, FlucomaError> {
+        parse_name(name, SORT_BY_NAMES, "unknown sort order")
+    }
+}
+
+impl fmt::Display for SortBy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(display_name(self, SORT_BY_NAMES))
+    }
+}
+
 // -------------------------------------------------------------------------------------------------
 
 /// Sinusoidal peak detector for complex spectral frames.
@@ -29,6 +56,8 @@ pub enum SortBy {
 pub struct Sine {
     inner: *mut u8,
     num_bins: usize, // fft_size / 2 + 1
+    window_size: usize,
+    fft_size: usize,
 }
 
 unsafe impl Send for Sine {}
@@ -41,22 +70,26 @@ impl Sine {
     /// * `fft_size`    - FFT size (must be >= `window_size`).
     ///
     /// # Errors
-    /// Returns an error string if parameters are invalid or allocation fails.
-    pub fn new(window_size: usize, fft_size: usize) -> Result<Self, &'static str> {
+    /// Returns an error if parameters are invalid or allocation fails.
+    pub fn new(window_size: usize, fft_size: usize) -> Result<Self, FlucomaError> {
         if window_size == 0 {
-            return Err("window_size must be > 0");
+            return Err(FlucomaError::InvalidParameter("window_size must be > 0"));
         }
         if fft_size < window_size {
-            return Err("fft_size must be >= window_size");
+            return Err(FlucomaError::InvalidParameter(
+                "fft_size must be >= window_size",
+            ));
         }
         let inner = sine_create();
         if inner.is_null() {
-            return Err("failed to create SineFeature instance");
+            return Err(FlucomaError::AllocationFailed);
         }
         sine_init(inner, window_size as isize, fft_size as isize);
         Ok(Self {
             inner,
             num_bins: fft_size / 2 + 1,
+            window_size,
+            fft_size,
         })
     }
 
@@ -123,6 +156,12 @@ impl Drop for Sine {
     }
 }
 
+impl Clone for Sine {
+    fn clone(&self) -> Self {
+        Self::new(self.window_size, self.fft_size).expect("settings were validated on construction")
+    }
+}
+
 // -------------------------------------------------------------------------------------------------
 
 #[cfg(test)]
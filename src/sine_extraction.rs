@@ -1,10 +1,13 @@
 use flucoma_sys::{sine_ext_create, sine_ext_destroy, sine_ext_init, sine_ext_process_frame};
 use num_complex::Complex64;
 
+use crate::error::FlucomaError;
+
 // -------------------------------------------------------------------------------------------------
 
 /// Tracking and detection parameters for [`SineExtraction::process_frame`].
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SineExtractionParams {
     /// Audio sample rate in Hz.
     pub sample_rate: f64,
@@ -52,8 +55,9 @@ impl Default for SineExtractionParams {
 /// - **sines** -- the reconstructed sinusoidal content
 /// - **residual** -- what remains after subtracting the sines
 ///
-/// Note: there is an inherent latency of `min_track_length` frames before
-/// the sinusoidal output becomes non-zero (the tracker needs history to confirm tracks).
+/// The tracker needs `min_track_length` frames of history to confirm a
+/// track, so the sinusoidal output lags by
+/// [`latency_frames`](SineExtraction::latency_frames) frames.
 ///
 /// Two-phase setup:
 /// 1. [`SineExtraction::new`] -- allocates internal buffers.
@@ -63,10 +67,15 @@ impl Default for SineExtractionParams {
 pub struct SineExtraction {
     inner: *mut u8,
     n_bins: usize,
+    window_size: usize,
+    fft_size: usize,
+    transform_size: usize,
     /// Interleaved FFI output buffer, shape (n_bins, 2) complex values.
     ffi_buf: Vec<Complex64>,
     /// Deinterleaved output: [sines | residual], each n_bins long.
     out_buf: Vec<Complex64>,
+    /// `min_track_length` of the last processed frame.
+    min_track_length: usize,
 }
 
 unsafe impl Send for SineExtraction {}
@@ -81,28 +90,32 @@ impl SineExtraction {
     /// * `transform_size` - Window transform size for partial synthesis (must be > 0).
     ///
     /// # Errors
-    /// Returns an error string if parameters are invalid or allocation fails.
+    /// Returns an error if parameters are invalid or allocation fails.
     pub fn new(
         window_size: usize,
         fft_size: usize,
         transform_size: usize,
-    ) -> Result<Self, &'static str> {
+    ) -> Result<Self, FlucomaError> {
         if window_size == 0 {
-            return Err("window_size must be > 0");
+            return Err(FlucomaError::InvalidParameter("window_size must be > 0"));
         }
         if fft_size == 0 || !fft_size.is_multiple_of(2) {
-            return Err("fft_size must be > 0 and even");
+            return Err(FlucomaError::InvalidParameter(
+                "fft_size must be > 0 and even",
+            ));
         }
         if fft_size < window_size {
-            return Err("fft_size must be >= window_size");
+            return Err(FlucomaError::InvalidParameter(
+                "fft_size must be >= window_size",
+            ));
         }
         if transform_size == 0 {
-            return Err("transform_size must be > 0");
+            return Err(FlucomaError::InvalidParameter("transform_size must be > 0"));
         }
         let n_bins = fft_size / 2 + 1;
         let inner = sine_ext_create(fft_size as isize);
         if inner.is_null() {
-            return Err("failed to create SineExtraction instance");
+            return Err(FlucomaError::AllocationFailed);
         }
         sine_ext_init(
             inner,
@@ -113,8 +126,12 @@ impl SineExtraction {
         Ok(Self {
             inner,
             n_bins,
+            window_size,
+            fft_size,
+            transform_size,
             ffi_buf: vec![Complex64::default(); n_bins * 2],
             out_buf: vec![Complex64::default(); n_bins * 2],
+            min_track_length: SineExtractionParams::default().min_track_length,
         })
     }
 
@@ -144,6 +161,7 @@ impl SineExtraction {
             input.len(),
             self.n_bins
         );
+        self.min_track_length = params.min_track_length;
         sine_ext_process_frame(
             self.inner,
             input.as_ptr() as *const f64,
@@ -174,6 +192,15 @@ impl SineExtraction {
     pub fn n_bins(&self) -> usize {
         self.n_bins
     }
+
+    /// Frames by which the sinusoidal output lags the input: the
+    /// `min_track_length` of the last processed frame (the default before
+    /// the first). Multiply by the hop and add the STFT's
+    /// [`latency_samples`](crate::fourier::Stft::latency_samples) for the
+    /// total delay in samples.
+    pub fn latency_frames(&self) -> usize {
+        self.min_track_length
+    }
 }
 
 impl Drop for SineExtraction {
@@ -182,6 +209,13 @@ impl Drop for SineExtraction {
     }
 }
 
+impl Clone for SineExtraction {
+    fn clone(&self) -> Self {
+        Self::new(self.window_size, self.fft_size, self.transform_size)
+            .expect("settings were validated on construction")
+    }
+}
+
 // -------------------------------------------------------------------------------------------------
 
 #[cfg(test)]
@@ -218,4 +252,31 @@ mod tests {
         assert_eq!(sines.len(), n_bins);
         assert_eq!(residual.len(), n_bins);
     }
+
+    #[test]
+    fn sine_ext_output_starts_after_latency() {
+        use crate::stft::{Stft, WindowType};
+
+        let mut stft = Stft::new(1024, 1024, 256, WindowType::Hann).unwrap();
+        let tone: Vec<f64> = (0..1024)
+            .map(|i| 0.5 * (2.0 * std::f64::consts::PI * 1000.0 * i as f64 / 44100.0).sin())
+            .collect();
+        let spectrum = stft.process_frame(&tone);
+        let params = SineExtractionParams {
+            min_track_length: 5,
+            ..SineExtractionParams::default()
+        };
+        let mut se = SineExtraction::new(1024, 1024, 1024).unwrap();
+        let first = (0..40)
+            .position(|_| {
+                let (sines, _) = se.process_frame(&spectrum.bins, &params);
+                sines.iter().any(|s| s.norm() > 1e-6)
+            })
+            .expect("no sinusoidal output");
+        assert_eq!(se.latency_frames(), 5);
+        assert!(
+            first.abs_diff(se.latency_frames()) <= 1,
+            "sines from frame {first}"
+        );
+    }
 }
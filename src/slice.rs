@@ -0,0 +1,322 @@
+// -------------------------------------------------------------------------------------------------
+
+/// A segment of a buffer, as returned by the `slices` methods of the
+/// whole-buffer (`Buf*`) segmenters.
+///
+/// Positions are latency-compensated, so they line up with the analysed
+/// audio the way FluCoMa's slice outputs do. `end_sample` is exclusive.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Slice {
+    /// First sample of the slice.
+    pub start_sample: usize,
+    /// One past the last sample of the slice.
+    pub end_sample: usize,
+    /// Start time in seconds.
+    pub start_sec: f64,
+    /// End time in seconds.
+    pub end_sec: f64,
+}
+
+impl Slice {
+    /// Create a slice covering `start_sample..end_sample` of a buffer at
+    /// `sample_rate`.
+    pub fn new(start_sample: usize, end_sample: usize, sample_rate: f64) -> Self {
+        Self {
+            start_sample,
+            end_sample,
+            start_sec: start_sample as f64 / sample_rate,
+            end_sec: end_sample as f64 / sample_rate,
+        }
+    }
+
+    /// Length in samples.
+    pub fn len_samples(&self) -> usize {
+        self.end_sample - self.start_sample
+    }
+
+    /// Duration in seconds.
+    pub fn duration_sec(&self) -> f64 {
+        self.end_sec - self.start_sec
+    }
+}
+
+/// A detected slice point with a confidence score, as returned by the
+/// `process_scored` methods of the whole-buffer segmenters.
+///
+/// `confidence` is the height of the segmenter's own detection function
+/// above its threshold at the detection, so it is never negative and a larger
+/// value is a stronger slice point. It is measured in the units of that
+/// function (novelty curve, onset detection function or prediction error
+/// ratio), so points can be ranked or pruned within one analysis but not
+/// compared across segmenters.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SlicePoint {
+    /// Position in samples.
+    pub position: usize,
+    /// Height of the detection function above the threshold, >= 0.
+    pub confidence: f64,
+}
+
+/// Turn ascending onset positions into contiguous slices covering
+/// `0..signal_len`: one slice before the first onset (if it is not at 0) and
+/// one from each onset to the next, the last ending at `signal_len`.
+pub(crate) fn slices_from_onsets(
+    onsets: &[usize],
+    signal_len: usize,
+    sample_rate: f64,
+) -> Vec<Slice> {
+    let mut boundaries = Vec::with_capacity(onsets.len() + 2);
+    boundaries.push(0);
+    boundaries.extend(onsets.iter().copied().filter(|&o| o > 0 && o < signal_len));
+    boundaries.push(signal_len);
+    boundaries.dedup();
+    boundaries
+        .windows(2)
+        .map(|pair| Slice::new(pair[0], pair[1], sample_rate))
+        .collect()
+}
+
+/// Move each onset back to the nearest preceding local minimum of short-time
+/// energy, so slices start before the attack rather than inside it (as
+/// librosa's `backtrack` option does).
+///
+/// Energy is measured over consecutive `frame_size`-sample frames. Each onset
+/// walks back frame by frame while the energy keeps falling, never past the
+/// previous onset, and lands on the start of the quietest frame. Onsets that
+/// are already at a minimum are left untouched.
+///
+/// ```rust
+/// use flucoma_rs::segmentation::backtrack_onsets;
+///
+/// let mut audio = vec![0.0f64; 1000];
+/// for (i, sample) in audio.iter_mut().enumerate().skip(400).take(300) {
+///     *sample = (i - 400) as f64 / 300.0;
+/// }
+/// assert_eq!(backtrack_onsets(&audio, &[600], 100), vec![300]);
+/// ```
+pub fn backtrack_onsets(signal: &[f64], onsets: &[usize], frame_size: usize) -> Vec<usize> {
+    backtrack(signal, onsets, frame_size)
+        .into_iter()
+        .flatten()
+        .collect()
+}
+
+/// [`backtrack_onsets`] for scored slice points, keeping each point's
+/// confidence.
+pub(crate) fn backtrack_points(
+    signal: &[f64],
+    points: &[SlicePoint],
+    frame_size: usize,
+) -> Vec<SlicePoint> {
+    let positions: Vec<usize> = points.iter().map(|point| point.position).collect();
+    backtrack(signal, &positions, frame_size)
+        .into_iter()
+        .zip(points)
+        .filter_map(|(position, point)| position.map(|position| SlicePoint { position, ..*point }))
+        .collect()
+}
+
+/// The backtracked position of each onset, or `None` where it would not lie
+/// after the previous one.
+fn backtrack(signal: &[f64], onsets: &[usize], frame_size: usize) -> Vec<Option<usize>> {
+    let frame_size = frame_size.max(1);
+    let energy: Vec<f64> = signal
+        .chunks(frame_size)
+        .map(|frame| frame.iter().map(|x| x * x).sum())
+        .collect();
+    let mut refined = Vec::with_capacity(onsets.len());
+    let mut last = None;
+    let mut floor = 0;
+    for &onset in onsets {
+        let start = (onset / frame_size).min(energy.len().saturating_sub(1));
+        let mut frame = start;
+        while frame > floor && energy[frame - 1] < energy[frame] {
+            frame -= 1;
+        }
+        let position = if frame == start {
+            onset
+        } else {
+            frame * frame_size
+        };
+        if last.is_none_or(|last| position > last) {
+            refined.push(Some(position));
+            last = Some(position);
+        } else {
+            refined.push(None);
+        }
+        floor = start;
+    }
+    refined
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Merge every slice shorter than `min_samples` into its neighbour: into the
+/// previous slice where there is one, otherwise into the next.
+///
+/// Input slices are expected to be contiguous and in ascending order, as
+/// returned by the `slices` methods.
+pub fn merge_short_slices(slices: &[Slice], min_samples: usize) -> Vec<Slice> {
+    let mut merged: Vec<Slice> = Vec::with_capacity(slices.len());
+    for slice in slices {
+        match merged.last_mut() {
+            Some(last) if slice.len_samples() < min_samples || last.len_samples() < min_samples => {
+                last.end_sample = slice.end_sample;
+                last.end_sec = slice.end_sec;
+            }
+            _ => merged.push(*slice),
+        }
+    }
+    merged
+}
+
+/// Split every slice longer than `max_samples` into the fewest equal parts
+/// that are no longer than `max_samples`.
+///
+/// ```rust
+/// use flucoma_rs::segmentation::{split_long_slices, Slice};
+///
+/// let parts = split_long_slices(&[Slice::new(0, 1000, 1000.0)], 400, 1000.0);
+/// assert_eq!(parts.len(), 3);
+/// assert_eq!(parts[1], Slice::new(333, 666, 1000.0));
+/// ```
+pub fn split_long_slices(slices: &[Slice], max_samples: usize, sample_rate: f64) -> Vec<Slice> {
+    let max_samples = max_samples.max(1);
+    let mut split = Vec::with_capacity(slices.len());
+    for slice in slices {
+        let len = slice.len_samples();
+        let parts = len.div_ceil(max_samples).max(1);
+        if parts == 1 {
+            split.push(*slice);
+            continue;
+        }
+        let boundary = |part: usize| slice.start_sample + len * part / parts;
+        split.extend(
+            (0..parts).map(|part| Slice::new(boundary(part), boundary(part + 1), sample_rate)),
+        );
+    }
+    split
+}
+
+/// Move every slice boundary to the nearest zero crossing of `signal` within
+/// `max_distance` samples, so cuts do not click.
+///
+/// Boundaries shared by neighbouring slices move together, keeping the
+/// slices contiguous; boundaries at the buffer edges stay put, and slices
+/// that collapse to nothing are dropped.
+pub fn snap_slices_to_zero_crossings(
+    slices: &[Slice],
+    signal: &[f64],
+    max_distance: usize,
+    sample_rate: f64,
+) -> Vec<Slice> {
+    slices
+        .iter()
+        .map(|slice| {
+            Slice::new(
+                nearest_zero_crossing(signal, slice.start_sample, max_distance),
+                nearest_zero_crossing(signal, slice.end_sample, max_distance),
+                sample_rate,
+            )
+        })
+        .filter(|slice| slice.end_sample > slice.start_sample)
+        .collect()
+}
+
+/// Clamp slices to `0..len`, dropping those that fall outside it entirely.
+pub fn clamp_slices(slices: &[Slice], len: usize, sample_rate: f64) -> Vec<Slice> {
+    slices
+        .iter()
+        .filter(|slice| slice.start_sample < len)
+        .map(|slice| {
+            if slice.end_sample > len {
+                Slice::new(slice.start_sample, len, sample_rate)
+            } else {
+                *slice
+            }
+        })
+        .filter(|slice| slice.end_sample > slice.start_sample)
+        .collect()
+}
+
+/// The zero crossing (a sample whose sign differs from the previous one, or
+/// which is exactly zero) nearest to `position`, or `position` itself if
+/// there is none within `max_distance` or it lies on a buffer edge.
+fn nearest_zero_crossing(signal: &[f64], position: usize, max_distance: usize) -> usize {
+    if position == 0 || position >= signal.len() {
+        return position;
+    }
+    let is_crossing = |i: usize| {
+        i > 0
+            && i < signal.len()
+            && (signal[i] == 0.0 || (signal[i - 1] < 0.0) != (signal[i] < 0.0))
+    };
+    for distance in 0..=max_distance {
+        if is_crossing(position + distance) {
+            return position + distance;
+        }
+        if distance <= position && is_crossing(position - distance) {
+            return position - distance;
+        }
+    }
+    position
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slices_cover_the_buffer() {
+        let slices = slices_from_onsets(&[0, 250, 600], 1000, 1000.0);
+        assert_eq!(slices.len(), 3);
+        assert_eq!(slices[0], Slice::new(0, 250, 1000.0));
+        assert_eq!(slices[1].start_sec, 0.25);
+        assert_eq!(slices[2].end_sample, 1000);
+        assert_eq!(slices[2].len_samples(), 400);
+        assert!((slices[2].duration_sec() - 0.4).abs() < 1e-12);
+        assert!(slices_from_onsets(&[], 0, 1000.0).is_empty());
+    }
+
+    #[test]
+    fn backtrack_stops_at_previous_onset() {
+        let audio: Vec<f64> = (0..1000).map(|i| i as f64 / 1000.0).collect();
+        // a steady ramp has no minimum, so the second onset stops at the first
+        assert_eq!(backtrack_onsets(&audio, &[300, 800], 100), vec![0, 300]);
+        assert_eq!(backtrack_onsets(&[], &[10], 100), vec![10]);
+        let silence = vec![0.0; 1000];
+        assert_eq!(backtrack_onsets(&silence, &[550], 100), vec![550]);
+    }
+
+    #[test]
+    fn merge_and_clamp_slices() {
+        let slices = slices_from_onsets(&[50, 400, 420], 1000, 1000.0);
+        let merged = merge_short_slices(&slices, 100);
+        assert_eq!(
+            merged,
+            vec![Slice::new(0, 420, 1000.0), Slice::new(420, 1000, 1000.0)]
+        );
+        let clamped = clamp_slices(&merged, 300, 1000.0);
+        assert_eq!(clamped, vec![Slice::new(0, 300, 1000.0)]);
+    }
+
+    #[test]
+    fn snap_keeps_slices_contiguous() {
+        // period of 100 samples with sign changes at multiples of 50
+        let signal: Vec<f64> = (0..1000)
+            .map(|i| if (i / 50) % 2 == 0 { 1.0 } else { -1.0 })
+            .collect();
+        let slices = slices_from_onsets(&[290, 640], 1000, 1000.0);
+        let snapped = snap_slices_to_zero_crossings(&slices, &signal, 20, 1000.0);
+        assert_eq!(snapped[0], Slice::new(0, 300, 1000.0));
+        assert_eq!(snapped[1], Slice::new(300, 650, 1000.0));
+        assert_eq!(snapped[2], Slice::new(650, 1000, 1000.0));
+        // nothing within reach: boundaries stay put
+        let unchanged = snap_slices_to_zero_crossings(&slices, &signal, 5, 1000.0);
+        assert_eq!(unchanged, slices);
+    }
+}
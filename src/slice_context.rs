@@ -0,0 +1,176 @@
+use std::f64::consts::PI;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::error::FlucomaError;
+use crate::param_names::{display_name, parse_name};
+
+// -------------------------------------------------------------------------------------------------
+
+/// Fade shape applied to the context regions added by [`SliceContext`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ContextTaper {
+    /// Context is included at full gain.
+    #[default]
+    None,
+    /// Linear fade in over the pre-attack context and out over the
+    /// post-release context.
+    Linear,
+    /// Raised-cosine (half Hann) fades over the context regions.
+    Hann,
+}
+
+const CONTEXT_TAPER_NAMES: &[(&str, ContextTaper)] = &[
+    ("none", ContextTaper::None),
+    ("linear", ContextTaper::Linear),
+    ("hann", ContextTaper::Hann),
+];
+
+impl FromStr for ContextTaper {
+    type Err = FlucomaError;
+
+    /// Parse a name such as `"hann"`, ignoring case and separators.
+    fn from_str(name: &str) -> Result<Self, FlucomaError> {
+        parse_name(name, CONTEXT_TAPER_NAMES, "unknown context taper")
+    }
+}
+
+impl fmt::Display for ContextTaper {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(display_name(self, CONTEXT_TAPER_NAMES))
+    }
+}
+
+/// Pre-attack and post-release context to include around a slice when
+/// computing per-slice descriptors.
+///
+/// Analysis windows near hard slice boundaries see either a truncated attack
+/// or zero-padding, which biases spectral statistics. Extending each slice by
+/// a few milliseconds of surrounding audio, optionally faded in and out, gives
+/// the analysis the full attack while keeping the slice itself at unit gain.
+/// [`BufStats::process_slice`](crate::data::BufStats::process_slice)
+/// uses the taper as per-sample weights.
+///
+/// ```rust
+/// use flucoma_rs::segmentation::{ContextTaper, SliceContext};
+///
+/// let signal = vec![1.0f64; 1000];
+/// let context = SliceContext::new(2.0, 1.0, ContextTaper::Linear);
+/// // 2 ms and 1 ms at 1 kHz are 2 and 1 samples
+/// let region = context.extract(&signal, 100, 200, 1000.0);
+/// assert_eq!(region.len(), 103);
+/// assert_eq!(&region[..2], &[0.0, 0.5]);
+/// assert_eq!(region[2], 1.0);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SliceContext {
+    /// Context before the slice start, in milliseconds.
+    pub pre_ms: f64,
+    /// Context after the slice end, in milliseconds.
+    pub post_ms: f64,
+    /// Fade applied to the context regions.
+    pub taper: ContextTaper,
+}
+
+impl SliceContext {
+    /// Create a context policy. Negative durations are treated as zero.
+    pub fn new(pre_ms: f64, post_ms: f64, taper: ContextTaper) -> Self {
+        Self {
+            pre_ms: pre_ms.max(0.0),
+            post_ms: post_ms.max(0.0),
+            taper,
+        }
+    }
+
+    /// Sample range `(start, end)` of the slice `start..end` extended by the
+    /// context and clamped to `0..signal_len`.
+    pub fn bounds(
+        &self,
+        start: usize,
+        end: usize,
+        signal_len: usize,
+        sample_rate: f64,
+    ) -> (usize, usize) {
+        let pre = ms_to_samples(self.pre_ms, sample_rate);
+        let post = ms_to_samples(self.post_ms, sample_rate);
+        let end = end.min(signal_len);
+        let start = start.min(end);
+        (start.saturating_sub(pre), (end + post).min(signal_len))
+    }
+
+    /// Per-sample gains for the extended region returned by
+    /// [`SliceContext::bounds`]: the taper over the context regions and `1.0`
+    /// over the slice itself.
+    pub fn gains(&self, start: usize, end: usize, signal_len: usize, sample_rate: f64) -> Vec<f64> {
+        let (ext_start, ext_end) = self.bounds(start, end, signal_len, sample_rate);
+        let end = end.min(signal_len);
+        let start = start.min(end);
+        let pre_len = start - ext_start;
+        let post_len = ext_end - end;
+        let mut gains = vec![1.0; ext_end - ext_start];
+        for (i, gain) in gains[..pre_len].iter_mut().enumerate() {
+            *gain = self.fade(i, pre_len);
+        }
+        for (i, gain) in gains[end - ext_start..].iter_mut().enumerate() {
+            *gain = self.fade(post_len - 1 - i, post_len);
+        }
+        gains
+    }
+
+    /// Copy the slice `signal[start..end]` with its context, applying the
+    /// taper.
+    pub fn extract(&self, signal: &[f64], start: usize, end: usize, sample_rate: f64) -> Vec<f64> {
+        let (ext_start, ext_end) = self.bounds(start, end, signal.len(), sample_rate);
+        signal[ext_start..ext_end]
+            .iter()
+            .zip(self.gains(start, end, signal.len(), sample_rate))
+            .map(|(x, g)| x * g)
+            .collect()
+    }
+
+    /// Gain at position `i` of a fade-in of length `len`.
+    fn fade(&self, i: usize, len: usize) -> f64 {
+        let t = i as f64 / len as f64;
+        match self.taper {
+            ContextTaper::None => 1.0,
+            ContextTaper::Linear => t,
+            ContextTaper::Hann => 0.5 - 0.5 * (PI * t).cos(),
+        }
+    }
+}
+
+fn ms_to_samples(ms: f64, sample_rate: f64) -> usize {
+    (ms * 0.001 * sample_rate).round().max(0.0) as usize
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bounds_clamp_to_signal() {
+        let ctx = SliceContext::new(10.0, 10.0, ContextTaper::None);
+        assert_eq!(ctx.bounds(5, 50, 100, 1000.0), (0, 60));
+        assert_eq!(ctx.bounds(50, 95, 100, 1000.0), (40, 100));
+        assert_eq!(SliceContext::default().bounds(5, 50, 100, 1000.0), (5, 50));
+    }
+
+    #[test]
+    fn gains_taper_context_only() {
+        let ctx = SliceContext::new(4.0, 2.0, ContextTaper::Hann);
+        let gains = ctx.gains(10, 14, 100, 1000.0);
+        assert_eq!(gains.len(), 10);
+        assert_eq!(gains[0], 0.0);
+        assert!(gains[1] > 0.0 && gains[1] < gains[2]);
+        assert_eq!(&gains[4..8], &[1.0; 4]);
+        assert!(gains[8] > 0.0 && gains[8] < 1.0);
+        assert_eq!(gains[9], 0.0);
+
+        let flat = SliceContext::new(4.0, 2.0, ContextTaper::None);
+        assert!(flat.gains(10, 14, 100, 1000.0).iter().all(|&g| g == 1.0));
+    }
+}
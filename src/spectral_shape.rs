@@ -0,0 +1,272 @@
+use flucoma_sys::{spectral_shape_create, spectral_shape_destroy, spectral_shape_process_frame};
+
+use crate::error::{check_len, FlucomaError};
+use crate::frame_analyzer::FrameAnalyzer;
+use crate::quality::FrameQuality;
+
+// -------------------------------------------------------------------------------------------------
+
+/// The seven spectral shape descriptors of one frame.
+///
+/// Frequencies are in Hz, or in MIDI note numbers when computed with
+/// `log_frequency`.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SpectralShapeResult {
+    /// Spectral centre of mass.
+    pub centroid: f64,
+    /// Standard deviation around the centroid.
+    pub spread: f64,
+    /// Asymmetry of the spectrum around the centroid.
+    pub skewness: f64,
+    /// Peakedness of the spectrum.
+    pub kurtosis: f64,
+    /// Frequency below which `rolloff_percent` of the energy lies.
+    pub rolloff: f64,
+    /// Ratio of geometric to arithmetic mean, in dB.
+    pub flatness: f64,
+    /// Ratio of maximum to arithmetic mean, in dB.
+    pub crest: f64,
+    /// Reliability flags of the frame, e.g. [`FrameQuality::SILENT`] for an
+    /// all-zero spectrum, whose shape is undefined.
+    pub quality: FrameQuality,
+}
+
+impl SpectralShapeResult {
+    /// Descriptor names, in the order of [`SpectralShapeResult::to_array`].
+    pub const NAMES: [&'static str; 7] = [
+        "centroid", "spread", "skewness", "kurtosis", "rolloff", "flatness", "crest",
+    ];
+
+    /// The descriptors as an array, in the order of
+    /// [`SpectralShapeResult::NAMES`].
+    pub fn to_array(&self) -> [f64; 7] {
+        [
+            self.centroid,
+            self.spread,
+            self.skewness,
+            self.kurtosis,
+            self.rolloff,
+            self.flatness,
+            self.crest,
+        ]
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Per-frame settings of [`SpectralShape`], defaulting to the FluCoMa
+/// object's defaults.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SpectralShapeParams {
+    /// Sample rate of the analysed audio in Hz.
+    pub sample_rate: f64,
+    /// Lowest frequency considered, in Hz.
+    pub min_hz: f64,
+    /// Highest frequency considered, in Hz. `None` means Nyquist.
+    pub max_hz: Option<f64>,
+    /// Percentage of energy below the rolloff frequency.
+    pub rolloff_percent: f64,
+    /// Report frequencies as MIDI note numbers.
+    pub log_frequency: bool,
+    /// Weight by power instead of magnitude.
+    pub use_power: bool,
+}
+
+impl Default for SpectralShapeParams {
+    fn default() -> Self {
+        Self {
+            sample_rate: 44100.0,
+            min_hz: 0.0,
+            max_hz: None,
+            rolloff_percent: 95.0,
+            log_frequency: false,
+            use_power: false,
+        }
+    }
+}
+
+/// Spectral shape descriptors of magnitude spectra.
+///
+/// Call [`SpectralShape::process_frame`] with magnitude spectra (not raw
+/// complex).
+///
+/// See <https://learn.flucoma.org/reference/spectralshape>
+pub struct SpectralShape {
+    inner: *mut u8,
+    n_bins: usize,
+}
+
+unsafe impl Send for SpectralShape {}
+
+impl SpectralShape {
+    /// Create a spectral shape analyser.
+    ///
+    /// # Arguments
+    /// * `n_bins` - Number of FFT magnitude bins (`fft_size / 2 + 1`).
+    ///
+    /// # Errors
+    /// Returns an error if parameters are invalid.
+    pub fn new(n_bins: usize) -> Result<Self, FlucomaError> {
+        if n_bins < 2 {
+            return Err(FlucomaError::InvalidParameter("n_bins must be >= 2"));
+        }
+        let inner = spectral_shape_create();
+        if inner.is_null() {
+            return Err(FlucomaError::AllocationFailed);
+        }
+        Ok(Self { inner, n_bins })
+    }
+
+    /// Compute the shape descriptors of a magnitude spectrum frame.
+    ///
+    /// # Arguments
+    /// * `magnitudes`      - Magnitude spectrum; must have exactly `n_bins` values.
+    /// * `sample_rate`     - Audio sample rate in Hz.
+    /// * `min_hz`          - Bins below this frequency are ignored.
+    /// * `max_hz`          - Bins above this frequency are ignored.
+    /// * `rolloff_percent` - Energy percentage for the rolloff descriptor (e.g. 95).
+    /// * `log_frequency`   - Report frequencies as MIDI note numbers.
+    /// * `use_power`       - Square the magnitudes (power spectrum input).
+    ///
+    /// # Panics
+    /// Panics if `magnitudes.len() != n_bins`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn process_frame(
+        &mut self,
+        magnitudes: &[f64],
+        sample_rate: f64,
+        min_hz: f64,
+        max_hz: f64,
+        rolloff_percent: f64,
+        log_frequency: bool,
+        use_power: bool,
+    ) -> SpectralShapeResult {
+        assert_eq!(
+            magnitudes.len(),
+            self.n_bins,
+            "magnitudes length ({}) must equal n_bins ({})",
+            magnitudes.len(),
+            self.n_bins
+        );
+        let mut out = [0.0f64; 7];
+        spectral_shape_process_frame(
+            self.inner,
+            magnitudes.as_ptr(),
+            magnitudes.len() as isize,
+            out.as_mut_ptr(),
+            sample_rate,
+            min_hz,
+            max_hz,
+            rolloff_percent,
+            log_frequency,
+            use_power,
+        );
+        let [centroid, spread, skewness, kurtosis, rolloff, flatness, crest] = out;
+        SpectralShapeResult {
+            centroid,
+            spread,
+            skewness,
+            kurtosis,
+            rolloff,
+            flatness,
+            crest,
+            quality: FrameQuality::inspect_spectrum(magnitudes)
+                | FrameQuality::inspect_output(&out),
+        }
+    }
+
+    /// Like [`SpectralShape::process_frame`], but returns
+    /// [`FlucomaError::InvalidShape`] instead of panicking if
+    /// `magnitudes.len() != n_bins`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn try_process_frame(
+        &mut self,
+        magnitudes: &[f64],
+        sample_rate: f64,
+        min_hz: f64,
+        max_hz: f64,
+        rolloff_percent: f64,
+        log_frequency: bool,
+        use_power: bool,
+    ) -> Result<SpectralShapeResult, FlucomaError> {
+        check_len(self.n_bins, magnitudes.len())?;
+        Ok(self.process_frame(
+            magnitudes,
+            sample_rate,
+            min_hz,
+            max_hz,
+            rolloff_percent,
+            log_frequency,
+            use_power,
+        ))
+    }
+
+    /// Number of FFT magnitude bins expected as input (`fft_size / 2 + 1`).
+    pub fn n_bins(&self) -> usize {
+        self.n_bins
+    }
+}
+
+impl Drop for SpectralShape {
+    fn drop(&mut self) {
+        spectral_shape_destroy(self.inner);
+    }
+}
+
+impl Clone for SpectralShape {
+    fn clone(&self) -> Self {
+        Self::new(self.n_bins).expect("settings were validated on construction")
+    }
+}
+
+impl FrameAnalyzer for SpectralShape {
+    type Input = [f64];
+    type Params = SpectralShapeParams;
+    type Output = SpectralShapeResult;
+
+    fn process(&mut self, magnitudes: &[f64], params: &SpectralShapeParams) -> SpectralShapeResult {
+        let nyquist = params.sample_rate / 2.0;
+        self.process_frame(
+            magnitudes,
+            params.sample_rate,
+            params.min_hz,
+            params.max_hz.unwrap_or(nyquist).min(nyquist),
+            params.rolloff_percent,
+            params.log_frequency,
+            params.use_power,
+        )
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spectral_shape_single_bin_centroid() {
+        let n_bins = 513;
+        let mut shape = SpectralShape::new(n_bins).unwrap();
+        let mut mags = vec![0.0f64; n_bins];
+        mags[10] = 1.0;
+        let result = shape.process_frame(&mags, 44100.0, 0.0, 22050.0, 95.0, false, false);
+        let bin_hz = 44100.0 / 1024.0;
+        assert!((result.centroid - 10.0 * bin_hz).abs() < 1e-6);
+        assert_eq!(result.to_array()[0], result.centroid);
+        assert!(result.quality.is_reliable());
+
+        let silence = shape.process_frame(
+            &vec![0.0; n_bins],
+            44100.0,
+            0.0,
+            22050.0,
+            95.0,
+            false,
+            false,
+        );
+        assert!(silence.quality.contains(FrameQuality::SILENT));
+    }
+}
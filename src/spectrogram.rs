@@ -0,0 +1,300 @@
+use num_complex::Complex64 as Complex;
+
+use crate::buf_frames::validate_fft;
+use crate::error::FlucomaError;
+use crate::matrix::Matrix;
+use crate::stft::{ComplexSpectrum, WindowType};
+
+// -------------------------------------------------------------------------------------------------
+
+/// A complete STFT analysis: frames × bins of complex data plus the settings
+/// it was computed with.
+///
+/// Produced by [`Stft::analyze`](crate::fourier::Stft::analyze) and turned
+/// back into audio by
+/// [`Istft::synthesize`](crate::fourier::Istft::synthesize). Frame `i` is
+/// centred on sample `i * hop_size` of the analysed signal.
+///
+/// ```rust,no_run
+/// use flucoma_rs::fourier::{Istft, Stft, WindowType};
+///
+/// let audio = vec![0.0f64; 44100];
+/// let mut stft = Stft::new(1024, 1024, 256, WindowType::Hann).unwrap();
+/// let mut spectrogram = stft.analyze(&audio);
+/// for frame in 0..spectrogram.num_frames() {
+///     spectrogram.frame_mut(frame)[0] = Default::default(); // drop DC
+/// }
+/// let mut istft = Istft::new(1024, 1024, 256, WindowType::Hann).unwrap();
+/// let resynthesized = istft.synthesize(&spectrogram);
+/// assert_eq!(resynthesized.len(), audio.len());
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "SpectrogramRepr"))]
+pub struct Spectrogram {
+    data: Vec<Complex>,
+    num_frames: usize,
+    num_bins: usize,
+    window_size: usize,
+    fft_size: usize,
+    hop_size: usize,
+    window_type: WindowType,
+    signal_len: usize,
+}
+
+impl Spectrogram {
+    /// Allocate a zeroed spectrogram of `num_frames` frames for the given
+    /// STFT settings. Its signal length is `(num_frames - 1) * hop_size`.
+    ///
+    /// # Errors
+    /// Returns an error if `num_frames` is zero or the settings are
+    /// invalid.
+    pub fn zeros(
+        num_frames: usize,
+        window_size: usize,
+        fft_size: usize,
+        hop_size: usize,
+        window_type: WindowType,
+    ) -> Result<Self, FlucomaError> {
+        if num_frames == 0 {
+            return Err(FlucomaError::InvalidParameter("num_frames must be > 0"));
+        }
+        validate_fft(window_size, hop_size, fft_size)?;
+        let num_bins = fft_size / 2 + 1;
+        Ok(Self {
+            data: vec![Complex::default(); num_frames * num_bins],
+            num_frames,
+            num_bins,
+            window_size,
+            fft_size,
+            hop_size,
+            window_type,
+            signal_len: (num_frames - 1) * hop_size,
+        })
+    }
+
+    /// Set the length of the signal the frames were analysed from, e.g. to
+    /// resynthesise a zeroed spectrogram to a known length.
+    pub(crate) fn with_signal_len(mut self, signal_len: usize) -> Self {
+        self.signal_len = signal_len;
+        self
+    }
+
+    pub(crate) fn from_frames(
+        frames: Vec<ComplexSpectrum>,
+        window_size: usize,
+        fft_size: usize,
+        hop_size: usize,
+        window_type: WindowType,
+        signal_len: usize,
+    ) -> Self {
+        let num_frames = frames.len();
+        let num_bins = fft_size / 2 + 1;
+        let data = frames.into_iter().flat_map(|frame| frame.bins).collect();
+        Self {
+            data,
+            num_frames,
+            num_bins,
+            window_size,
+            fft_size,
+            hop_size,
+            window_type,
+            signal_len,
+        }
+    }
+
+    /// Number of frames (rows).
+    pub fn num_frames(&self) -> usize {
+        self.num_frames
+    }
+
+    /// Number of complex bins per frame (`fft_size / 2 + 1`).
+    pub fn num_bins(&self) -> usize {
+        self.num_bins
+    }
+
+    /// Analysis window size in samples.
+    pub fn window_size(&self) -> usize {
+        self.window_size
+    }
+
+    /// FFT size in samples.
+    pub fn fft_size(&self) -> usize {
+        self.fft_size
+    }
+
+    /// Hop between successive frames in samples.
+    pub fn hop_size(&self) -> usize {
+        self.hop_size
+    }
+
+    /// Window function.
+    pub fn window_type(&self) -> WindowType {
+        self.window_type
+    }
+
+    /// Length of the analysed signal in samples.
+    pub fn signal_len(&self) -> usize {
+        self.signal_len
+    }
+
+    /// Flat frame-major complex data.
+    pub fn data(&self) -> &[Complex] {
+        &self.data
+    }
+
+    /// Mutable flat frame-major complex data.
+    pub fn data_mut(&mut self) -> &mut [Complex] {
+        &mut self.data
+    }
+
+    /// Complex bins of frame `index`, DC to Nyquist.
+    ///
+    /// # Panics
+    /// Panics if `index >= num_frames()`.
+    pub fn frame(&self, index: usize) -> &[Complex] {
+        &self.data[index * self.num_bins..(index + 1) * self.num_bins]
+    }
+
+    /// Mutable complex bins of frame `index`.
+    ///
+    /// # Panics
+    /// Panics if `index >= num_frames()`.
+    pub fn frame_mut(&mut self, index: usize) -> &mut [Complex] {
+        &mut self.data[index * self.num_bins..(index + 1) * self.num_bins]
+    }
+
+    /// Frames × bins matrix of magnitudes.
+    pub fn magnitudes(&self) -> Matrix {
+        self.map(|c| c.norm())
+    }
+
+    /// Frames × bins matrix of phases in radians.
+    pub fn phases(&self) -> Matrix {
+        self.map(|c| c.arg())
+    }
+
+    /// Frames × bins matrix of magnitudes in dB (`20 * log10`), clamped
+    /// below at `floor_db`.
+    pub fn magnitudes_db(&self, floor_db: f64) -> Matrix {
+        self.map(|c| (20.0 * c.norm().log10()).max(floor_db))
+    }
+
+    fn map<F: Fn(&Complex) -> f64>(&self, f: F) -> Matrix {
+        let values = self.data.iter().map(f).collect();
+        Matrix::from_vec(values, self.num_frames, self.num_bins)
+            .expect("spectrogram has at least one frame and bin")
+    }
+}
+
+/// Unchecked serialized form of [`Spectrogram`], validated on
+/// deserialization.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct SpectrogramRepr {
+    data: Vec<Complex>,
+    num_frames: usize,
+    num_bins: usize,
+    window_size: usize,
+    fft_size: usize,
+    hop_size: usize,
+    window_type: WindowType,
+    signal_len: usize,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<SpectrogramRepr> for Spectrogram {
+    type Error = FlucomaError;
+
+    fn try_from(repr: SpectrogramRepr) -> Result<Self, FlucomaError> {
+        use crate::error::check_len;
+
+        if repr.num_frames == 0 {
+            return Err(FlucomaError::InvalidParameter("num_frames must be > 0"));
+        }
+        validate_fft(repr.window_size, repr.hop_size, repr.fft_size)?;
+        check_len(repr.fft_size / 2 + 1, repr.num_bins)?;
+        check_len(repr.num_frames * repr.num_bins, repr.data.len())?;
+        Ok(Self {
+            data: repr.data,
+            num_frames: repr.num_frames,
+            num_bins: repr.num_bins,
+            window_size: repr.window_size,
+            fft_size: repr.fft_size,
+            hop_size: repr.hop_size,
+            window_type: repr.window_type,
+            signal_len: repr.signal_len,
+        })
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Samples of an analysis/synthesis window, matching flucoma-core's
+/// periodic window functions.
+pub(crate) fn window_values(window_type: WindowType, size: usize) -> Vec<f64> {
+    use std::f64::consts::PI;
+    let n = size as f64;
+    (0..size)
+        .map(|i| {
+            let x = 2.0 * PI * i as f64 / n;
+            match window_type {
+                WindowType::Hann => 0.5 - 0.5 * x.cos(),
+                WindowType::Hamming => 0.54 - 0.46 * x.cos(),
+                WindowType::Blackman => 0.42 - 0.5 * x.cos() + 0.08 * (2.0 * x).cos(),
+                WindowType::Rectangular => 1.0,
+            }
+        })
+        .collect()
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spectrogram_accessors() {
+        let mut spectrogram = Spectrogram::zeros(3, 8, 8, 4, WindowType::Hann).unwrap();
+        assert_eq!(spectrogram.num_bins(), 5);
+        assert_eq!(spectrogram.signal_len(), 8);
+        spectrogram.frame_mut(1)[2] = Complex::new(0.0, 10.0);
+        let mags = spectrogram.magnitudes();
+        assert_eq!((mags.rows(), mags.cols()), (3, 5));
+        assert_eq!(mags.data()[5 + 2], 10.0);
+        let phases = spectrogram.phases();
+        assert!((phases.data()[5 + 2] - std::f64::consts::FRAC_PI_2).abs() < 1e-12);
+        let db = spectrogram.magnitudes_db(-120.0);
+        assert!((db.data()[5 + 2] - 20.0).abs() < 1e-12);
+        assert_eq!(db.data()[0], -120.0);
+        assert!(Spectrogram::zeros(0, 8, 8, 4, WindowType::Hann).is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn deserialization_checks_shape() {
+        let repr = |num_frames, num_bins, len| SpectrogramRepr {
+            data: vec![Complex::default(); len],
+            num_frames,
+            num_bins,
+            window_size: 8,
+            fft_size: 8,
+            hop_size: 4,
+            window_type: WindowType::Hann,
+            signal_len: 8,
+        };
+        assert!(Spectrogram::try_from(repr(3, 5, 15)).is_ok());
+        assert!(Spectrogram::try_from(repr(0, 5, 0)).is_err());
+        assert!(Spectrogram::try_from(repr(3, 4, 12)).is_err());
+        assert!(Spectrogram::try_from(repr(3, 5, 14)).is_err());
+    }
+
+    #[test]
+    fn hann_window_overlap_adds_to_constant() {
+        let window = window_values(WindowType::Hann, 8);
+        for i in 0..4 {
+            assert!((window[i] + window[i + 4] - 1.0).abs() < 1e-12);
+        }
+    }
+}
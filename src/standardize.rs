@@ -1,9 +1,13 @@
 use flucoma_sys::{
     standardization_create, standardization_destroy, standardization_fit,
-    standardization_initialized, standardization_process, FlucomaIndex,
+    standardization_initialized, standardization_means, standardization_process,
+    standardization_set_model, standardization_stds, FlucomaIndex,
 };
 
+use crate::column_mask::{replace_columns, select_columns, update_mask, ColumnMasked};
+use crate::error::FlucomaError;
 use crate::matrix::Matrix;
+use crate::transform::Transform;
 
 /// Z-score standardizer for dataset-style matrices.
 ///
@@ -17,6 +21,61 @@ use crate::matrix::Matrix;
 pub struct Standardize {
     inner: *mut u8,
     cols: Option<usize>,
+    /// Feature columns to scale, or `None` for all of them.
+    columns: Option<Vec<usize>>,
+    /// Running moments of the chunks seen by [`Standardize::partial_fit`].
+    accumulator: Option<MomentAccumulator>,
+}
+
+/// Running per-feature count, mean and sum of squared deviations, merged
+/// chunk by chunk with the pairwise update of Chan et al.
+struct MomentAccumulator {
+    count: usize,
+    mean: Vec<f64>,
+    m2: Vec<f64>,
+}
+
+impl MomentAccumulator {
+    fn new(cols: usize) -> Self {
+        Self {
+            count: 0,
+            mean: vec![0.0; cols],
+            m2: vec![0.0; cols],
+        }
+    }
+
+    fn update(&mut self, chunk: &Matrix) {
+        let cols = self.mean.len();
+        let n_b = chunk.rows() as f64;
+        let mut mean_b = vec![0.0; cols];
+        for row in chunk.data().chunks_exact(cols) {
+            mean_b.iter_mut().zip(row).for_each(|(m, x)| *m += x);
+        }
+        mean_b.iter_mut().for_each(|m| *m /= n_b);
+        let mut m2_b = vec![0.0; cols];
+        for row in chunk.data().chunks_exact(cols) {
+            for ((m2, mean), x) in m2_b.iter_mut().zip(&mean_b).zip(row) {
+                *m2 += (x - mean) * (x - mean);
+            }
+        }
+
+        let n_a = self.count as f64;
+        let n = n_a + n_b;
+        for i in 0..cols {
+            let delta = mean_b[i] - self.mean[i];
+            self.m2[i] += m2_b[i] + delta * delta * n_a * n_b / n;
+            self.mean[i] += delta * n_b / n;
+        }
+        self.count += chunk.rows();
+    }
+
+    /// Population standard deviation of each feature.
+    fn stds(&self) -> Vec<f64> {
+        self.m2
+            .iter()
+            .map(|m2| (m2 / self.count as f64).sqrt())
+            .collect()
+    }
 }
 
 // SAFETY: flucoma algorithms are thread-safe to move between threads.
@@ -24,16 +83,22 @@ unsafe impl Send for Standardize {}
 
 impl Standardize {
     /// Create a new standardizer.
-    pub fn new() -> Result<Self, &'static str> {
+    pub fn new() -> Result<Self, FlucomaError> {
         let inner = standardization_create();
         if inner.is_null() {
-            return Err("failed to create Standardization instance");
+            return Err(FlucomaError::AllocationFailed);
         }
-        Ok(Self { inner, cols: None })
+        Ok(Self {
+            inner,
+            cols: None,
+            accumulator: None,
+            columns: None,
+        })
     }
 
     /// Fit the standardizer from a row-major matrix.
-    pub fn fit(&mut self, data: &Matrix) -> Result<(), &'static str> {
+    pub fn fit(&mut self, data: &Matrix) -> Result<(), FlucomaError> {
+        let data = select_columns(data, self.columns.as_deref())?;
         standardization_fit(
             self.inner,
             data.data().as_ptr(),
@@ -41,46 +106,149 @@ impl Standardize {
             data.cols() as FlucomaIndex,
         );
         self.cols = Some(data.cols());
+        self.accumulator = None;
+        Ok(())
+    }
+
+    /// Update the fitted statistics with another chunk of rows, so the
+    /// standardizer can be fitted over streams or datasets too large to hold
+    /// in memory.
+    ///
+    /// Only a running mean and variance per feature are kept, and the model
+    /// is updated after every chunk, so it can be used for
+    /// [`Standardize::transform`] at any point. The result matches
+    /// [`Standardize::fit`] on all rows seen so far, up to rounding. A call
+    /// to [`Standardize::fit`] discards the accumulated state.
+    ///
+    /// # Errors
+    /// Returns an error if the chunk is empty or its column count differs
+    /// from earlier chunks.
+    pub fn partial_fit(&mut self, chunk: &Matrix) -> Result<(), FlucomaError> {
+        let chunk = select_columns(chunk, self.columns.as_deref())?;
+        if chunk.rows() == 0 || chunk.cols() == 0 {
+            return Err(FlucomaError::InvalidParameter("chunk must not be empty"));
+        }
+        let cols = chunk.cols();
+        let accumulator = self
+            .accumulator
+            .get_or_insert_with(|| MomentAccumulator::new(cols));
+        if accumulator.mean.len() != cols {
+            return Err(FlucomaError::InvalidParameter(
+                "cols must match previous chunks",
+            ));
+        }
+        accumulator.update(&chunk);
+        // Match `fit`, which scales constant columns by 1 rather than 0.
+        let stds: Vec<f64> = accumulator
+            .stds()
+            .into_iter()
+            .map(|std| if std == 0.0 { 1.0 } else { std })
+            .collect();
+        standardization_set_model(
+            self.inner,
+            accumulator.mean.as_ptr(),
+            stds.as_ptr(),
+            cols as FlucomaIndex,
+        );
+        self.cols = Some(cols);
         Ok(())
     }
 
     /// Transform a matrix using the fitted statistics.
-    pub fn transform(&self, data: &Matrix) -> Result<Matrix, &'static str> {
+    pub fn transform(&self, data: &Matrix) -> Result<Matrix, FlucomaError> {
         self.process_internal(data, false)
     }
 
     /// Undo a previous standardization step.
-    pub fn inverse_transform(&self, data: &Matrix) -> Result<Matrix, &'static str> {
+    pub fn inverse_transform(&self, data: &Matrix) -> Result<Matrix, FlucomaError> {
         self.process_internal(data, true)
     }
 
     /// Fit the standardizer and transform the same matrix in one step.
-    pub fn fit_transform(&mut self, data: &Matrix) -> Result<Matrix, &'static str> {
+    pub fn fit_transform(&mut self, data: &Matrix) -> Result<Matrix, FlucomaError> {
         self.fit(data)?;
         self.transform(data)
     }
 
     pub fn is_fitted(&self) -> bool {
-        standardization_initialized(self.inner)
+        self.cols.is_some() && standardization_initialized(self.inner)
+    }
+
+    /// Per-feature mean learned by [`Standardize::fit`], or `None` if the
+    /// standardizer is not fitted.
+    pub fn means(&self) -> Option<Vec<f64>> {
+        self.fitted_vector(standardization_means)
     }
 
-    fn process_internal(&self, data: &Matrix, inverse: bool) -> Result<Matrix, &'static str> {
+    /// Per-feature standard deviation learned by [`Standardize::fit`], or
+    /// `None` if the standardizer is not fitted.
+    ///
+    /// Values near zero flag constant columns, whose standardized output is
+    /// not meaningful.
+    pub fn stds(&self) -> Option<Vec<f64>> {
+        self.fitted_vector(standardization_stds)
+    }
+
+    fn fitted_vector(&self, get: fn(*mut u8, *mut f64, FlucomaIndex)) -> Option<Vec<f64>> {
+        let cols = self.cols.filter(|_| self.is_fitted())?;
+        let mut values = vec![0.0; cols];
+        get(self.inner, values.as_mut_ptr(), cols as FlucomaIndex);
+        Some(values)
+    }
+
+    fn process_internal(&self, data: &Matrix, inverse: bool) -> Result<Matrix, FlucomaError> {
         if !self.is_fitted() {
-            return Err("standardizer is not fitted");
+            return Err(FlucomaError::NotFitted);
         }
-        if self.cols != Some(data.cols()) {
-            return Err("cols must match fitted feature dimension");
+        let columns = self.columns.as_deref();
+        let selected = select_columns(data, columns)?;
+        if self.cols != Some(selected.cols()) {
+            return Err(FlucomaError::InvalidParameter(
+                "cols must match fitted feature dimension",
+            ));
         }
-        let mut out = Matrix::new(data.rows(), data.cols());
+        let mut out = Matrix::new(selected.rows(), selected.cols());
         standardization_process(
             self.inner,
-            data.data().as_ptr(),
-            data.rows() as FlucomaIndex,
-            data.cols() as FlucomaIndex,
+            selected.data().as_ptr(),
+            selected.rows() as FlucomaIndex,
+            selected.cols() as FlucomaIndex,
             out.data_mut().as_mut_ptr(),
             inverse,
         );
-        Ok(out)
+        Ok(replace_columns(data, columns, out))
+    }
+}
+
+impl ColumnMasked for Standardize {
+    fn set_columns(&mut self, columns: Option<Vec<usize>>) -> Result<(), FlucomaError> {
+        if update_mask(&mut self.columns, columns)? {
+            self.cols = None;
+            self.accumulator = None;
+        }
+        Ok(())
+    }
+
+    fn columns(&self) -> Option<&[usize]> {
+        self.columns.as_deref()
+    }
+}
+
+impl Transform for Standardize {
+    fn fit(&mut self, data: &Matrix) -> Result<(), FlucomaError> {
+        Standardize::fit(self, data)
+    }
+
+    fn transform(&self, data: &Matrix) -> Result<Matrix, FlucomaError> {
+        Standardize::transform(self, data)
+    }
+
+    fn inverse_transform(&self, data: &Matrix) -> Result<Matrix, FlucomaError> {
+        Standardize::inverse_transform(self, data)
+    }
+
+    fn is_fitted(&self) -> bool {
+        Standardize::is_fitted(self)
     }
 }
 
@@ -105,11 +273,71 @@ mod tests {
         }
     }
 
+    #[test]
+    fn fitted_parameters_are_per_feature() {
+        let data = Matrix::from_vec(vec![1.0, 10.0, 3.0, 20.0, 5.0, 30.0], 3, 2).unwrap();
+        let mut s = Standardize::new().unwrap();
+        assert_eq!(s.means(), None);
+        s.fit(&data).unwrap();
+        assert_eq!(s.means(), Some(vec![3.0, 20.0]));
+        let stds = s.stds().unwrap();
+        assert!(stds[0] > 0.0 && (stds[1] - 10.0 * stds[0]).abs() < 1e-9);
+    }
+
+    #[test]
+    fn moment_accumulator_matches_single_pass() {
+        let data =
+            Matrix::from_vec(vec![1.0, 10.0, 3.0, 20.0, 5.0, 30.0, 7.0, -4.0], 4, 2).unwrap();
+        let mut whole = MomentAccumulator::new(2);
+        whole.update(&data);
+        let mut chunked = MomentAccumulator::new(2);
+        for rows in data.data().chunks(4) {
+            chunked.update(&Matrix::from_vec(rows.to_vec(), rows.len() / 2, 2).unwrap());
+        }
+        assert_eq!(chunked.count, 4);
+        assert_eq!(whole.mean, vec![4.0, 14.0]);
+        for (a, b) in whole.stds().iter().zip(chunked.stds()) {
+            assert!((a - b).abs() < 1e-12);
+        }
+        assert!((whole.stds()[0] - 5f64.sqrt()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn chunked_partial_fit_matches_fit() {
+        // The middle column is constant, so its scale falls back to 1.
+        let data = Matrix::from_vec(
+            vec![
+                1.0, 4.0, 10.0, 3.0, 4.0, 20.0, 5.0, 4.0, 30.0, 7.0, 4.0, -4.0,
+            ],
+            4,
+            3,
+        )
+        .unwrap();
+        let mut whole = Standardize::new().unwrap();
+        whole.fit(&data).unwrap();
+        let mut chunked = Standardize::new().unwrap();
+        for rows in data.data().chunks(6) {
+            chunked
+                .partial_fit(&Matrix::from_vec(rows.to_vec(), 2, 3).unwrap())
+                .unwrap();
+        }
+        let expected = whole.transform(&data).unwrap();
+        let got = chunked.transform(&data).unwrap();
+        for (a, b) in expected.data().iter().zip(got.data()) {
+            assert!(
+                b.is_finite() && (a - b).abs() < 1e-9,
+                "expected {a}, got {b}"
+            );
+        }
+        assert_eq!(got.data()[1], 0.0);
+        assert_eq!(chunked.inverse_transform(&got).unwrap().data()[1], 4.0);
+    }
+
     #[test]
     fn transform_before_fit_fails() {
         let s = Standardize::new().unwrap();
         let data = Matrix::from_vec(vec![1.0, 2.0], 1, 2).unwrap();
         let err = s.transform(&data).unwrap_err();
-        assert_eq!(err, "standardizer is not fitted");
+        assert_eq!(err, FlucomaError::NotFitted);
     }
 }
@@ -1,13 +1,29 @@
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops::{Index, IndexMut};
+use std::str::FromStr;
+
 use flucoma_sys::{
-    istft_create, istft_destroy, istft_process_frame, stft_create, stft_destroy, stft_process_frame,
+    istft_create, istft_destroy, istft_process_frame, istft_reset, stft_create, stft_destroy,
+    stft_process_frame, stft_reset,
 };
 use num_complex::Complex64 as Complex;
 
+use crate::buf_frames::{fill_frame, num_frames};
+use crate::error::{check_len, FlucomaError};
+use crate::fft_settings::FftSettings;
+use crate::frame_analyzer::FrameAnalyzer;
+use crate::overlap_add::OverlapAdd;
+use crate::param_names::{display_name, parse_name};
+use crate::sample_f32::widen;
+use crate::spectrogram::{window_values, Spectrogram};
+
 // -------------------------------------------------------------------------------------------------
 
 /// Window function type for STFT/ISTFT.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 #[repr(isize)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum WindowType {
     #[default]
     Hann = 0,
@@ -16,6 +32,28 @@ pub enum WindowType {
     Rectangular = 3,
 }
 
+const WINDOW_TYPE_NAMES: &[(&str, WindowType)] = &[
+    ("hann", WindowType::Hann),
+    ("hamming", WindowType::Hamming),
+    ("blackman", WindowType::Blackman),
+    ("rectangular", WindowType::Rectangular),
+];
+
+impl FromStr for WindowType {
+    type Err = FlucomaError;
+
+    /// Parse a name such as `"hann"` or `"blackman"`, ignoring case and separators.
+    fn from_str(name: &str) -> Result<Self, FlucomaError> {
+        parse_name(name, WINDOW_TYPE_NAMES, "unknown window type")
+    }
+}
+
+impl fmt::Display for WindowType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(display_name(self, WINDOW_TYPE_NAMES))
+    }
+}
+
 // -------------------------------------------------------------------------------------------------
 
 /// A complex spectral frame produced by [`Stft`] and consumed by [`Istft`].
@@ -23,6 +61,7 @@ pub enum WindowType {
 /// Each bin is a [`Complex`] value (`re` + `im`). Bins are ordered from DC
 /// to Nyquist: `bins.len() == fft_size / 2 + 1`.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ComplexSpectrum {
     /// Complex bins, DC to Nyquist.
     pub bins: Vec<Complex>,
@@ -47,10 +86,150 @@ impl ComplexSpectrum {
         self.bins.iter().map(|c| c.norm()).collect()
     }
 
+    /// Write all magnitudes into `output` without allocating.
+    ///
+    /// # Panics
+    /// Panics if `output.len() != num_bins()`.
+    pub fn magnitudes_into(&self, output: &mut [f64]) {
+        assert_eq!(
+            output.len(),
+            self.bins.len(),
+            "output length ({}) must equal num_bins ({})",
+            output.len(),
+            self.bins.len()
+        );
+        for (out, c) in output.iter_mut().zip(&self.bins) {
+            *out = c.norm();
+        }
+    }
+
     /// All phases in radians as a `Vec<f64>`.
     pub fn phases(&self) -> Vec<f64> {
         self.bins.iter().map(|c| c.arg()).collect()
     }
+
+    /// Power (squared magnitude) of bin `index`.
+    ///
+    /// # Panics
+    /// Panics if `index >= num_bins()`.
+    #[inline]
+    pub fn power(&self, index: usize) -> f64 {
+        self.bins[index].norm_sqr()
+    }
+
+    /// All powers (squared magnitudes) as a `Vec<f64>`.
+    pub fn powers(&self) -> Vec<f64> {
+        self.bins.iter().map(|c| c.norm_sqr()).collect()
+    }
+
+    /// Write all powers into `output` without allocating.
+    ///
+    /// # Panics
+    /// Panics if `output.len() != num_bins()`.
+    pub fn powers_into(&self, output: &mut [f64]) {
+        assert_eq!(
+            output.len(),
+            self.bins.len(),
+            "output length ({}) must equal num_bins ({})",
+            output.len(),
+            self.bins.len()
+        );
+        for (out, c) in output.iter_mut().zip(&self.bins) {
+            *out = c.norm_sqr();
+        }
+    }
+
+    /// All magnitudes in dB (`20 * log10`), clamped below at `floor_db`.
+    pub fn magnitudes_db(&self, floor_db: f64) -> Vec<f64> {
+        self.bins
+            .iter()
+            .map(|c| (20.0 * c.norm().log10()).max(floor_db))
+            .collect()
+    }
+
+    /// Build a spectrum from per-bin magnitudes and phases in radians.
+    ///
+    /// # Panics
+    /// Panics if `magnitudes.len() != phases.len()`.
+    pub fn from_polar(magnitudes: &[f64], phases: &[f64]) -> Self {
+        assert_eq!(
+            magnitudes.len(),
+            phases.len(),
+            "magnitudes length ({}) must equal phases length ({})",
+            magnitudes.len(),
+            phases.len()
+        );
+        Self {
+            bins: magnitudes
+                .iter()
+                .zip(phases)
+                .map(|(&mag, &phase)| Complex::from_polar(mag, phase))
+                .collect(),
+        }
+    }
+
+    /// Split into `(magnitudes, phases)`, the inverse of
+    /// [`ComplexSpectrum::from_polar`].
+    pub fn to_polar(&self) -> (Vec<f64>, Vec<f64>) {
+        self.bins.iter().map(|c| c.to_polar()).unzip()
+    }
+
+    /// Multiply every bin by `gain`.
+    pub fn scale(&mut self, gain: f64) {
+        for bin in &mut self.bins {
+            *bin *= gain;
+        }
+    }
+
+    /// Multiply each bin by the real gain of the same index, e.g. a spectral
+    /// mask in `0.0..=1.0`.
+    ///
+    /// # Panics
+    /// Panics if `gains.len() != num_bins()`.
+    pub fn apply_gains(&mut self, gains: &[f64]) {
+        assert_eq!(
+            gains.len(),
+            self.bins.len(),
+            "gains length ({}) must equal num_bins ({})",
+            gains.len(),
+            self.bins.len()
+        );
+        for (bin, &gain) in self.bins.iter_mut().zip(gains) {
+            *bin *= gain;
+        }
+    }
+
+    /// Complex-multiply each bin by the bin of the same index in `other`,
+    /// e.g. to apply a filter's frequency response.
+    ///
+    /// # Panics
+    /// Panics if the spectra have different numbers of bins.
+    pub fn multiply(&mut self, other: &ComplexSpectrum) {
+        assert_eq!(
+            other.num_bins(),
+            self.bins.len(),
+            "other num_bins ({}) must equal num_bins ({})",
+            other.num_bins(),
+            self.bins.len()
+        );
+        for (bin, &factor) in self.bins.iter_mut().zip(&other.bins) {
+            *bin *= factor;
+        }
+    }
+}
+
+impl Index<usize> for ComplexSpectrum {
+    type Output = Complex;
+
+    fn index(&self, bin: usize) -> &Complex {
+        &self.bins[bin]
+    }
+}
+
+impl IndexMut<usize> for ComplexSpectrum {
+    fn index_mut(&mut self, bin: usize) -> &mut Complex {
+        &mut self.bins[bin]
+    }
 }
 
 // -------------------------------------------------------------------------------------------------
@@ -68,7 +247,9 @@ pub struct Stft {
     window_size: usize,
     fft_size: usize,
     hop_size: usize,
+    window_type: WindowType,
     num_bins: usize,
+    scratch: Vec<f64>,
 }
 
 unsafe impl Send for Stft {}
@@ -83,22 +264,14 @@ impl Stft {
     /// * `window_type` - Window function.
     ///
     /// # Errors
-    /// Returns an error string if parameters are invalid.
+    /// Returns an error if parameters are invalid.
     pub fn new(
         window_size: usize,
         fft_size: usize,
         hop_size: usize,
         window_type: WindowType,
-    ) -> Result<Self, &'static str> {
-        if window_size == 0 {
-            return Err("window_size must be > 0");
-        }
-        if fft_size < window_size {
-            return Err("fft_size must be >= window_size");
-        }
-        if hop_size == 0 {
-            return Err("hop_size must be > 0");
-        }
+    ) -> Result<Self, FlucomaError> {
+        validate_params(window_size, fft_size, hop_size)?;
         let inner = stft_create(
             window_size as isize,
             fft_size as isize,
@@ -106,17 +279,51 @@ impl Stft {
             window_type as isize,
         );
         if inner.is_null() {
-            return Err("failed to create STFT instance");
+            return Err(FlucomaError::AllocationFailed);
         }
         Ok(Self {
             inner,
             window_size,
             fft_size,
             hop_size,
+            window_type,
             num_bins: fft_size / 2 + 1,
+            scratch: Vec::with_capacity(window_size),
         })
     }
 
+    /// Create an STFT analyser from shared [`FftSettings`].
+    ///
+    /// # Errors
+    /// Returns an error if the settings are invalid.
+    pub fn from_settings(settings: &FftSettings) -> Result<Self, FlucomaError> {
+        Self::new(
+            settings.window_size,
+            settings.fft_size,
+            settings.hop_size,
+            settings.window_type,
+        )
+    }
+
+    /// Start building an STFT analyser from named settings, see
+    /// [`StftBuilder`].
+    ///
+    /// ```rust,no_run
+    /// use flucoma_rs::fourier::{Stft, WindowType};
+    ///
+    /// let stft = Stft::builder()
+    ///     .window(1024)
+    ///     .fft(4096)
+    ///     .hop(512)
+    ///     .window_type(WindowType::Hann)
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(stft.num_bins(), 2049);
+    /// ```
+    pub fn builder() -> StftBuilder<Stft> {
+        StftBuilder::default()
+    }
+
     /// Process one audio frame and return its complex spectrum.
     ///
     /// # Arguments
@@ -125,6 +332,26 @@ impl Stft {
     /// # Panics
     /// Panics if `frame.len() != window_size`.
     pub fn process_frame(&mut self, frame: &[f64]) -> ComplexSpectrum {
+        let mut spec = ComplexSpectrum::zeros(self.num_bins);
+        self.process_into(frame, &mut spec);
+        spec
+    }
+
+    /// Like [`Stft::process_frame`], but returns
+    /// [`FlucomaError::InvalidShape`] instead of panicking on a wrong-sized
+    /// frame.
+    pub fn try_process_frame(&mut self, frame: &[f64]) -> Result<ComplexSpectrum, FlucomaError> {
+        check_len(self.window_size, frame.len())?;
+        Ok(self.process_frame(frame))
+    }
+
+    /// Like [`Stft::process_frame`], but writes into an existing spectrum
+    /// instead of allocating, for use on an audio thread.
+    ///
+    /// # Panics
+    /// Panics if `frame.len() != window_size` or `spectrum.num_bins()` does
+    /// not match.
+    pub fn process_into(&mut self, frame: &[f64], spectrum: &mut ComplexSpectrum) {
         assert_eq!(
             frame.len(),
             self.window_size,
@@ -132,17 +359,67 @@ impl Stft {
             frame.len(),
             self.window_size
         );
-        let mut spec = ComplexSpectrum::zeros(self.num_bins);
+        assert_eq!(
+            spectrum.num_bins(),
+            self.num_bins,
+            "spectrum num_bins ({}) must equal num_bins ({})",
+            spectrum.num_bins(),
+            self.num_bins
+        );
         stft_process_frame(
             self.inner,
             frame.as_ptr(),
             frame.len() as isize,
-            spec.bins.as_mut_ptr() as *mut f64,
+            spectrum.bins.as_mut_ptr() as *mut f64,
             self.num_bins as isize,
         );
+    }
+
+    /// Like [`Stft::process_frame`], for f32 audio.
+    ///
+    /// # Panics
+    /// Panics if `frame.len() != window_size`.
+    pub fn process_frame_f32(&mut self, frame: &[f32]) -> ComplexSpectrum {
+        let mut spec = ComplexSpectrum::zeros(self.num_bins);
+        self.process_into_f32(frame, &mut spec);
         spec
     }
 
+    /// Like [`Stft::process_into`], for f32 audio. The samples are widened
+    /// into an internal buffer, so no allocation happens per call.
+    ///
+    /// # Panics
+    /// Panics if `frame.len() != window_size` or `spectrum.num_bins()` does
+    /// not match.
+    pub fn process_into_f32(&mut self, frame: &[f32], spectrum: &mut ComplexSpectrum) {
+        let mut scratch = std::mem::take(&mut self.scratch);
+        self.process_into(widen(frame, &mut scratch), spectrum);
+        self.scratch = scratch;
+    }
+
+    /// Analyse a whole signal into a [`Spectrogram`].
+    ///
+    /// The signal is zero-padded by half a window on both ends, so frame `i`
+    /// is centred on sample `i * hop_size` and `signal.len() / hop_size + 1`
+    /// frames are produced.
+    pub fn analyze(&mut self, signal: &[f64]) -> Spectrogram {
+        let mut frame = vec![0.0; self.window_size];
+        let frames = (0..num_frames(signal.len(), self.hop_size))
+            .map(|index| {
+                fill_frame(signal, index, self.hop_size, &mut frame);
+                self.process_frame(&frame)
+            })
+            .collect();
+        Spectrogram::from_frames(
+            frames,
+            self.window_size,
+            self.fft_size,
+            self.hop_size,
+            self.window_type,
+            signal.len(),
+        )
+    }
+
     /// Analysis window size in samples.
     pub fn window_size(&self) -> usize {
         self.window_size
@@ -158,10 +435,74 @@ impl Stft {
         self.hop_size
     }
 
+    /// Window function.
+    pub fn window_type(&self) -> WindowType {
+        self.window_type
+    }
+
+    /// The framing of this transform as [`FftSettings`].
+    pub fn settings(&self) -> FftSettings {
+        FftSettings {
+            window_size: self.window_size,
+            fft_size: self.fft_size,
+            hop_size: self.hop_size,
+            window_type: self.window_type,
+        }
+    }
+
     /// Number of complex bins per spectrum (`fft_size / 2 + 1`).
     pub fn num_bins(&self) -> usize {
         self.num_bins
     }
+
+    /// Delay in samples of a streaming analysis/resynthesis chain built on
+    /// this transform: one full window, as FluCoMa reports for its spectral
+    /// processes.
+    pub fn latency_samples(&self) -> usize {
+        self.window_size
+    }
+
+    /// Re-initialise with new parameters in place, without reallocating the
+    /// instance. Does nothing if the parameters are unchanged.
+    ///
+    /// Internal frame state is reset, so the next processed frame starts a
+    /// fresh analysis.
+    ///
+    /// # Errors
+    /// Returns an error if parameters are invalid. The current
+    /// configuration is kept in that case.
+    pub fn set_params(
+        &mut self,
+        window_size: usize,
+        fft_size: usize,
+        hop_size: usize,
+        window_type: WindowType,
+    ) -> Result<(), FlucomaError> {
+        validate_params(window_size, fft_size, hop_size)?;
+        if (window_size, fft_size, hop_size, window_type)
+            == (
+                self.window_size,
+                self.fft_size,
+                self.hop_size,
+                self.window_type,
+            )
+        {
+            return Ok(());
+        }
+        stft_reset(
+            self.inner,
+            window_size as isize,
+            fft_size as isize,
+            hop_size as isize,
+            window_type as isize,
+        );
+        self.window_size = window_size;
+        self.fft_size = fft_size;
+        self.hop_size = hop_size;
+        self.window_type = window_type;
+        self.num_bins = fft_size / 2 + 1;
+        Ok(())
+    }
 }
 
 impl Drop for Stft {
@@ -170,6 +511,28 @@ impl Drop for Stft {
     }
 }
 
+impl Clone for Stft {
+    fn clone(&self) -> Self {
+        Self::new(
+            self.window_size,
+            self.fft_size,
+            self.hop_size,
+            self.window_type,
+        )
+        .expect("settings were validated on construction")
+    }
+}
+
+impl FrameAnalyzer for Stft {
+    type Input = [f64];
+    type Params = ();
+    type Output = ComplexSpectrum;
+
+    fn process(&mut self, frame: &[f64], _params: &()) -> ComplexSpectrum {
+        self.process_frame(frame)
+    }
+}
+
 // -------------------------------------------------------------------------------------------------
 
 /// Inverse Short-Time Fourier Transform -- reconstructs audio from complex
@@ -181,6 +544,7 @@ pub struct Istft {
     window_size: usize,
     fft_size: usize,
     hop_size: usize,
+    window_type: WindowType,
     num_bins: usize,
 }
 
@@ -193,16 +557,8 @@ impl Istft {
         fft_size: usize,
         hop_size: usize,
         window_type: WindowType,
-    ) -> Result<Self, &'static str> {
-        if window_size == 0 {
-            return Err("window_size must be > 0");
-        }
-        if fft_size < window_size {
-            return Err("fft_size must be >= window_size");
-        }
-        if hop_size == 0 {
-            return Err("hop_size must be > 0");
-        }
+    ) -> Result<Self, FlucomaError> {
+        validate_params(window_size, fft_size, hop_size)?;
         let inner = istft_create(
             window_size as isize,
             fft_size as isize,
@@ -210,17 +566,37 @@ impl Istft {
             window_type as isize,
         );
         if inner.is_null() {
-            return Err("failed to create ISTFT instance");
+            return Err(FlucomaError::AllocationFailed);
         }
         Ok(Self {
             inner,
             window_size,
             fft_size,
             hop_size,
+            window_type,
             num_bins: fft_size / 2 + 1,
         })
     }
 
+    /// Create an ISTFT synthesiser from shared [`FftSettings`].
+    ///
+    /// # Errors
+    /// Returns an error if the settings are invalid.
+    pub fn from_settings(settings: &FftSettings) -> Result<Self, FlucomaError> {
+        Self::new(
+            settings.window_size,
+            settings.fft_size,
+            settings.hop_size,
+            settings.window_type,
+        )
+    }
+
+    /// Start building an ISTFT synthesiser from named settings, see
+    /// [`StftBuilder`].
+    pub fn builder() -> IstftBuilder {
+        StftBuilder::default()
+    }
+
     /// Synthesise one audio frame from a complex spectrum.
     ///
     /// # Arguments
@@ -254,6 +630,72 @@ impl Istft {
         );
     }
 
+    /// Like [`Istft::process_frame`], but returns
+    /// [`FlucomaError::InvalidShape`] instead of panicking on a wrong-sized
+    /// spectrum or output buffer.
+    pub fn try_process_frame(
+        &mut self,
+        spectrum: &ComplexSpectrum,
+        output: &mut [f64],
+    ) -> Result<(), FlucomaError> {
+        check_len(self.num_bins, spectrum.num_bins())?;
+        check_len(self.window_size, output.len())?;
+        self.process_frame(spectrum, output);
+        Ok(())
+    }
+
+    /// Resynthesise a whole signal from a [`Spectrogram`].
+    ///
+    /// Frames are overlap-added at their centred positions and normalised by
+    /// the summed squared window, so an unmodified spectrogram from
+    /// [`Stft::analyze`] with matching settings reconstructs its input. The
+    /// output has the spectrogram's [`signal_len`](Spectrogram::signal_len).
+    ///
+    /// # Panics
+    /// Panics if `spectrogram.num_bins() != self.num_bins`.
+    pub fn synthesize(&mut self, spectrogram: &Spectrogram) -> Vec<f64> {
+        assert_eq!(
+            spectrogram.num_bins(),
+            self.num_bins,
+            "spectrogram num_bins ({}) must equal num_bins ({})",
+            spectrogram.num_bins(),
+            self.num_bins
+        );
+        let half = self.window_size / 2;
+        // A hop longer than the window leaves gaps between frames; zero-pad
+        // the frames to the hop so the accumulator still accepts them.
+        let frame_len = self.window_size.max(self.hop_size);
+        let mut ola = OverlapAdd::new(frame_len, self.hop_size, self.window_type)
+            .expect("frame_len is at least hop_size");
+        let mut norm: Vec<f64> = window_values(self.window_type, self.window_size)
+            .into_iter()
+            .map(|w| w * w)
+            .collect();
+        norm.resize(frame_len, 0.0);
+        let mut output = Vec::with_capacity(
+            spectrogram.num_frames().saturating_sub(1) * self.hop_size + frame_len,
+        );
+        let mut spectrum = ComplexSpectrum::zeros(self.num_bins);
+        let mut frame = vec![0.0; frame_len];
+        for index in 0..spectrogram.num_frames() {
+            spectrum.bins.copy_from_slice(spectrogram.frame(index));
+            self.process_frame(&spectrum, &mut frame[..self.window_size]);
+            output.extend_from_slice(ola.process_frame_with_norm(&frame, &norm));
+        }
+        output.extend(ola.flush());
+        let signal_len = if spectrogram.hop_size() == self.hop_size {
+            spectrogram.signal_len()
+        } else {
+            spectrogram.num_frames().saturating_sub(1) * self.hop_size
+        };
+        output
+            .into_iter()
+            .skip(half)
+            .chain(std::iter::repeat(0.0))
+            .take(signal_len)
+            .collect()
+    }
+
     /// Synthesis window size in samples.
     pub fn window_size(&self) -> usize {
         self.window_size
@@ -269,10 +711,67 @@ impl Istft {
         self.hop_size
     }
 
+    /// Window function.
+    pub fn window_type(&self) -> WindowType {
+        self.window_type
+    }
+
+    /// The framing of this transform as [`FftSettings`].
+    pub fn settings(&self) -> FftSettings {
+        FftSettings {
+            window_size: self.window_size,
+            fft_size: self.fft_size,
+            hop_size: self.hop_size,
+            window_type: self.window_type,
+        }
+    }
+
     /// Number of complex bins per spectrum (`fft_size / 2 + 1`).
     pub fn num_bins(&self) -> usize {
         self.num_bins
     }
+
+    /// Re-initialise with new parameters in place, without reallocating the
+    /// instance. Does nothing if the parameters are unchanged.
+    ///
+    /// Internal frame state is reset, so the next processed frame starts a
+    /// fresh analysis.
+    ///
+    /// # Errors
+    /// Returns an error if parameters are invalid. The current
+    /// configuration is kept in that case.
+    pub fn set_params(
+        &mut self,
+        window_size: usize,
+        fft_size: usize,
+        hop_size: usize,
+        window_type: WindowType,
+    ) -> Result<(), FlucomaError> {
+        validate_params(window_size, fft_size, hop_size)?;
+        if (window_size, fft_size, hop_size, window_type)
+            == (
+                self.window_size,
+                self.fft_size,
+                self.hop_size,
+                self.window_type,
+            )
+        {
+            return Ok(());
+        }
+        istft_reset(
+            self.inner,
+            window_size as isize,
+            fft_size as isize,
+            hop_size as isize,
+            window_type as isize,
+        );
+        self.window_size = window_size;
+        self.fft_size = fft_size;
+        self.hop_size = hop_size;
+        self.window_type = window_type;
+        self.num_bins = fft_size / 2 + 1;
+        Ok(())
+    }
 }
 
 impl Drop for Istft {
@@ -281,6 +780,246 @@ impl Drop for Istft {
     }
 }
 
+impl Clone for Istft {
+    fn clone(&self) -> Self {
+        Self::new(
+            self.window_size,
+            self.fft_size,
+            self.hop_size,
+            self.window_type,
+        )
+        .expect("settings were validated on construction")
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Named-parameter construction of an [`Stft`] or [`Istft`], created with
+/// [`Stft::builder`] or [`Istft::builder`].
+///
+/// Defaults to a 1024 sample Hann window. Unless set, the FFT size equals
+/// the window size and the hop is half the window size.
+#[derive(Debug, Clone, Copy)]
+pub struct StftBuilder<T> {
+    window_size: usize,
+    fft_size: Option<usize>,
+    hop_size: Option<usize>,
+    window_type: WindowType,
+    target: PhantomData<fn() -> T>,
+}
+
+/// Builder for an [`Istft`], see [`StftBuilder`].
+pub type IstftBuilder = StftBuilder<Istft>;
+
+impl<T> Default for StftBuilder<T> {
+    fn default() -> Self {
+        Self {
+            window_size: 1024,
+            fft_size: None,
+            hop_size: None,
+            window_type: WindowType::Hann,
+            target: PhantomData,
+        }
+    }
+}
+
+impl<T> StftBuilder<T> {
+    /// Analysis window length in samples.
+    pub fn window(mut self, window_size: usize) -> Self {
+        self.window_size = window_size;
+        self
+    }
+
+    /// FFT size, at least the window size.
+    pub fn fft(mut self, fft_size: usize) -> Self {
+        self.fft_size = Some(fft_size);
+        self
+    }
+
+    /// Hop between successive frames in samples.
+    pub fn hop(mut self, hop_size: usize) -> Self {
+        self.hop_size = Some(hop_size);
+        self
+    }
+
+    pub fn window_type(mut self, window_type: WindowType) -> Self {
+        self.window_type = window_type;
+        self
+    }
+
+    /// Take all sizes and the window type from shared [`FftSettings`].
+    pub fn settings(mut self, settings: &FftSettings) -> Self {
+        self.window_size = settings.window_size;
+        self.fft_size = Some(settings.fft_size);
+        self.hop_size = Some(settings.hop_size);
+        self.window_type = settings.window_type;
+        self
+    }
+
+    /// `(window_size, fft_size, hop_size)` with defaults applied.
+    fn sizes(&self) -> (usize, usize, usize) {
+        let fft_size = self.fft_size.unwrap_or(self.window_size);
+        let hop_size = self.hop_size.unwrap_or(self.window_size / 2);
+        (self.window_size, fft_size, hop_size)
+    }
+}
+
+impl StftBuilder<Stft> {
+    /// Create the analyser.
+    ///
+    /// # Errors
+    /// See [`Stft::new`].
+    pub fn build(self) -> Result<Stft, FlucomaError> {
+        let (window_size, fft_size, hop_size) = self.sizes();
+        Stft::new(window_size, fft_size, hop_size, self.window_type)
+    }
+}
+
+impl StftBuilder<Istft> {
+    /// Create the synthesiser.
+    ///
+    /// # Errors
+    /// See [`Istft::new`].
+    pub fn build(self) -> Result<Istft, FlucomaError> {
+        let (window_size, fft_size, hop_size) = self.sizes();
+        Istft::new(window_size, fft_size, hop_size, self.window_type)
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// [`Stft`] with window and hop size fixed at compile time, for
+/// allocation-free real-time and embedded pipelines.
+///
+/// The FFT size equals `WINDOW`. Frames and spectra are fixed-size arrays, so
+/// length mismatches are compile errors rather than runtime panics, and
+/// [`FixedStft::process_frame`] writes into a caller-owned (e.g. stack)
+/// buffer instead of allocating. Allocation only happens in
+/// [`FixedStft::new`].
+///
+/// ```rust,no_run
+/// use flucoma_rs::fourier::{Complex, FixedStft, WindowType};
+///
+/// type Analysis = FixedStft<1024, 512>;
+/// let mut stft = Analysis::new(WindowType::Hann).unwrap();
+/// let frame = [0.0f64; 1024];
+/// let mut spectrum = [Complex::default(); Analysis::NUM_BINS];
+/// stft.process_frame(&frame, &mut spectrum);
+/// ```
+pub struct FixedStft<const WINDOW: usize, const HOP: usize> {
+    stft: Stft,
+}
+
+impl<const WINDOW: usize, const HOP: usize> FixedStft<WINDOW, HOP> {
+    /// Number of complex bins per spectrum (`WINDOW / 2 + 1`).
+    pub const NUM_BINS: usize = WINDOW / 2 + 1;
+
+    /// Create a new fixed-size STFT analyser.
+    ///
+    /// Fails to compile unless `WINDOW > 0` and `0 < HOP <= WINDOW`.
+    ///
+    /// # Errors
+    /// Returns an error if allocation fails.
+    pub fn new(window_type: WindowType) -> Result<Self, FlucomaError> {
+        const { assert!(WINDOW > 0 && HOP > 0 && HOP <= WINDOW, "invalid WINDOW/HOP") };
+        Ok(Self {
+            stft: Stft::new(WINDOW, WINDOW, HOP, window_type)?,
+        })
+    }
+
+    /// Process one audio frame into `spectrum` without allocating.
+    ///
+    /// Fails to compile unless `BINS == WINDOW / 2 + 1`.
+    pub fn process_frame<const BINS: usize>(
+        &mut self,
+        frame: &[f64; WINDOW],
+        spectrum: &mut [Complex; BINS],
+    ) {
+        const { assert!(BINS == WINDOW / 2 + 1, "BINS must equal WINDOW / 2 + 1") };
+        stft_process_frame(
+            self.stft.inner,
+            frame.as_ptr(),
+            WINDOW as isize,
+            spectrum.as_mut_ptr() as *mut f64,
+            BINS as isize,
+        );
+    }
+
+    /// Window function.
+    pub fn window_type(&self) -> WindowType {
+        self.stft.window_type
+    }
+}
+
+/// [`Istft`] with window and hop size fixed at compile time, the synthesis
+/// counterpart of [`FixedStft`].
+pub struct FixedIstft<const WINDOW: usize, const HOP: usize> {
+    istft: Istft,
+}
+
+impl<const WINDOW: usize, const HOP: usize> FixedIstft<WINDOW, HOP> {
+    /// Number of complex bins per spectrum (`WINDOW / 2 + 1`).
+    pub const NUM_BINS: usize = WINDOW / 2 + 1;
+
+    /// Create a new fixed-size ISTFT synthesiser.
+    ///
+    /// Fails to compile unless `WINDOW > 0` and `0 < HOP <= WINDOW`.
+    ///
+    /// # Errors
+    /// Returns an error if allocation fails.
+    pub fn new(window_type: WindowType) -> Result<Self, FlucomaError> {
+        const { assert!(WINDOW > 0 && HOP > 0 && HOP <= WINDOW, "invalid WINDOW/HOP") };
+        Ok(Self {
+            istft: Istft::new(WINDOW, WINDOW, HOP, window_type)?,
+        })
+    }
+
+    /// Synthesise one audio frame from `spectrum` into `output` without
+    /// allocating.
+    ///
+    /// Fails to compile unless `BINS == WINDOW / 2 + 1`.
+    pub fn process_frame<const BINS: usize>(
+        &mut self,
+        spectrum: &[Complex; BINS],
+        output: &mut [f64; WINDOW],
+    ) {
+        const { assert!(BINS == WINDOW / 2 + 1, "BINS must equal WINDOW / 2 + 1") };
+        istft_process_frame(
+            self.istft.inner,
+            spectrum.as_ptr() as *const f64,
+            BINS as isize,
+            output.as_mut_ptr(),
+            WINDOW as isize,
+        );
+    }
+
+    /// Window function.
+    pub fn window_type(&self) -> WindowType {
+        self.istft.window_type
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+pub(crate) fn validate_params(
+    window_size: usize,
+    fft_size: usize,
+    hop_size: usize,
+) -> Result<(), FlucomaError> {
+    if window_size == 0 {
+        return Err(FlucomaError::InvalidParameter("window_size must be > 0"));
+    }
+    if fft_size < window_size {
+        return Err(FlucomaError::InvalidParameter(
+            "fft_size must be >= window_size",
+        ));
+    }
+    if hop_size == 0 {
+        return Err(FlucomaError::InvalidParameter("hop_size must be > 0"));
+    }
+    Ok(())
+}
+
 // -------------------------------------------------------------------------------------------------
 
 #[cfg(test)]
@@ -297,6 +1036,109 @@ mod tests {
         assert_eq!(spec.bins.len(), fft_size / 2 + 1);
     }
 
+    #[test]
+    fn stft_set_params_changes_resolution() {
+        let mut stft = Stft::new(1024, 1024, 512, WindowType::Hann).unwrap();
+        stft.set_params(256, 512, 128, WindowType::Hamming).unwrap();
+        assert_eq!(stft.window_size(), 256);
+        assert_eq!(stft.num_bins(), 257);
+        assert_eq!(stft.window_type(), WindowType::Hamming);
+        let spec = stft.process_frame(&vec![0.0f64; 256]);
+        assert_eq!(spec.num_bins(), 257);
+
+        assert!(stft.set_params(512, 256, 128, WindowType::Hann).is_err());
+        assert_eq!(stft.fft_size(), 512);
+    }
+
+    #[test]
+    fn stft_builder_applies_defaults() {
+        let builder = Stft::builder().window(2048);
+        assert_eq!(builder.sizes(), (2048, 2048, 1024));
+        assert_eq!(builder.fft(4096).hop(256).sizes(), (2048, 4096, 256));
+        assert!(Istft::builder().window(512).fft(256).build().is_err());
+
+        let stft = Stft::builder()
+            .window(1024)
+            .fft(4096)
+            .hop(512)
+            .window_type(WindowType::Hamming)
+            .build()
+            .unwrap();
+        assert_eq!(stft.num_bins(), 2049);
+        assert_eq!(stft.window_type(), WindowType::Hamming);
+    }
+
+    #[test]
+    fn complex_spectrum_polar_round_trip_and_masking() {
+        let mut spectrum = ComplexSpectrum::from_polar(&[1.0, 2.0, 0.5], &[0.0, 1.0, -2.0]);
+        let (mags, phases) = spectrum.to_polar();
+        for (a, b) in mags.iter().zip([1.0, 2.0, 0.5]) {
+            assert!((a - b).abs() < 1e-12);
+        }
+        assert!((phases[1] - 1.0).abs() < 1e-12);
+
+        spectrum.apply_gains(&[1.0, 0.0, 2.0]);
+        spectrum.scale(0.5);
+        assert!((spectrum[0].re - 0.5).abs() < 1e-12);
+        assert_eq!(spectrum[1], Complex::new(0.0, 0.0));
+        assert!((spectrum[2].norm() - 0.5).abs() < 1e-12);
+
+        assert!((spectrum.power(2) - 0.25).abs() < 1e-12);
+        assert_eq!(spectrum.powers()[1], 0.0);
+        let db = spectrum.magnitudes_db(-120.0);
+        assert!((db[0] - 20.0 * 0.5f64.log10()).abs() < 1e-9);
+        assert_eq!(db[1], -120.0);
+
+        let mut filter = ComplexSpectrum::zeros(3);
+        filter[0] = Complex::new(0.0, 2.0);
+        spectrum.multiply(&filter);
+        assert!((spectrum[0] - Complex::new(0.0, 1.0)).norm() < 1e-12);
+        assert_eq!(spectrum[2], Complex::new(0.0, 0.0));
+    }
+
+    #[test]
+    fn try_process_frame_rejects_wrong_length() {
+        let mut stft = Stft::new(256, 256, 128, WindowType::Hann).unwrap();
+        assert_eq!(
+            stft.try_process_frame(&[0.0; 100]).unwrap_err(),
+            FlucomaError::InvalidShape {
+                expected: 256,
+                got: 100
+            }
+        );
+        assert_eq!(stft.try_process_frame(&[0.0; 256]).unwrap().num_bins(), 129);
+    }
+
+    #[test]
+    fn fixed_stft_matches_dynamic_stft() {
+        use std::f64::consts::PI;
+        let frame: [f64; 256] =
+            std::array::from_fn(|i| (2.0 * PI * 440.0 * i as f64 / 44100.0).sin());
+        let mut fixed = FixedStft::<256, 128>::new(WindowType::Hann).unwrap();
+        let mut spectrum = [Complex::default(); FixedStft::<256, 128>::NUM_BINS];
+        fixed.process_frame(&frame, &mut spectrum);
+
+        let mut dynamic = Stft::new(256, 256, 128, WindowType::Hann).unwrap();
+        assert_eq!(dynamic.process_frame(&frame).bins, spectrum.to_vec());
+
+        let mut istft = FixedIstft::<256, 128>::new(WindowType::Hann).unwrap();
+        let mut output = [0.0f64; 256];
+        istft.process_frame(&spectrum, &mut output);
+        assert!(output.iter().any(|&x| x != 0.0));
+    }
+
+    #[test]
+    fn cloned_stft_matches_prototype() {
+        let frame: Vec<f64> = (0..512).map(|i| (i as f64 * 0.1).sin()).collect();
+        let mut prototype = Stft::new(512, 1024, 128, WindowType::Hann).unwrap();
+        let mut clone = prototype.clone();
+        assert_eq!(clone.fft_size(), 1024);
+        assert_eq!(
+            clone.process_frame(&frame).bins,
+            prototype.process_frame(&frame).bins
+        );
+    }
+
     #[test]
     fn stft_istft_roundtrip_impulse() {
         let win = 1024usize;
@@ -320,4 +1162,36 @@ mod tests {
         assert!(orig_energy > 0.0);
         assert!(rec_energy > 0.0, "reconstructed energy is zero");
     }
+
+    #[test]
+    fn analyze_synthesize_roundtrip() {
+        let signal: Vec<f64> = (0..5000).map(|i| (i as f64 * 0.05).sin()).collect();
+        let mut stft = Stft::new(512, 512, 128, WindowType::Hann).unwrap();
+        let spectrogram = stft.analyze(&signal);
+        assert_eq!(spectrogram.num_frames(), 5000 / 128 + 1);
+        assert_eq!(spectrogram.num_bins(), 257);
+
+        let mut istft = Istft::new(512, 512, 128, WindowType::Hann).unwrap();
+        let output = istft.synthesize(&spectrogram);
+        assert_eq!(output.len(), signal.len());
+        for (a, b) in signal.iter().zip(&output).skip(256).take(4000) {
+            assert!((a - b).abs() < 1e-6, "{a} != {b}");
+        }
+
+        let empty = Spectrogram::from_frames(Vec::new(), 512, 512, 128, WindowType::Hann, 0);
+        assert!(istft.synthesize(&empty).is_empty());
+    }
+
+    #[test]
+    fn stft_process_into_matches_process_frame() {
+        let mut stft = Stft::new(256, 512, 128, WindowType::Hann).unwrap();
+        let frame: Vec<f64> = (0..256).map(|i| (i as f64 * 0.2).sin()).collect();
+        let expected = stft.process_frame(&frame);
+        let mut spectrum = ComplexSpectrum::zeros(stft.num_bins());
+        stft.process_into(&frame, &mut spectrum);
+        assert_eq!(spectrum.bins, expected.bins);
+        let mut magnitudes = vec![0.0f64; stft.num_bins()];
+        spectrum.magnitudes_into(&mut magnitudes);
+        assert_eq!(magnitudes, expected.magnitudes());
+    }
 }
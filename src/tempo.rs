@@ -1,8 +1,10 @@
+use crate::error::FlucomaError;
 use crate::segmentation::OnsetSlice;
 pub use crate::onset::OnsetFunction;
 
 /// Configuration for [`TempoEstimator`].
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TempoConfig {
     pub window_size: usize,
     pub fft_size: usize,
@@ -38,6 +40,7 @@ pub struct TempoEstimator {
 
 /// Alternative tempo candidate with relative confidence.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TempoAlternative {
     pub bpm: f64,
     pub confidence: f64,
@@ -45,6 +48,7 @@ pub struct TempoAlternative {
 
 /// Onset diagnostics used during tempo estimation.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TempoOnsetMetrics {
     pub onset_count: usize,
     pub duration_seconds: f64,
@@ -57,6 +61,7 @@ pub struct TempoOnsetMetrics {
 
 /// Detailed tempo estimation result.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TempoEstimate {
     pub bpm: f64,
     pub confidence: f64,
@@ -91,7 +96,7 @@ impl TempoEstimator {
         num_frames: usize,
         num_channels: usize,
         sample_rate: f64,
-    ) -> Result<f64, &'static str> {
+    ) -> Result<f64, FlucomaError> {
         self.estimate_with_details(input, num_frames, num_channels, sample_rate)
             .map(|estimate| estimate.bpm)
     }
@@ -105,27 +110,27 @@ impl TempoEstimator {
         num_frames: usize,
         num_channels: usize,
         sample_rate: f64,
-    ) -> Result<TempoEstimate, &'static str> {
+    ) -> Result<TempoEstimate, FlucomaError> {
         if num_frames == 0 || num_channels == 0 {
-            return Err("input buffer is empty");
+            return Err(FlucomaError::InvalidParameter("input buffer is empty"));
         }
         if sample_rate <= 0.0 {
-            return Err("sample_rate must be > 0");
+            return Err(FlucomaError::InvalidParameter("sample_rate must be > 0"));
         }
         if input.len() != num_frames * num_channels {
-            return Err("input length does not match num_frames * num_channels");
+            return Err(FlucomaError::InvalidParameter("input length does not match num_frames * num_channels"));
         }
         if self.config.window_size == 0
             || self.config.hop_size == 0
             || self.config.fft_size < self.config.window_size
         {
-            return Err("invalid tempo analysis configuration");
+            return Err(FlucomaError::InvalidParameter("invalid tempo analysis configuration"));
         }
         if num_frames < self.config.window_size {
-            return Err("input shorter than analysis window");
+            return Err(FlucomaError::InvalidParameter("input shorter than analysis window"));
         }
         if self.config.min_bpm <= 0.0 || self.config.max_bpm <= self.config.min_bpm {
-            return Err("invalid BPM range");
+            return Err(FlucomaError::InvalidParameter("invalid BPM range"));
         }
 
         let n_hops = (num_frames - self.config.window_size) / self.config.hop_size + 1;
@@ -133,7 +138,7 @@ impl TempoEstimator {
         // 1) Detect onset candidates.
         let onset_result = self.detect_onset_hops(input, num_frames, num_channels, n_hops)?;
         if onset_result.hops.len() < 2 {
-            return Err("not enough onsets detected to estimate tempo");
+            return Err(FlucomaError::InvalidParameter("not enough onsets detected to estimate tempo"));
         }
 
         // 2) Weighted IOI voting over multiple onset separations.
@@ -169,7 +174,7 @@ impl TempoEstimator {
         num_frames: usize,
         num_channels: usize,
         n_hops: usize,
-    ) -> Result<OnsetDetectionResult, &'static str> {
+    ) -> Result<OnsetDetectionResult, FlucomaError> {
         // Try robust threshold sweep with the binary onset segmenter first.
         let mut thresholds = vec![self.config.threshold.max(0.0)];
         let t = self.config.threshold.max(0.0);
@@ -240,7 +245,7 @@ impl TempoEstimator {
         channel: &[f64],
         n_hops: usize,
         threshold: f64,
-    ) -> Result<Vec<usize>, &'static str> {
+    ) -> Result<Vec<usize>, FlucomaError> {
         let mut seg = OnsetSlice::new(
             self.config.window_size,
             self.config.fft_size,
@@ -266,7 +271,7 @@ impl TempoEstimator {
         Ok(onsets)
     }
 
-    fn compute_novelty(&self, channel: &[f64], n_hops: usize) -> Result<Vec<f64>, &'static str> {
+    fn compute_novelty(&self, channel: &[f64], n_hops: usize) -> Result<Vec<f64>, FlucomaError> {
         let mut odf = OnsetSlice::new(
             self.config.window_size,
             self.config.fft_size,
@@ -325,7 +330,7 @@ impl TempoEstimator {
         &self,
         onset_hops: &[usize],
         sample_rate: f64,
-    ) -> Result<TempoVoteResult, &'static str> {
+    ) -> Result<TempoVoteResult, FlucomaError> {
         let bin_size = 0.5;
         let n_bins = ((self.config.max_bpm - self.config.min_bpm) / bin_size).ceil() as usize + 1;
         let mut hist = vec![0.0; n_bins];
@@ -378,9 +383,9 @@ impl TempoEstimator {
             .copied()
             .enumerate()
             .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal))
-            .ok_or("failed to build tempo histogram")?;
+            .ok_or(FlucomaError::InvalidParameter("failed to build tempo histogram"))?;
         if best_score <= 0.0 {
-            return Err("no Inter-Onset Intervals within the specified BPM range");
+            return Err(FlucomaError::InvalidParameter("no Inter-Onset Intervals within the specified BPM range"));
         }
 
         // Pick the best tactus candidate using harmonic reinforcement.
@@ -412,14 +417,14 @@ impl TempoEstimator {
             .copied()
             .enumerate()
             .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal))
-            .ok_or("failed to rank tempo candidates")?;
+            .ok_or(FlucomaError::InvalidParameter("failed to rank tempo candidates"))?;
         if best_combined <= 0.0 {
-            return Err("failed to rank tempo candidates");
+            return Err(FlucomaError::InvalidParameter("failed to rank tempo candidates"));
         }
 
         // Refine by weighted average in a local neighborhood.
         let best_bpm = refine_histogram_peak(&hist, self.config.min_bpm, bin_size, best_idx)
-            .ok_or("failed to refine tempo estimate")?;
+            .ok_or(FlucomaError::InvalidParameter("failed to refine tempo estimate"))?;
 
         let mut rank: Vec<(usize, f64)> = combined_scores.iter().copied().enumerate().collect();
         rank.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
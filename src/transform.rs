@@ -0,0 +1,189 @@
+use crate::error::FlucomaError;
+use crate::matrix::Matrix;
+
+// -------------------------------------------------------------------------------------------------
+
+/// Common interface of the fitted dataset transforms, so scalers and
+/// projections can be chained in a [`TransformPipeline`] or used
+/// generically.
+///
+/// Implemented by [`Normalize`](crate::data::Normalize),
+/// [`Standardize`](crate::data::Standardize),
+/// [`RobustScale`](crate::data::RobustScale) and
+/// [`Pca`](crate::data::Pca). Data is row-major over points, as in the
+/// inherent methods of those types. Transforms are `Send`, so a fitted
+/// [`TransformPipeline`] can be moved to another thread like its stages.
+pub trait Transform: Send {
+    /// Learn the transform's parameters from `data`.
+    fn fit(&mut self, data: &Matrix) -> Result<(), FlucomaError>;
+
+    /// Apply the fitted transform.
+    ///
+    /// # Errors
+    /// Returns [`FlucomaError::NotFitted`] before [`Transform::fit`], or an
+    /// error if `data` does not match the fitted shape.
+    fn transform(&self, data: &Matrix) -> Result<Matrix, FlucomaError>;
+
+    /// Undo [`Transform::transform`].
+    fn inverse_transform(&self, data: &Matrix) -> Result<Matrix, FlucomaError>;
+
+    fn is_fitted(&self) -> bool;
+
+    /// Fit the transform and apply it to the same matrix in one step.
+    fn fit_transform(&mut self, data: &Matrix) -> Result<Matrix, FlucomaError> {
+        self.fit(data)?;
+        self.transform(data)
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// A sequence of [`Transform`]s fitted and applied as one.
+///
+/// Each stage is fitted on the output of the previous one, so e.g.
+/// standardizing before PCA, which [`Pca`](crate::data::Pca) offers through
+/// [`PcaScaler`](crate::data::PcaScaler), can be combined with any other
+/// scaler or column mask. [`TransformPipeline::inverse_transform`] undoes
+/// the stages in reverse order. The pipeline is itself a [`Transform`], so
+/// pipelines nest.
+///
+/// ```rust,no_run
+/// use flucoma_rs::data::{
+///     ColumnMasked, Matrix, Normalize, Pca, PcaConfig, Standardize, Transform,
+///     TransformPipeline,
+/// };
+///
+/// let mut standardize = Standardize::new().unwrap();
+/// standardize.set_columns(Some(vec![0, 1, 2])).unwrap();
+/// let mut pipeline = TransformPipeline::new()
+///     .then(standardize)
+///     .then(Pca::new(PcaConfig::default()).unwrap())
+///     .then(Normalize::new(0.0, 1.0).unwrap());
+///
+/// let data = Matrix::new(100, 4);
+/// let scaled = pipeline.fit_transform(&data).unwrap();
+/// let restored = pipeline.inverse_transform(&scaled).unwrap();
+/// ```
+#[derive(Default)]
+pub struct TransformPipeline {
+    stages: Vec<Box<dyn Transform>>,
+}
+
+impl TransformPipeline {
+    /// Create an empty pipeline, which passes data through unchanged.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a stage.
+    pub fn then(mut self, stage: impl Transform + 'static) -> Self {
+        self.stages.push(Box::new(stage));
+        self
+    }
+
+    /// Append an already boxed stage.
+    pub fn push(&mut self, stage: Box<dyn Transform>) {
+        self.stages.push(stage);
+    }
+
+    /// Number of stages.
+    pub fn len(&self) -> usize {
+        self.stages.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.stages.is_empty()
+    }
+}
+
+impl From<Vec<Box<dyn Transform>>> for TransformPipeline {
+    fn from(stages: Vec<Box<dyn Transform>>) -> Self {
+        Self { stages }
+    }
+}
+
+impl Transform for TransformPipeline {
+    fn fit(&mut self, data: &Matrix) -> Result<(), FlucomaError> {
+        self.fit_transform(data).map(|_| ())
+    }
+
+    fn transform(&self, data: &Matrix) -> Result<Matrix, FlucomaError> {
+        self.stages
+            .iter()
+            .try_fold(data.clone(), |data, stage| stage.transform(&data))
+    }
+
+    fn inverse_transform(&self, data: &Matrix) -> Result<Matrix, FlucomaError> {
+        self.stages
+            .iter()
+            .rev()
+            .try_fold(data.clone(), |data, stage| stage.inverse_transform(&data))
+    }
+
+    fn is_fitted(&self) -> bool {
+        self.stages.iter().all(|stage| stage.is_fitted())
+    }
+
+    fn fit_transform(&mut self, data: &Matrix) -> Result<Matrix, FlucomaError> {
+        self.stages
+            .iter_mut()
+            .try_fold(data.clone(), |data, stage| stage.fit_transform(&data))
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A pure-Rust transform, so the pipeline plumbing can be tested without
+    /// the C++ library.
+    struct Offset(Option<f64>);
+
+    impl Transform for Offset {
+        fn fit(&mut self, data: &Matrix) -> Result<(), FlucomaError> {
+            self.0 = Some(data.data().iter().cloned().fold(f64::INFINITY, f64::min));
+            Ok(())
+        }
+
+        fn transform(&self, data: &Matrix) -> Result<Matrix, FlucomaError> {
+            let offset = self.0.ok_or(FlucomaError::NotFitted)?;
+            let values = data.data().iter().map(|x| x - offset).collect();
+            Matrix::from_vec(values, data.rows(), data.cols())
+        }
+
+        fn inverse_transform(&self, data: &Matrix) -> Result<Matrix, FlucomaError> {
+            let offset = self.0.ok_or(FlucomaError::NotFitted)?;
+            let values = data.data().iter().map(|x| x + offset).collect();
+            Matrix::from_vec(values, data.rows(), data.cols())
+        }
+
+        fn is_fitted(&self) -> bool {
+            self.0.is_some()
+        }
+    }
+
+    #[test]
+    fn pipeline_fits_stages_in_sequence() {
+        let data = Matrix::from_vec(vec![3.0, 5.0, 4.0, 9.0], 2, 2).unwrap();
+        let mut pipeline = TransformPipeline::new().then(Offset(None));
+        pipeline.push(Box::new(Offset(None)));
+        assert!(!pipeline.is_fitted());
+        assert_eq!(pipeline.transform(&data), Err(FlucomaError::NotFitted));
+
+        // The second stage is fitted on the already offset data, so it has
+        // nothing left to remove.
+        let out = pipeline.fit_transform(&data).unwrap();
+        assert_eq!(out.data(), &[0.0, 2.0, 1.0, 6.0]);
+        assert!(pipeline.is_fitted());
+        assert_eq!(pipeline.inverse_transform(&out).unwrap(), data);
+        assert_eq!(TransformPipeline::new().transform(&data).unwrap(), data);
+    }
+
+    #[test]
+    fn pipeline_is_send() {
+        fn assert_send<T: Send>() {}
+        assert_send::<TransformPipeline>();
+    }
+}
@@ -3,6 +3,8 @@ use flucoma_sys::{
     transient_ext_input_size, transient_ext_process, transient_ext_set_detection_params,
 };
 
+use crate::error::{check_len, FlucomaError};
+
 // -------------------------------------------------------------------------------------------------
 
 /// Transient and residual extraction from audio blocks.
@@ -23,8 +25,13 @@ use flucoma_sys::{
 /// See <https://learn.flucoma.org/reference/transients>
 pub struct TransientExtraction {
     inner: *mut u8,
+    order: usize,
+    block_size: usize,
+    pad_size: usize,
     hop_size: usize,
     input_size: usize,
+    /// Last values passed to `set_detection_parameters`, re-applied on clone.
+    detection_parameters: Option<(f64, f64, f64, usize, usize)>,
     /// Output buffer: [transients | residual], each hop_size long.
     buf: Vec<f64>,
 }
@@ -43,20 +50,20 @@ impl TransientExtraction {
     /// audio buffers correctly.
     ///
     /// # Errors
-    /// Returns an error string if parameters are invalid or allocation fails.
-    pub fn new(order: usize, block_size: usize, pad_size: usize) -> Result<Self, &'static str> {
+    /// Returns an error if parameters are invalid or allocation fails.
+    pub fn new(order: usize, block_size: usize, pad_size: usize) -> Result<Self, FlucomaError> {
         if order == 0 {
-            return Err("order must be > 0");
+            return Err(FlucomaError::InvalidParameter("order must be > 0"));
         }
         if block_size <= order {
-            return Err("block_size must be > order");
+            return Err(FlucomaError::InvalidParameter("block_size must be > order"));
         }
         if pad_size <= order {
-            return Err("pad_size must be > order");
+            return Err(FlucomaError::InvalidParameter("pad_size must be > order"));
         }
         let inner = transient_ext_create(order as isize, block_size as isize, pad_size as isize);
         if inner.is_null() {
-            return Err("failed to create TransientExtraction instance");
+            return Err(FlucomaError::AllocationFailed);
         }
         transient_ext_init(
             inner,
@@ -68,8 +75,12 @@ impl TransientExtraction {
         let input_size = transient_ext_input_size(inner) as usize;
         Ok(Self {
             inner,
+            order,
+            block_size,
+            pad_size,
             hop_size,
             input_size,
+            detection_parameters: None,
             buf: vec![0.0f64; 2 * hop_size],
         })
     }
@@ -90,6 +101,7 @@ impl TransientExtraction {
         half_window: usize,
         hold: usize,
     ) {
+        self.detection_parameters = Some((power, thresh_hi, thresh_lo, half_window, hold));
         transient_ext_set_detection_params(
             self.inner,
             power,
@@ -129,6 +141,17 @@ impl TransientExtraction {
         self.buf.split_at(self.hop_size)
     }
 
+    /// Like [`TransientExtraction::process`], but returns
+    /// [`FlucomaError::InvalidShape`] instead of panicking if
+    /// `input.len() != input_size()`.
+    pub fn try_process<'a>(
+        &'a mut self,
+        input: &[f64],
+    ) -> Result<(&'a [f64], &'a [f64]), FlucomaError> {
+        check_len(self.input_size, input.len())?;
+        Ok(self.process(input))
+    }
+
     /// Number of output samples per block.
     pub fn hop_size(&self) -> usize {
         self.hop_size
@@ -138,6 +161,13 @@ impl TransientExtraction {
     pub fn input_size(&self) -> usize {
         self.input_size
     }
+
+    /// Delay in samples when fed hop by hop from a stream: each block's
+    /// output covers the hop `(input_size - hop_size) / 2` samples into the
+    /// block, and a full hop is buffered before the block is complete.
+    pub fn latency_samples(&self) -> usize {
+        self.input_size - (self.input_size - self.hop_size) / 2
+    }
 }
 
 impl Drop for TransientExtraction {
@@ -146,6 +176,17 @@ impl Drop for TransientExtraction {
     }
 }
 
+impl Clone for TransientExtraction {
+    fn clone(&self) -> Self {
+        let mut clone = Self::new(self.order, self.block_size, self.pad_size)
+            .expect("settings were validated on construction");
+        if let Some((power, thresh_hi, thresh_lo, half_window, hold)) = self.detection_parameters {
+            clone.set_detection_parameters(power, thresh_hi, thresh_lo, half_window, hold);
+        }
+        clone
+    }
+}
+
 // -------------------------------------------------------------------------------------------------
 
 #[cfg(test)]
@@ -197,4 +238,24 @@ mod tests {
         assert_eq!(t.len(), hop);
         assert_eq!(r.len(), hop);
     }
+
+    #[test]
+    fn transient_ext_output_is_delayed_by_latency() {
+        let mut ext = TransientExtraction::new(20, 256, 128).unwrap();
+        let (hop, input_size) = (ext.hop_size(), ext.input_size());
+        let mut audio: Vec<f64> = (0..8192).map(|i| 0.01 * (i as f64 * 0.05).sin()).collect();
+        audio[3000] = 1.0;
+        let mut history = vec![0.0; input_size];
+        let mut output = Vec::with_capacity(audio.len());
+        for block in audio.chunks_exact(hop) {
+            history.copy_within(hop.., 0);
+            history[input_size - hop..].copy_from_slice(block);
+            let (transients, residual) = ext.process(&history);
+            output.extend(transients.iter().zip(residual).map(|(t, r)| t + r));
+        }
+        let loudest = (0..output.len())
+            .max_by(|&a, &b| output[a].abs().total_cmp(&output[b].abs()))
+            .unwrap();
+        assert_eq!(loudest, 3000 + ext.latency_samples());
+    }
 }
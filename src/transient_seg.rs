@@ -1,8 +1,13 @@
 use flucoma_sys::{
-    transient_seg_create, transient_seg_destroy, transient_seg_hop_size, transient_seg_init,
-    transient_seg_input_size, transient_seg_process, transient_seg_set_detection_params,
+    transient_seg_create, transient_seg_destroy, transient_seg_detection_peak,
+    transient_seg_hop_size, transient_seg_init, transient_seg_input_size, transient_seg_process,
+    transient_seg_set_detection_params,
 };
 
+use crate::error::{check_len, FlucomaError};
+use crate::frame_analyzer::FrameAnalyzer;
+use crate::sample_f32::widen;
+
 // -------------------------------------------------------------------------------------------------
 
 /// Transient detector and segmenter operating on audio blocks.
@@ -19,8 +24,14 @@ use flucoma_sys::{
 /// See <https://learn.flucoma.org/reference/transientslice>
 pub struct TransientSlice {
     inner: *mut u8,
+    order: usize,
+    block_size: usize,
+    pad_size: usize,
     hop_size: usize,
     input_size: usize,
+    /// Last values passed to `set_detection_parameters`, re-applied on clone.
+    detection_parameters: Option<(f64, f64, f64, usize, usize, usize)>,
+    scratch: Vec<f64>,
 }
 
 unsafe impl Send for TransientSlice {}
@@ -37,20 +48,20 @@ impl TransientSlice {
     /// audio buffers correctly.
     ///
     /// # Errors
-    /// Returns an error string if parameters are invalid or allocation fails.
-    pub fn new(order: usize, block_size: usize, pad_size: usize) -> Result<Self, &'static str> {
+    /// Returns an error if parameters are invalid or allocation fails.
+    pub fn new(order: usize, block_size: usize, pad_size: usize) -> Result<Self, FlucomaError> {
         if order == 0 {
-            return Err("order must be > 0");
+            return Err(FlucomaError::InvalidParameter("order must be > 0"));
         }
         if block_size <= order {
-            return Err("block_size must be > order");
+            return Err(FlucomaError::InvalidParameter("block_size must be > order"));
         }
         if pad_size <= order {
-            return Err("pad_size must be > order");
+            return Err(FlucomaError::InvalidParameter("pad_size must be > order"));
         }
         let inner = transient_seg_create(order as isize, block_size as isize, pad_size as isize);
         if inner.is_null() {
-            return Err("failed to create TransientSlice instance");
+            return Err(FlucomaError::AllocationFailed);
         }
         transient_seg_init(
             inner,
@@ -62,8 +73,13 @@ impl TransientSlice {
         let input_size = transient_seg_input_size(inner) as usize;
         Ok(Self {
             inner,
+            order,
+            block_size,
+            pad_size,
             hop_size,
             input_size,
+            detection_parameters: None,
+            scratch: Vec::with_capacity(input_size),
         })
     }
 
@@ -85,6 +101,8 @@ impl TransientSlice {
         hold: usize,
         min_segment: usize,
     ) {
+        self.detection_parameters =
+            Some((power, thresh_hi, thresh_lo, half_window, hold, min_segment));
         transient_seg_set_detection_params(
             self.inner,
             power,
@@ -107,6 +125,25 @@ impl TransientSlice {
     /// # Panics
     /// Panics if `input.len() != input_size()`.
     pub fn process(&mut self, input: &[f64]) -> Vec<f64> {
+        let mut output = vec![0.0f64; self.hop_size];
+        self.process_into(input, &mut output);
+        output
+    }
+
+    /// Like [`TransientSlice::process`], but returns
+    /// [`FlucomaError::InvalidShape`] instead of panicking if
+    /// `input.len() != input_size()`.
+    pub fn try_process(&mut self, input: &[f64]) -> Result<Vec<f64>, FlucomaError> {
+        check_len(self.input_size, input.len())?;
+        Ok(self.process(input))
+    }
+
+    /// Like [`TransientSlice::process`], but writes the onset flags into
+    /// `output` instead of allocating, for use on an audio thread.
+    ///
+    /// # Panics
+    /// Panics if `input.len() != input_size()` or `output.len() != hop_size()`.
+    pub fn process_into(&mut self, input: &[f64], output: &mut [f64]) {
         assert_eq!(
             input.len(),
             self.input_size,
@@ -114,7 +151,13 @@ impl TransientSlice {
             input.len(),
             self.input_size
         );
-        let mut output = vec![0.0f64; self.hop_size];
+        assert_eq!(
+            output.len(),
+            self.hop_size,
+            "output length ({}) must equal hop_size ({})",
+            output.len(),
+            self.hop_size
+        );
         transient_seg_process(
             self.inner,
             input.as_ptr(),
@@ -122,9 +165,59 @@ impl TransientSlice {
             output.as_mut_ptr(),
             output.len() as isize,
         );
+    }
+
+    /// Like [`TransientSlice::process`], for f32 audio.
+    ///
+    /// # Panics
+    /// Panics if `input.len() != input_size()`.
+    pub fn process_f32(&mut self, input: &[f32]) -> Vec<f64> {
+        let mut output = vec![0.0f64; self.hop_size];
+        self.process_into_f32(input, &mut output);
         output
     }
 
+    /// Like [`TransientSlice::process_into`], for f32 audio. The samples are
+    /// widened into an internal buffer, so no allocation happens per call.
+    ///
+    /// # Panics
+    /// Panics if `input.len() != input_size()` or `output.len() != hop_size()`.
+    pub fn process_into_f32(&mut self, input: &[f32], output: &mut [f64]) {
+        let mut scratch = std::mem::take(&mut self.scratch);
+        self.process_into(widen(input, &mut scratch), output);
+        self.scratch = scratch;
+    }
+
+    /// Peak of the detection function over the block processed by the last
+    /// call to [`TransientSlice::process`]: the larger of the windowed
+    /// forward and backward prediction errors, compared against `thresh_hi`.
+    pub fn detection_peak(&self) -> f64 {
+        transient_seg_detection_peak(self.inner, self.hop_size as isize)
+    }
+
+    /// Clear the model and detection history, as if newly created, keeping
+    /// the detection parameters.
+    pub fn reset(&mut self) {
+        transient_seg_init(
+            self.inner,
+            self.order as isize,
+            self.block_size as isize,
+            self.pad_size as isize,
+        );
+        if let Some((power, thresh_hi, thresh_lo, half_window, hold, min_segment)) =
+            self.detection_parameters
+        {
+            self.set_detection_parameters(
+                power,
+                thresh_hi,
+                thresh_lo,
+                half_window,
+                hold,
+                min_segment,
+            );
+        }
+    }
+
     /// Number of output samples per block (block_size - model_order).
     pub fn hop_size(&self) -> usize {
         self.hop_size
@@ -134,6 +227,13 @@ impl TransientSlice {
     pub fn input_size(&self) -> usize {
         self.input_size
     }
+
+    /// Delay in samples when fed hop by hop from a stream: each block's
+    /// output covers the hop `(input_size - hop_size) / 2` samples into the
+    /// block, and a full hop is buffered before the block is complete.
+    pub fn latency_samples(&self) -> usize {
+        self.input_size - (self.input_size - self.hop_size) / 2
+    }
 }
 
 impl Drop for TransientSlice {
@@ -142,6 +242,36 @@ impl Drop for TransientSlice {
     }
 }
 
+impl Clone for TransientSlice {
+    fn clone(&self) -> Self {
+        let mut clone = Self::new(self.order, self.block_size, self.pad_size)
+            .expect("settings were validated on construction");
+        if let Some((power, thresh_hi, thresh_lo, half_window, hold, min_segment)) =
+            self.detection_parameters
+        {
+            clone.set_detection_parameters(
+                power,
+                thresh_hi,
+                thresh_lo,
+                half_window,
+                hold,
+                min_segment,
+            );
+        }
+        clone
+    }
+}
+
+impl FrameAnalyzer for TransientSlice {
+    type Input = [f64];
+    type Params = ();
+    type Output = Vec<f64>;
+
+    fn process(&mut self, input: &[f64], _params: &()) -> Vec<f64> {
+        TransientSlice::process(self, input)
+    }
+}
+
 // -------------------------------------------------------------------------------------------------
 
 #[cfg(test)]
@@ -182,4 +312,23 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn transient_seg_process_into_matches_process() {
+        let mut a = TransientSlice::new(20, 256, 128).unwrap();
+        let mut b = TransientSlice::new(20, 256, 128).unwrap();
+        a.set_detection_parameters(1.0, 1.0, 0.5, 7, 25, 50);
+        b.set_detection_parameters(1.0, 1.0, 0.5, 7, 25, 50);
+        let mut input = vec![0.0f64; a.input_size()];
+        let mut output = vec![0.0f64; b.hop_size()];
+        for block in 0..4 {
+            input.fill(0.0);
+            if block == 2 {
+                input[0] = 1.0;
+            }
+            let expected = a.process(&input);
+            b.process_into(&input, &mut output);
+            assert_eq!(output, expected, "block {block}");
+        }
+    }
 }
@@ -0,0 +1,317 @@
+use std::collections::VecDeque;
+
+use crate::error::FlucomaError;
+use crate::replay::{replay_len, ReplayHistory};
+use crate::transient_seg::TransientSlice;
+
+// -------------------------------------------------------------------------------------------------
+
+/// Configuration for [`TransientSliceStream`].
+///
+/// Defaults match FluidTransientSlice.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TransientSliceStreamConfig {
+    /// AR model order.
+    pub order: usize,
+    /// Analysis block size in samples (must be > `order`).
+    pub block_size: usize,
+    /// Look-ahead padding in samples (must be > `order`).
+    pub pad_size: usize,
+    /// Spectral power threshold for residual detection.
+    pub power: f64,
+    /// Upper detection threshold.
+    pub thresh_hi: f64,
+    /// Lower detection threshold (hysteresis).
+    pub thresh_lo: f64,
+    /// Half-width of the peak-detection window in frames.
+    pub half_window: usize,
+    /// Minimum frames between successive detections.
+    pub hold: usize,
+    /// Minimum segment length in samples.
+    pub min_segment: usize,
+}
+
+impl Default for TransientSliceStreamConfig {
+    fn default() -> Self {
+        Self {
+            order: 20,
+            block_size: 256,
+            pad_size: 128,
+            power: 1.0,
+            thresh_hi: 2.0,
+            thresh_lo: 1.1,
+            half_window: 7,
+            hold: 25,
+            min_segment: 1000,
+        }
+    }
+}
+
+/// Snapshot of a [`TransientSliceStream`], from
+/// [`TransientSliceStream::save_state`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TransientSliceStreamState {
+    input_size: usize,
+    hop_size: usize,
+    until_hop: usize,
+    /// Recent audio, starting on a hop boundary.
+    recent: Vec<f64>,
+}
+
+/// Streaming transient slicing over arbitrarily sized audio chunks.
+///
+/// Wraps a [`TransientSlice`], buffers the incoming audio, feeds it
+/// `input_size()` blocks every `hop_size()` samples and returns exactly one
+/// 0/1 value per input sample. The output is the detection for the input
+/// [`latency_samples`](TransientSliceStream::latency_samples) samples
+/// earlier, so the hop/pad framing never leaks into the caller.
+///
+/// ```rust,no_run
+/// use flucoma_rs::segmentation::{TransientSliceStream, TransientSliceStreamConfig};
+///
+/// let mut stream = TransientSliceStream::new(TransientSliceStreamConfig::default()).unwrap();
+/// let block = [0.0f64; 64];
+/// let flags = stream.process(&block);
+/// assert_eq!(flags.len(), 64);
+/// ```
+pub struct TransientSliceStream {
+    config: TransientSliceStreamConfig,
+    slicer: TransientSlice,
+    /// The most recent `input_size` samples, oldest first.
+    history: Vec<f64>,
+    until_hop: usize,
+    pending: VecDeque<f64>,
+    flags: Vec<f64>,
+    /// Enough recent audio to rebuild the segmenter state on restore; only
+    /// kept while state recording is on.
+    recent: Option<ReplayHistory>,
+}
+
+impl TransientSliceStream {
+    /// # Errors
+    /// Returns an error if the configuration is invalid or the
+    /// segmenter cannot be created.
+    pub fn new(config: TransientSliceStreamConfig) -> Result<Self, FlucomaError> {
+        let mut slicer = TransientSlice::new(config.order, config.block_size, config.pad_size)?;
+        slicer.set_detection_parameters(
+            config.power,
+            config.thresh_hi,
+            config.thresh_lo,
+            config.half_window,
+            config.hold,
+            config.min_segment,
+        );
+        let hop = slicer.hop_size();
+        Ok(Self {
+            history: vec![0.0; slicer.input_size()],
+            until_hop: hop,
+            pending: std::iter::repeat_n(0.0, hop).collect(),
+            flags: vec![0.0; hop],
+            recent: None,
+            slicer,
+            config,
+        })
+    }
+
+    pub fn config(&self) -> &TransientSliceStreamConfig {
+        &self.config
+    }
+
+    /// Delay in samples between an input sample and its output value; see
+    /// [`TransientSlice::latency_samples`].
+    pub fn latency_samples(&self) -> usize {
+        self.slicer.latency_samples()
+    }
+
+    /// Consume a chunk of audio of any length and return one value per
+    /// sample: 1.0 at a transient onset, 0.0 otherwise.
+    pub fn process(&mut self, chunk: &[f64]) -> Vec<f64> {
+        self.process_samples(chunk)
+    }
+
+    /// Like [`TransientSliceStream::process`], for f32 audio.
+    pub fn process_f32(&mut self, chunk: &[f32]) -> Vec<f64> {
+        self.process_samples(chunk)
+    }
+
+    /// Clear the buffered audio, pending output and segmenter state.
+    pub fn reset(&mut self) {
+        let hop = self.slicer.hop_size();
+        self.slicer.reset();
+        self.history.fill(0.0);
+        self.until_hop = hop;
+        self.pending.clear();
+        self.pending.extend(std::iter::repeat_n(0.0, hop));
+        if let Some(recent) = &mut self.recent {
+            recent.clear();
+        }
+    }
+
+    /// Keep enough recent audio for [`TransientSliceStream::save_state`] to
+    /// rebuild the segmenter. Off by default, so streams that are never
+    /// checkpointed do not copy their input.
+    pub fn set_state_recording(&mut self, enabled: bool) {
+        match (enabled, &self.recent) {
+            (true, None) => {
+                let capacity = 4 * self.slicer.input_size() + self.slicer.hop_size();
+                self.recent = Some(ReplayHistory::new(capacity));
+            }
+            (false, Some(_)) => self.recent = None,
+            _ => {}
+        }
+    }
+
+    /// Capture the recent audio so slicing can be resumed later, e.g. after
+    /// restarting a long-running session.
+    ///
+    /// Only audio processed while state recording is enabled (see
+    /// [`TransientSliceStream::set_state_recording`]) is captured.
+    pub fn save_state(&self) -> TransientSliceStreamState {
+        let hop = self.slicer.hop_size();
+        let recent = self.recent.as_ref().map_or_else(Vec::new, |recent| {
+            recent.latest(replay_len(recent.len(), hop, self.until_hop))
+        });
+        TransientSliceStreamState {
+            input_size: self.slicer.input_size(),
+            hop_size: hop,
+            until_hop: self.until_hop,
+            recent,
+        }
+    }
+
+    /// Resume from a state captured by [`TransientSliceStream::save_state`].
+    ///
+    /// The segmenter is reset and the recorded audio replayed through it,
+    /// rebuilding the model history and the pending output.
+    ///
+    /// # Errors
+    /// Returns an error if the state was saved from a stream with a
+    /// different block, pad or hop size.
+    pub fn restore_state(&mut self, state: &TransientSliceStreamState) -> Result<(), FlucomaError> {
+        if (state.input_size, state.hop_size) != (self.slicer.input_size(), self.slicer.hop_size())
+        {
+            return Err(FlucomaError::InvalidParameter(
+                "state was saved with a different input_size or hop_size",
+            ));
+        }
+        self.reset();
+        self.process_samples(&state.recent);
+        if state.recent.is_empty() {
+            // Nothing was recorded, so only the hop phase can be restored.
+            self.until_hop = state.until_hop;
+            self.pending.truncate(state.until_hop);
+        }
+        debug_assert_eq!(self.until_hop, state.until_hop);
+        Ok(())
+    }
+
+    fn process_samples<S: Copy + Into<f64>>(&mut self, chunk: &[S]) -> Vec<f64> {
+        if let Some(recent) = &mut self.recent {
+            recent.extend(chunk.iter().map(|&sample| sample.into()));
+        }
+        let mut output = Vec::with_capacity(chunk.len());
+        let mut offset = 0;
+        while offset < chunk.len() {
+            let take = self.until_hop.min(chunk.len() - offset);
+            self.push(&chunk[offset..offset + take]);
+            output.extend(self.pending.drain(..take));
+            offset += take;
+            self.until_hop -= take;
+            if self.until_hop == 0 {
+                self.until_hop = self.slicer.hop_size();
+                self.slicer.process_into(&self.history, &mut self.flags);
+                self.pending.extend(&self.flags);
+            }
+        }
+        output
+    }
+
+    fn push<S: Copy + Into<f64>>(&mut self, samples: &[S]) {
+        let len = self.history.len();
+        self.history.copy_within(samples.len().., 0);
+        for (slot, &sample) in self.history[len - samples.len()..].iter_mut().zip(samples) {
+            *slot = sample.into();
+        }
+    }
+}
+
+impl Clone for TransientSliceStream {
+    fn clone(&self) -> Self {
+        let mut clone =
+            Self::new(self.config.clone()).expect("settings were validated on construction");
+        clone.set_state_recording(self.recent.is_some());
+        clone
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stream() -> TransientSliceStream {
+        TransientSliceStream::new(TransientSliceStreamConfig::default()).unwrap()
+    }
+
+    #[test]
+    fn transient_stream_is_chunk_size_agnostic() {
+        let mut audio: Vec<f64> = (0..12000).map(|i| 0.01 * (i as f64 * 0.05).sin()).collect();
+        audio[3000] = 1.0;
+        audio[9000] = 1.0;
+        let run = |chunk_size: usize| {
+            let mut stream = stream();
+            let mut flags = Vec::new();
+            for chunk in audio.chunks(chunk_size) {
+                let out = stream.process(chunk);
+                assert_eq!(out.len(), chunk.len());
+                flags.extend(out);
+            }
+            flags
+        };
+        let reference = run(256);
+        assert_eq!(reference.len(), audio.len());
+        assert_eq!(run(64), reference);
+        assert_eq!(run(1000), reference);
+    }
+
+    #[test]
+    fn transient_stream_delays_detections_by_latency_and_resets() {
+        let mut audio: Vec<f64> = (0..12000).map(|i| 0.01 * (i as f64 * 0.05).sin()).collect();
+        audio[3000] = 1.0;
+        let mut stream = stream();
+        let flags = stream.process(&audio);
+        let detected = flags
+            .iter()
+            .position(|&flag| flag == 1.0)
+            .expect("click not detected");
+        let input_index = detected - stream.latency_samples();
+        assert!(
+            input_index.abs_diff(3000) <= 32,
+            "detected at input {input_index}"
+        );
+
+        // Stop right after the click so the model holds state, then reset.
+        stream.process(&audio[..3100]);
+        stream.reset();
+        assert_eq!(stream.process(&audio), flags);
+    }
+
+    #[test]
+    fn restored_transient_stream_resumes_detection() {
+        let mut audio: Vec<f64> = (0..12000).map(|i| 0.01 * (i as f64 * 0.05).sin()).collect();
+        audio[9000] = 1.0;
+        let mut original = stream();
+        original.set_state_recording(true);
+        original.process(&audio[..5000]);
+        let state = original.save_state();
+        let expected = original.process(&audio[5000..]);
+        assert!(expected.contains(&1.0));
+
+        let mut resumed = stream();
+        resumed.restore_state(&state).unwrap();
+        assert_eq!(resumed.process(&audio[5000..]), expected);
+    }
+}